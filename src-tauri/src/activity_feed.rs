@@ -0,0 +1,172 @@
+//! Activity Feed Module
+//!
+//! Per-project timeline of what happened: sync runs, snapshots, scrapes,
+//! scheduled triggers, inbox arrivals and timer sessions. Each project's
+//! events are kept in `activity/<project_id>.json` (newest last, capped) and
+//! queried newest first, page by page.
+
+use crate::state_file;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+/// Events kept per project, the oldest are dropped
+const MAX_EVENTS: usize = 2000;
+
+const DEFAULT_PAGE_SIZE: usize = 50;
+
+/// Uploaded paths kept in a sync event, for the history search
+pub const MAX_SYNC_PATHS: usize = 500;
+
+pub const KINDS: [&str; 8] = ["sync", "snapshot", "scrape", "schedule", "inbox", "timer", "db_dump", "remote_change"];
+
+/// Serializes read-modify-write of the feed files
+static FEED_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityEvent {
+    pub id: String,
+    pub project_id: String,
+    /// One of KINDS
+    pub kind: String,
+    pub title: String,
+    pub detail: Option<String>,
+    /// "success", "error", "cancelled"... when the event is an outcome
+    pub status: Option<String>,
+    pub timestamp: String,
+    /// Kind-specific values (file counts, durations, snapshot id...)
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivityPage {
+    pub events: Vec<ActivityEvent>,
+    pub total: usize,
+    /// Offset of the next page, None on the last one
+    pub next_offset: Option<usize>,
+}
+
+fn feed_path(app_data_dir: &Path, project_id: &str) -> PathBuf {
+    let name: String = project_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    app_data_dir.join("activity").join(format!("{}.json", name))
+}
+
+/// Every event of a project, oldest first
+pub fn load(app_data_dir: &Path, project_id: &str) -> Vec<ActivityEvent> {
+    state_file::read_json(&feed_path(app_data_dir, project_id))
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+/// Append an event to its project's feed
+pub fn append(app_data_dir: &Path, event: &ActivityEvent) -> Result<(), String> {
+    if !KINDS.contains(&event.kind.as_str()) {
+        return Err(format!("Type d'activite inconnu: {}", event.kind));
+    }
+    let _guard = FEED_LOCK.lock().map_err(|_| "Failed to access activity feed".to_string())?;
+    let mut events = load(app_data_dir, &event.project_id);
+    events.push(event.clone());
+    if events.len() > MAX_EVENTS {
+        events.drain(..events.len() - MAX_EVENTS);
+    }
+    state_file::write_json(&feed_path(app_data_dir, &event.project_id), &events)
+}
+
+/// Build an event timestamped now
+pub fn new_event(project_id: &str, kind: &str, title: &str, detail: Option<String>, status: Option<&str>, data: serde_json::Value) -> ActivityEvent {
+    ActivityEvent {
+        id: uuid::Uuid::new_v4().to_string(),
+        project_id: project_id.to_string(),
+        kind: kind.to_string(),
+        title: title.to_string(),
+        detail,
+        status: status.map(String::from),
+        timestamp: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        data,
+    }
+}
+
+/// Record an event and tell the UI ("activity-recorded"); failures are only logged
+pub fn record(app: &AppHandle, event: ActivityEvent) {
+    let app_dir = match crate::data_location::app_data_dir(app) {
+        Some(dir) => dir,
+        None => return,
+    };
+    match append(&app_dir, &event) {
+        Ok(()) => {
+            let _ = app.emit_all(crate::events::ACTIVITY_RECORDED, &event);
+        }
+        Err(e) => println!("[Activity] Failed to record {} event: {}", event.kind, e),
+    }
+}
+
+/// Events of a project, newest first, optionally limited to some kinds
+pub fn query(app_data_dir: &Path, project_id: &str, kinds: Option<&[String]>, offset: usize, limit: Option<usize>) -> ActivityPage {
+    let events: Vec<ActivityEvent> = load(app_data_dir, project_id)
+        .into_iter()
+        .rev()
+        .filter(|e| kinds.map(|k| k.iter().any(|kind| *kind == e.kind)).unwrap_or(true))
+        .collect();
+    let total = events.len();
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+    let page: Vec<ActivityEvent> = events.into_iter().skip(offset).take(limit).collect();
+    let next = offset + page.len();
+    ActivityPage {
+        next_offset: if next < total { Some(next) } else { None },
+        events: page,
+        total,
+    }
+}
+
+/// Remove a project's feed
+pub fn clear(app_data_dir: &Path, project_id: &str) -> Result<(), String> {
+    let _guard = FEED_LOCK.lock().map_err(|_| "Failed to access activity feed".to_string())?;
+    let path = feed_path(app_data_dir, project_id);
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| format!("Failed to clear activity feed: {}", e))?;
+    }
+    let _ = std::fs::remove_file(state_file::backup_path(&path));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_feed_pages_newest_first() {
+        let dir = TempDir::new("activity-feed");
+        let project = "/Users/me/Sites/shop";
+        assert!(append(dir.path(), &new_event(project, "deploy", "?", None, None, serde_json::Value::Null))
+            .unwrap_err()
+            .starts_with("Type d'activite inconnu"));
+
+        for (index, kind) in ["sync", "timer", "sync", "db_dump", "sync"].iter().enumerate() {
+            let data = serde_json::json!({ "index": index });
+            let event = new_event(project, kind, &format!("event {}", index), None, Some("success"), data);
+            append(dir.path(), &event).unwrap();
+        }
+        assert!(feed_path(dir.path(), project).ends_with("activity/_Users_me_Sites_shop.json"));
+
+        let first = query(dir.path(), project, None, 0, Some(2));
+        assert_eq!(first.events.iter().map(|e| e.title.as_str()).collect::<Vec<_>>(), ["event 4", "event 3"]);
+        assert_eq!((first.total, first.next_offset), (5, Some(2)));
+        let last = query(dir.path(), project, None, 4, Some(2));
+        assert_eq!((last.events.len(), last.next_offset), (1, None));
+
+        let syncs = query(dir.path(), project, Some(&["sync".to_string()]), 0, None);
+        assert_eq!(syncs.events.iter().map(|e| e.data["index"].as_u64().unwrap()).collect::<Vec<_>>(), [4, 2, 0]);
+
+        clear(dir.path(), project).unwrap();
+        assert!(load(dir.path(), project).is_empty());
+        assert!(clear(dir.path(), "never-used").is_ok());
+    }
+}
@@ -0,0 +1,500 @@
+//! App Backup Module
+//!
+//! Bundles La Forge's own configuration (project configs, settings, sync
+//! schedules, quotas, version history metadata and the templates: project
+//! folder structure, connection profiles, command presets) into one backup
+//! file for moving to another machine or recovering from a disk failure.
+//! Credentials are only included when a passphrase is given, and are then
+//! encrypted with XChaCha20-Poly1305 under a key derived by Argon2id. So are
+//! the secrets kept in the OS keyring that the configuration refers to:
+//! connection profile passwords, secret project variables and database
+//! passwords, which are written back to the keyring of the new machine.
+
+use crate::scheduler::{self, SyncSchedule};
+use crate::state_file::write_atomic;
+use crate::{project_env, KEYRING_SERVICE};
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// 2: credentials encrypted with XChaCha20-Poly1305 and Argon2id
+const BACKUP_FORMAT_VERSION: u32 = 2;
+
+/// Settings stores, read by the frontend from the default app data directory
+const SETTINGS_FILES: [&str; 2] = ["app-settings.json", "projects-config.json"];
+
+const CREDENTIALS_FILE: &str = "credentials.dat";
+
+/// Files of the data directory (see data_location) included in a backup;
+/// connection profiles and command presets are the templates of new projects
+const DATA_FILES: [&str; 5] = [
+    "remote_commands.json",
+    "connection_profiles.json",
+    "project_env.json",
+    "transfer_quotas.json",
+    "pomodoro.json",
+];
+
+/// Fields of the configuration naming a keyring entry (connection profiles, database dumps)
+const KEYRING_KEY_FIELDS: [&str; 2] = ["keyringKey", "passwordKey"];
+
+/// Folders of the data directory whose JSON files are included (backed up file contents are not)
+const DATA_FOLDERS: [&str; 1] = ["version_history"];
+
+/// Cipher and key derivation of the credentials
+const CREDENTIALS_ALGORITHM: &str = "xchacha20poly1305-argon2id";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EncryptedBlob {
+    /// Empty for the credentials of format 1 backups, which can't be read anymore
+    #[serde(default)]
+    algorithm: String,
+    /// Base64, like the nonce and ciphertext
+    salt: String,
+    nonce: String,
+    /// Authenticated: a wrong passphrase or an altered file fails to decrypt
+    ciphertext: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AppBackup {
+    format_version: u32,
+    app_version: String,
+    created_at: String,
+    /// Settings stores by file name
+    settings: BTreeMap<String, String>,
+    /// Data directory files by relative path
+    data: BTreeMap<String, String>,
+    schedules: Vec<SyncSchedule>,
+    credentials: Option<EncryptedBlob>,
+    /// Keyring secrets by key, encrypted like the credentials
+    #[serde(default)]
+    keyring: Option<EncryptedBlob>,
+}
+
+/// Secrets store the backup reads from and restores to
+pub trait SecretStore {
+    fn get(&self, key: &str) -> Option<String>;
+    fn set(&self, key: &str, secret: &str) -> Result<(), String>;
+}
+
+/// The OS keyring, under the service of the app
+pub struct OsKeyring;
+
+impl SecretStore for OsKeyring {
+    fn get(&self, key: &str) -> Option<String> {
+        keyring::Entry::new(KEYRING_SERVICE, key).and_then(|entry| entry.get_password()).ok()
+    }
+
+    fn set(&self, key: &str, secret: &str) -> Result<(), String> {
+        keyring::Entry::new(KEYRING_SERVICE, key)
+            .and_then(|entry| entry.set_password(secret))
+            .map_err(|e| format!("Keyring error: {}", e))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupSummary {
+    pub path: String,
+    pub files: usize,
+    pub schedules: usize,
+    pub includes_credentials: bool,
+    /// Keyring secrets included (encrypted)
+    pub keyring_secrets: usize,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreSummary {
+    pub files_restored: usize,
+    pub schedules_restored: usize,
+    pub credentials_restored: bool,
+    pub keyring_secrets_restored: usize,
+    /// Copy of the replaced configuration, taken before restoring
+    pub previous_config_backup: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Write a backup of the configuration to `output_path`
+pub fn export_backup(
+    default_dir: &Path,
+    data_dir: &Path,
+    output_path: &str,
+    passphrase: Option<&str>,
+    app_version: &str,
+    secrets: &dyn SecretStore,
+) -> Result<BackupSummary, String> {
+    let mut settings = BTreeMap::new();
+    for name in SETTINGS_FILES {
+        if let Ok(content) = fs::read_to_string(default_dir.join(name)) {
+            settings.insert(name.to_string(), content);
+        }
+    }
+
+    let mut data = BTreeMap::new();
+    for name in DATA_FILES {
+        if let Ok(content) = fs::read_to_string(data_dir.join(name)) {
+            data.insert(name.to_string(), content);
+        }
+    }
+    for folder in DATA_FOLDERS {
+        for entry in WalkDir::new(data_dir.join(folder)).into_iter().filter_map(|e| e.ok()) {
+            let is_json = entry.path().extension().map(|ext| ext == "json").unwrap_or(false);
+            if !entry.file_type().is_file() || !is_json {
+                continue;
+            }
+            if let (Ok(relative), Ok(content)) = (entry.path().strip_prefix(data_dir), fs::read_to_string(entry.path())) {
+                data.insert(relative.to_string_lossy().replace('\\', "/"), content);
+            }
+        }
+    }
+
+    let schedules = scheduler::export_schedules();
+    let (credentials, keyring_secrets) = match passphrase {
+        Some(passphrase) if !passphrase.is_empty() => {
+            let credentials = match fs::read(default_dir.join(CREDENTIALS_FILE)) {
+                Ok(content) => Some(encrypt(&content, passphrase)?),
+                Err(_) => None,
+            };
+            // Keys whose entry is missing from the keyring are left out
+            let found: BTreeMap<String, String> = keyring_keys(&settings, &data, &schedules)
+                .into_iter()
+                .filter_map(|key| secrets.get(&key).map(|secret| (key, secret)))
+                .collect();
+            (credentials, Some(found))
+        }
+        _ => (None, None),
+    };
+    let keyring_count = keyring_secrets.as_ref().map(|found| found.len()).unwrap_or(0);
+    let keyring_secrets = match (keyring_secrets, passphrase) {
+        (Some(found), Some(passphrase)) if !found.is_empty() => {
+            let content = serde_json::to_vec(&found).map_err(|e| format!("Failed to serialize keyring secrets: {}", e))?;
+            Some(encrypt(&content, passphrase)?)
+        }
+        _ => None,
+    };
+
+    let backup = AppBackup {
+        format_version: BACKUP_FORMAT_VERSION,
+        app_version: app_version.to_string(),
+        created_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        settings,
+        data,
+        schedules,
+        credentials,
+        keyring: keyring_secrets,
+    };
+
+    let content = serde_json::to_string_pretty(&backup)
+        .map_err(|e| format!("Failed to serialize backup: {}", e))?;
+    let output = PathBuf::from(output_path);
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create backup directory: {}", e))?;
+    }
+    write_atomic(&output, content.as_bytes())?;
+
+    println!(
+        "[AppBackup] Exported {} files, {} schedules to {}",
+        backup.settings.len() + backup.data.len(),
+        backup.schedules.len(),
+        output_path
+    );
+
+    Ok(BackupSummary {
+        path: output_path.to_string(),
+        files: backup.settings.len() + backup.data.len(),
+        schedules: backup.schedules.len(),
+        includes_credentials: backup.credentials.is_some(),
+        keyring_secrets: keyring_count,
+        size: content.len() as u64,
+    })
+}
+
+/// Restore a backup written by `export_backup`, after saving the current configuration
+/// (without credentials) next to the data. The frontend must reload its stores afterwards.
+pub fn import_backup(
+    default_dir: &Path,
+    data_dir: &Path,
+    input_path: &str,
+    passphrase: Option<&str>,
+    app_version: &str,
+    secrets: &dyn SecretStore,
+) -> Result<RestoreSummary, String> {
+    let content = fs::read_to_string(input_path).map_err(|e| format!("Failed to read backup: {}", e))?;
+    let backup: AppBackup = serde_json::from_str(&content)
+        .map_err(|e| format!("Fichier de sauvegarde invalide: {}", e))?;
+    if backup.format_version > BACKUP_FORMAT_VERSION {
+        return Err(format!(
+            "Sauvegarde creee par une version plus recente de La Forge ({})",
+            backup.app_version
+        ));
+    }
+
+    // Decrypt first: a wrong passphrase must not leave a half restored configuration
+    let mut warnings = Vec::new();
+    let credentials = match (&backup.credentials, passphrase) {
+        (Some(blob), _) if blob.algorithm != CREDENTIALS_ALGORITHM => {
+            warnings.push("Identifiants non restaures: chiffrement d'une ancienne version, non pris en charge".to_string());
+            None
+        }
+        (Some(blob), Some(passphrase)) if !passphrase.is_empty() => Some(decrypt(blob, passphrase)?),
+        (Some(_), _) => {
+            warnings.push("Identifiants non restaures: phrase de passe manquante".to_string());
+            None
+        }
+        (None, _) => None,
+    };
+    let keyring_secrets: BTreeMap<String, String> = match (&backup.keyring, passphrase) {
+        (Some(blob), Some(passphrase)) if !passphrase.is_empty() => {
+            serde_json::from_slice(&decrypt(blob, passphrase)?)
+                .map_err(|_| "Identifiants de la sauvegarde illisibles".to_string())?
+        }
+        (Some(_), _) => {
+            warnings.push("Secrets du trousseau non restaures: phrase de passe manquante".to_string());
+            BTreeMap::new()
+        }
+        (None, _) => BTreeMap::new(),
+    };
+    for relative in backup.data.keys().chain(backup.settings.keys()) {
+        if !is_safe_relative(relative) {
+            return Err(format!("Chemin invalide dans la sauvegarde: {}", relative));
+        }
+    }
+
+    let safety_path = data_dir
+        .join("app_backups")
+        .join(format!("before-restore-{}.laforge-backup", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+    let previous_config_backup = match export_backup(default_dir, data_dir, &safety_path.to_string_lossy(), None, app_version, secrets) {
+        Ok(summary) => Some(summary.path),
+        Err(e) => {
+            warnings.push(format!("Copie de la configuration actuelle impossible: {}", e));
+            None
+        }
+    };
+
+    let mut files_restored = 0;
+    for (name, content) in &backup.settings {
+        write_atomic(&default_dir.join(name), content.as_bytes())?;
+        files_restored += 1;
+    }
+    for (relative, content) in &backup.data {
+        let path = data_dir.join(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        write_atomic(&path, content.as_bytes())?;
+        files_restored += 1;
+    }
+    let credentials_restored = match &credentials {
+        Some(content) => {
+            write_atomic(&default_dir.join(CREDENTIALS_FILE), content)?;
+            true
+        }
+        None => false,
+    };
+    let mut keyring_secrets_restored = 0;
+    for (key, secret) in &keyring_secrets {
+        match secrets.set(key, secret) {
+            Ok(()) => keyring_secrets_restored += 1,
+            Err(e) => warnings.push(format!("Secret {} non restaure dans le trousseau: {}", key, e)),
+        }
+    }
+
+    let schedules_restored = backup.schedules.len();
+    scheduler::load_schedules(backup.schedules);
+
+    println!(
+        "[AppBackup] Restored {} files, {} schedules from {} (created {})",
+        files_restored, schedules_restored, input_path, backup.created_at
+    );
+
+    Ok(RestoreSummary {
+        files_restored,
+        schedules_restored,
+        credentials_restored,
+        keyring_secrets_restored,
+        previous_config_backup,
+        warnings,
+    })
+}
+
+/// Keyring entries the backed up configuration refers to
+fn keyring_keys(settings: &BTreeMap<String, String>, data: &BTreeMap<String, String>, schedules: &[SyncSchedule]) -> BTreeSet<String> {
+    fn collect(value: &serde_json::Value, keys: &mut BTreeSet<String>) {
+        match value {
+            serde_json::Value::Object(fields) => {
+                for (name, field) in fields {
+                    match field {
+                        serde_json::Value::String(key) if KEYRING_KEY_FIELDS.contains(&name.as_str()) && !key.is_empty() => {
+                            keys.insert(key.clone());
+                        }
+                        _ => collect(field, keys),
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => items.iter().for_each(|item| collect(item, keys)),
+            _ => {}
+        }
+    }
+
+    let mut keys = BTreeSet::new();
+    for content in settings.values().chain(data.values()) {
+        if let Ok(value) = serde_json::from_str(content) {
+            collect(&value, &mut keys);
+        }
+    }
+    if let Ok(value) = serde_json::to_value(schedules) {
+        collect(&value, &mut keys);
+    }
+    let variables: HashMap<String, Vec<project_env::ProjectVariable>> = data
+        .get("project_env.json")
+        .and_then(|content| serde_json::from_str(content).ok())
+        .unwrap_or_default();
+    for (project_id, variables) in variables {
+        for variable in variables.into_iter().filter(|v| v.secret) {
+            keys.insert(project_env::keyring_key(&project_id, &variable.name));
+        }
+    }
+    keys
+}
+
+fn is_safe_relative(relative: &str) -> bool {
+    let path = Path::new(relative);
+    !relative.is_empty() && !path.is_absolute() && !relative.split(['/', '\\']).any(|part| part == "..")
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive key: {}", e))?;
+    Ok(key)
+}
+
+fn encrypt(data: &[u8], passphrase: &str) -> Result<EncryptedBlob, String> {
+    let salt = uuid::Uuid::new_v4();
+    let key = derive_key(passphrase, salt.as_bytes())?;
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = XChaCha20Poly1305::new(&key.into())
+        .encrypt(&nonce, data)
+        .map_err(|_| "Failed to encrypt credentials".to_string())?;
+    Ok(EncryptedBlob {
+        algorithm: CREDENTIALS_ALGORITHM.to_string(),
+        salt: BASE64.encode(salt.as_bytes()),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    })
+}
+
+fn decrypt(blob: &EncryptedBlob, passphrase: &str) -> Result<Vec<u8>, String> {
+    let invalid = |_| "Identifiants de la sauvegarde illisibles".to_string();
+    let salt = BASE64.decode(&blob.salt).map_err(invalid)?;
+    let nonce = BASE64.decode(&blob.nonce).map_err(invalid)?;
+    let ciphertext = BASE64.decode(&blob.ciphertext).map_err(invalid)?;
+    if nonce.len() != 24 {
+        return Err("Identifiants de la sauvegarde illisibles".to_string());
+    }
+    let key = derive_key(passphrase, &salt)?;
+    XChaCha20Poly1305::new(&key.into())
+        .decrypt(XNonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| "Phrase de passe incorrecte".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::TempDir;
+    use std::cell::RefCell;
+
+    /// Keyring kept in memory
+    #[derive(Default)]
+    struct MemoryKeyring(RefCell<HashMap<String, String>>);
+
+    impl SecretStore for MemoryKeyring {
+        fn get(&self, key: &str) -> Option<String> {
+            self.0.borrow().get(key).cloned()
+        }
+
+        fn set(&self, key: &str, secret: &str) -> Result<(), String> {
+            self.0.borrow_mut().insert(key.to_string(), secret.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_credentials_roundtrip() {
+        let blob = encrypt(b"{\"ftp-password\":\"s3cret\"}", "phrase").unwrap();
+        assert_eq!(decrypt(&blob, "phrase").unwrap(), b"{\"ftp-password\":\"s3cret\"}");
+        assert!(decrypt(&blob, "autre").is_err());
+
+        // An altered ciphertext is refused, not decrypted to garbage
+        let mut altered = BASE64.decode(&blob.ciphertext).unwrap();
+        altered[0] ^= 1;
+        let altered = EncryptedBlob { ciphertext: BASE64.encode(altered), ..blob };
+        assert!(decrypt(&altered, "phrase").is_err());
+    }
+
+    #[test]
+    fn test_backup_restores_templates_and_credentials() {
+        let source = TempDir::new("app-backup-source");
+        let target = TempDir::new("app-backup-target");
+        fs::write(source.path().join("connection_profiles.json"), "[{\"id\":\"ovh\"}]").unwrap();
+        fs::write(source.path().join(CREDENTIALS_FILE), "secret").unwrap();
+        let output = source.path().join("out.laforge-backup");
+
+        let summary = export_backup(source.path(), source.path(), &output.to_string_lossy(), Some("phrase"), "1.0.0", &MemoryKeyring::default()).unwrap();
+        assert!(summary.includes_credentials);
+        assert!(!fs::read_to_string(&output).unwrap().contains("secret"));
+
+        let restored = import_backup(target.path(), target.path(), &output.to_string_lossy(), Some("phrase"), "1.0.0", &MemoryKeyring::default()).unwrap();
+        assert!(restored.credentials_restored);
+        assert_eq!(fs::read_to_string(target.path().join("connection_profiles.json")).unwrap(), "[{\"id\":\"ovh\"}]");
+        assert_eq!(fs::read_to_string(target.path().join(CREDENTIALS_FILE)).unwrap(), "secret");
+    }
+
+    #[test]
+    fn test_backup_carries_the_keyring_secrets() {
+        let source = TempDir::new("app-backup-keyring-source");
+        let target = TempDir::new("app-backup-keyring-target");
+        source.write_files(&[
+            ("connection_profiles.json", "{\"profiles\":[{\"id\":\"ovh\",\"keyringKey\":\"profile-ovh\"}]}"),
+            ("project_env.json", "{\"shop\":[{\"name\":\"TOKEN\",\"value\":null,\"secret\":true},{\"name\":\"URL\",\"value\":\"x\",\"secret\":false}]}"),
+            ("projects-config.json", "{\"shop\":{\"dbDump\":{\"passwordKey\":\"db-shop\"},\"lost\":{\"passwordKey\":\"db-gone\"}}}"),
+        ]);
+        let keyring = MemoryKeyring::default();
+        for (key, secret) in [("profile-ovh", "ftp-s3cret"), ("env-shop-TOKEN", "tok3n"), ("db-shop", "db-s3cret"), ("unrelated", "x")] {
+            keyring.set(key, secret).unwrap();
+        }
+        let output = source.path().join("out.laforge-backup");
+
+        let plain = export_backup(source.path(), source.path(), &output.to_string_lossy(), None, "1.0.0", &keyring).unwrap();
+        assert_eq!(plain.keyring_secrets, 0);
+        let summary = export_backup(source.path(), source.path(), &output.to_string_lossy(), Some("phrase"), "1.0.0", &keyring).unwrap();
+        assert_eq!(summary.keyring_secrets, 3);
+        let written = fs::read_to_string(&output).unwrap();
+        assert!(!written.contains("s3cret") && !written.contains("tok3n"));
+
+        let restored_keyring = MemoryKeyring::default();
+        let missing = import_backup(target.path(), target.path(), &output.to_string_lossy(), None, "1.0.0", &restored_keyring).unwrap();
+        assert_eq!(missing.keyring_secrets_restored, 0);
+        assert!(missing.warnings.iter().any(|w| w.contains("trousseau")));
+
+        let restored = import_backup(target.path(), target.path(), &output.to_string_lossy(), Some("phrase"), "1.0.0", &restored_keyring).unwrap();
+        assert_eq!(restored.keyring_secrets_restored, 3);
+        let mut entries: Vec<(String, String)> = restored_keyring.0.into_inner().into_iter().collect();
+        entries.sort();
+        assert_eq!(
+            entries,
+            [("db-shop", "db-s3cret"), ("env-shop-TOKEN", "tok3n"), ("profile-ovh", "ftp-s3cret")]
+                .map(|(key, secret)| (key.to_string(), secret.to_string()))
+        );
+    }
+}
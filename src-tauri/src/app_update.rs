@@ -0,0 +1,221 @@
+//! App Update Module
+//!
+//! Update checks through Tauri's updater on a stable or beta channel,
+//! download with progress events, and a deferred restart: an installed
+//! update only takes effect when the user chooses to restart, so a sync
+//! in progress is never cut short.
+
+use crate::state_file;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+
+/// Update manifests by channel
+const CHANNEL_ENDPOINTS: [(&str, &str); 2] = [
+    ("stable", "https://github.com/flagorn-sudo/Laforge/releases/latest/download/latest.json"),
+    ("beta", "https://github.com/flagorn-sudo/Laforge/releases/download/beta/latest.json"),
+];
+
+/// Progress event emitted by Tauri's updater while downloading
+const TAURI_PROGRESS_EVENT: &str = "tauri://update-download-progress";
+
+/// An update was installed and waits for a restart
+static PENDING_RESTART: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+/// A download is running
+static DOWNLOADING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateSettings {
+    /// "stable" or "beta"
+    pub channel: String,
+    pub last_check: Option<String>,
+}
+
+impl Default for UpdateSettings {
+    fn default() -> Self {
+        Self {
+            channel: "stable".to_string(),
+            last_check: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateInfo {
+    pub available: bool,
+    pub channel: String,
+    pub current_version: String,
+    pub latest_version: Option<String>,
+    /// Release notes (markdown)
+    pub notes: Option<String>,
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateProgress {
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    pub percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateStatus {
+    pub channel: String,
+    pub downloading: bool,
+    /// Version installed and waiting for a restart
+    pub pending_restart: Option<String>,
+    pub last_check: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TauriProgressPayload {
+    chunk_length: u64,
+    content_length: Option<u64>,
+}
+
+fn settings_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("update_settings.json")
+}
+
+pub fn load_settings(app_data_dir: &Path) -> UpdateSettings {
+    state_file::read_json(&settings_path(app_data_dir)).ok().flatten().unwrap_or_default()
+}
+
+fn save_settings(app_data_dir: &Path, settings: &UpdateSettings) -> Result<(), String> {
+    state_file::write_json(&settings_path(app_data_dir), settings)
+}
+
+fn endpoint(channel: &str) -> Result<String, String> {
+    CHANNEL_ENDPOINTS
+        .iter()
+        .find(|(c, _)| *c == channel)
+        .map(|(_, url)| url.to_string())
+        .ok_or_else(|| format!("Canal de mise a jour inconnu: {}", channel))
+}
+
+pub fn set_channel(app_data_dir: &Path, channel: &str) -> Result<UpdateSettings, String> {
+    endpoint(channel)?;
+    let mut settings = load_settings(app_data_dir);
+    settings.channel = channel.to_string();
+    save_settings(app_data_dir, &settings)?;
+    println!("[Update] Channel set to {}", channel);
+    Ok(settings)
+}
+
+pub fn status(app_data_dir: &Path) -> UpdateStatus {
+    let settings = load_settings(app_data_dir);
+    UpdateStatus {
+        channel: settings.channel,
+        downloading: DOWNLOADING.load(Ordering::SeqCst),
+        pending_restart: PENDING_RESTART.lock().ok().and_then(|p| p.clone()),
+        last_check: settings.last_check,
+    }
+}
+
+/// Check the channel's manifest for a newer version
+pub async fn check(app: &AppHandle, app_data_dir: &Path) -> Result<UpdateInfo, String> {
+    let mut settings = load_settings(app_data_dir);
+    let response = app
+        .updater()
+        .endpoints(&[endpoint(&settings.channel)?])
+        .check()
+        .await;
+
+    settings.last_check = Some(chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string());
+    let _ = save_settings(app_data_dir, &settings);
+
+    let current_version = app.package_info().version.to_string();
+    match response {
+        Ok(update) if update.is_update_available() => {
+            println!("[Update] {} available on {} channel", update.latest_version(), settings.channel);
+            Ok(UpdateInfo {
+                available: true,
+                channel: settings.channel,
+                current_version,
+                latest_version: Some(update.latest_version().to_string()),
+                notes: update.body().cloned(),
+                date: update.date().map(|d| d.to_string()),
+            })
+        }
+        Ok(_) => Ok(UpdateInfo {
+            available: false,
+            channel: settings.channel,
+            current_version,
+            latest_version: None,
+            notes: None,
+            date: None,
+        }),
+        Err(e) => Err(format!("Verification des mises a jour impossible: {}", e)),
+    }
+}
+
+/// Download and install the channel's update, emitting "update-download-progress".
+/// The new version runs after `restart`.
+pub async fn download_and_install(app: &AppHandle, app_data_dir: &Path) -> Result<String, String> {
+    if DOWNLOADING.swap(true, Ordering::SeqCst) {
+        return Err("Un telechargement de mise a jour est deja en cours".to_string());
+    }
+    let result = install(app, app_data_dir).await;
+    DOWNLOADING.store(false, Ordering::SeqCst);
+    result
+}
+
+async fn install(app: &AppHandle, app_data_dir: &Path) -> Result<String, String> {
+    let settings = load_settings(app_data_dir);
+    let update = app
+        .updater()
+        .endpoints(&[endpoint(&settings.channel)?])
+        .check()
+        .await
+        .map_err(|e| format!("Verification des mises a jour impossible: {}", e))?;
+    if !update.is_update_available() {
+        return Err("Aucune mise a jour disponible".to_string());
+    }
+    let version = update.latest_version().to_string();
+
+    // Tauri reports chunks; forward a cumulated progress to the frontend
+    let downloaded = Arc::new(AtomicU64::new(0));
+    let progress_app = app.clone();
+    let handler = app.listen_global(TAURI_PROGRESS_EVENT, move |event| {
+        let payload: TauriProgressPayload = match event.payload().and_then(|p| serde_json::from_str(p).ok()) {
+            Some(payload) => payload,
+            None => return,
+        };
+        let done = downloaded.fetch_add(payload.chunk_length, Ordering::SeqCst) + payload.chunk_length;
+        let _ = progress_app.emit_all(
+            crate::events::UPDATE_DOWNLOAD_PROGRESS,
+            UpdateProgress {
+                downloaded: done,
+                total: payload.content_length,
+                percent: payload.content_length.filter(|t| *t > 0).map(|t| done as f64 * 100.0 / t as f64),
+            },
+        );
+    });
+
+    println!("[Update] Downloading {}", version);
+    let result = update.download_and_install().await;
+    app.unlisten(handler);
+    result.map_err(|e| format!("Installation de la mise a jour impossible: {}", e))?;
+
+    if let Ok(mut pending) = PENDING_RESTART.lock() {
+        *pending = Some(version.clone());
+    }
+    println!("[Update] {} installed, waiting for restart", version);
+    Ok(version)
+}
+
+/// Restart into the installed update
+pub fn restart(app: &AppHandle) -> Result<(), String> {
+    let version = match PENDING_RESTART.lock().ok().and_then(|p| p.clone()) {
+        Some(version) => version,
+        None => return Err("Aucune mise a jour en attente de redemarrage".to_string()),
+    };
+    println!("[Update] Restarting into {}", version);
+    app.restart();
+    Ok(())
+}
@@ -0,0 +1,88 @@
+//! Atomic Upload Module
+//!
+//! Uploading in place leaves a half-written file on the live site when a
+//! transfer dies. With `atomic_upload`, the file is written next to its
+//! target as `<name>.laforge-tmp`, then renamed over it once complete; a
+//! failed upload removes its temporary file.
+
+use crate::remote_fs::RemoteFs;
+use std::io::Read;
+
+/// Suffix of the temporary name an atomic upload writes to
+pub const TEMP_SUFFIX: &str = ".laforge-tmp";
+
+/// Name the upload writes to: the temporary one when atomic, else the target itself
+pub fn upload_target(remote_file: &str, atomic: bool) -> String {
+    if atomic {
+        format!("{}{}", remote_file, TEMP_SUFFIX)
+    } else {
+        remote_file.to_string()
+    }
+}
+
+/// Upload `reader` to `remote_file`
+pub fn put(remote: &mut dyn RemoteFs, remote_file: &str, atomic: bool, reader: &mut dyn Read) -> Result<(), String> {
+    let target = upload_target(remote_file, atomic);
+    let written = remote.write_file(&target, false, reader).map(|_| ());
+    if !atomic {
+        return written;
+    }
+    match written.and_then(|_| rename_over(remote, &target, remote_file)) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            let _ = remote.remove_file(&target);
+            Err(e)
+        }
+    }
+}
+
+/// Most servers replace the target on rename; SFTP v3 ones (OpenSSH) and
+/// some FTP servers refuse, so the target is removed first
+fn rename_over(remote: &mut dyn RemoteFs, from: &str, to: &str) -> Result<(), String> {
+    if remote.rename_file(from, to).is_ok() {
+        return Ok(());
+    }
+    let _ = remote.remove_file(to);
+    remote.rename_file(from, to)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{FolderRemote, TempDir};
+    use std::fs;
+
+    #[test]
+    fn test_atomic_upload_replaces_the_file_in_one_step() {
+        let server = TempDir::new("atomic-upload");
+        server.write_files(&[("index.html", "<h1>v1</h1>")]);
+        let mut remote = FolderRemote::new(server.path());
+
+        // The folder refuses to rename over an existing file, like OpenSSH
+        put(&mut remote, "/index.html", true, &mut "<h1>v2</h1>".as_bytes()).unwrap();
+        assert_eq!(fs::read_to_string(server.path().join("index.html")).unwrap(), "<h1>v2</h1>");
+        assert!(!server.path().join("index.html.laforge-tmp").exists());
+
+        put(&mut remote, "/new.js", true, &mut "1".as_bytes()).unwrap();
+        assert_eq!(fs::read_to_string(server.path().join("new.js")).unwrap(), "1");
+    }
+
+    #[test]
+    fn test_failed_upload_leaves_the_live_file() {
+        let server = TempDir::new("atomic-upload-failure");
+        server.write_files(&[("index.html", "<h1>v1</h1>"), ("app.js", "old")]);
+        let mut remote = FolderRemote::new(server.path());
+
+        remote.fail_after = Some(4);
+        let error = put(&mut remote, "/index.html", true, &mut "<h1>v2</h1>".as_bytes()).unwrap_err();
+        assert!(error.contains("connection reset"));
+        assert_eq!(fs::read_to_string(server.path().join("index.html")).unwrap(), "<h1>v1</h1>");
+        assert!(!server.path().join("index.html.laforge-tmp").exists());
+
+        // In place, the same failure leaves a truncated file on the site
+        remote.fail_after = Some(2);
+        assert!(put(&mut remote, "/app.js", false, &mut "new code".as_bytes()).is_err());
+        assert_eq!(fs::read_to_string(server.path().join("app.js")).unwrap(), "ne");
+        assert_eq!(upload_target("/app.js", false), "/app.js");
+    }
+}
@@ -0,0 +1,188 @@
+//! Automation Pause Module
+//!
+//! Date ranges ("holidays from Aug 1 to Aug 20") during which nothing
+//! deploys on its own: scheduled jobs are skipped, webhook deploys refused
+//! and offline syncs set to run automatically keep waiting. A period applies
+//! to one project or, without one, to all of them. Manual syncs are not
+//! affected; the frontend asks `is_automation_paused` before its own
+//! automatic syncs.
+
+use crate::state_file;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PausePeriod {
+    pub id: String,
+    /// None: every project
+    pub project_id: Option<String>,
+    /// UTC bounds of the period
+    pub start: String,
+    pub end: String,
+    pub reason: Option<String>,
+    pub created_at: String,
+}
+
+/// Event emitted on "automation-paused" when an automatic job is skipped
+#[derive(Debug, Clone, Serialize)]
+pub struct AutomationPausedEvent {
+    pub project_id: String,
+    pub trigger: String,
+    pub message: String,
+}
+
+fn pauses_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("automation_pauses.json")
+}
+
+fn format_utc(date: chrono::DateTime<chrono::Utc>) -> String {
+    date.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+fn parse_utc(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(value).ok().map(|d| d.with_timezone(&chrono::Utc))
+}
+
+fn load(app_data_dir: &Path) -> Vec<PausePeriod> {
+    state_file::read_json(&pauses_path(app_data_dir))
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+/// Periods not over yet, soonest first; past ones are dropped from the file
+pub fn list(app_data_dir: &Path) -> Vec<PausePeriod> {
+    let mut pauses = load(app_data_dir);
+    let count = pauses.len();
+    let now = chrono::Utc::now();
+    pauses.retain(|p| parse_utc(&p.end).map(|end| end > now).unwrap_or(false));
+    if pauses.len() != count {
+        let _ = state_file::write_json(&pauses_path(app_data_dir), &pauses);
+    }
+    pauses.sort_by(|a, b| a.start.cmp(&b.start));
+    pauses
+}
+
+/// Pause automation from `start` to `end` (RFC 3339), for a project or all of them
+pub fn add(
+    app_data_dir: &Path,
+    project_id: Option<String>,
+    start: &str,
+    end: &str,
+    reason: Option<String>,
+) -> Result<PausePeriod, String> {
+    let start = parse_utc(start).ok_or_else(|| format!("Date de debut invalide: {}", start))?;
+    let end = parse_utc(end).ok_or_else(|| format!("Date de fin invalide: {}", end))?;
+    if end <= start {
+        return Err("La fin de la pause doit suivre son debut".to_string());
+    }
+    if end <= chrono::Utc::now() {
+        return Err("La pause est deja terminee".to_string());
+    }
+    let pause = PausePeriod {
+        id: uuid::Uuid::new_v4().to_string(),
+        project_id: project_id.filter(|p| !p.is_empty()),
+        start: format_utc(start),
+        end: format_utc(end),
+        reason: reason.filter(|r| !r.trim().is_empty()),
+        created_at: format_utc(chrono::Utc::now()),
+    };
+    let mut pauses = list(app_data_dir);
+    pauses.push(pause.clone());
+    state_file::write_json(&pauses_path(app_data_dir), &pauses)?;
+    println!(
+        "[AutomationPause] Paused {} from {} to {}",
+        pause.project_id.as_deref().unwrap_or("all projects"),
+        pause.start,
+        pause.end
+    );
+    Ok(pause)
+}
+
+pub fn remove(app_data_dir: &Path, id: &str) -> Result<(), String> {
+    let mut pauses = load(app_data_dir);
+    let count = pauses.len();
+    pauses.retain(|p| p.id != id);
+    if pauses.len() == count {
+        return Err("Pause introuvable".to_string());
+    }
+    state_file::write_json(&pauses_path(app_data_dir), &pauses)
+}
+
+/// Period pausing the project right now, its own or a global one
+pub fn active_pause(app_data_dir: &Path, project_id: &str) -> Option<PausePeriod> {
+    active_in(&load(app_data_dir), project_id, chrono::Utc::now())
+}
+
+fn active_in(pauses: &[PausePeriod], project_id: &str, now: chrono::DateTime<chrono::Utc>) -> Option<PausePeriod> {
+    pauses
+        .iter()
+        .filter(|p| p.project_id.as_deref().map(|id| id == project_id).unwrap_or(true))
+        .find(|p| match (parse_utc(&p.start), parse_utc(&p.end)) {
+            (Some(start), Some(end)) => start <= now && now < end,
+            _ => false,
+        })
+        .cloned()
+}
+
+/// Message shown when an automatic job is skipped
+pub fn describe(pause: &PausePeriod) -> String {
+    match &pause.reason {
+        Some(reason) => format!("Automatisations en pause jusqu'au {} ({})", pause.end, reason),
+        None => format!("Automatisations en pause jusqu'au {}", pause.end),
+    }
+}
+
+/// Whether an automatic job may start; tells the UI with "automation-paused" if not
+pub fn allow_automation(app_handle: &tauri::AppHandle, project_id: &str, trigger: &str) -> bool {
+    let app_dir = match crate::data_location::app_data_dir(app_handle) {
+        Some(dir) => dir,
+        None => return true,
+    };
+    match active_pause(&app_dir, project_id) {
+        None => true,
+        Some(pause) => {
+            let message = describe(&pause);
+            println!("[AutomationPause] {} job of {} skipped: {}", trigger, project_id, message);
+            let _ = app_handle.emit_all(
+                crate::events::AUTOMATION_PAUSED,
+                AutomationPausedEvent {
+                    project_id: project_id.to_string(),
+                    trigger: trigger.to_string(),
+                    message,
+                },
+            );
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pause(project_id: Option<&str>, start: &str, end: &str) -> PausePeriod {
+        PausePeriod {
+            id: start.to_string(),
+            project_id: project_id.map(str::to_string),
+            start: start.to_string(),
+            end: end.to_string(),
+            reason: None,
+            created_at: start.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_project_and_global_periods() {
+        let pauses = vec![
+            pause(Some("shop"), "2026-08-01T00:00:00Z", "2026-08-21T00:00:00Z"),
+            pause(None, "2026-12-24T00:00:00Z", "2027-01-02T00:00:00Z"),
+        ];
+        let at = |date: &str| parse_utc(date).unwrap();
+        assert!(active_in(&pauses, "shop", at("2026-08-10T12:00:00Z")).is_some());
+        assert!(active_in(&pauses, "blog", at("2026-08-10T12:00:00Z")).is_none());
+        assert!(active_in(&pauses, "shop", at("2026-08-21T00:00:00Z")).is_none());
+        assert!(active_in(&pauses, "blog", at("2026-12-31T23:00:00Z")).is_some());
+    }
+}
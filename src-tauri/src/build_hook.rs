@@ -0,0 +1,147 @@
+//! Build Hook Module
+//!
+//! Local command run before a sync (`npm run build`, `hugo`...). It goes
+//! through the user's login shell, so the PATH of a terminal applies even
+//! when the app was started from the Finder. Every output line is sent as a
+//! `build-output` event while it runs; a non-zero exit, a timeout or a
+//! cancelled sync stops the sync before anything is uploaded.
+
+use crate::events;
+use crate::is_cancelled;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use tauri::Manager;
+
+/// Longest a build may run when the sync doesn't say
+pub const DEFAULT_TIMEOUT_SECS: u64 = 600;
+
+/// Last output lines quoted in the error of a failed build
+const TAIL_LINES: usize = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildOutputEvent {
+    pub schema_version: u32,
+    pub project_id: String,
+    /// "stdout" or "stderr"
+    pub stream: &'static str,
+    pub line: String,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildResult {
+    pub command: String,
+    pub duration_ms: u64,
+    pub lines: usize,
+}
+
+fn shell_command(command: &str) -> Command {
+    if cfg!(target_os = "windows") {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let shell = std::env::var("SHELL").ok().filter(|s| !s.is_empty()).unwrap_or_else(|| "/bin/sh".to_string());
+        let mut login = Command::new(shell);
+        login.args(["-l", "-c", command]);
+        login
+    }
+}
+
+fn read_lines(reader: impl Read + Send + 'static, stream: &'static str, sender: mpsc::Sender<(&'static str, String)>) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(reader).lines().map_while(Result::ok) {
+            if sender.send((stream, line)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Run `command` in `cwd` with the project variables `env`, streaming its
+/// output, and fail unless it exits with 0
+pub fn run(
+    command: &str,
+    cwd: &Path,
+    env: &[(String, String)],
+    timeout: Duration,
+    project_id: &str,
+    app_handle: &tauri::AppHandle,
+) -> Result<BuildResult, String> {
+    if !cwd.is_dir() {
+        return Err(format!("Dossier de build introuvable: {}", cwd.display()));
+    }
+    println!("[Build] Running `{}` in {}", command, cwd.display());
+    let started = Instant::now();
+    let mut child = shell_command(command)
+        .current_dir(cwd)
+        .envs(env.iter().map(|(name, value)| (name, value)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start build command: {}", e))?;
+
+    let (sender, receiver) = mpsc::channel();
+    if let Some(stdout) = child.stdout.take() {
+        read_lines(stdout, "stdout", sender.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        read_lines(stderr, "stderr", sender);
+    }
+
+    let mut tail: VecDeque<String> = VecDeque::with_capacity(TAIL_LINES);
+    let mut lines = 0;
+    loop {
+        if is_cancelled(project_id) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err("Synchronisation annulée pendant le build".to_string());
+        }
+        if started.elapsed() > timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!("Build interrompu apres {} s: {}", timeout.as_secs(), command));
+        }
+        match receiver.recv_timeout(Duration::from_millis(200)) {
+            Ok((stream, line)) => {
+                lines += 1;
+                if tail.len() == TAIL_LINES {
+                    tail.pop_front();
+                }
+                tail.push_back(line.clone());
+                let _ = app_handle.emit_all(
+                    events::BUILD_OUTPUT,
+                    BuildOutputEvent {
+                        schema_version: events::SCHEMA_VERSION,
+                        project_id: project_id.to_string(),
+                        stream,
+                        line,
+                        timestamp: std::time::SystemTime::now()
+                            .duration_since(std::time::UNIX_EPOCH)
+                            .unwrap_or_default()
+                            .as_millis() as u64,
+                    },
+                );
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            // Both streams closed: the command is done or about to be
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    let status = child.wait().map_err(|e| format!("Failed to wait for build command: {}", e))?;
+    let duration_ms = started.elapsed().as_millis() as u64;
+    if !status.success() {
+        let code = status.code().map(|c| c.to_string()).unwrap_or_else(|| "signal".to_string());
+        let output = Vec::from(tail).join("\n");
+        return Err(format!("Le build a echoue (code {}): {}", code, output.trim()));
+    }
+    println!("[Build] `{}` done in {} ms", command, duration_ms);
+    Ok(BuildResult { command: command.to_string(), duration_ms, lines })
+}
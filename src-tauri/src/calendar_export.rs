@@ -0,0 +1,241 @@
+//! Calendar Export Module
+//!
+//! Exports tracked work sessions and upcoming scheduled syncs as an
+//! iCalendar (.ics) file that Calendar, Fantastical or any CalDAV client
+//! can import or subscribe to.
+
+use crate::scheduler;
+use chrono::{DateTime, Duration, Utc};
+use cron::Schedule;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::str::FromStr;
+
+/// Length of the calendar event created for a scheduled sync
+const SCHEDULED_SYNC_MINUTES: i64 = 15;
+
+/// A tracked work session, as stored by the frontend time tracking
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarSession {
+    pub id: String,
+    pub project_id: String,
+    pub start_time: String,
+    pub end_time: Option<String>,
+    /// Tracked duration in seconds (excludes pauses)
+    pub duration: u64,
+    pub notes: Option<String>,
+}
+
+/// What to include in the export
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarExportOptions {
+    /// Project id -> display name
+    #[serde(default)]
+    pub project_names: HashMap<String, String>,
+    /// Only export these projects (all when empty)
+    #[serde(default)]
+    pub project_ids: Vec<String>,
+    #[serde(default)]
+    pub include_scheduled_syncs: bool,
+    /// How far ahead scheduled syncs are listed
+    #[serde(default = "default_days_ahead")]
+    pub days_ahead: i64,
+}
+
+fn default_days_ahead() -> i64 { 30 }
+
+/// Build the .ics content
+pub fn build_calendar(sessions: &[CalendarSession], options: &CalendarExportOptions) -> String {
+    let now = Utc::now();
+    let stamp = ics_datetime(&now);
+    let included = |project_id: &str| options.project_ids.is_empty() || options.project_ids.iter().any(|p| p == project_id);
+    let project_name = |project_id: &str| {
+        options
+            .project_names
+            .get(project_id)
+            .cloned()
+            .unwrap_or_else(|| project_id.rsplit('/').next().unwrap_or(project_id).to_string())
+    };
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//La Forge//Time Tracking//FR".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "X-WR-CALNAME:La Forge".to_string(),
+    ];
+
+    for session in sessions.iter().filter(|s| included(&s.project_id)) {
+        let start = match parse_datetime(&session.start_time) {
+            Some(start) => start,
+            None => continue,
+        };
+        let end = session
+            .end_time
+            .as_deref()
+            .and_then(parse_datetime)
+            .unwrap_or_else(|| start + Duration::seconds(session.duration as i64));
+        let name = project_name(&session.project_id);
+
+        let mut description = format!("Temps suivi: {}", format_duration(session.duration));
+        if let Some(notes) = session.notes.as_deref().filter(|n| !n.trim().is_empty()) {
+            description.push_str(&format!("\n{}", notes));
+        }
+
+        lines.extend(vevent(
+            &format!("session-{}@laforge", session.id),
+            &stamp,
+            &start,
+            &end,
+            &format!("Travail - {}", name),
+            &description,
+            "Temps suivi",
+        ));
+    }
+
+    if options.include_scheduled_syncs {
+        let horizon = now + Duration::days(options.days_ahead.max(1));
+        for schedule in scheduler::get_all_schedules() {
+            if !schedule.enabled || !included(&schedule.project_id) {
+                continue;
+            }
+            let parsed = match schedule.cron_expression.as_deref().and_then(|c| Schedule::from_str(c).ok()) {
+                Some(parsed) => parsed,
+                None => continue,
+            };
+            let name = project_name(&schedule.project_id);
+
+            for next in parsed.upcoming(Utc).take_while(|t| *t <= horizon) {
+                lines.extend(vevent(
+                    &format!("sync-{}-{}@laforge", sanitize_uid(&schedule.project_id), next.timestamp()),
+                    &stamp,
+                    &next,
+                    &(next + Duration::minutes(SCHEDULED_SYNC_MINUTES)),
+                    &format!("Synchronisation - {}", name),
+                    "Synchronisation FTP/SFTP planifiee",
+                    "Synchronisation",
+                ));
+            }
+        }
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|l| fold_line(l)).collect::<Vec<_>>().join("\r\n") + "\r\n"
+}
+
+/// Write the calendar to `output_path` and return the number of events
+pub fn export_calendar(sessions: &[CalendarSession], options: &CalendarExportOptions, output_path: &str) -> Result<usize, String> {
+    let content = build_calendar(sessions, options);
+    fs::write(output_path, &content).map_err(|e| format!("Failed to write calendar file: {}", e))?;
+    Ok(content.matches("BEGIN:VEVENT").count())
+}
+
+fn vevent(
+    uid: &str,
+    stamp: &str,
+    start: &DateTime<Utc>,
+    end: &DateTime<Utc>,
+    summary: &str,
+    description: &str,
+    category: &str,
+) -> Vec<String> {
+    vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", uid),
+        format!("DTSTAMP:{}", stamp),
+        format!("DTSTART:{}", ics_datetime(start)),
+        format!("DTEND:{}", ics_datetime(end)),
+        format!("SUMMARY:{}", escape_text(summary)),
+        format!("DESCRIPTION:{}", escape_text(description)),
+        format!("CATEGORIES:{}", escape_text(category)),
+        "END:VEVENT".to_string(),
+    ]
+}
+
+fn parse_datetime(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value).ok().map(|dt| dt.with_timezone(&Utc))
+}
+
+fn ics_datetime(dt: &DateTime<Utc>) -> String {
+    dt.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn format_duration(seconds: u64) -> String {
+    format!("{}h{:02}", seconds / 3600, (seconds % 3600) / 60)
+}
+
+/// Escape TEXT values (RFC 5545 section 3.3.11)
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+fn sanitize_uid(value: &str) -> String {
+    value.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '-' }).collect()
+}
+
+/// Fold lines longer than 75 octets (RFC 5545 section 3.1)
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, project_id: &str, notes: Option<&str>) -> CalendarSession {
+        CalendarSession {
+            id: id.to_string(),
+            project_id: project_id.to_string(),
+            start_time: "2026-03-02T09:00:00+01:00".to_string(),
+            end_time: None,
+            duration: 5400,
+            notes: notes.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_sessions_become_escaped_folded_events() {
+        let notes = "Refonte du panier, paiement; tests\n".to_string() + &"x".repeat(80);
+        let options = CalendarExportOptions {
+            project_names: HashMap::from([("p1".to_string(), "Boutique".to_string())]),
+            project_ids: vec!["p1".to_string(), "/sites/blog".to_string()],
+            include_scheduled_syncs: false,
+            days_ahead: 30,
+        };
+        let sessions = [
+            session("a", "p1", Some(&notes)),
+            session("b", "/sites/blog", None),
+            session("c", "other", None),
+        ];
+        let calendar = build_calendar(&sessions, &options);
+
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\n") && calendar.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(calendar.matches("BEGIN:VEVENT").count(), 2);
+        assert!(calendar.contains("UID:session-a@laforge\r\nDTSTAMP:"));
+        // Times in UTC, the end from the tracked duration
+        assert!(calendar.contains("DTSTART:20260302T080000Z\r\nDTEND:20260302T093000Z"));
+        assert!(calendar.contains("SUMMARY:Travail - Boutique"));
+        assert!(calendar.contains("SUMMARY:Travail - blog"));
+        assert!(calendar.contains("DESCRIPTION:Temps suivi: 1h30\\nRefonte du panier\\, paiement\\; tests\\nxx"));
+        assert!(calendar.split("\r\n").all(|line| line.len() <= 75));
+        assert!(calendar.contains("\r\n x"));
+    }
+}
@@ -0,0 +1,418 @@
+//! Color Palette Module
+//!
+//! Normalizes CSS color notations (`#fff`, `#ffffff`, `rgb()`, `rgba()`),
+//! merges near-identical shades by perceptual distance (CIELAB),
+//! derives a primary/secondary/neutral palette and rates the WCAG contrast
+//! of every pair of palette colors.
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Shades closer than this (CIE76 delta E) are merged
+const MERGE_DISTANCE: f64 = 6.0;
+
+/// Below this chroma a color is considered a neutral (white, grey, black...)
+const NEUTRAL_CHROMA: f64 = 12.0;
+
+/// Maximum number of secondary colors in the palette
+const MAX_SECONDARY: usize = 3;
+
+/// Palette colors kept in the contrast matrix (the most used ones)
+const MAX_CONTRAST_COLORS: usize = 10;
+
+/// Group of shades merged into one representative color
+#[derive(Debug, Clone)]
+pub struct ColorCluster {
+    pub hex: String,
+    pub occurrences: usize,
+    /// Other normalized shades merged into this one
+    pub variants: Vec<String>,
+    lab: (f64, f64, f64),
+}
+
+impl ColorCluster {
+    pub fn is_neutral(&self) -> bool {
+        chroma(self.lab) < NEUTRAL_CHROMA
+    }
+}
+
+/// Clean palette derived from the clusters
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct ColorPalette {
+    pub primary: Option<String>,
+    pub secondary: Vec<String>,
+    pub accents: Vec<String>,
+    /// Neutrals from lightest to darkest
+    pub neutrals: Vec<String>,
+}
+
+/// Parse a CSS color into RGB; fully transparent colors are ignored
+pub fn parse_css_color(value: &str) -> Option<(u8, u8, u8)> {
+    let value = value.trim().to_lowercase();
+
+    if let Some(hex) = value.strip_prefix('#') {
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let expand = |s: &str| u8::from_str_radix(&s.repeat(2), 16).ok();
+        let full = |s: &str| u8::from_str_radix(s, 16).ok();
+        return match hex.len() {
+            3 => Some((expand(&hex[0..1])?, expand(&hex[1..2])?, expand(&hex[2..3])?)),
+            4 if expand(&hex[3..4])? > 0 => Some((expand(&hex[0..1])?, expand(&hex[1..2])?, expand(&hex[2..3])?)),
+            6 => Some((full(&hex[0..2])?, full(&hex[2..4])?, full(&hex[4..6])?)),
+            8 if full(&hex[6..8])? > 0 => Some((full(&hex[0..2])?, full(&hex[2..4])?, full(&hex[4..6])?)),
+            _ => None,
+        };
+    }
+
+    let inner = value
+        .strip_prefix("rgba(")
+        .or_else(|| value.strip_prefix("rgb("))?
+        .trim_end_matches(')');
+    // Accept both `rgb(1, 2, 3, .5)` and `rgb(1 2 3 / 50%)`
+    let parts: Vec<&str> = inner
+        .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|p| !p.is_empty())
+        .collect();
+    if parts.len() < 3 {
+        return None;
+    }
+
+    let channel = |s: &str| -> Option<u8> {
+        match s.strip_suffix('%') {
+            Some(pct) => pct.parse::<f64>().ok().map(|p| (p.clamp(0.0, 100.0) * 2.55).round() as u8),
+            None => s.parse::<f64>().ok().map(|v| v.clamp(0.0, 255.0).round() as u8),
+        }
+    };
+    if let Some(alpha) = parts.get(3) {
+        let alpha = match alpha.strip_suffix('%') {
+            Some(pct) => pct.parse::<f64>().ok()? / 100.0,
+            None => alpha.parse::<f64>().ok()?,
+        };
+        if alpha <= 0.0 {
+            return None;
+        }
+    }
+
+    Some((channel(parts[0])?, channel(parts[1])?, channel(parts[2])?))
+}
+
+/// Canonical `#rrggbb` form
+pub fn to_hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Count the raw hex and rgb()/rgba() colors of a stylesheet
+pub fn collect_css_colors(css: &str, counts: &mut HashMap<String, usize>) {
+    // Extract hex colors
+    for word in css.split([' ', ';', ':', '{', '}', '\n', '\r', '(', ')', ',']) {
+        let trimmed = word.trim();
+        if trimmed.starts_with('#') && matches!(trimmed.len(), 4 | 5 | 7 | 9) {
+            if trimmed.chars().skip(1).all(|c| c.is_ascii_hexdigit()) {
+                let normalized = trimmed.to_lowercase();
+                *counts.entry(normalized).or_insert(0) += 1;
+            }
+        }
+    }
+
+    // Extract rgb/rgba colors
+    let css_lower = css.to_lowercase();
+    for pattern in ["rgb(", "rgba("] {
+        let mut search_from = 0;
+        while let Some(start) = css_lower[search_from..].find(pattern) {
+            let actual_start = search_from + start;
+            if let Some(end) = css_lower[actual_start..].find(')') {
+                let color = &css[actual_start..actual_start + end + 1];
+                *counts.entry(color.to_string()).or_insert(0) += 1;
+            }
+            search_from = actual_start + 1;
+        }
+    }
+}
+
+/// Normalize raw color strings and merge perceptually close shades.
+///
+/// Clusters are returned from most to least used; each is represented
+/// by its most frequent shade.
+pub fn cluster_colors(raw_counts: &HashMap<String, usize>) -> Vec<ColorCluster> {
+    let mut normalized: HashMap<String, usize> = HashMap::new();
+    for (raw, count) in raw_counts {
+        if let Some(rgb) = parse_css_color(raw) {
+            *normalized.entry(to_hex(rgb)).or_insert(0) += count;
+        }
+    }
+
+    let mut sorted: Vec<(String, usize)> = normalized.into_iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let mut clusters: Vec<ColorCluster> = Vec::new();
+    for (hex, count) in sorted {
+        let rgb = match parse_css_color(&hex) {
+            Some(rgb) => rgb,
+            None => continue,
+        };
+        let lab = rgb_to_lab(rgb);
+
+        match clusters.iter_mut().find(|c| delta_e(c.lab, lab) < MERGE_DISTANCE) {
+            Some(cluster) => {
+                cluster.occurrences += count;
+                cluster.variants.push(hex);
+            }
+            None => clusters.push(ColorCluster {
+                hex,
+                occurrences: count,
+                variants: Vec::new(),
+                lab,
+            }),
+        }
+    }
+
+    clusters.sort_by(|a, b| b.occurrences.cmp(&a.occurrences));
+    clusters
+}
+
+/// Derive primary/secondary/accent/neutral roles from clusters
+pub fn build_palette(clusters: &[ColorCluster]) -> ColorPalette {
+    let (neutral, chromatic): (Vec<&ColorCluster>, Vec<&ColorCluster>) =
+        clusters.iter().partition(|c| c.is_neutral());
+
+    let mut neutrals = neutral;
+    neutrals.sort_by(|a, b| b.lab.0.total_cmp(&a.lab.0));
+
+    ColorPalette {
+        primary: chromatic.first().map(|c| c.hex.clone()),
+        secondary: chromatic.iter().skip(1).take(MAX_SECONDARY).map(|c| c.hex.clone()).collect(),
+        accents: chromatic.iter().skip(1 + MAX_SECONDARY).map(|c| c.hex.clone()).collect(),
+        neutrals: neutrals.iter().map(|c| c.hex.clone()).collect(),
+    }
+}
+
+/// Palette role of a cluster, used as the color's `usage`
+pub fn palette_role(palette: &ColorPalette, hex: &str) -> &'static str {
+    if palette.primary.as_deref() == Some(hex) {
+        "primary"
+    } else if palette.secondary.iter().any(|c| c == hex) {
+        "secondary"
+    } else if palette.neutrals.iter().any(|c| c == hex) {
+        "neutral"
+    } else {
+        "accent"
+    }
+}
+
+/// WCAG 2.1 contrast of a text color on a background color
+#[derive(Debug, Clone, Serialize)]
+pub struct ContrastPair {
+    pub foreground: String,
+    pub background: String,
+    /// From 1 to 21, rounded to 2 decimals
+    pub ratio: f64,
+    /// Normal text, 4.5:1
+    pub aa: bool,
+    /// Large text (24px, or 18.66px bold) and UI components, 3:1
+    pub aa_large: bool,
+    /// Normal text, 7:1
+    pub aaa: bool,
+}
+
+impl ContrastPair {
+    /// Short rating shown in the matrices
+    pub fn grade(&self) -> &'static str {
+        if self.aaa {
+            "AAA"
+        } else if self.aa {
+            "AA"
+        } else if self.aa_large {
+            "AA large"
+        } else {
+            "Echec"
+        }
+    }
+}
+
+fn relative_luminance((r, g, b): (u8, u8, u8)) -> f64 {
+    let channel = |c: u8| {
+        let c = c as f64 / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * channel(r) + 0.7152 * channel(g) + 0.0722 * channel(b)
+}
+
+pub fn contrast_ratio(a: (u8, u8, u8), b: (u8, u8, u8)) -> f64 {
+    let (la, lb) = (relative_luminance(a), relative_luminance(b));
+    let (light, dark) = if la > lb { (la, lb) } else { (lb, la) };
+    (light + 0.05) / (dark + 0.05)
+}
+
+/// Colors of the palette rated in the contrast matrix: primary, secondary,
+/// accents, then neutrals
+pub fn contrast_colors(palette: &ColorPalette) -> Vec<String> {
+    let mut colors: Vec<String> = Vec::new();
+    let all = palette
+        .primary
+        .iter()
+        .chain(&palette.secondary)
+        .chain(&palette.accents)
+        .chain(&palette.neutrals);
+    for color in all {
+        if !colors.contains(color) {
+            colors.push(color.clone());
+        }
+    }
+    if colors.len() <= MAX_CONTRAST_COLORS {
+        return colors;
+    }
+    // Too many colors: keep the lightest and darkest neutrals, the usual
+    // backgrounds, and the first chromatic colors
+    let ends: Vec<&String> = palette.neutrals.first().into_iter().chain(palette.neutrals.last()).collect();
+    let mut trimmed: Vec<String> = colors
+        .iter()
+        .filter(|c| !palette.neutrals.contains(c))
+        .take(MAX_CONTRAST_COLORS - 2)
+        .cloned()
+        .collect();
+    for end in ends {
+        if !trimmed.contains(end) {
+            trimmed.push(end.clone());
+        }
+    }
+    trimmed
+}
+
+/// Contrast of every foreground/background pair of distinct colors
+pub fn contrast_matrix(colors: &[String]) -> Vec<ContrastPair> {
+    let mut pairs = Vec::new();
+    for foreground in colors {
+        for background in colors {
+            if foreground == background {
+                continue;
+            }
+            let (fg, bg) = match (parse_css_color(foreground), parse_css_color(background)) {
+                (Some(fg), Some(bg)) => (fg, bg),
+                _ => continue,
+            };
+            let ratio = contrast_ratio(fg, bg);
+            pairs.push(ContrastPair {
+                foreground: foreground.clone(),
+                background: background.clone(),
+                ratio: (ratio * 100.0).round() / 100.0,
+                aa: ratio >= 4.5,
+                aa_large: ratio >= 3.0,
+                aaa: ratio >= 7.0,
+            });
+        }
+    }
+    pairs
+}
+
+/// Markdown table of a contrast matrix, text colors as rows and backgrounds as columns
+pub fn contrast_table(colors: &[String], pairs: &[ContrastPair]) -> String {
+    let mut table = String::from("| Texte \\ Fond |");
+    for background in colors {
+        table.push_str(&format!(" `{}` |", background));
+    }
+    table.push_str("\n|---|");
+    table.push_str(&"---|".repeat(colors.len()));
+    table.push('\n');
+    for foreground in colors {
+        table.push_str(&format!("| `{}` |", foreground));
+        for background in colors {
+            match pairs.iter().find(|p| &p.foreground == foreground && &p.background == background) {
+                Some(pair) => table.push_str(&format!(" {:.2} {} |", pair.ratio, pair.grade())),
+                None => table.push_str(" - |"),
+            }
+        }
+        table.push('\n');
+    }
+    table
+}
+
+fn chroma(lab: (f64, f64, f64)) -> f64 {
+    (lab.1 * lab.1 + lab.2 * lab.2).sqrt()
+}
+
+fn delta_e(a: (f64, f64, f64), b: (f64, f64, f64)) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2)).sqrt()
+}
+
+/// sRGB (D65) to CIELAB
+fn rgb_to_lab((r, g, b): (u8, u8, u8)) -> (f64, f64, f64) {
+    let linear = |c: u8| {
+        let c = c as f64 / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let (r, g, b) = (linear(r), linear(g), linear(b));
+
+    let x = (r * 0.4124 + g * 0.3576 + b * 0.1805) / 0.95047;
+    let y = r * 0.2126 + g * 0.7152 + b * 0.0722;
+    let z = (r * 0.0193 + g * 0.1192 + b * 0.9505) / 1.08883;
+
+    let f = |t: f64| {
+        if t > 0.008856 {
+            t.cbrt()
+        } else {
+            7.787 * t + 16.0 / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+
+    (116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_equivalent_notations() {
+        let white = Some((255, 255, 255));
+        assert_eq!(parse_css_color("#fff"), white);
+        assert_eq!(parse_css_color("#FFFFFF"), white);
+        assert_eq!(parse_css_color("rgb(255,255,255)"), white);
+        assert_eq!(parse_css_color("rgba(255, 255, 255, 1)"), white);
+        assert_eq!(parse_css_color("rgb(100% 100% 100% / 50%)"), white);
+        assert_eq!(parse_css_color("rgba(0, 0, 0, 0)"), None);
+    }
+
+    #[test]
+    fn test_cluster_merges_close_shades() {
+        let mut counts = HashMap::new();
+        counts.insert("#fff".to_string(), 5);
+        counts.insert("rgb(255,255,255)".to_string(), 3);
+        counts.insert("#fefefe".to_string(), 1);
+        counts.insert("#e63946".to_string(), 4);
+
+        let clusters = cluster_colors(&counts);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].hex, "#ffffff");
+        assert_eq!(clusters[0].occurrences, 9);
+
+        let palette = build_palette(&clusters);
+        assert_eq!(palette.primary.as_deref(), Some("#e63946"));
+        assert_eq!(palette.neutrals, vec!["#ffffff".to_string()]);
+    }
+
+    #[test]
+    fn test_contrast_matrix_grades() {
+        assert!((contrast_ratio((0, 0, 0), (255, 255, 255)) - 21.0).abs() < 0.01);
+
+        let colors = vec!["#000000".to_string(), "#ffffff".to_string(), "#777777".to_string()];
+        let pairs = contrast_matrix(&colors);
+        assert_eq!(pairs.len(), 6);
+        let grade = |fg: &str, bg: &str| {
+            pairs.iter().find(|p| p.foreground == fg && p.background == bg).map(|p| p.grade())
+        };
+        assert_eq!(grade("#000000", "#ffffff"), Some("AAA"));
+        // #777 on white is 4.48:1, just below AA
+        assert_eq!(grade("#777777", "#ffffff"), Some("AA large"));
+        assert_eq!(grade("#777777", "#000000"), Some("AA"));
+    }
+}
@@ -0,0 +1,283 @@
+//! Concurrency Tuning Module
+//!
+//! Adaptive number of parallel upload connections. A sync starts at 2
+//! connections, or at the optimum learned for its target, and adds one while
+//! the extra connection brings enough throughput. It steps back when a level
+//! brings too little, and drops for good when the server refuses a login or
+//! files fail: many shared hosts cap concurrent logins. The best level and
+//! the refusal ceiling are kept per target for the next syncs.
+
+use crate::state_file;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Level of a target without a learned optimum
+const START_CONNECTIONS: usize = 2;
+
+/// Shortest measurement of a level
+const WINDOW: Duration = Duration::from_secs(3);
+
+/// Part of one connection's throughput an extra connection must add to be kept
+const MIN_GAIN: f64 = 0.25;
+
+/// Share of failed files above which the level is lowered
+const MAX_ERROR_RATE: f64 = 0.2;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LearnedConcurrency {
+    pub optimum: usize,
+    /// Highest level the server accepted logins for, not exceeded afterwards
+    pub ceiling: Option<usize>,
+    /// Bytes per second measured at the optimum
+    pub throughput: f64,
+    pub updated_at: String,
+}
+
+/// Key of a target in the learned store
+pub fn target_key(protocol: &str, username: &str, host: &str, port: u16) -> String {
+    format!("{}://{}@{}:{}", protocol, username, host, port)
+}
+
+fn store_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("concurrency_tuning.json")
+}
+
+pub fn list(app_data_dir: &Path) -> HashMap<String, LearnedConcurrency> {
+    state_file::read_json(&store_path(app_data_dir))
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+pub fn learned(app_data_dir: &Path, key: &str) -> Option<LearnedConcurrency> {
+    list(app_data_dir).remove(key)
+}
+
+pub fn save(app_data_dir: &Path, key: &str, learned: LearnedConcurrency) -> Result<(), String> {
+    let mut all = list(app_data_dir);
+    all.insert(key.to_string(), learned);
+    state_file::write_json(&store_path(app_data_dir), &all)
+}
+
+pub fn forget(app_data_dir: &Path, key: &str) -> Result<(), String> {
+    let mut all = list(app_data_dir);
+    if all.remove(key).is_some() {
+        state_file::write_json(&store_path(app_data_dir), &all)?;
+    }
+    Ok(())
+}
+
+struct Window {
+    started: Instant,
+    bytes: u64,
+    files: usize,
+    errors: usize,
+}
+
+impl Window {
+    fn new() -> Self {
+        Window { started: Instant::now(), bytes: 0, files: 0, errors: 0 }
+    }
+}
+
+struct TunerState {
+    window: Window,
+    /// Best (level, bytes per second) measured so far
+    best: Option<(usize, f64)>,
+    ceiling: Option<usize>,
+    /// No more ramping up once a level brought too little or was refused
+    settled: bool,
+}
+
+/// Connections of a parallel upload
+#[derive(Clone, Copy)]
+pub enum Connections<'a> {
+    Fixed(usize),
+    Adaptive(&'a AdaptiveLimit),
+}
+
+impl<'a> Connections<'a> {
+    /// Workers to start
+    pub fn max(&self) -> usize {
+        match self {
+            Connections::Fixed(count) => *count,
+            Connections::Adaptive(limit) => limit.max,
+        }
+    }
+
+    pub fn adaptive(&self) -> Option<&'a AdaptiveLimit> {
+        match self {
+            Connections::Fixed(_) => None,
+            Connections::Adaptive(limit) => Some(limit),
+        }
+    }
+}
+
+/// Connection level shared by the upload workers of one sync
+pub struct AdaptiveLimit {
+    level: AtomicUsize,
+    max: usize,
+    state: Mutex<TunerState>,
+}
+
+impl AdaptiveLimit {
+    pub fn new(learned: Option<&LearnedConcurrency>, max: usize) -> Self {
+        let max = max.max(1);
+        let ceiling = learned.and_then(|l| l.ceiling);
+        let start = learned
+            .map(|l| l.optimum)
+            .unwrap_or(START_CONNECTIONS)
+            .min(ceiling.unwrap_or(max))
+            .clamp(1, max);
+        AdaptiveLimit {
+            level: AtomicUsize::new(start),
+            max,
+            state: Mutex::new(TunerState { window: Window::new(), best: None, ceiling, settled: false }),
+        }
+    }
+
+    pub fn level(&self) -> usize {
+        self.level.load(Ordering::SeqCst)
+    }
+
+    /// Whether worker `index` (from 0) may take a file
+    pub fn allows(&self, index: usize) -> bool {
+        index < self.level()
+    }
+
+    fn set_level(&self, level: usize, reason: &str) {
+        let previous = self.level.swap(level, Ordering::SeqCst);
+        if previous != level {
+            println!("[Concurrency] {} -> {} connection(s): {}", previous, level, reason);
+        }
+    }
+
+    /// A file finished on a connection, `ok` false when it failed
+    pub fn record_file(&self, bytes: u64, ok: bool) {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(_) => return,
+        };
+        if ok {
+            state.window.bytes += bytes;
+            state.window.files += 1;
+        } else {
+            state.window.errors += 1;
+        }
+        self.evaluate(&mut state);
+    }
+
+    /// Worker `index` could not log in. Returns whether the level was lowered,
+    /// its file is then worth retrying on the connections left.
+    pub fn record_refused(&self, index: usize) -> bool {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(_) => return false,
+        };
+        let level = self.level();
+        if level <= 1 {
+            return false;
+        }
+        let lowered = index.clamp(1, level - 1);
+        state.ceiling = Some(state.ceiling.map(|c| c.min(lowered)).unwrap_or(lowered));
+        state.settled = true;
+        state.window = Window::new();
+        if state.best.map(|(best, _)| best > lowered).unwrap_or(false) {
+            state.best = None;
+        }
+        self.set_level(lowered, "connexion refusee par le serveur");
+        true
+    }
+
+    fn evaluate(&self, state: &mut TunerState) {
+        let level = self.level();
+        let window = &state.window;
+        let attempts = window.files + window.errors;
+        let elapsed = window.started.elapsed();
+        if elapsed < WINDOW || attempts < level {
+            return;
+        }
+
+        if window.errors as f64 / attempts as f64 > MAX_ERROR_RATE {
+            if level > 1 {
+                state.ceiling = Some(level - 1);
+                state.best = state.best.filter(|(best, _)| *best < level);
+                self.set_level(level - 1, "trop de fichiers en erreur");
+            }
+            state.settled = true;
+            state.window = Window::new();
+            return;
+        }
+
+        let throughput = window.bytes as f64 / elapsed.as_secs_f64();
+        match state.best {
+            Some((best, best_throughput)) if level > best => {
+                // What the extra connections added, against one connection at the best level
+                let per_connection = best_throughput / best as f64;
+                let added = (level - best) as f64;
+                if throughput - best_throughput >= per_connection * MIN_GAIN * added {
+                    state.best = Some((level, throughput));
+                } else {
+                    state.settled = true;
+                    self.set_level(best, "debit insuffisant avec plus de connexions");
+                }
+            }
+            Some((best, _)) if level < best => {}
+            _ => state.best = Some((level, throughput)),
+        }
+
+        let limit = state.ceiling.unwrap_or(self.max).min(self.max);
+        if !state.settled && state.best.map(|(best, _)| best == level).unwrap_or(false) && level < limit {
+            self.set_level(level + 1, "debit en hausse");
+        }
+        state.window = Window::new();
+    }
+
+    /// What this sync learned, `None` when it was too short to measure anything
+    pub fn outcome(&self) -> Option<LearnedConcurrency> {
+        let state = self.state.lock().ok()?;
+        let (optimum, throughput) = match (state.best, state.ceiling) {
+            (Some(best), _) => best,
+            (None, Some(_)) => (self.level(), 0.0),
+            (None, None) => return None,
+        };
+        Some(LearnedConcurrency {
+            optimum,
+            ceiling: state.ceiling,
+            throughput,
+            updated_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refusal_lowers_and_caps_the_level() {
+        let learned = LearnedConcurrency { optimum: 5, ceiling: None, throughput: 0.0, updated_at: String::new() };
+        let limit = AdaptiveLimit::new(Some(&learned), 8);
+        assert_eq!(limit.level(), 5);
+        assert!(limit.allows(4) && !limit.allows(5));
+
+        // The fourth login is refused: three connections are what the host accepts
+        assert!(limit.record_refused(3));
+        assert_eq!(limit.level(), 3);
+        let outcome = limit.outcome().unwrap();
+        assert_eq!((outcome.optimum, outcome.ceiling), (3, Some(3)));
+
+        // Restarting from what was learned stays under the ceiling
+        let learned = LearnedConcurrency { optimum: 6, ..outcome };
+        assert_eq!(AdaptiveLimit::new(Some(&learned), 8).level(), 3);
+        assert_eq!(AdaptiveLimit::new(None, 8).level(), START_CONNECTIONS);
+
+        let single = AdaptiveLimit::new(None, 1);
+        assert!(!single.record_refused(0));
+    }
+}
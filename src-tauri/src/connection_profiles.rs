@@ -0,0 +1,280 @@
+//! Connection Profiles Module
+//!
+//! Named server connections (host, port, protocol, authentication) shared
+//! by several projects. Projects keep the profile id and their own remote
+//! path; the password lives once in the keyring under the profile's key,
+//! so changing it updates every project using the profile. Profiles are
+//! kept in `connection_profiles.json` in the app data.
+
+use crate::{proxy, state_file, SFTPConfig, KEYRING_SERVICE};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuthMethod {
+    Password,
+    /// SFTP: ssh-agent, then the key file, then the password
+    SshAgent,
+    /// SFTP: private key file, then the password
+    Key,
+    /// SFTP: one-time code asked through the UI (see ssh_auth)
+    KeyboardInteractive,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionProfile {
+    pub id: String,
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    /// "sftp", "ftp", "ftps" or "webdav"
+    pub protocol: String,
+    pub username: String,
+    pub auth_method: AuthMethod,
+    pub private_key_path: Option<String>,
+    pub passive: Option<bool>,
+    pub accept_invalid_certs: Option<bool>,
+    pub proxy: Option<proxy::ProxyConfig>,
+    /// Keyring entry holding the password (and the key passphrase for `Key`)
+    pub keyring_key: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A profile as edited in the UI; no id creates a new profile
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileInput {
+    pub id: Option<String>,
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    pub protocol: String,
+    pub username: String,
+    pub auth_method: AuthMethod,
+    pub private_key_path: Option<String>,
+    pub passive: Option<bool>,
+    pub accept_invalid_certs: Option<bool>,
+    pub proxy: Option<proxy::ProxyConfig>,
+    /// Defaults to "profile-<id>"
+    pub keyring_key: Option<String>,
+    /// Written to the keyring when given, the current one is kept otherwise
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ProfileStore {
+    profiles: Vec<ConnectionProfile>,
+}
+
+fn store_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("connection_profiles.json")
+}
+
+fn load(app_data_dir: &Path) -> ProfileStore {
+    state_file::read_json(&store_path(app_data_dir)).ok().flatten().unwrap_or_default()
+}
+
+/// Profiles sorted by name
+pub fn list(app_data_dir: &Path) -> Vec<ConnectionProfile> {
+    let mut profiles = load(app_data_dir).profiles;
+    profiles.sort_by_key(|p| p.name.to_lowercase());
+    profiles
+}
+
+pub fn get(app_data_dir: &Path, id: &str) -> Result<ConnectionProfile, String> {
+    load(app_data_dir)
+        .profiles
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| format!("Profil de connexion introuvable: {}", id))
+}
+
+fn validate(input: &ProfileInput, others: &[ConnectionProfile]) -> Result<(), String> {
+    if input.name.trim().is_empty() {
+        return Err("Le nom du profil est requis".to_string());
+    }
+    if input.host.trim().is_empty() {
+        return Err("L'hote du profil est requis".to_string());
+    }
+    if input.port == 0 {
+        return Err("Port invalide".to_string());
+    }
+    if !["sftp", "ftp", "ftps", "webdav"].contains(&input.protocol.as_str()) {
+        return Err(format!("Protocole inconnu: {}", input.protocol));
+    }
+    if input.auth_method != AuthMethod::Password && input.protocol != "sftp" {
+        return Err("Seul le protocole SFTP accepte une authentification par cle, agent ou code".to_string());
+    }
+    let name = input.name.trim().to_lowercase();
+    if others.iter().any(|p| p.name.to_lowercase() == name) {
+        return Err(format!("Un profil nomme \"{}\" existe deja", input.name.trim()));
+    }
+    Ok(())
+}
+
+fn keyring_entry(key: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, key).map_err(|e| format!("Keyring error: {}", e))
+}
+
+/// Create or update a profile, writing its password to the keyring when given
+pub fn save(app_data_dir: &Path, input: ProfileInput) -> Result<ConnectionProfile, String> {
+    let mut store = load(app_data_dir);
+    let existing = input.id.as_ref().and_then(|id| store.profiles.iter().position(|p| &p.id == id));
+    if input.id.is_some() && existing.is_none() {
+        return Err(format!("Profil de connexion introuvable: {}", input.id.unwrap_or_default()));
+    }
+    let others: Vec<ConnectionProfile> = store
+        .profiles
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| Some(*index) != existing)
+        .map(|(_, p)| p.clone())
+        .collect();
+    validate(&input, &others)?;
+
+    let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let id = input.id.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let keyring_key = input
+        .keyring_key
+        .clone()
+        .filter(|k| !k.trim().is_empty())
+        .or_else(|| existing.map(|index| store.profiles[index].keyring_key.clone()))
+        .unwrap_or_else(|| format!("profile-{}", id));
+    let profile = ConnectionProfile {
+        created_at: existing.map(|index| store.profiles[index].created_at.clone()).unwrap_or_else(|| now.clone()),
+        updated_at: now,
+        id,
+        name: input.name.trim().to_string(),
+        host: input.host.trim().to_string(),
+        port: input.port,
+        protocol: input.protocol,
+        username: input.username,
+        auth_method: input.auth_method,
+        private_key_path: input.private_key_path.filter(|p| !p.trim().is_empty()),
+        passive: input.passive,
+        accept_invalid_certs: input.accept_invalid_certs,
+        proxy: input.proxy,
+        keyring_key,
+    };
+
+    if let Some(password) = &input.password {
+        set_password(&profile, password)?;
+    }
+    match existing {
+        Some(index) => store.profiles[index] = profile.clone(),
+        None => store.profiles.push(profile.clone()),
+    }
+    state_file::write_json(&store_path(app_data_dir), &store)?;
+    println!("[Profiles] Saved profile {} ({})", profile.name, profile.id);
+    Ok(profile)
+}
+
+/// Change the password of every project using the profile
+pub fn set_password(profile: &ConnectionProfile, password: &str) -> Result<(), String> {
+    keyring_entry(&profile.keyring_key)?
+        .set_password(password)
+        .map_err(|e| format!("Failed to save password: {}", e))
+}
+
+/// Remove a profile, and its keyring entry when `remove_password` is set
+pub fn delete(app_data_dir: &Path, id: &str, remove_password: bool) -> Result<(), String> {
+    let mut store = load(app_data_dir);
+    let index = store
+        .profiles
+        .iter()
+        .position(|p| p.id == id)
+        .ok_or_else(|| format!("Profil de connexion introuvable: {}", id))?;
+    let profile = store.profiles.remove(index);
+    state_file::write_json(&store_path(app_data_dir), &store)?;
+    if remove_password {
+        if let Err(e) = keyring_entry(&profile.keyring_key).and_then(|entry| {
+            entry.delete_credential().map_err(|e| e.to_string())
+        }) {
+            println!("[Profiles] Keyring entry of {} not removed: {}", profile.name, e);
+        }
+    }
+    println!("[Profiles] Deleted profile {} ({})", profile.name, profile.id);
+    Ok(())
+}
+
+/// Connection of a project using the profile, with its own remote path
+pub fn config_for(app_data_dir: &Path, id: &str, remote_path: &str) -> Result<SFTPConfig, String> {
+    let profile = get(app_data_dir, id)?;
+    let password = keyring_entry(&profile.keyring_key)?.get_password();
+    let password = match (password, profile.auth_method) {
+        (Ok(password), _) => password,
+        (Err(e), AuthMethod::Password) => {
+            return Err(format!("Mot de passe du profil {} introuvable dans le trousseau: {}", profile.name, e))
+        }
+        // Agent, key and one-time code logins only fall back to the password
+        (Err(_), _) => String::new(),
+    };
+    let key_passphrase = (profile.auth_method == AuthMethod::Key && !password.is_empty()).then(|| password.clone());
+
+    Ok(SFTPConfig {
+        host: profile.host,
+        port: profile.port,
+        username: profile.username,
+        password,
+        remote_path: remote_path.to_string(),
+        passive: profile.passive,
+        protocol: Some(profile.protocol),
+        accept_invalid_certs: profile.accept_invalid_certs,
+        use_ssh_agent: Some(profile.auth_method == AuthMethod::SshAgent),
+        private_key_path: profile.private_key_path,
+        key_passphrase,
+        keyboard_interactive: Some(profile.auth_method == AuthMethod::KeyboardInteractive),
+        proxy: profile.proxy,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn input(name: &str, protocol: &str, auth_method: AuthMethod) -> ProfileInput {
+        ProfileInput {
+            id: None,
+            name: name.to_string(),
+            host: "example.com".to_string(),
+            port: 22,
+            protocol: protocol.to_string(),
+            username: "deploy".to_string(),
+            auth_method,
+            private_key_path: None,
+            passive: None,
+            accept_invalid_certs: None,
+            proxy: None,
+            keyring_key: None,
+            password: None,
+        }
+    }
+
+    #[test]
+    fn test_profiles_are_named_once_and_updated_in_place() {
+        let dir = TempDir::new("connection-profiles");
+        let created = save(dir.path(), input("Mutualise", "sftp", AuthMethod::SshAgent)).unwrap();
+        assert_eq!(created.keyring_key, format!("profile-{}", created.id));
+
+        assert!(save(dir.path(), input("mutualise", "ftp", AuthMethod::Password)).is_err());
+        assert!(save(dir.path(), input("FTP", "ftp", AuthMethod::Key)).is_err());
+
+        let mut renamed = input("Mutualise", "sftp", AuthMethod::Password);
+        renamed.id = Some(created.id.clone());
+        renamed.port = 2222;
+        let updated = save(dir.path(), renamed).unwrap();
+        assert_eq!((updated.created_at, updated.keyring_key), (created.created_at, created.keyring_key));
+
+        save(dir.path(), input("Agence", "ftp", AuthMethod::Password)).unwrap();
+        let names: Vec<String> = list(dir.path()).into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["Agence", "Mutualise"]);
+        assert_eq!(get(dir.path(), &created.id).unwrap().port, 2222);
+
+        delete(dir.path(), &created.id, false).unwrap();
+        assert!(get(dir.path(), &created.id).is_err());
+    }
+}
@@ -0,0 +1,235 @@
+//! Content Inventory Module
+//!
+//! Language detection and word counts for scraped text, and the per-page
+//! inventory (words, text blocks, images) a copywriter needs to quote a site
+//! migration. The language is guessed from stop words, which is reliable on
+//! a paragraph or a page; the page's `lang` attribute is the fallback for
+//! text too short to tell.
+
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Below this many words a text is too short for a language guess
+const MIN_WORDS_FOR_DETECTION: usize = 5;
+
+/// Stop words that hit at least this share of the words confirm a language
+const MIN_STOP_WORD_SHARE: f64 = 0.08;
+
+/// Elements holding the readable text of a page
+const TEXT_ELEMENTS: &str = "h1, h2, h3, h4, h5, h6, p, li, td, th, blockquote, figcaption, dt, dd";
+
+const STOP_WORDS: [(&str, &[&str]); 7] = [
+    ("fr", &["le", "la", "les", "des", "une", "est", "et", "pour", "dans", "que", "qui", "sur", "avec", "pas", "vous", "nous", "du", "de", "ce", "sont"]),
+    ("en", &["the", "and", "is", "are", "of", "to", "for", "with", "that", "this", "you", "on", "it", "be", "was", "our", "your", "from", "have", "by"]),
+    ("de", &["der", "die", "das", "und", "ist", "nicht", "mit", "sie", "ein", "eine", "zu", "den", "von", "auf", "für", "wir", "ihr", "auch", "sich", "dem"]),
+    ("es", &["el", "los", "las", "del", "una", "es", "y", "para", "con", "que", "por", "en", "su", "como", "más", "nuestro", "sus", "al", "lo", "se"]),
+    ("it", &["il", "gli", "della", "delle", "una", "è", "e", "per", "con", "che", "non", "sono", "di", "nel", "alla", "anche", "più", "questo", "come", "del"]),
+    ("nl", &["de", "het", "een", "en", "van", "is", "niet", "met", "voor", "op", "zijn", "dat", "die", "wij", "ook", "naar", "bij", "uw", "aan", "deze"]),
+    ("pt", &["o", "os", "as", "uma", "um", "não", "para", "com", "que", "por", "em", "do", "da", "dos", "das", "é", "mais", "seu", "sua", "são"]),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageInventory {
+    pub url: String,
+    pub title: String,
+    pub meta_description: Option<String>,
+    /// Text of the first h1
+    pub h1: Option<String>,
+    /// HTTP status of the page (captured pages are 200)
+    pub status: u16,
+    pub language: Option<String>,
+    pub word_count: usize,
+    /// Headings, paragraphs, list items... holding text
+    pub text_blocks: usize,
+    pub image_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageShare {
+    pub language: String,
+    pub pages: usize,
+    pub words: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContentInventory {
+    pub pages: Vec<PageInventory>,
+    pub total_pages: usize,
+    pub total_words: usize,
+    pub total_images: usize,
+    /// Languages found, most words first
+    pub languages: Vec<LanguageShare>,
+}
+
+/// Words of a text: whitespace-separated tokens holding a letter or digit
+pub fn word_count(text: &str) -> usize {
+    text.split_whitespace().filter(|token| token.chars().any(char::is_alphanumeric)).count()
+}
+
+/// ISO 639-1 code of the text's language, None when too short or unrecognized
+pub fn detect_language(text: &str) -> Option<String> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if words.len() < MIN_WORDS_FOR_DETECTION {
+        return None;
+    }
+    let (language, hits) = STOP_WORDS
+        .iter()
+        .map(|(language, stop_words)| (*language, words.iter().filter(|w| stop_words.contains(&w.as_str())).count()))
+        .max_by_key(|(_, hits)| *hits)?;
+    if (hits as f64) < words.len() as f64 * MIN_STOP_WORD_SHARE {
+        return None;
+    }
+    Some(language.to_string())
+}
+
+/// Primary subtag of a `lang` attribute ("fr-FR" -> "fr")
+fn primary_language(lang: &str) -> Option<String> {
+    let primary = lang.trim().split(['-', '_']).next()?.to_lowercase();
+    (primary.len() == 2 || primary.len() == 3).then_some(primary)
+}
+
+/// First match of `selector`, its text or the `attr` attribute, trimmed and not empty
+fn first_value(document: &Html, selector: &str, attr: Option<&str>) -> Option<String> {
+    let selector = Selector::parse(selector).unwrap();
+    let element = document.select(&selector).next()?;
+    let value = match attr {
+        Some(attr) => element.value().attr(attr)?.to_string(),
+        None => element.text().collect::<String>(),
+    };
+    let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!value.is_empty()).then_some(value)
+}
+
+/// Inventory of a page from the text blocks already extracted from it
+pub fn page_inventory(url: &str, document: &Html, blocks: &[&str]) -> PageInventory {
+    let text = blocks.join("\n");
+    let img_selector = Selector::parse("img").unwrap();
+    let lang_attr = first_value(document, "html[lang]", Some("lang"));
+    PageInventory {
+        url: url.to_string(),
+        title: first_value(document, "title", None).unwrap_or_default(),
+        meta_description: first_value(document, "meta[name='description']", Some("content")),
+        h1: first_value(document, "h1", None),
+        status: 200,
+        language: detect_language(&text).or_else(|| lang_attr.as_deref().and_then(primary_language)),
+        word_count: word_count(&text),
+        text_blocks: blocks.len(),
+        image_count: document.select(&img_selector).count(),
+    }
+}
+
+/// Row of a page that answered with an HTTP error
+pub fn error_page(url: &str, status: u16) -> PageInventory {
+    PageInventory {
+        url: url.to_string(),
+        title: String::new(),
+        meta_description: None,
+        h1: None,
+        status,
+        language: None,
+        word_count: 0,
+        text_blocks: 0,
+        image_count: 0,
+    }
+}
+
+/// Inventory of a captured HTML page
+pub fn page_inventory_from_html(url: &str, html: &str) -> PageInventory {
+    let document = Html::parse_document(html);
+    let text_selector = Selector::parse(TEXT_ELEMENTS).unwrap();
+
+    let blocks: Vec<String> = document
+        .select(&text_selector)
+        // Nested blocks (p in li, li in td) would be counted twice
+        .filter(|element| !element.ancestors().filter_map(scraper::ElementRef::wrap).any(|a| text_selector.matches(&a)))
+        .map(|element| element.text().collect::<String>().trim().to_string())
+        .filter(|text| !text.is_empty())
+        .collect();
+    let blocks: Vec<&str> = blocks.iter().map(String::as_str).collect();
+    page_inventory(url, &document, &blocks)
+}
+
+impl ContentInventory {
+    pub fn from_pages(mut pages: Vec<PageInventory>) -> Self {
+        pages.sort_by(|a, b| a.url.cmp(&b.url));
+        let mut languages: HashMap<String, LanguageShare> = HashMap::new();
+        for page in pages.iter().filter(|p| p.status < 400) {
+            let language = page.language.clone().unwrap_or_else(|| "?".to_string());
+            let share = languages.entry(language.clone()).or_insert(LanguageShare { language, pages: 0, words: 0 });
+            share.pages += 1;
+            share.words += page.word_count;
+        }
+        let mut languages: Vec<LanguageShare> = languages.into_values().collect();
+        languages.sort_by(|a, b| b.words.cmp(&a.words).then_with(|| a.language.cmp(&b.language)));
+
+        ContentInventory {
+            total_pages: pages.iter().filter(|p| p.status < 400).count(),
+            total_words: pages.iter().map(|p| p.word_count).sum(),
+            total_images: pages.iter().map(|p| p.image_count).sum(),
+            languages,
+            pages,
+        }
+    }
+
+    /// "Inventaire du contenu" section of a Markdown report
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::from("## Inventaire du contenu\n\n");
+        md.push_str(&format!("- **Pages:** {}\n", self.total_pages));
+        md.push_str(&format!("- **Mots:** {}\n", self.total_words));
+        md.push_str(&format!("- **Images:** {}\n", self.total_images));
+        let languages: Vec<String> = self
+            .languages
+            .iter()
+            .map(|l| format!("{} ({} page(s), {} mots)", l.language, l.pages, l.words))
+            .collect();
+        md.push_str(&format!("- **Langues:** {}\n\n", if languages.is_empty() { "-".to_string() } else { languages.join(", ") }));
+
+        md.push_str("| Page | Titre | Langue | Mots | Blocs | Images |\n");
+        md.push_str("|------|-------|--------|------|-------|--------|\n");
+        for page in &self.pages {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} |\n",
+                page.url,
+                page.title.replace('|', "\\|"),
+                page.language.as_deref().unwrap_or("-"),
+                page.word_count,
+                page.text_blocks,
+                page.image_count
+            ));
+        }
+        md.push('\n');
+        md
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_inventory_counts_and_language() {
+        let html = r#"<html lang="fr-FR"><head><title>Accueil</title></head><body>
+            <h1>Bienvenue</h1>
+            <p>Nous sommes une agence qui cree des sites pour les artisans et les commerces de la region.</p>
+            <ul><li><p>Un seul bloc</p></li></ul>
+            <img src="a.jpg"><img src="b.jpg">
+        </body></html>"#;
+        let page = page_inventory_from_html("https://example.com/", html);
+        assert_eq!(page.title, "Accueil");
+        assert_eq!(page.language.as_deref(), Some("fr"));
+        assert_eq!(page.text_blocks, 3);
+        assert_eq!(page.word_count, 1 + 17 + 3);
+        assert_eq!(page.image_count, 2);
+
+        assert_eq!(detect_language("The quick brown fox jumps over the lazy dog and the cat").as_deref(), Some("en"));
+        assert_eq!(detect_language("Trop court"), None);
+        let inventory = ContentInventory::from_pages(vec![page]);
+        assert_eq!(inventory.total_words, 21);
+        assert_eq!(inventory.languages[0].language, "fr");
+    }
+}
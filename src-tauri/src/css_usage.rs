@@ -0,0 +1,226 @@
+//! CSS Usage Module
+//!
+//! Estimates which rules of a stylesheet are used by a set of captured
+//! pages by matching their selectors against the downloaded HTML.
+
+use scraper::{Html, Selector};
+use serde::Serialize;
+
+/// Pseudo-classes that depend on user interaction and never match a static document
+const DYNAMIC_PSEUDOS: [&str; 8] = [
+    "hover", "focus", "active", "visited", "focus-within", "focus-visible", "target", "link",
+];
+
+/// Legacy single-colon pseudo-elements
+const LEGACY_PSEUDO_ELEMENTS: [&str; 4] = ["before", "after", "first-letter", "first-line"];
+
+/// At-rules whose blocks contain regular style rules
+const NESTING_AT_RULES: [&str; 4] = ["@media", "@supports", "@layer", "@container"];
+
+/// Rule usage of a single stylesheet
+#[derive(Debug, Clone, Serialize)]
+pub struct StylesheetUsage {
+    pub stylesheet: String, // path relative to the scrape output folder
+    pub total_rules: usize,
+    pub used_rules: usize,
+    /// Rules whose selector could not be evaluated (counted as used)
+    pub unknown_rules: usize,
+    pub unused_percent: f32,
+}
+
+/// Count the style rules of `css` and how many match at least one document
+pub fn analyze_stylesheet(stylesheet: &str, css: &str, documents: &[Html]) -> StylesheetUsage {
+    let css = strip_comments(css);
+    let mut selectors = Vec::new();
+    collect_selectors(&css, &mut selectors);
+
+    let mut used_rules = 0;
+    let mut unknown_rules = 0;
+    for selector_list in &selectors {
+        match rule_is_used(selector_list, documents) {
+            Some(true) => used_rules += 1,
+            Some(false) => {}
+            None => {
+                used_rules += 1;
+                unknown_rules += 1;
+            }
+        }
+    }
+
+    let total_rules = selectors.len();
+    let unused_percent = if total_rules == 0 {
+        0.0
+    } else {
+        ((total_rules - used_rules) as f32 / total_rules as f32) * 100.0
+    };
+
+    StylesheetUsage {
+        stylesheet: stylesheet.to_string(),
+        total_rules,
+        used_rules,
+        unknown_rules,
+        unused_percent,
+    }
+}
+
+/// `Some(used)` when every selector of the list could be evaluated, `None` otherwise
+fn rule_is_used(selector_list: &str, documents: &[Html]) -> Option<bool> {
+    let mut evaluated_all = true;
+    for part in selector_list.split(',') {
+        let cleaned = strip_dynamic_pseudos(part.trim());
+        match Selector::parse(&cleaned) {
+            Ok(selector) => {
+                if documents.iter().any(|doc| doc.select(&selector).next().is_some()) {
+                    return Some(true);
+                }
+            }
+            Err(_) => evaluated_all = false,
+        };
+    }
+    if evaluated_all {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Walk a stylesheet and collect the selector text of every style rule,
+/// descending into `@media`-like blocks and skipping `@font-face`, `@keyframes`...
+fn collect_selectors(css: &str, out: &mut Vec<String>) {
+    let bytes = css.as_bytes();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b';' => {
+                // Top-level statement such as @import or @charset
+                start = i + 1;
+            }
+            b'{' => {
+                let prelude = css[start..i].trim();
+                let end = find_block_end(bytes, i);
+                if prelude.starts_with('@') {
+                    let name = prelude.split_whitespace().next().unwrap_or("").to_lowercase();
+                    if NESTING_AT_RULES.contains(&name.as_str()) {
+                        collect_selectors(&css[i + 1..end], out);
+                    }
+                } else if !prelude.is_empty() {
+                    out.push(prelude.to_string());
+                }
+                i = end;
+                start = end + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+}
+
+/// Index of the `}` closing the block opened at `open`
+fn find_block_end(bytes: &[u8], open: usize) -> usize {
+    let mut depth = 0;
+    for (offset, b) in bytes[open..].iter().enumerate() {
+        match b {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return open + offset;
+                }
+            }
+            _ => {}
+        }
+    }
+    bytes.len().saturating_sub(1)
+}
+
+fn strip_comments(css: &str) -> String {
+    let mut result = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        result.push_str(&rest[..start]);
+        rest = match rest[start + 2..].find("*/") {
+            Some(end) => &rest[start + 2 + end + 2..],
+            None => "",
+        };
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Remove pseudo-elements and interaction pseudo-classes so `a:hover`
+/// is evaluated as `a`
+fn strip_dynamic_pseudos(selector: &str) -> String {
+    let mut result = String::with_capacity(selector.len());
+    let mut chars = selector.char_indices().peekable();
+
+    while let Some((idx, c)) = chars.next() {
+        if c != ':' {
+            result.push(c);
+            continue;
+        }
+
+        let is_element = selector[idx + 1..].starts_with(':');
+        let name_start = if is_element { idx + 2 } else { idx + 1 };
+        let name: String = selector[name_start..]
+            .chars()
+            .take_while(|ch| ch.is_alphanumeric() || *ch == '-')
+            .collect();
+        let name_lower = name.to_lowercase();
+
+        let remove = is_element
+            || DYNAMIC_PSEUDOS.contains(&name_lower.as_str())
+            || LEGACY_PSEUDO_ELEMENTS.contains(&name_lower.as_str());
+        if !remove {
+            result.push(c);
+            continue;
+        }
+
+        // Skip the pseudo name and an optional parenthesized argument
+        let mut end = name_start + name.len();
+        if selector[end..].starts_with('(') {
+            end = selector[end..].find(')').map(|p| end + p + 1).unwrap_or(selector.len());
+        }
+        while chars.peek().map(|(i, _)| *i < end).unwrap_or(false) {
+            chars.next();
+        }
+    }
+
+    let trimmed = result.trim_end();
+    if trimmed.is_empty() || trimmed.ends_with(['>', '+', '~']) || result.ends_with(' ') {
+        format!("{}*", result)
+    } else {
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules_matched_against_the_pages() {
+        let pages = [
+            Html::parse_document("<body><nav class=\"menu\"><a href=\"/\">Accueil</a></nav></body>"),
+            Html::parse_document("<body><article id=\"post\"><p>Texte</p></article></body>"),
+        ];
+        let css = r#"
+            /* .old { color: red } */
+            .menu a:hover { color: blue }
+            #post p::before, .missing { content: "" }
+            .carousel { display: flex }
+            @media (max-width: 600px) { .sidebar { display: none } nav { display: block } }
+            @font-face { font-family: Inter; src: url(inter.woff2) }
+            @keyframes spin { from { opacity: 0 } to { opacity: 1 } }
+            :unknown-pseudo(1) { color: red }
+        "#;
+        let usage = analyze_stylesheet("css/site.css", css, &pages);
+
+        assert_eq!(usage.stylesheet, "css/site.css");
+        // Used: .menu a:hover, #post p::before, nav, and the unparsable rule
+        assert_eq!((usage.total_rules, usage.used_rules, usage.unknown_rules), (6, 4, 1));
+        assert!((usage.unused_percent - 100.0 / 3.0).abs() < 0.01);
+        assert_eq!(analyze_stylesheet("empty.css", "", &pages).unused_percent, 0.0);
+    }
+}
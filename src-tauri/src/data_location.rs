@@ -0,0 +1,286 @@
+//! Data Location Module
+//!
+//! Lets La Forge keep its data (caches, backups, histories, sessions) in
+//! another folder than the default app data directory, e.g. on an external
+//! SSD or a synced drive. The chosen folder is recorded in a pointer file
+//! that always stays in the default directory, next to the settings stores.
+
+use crate::state_file;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::AppHandle;
+use walkdir::WalkDir;
+
+/// Pointer file, in the default app data directory
+const POINTER_FILE: &str = "data_location.json";
+
+/// Files read by the settings stores from the default directory, never moved
+const PINNED_FILES: [&str; 6] = [
+    POINTER_FILE,
+    "app-settings.json",
+    crate::menu_config::MENU_CONFIG_FILE,
+    "projects-config.json",
+    "credentials.dat",
+    "backups/projects-config.backup.json",
+];
+
+/// Resolved data directory, replaced when the data is moved
+static CURRENT: Lazy<Mutex<Option<PathBuf>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LocationPointer {
+    path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DataLocation {
+    pub path: String,
+    pub default_path: String,
+    pub is_custom: bool,
+    /// False when the custom folder is missing (volume not mounted), the default is used meanwhile
+    pub available: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DataMigrationProgress {
+    pub file: String,
+    pub files_done: usize,
+    pub files_total: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DataMigrationResult {
+    pub from: String,
+    pub to: String,
+    pub files_moved: usize,
+    pub bytes_moved: u64,
+    /// Old copies that could not be removed after the switch
+    pub leftovers: Vec<String>,
+}
+
+/// Default app data directory, where the settings stores and the pointer file live
+pub fn default_dir(app: &AppHandle) -> Option<PathBuf> {
+    app.path_resolver().app_data_dir()
+}
+
+fn read_pointer(default_dir: &Path) -> Option<PathBuf> {
+    fs::read_to_string(default_dir.join(POINTER_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str::<LocationPointer>(&content).ok())
+        .map(|pointer| PathBuf::from(pointer.path))
+}
+
+/// Directory every module stores its data in
+pub fn app_data_dir(app: &AppHandle) -> Option<PathBuf> {
+    if let Ok(current) = CURRENT.lock() {
+        if let Some(dir) = current.as_ref() {
+            return Some(dir.clone());
+        }
+    }
+
+    let default_dir = default_dir(app)?;
+    let dir = match read_pointer(&default_dir) {
+        Some(custom) if custom.is_dir() => custom,
+        Some(custom) => {
+            // Don't remember the fallback: the volume may come back
+            println!("[DataLocation] {} unavailable, using default directory", custom.display());
+            return Some(default_dir);
+        }
+        None => default_dir,
+    };
+    if let Ok(mut current) = CURRENT.lock() {
+        *current = Some(dir.clone());
+    }
+    Some(dir)
+}
+
+/// Data directory resolved so far, for code running without an AppHandle
+pub fn current_dir() -> Option<PathBuf> {
+    CURRENT.lock().ok().and_then(|current| current.clone())
+}
+
+pub fn get_location(app: &AppHandle) -> Result<DataLocation, String> {
+    let default_dir = default_dir(app).ok_or("Could not get app data directory")?;
+    let custom = read_pointer(&default_dir);
+    let available = custom.as_ref().map(|p| p.is_dir()).unwrap_or(true);
+    Ok(DataLocation {
+        path: custom.as_ref().unwrap_or(&default_dir).to_string_lossy().to_string(),
+        default_path: default_dir.to_string_lossy().to_string(),
+        is_custom: custom.is_some(),
+        available,
+    })
+}
+
+fn is_pinned(relative: &str) -> bool {
+    PINNED_FILES.contains(&relative)
+}
+
+/// Files of the data directory that follow the data location
+fn movable_files(dir: &Path) -> Vec<(PathBuf, String)> {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let relative = e.path().strip_prefix(dir).ok()?.to_string_lossy().replace('\\', "/");
+            if is_pinned(&relative) {
+                None
+            } else {
+                Some((e.path().to_path_buf(), relative))
+            }
+        })
+        .collect()
+}
+
+fn write_pointer(default_dir: &Path, target: Option<&Path>) -> Result<(), String> {
+    let pointer_path = default_dir.join(POINTER_FILE);
+    let target = match target {
+        Some(target) => target,
+        None => {
+            return match fs::remove_file(&pointer_path) {
+                Ok(()) => Ok(()),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                Err(e) => Err(format!("Failed to reset data location: {}", e)),
+            };
+        }
+    };
+    let content = serde_json::to_string_pretty(&LocationPointer {
+        path: target.to_string_lossy().to_string(),
+    })
+    .map_err(|e| format!("Failed to serialize data location: {}", e))?;
+    // Written atomically so the pointer is never half written
+    state_file::write_atomic(&pointer_path, content.as_bytes())
+}
+
+/// Move the data to `target` (back to the default directory when None) and switch to it.
+/// The switch happens only once every file is copied and checked; the old copies are removed last.
+pub fn migrate<F>(app: &AppHandle, target: Option<&str>, mut on_progress: F) -> Result<DataMigrationResult, String>
+where
+    F: FnMut(DataMigrationProgress),
+{
+    let default_dir = default_dir(app).ok_or("Could not get app data directory")?;
+    let source = app_data_dir(app).ok_or("Could not get app data directory")?;
+    let target_dir = match target {
+        Some(target) if !target.trim().is_empty() => PathBuf::from(target.trim()),
+        _ => default_dir.clone(),
+    };
+
+    if !target_dir.is_absolute() {
+        return Err("Le nouvel emplacement doit etre un chemin absolu".to_string());
+    }
+    if target_dir == source {
+        return Err("Les donnees sont deja a cet emplacement".to_string());
+    }
+    if target_dir.starts_with(&source) {
+        return Err("Le nouvel emplacement ne peut pas etre dans le dossier actuel".to_string());
+    }
+    if !crate::sync_lock::active_syncs().is_empty() {
+        return Err("Une synchronisation est en cours, reessayez une fois terminee".to_string());
+    }
+
+    let files = movable_files(&source);
+    fs::create_dir_all(&target_dir).map_err(|e| format!("Failed to create {}: {}", target_dir.display(), e))?;
+    // Never mix two data sets
+    if target_dir != default_dir {
+        let existing = movable_files(&target_dir);
+        if !existing.is_empty() {
+            return Err(format!(
+                "Le dossier {} contient deja des fichiers, choisissez un dossier vide",
+                target_dir.display()
+            ));
+        }
+    }
+
+    println!(
+        "[DataLocation] Moving {} files from {} to {}",
+        files.len(),
+        source.display(),
+        target_dir.display()
+    );
+
+    let mut copied: Vec<PathBuf> = Vec::new();
+    let mut bytes_moved = 0;
+    for (index, (path, relative)) in files.iter().enumerate() {
+        on_progress(DataMigrationProgress {
+            file: relative.clone(),
+            files_done: index,
+            files_total: files.len(),
+        });
+        let dest = target_dir.join(relative);
+        let result = dest
+            .parent()
+            .map(fs::create_dir_all)
+            .unwrap_or(Ok(()))
+            .and_then(|_| fs::copy(path, &dest));
+        let expected = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        match result {
+            Ok(size) if size == expected => {
+                copied.push(dest);
+                bytes_moved += size;
+            }
+            other => {
+                // Roll back: the current location stays in use, untouched
+                for file in &copied {
+                    let _ = fs::remove_file(file);
+                }
+                let _ = fs::remove_file(&dest);
+                let reason = match other {
+                    Err(e) => e.to_string(),
+                    Ok(size) => format!("{} octets copies sur {}", size, expected),
+                };
+                return Err(format!("Failed to copy {}: {}", relative, reason));
+            }
+        }
+    }
+
+    let new_location = if target_dir == default_dir { None } else { Some(target_dir.as_path()) };
+    write_pointer(&default_dir, new_location)?;
+    if let Ok(mut current) = CURRENT.lock() {
+        *current = Some(target_dir.clone());
+    }
+
+    let mut leftovers = Vec::new();
+    for (path, relative) in &files {
+        if fs::remove_file(path).is_err() {
+            leftovers.push(relative.clone());
+        }
+    }
+    remove_empty_dirs(&source);
+
+    on_progress(DataMigrationProgress {
+        file: String::new(),
+        files_done: files.len(),
+        files_total: files.len(),
+    });
+    println!(
+        "[DataLocation] Data now in {} ({} files, {} leftovers)",
+        target_dir.display(),
+        files.len(),
+        leftovers.len()
+    );
+
+    Ok(DataMigrationResult {
+        from: source.to_string_lossy().to_string(),
+        to: target_dir.to_string_lossy().to_string(),
+        files_moved: files.len(),
+        bytes_moved,
+        leftovers,
+    })
+}
+
+/// Remove the directories emptied by the move, keeping the root
+fn remove_empty_dirs(root: &Path) {
+    for entry in WalkDir::new(root)
+        .contents_first(true)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_dir())
+    {
+        // Fails on non-empty directories, which is what we want
+        let _ = fs::remove_dir(entry.path());
+    }
+}
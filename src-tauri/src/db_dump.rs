@@ -0,0 +1,310 @@
+//! Database Dump Module
+//!
+//! Runs `mysqldump` or `pg_dump` on the client's server over SSH, streams
+//! the compressed dump into the project's `backups/db/` folder and keeps
+//! only the most recent dumps. The password is sent on the channel's stdin,
+//! never in the command line. Schedules with `include_db_dump` run the dump
+//! from the scheduler when they fire.
+
+use crate::remote_exec::{self, shell_quote};
+use crate::{SFTPConfig, KEYRING_SERVICE};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Folder (relative to the project root) receiving the dumps
+pub const DB_BACKUP_FOLDER: &str = "backups/db";
+
+/// Written to stderr by the remote command when the dump tool fails
+const DUMP_FAILED_MARKER: &str = "LAFORGE_DUMP_FAILED";
+
+/// Database to dump, the password being read from the keyring
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbDumpConfig {
+    pub engine: String, // "mysql" or "postgres"
+    #[serde(default = "default_db_host")]
+    pub host: String,
+    pub port: Option<u16>,
+    pub database: String,
+    pub username: String,
+    /// Keyring key holding the database password
+    pub password_key: String,
+    /// Number of dumps kept per database
+    #[serde(default = "default_keep")]
+    pub keep: usize,
+}
+
+fn default_db_host() -> String { "localhost".to_string() }
+fn default_keep() -> usize { 7 }
+
+/// Database dumped when a schedule fires. The scheduler has no access to
+/// the project credentials, so the SSH connection comes from a connection profile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledDbDump {
+    pub profile_id: String,
+    pub project_path: String,
+    pub db: DbDumpConfig,
+}
+
+/// A dump stored in the project
+#[derive(Debug, Clone, Serialize)]
+pub struct DbDumpResult {
+    pub file_path: String,
+    pub size: u64,
+    pub duration_ms: u64,
+    /// Older dumps removed by the rotation
+    pub removed: Vec<String>,
+}
+
+/// Dump the database over SSH into `<project>/backups/db/<database>-<timestamp>.sql.gz`
+pub fn dump_database(ssh: &SFTPConfig, db: &DbDumpConfig, project_path: &str) -> Result<DbDumpResult, String> {
+    if ssh.protocol.as_deref() != Some("sftp") {
+        return Err("Le dump de base de donnees necessite une connexion SFTP (SSH)".to_string());
+    }
+    let started = Instant::now();
+    let password = keyring::Entry::new(KEYRING_SERVICE, &db.password_key)
+        .and_then(|entry| entry.get_password())
+        .map_err(|e| format!("Mot de passe de la base introuvable dans le trousseau: {}", e))?;
+    if password.contains('\n') {
+        return Err("Le mot de passe de la base ne peut pas contenir de retour a la ligne".to_string());
+    }
+    let command = dump_command(db)?;
+
+    let backup_dir = Path::new(project_path).join(DB_BACKUP_FOLDER);
+    fs::create_dir_all(&backup_dir).map_err(|e| format!("Failed to create backup directory: {}", e))?;
+    let file_name = format!(
+        "{}-{}.sql.gz",
+        sanitize(&db.database),
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    );
+    let file_path = backup_dir.join(&file_name);
+
+    println!("[DbDump] Dumping {} ({}) from {}", db.database, db.engine, ssh.host);
+    let sess = remote_exec::connect_ssh(ssh)?;
+    let mut channel = sess.channel_session().map_err(|e| format!("Failed to open channel: {}", e))?;
+    channel.exec(&command).map_err(|e| format!("Failed to run dump: {}", e))?;
+    // Through stdin: in the command line it would show in the server's process list
+    channel
+        .write_all(format!("{}\n", password).as_bytes())
+        .and_then(|_| channel.flush())
+        .map_err(|e| format!("Failed to send the database password: {}", e))?;
+    channel.send_eof().map_err(|e| format!("Failed to send the database password: {}", e))?;
+
+    let size = {
+        let mut file = File::create(&file_path).map_err(|e| format!("Failed to create dump file: {}", e))?;
+        let mut buffer = [0u8; 64 * 1024];
+        let mut total = 0u64;
+        loop {
+            let read = channel.read(&mut buffer).map_err(|e| format!("Failed to read dump: {}", e))?;
+            if read == 0 {
+                break;
+            }
+            file.write_all(&buffer[..read]).map_err(|e| format!("Failed to write dump file: {}", e))?;
+            total += read as u64;
+        }
+        total
+    };
+
+    let mut stderr = String::new();
+    let _ = channel.stderr().read_to_string(&mut stderr);
+    channel.wait_close().map_err(|e| format!("Failed to close channel: {}", e))?;
+    let exit_code = channel.exit_status().unwrap_or(-1);
+
+    // gzip succeeds even when the dump fails, hence the marker
+    if exit_code != 0 || stderr.contains(DUMP_FAILED_MARKER) {
+        let _ = fs::remove_file(&file_path);
+        return Err(format!("Echec du dump (code {}): {}", exit_code, stderr.trim()));
+    }
+
+    let removed = rotate_dumps(&backup_dir, &db.database, db.keep)?;
+    println!("[DbDump] Saved {} ({} bytes)", file_name, size);
+
+    Ok(DbDumpResult {
+        file_path: file_path.to_string_lossy().to_string(),
+        size,
+        duration_ms: started.elapsed().as_millis() as u64,
+        removed,
+    })
+}
+
+/// Run the dump of a schedule
+pub fn run_scheduled(app_data_dir: &Path, dump: &ScheduledDbDump) -> Result<DbDumpResult, String> {
+    let ssh = crate::connection_profiles::config_for(app_data_dir, &dump.profile_id, "")?;
+    dump_database(&ssh, &dump.db, &dump.project_path)
+}
+
+/// Remote shell command producing a gzipped dump on stdout. The password is
+/// read from the first line of stdin into the tool's environment variable.
+fn dump_command(db: &DbDumpConfig) -> Result<String, String> {
+    let (password_variable, dump) = match db.engine.as_str() {
+        "mysql" => (
+            "MYSQL_PWD",
+            format!(
+                "mysqldump --single-transaction --quick --routines -h {} -P {} -u {} {}",
+                shell_quote(&db.host),
+                db.port.unwrap_or(3306),
+                shell_quote(&db.username),
+                shell_quote(&db.database)
+            ),
+        ),
+        "postgres" => (
+            "PGPASSWORD",
+            format!(
+                "pg_dump --no-owner -h {} -p {} -U {} {}",
+                shell_quote(&db.host),
+                db.port.unwrap_or(5432),
+                shell_quote(&db.username),
+                shell_quote(&db.database)
+            ),
+        ),
+        other => return Err(format!("Moteur de base de donnees non supporte: {}", other)),
+    };
+    Ok(format!(
+        "IFS= read -r {0}; export {0}; {{ {1} || echo {2} >&2; }} | gzip -c",
+        password_variable, dump, DUMP_FAILED_MARKER
+    ))
+}
+
+/// Keep the `keep` most recent dumps of a database, return the removed files
+fn rotate_dumps(backup_dir: &Path, database: &str, keep: usize) -> Result<Vec<String>, String> {
+    let prefix = format!("{}-", sanitize(database));
+    let mut dumps: Vec<PathBuf> = fs::read_dir(backup_dir)
+        .map_err(|e| format!("Failed to read backup directory: {}", e))?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .map(|n| {
+                    let name = n.to_string_lossy();
+                    name.starts_with(&prefix) && name.ends_with(".sql.gz")
+                })
+                .unwrap_or(false)
+        })
+        .collect();
+    // Timestamped names sort chronologically
+    dumps.sort();
+
+    let excess = dumps.len().saturating_sub(keep.max(1));
+    let mut removed = Vec::new();
+    for path in dumps.into_iter().take(excess) {
+        if fs::remove_file(&path).is_ok() {
+            removed.push(path.to_string_lossy().to_string());
+        }
+    }
+    Ok(removed)
+}
+
+/// List the dumps stored in the project, most recent first
+pub fn list_dumps(project_path: &str) -> Vec<String> {
+    let mut dumps: Vec<String> = fs::read_dir(Path::new(project_path).join(DB_BACKUP_FOLDER))
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .filter(|name| name.ends_with(".sql.gz"))
+                .collect()
+        })
+        .unwrap_or_default();
+    dumps.sort_by(|a, b| b.cmp(a));
+    dumps
+}
+
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn db(engine: &str) -> DbDumpConfig {
+        DbDumpConfig {
+            engine: engine.to_string(),
+            host: "localhost".to_string(),
+            port: None,
+            database: "boutique".to_string(),
+            username: "o'neil".to_string(),
+            password_key: "db-boutique".to_string(),
+            keep: 3,
+        }
+    }
+
+    #[test]
+    fn test_dump_commands_quote_arguments() {
+        let mysql = dump_command(&db("mysql")).unwrap();
+        assert!(mysql.starts_with("IFS= read -r MYSQL_PWD; export MYSQL_PWD; { mysqldump "));
+        assert!(mysql.contains("-h 'localhost' -P 3306 -u 'o'\\''neil' 'boutique'"));
+        let postgres = dump_command(&DbDumpConfig { port: Some(6432), ..db("postgres") }).unwrap();
+        assert!(postgres.contains("pg_dump --no-owner -h 'localhost' -p 6432 -U 'o'\\''neil' 'boutique'"));
+        assert!(dump_command(&db("sqlite")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_password_reaches_the_tool_through_stdin_only() {
+        use std::os::unix::fs::PermissionsExt;
+        use std::process::{Command, Stdio};
+
+        // Stand-in mysqldump printing what it received
+        let bin = TempDir::new("dump-bin");
+        let tool = bin.path().join("mysqldump");
+        fs::write(&tool, "#!/bin/sh\necho \"password=$MYSQL_PWD\"\necho \"args=$*\"\n").unwrap();
+        fs::set_permissions(&tool, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let command = dump_command(&db("mysql")).unwrap();
+        assert!(!command.contains("s3cr3t"));
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(format!("{} | gzip -dc", command))
+            .env("PATH", format!("{}:{}", bin.path_str(), std::env::var("PATH").unwrap_or_default()))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(b"s3cr3t\n").unwrap();
+        let output = String::from_utf8(child.wait_with_output().unwrap().stdout).unwrap();
+
+        assert!(output.contains("password=s3cr3t\n"));
+        assert!(output.contains("args=--single-transaction --quick --routines -h localhost -P 3306 -u o'neil boutique"));
+    }
+
+    #[test]
+    fn test_rotation_keeps_the_most_recent_dumps_of_the_database() {
+        let dir = TempDir::new("dumps");
+        for day in 1..=5 {
+            fs::write(dir.path().join(format!("boutique-2026030{}-020000.sql.gz", day)), "x").unwrap();
+        }
+        fs::write(dir.path().join("blog-20260301-020000.sql.gz"), "x").unwrap();
+        fs::write(dir.path().join("boutique-notes.txt"), "x").unwrap();
+
+        let removed = rotate_dumps(dir.path(), "boutique", 3).unwrap();
+        let removed: Vec<String> = removed
+            .iter()
+            .map(|p| Path::new(p).file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(removed, vec!["boutique-20260301-020000.sql.gz", "boutique-20260302-020000.sql.gz"]);
+        assert_eq!(list_dumps_in(dir.path()).len(), 4);
+
+        // At least the latest dump is always kept
+        rotate_dumps(dir.path(), "boutique", 0).unwrap();
+        assert!(dir.path().join("boutique-20260305-020000.sql.gz").exists());
+        assert!(!dir.path().join("boutique-20260304-020000.sql.gz").exists());
+        assert!(dir.path().join("blog-20260301-020000.sql.gz").exists());
+    }
+
+    fn list_dumps_in(dir: &Path) -> Vec<String> {
+        fs::read_dir(dir)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .filter(|name| name.ends_with(".sql.gz"))
+            .collect()
+    }
+}
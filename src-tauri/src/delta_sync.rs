@@ -3,6 +3,8 @@
 //! Implements smart delta synchronization that only transfers changed portions of files.
 //! Uses hash-based change detection and chunked comparison for efficient transfers.
 
+use crate::ignore_rules::IgnoreRules;
+use crate::state_file;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
@@ -68,6 +70,18 @@ impl SignatureCache {
     }
 }
 
+/// What local files are compared against
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DeltaBaseline {
+    /// Signatures recorded after the last sync (default)
+    LastSync,
+    /// Files of a version history snapshot
+    Snapshot { snapshot_id: String },
+    /// Deploy manifest currently on the server
+    RemoteManifest,
+}
+
 /// Delta analysis result for a file
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileDelta {
@@ -95,7 +109,7 @@ pub enum DeltaStatus {
 }
 
 /// Compute SHA-256 hash of data
-fn compute_hash(data: &[u8]) -> String {
+pub fn compute_hash(data: &[u8]) -> String {
     let mut hasher = Sha256::new();
     hasher.update(data);
     format!("{:x}", hasher.finalize())
@@ -262,8 +276,41 @@ pub fn analyze_delta_sync(
 
     let mut deltas = Vec::new();
 
-    for entry in walkdir::WalkDir::new(local_path)
+    for (path, relative) in list_syncable_files(base_path) {
+        // Compute delta for this file
+        let cached_sig = cache.get_signature(&relative);
+        match compute_file_delta(&path, &relative, cached_sig) {
+            Ok(delta) => deltas.push(delta),
+            Err(e) => eprintln!("Warning: Failed to analyze {}: {}", relative, e),
+        }
+    }
+
+    // Check for deleted files (in cache but not on disk)
+    for cached_path in cache.signatures.keys() {
+        let full_path = base_path.join(cached_path);
+        if !full_path.exists() {
+            deltas.push(FileDelta {
+                path: cached_path.clone(),
+                status: DeltaStatus::Deleted,
+                total_size: 0,
+                transfer_size: 0,
+                changed_chunks: vec![],
+                savings_percent: 100.0,
+            });
+        }
+    }
+
+    Ok(deltas)
+}
+
+/// Files of the project that a sync considers, with their relative path
+pub fn list_syncable_files(base_path: &Path) -> Vec<(PathBuf, String)> {
+    let mut files = Vec::new();
+    let ignore = IgnoreRules::load(base_path);
+
+    for entry in walkdir::WalkDir::new(base_path)
         .into_iter()
+        .filter_entry(|e| ignore.keeps_entry(base_path, e))
         .filter_map(|e| e.ok())
     {
         let path = entry.path();
@@ -289,30 +336,78 @@ pub fn analyze_delta_sync(
             continue;
         }
 
-        // Compute delta for this file
-        let cached_sig = cache.get_signature(&relative);
-        match compute_file_delta(path, &relative, cached_sig) {
-            Ok(delta) => deltas.push(delta),
-            Err(e) => eprintln!("Warning: Failed to analyze {}: {}", relative, e),
-        }
+        files.push((path.to_path_buf(), relative));
     }
 
-    // Check for deleted files (in cache but not on disk)
-    for cached_path in cache.signatures.keys() {
-        let full_path = base_path.join(cached_path);
-        if !full_path.exists() {
-            deltas.push(FileDelta {
-                path: cached_path.clone(),
-                status: DeltaStatus::Deleted,
-                total_size: 0,
-                transfer_size: 0,
-                changed_chunks: vec![],
-                savings_percent: 100.0,
-            });
+    files
+}
+
+/// Whether a cached signature still describes the file on disk (same size and mtime)
+pub fn signature_is_current(signature: &FileSignature, file_path: &Path) -> bool {
+    let metadata = match fs::metadata(file_path) {
+        Ok(metadata) => metadata,
+        Err(_) => return false,
+    };
+    let modified_at = metadata
+        .modified()
+        .ok()
+        .map(|t| {
+            let datetime: chrono::DateTime<chrono::Utc> = t.into();
+            datetime.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+        })
+        .unwrap_or_default();
+    signature.total_size == metadata.len() && signature.modified_at == modified_at
+}
+
+/// Signature known only by its full hash: any change marks every chunk as changed
+fn hash_only_signature(path: &str, total_size: u64, full_hash: &str, modified_at: &str) -> FileSignature {
+    FileSignature {
+        path: path.to_string(),
+        total_size,
+        full_hash: full_hash.to_string(),
+        chunk_size: CHUNK_SIZE,
+        chunk_hashes: Vec::new(),
+        modified_at: modified_at.to_string(),
+        created_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+    }
+}
+
+/// Build the signature cache representing the chosen baseline
+pub fn load_baseline(
+    app_data_dir: &Path,
+    project_id: &str,
+    baseline: &DeltaBaseline,
+    config: Option<&crate::SFTPConfig>,
+) -> Result<SignatureCache, String> {
+    let mut cache = SignatureCache::new(project_id);
+
+    match baseline {
+        DeltaBaseline::LastSync => return load_cache(app_data_dir, project_id),
+        DeltaBaseline::Snapshot { snapshot_id } => {
+            let history = crate::version_history::load_history(app_data_dir, project_id)?;
+            let snapshot = history
+                .get_snapshot(snapshot_id)
+                .ok_or_else(|| format!("Snapshot not found: {}", snapshot_id))?;
+            for file in &snapshot.files {
+                // The backup copy gives chunk hashes, the snapshot alone only the full hash
+                let signature = file
+                    .backup_path
+                    .as_deref()
+                    .and_then(|backup| generate_file_signature(Path::new(backup), &file.path).ok())
+                    .unwrap_or_else(|| hash_only_signature(&file.path, file.size, &file.hash, &file.modified));
+                cache.update_signature(signature);
+            }
+        }
+        DeltaBaseline::RemoteManifest => {
+            let config = config.ok_or("La configuration du serveur est requise pour comparer au manifeste distant")?;
+            let manifest = crate::deploy_manifest::download_manifest(config)?;
+            for entry in &manifest.files {
+                cache.update_signature(hash_only_signature(&entry.path, entry.size, &entry.hash, &manifest.deployed_at));
+            }
         }
     }
 
-    Ok(deltas)
+    Ok(cache)
 }
 
 /// Extract only the changed chunks from a file for transfer
@@ -412,31 +507,12 @@ pub fn get_cache_path(app_data_dir: &Path, project_id: &str) -> PathBuf {
 pub fn load_cache(app_data_dir: &Path, project_id: &str) -> Result<SignatureCache, String> {
     let path = get_cache_path(app_data_dir, project_id);
 
-    if !path.exists() {
-        return Ok(SignatureCache::new(project_id));
-    }
-
-    let content = fs::read_to_string(&path)
-        .map_err(|e| format!("Failed to read cache file: {}", e))?;
-
-    serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse cache: {}", e))
+    Ok(state_file::read_json(&path)?.unwrap_or_else(|| SignatureCache::new(project_id)))
 }
 
 /// Save signature cache to disk
 pub fn save_cache(app_data_dir: &Path, cache: &SignatureCache) -> Result<(), String> {
-    let path = get_cache_path(app_data_dir, &cache.project_id);
-
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create cache directory: {}", e))?;
-    }
-
-    let content = serde_json::to_string_pretty(cache)
-        .map_err(|e| format!("Failed to serialize cache: {}", e))?;
-
-    fs::write(&path, content)
-        .map_err(|e| format!("Failed to write cache file: {}", e))
+    state_file::write_json(&get_cache_path(app_data_dir, &cache.project_id), cache)
 }
 
 /// Update cache after successful sync
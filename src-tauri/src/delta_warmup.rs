@@ -0,0 +1,182 @@
+//! Delta Warm-up Module
+//!
+//! Background job pre-computing the delta signatures of a whole project,
+//! optionally only while the machine is idle, so the first delta-aware
+//! sync does not start with bulk hashing.
+
+use crate::{delta_sync, idle_monitor};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+/// Seconds without input before an idle-only warm-up hashes files
+const IDLE_THRESHOLD_SECS: u64 = 60;
+
+/// How often an idle-only warm-up checks that the user is still away
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Save the cache and emit progress every N files
+const SAVE_INTERVAL: usize = 50;
+
+/// Cancel flags of the running warm-ups, by project
+static WARMUPS: Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Payload of `delta-cache-warmup`
+#[derive(Debug, Clone, Serialize)]
+pub struct WarmupProgress {
+    pub project_id: String,
+    /// "running", "waiting_idle", "completed", "cancelled" or "error"
+    pub status: String,
+    pub files_done: usize,
+    pub files_total: usize,
+    /// Signatures computed by this run
+    pub hashed: usize,
+    /// Files whose cached signature was still current
+    pub skipped: usize,
+    pub message: Option<String>,
+}
+
+pub fn is_running(project_id: &str) -> bool {
+    WARMUPS.lock().map(|w| w.contains_key(project_id)).unwrap_or(false)
+}
+
+/// Start warming the signature cache of a project in the background
+pub fn start(app: AppHandle, project_id: String, local_path: String, only_when_idle: bool) -> Result<(), String> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let mut warmups = WARMUPS.lock().map_err(|e| e.to_string())?;
+        if warmups.contains_key(&project_id) {
+            return Err("Le prechauffage du cache est deja en cours pour ce projet".to_string());
+        }
+        warmups.insert(project_id.clone(), cancel.clone());
+    }
+
+    thread::spawn(move || {
+        println!("[DeltaWarmup] Started for project: {}", project_id);
+        let result = run(&app, &project_id, &local_path, only_when_idle, &cancel);
+        if let Err(e) = result {
+            println!("[DeltaWarmup] {}", e);
+            let _ = app.emit_all(crate::events::DELTA_CACHE_WARMUP, WarmupProgress {
+                project_id: project_id.clone(),
+                status: "error".to_string(),
+                files_done: 0,
+                files_total: 0,
+                hashed: 0,
+                skipped: 0,
+                message: Some(e),
+            });
+        }
+        if let Ok(mut warmups) = WARMUPS.lock() {
+            warmups.remove(&project_id);
+        }
+    });
+    Ok(())
+}
+
+pub fn cancel(project_id: &str) {
+    if let Ok(warmups) = WARMUPS.lock() {
+        if let Some(flag) = warmups.get(project_id) {
+            flag.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+fn run(app: &AppHandle, project_id: &str, local_path: &str, only_when_idle: bool, cancel: &AtomicBool) -> Result<(), String> {
+    let app_dir = crate::data_location::app_data_dir(app)
+        .ok_or("Could not get app data directory")?;
+    let base_path = Path::new(local_path);
+    if !base_path.exists() {
+        return Err(format!("Local path does not exist: {}", local_path));
+    }
+
+    let mut cache = delta_sync::load_cache(&app_dir, project_id)?;
+    let files = delta_sync::list_syncable_files(base_path);
+    let mut progress = WarmupProgress {
+        project_id: project_id.to_string(),
+        status: "running".to_string(),
+        files_done: 0,
+        files_total: files.len(),
+        hashed: 0,
+        skipped: 0,
+        message: None,
+    };
+    let _ = app.emit_all(crate::events::DELTA_CACHE_WARMUP, &progress);
+
+    let mut idle_checked: Option<Instant> = None;
+    for (path, relative) in &files {
+        if only_when_idle && idle_checked.map(|t| t.elapsed() >= IDLE_CHECK_INTERVAL).unwrap_or(true) {
+            if !wait_for_idle(app, &mut progress, cancel) {
+                break;
+            }
+            idle_checked = Some(Instant::now());
+        }
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
+
+        let current = cache
+            .get_signature(relative)
+            .map(|sig| delta_sync::signature_is_current(sig, path))
+            .unwrap_or(false);
+        if current {
+            progress.skipped += 1;
+        } else {
+            match delta_sync::generate_file_signature(path, relative) {
+                Ok(sig) => {
+                    cache.update_signature(sig);
+                    progress.hashed += 1;
+                }
+                Err(e) => println!("[DeltaWarmup] Failed to hash {}: {}", relative, e),
+            }
+        }
+        progress.files_done += 1;
+
+        if progress.files_done % SAVE_INTERVAL == 0 {
+            delta_sync::save_cache(&app_dir, &cache)?;
+            let _ = app.emit_all(crate::events::DELTA_CACHE_WARMUP, &progress);
+        }
+    }
+
+    delta_sync::save_cache(&app_dir, &cache)?;
+    progress.status = if cancel.load(Ordering::Relaxed) { "cancelled" } else { "completed" }.to_string();
+    println!(
+        "[DeltaWarmup] {} for {}: {} hashed, {} already current",
+        progress.status, project_id, progress.hashed, progress.skipped
+    );
+    let _ = app.emit_all(crate::events::DELTA_CACHE_WARMUP, &progress);
+    Ok(())
+}
+
+/// Block until the user is idle; false when cancelled meanwhile
+fn wait_for_idle(app: &AppHandle, progress: &mut WarmupProgress, cancel: &AtomicBool) -> bool {
+    let mut notified = false;
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return false;
+        }
+        // Idle time unavailable on this system: don't wait forever
+        let idle = match idle_monitor::system_idle_seconds() {
+            Some(idle) => idle,
+            None => return true,
+        };
+        if idle >= IDLE_THRESHOLD_SECS {
+            if notified {
+                progress.status = "running".to_string();
+                let _ = app.emit_all(crate::events::DELTA_CACHE_WARMUP, &*progress);
+            }
+            return true;
+        }
+        if !notified {
+            progress.status = "waiting_idle".to_string();
+            let _ = app.emit_all(crate::events::DELTA_CACHE_WARMUP, &*progress);
+            notified = true;
+        }
+        thread::sleep(IDLE_CHECK_INTERVAL);
+    }
+}
@@ -0,0 +1,241 @@
+//! Deploy Guard Module
+//!
+//! Safety rails on deploy targets. A project marked "production" only syncs
+//! when the confirmation phrase is passed along with the sync, and a
+//! temporary lock ("freeze deploys until Monday") blocks every sync of the
+//! project, manual, scheduled or from the tray, until it expires or is lifted.
+//! Both are checked by `sftp_sync` itself, not only by the UI.
+
+use crate::state_file;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+/// Phrase asked when a production target has none of its own
+pub const DEFAULT_CONFIRMATION_PHRASE: &str = "PRODUCTION";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeployLock {
+    /// UTC end of the freeze
+    pub until: String,
+    pub reason: Option<String>,
+    pub locked_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DeployGuard {
+    pub project_id: String,
+    pub production: bool,
+    pub confirmation_phrase: Option<String>,
+    pub lock: Option<DeployLock>,
+}
+
+/// Event emitted on "deploy-blocked" when a scheduled or tray sync is not started
+#[derive(Debug, Clone, Serialize)]
+pub struct DeployBlockedEvent {
+    pub project_id: String,
+    pub trigger: String,
+    pub message: String,
+}
+
+fn guards_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("deploy_guards.json")
+}
+
+fn now() -> String {
+    chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+fn load_all(app_data_dir: &Path) -> HashMap<String, DeployGuard> {
+    state_file::read_json(&guards_path(app_data_dir))
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+fn update<F>(app_data_dir: &Path, project_id: &str, change: F) -> Result<DeployGuard, String>
+where
+    F: FnOnce(&mut DeployGuard),
+{
+    let mut guards = load_all(app_data_dir);
+    let guard = guards.entry(project_id.to_string()).or_insert_with(|| DeployGuard {
+        project_id: project_id.to_string(),
+        ..Default::default()
+    });
+    change(guard);
+    let guard = guard.clone();
+    // Drop entries back to their defaults
+    guards.retain(|_, g| g.production || g.lock.is_some());
+    state_file::write_json(&guards_path(app_data_dir), &guards)?;
+    Ok(guard)
+}
+
+/// Guard of a project, with an expired lock left out
+pub fn get(app_data_dir: &Path, project_id: &str) -> DeployGuard {
+    let mut guard = load_all(app_data_dir).remove(project_id).unwrap_or_else(|| DeployGuard {
+        project_id: project_id.to_string(),
+        ..Default::default()
+    });
+    let current = now();
+    if guard.lock.as_ref().map(|l| l.until <= current).unwrap_or(false) {
+        guard.lock = None;
+    }
+    guard
+}
+
+pub fn list(app_data_dir: &Path) -> Vec<DeployGuard> {
+    let mut guards: Vec<DeployGuard> = load_all(app_data_dir)
+        .into_keys()
+        .map(|id| get(app_data_dir, &id))
+        .filter(|g| g.production || g.lock.is_some())
+        .collect();
+    guards.sort_by(|a, b| a.project_id.cmp(&b.project_id));
+    guards
+}
+
+/// Mark a project as production (or not), with the phrase to type before a sync
+pub fn set_production(app_data_dir: &Path, project_id: &str, production: bool, phrase: Option<String>) -> Result<DeployGuard, String> {
+    let phrase = phrase.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    update(app_data_dir, project_id, |guard| {
+        guard.production = production;
+        guard.confirmation_phrase = if production { phrase } else { None };
+    })
+}
+
+/// Freeze the deploys of a project until `until` (RFC 3339)
+pub fn lock(app_data_dir: &Path, project_id: &str, until: &str, reason: Option<String>) -> Result<DeployGuard, String> {
+    let until = chrono::DateTime::parse_from_rfc3339(until)
+        .map_err(|e| format!("Date de fin invalide: {}", e))?
+        .with_timezone(&chrono::Utc);
+    if until <= chrono::Utc::now() {
+        return Err("La date de fin du gel est deja passee".to_string());
+    }
+    let lock = DeployLock {
+        until: until.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        reason: reason.filter(|r| !r.trim().is_empty()),
+        locked_at: now(),
+    };
+    println!("[DeployGuard] Deploys of {} frozen until {}", project_id, lock.until);
+    update(app_data_dir, project_id, |guard| guard.lock = Some(lock))
+}
+
+pub fn unlock(app_data_dir: &Path, project_id: &str) -> Result<DeployGuard, String> {
+    println!("[DeployGuard] Deploys of {} unfrozen", project_id);
+    update(app_data_dir, project_id, |guard| guard.lock = None)
+}
+
+/// Error message if the project's deploys are frozen
+pub fn check_lock(app_data_dir: &Path, project_id: &str) -> Result<(), String> {
+    match get(app_data_dir, project_id).lock {
+        Some(lock) => Err(match lock.reason {
+            Some(reason) => format!("Deploiements geles jusqu'au {} ({})", lock.until, reason),
+            None => format!("Deploiements geles jusqu'au {}", lock.until),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Check a sync that writes to the remote: not frozen, and confirmed if production
+pub fn check_sync(app_data_dir: &Path, project_id: &str, confirmation: Option<&str>) -> Result<(), String> {
+    check_lock(app_data_dir, project_id)?;
+    let guard = get(app_data_dir, project_id);
+    if !guard.production {
+        return Ok(());
+    }
+    let phrase = guard.confirmation_phrase.as_deref().unwrap_or(DEFAULT_CONFIRMATION_PHRASE);
+    match confirmation.map(str::trim) {
+        Some(typed) if typed == phrase => Ok(()),
+        Some(_) => Err("Phrase de confirmation incorrecte, synchronisation de production refusee".to_string()),
+        None => Err(format!("Cible de production: tapez \"{}\" pour confirmer la synchronisation", phrase)),
+    }
+}
+
+/// Event to emit when a scheduled or tray sync must not be started
+fn blocked_trigger(app_data_dir: &Path, project_id: &str, trigger: &str) -> Option<DeployBlockedEvent> {
+    check_lock(app_data_dir, project_id).err().map(|message| DeployBlockedEvent {
+        project_id: project_id.to_string(),
+        trigger: trigger.to_string(),
+        message,
+    })
+}
+
+/// Whether a scheduled or tray sync may be started; tells the UI with "deploy-blocked" if not
+pub fn allow_triggered_sync(app_handle: &tauri::AppHandle, project_id: &str, trigger: &str) -> bool {
+    let app_dir = match crate::data_location::app_data_dir(app_handle) {
+        Some(dir) => dir,
+        None => return true,
+    };
+    match blocked_trigger(&app_dir, project_id, trigger) {
+        None => true,
+        Some(event) => {
+            println!("[DeployGuard] {} sync of {} skipped: {}", trigger, project_id, event.message);
+            let _ = app_handle.emit_all(crate::events::DEPLOY_BLOCKED, event);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_production_sync_needs_the_phrase() {
+        let dir = TempDir::new("deploy-guard-phrase");
+        set_production(dir.path(), "shop", true, None).unwrap();
+        assert!(check_sync(dir.path(), "shop", None).unwrap_err().contains("\"PRODUCTION\""));
+        assert!(check_sync(dir.path(), "shop", Some("production")).unwrap_err().contains("incorrecte"));
+        assert!(check_sync(dir.path(), "shop", Some(" PRODUCTION ")).is_ok());
+
+        set_production(dir.path(), "shop", true, Some("  deploy shop ".to_string())).unwrap();
+        assert!(check_sync(dir.path(), "shop", Some("PRODUCTION")).is_err());
+        assert!(check_sync(dir.path(), "shop", Some("deploy shop")).is_ok());
+
+        // Other projects and unmarked ones sync without a phrase
+        assert!(check_sync(dir.path(), "blog", None).is_ok());
+        set_production(dir.path(), "shop", false, None).unwrap();
+        assert!(check_sync(dir.path(), "shop", None).is_ok());
+        assert!(list(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_expired_lock_is_released() {
+        let dir = TempDir::new("deploy-guard-expiry");
+        assert!(lock(dir.path(), "shop", "2000-01-01T00:00:00Z", None).is_err());
+
+        lock(dir.path(), "shop", "2999-01-01T00:00:00Z", Some("release".to_string())).unwrap();
+        let error = check_sync(dir.path(), "shop", None).unwrap_err();
+        assert!(error.contains("2999-01-01T00:00:00Z") && error.contains("release"));
+
+        // The end of the freeze passes while the lock is stored
+        update(dir.path(), "shop", |guard| {
+            guard.lock.as_mut().unwrap().until = "2000-01-01T00:00:00Z".to_string();
+        })
+        .unwrap();
+        assert!(get(dir.path(), "shop").lock.is_none());
+        assert!(check_sync(dir.path(), "shop", None).is_ok());
+        assert!(list(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_freeze_blocks_every_trigger() {
+        let dir = TempDir::new("deploy-guard-triggers");
+        assert!(blocked_trigger(dir.path(), "shop", "scheduled").is_none());
+
+        lock(dir.path(), "shop", "2999-01-01T00:00:00Z", None).unwrap();
+        for trigger in ["scheduled", "tray"] {
+            let event = blocked_trigger(dir.path(), "shop", trigger).unwrap();
+            assert_eq!((event.project_id.as_str(), event.trigger.as_str()), ("shop", trigger));
+            assert!(event.message.starts_with("Deploiements geles"));
+        }
+        // The phrase does not lift a freeze on a manual sync either
+        set_production(dir.path(), "shop", true, None).unwrap();
+        assert!(check_sync(dir.path(), "shop", Some("PRODUCTION")).is_err());
+        assert!(blocked_trigger(dir.path(), "blog", "tray").is_none());
+
+        unlock(dir.path(), "shop").unwrap();
+        assert!(blocked_trigger(dir.path(), "shop", "tray").is_none());
+    }
+}
@@ -0,0 +1,538 @@
+//! Deploy Import Module
+//!
+//! Turns the deployment settings another editor left in a folder into sync
+//! targets the project form can take as is: the vscode-sftp extension's
+//! `.vscode/sftp.json` (one config, a list of them, or `profiles` overriding
+//! a base config) and PhpStorm's `.idea/deployment.xml`, whose servers are
+//! described in `.idea/webServers.xml` and `.idea/sshConfigs.xml` when they
+//! are shared with the project. Local folders become relative to the
+//! imported one and exclusions become `.laforgeignore`-style patterns.
+//! Only sftp.json may hold a password; PhpStorm keeps its own in the IDE.
+
+use crate::ide_monitor::{expand_jetbrains_path, xml_attribute, xml_option_value};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+pub const VSCODE_SFTP_FILE: &str = ".vscode/sftp.json";
+pub const PHPSTORM_DEPLOYMENT_FILE: &str = ".idea/deployment.xml";
+const PHPSTORM_SERVERS_FILE: &str = ".idea/webServers.xml";
+const PHPSTORM_SSH_FILE: &str = ".idea/sshConfigs.xml";
+
+/// A sync target read from an editor config
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedTarget {
+    /// "vscode-sftp" or "phpstorm"
+    pub source: String,
+    /// Relative to the imported folder
+    pub file: String,
+    pub name: Option<String>,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    /// "sftp", "ftp" or "ftps"
+    pub protocol: Option<String>,
+    pub username: Option<String>,
+    /// For the keychain only, never written to project.json
+    pub password: Option<String>,
+    pub private_key_path: Option<String>,
+    pub use_ssh_agent: bool,
+    pub passive: Option<bool>,
+    pub remote_path: Option<String>,
+    /// Folder uploaded, relative to the imported one ("." for the folder itself)
+    pub local_path: String,
+    /// Gitignore-style patterns, relative to local_path
+    pub ignore: Vec<String>,
+    /// Public URL of the server (PhpStorm)
+    pub site_url: Option<String>,
+    /// The target the editor uploads to by default
+    pub is_default: bool,
+    /// Settings that could not be carried over
+    pub warnings: Vec<String>,
+}
+
+// ============================================
+// Editor JSON helpers
+// ============================================
+
+/// JSON as editors write it: `//` comment lines and trailing commas allowed
+pub fn relaxed_json(text: &str) -> Option<serde_json::Value> {
+    let without_comments: String = text
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("//"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut cleaned = String::with_capacity(without_comments.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let chars: Vec<char> = without_comments.chars().collect();
+    for (index, &c) in chars.iter().enumerate() {
+        if in_string {
+            in_string = c != '"' || escaped;
+            escaped = c == '\\' && !escaped;
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' && chars[index + 1..].iter().find(|c| !c.is_whitespace()).map(|c| *c == '}' || *c == ']').unwrap_or(false) {
+            continue;
+        }
+        cleaned.push(c);
+    }
+    serde_json::from_str(&cleaned).ok()
+}
+
+pub(crate) fn text(value: &serde_json::Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|key| value.get(key).and_then(|v| v.as_str()))
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+pub(crate) fn port(value: &serde_json::Value) -> Option<u16> {
+    let port = value.get("port")?;
+    port.as_u64().and_then(|p| u16::try_from(p).ok()).or_else(|| port.as_str()?.trim().parse().ok())
+}
+
+pub(crate) fn protocol(value: Option<String>) -> Option<String> {
+    match value?.to_lowercase().as_str() {
+        "sftp" | "scp" | "ssh" => Some("sftp".to_string()),
+        "ftps" | "ftpes" => Some("ftps".to_string()),
+        "ftp" => Some("ftp".to_string()),
+        _ => None,
+    }
+}
+
+pub(crate) fn strings(value: &serde_json::Value, key: &str) -> Vec<String> {
+    value
+        .get(key)
+        .and_then(|v| v.as_array())
+        .map(|items| items.iter().filter_map(|i| i.as_str()).map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// `~/.ssh/id_rsa` with the home folder expanded
+fn expand_home(path: String) -> String {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).to_string_lossy().to_string(),
+        _ => path,
+    }
+}
+
+/// `www`, `./www/` or an absolute path inside `root` as `www`; None outside `root`
+fn relative_local(root: &Path, path: &str) -> Option<String> {
+    let path = path.replace('\\', "/");
+    let relative = if Path::new(&path).is_absolute() {
+        Path::new(&path).strip_prefix(root).ok()?.to_string_lossy().replace('\\', "/")
+    } else {
+        path
+    };
+    let relative = relative.trim_start_matches("./").trim_matches('/');
+    if relative.starts_with("..") {
+        return None;
+    }
+    Some(if relative.is_empty() || relative == "." { ".".to_string() } else { relative.to_string() })
+}
+
+// ============================================
+// VS Code (vscode-sftp)
+// ============================================
+
+fn vscode_target(root: &Path, config: &serde_json::Value) -> ImportedTarget {
+    let mut warnings = Vec::new();
+    // `secure` is true, "control" or "implicit" for FTP over TLS
+    let secure = match config.get("secure") {
+        Some(serde_json::Value::Bool(secure)) => *secure,
+        Some(serde_json::Value::String(_)) => true,
+        _ => false,
+    };
+    let protocol = match protocol(text(config, &["protocol"])).unwrap_or_else(|| "sftp".to_string()) {
+        ftp if ftp == "ftp" && secure => "ftps".to_string(),
+        other => other,
+    };
+    let local_path = match text(config, &["context"]) {
+        Some(context) => relative_local(root, &context).unwrap_or_else(|| {
+            warnings.push(format!("Dossier local hors du projet ignore: {}", context));
+            ".".to_string()
+        }),
+        None => ".".to_string(),
+    };
+    ImportedTarget {
+        source: "vscode-sftp".to_string(),
+        file: VSCODE_SFTP_FILE.to_string(),
+        name: text(config, &["name"]),
+        host: text(config, &["host"]),
+        port: port(config),
+        protocol: Some(protocol),
+        username: text(config, &["username"]),
+        password: text(config, &["password"]),
+        private_key_path: text(config, &["privateKeyPath"]).map(expand_home),
+        use_ssh_agent: text(config, &["agent"]).is_some(),
+        passive: None,
+        remote_path: text(config, &["remotePath"]),
+        local_path,
+        ignore: strings(config, "ignore"),
+        warnings,
+        ..Default::default()
+    }
+}
+
+/// Targets of `.vscode/sftp.json`, one per profile when it has some
+pub fn vscode_sftp(root: &Path) -> Vec<ImportedTarget> {
+    let value = match fs::read_to_string(root.join(VSCODE_SFTP_FILE)).ok().and_then(|content| relaxed_json(&content)) {
+        Some(value) => value,
+        None => return Vec::new(),
+    };
+    let configs = match value {
+        serde_json::Value::Array(configs) => configs,
+        config => vec![config],
+    };
+    let mut targets = Vec::new();
+    for config in configs {
+        let profiles = config.get("profiles").and_then(|p| p.as_object()).cloned().unwrap_or_default();
+        if profiles.is_empty() {
+            targets.push(vscode_target(root, &config));
+            continue;
+        }
+        let default_profile = text(&config, &["defaultProfile"]);
+        for (profile, overrides) in profiles {
+            let mut merged = config.clone();
+            if let (Some(base), Some(overrides)) = (merged.as_object_mut(), overrides.as_object()) {
+                base.remove("profiles");
+                base.extend(overrides.clone());
+            }
+            let mut target = vscode_target(root, &merged);
+            target.name = Some(match target.name.take() {
+                Some(name) => format!("{} ({})", name, profile),
+                None => profile.clone(),
+            });
+            target.is_default = default_profile.as_deref() == Some(profile.as_str());
+            targets.push(target);
+        }
+    }
+    targets
+}
+
+// ============================================
+// PhpStorm
+// ============================================
+
+/// `<tag ...>` elements in order, as (opening tag, content); content is empty for `<tag ... />`
+fn xml_elements<'a>(xml: &'a str, tag: &str) -> Vec<(&'a str, &'a str)> {
+    let open = format!("<{}", tag);
+    let close = format!("</{}>", tag);
+    let mut elements = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        // `<paths` must not match `<pathsMapping`
+        if !rest.starts_with(|c: char| c.is_whitespace() || c == '>' || c == '/') {
+            continue;
+        }
+        let head_end = match rest.find('>') {
+            Some(end) => end,
+            None => break,
+        };
+        let head = &rest[..head_end];
+        rest = &rest[head_end + 1..];
+        if head.ends_with('/') {
+            elements.push((head, ""));
+        } else {
+            let content_end = rest.find(&close).unwrap_or(rest.len());
+            elements.push((head, &rest[..content_end]));
+            rest = &rest[content_end..];
+        }
+    }
+    elements
+}
+
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    xml_attribute(tag, name)
+        .map(|value| {
+            value
+                .replace("&quot;", "\"")
+                .replace("&apos;", "'")
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&amp;", "&")
+        })
+        .filter(|value| !value.is_empty())
+}
+
+/// `$PROJECT_DIR$/www` as `www`; None for folders outside the project
+fn project_relative(path: &str) -> Option<String> {
+    let relative = path.strip_prefix("$PROJECT_DIR$")?.trim_matches('/');
+    Some(if relative.is_empty() { ".".to_string() } else { relative.to_string() })
+}
+
+/// `path` as an anchored pattern when it lies inside `base`
+fn anchored_inside(path: &str, base: &str) -> Option<String> {
+    let path = path.trim_end_matches('/');
+    let rest = match base.trim_matches('/') {
+        "" | "." => path.trim_start_matches('/'),
+        base => path.trim_start_matches('/').strip_prefix(base)?.strip_prefix('/')?,
+    };
+    (!rest.is_empty()).then(|| format!("/{}", rest))
+}
+
+/// Server root folder and mapping path joined
+fn remote_path(root_folder: Option<String>, deploy: Option<&str>) -> Option<String> {
+    match (root_folder, deploy.map(|d| d.trim_matches('/')).filter(|d| !d.is_empty())) {
+        (Some(root), Some(deploy)) => Some(format!("{}/{}", root.trim_end_matches('/'), deploy)),
+        (Some(root), None) => Some(root),
+        (None, Some(deploy)) => Some(format!("/{}", deploy)),
+        (None, None) => None,
+    }
+}
+
+/// Fill the connection of `target` from the `<webServer>` it is named after
+fn apply_web_server(target: &mut ImportedTarget, servers: &str, ssh_configs: &str, name: &str) -> Option<String> {
+    let (server, server_content) = match xml_elements(servers, "webServer")
+        .into_iter()
+        .find(|(tag, _)| attribute(tag, "name").as_deref() == Some(name))
+    {
+        Some(server) => server,
+        None => {
+            target.warnings.push(format!("Serveur \"{}\" defini au niveau de l'IDE: hote et identifiants a completer", name));
+            return None;
+        }
+    };
+    target.site_url = attribute(server, "url");
+    let (transfer, transfer_content) = match xml_elements(server_content, "fileTransfer").into_iter().next() {
+        Some(transfer) => transfer,
+        None => {
+            target.warnings.push(format!("Serveur \"{}\" sans transfert de fichiers", name));
+            return None;
+        }
+    };
+    target.protocol = protocol(attribute(transfer, "accessType"));
+    if target.protocol.is_none() {
+        target.warnings.push(format!(
+            "Type de transfert non pris en charge: {}",
+            attribute(transfer, "accessType").unwrap_or_default()
+        ));
+    }
+    target.host = attribute(transfer, "host");
+    target.port = attribute(transfer, "port")
+        .or_else(|| xml_option_value(transfer_content, "port"))
+        .and_then(|p| p.parse().ok());
+    target.username = attribute(transfer, "username").or_else(|| attribute(transfer, "login"));
+    target.passive = attribute(transfer, "passive")
+        .or_else(|| attribute(transfer_content, "passiveMode"))
+        .map(|passive| passive == "true");
+
+    if let Some(ssh_id) = attribute(transfer, "sshConfigId") {
+        match xml_elements(ssh_configs, "sshConfig")
+            .into_iter()
+            .find(|(tag, _)| attribute(tag, "id").as_deref() == Some(ssh_id.as_str()))
+        {
+            Some((ssh, _)) => {
+                target.host = target.host.take().or_else(|| attribute(ssh, "host"));
+                target.port = target.port.or_else(|| attribute(ssh, "port").and_then(|p| p.parse().ok()));
+                target.username = attribute(ssh, "username").or(target.username.take());
+                match attribute(ssh, "authType").as_deref() {
+                    Some("KEY_PAIR") => {
+                        let home = dirs::home_dir().map(|h| h.to_string_lossy().to_string()).unwrap_or_default();
+                        target.private_key_path = attribute(ssh, "keyPath").map(|key| expand_jetbrains_path(&key, &home));
+                    }
+                    Some("OPEN_SSH") => target.use_ssh_agent = true,
+                    _ => {}
+                }
+            }
+            None => {
+                // IDE-level SSH config, only summarized as "user@host:port auth"
+                target.username = target.username.take().or_else(|| {
+                    attribute(transfer, "sshConfig").and_then(|summary| summary.split_once('@').map(|(user, _)| user.to_string()))
+                });
+                target.warnings.push("Configuration SSH definie au niveau de l'IDE: authentification a verifier".to_string());
+            }
+        }
+    }
+    attribute(transfer, "rootFolder")
+}
+
+/// Targets of `.idea/deployment.xml`, one per server
+pub fn phpstorm(root: &Path) -> Vec<ImportedTarget> {
+    let deployment = match fs::read_to_string(root.join(PHPSTORM_DEPLOYMENT_FILE)) {
+        Ok(deployment) => deployment,
+        Err(_) => return Vec::new(),
+    };
+    let servers = fs::read_to_string(root.join(PHPSTORM_SERVERS_FILE)).unwrap_or_default();
+    let ssh_configs = fs::read_to_string(root.join(PHPSTORM_SSH_FILE)).unwrap_or_default();
+    let default_server = xml_elements(&deployment, "component")
+        .into_iter()
+        .find(|(tag, _)| attribute(tag, "name").as_deref() == Some("PublishConfigData"))
+        .and_then(|(tag, _)| attribute(tag, "serverName"));
+
+    let mut targets = Vec::new();
+    for (paths, content) in xml_elements(&deployment, "paths") {
+        let name = match attribute(paths, "name") {
+            Some(name) => name,
+            None => continue,
+        };
+        let mut target = ImportedTarget {
+            source: "phpstorm".to_string(),
+            file: PHPSTORM_DEPLOYMENT_FILE.to_string(),
+            name: Some(name.clone()),
+            local_path: ".".to_string(),
+            is_default: default_server.as_deref() == Some(name.as_str()),
+            ..Default::default()
+        };
+
+        // The first mapping is the site, later ones are extra folders
+        let mappings = xml_elements(content, "mapping");
+        let deploy = mappings.first().and_then(|(tag, _)| attribute(tag, "deploy"));
+        if let Some(local) = mappings.first().and_then(|(tag, _)| attribute(tag, "local")) {
+            match project_relative(&local) {
+                Some(local_path) => target.local_path = local_path,
+                None => target.warnings.push(format!("Dossier local hors du projet ignore: {}", local)),
+            }
+        }
+        if mappings.len() > 1 {
+            target.warnings.push(format!("{} correspondances de dossiers: seule la premiere est importee", mappings.len()));
+        }
+
+        let root_folder = apply_web_server(&mut target, &servers, &ssh_configs, &name);
+        target.remote_path = remote_path(root_folder, deploy.as_deref());
+
+        // Local exclusions are project paths, remote ones are relative to the server root
+        for (excluded, _) in xml_elements(content, "excludedPath") {
+            let path = match attribute(excluded, "path") {
+                Some(path) => path,
+                None => continue,
+            };
+            let pattern = if attribute(excluded, "local").as_deref() == Some("true") {
+                project_relative(&path).and_then(|p| anchored_inside(&p, &target.local_path))
+            } else {
+                anchored_inside(&path, deploy.as_deref().unwrap_or(""))
+            };
+            match pattern {
+                Some(pattern) => target.ignore.push(pattern),
+                None => target.warnings.push(format!("Exclusion hors du dossier synchronise ignoree: {}", path)),
+            }
+        }
+        targets.push(target);
+    }
+    targets
+}
+
+/// Targets of every editor config found in `path`, the editors' defaults first
+pub fn import(path: &str) -> Result<Vec<ImportedTarget>, String> {
+    let root = Path::new(path);
+    if !root.is_dir() {
+        return Err(format!("Dossier introuvable: {}", path));
+    }
+    let mut targets = vscode_sftp(root);
+    targets.extend(phpstorm(root));
+    targets.sort_by_key(|t| !t.is_default);
+    println!("[DeployImport] {}: {} target(s) found", path, targets.len());
+    Ok(targets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_import_vscode_profiles_and_phpstorm_server() {
+        let dir = TempDir::new("deploy-import");
+        let root = dir.path();
+        for (file, content) in [
+            (
+                VSCODE_SFTP_FILE,
+                r#"{
+                    "host": "example.com",
+                    "username": "deploy",
+                    "protocol": "ftp",
+                    "secure": true,
+                    "context": "./www/",
+                    "ignore": ["/cache"],
+                    "defaultProfile": "prod",
+                    "profiles": {
+                        "prod": { "remotePath": "/var/www/site", "password": "s3cret" },
+                        "staging": { "host": "staging.example.com", "remotePath": "/var/www/staging", },
+                    },
+                }"#,
+            ),
+            (
+                PHPSTORM_DEPLOYMENT_FILE,
+                r#"<project version="4">
+  <component name="PublishConfigData" serverName="prod" remoteFilesAllowedToDisappearOnAutoupload="false">
+    <serverData>
+      <paths name="prod">
+        <serverdata>
+          <mappings>
+            <mapping deploy="/site" local="$PROJECT_DIR$/www" web="/" />
+          </mappings>
+          <excludedPaths>
+            <excludedPath local="true" path="$PROJECT_DIR$/www/wp-content/cache" />
+            <excludedPath path="/site/uploads" />
+          </excludedPaths>
+        </serverdata>
+      </paths>
+      <paths name="ide-level">
+        <serverdata>
+          <mappings>
+            <mapping deploy="/" local="$PROJECT_DIR$" />
+          </mappings>
+        </serverdata>
+      </paths>
+    </serverData>
+  </component>
+</project>"#,
+            ),
+            (
+                PHPSTORM_SERVERS_FILE,
+                r#"<project version="4">
+  <component name="WebServers">
+    <option name="servers">
+      <webServer id="1" name="prod" url="https://example.com">
+        <fileTransfer accessType="SFTP" host="example.com" port="2222" sshConfigId="abc" sshConfig="deploy@example.com:2222 agent" rootFolder="/var/www">
+          <advancedOptions>
+            <advancedOptions dataProtectionLevel="Private" passiveMode="true" shareSSLContext="true" />
+          </advancedOptions>
+        </fileTransfer>
+      </webServer>
+    </option>
+  </component>
+</project>"#,
+            ),
+            (
+                PHPSTORM_SSH_FILE,
+                r#"<project version="4">
+  <component name="SshConfigs">
+    <configs>
+      <sshConfig authType="OPEN_SSH" host="example.com" id="abc" port="2222" nameFormat="DESCRIPTIVE" username="deploy" />
+    </configs>
+  </component>
+</project>"#,
+            ),
+        ] {
+            fs::create_dir_all(root.join(file).parent().unwrap()).unwrap();
+            fs::write(root.join(file), content).unwrap();
+        }
+
+        let targets = import(&root.to_string_lossy()).unwrap();
+        let names: Vec<_> = targets.iter().map(|t| t.name.as_deref().unwrap_or("")).collect();
+        assert_eq!(names, vec!["prod", "prod", "staging", "ide-level"]);
+
+        let vscode = &targets[0];
+        assert_eq!(vscode.source, "vscode-sftp");
+        assert_eq!(vscode.protocol.as_deref(), Some("ftps"));
+        assert_eq!((vscode.local_path.as_str(), vscode.remote_path.as_deref()), ("www", Some("/var/www/site")));
+        assert_eq!(vscode.password.as_deref(), Some("s3cret"));
+        assert_eq!(targets[2].host.as_deref(), Some("staging.example.com"));
+        assert_eq!(targets[2].password, None);
+
+        let phpstorm = &targets[1];
+        assert_eq!(phpstorm.source, "phpstorm");
+        assert_eq!((phpstorm.host.as_deref(), phpstorm.port, phpstorm.protocol.as_deref()), (Some("example.com"), Some(2222), Some("sftp")));
+        assert_eq!((phpstorm.username.as_deref(), phpstorm.use_ssh_agent, phpstorm.passive), (Some("deploy"), true, Some(true)));
+        assert_eq!((phpstorm.local_path.as_str(), phpstorm.remote_path.as_deref()), ("www", Some("/var/www/site")));
+        assert_eq!(phpstorm.ignore, vec!["/wp-content/cache", "/uploads"]);
+        assert!(phpstorm.warnings.is_empty());
+
+        let ide_level = &targets[3];
+        assert_eq!((ide_level.host.as_deref(), ide_level.remote_path.as_deref()), (None, None));
+        assert_eq!(ide_level.warnings.len(), 1);
+    }
+}
@@ -0,0 +1,230 @@
+//! Deploy Manifest Module
+//!
+//! Generates an integrity manifest (file list + hashes + snapshot id)
+//! uploaded alongside the site as `.laforge-manifest.json`, and verifies
+//! a remote tree against the manifest of the last deploy.
+
+use crate::webdav::WebDavClient;
+use crate::{proxy, RemoteFile, SFTPConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+/// Name of the manifest file at the remote root
+pub const MANIFEST_FILE_NAME: &str = ".laforge-manifest.json";
+
+/// Single file entry in a deploy manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub size: u64,
+    pub hash: String,
+}
+
+/// Authoritative record of what a deploy contained
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeployManifest {
+    pub version: u32,
+    pub project_id: String,
+    pub deployed_at: String,
+    pub snapshot_id: Option<String>,
+    pub files_count: usize,
+    pub total_size: u64,
+    pub files: Vec<ManifestEntry>,
+}
+
+/// Result of comparing the remote tree against a manifest
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestVerification {
+    pub deployed_at: String,
+    pub snapshot_id: Option<String>,
+    pub verified: usize,
+    pub missing: Vec<String>,
+    pub size_mismatch: Vec<String>,
+    pub unexpected: Vec<String>,
+    pub is_valid: bool,
+}
+
+/// Build a manifest from the local files that were deployed
+pub fn build_manifest(
+    project_id: &str,
+    local_path: &str,
+    local_files: &HashMap<String, u64>,
+    snapshot_id: Option<&str>,
+) -> Result<DeployManifest, String> {
+    let base = Path::new(local_path);
+    let mut files = Vec::with_capacity(local_files.len());
+
+    for (path, size) in local_files {
+        let hash = crate::version_history::compute_file_hash(&base.join(path))?;
+        files.push(ManifestEntry {
+            path: path.clone(),
+            size: *size,
+            hash,
+        });
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(DeployManifest {
+        version: 1,
+        project_id: project_id.to_string(),
+        deployed_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        snapshot_id: snapshot_id.map(String::from),
+        files_count: files.len(),
+        total_size: files.iter().map(|f| f.size).sum(),
+        files,
+    })
+}
+
+/// Upload the manifest to the remote root
+pub fn upload_manifest(config: &SFTPConfig, manifest: &DeployManifest) -> Result<(), String> {
+    let content = serde_json::to_vec_pretty(manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    let remote_file = format!("{}/{}", config.remote_path, MANIFEST_FILE_NAME);
+
+    match config.protocol.as_deref().unwrap_or("ftp") {
+        "sftp" => {
+            let sess = connect_sftp(config)?;
+            let sftp = sess.sftp().map_err(|e| format!("SFTP error: {}", e))?;
+            let mut remote = sftp
+                .create(Path::new(&remote_file))
+                .map_err(|e| format!("Failed to create {}: {}", remote_file, e))?;
+            remote
+                .write_all(&content)
+                .map_err(|e| format!("Failed to write {}: {}", remote_file, e))?;
+            Ok(())
+        }
+        "ftp" | "ftps" => {
+            let mut ftp = connect_ftp(config)?;
+            let mut cursor = std::io::Cursor::new(content);
+            let result = ftp
+                .put_file(&remote_file, &mut cursor)
+                .map(|_| ())
+                .map_err(|e| format!("Failed to upload {}: {}", remote_file, e));
+            let _ = ftp.quit();
+            result
+        }
+        "webdav" => WebDavClient::connect(config)?.upload(&remote_file, content, |_| {}),
+        other => Err(format!("Unknown protocol: {}", other)),
+    }
+}
+
+/// Download the manifest of the last deploy from the remote root
+pub fn download_manifest(config: &SFTPConfig) -> Result<DeployManifest, String> {
+    let remote_file = format!("{}/{}", config.remote_path, MANIFEST_FILE_NAME);
+
+    let content = match config.protocol.as_deref().unwrap_or("ftp") {
+        "sftp" => {
+            let sess = connect_sftp(config)?;
+            let sftp = sess.sftp().map_err(|e| format!("SFTP error: {}", e))?;
+            let mut remote = sftp
+                .open(Path::new(&remote_file))
+                .map_err(|e| format!("Manifest not found ({}): {}", remote_file, e))?;
+            let mut buffer = Vec::new();
+            remote
+                .read_to_end(&mut buffer)
+                .map_err(|e| format!("Failed to read manifest: {}", e))?;
+            buffer
+        }
+        "ftp" | "ftps" => {
+            let mut ftp = connect_ftp(config)?;
+            let result = ftp
+                .retr_as_buffer(&remote_file)
+                .map(|cursor| cursor.into_inner())
+                .map_err(|e| format!("Manifest not found ({}): {}", remote_file, e));
+            let _ = ftp.quit();
+            result?
+        }
+        "webdav" => {
+            let mut buffer = Vec::new();
+            WebDavClient::connect(config)?
+                .download(&remote_file, &mut buffer)
+                .map_err(|e| format!("Manifest not found ({}): {}", remote_file, e))?;
+            buffer
+        }
+        other => return Err(format!("Unknown protocol: {}", other)),
+    };
+
+    serde_json::from_slice(&content).map_err(|e| format!("Failed to parse manifest: {}", e))
+}
+
+/// Compare a remote scan against the manifest
+pub fn verify_against_remote(
+    manifest: &DeployManifest,
+    remote_files: &HashMap<String, RemoteFile>,
+) -> ManifestVerification {
+    let mut verified = 0;
+    let mut missing = Vec::new();
+    let mut size_mismatch = Vec::new();
+
+    for entry in &manifest.files {
+        match remote_files.get(&entry.path) {
+            Some(remote) if remote.size == entry.size => verified += 1,
+            Some(_) => size_mismatch.push(entry.path.clone()),
+            None => missing.push(entry.path.clone()),
+        }
+    }
+
+    let mut unexpected: Vec<String> = remote_files
+        .keys()
+        .filter(|path| !manifest.files.iter().any(|f| &f.path == *path))
+        .cloned()
+        .collect();
+    unexpected.sort();
+
+    ManifestVerification {
+        deployed_at: manifest.deployed_at.clone(),
+        snapshot_id: manifest.snapshot_id.clone(),
+        verified,
+        is_valid: missing.is_empty() && size_mismatch.is_empty(),
+        missing,
+        size_mismatch,
+        unexpected,
+    }
+}
+
+pub fn connect_sftp(config: &SFTPConfig) -> Result<ssh2::Session, String> {
+    let tcp = proxy::connect(config.proxy.as_ref(), &config.host, config.port, Duration::from_secs(10))?;
+    tcp.set_read_timeout(Some(Duration::from_secs(60)))
+        .map_err(|e| format!("Failed to set read timeout: {}", e))?;
+
+    let mut sess = ssh2::Session::new().map_err(|e| format!("Session error: {}", e))?;
+    sess.set_tcp_stream(tcp);
+    sess.handshake().map_err(|e| format!("Handshake failed: {}", e))?;
+    crate::ssh_auth::authenticate(&sess, config)?;
+    Ok(sess)
+}
+
+pub fn connect_ftp(config: &SFTPConfig) -> Result<suppaftp::FtpStream, String> {
+    let mut ftp = proxy::connect_ftp(config.proxy.as_ref(), &config.host, config.port, Duration::from_secs(10))?;
+    ftp.login(&config.username, &config.password)
+        .map_err(|e| format!("FTP login failed: {}", e))?;
+    if config.passive.unwrap_or(true) {
+        ftp.set_mode(suppaftp::Mode::Passive);
+    }
+    ftp.transfer_type(suppaftp::types::FileType::Binary)
+        .map_err(|e| format!("Failed to set binary mode: {}", e))?;
+    Ok(ftp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{sftp_config, HttpFixture};
+
+    #[test]
+    fn test_download_manifest_over_webdav() {
+        let manifest = r#"{"version":1,"project_id":"site","deployed_at":"2026-03-02T10:00:00Z","snapshot_id":null,"files_count":1,"total_size":6,"files":[{"path":"index.html","size":6,"hash":"abc"}]}"#;
+        let server = HttpFixture::serve(&[("/dav/www/.laforge-manifest.json", "application/json", manifest)]);
+        let config = sftp_config("webdav", &server.url("/dav"), 0, "/www");
+
+        let downloaded = download_manifest(&config).unwrap();
+        assert_eq!(downloaded.deployed_at, "2026-03-02T10:00:00Z");
+        assert_eq!(downloaded.files[0].path, "index.html");
+
+        let missing = SFTPConfig { remote_path: "/ailleurs".to_string(), ..config };
+        assert!(download_manifest(&missing).unwrap_err().starts_with("Manifest not found"));
+    }
+}
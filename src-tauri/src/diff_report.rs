@@ -0,0 +1,220 @@
+//! Diff Report Module
+//!
+//! Writes the result of a dry run (`sftp_get_diff`) as a report to share
+//! with the client before deploying: the files to add, the ones modified
+//! with their old and new sizes, the remote-only ones mirror mode would
+//! delete, the ones too large to send, and the totals. Markdown to read,
+//! CSV for a spreadsheet, JSON for tools. Reports go to REPORT_DIR in the
+//! project folder, one file per export.
+
+use crate::file_type_stats;
+use crate::inventory_export::csv_field;
+use crate::sync_history::SyncTarget;
+use crate::transfer_quota::format_bytes;
+use crate::FileDiff;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+/// Relative to the project folder
+pub const REPORT_DIR: &str = "Documentation/deploiement";
+
+/// Figures of an exported report
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffReport {
+    pub path: String,
+    /// "markdown", "csv" or "json"
+    pub format: String,
+    pub added: usize,
+    pub modified: usize,
+    /// Remote-only files, deleted by mirror mode
+    pub deleted: usize,
+    pub oversized: usize,
+    pub unchanged: usize,
+    pub upload_bytes: u64,
+    pub delete_bytes: u64,
+}
+
+fn totals(diffs: &[FileDiff]) -> DiffReport {
+    let mut report = DiffReport::default();
+    for diff in diffs {
+        match diff.status.as_str() {
+            "added" => report.added += 1,
+            "modified" => report.modified += 1,
+            "deleted" => {
+                report.deleted += 1;
+                report.delete_bytes += diff.remote_size.unwrap_or(0);
+            }
+            "oversized" => report.oversized += 1,
+            _ => report.unchanged += 1,
+        }
+        if diff.status == "added" || diff.status == "modified" {
+            report.upload_bytes += diff.local_size.unwrap_or(0);
+        }
+    }
+    report
+}
+
+fn size(bytes: Option<u64>) -> String {
+    bytes.map(format_bytes).unwrap_or_else(|| "-".to_string())
+}
+
+/// `|` would end a table cell
+fn cell(path: &str) -> String {
+    format!("`{}`", path.replace('|', "\\|"))
+}
+
+fn build_markdown(diffs: &[FileDiff], totals: &DiffReport, project_name: &str, target: Option<&SyncTarget>, generated_at: &str) -> String {
+    let mut md = format!("# Rapport de deploiement - {}\n\n", project_name);
+    md.push_str(&format!("Genere le {}", generated_at));
+    if let Some(target) = target {
+        md.push_str(&format!(", vers {}://{}:{}{}", target.protocol, target.host, target.port, target.remote_path));
+    }
+    md.push_str("\n\n## Resume\n\n| | Fichiers | Taille |\n|---|---:|---:|\n");
+    md.push_str(&format!("| Nouveaux | {} | |\n", totals.added));
+    md.push_str(&format!("| Modifies | {} | |\n", totals.modified));
+    md.push_str(&format!("| **A envoyer** | **{}** | **{}** |\n", totals.added + totals.modified, format_bytes(totals.upload_bytes)));
+    md.push_str(&format!("| Uniquement sur le serveur | {} | {} |\n", totals.deleted, format_bytes(totals.delete_bytes)));
+    if totals.oversized > 0 {
+        md.push_str(&format!("| Trop volumineux (non envoyes) | {} | |\n", totals.oversized));
+    }
+    md.push_str(&format!("| Inchanges | {} | |\n", totals.unchanged));
+
+    let categories = file_type_stats::compute(diffs).by_category;
+    if !categories.is_empty() {
+        md.push_str("\n## Types de fichiers envoyes\n\n| Type | Fichiers | Taille |\n|---|---:|---:|\n");
+        for share in &categories {
+            md.push_str(&format!("| {} | {} | {} |\n", share.key, share.files, format_bytes(share.bytes)));
+        }
+    }
+
+    let section = |md: &mut String, title: &str, status: &str, with_remote: bool| {
+        let files: Vec<&FileDiff> = diffs.iter().filter(|d| d.status == status).collect();
+        if files.is_empty() {
+            return;
+        }
+        md.push_str(&format!("\n## {} ({})\n\n", title, files.len()));
+        if with_remote {
+            md.push_str("| Fichier | Taille locale | Taille serveur |\n|---|---:|---:|\n");
+            for diff in files {
+                md.push_str(&format!("| {} | {} | {} |\n", cell(&diff.path), size(diff.local_size), size(diff.remote_size)));
+            }
+        } else {
+            md.push_str("| Fichier | Taille |\n|---|---:|\n");
+            for diff in files {
+                md.push_str(&format!("| {} | {} |\n", cell(&diff.path), size(diff.local_size.or(diff.remote_size))));
+            }
+        }
+    };
+    section(&mut md, "Nouveaux fichiers", "added", false);
+    section(&mut md, "Fichiers modifies", "modified", true);
+    section(&mut md, "Fichiers uniquement sur le serveur (supprimes en mode miroir)", "deleted", false);
+    section(&mut md, "Fichiers trop volumineux, non envoyes", "oversized", false);
+    md
+}
+
+/// UTF-8 with a BOM so Excel reads the accents right; unchanged files left out
+fn build_csv(diffs: &[FileDiff]) -> String {
+    let mut csv = String::from("\u{feff}Statut,Fichier,Taille locale,Taille serveur\r\n");
+    for diff in diffs.iter().filter(|d| d.status != "unchanged") {
+        csv.push_str(&format!(
+            "{},{},{},{}\r\n",
+            diff.status,
+            csv_field(&diff.path),
+            diff.local_size.map(|s| s.to_string()).unwrap_or_default(),
+            diff.remote_size.map(|s| s.to_string()).unwrap_or_default()
+        ));
+    }
+    csv
+}
+
+fn build_json(diffs: &[FileDiff], totals: &DiffReport, project_name: &str, target: Option<&SyncTarget>, generated_at: &str) -> Result<String, String> {
+    let report = serde_json::json!({
+        "project": project_name,
+        "generatedAt": generated_at,
+        "target": target,
+        "totals": totals,
+        "files": diffs.iter().filter(|d| d.status != "unchanged").collect::<Vec<_>>(),
+    });
+    serde_json::to_string_pretty(&report).map_err(|e| format!("Failed to serialize diff report: {}", e))
+}
+
+/// Write the report of `diffs` in REPORT_DIR of `project_path` as "markdown" (default), "csv" or "json"
+pub fn export(
+    diffs: &[FileDiff],
+    project_path: &str,
+    format: Option<&str>,
+    project_name: Option<&str>,
+    target: Option<&SyncTarget>,
+) -> Result<DiffReport, String> {
+    let (format, extension) = match format.map(str::to_lowercase).as_deref() {
+        None | Some("markdown") | Some("md") => ("markdown", "md"),
+        Some("csv") => ("csv", "csv"),
+        Some("json") => ("json", "json"),
+        Some(other) => return Err(format!("Format de rapport inconnu: {} (markdown, csv ou json)", other)),
+    };
+    let project_dir = Path::new(project_path);
+    if !project_dir.is_dir() {
+        return Err(format!("Dossier projet introuvable: {}", project_path));
+    }
+    let project_name = project_name
+        .map(String::from)
+        .or_else(|| project_dir.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_default();
+
+    let now = chrono::Local::now();
+    let generated_at = now.format("%Y-%m-%d %H:%M").to_string();
+    let mut report = totals(diffs);
+    let content = match format {
+        "csv" => build_csv(diffs),
+        "json" => build_json(diffs, &report, &project_name, target, &generated_at)?,
+        _ => build_markdown(diffs, &report, &project_name, target, &generated_at),
+    };
+
+    let report_dir = project_dir.join(REPORT_DIR);
+    fs::create_dir_all(&report_dir).map_err(|e| format!("Failed to create report directory: {}", e))?;
+    let path = report_dir.join(format!("rapport-diff-{}.{}", now.format("%Y%m%d-%H%M%S"), extension));
+    fs::write(&path, content).map_err(|e| format!("Failed to write diff report: {}", e))?;
+
+    report.path = path.to_string_lossy().to_string();
+    report.format = format.to_string();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn diff(path: &str, status: &str, local_size: Option<u64>, remote_size: Option<u64>) -> FileDiff {
+        FileDiff { path: path.to_string(), status: status.to_string(), local_size, remote_size }
+    }
+
+    #[test]
+    fn test_export_markdown_and_csv_reports() {
+        let dir = TempDir::new("diff-report");
+        let project = dir.path().to_string_lossy().to_string();
+        let diffs = vec![
+            diff("index.html", "modified", Some(2048), Some(1024)),
+            diff("img/a|b.png", "added", Some(1024), None),
+            diff("old.php", "deleted", None, Some(512)),
+            diff("style.css", "unchanged", Some(10), Some(10)),
+        ];
+
+        let report = export(&diffs, &project, None, Some("Client"), None).unwrap();
+        assert_eq!((report.added, report.modified, report.deleted, report.unchanged), (1, 1, 1, 1));
+        assert_eq!((report.upload_bytes, report.delete_bytes), (3072, 512));
+        assert!(report.path.ends_with(".md"));
+        let markdown = fs::read_to_string(&report.path).unwrap();
+        assert!(markdown.contains("| **A envoyer** | **2** | **3.0 Ko** |"));
+        assert!(markdown.contains("| `img/a\\|b.png` | 1.0 Ko |"));
+        assert!(markdown.contains("| `index.html` | 2.0 Ko | 1.0 Ko |"));
+        assert!(!markdown.contains("style.css"));
+
+        let csv = fs::read_to_string(export(&diffs, &project, Some("csv"), None, None).unwrap().path).unwrap();
+        assert_eq!(csv.lines().count(), 4);
+        assert!(csv.contains("deleted,old.php,,512"));
+        assert!(export(&diffs, &project, Some("pdf"), None, None).is_err());
+    }
+}
@@ -0,0 +1,285 @@
+//! Events Module
+//!
+//! Contract of the events the backend emits, for the frontend and future
+//! CLI/API consumers. Every event name is a constant here. Progress payloads
+//! carry a `schema_version` and a typed kind, serialized as the snake_case
+//! strings the frontend already matches on. Adding a kind or a field keeps
+//! the contract; renaming or removing one bumps SCHEMA_VERSION.
+//! `get_event_schema` returns the whole contract.
+
+use serde::Serialize;
+
+/// Version of the payloads below, sent in their `schema_version` field
+pub const SCHEMA_VERSION: u32 = 1;
+
+// Sync
+pub const SYNC_PROGRESS: &str = "sync-progress";
+pub const PULL_PROGRESS: &str = "pull-progress";
+pub const SYNC_QUEUE_STATUS: &str = "sync-queue-status";
+pub const INTERRUPTED_SYNCS: &str = "interrupted-syncs";
+pub const SIMULATION_REPORT: &str = "simulation-report";
+pub const DEPLOY_BLOCKED: &str = "deploy-blocked";
+pub const AUTOMATION_PAUSED: &str = "automation-paused";
+pub const WEBHOOK_DEPLOY: &str = "webhook-deploy";
+pub const REMOTE_CHANGE_DETECTED: &str = "remote-change-detected";
+pub const SSH_AUTH_PROMPT: &str = "ssh-auth-prompt";
+pub const SITE_BACKUP_PROGRESS: &str = "site-backup-progress";
+pub const MIGRATION_PROGRESS: &str = "migration-progress";
+pub const DELTA_CACHE_WARMUP: &str = "delta-cache-warmup";
+pub const BUILD_OUTPUT: &str = "build-output";
+
+// Scraping
+pub const SCRAPE_PROGRESS: &str = "scrape-progress";
+pub const FULL_SCRAPE_PROGRESS: &str = "full-scrape-progress";
+pub const SCRAPE_REFRESH_PROGRESS: &str = "scrape-refresh-progress";
+pub const SCRAPE_QUEUE_PROGRESS: &str = "scrape-queue-progress";
+pub const SCRAPE_ASSET_FILED: &str = "scrape-asset-filed";
+
+// Watchers and monitoring
+pub const FILE_WATCHER_EVENT: &str = "file-watcher-event";
+pub const ACTIVITY_RECORDED: &str = "activity-recorded";
+pub const NOTIFICATIONS_DEFERRED_SUMMARY: &str = "notifications-deferred-summary";
+pub const WATCHDOG_INCIDENT: &str = "watchdog-incident";
+
+// Timer
+pub const POMODORO_TICK: &str = "pomodoro-tick";
+pub const POMODORO_WORK_COMPLETE: &str = "pomodoro-work-complete";
+pub const POMODORO_PHASE_CHANGE: &str = "pomodoro-phase-change";
+pub const TIMER_AUTO_PAUSE: &str = "timer-auto-pause";
+pub const TIMER_AUTO_START: &str = "timer-auto-start";
+pub const SUGGEST_TIMER_START: &str = "suggest-timer-start";
+pub const TIMER_IDLE_PAUSE: &str = "timer-idle-pause";
+pub const TIMER_IDLE_RESUME: &str = "timer-idle-resume";
+
+// Application
+pub const UPDATE_DOWNLOAD_PROGRESS: &str = "update-download-progress";
+pub const DATA_LOCATION_PROGRESS: &str = "data-location-progress";
+pub const MENU_ABOUT: &str = "menu-about";
+pub const MENU_CHECK_UPDATES: &str = "menu-check-updates";
+pub const MENU_PREFERENCES: &str = "menu-preferences";
+pub const MENU_NEW_PROJECT: &str = "menu-new-project";
+pub const MENU_REFRESH: &str = "menu-refresh";
+pub const MENU_OPEN_FINDER: &str = "menu-open-finder";
+pub const MENU_OPEN_BROWSER: &str = "menu-open-browser";
+pub const MENU_SYNC: &str = "menu-sync";
+pub const MENU_SCRAPE: &str = "menu-scrape";
+pub const MENU_QUICK_ACTION: &str = "menu-quick-action";
+
+/// Every event: (name, payload and what it reports)
+pub const EVENTS: [(&str, &str); 43] = [
+    (SYNC_PROGRESS, "SyncProgressEvent: steps and files of an upload"),
+    (PULL_PROGRESS, "SyncProgressEvent: steps and files of a download from the server"),
+    (SYNC_QUEUE_STATUS, "offline queue item waiting for, or regaining, its server"),
+    (INTERRUPTED_SYNCS, "syncs interrupted by a crash or a quit, found at startup"),
+    (SIMULATION_REPORT, "SimulationReport of a dry run"),
+    (DEPLOY_BLOCKED, "sync refused by a freeze window or a missing confirmation"),
+    (AUTOMATION_PAUSED, "automatic sync skipped during a pause period"),
+    (WEBHOOK_DEPLOY, "deploy started or refused by the webhook receiver"),
+    (REMOTE_CHANGE_DETECTED, "files changed on the server outside of the app"),
+    (SSH_AUTH_PROMPT, "one-time code prompts of an SSH server, answered with ssh_auth_answer"),
+    (SITE_BACKUP_PROGRESS, "progress of a full remote backup"),
+    (MIGRATION_PROGRESS, "progress of a site migration between two servers"),
+    (DELTA_CACHE_WARMUP, "state of the background delta cache warmup"),
+    (BUILD_OUTPUT, "BuildOutputEvent: one output line of the pre-sync build command"),
+    (SCRAPE_PROGRESS, "ScrapeProgressEvent: pages and assets of a simple scrape"),
+    (FULL_SCRAPE_PROGRESS, "FullScrapeProgress: pages and assets of a full site scrape"),
+    (SCRAPE_REFRESH_PROGRESS, "progress of a capture refresh"),
+    (SCRAPE_QUEUE_PROGRESS, "jobs of the scrape queue"),
+    (SCRAPE_ASSET_FILED, "file dropped into a capture asset folder and renamed"),
+    (FILE_WATCHER_EVENT, "FileWatcherEvent: file added to a project inbox"),
+    (ACTIVITY_RECORDED, "new entry of the activity feed"),
+    (NOTIFICATIONS_DEFERRED_SUMMARY, "notifications held during quiet hours"),
+    (WATCHDOG_INCIDENT, "stuck job or thread recovered by the watchdog"),
+    (POMODORO_TICK, "remaining time of the running pomodoro"),
+    (POMODORO_WORK_COMPLETE, "end of a pomodoro work period"),
+    (POMODORO_PHASE_CHANGE, "switch between work and break"),
+    (TIMER_AUTO_PAUSE, "timer paused after the project's IDE closed"),
+    (TIMER_AUTO_START, "timer started when the project's IDE opened"),
+    (SUGGEST_TIMER_START, "project's IDE opened while no timer runs"),
+    (TIMER_IDLE_PAUSE, "timer paused after the idle threshold"),
+    (TIMER_IDLE_RESUME, "activity back after an idle pause"),
+    (UPDATE_DOWNLOAD_PROGRESS, "download of an application update"),
+    (DATA_LOCATION_PROGRESS, "move of the data directory"),
+    (MENU_ABOUT, "menu item, no payload"),
+    (MENU_CHECK_UPDATES, "menu item, no payload"),
+    (MENU_PREFERENCES, "menu item, no payload"),
+    (MENU_NEW_PROJECT, "menu item, no payload"),
+    (MENU_REFRESH, "menu item, no payload"),
+    (MENU_OPEN_FINDER, "menu item, no payload"),
+    (MENU_OPEN_BROWSER, "menu item, no payload"),
+    (MENU_SYNC, "menu item, no payload"),
+    (MENU_SCRAPE, "menu item, no payload"),
+    (MENU_QUICK_ACTION, "quick action of the configurable menu"),
+];
+
+/// `event` of SyncProgressEvent
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncEventKind {
+    Queued,
+    Building,
+    Connecting,
+    Analyzing,
+    Snapshot,
+    DeletePreview,
+    FileStart,
+    FileProgress,
+    FileComplete,
+    FileError,
+    Deleting,
+    Verifying,
+    Verification,
+    Manifest,
+    QuotaWarning,
+    Maintenance,
+    PostCommand,
+    Complete,
+    Error,
+    Cancelled,
+}
+
+impl SyncEventKind {
+    pub const ALL: [SyncEventKind; 20] = [
+        SyncEventKind::Queued,
+        SyncEventKind::Building,
+        SyncEventKind::Connecting,
+        SyncEventKind::Analyzing,
+        SyncEventKind::Snapshot,
+        SyncEventKind::DeletePreview,
+        SyncEventKind::FileStart,
+        SyncEventKind::FileProgress,
+        SyncEventKind::FileComplete,
+        SyncEventKind::FileError,
+        SyncEventKind::Deleting,
+        SyncEventKind::Verifying,
+        SyncEventKind::Verification,
+        SyncEventKind::Manifest,
+        SyncEventKind::QuotaWarning,
+        SyncEventKind::Maintenance,
+        SyncEventKind::PostCommand,
+        SyncEventKind::Complete,
+        SyncEventKind::Error,
+        SyncEventKind::Cancelled,
+    ];
+}
+
+/// `event_type` of ScrapeProgressEvent
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrapeEventKind {
+    Start,
+    PageStart,
+    ImageDownload,
+    CssDownload,
+    PageComplete,
+    Complete,
+    Error,
+}
+
+impl ScrapeEventKind {
+    pub const ALL: [ScrapeEventKind; 7] = [
+        ScrapeEventKind::Start,
+        ScrapeEventKind::PageStart,
+        ScrapeEventKind::ImageDownload,
+        ScrapeEventKind::CssDownload,
+        ScrapeEventKind::PageComplete,
+        ScrapeEventKind::Complete,
+        ScrapeEventKind::Error,
+    ];
+}
+
+/// `event_type` of FullScrapeProgress
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FullScrapeEventKind {
+    Connecting,
+    PageStart,
+    PageComplete,
+    Rewriting,
+    Analyzing,
+    Complete,
+    Error,
+}
+
+impl FullScrapeEventKind {
+    pub const ALL: [FullScrapeEventKind; 7] = [
+        FullScrapeEventKind::Connecting,
+        FullScrapeEventKind::PageStart,
+        FullScrapeEventKind::PageComplete,
+        FullScrapeEventKind::Rewriting,
+        FullScrapeEventKind::Analyzing,
+        FullScrapeEventKind::Complete,
+        FullScrapeEventKind::Error,
+    ];
+}
+
+/// `event_type` of FileWatcherEvent
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WatcherEventKind {
+    /// Created or modified
+    FileAdded,
+}
+
+impl WatcherEventKind {
+    pub const ALL: [WatcherEventKind; 1] = [WatcherEventKind::FileAdded];
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventDescription {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Values of the typed kind field, empty for untyped payloads
+    pub kinds: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventSchema {
+    pub schema_version: u32,
+    pub events: Vec<EventDescription>,
+}
+
+fn names<T: Serialize>(kinds: &[T]) -> Vec<String> {
+    kinds
+        .iter()
+        .filter_map(|kind| serde_json::to_value(kind).ok())
+        .filter_map(|value| value.as_str().map(|s| s.to_string()))
+        .collect()
+}
+
+pub fn schema() -> EventSchema {
+    let events = EVENTS
+        .iter()
+        .map(|(name, description)| EventDescription {
+            name,
+            description,
+            kinds: match *name {
+                SYNC_PROGRESS | PULL_PROGRESS => names(&SyncEventKind::ALL),
+                SCRAPE_PROGRESS => names(&ScrapeEventKind::ALL),
+                FULL_SCRAPE_PROGRESS => names(&FullScrapeEventKind::ALL),
+                FILE_WATCHER_EVENT => names(&WatcherEventKind::ALL),
+                _ => Vec::new(),
+            },
+        })
+        .collect();
+    EventSchema { schema_version: SCHEMA_VERSION, events }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kinds_keep_their_wire_names() {
+        let schema = schema();
+        let sync = schema.events.iter().find(|e| e.name == SYNC_PROGRESS).unwrap();
+        assert!(sync.kinds.contains(&"file_start".to_string()));
+        assert!(sync.kinds.contains(&"quota_warning".to_string()));
+        assert_eq!(names(&[ScrapeEventKind::CssDownload]), vec!["css_download"]);
+
+        let mut seen = std::collections::HashSet::new();
+        assert!(EVENTS.iter().all(|(name, _)| seen.insert(*name)), "duplicate event name");
+    }
+}
@@ -0,0 +1,59 @@
+//! File Attributes Module
+//!
+//! Attributes of the local file carried over to its uploaded copy. Servers
+//! create uploaded files with their default mode (usually 0644), so CGI and
+//! shell scripts lose their executable bit: with `preserve_permissions` the
+//! local mode is applied with SFTP setstat. The remote modification time is
+//! set to the local one (SFTP setstat, FTP MFMT) unless `preserve_mtime` is
+//! off, so later diffs and external tools see the same dates on both sides.
+//! A server refusing either only gets a log line, the upload stands.
+
+use crate::mtime_diff::local_mtime;
+use crate::UploadOptions;
+use std::path::Path;
+
+/// Permission bits of a local file; None where files have no Unix mode
+#[cfg(unix)]
+pub fn local_mode(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(std::fs::metadata(path).ok()?.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+pub fn local_mode(_path: &Path) -> Option<u32> {
+    None
+}
+
+/// Give `remote_file` the mode and/or modification time of `local_file`, as `upload` asks
+pub fn apply_sftp(sftp: &ssh2::Sftp, local_file: &str, remote_file: &str, upload: UploadOptions) {
+    let local = Path::new(local_file);
+    let perm = if upload.preserve_permissions { local_mode(local) } else { None };
+    let mtime = if upload.preserve_mtime { local_mtime(local).map(|t| t as u64) } else { None };
+    if perm.is_none() && mtime.is_none() {
+        return;
+    }
+    let stat = ssh2::FileStat {
+        size: None,
+        uid: None,
+        gid: None,
+        perm,
+        // Times are set together; the access time follows the modification time
+        atime: mtime,
+        mtime,
+    };
+    if let Err(e) = sftp.setstat(Path::new(remote_file), stat) {
+        println!("[Upload] setstat {} failed: {}", remote_file, e);
+    }
+}
+
+/// Set the modification time of `remote_file` to the local one with MFMT
+pub fn apply_ftp_mtime(ftp: &mut suppaftp::FtpStream, local_file: &str, remote_file: &str) {
+    let mtime = match local_mtime(Path::new(local_file)).and_then(|t| chrono::DateTime::from_timestamp(t, 0)) {
+        Some(mtime) => mtime,
+        None => return,
+    };
+    let command = format!("MFMT {} {}", mtime.format("%Y%m%d%H%M%S"), remote_file);
+    if let Err(e) = ftp.custom_command(command, &[suppaftp::Status::File]) {
+        println!("[Upload] MFMT {} failed: {}", remote_file, e);
+    }
+}
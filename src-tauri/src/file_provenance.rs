@@ -0,0 +1,167 @@
+//! File Provenance Module
+//!
+//! Per-file history across syncs: when a file was first uploaded, when it
+//! was last modified locally, when it last went live, how many deploys it
+//! took part in and which snapshots hold a copy of it. Backs the file
+//! inspector, which answers "when did this file last go live?". One store
+//! per project in `file_provenance/` in the app data.
+
+use crate::state_file;
+use crate::version_history::SyncSnapshot;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Snapshot ids kept per file, the oldest are dropped beyond
+const MAX_SNAPSHOT_IDS: usize = 20;
+
+/// Syncs and snapshots of a project may record at the same time
+static STORE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileProvenance {
+    pub path: String,
+    pub first_uploaded: Option<String>,
+    pub last_modified_local: Option<String>,
+    pub last_deployed: Option<String>,
+    pub deploy_count: u32,
+    /// Size sent by the last deploy
+    pub last_size: Option<u64>,
+    /// What started the last deploy ("manual", "scheduled", "two-way"...)
+    pub last_trigger: Option<String>,
+    /// Snapshots holding a copy of the file, oldest first
+    pub snapshot_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ProvenanceStore {
+    files: HashMap<String, FileProvenance>,
+}
+
+fn store_path(app_data_dir: &Path, project_id: &str) -> PathBuf {
+    app_data_dir.join("file_provenance").join(format!("{}.json", project_id))
+}
+
+fn load(app_data_dir: &Path, project_id: &str) -> ProvenanceStore {
+    state_file::read_json(&store_path(app_data_dir, project_id)).ok().flatten().unwrap_or_default()
+}
+
+fn now() -> String {
+    chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+fn local_mtime(metadata: &fs::Metadata) -> Option<String> {
+    let datetime: chrono::DateTime<chrono::Utc> = metadata.modified().ok()?.into();
+    Some(datetime.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+}
+
+/// Load the store, let `update` change it and save it back
+fn update(app_data_dir: &Path, project_id: &str, update: impl FnOnce(&mut ProvenanceStore)) -> Result<(), String> {
+    let _guard = STORE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut store = load(app_data_dir, project_id);
+    update(&mut store);
+    state_file::write_json(&store_path(app_data_dir, project_id), &store)
+}
+
+fn entry<'a>(store: &'a mut ProvenanceStore, path: &str) -> &'a mut FileProvenance {
+    store.files.entry(path.to_string()).or_insert_with(|| FileProvenance {
+        path: path.to_string(),
+        ..Default::default()
+    })
+}
+
+/// Record the files a sync uploaded, relative to `local_path`
+pub fn record_deploy(
+    app_data_dir: &Path,
+    project_id: &str,
+    local_path: &str,
+    uploaded: &[&str],
+    trigger: &str,
+) -> Result<(), String> {
+    if uploaded.is_empty() {
+        return Ok(());
+    }
+    let deployed_at = now();
+    update(app_data_dir, project_id, |store| {
+        for path in uploaded {
+            let metadata = fs::metadata(Path::new(local_path).join(path)).ok();
+            let file = entry(store, path);
+            file.first_uploaded.get_or_insert_with(|| deployed_at.clone());
+            file.last_deployed = Some(deployed_at.clone());
+            file.deploy_count += 1;
+            file.last_trigger = Some(trigger.to_string());
+            if let Some(metadata) = metadata {
+                file.last_size = Some(metadata.len());
+                file.last_modified_local = local_mtime(&metadata).or(file.last_modified_local.take());
+            }
+        }
+    })
+}
+
+/// Record the snapshot in the history of every file it holds
+pub fn record_snapshot(app_data_dir: &Path, snapshot: &SyncSnapshot) -> Result<(), String> {
+    update(app_data_dir, &snapshot.project_id, |store| {
+        for version in &snapshot.files {
+            let file = entry(store, &version.path);
+            if !file.snapshot_ids.contains(&snapshot.id) {
+                file.snapshot_ids.push(snapshot.id.clone());
+            }
+            let excess = file.snapshot_ids.len().saturating_sub(MAX_SNAPSHOT_IDS);
+            file.snapshot_ids.drain(..excess);
+            if !version.modified.is_empty() {
+                file.last_modified_local = Some(version.modified.clone());
+            }
+        }
+    })
+}
+
+/// Provenance of one file; the local modification date is read from disk when `local_path` is given
+pub fn get(app_data_dir: &Path, project_id: &str, path: &str, local_path: Option<&str>) -> Option<FileProvenance> {
+    let mut file = load(app_data_dir, project_id).files.remove(path)?;
+    let metadata = local_path.and_then(|root| fs::metadata(Path::new(root).join(path)).ok());
+    if let Some(mtime) = metadata.as_ref().and_then(local_mtime) {
+        file.last_modified_local = Some(mtime);
+    }
+    Some(file)
+}
+
+/// Files under `prefix` (all when None), most recently deployed first
+pub fn list(app_data_dir: &Path, project_id: &str, prefix: Option<&str>) -> Vec<FileProvenance> {
+    let mut files: Vec<FileProvenance> = load(app_data_dir, project_id)
+        .files
+        .into_values()
+        .filter(|f| prefix.map(|p| f.path.starts_with(p)).unwrap_or(true))
+        .collect();
+    files.sort_by(|a, b| b.last_deployed.cmp(&a.last_deployed).then_with(|| a.path.cmp(&b.path)));
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_deploys_and_snapshots_accumulate() {
+        let data = TempDir::new("provenance");
+        let local = TempDir::new("provenance-site");
+        local.write_files(&[("index.html", "<h1>v1</h1>"), ("css/site.css", "body{}")]);
+
+        record_deploy(data.path(), "p", &local.path_str(), &["index.html", "css/site.css"], "manual").unwrap();
+        let snapshot = crate::version_history::create_snapshot("p", &local.path_str(), None, None).unwrap();
+        record_snapshot(data.path(), &snapshot).unwrap();
+        record_deploy(data.path(), "p", &local.path_str(), &["index.html"], "scheduled").unwrap();
+
+        let index = get(data.path(), "p", "index.html", None).unwrap();
+        assert_eq!(index.deploy_count, 2);
+        assert_eq!(index.last_size, Some(11));
+        assert_eq!(index.last_trigger.as_deref(), Some("scheduled"));
+        assert!(index.first_uploaded <= index.last_deployed);
+        assert_eq!(index.snapshot_ids, vec![snapshot.id.clone()]);
+        assert_eq!(list(data.path(), "p", Some("css/")).len(), 1);
+        assert!(get(data.path(), "p", "missing.html", None).is_none());
+    }
+}
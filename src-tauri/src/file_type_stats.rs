@@ -0,0 +1,139 @@
+//! File Type Stats Module
+//!
+//! Composition of a transfer by extension and by broad category, so the UI
+//! can say "82% of this deploy is images" and the activity feed keeps the
+//! composition of every sync for later comparison. For a sync only the
+//! files it uploads (added and modified) are counted.
+
+use crate::FileDiff;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Extensions by category; anything else is "other"
+const CATEGORIES: [(&str, &[&str]); 8] = [
+    ("image", &["jpg", "jpeg", "png", "gif", "webp", "avif", "svg", "ico", "bmp", "tif", "tiff"]),
+    ("style", &["css", "scss", "sass", "less"]),
+    ("script", &["js", "mjs", "cjs", "ts", "map"]),
+    ("page", &["html", "htm", "php", "phtml", "twig", "tpl"]),
+    ("font", &["woff", "woff2", "ttf", "otf", "eot"]),
+    ("media", &["mp4", "webm", "mov", "mp3", "ogg", "wav", "m4a"]),
+    ("document", &["pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "odt", "txt", "csv", "md", "json", "xml"]),
+    ("archive", &["zip", "gz", "tgz", "tar", "rar", "7z"]),
+];
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TypeShare {
+    /// Lowercase extension without the dot ("" for none), or category id
+    pub key: String,
+    pub files: usize,
+    pub bytes: u64,
+    /// Part of the transferred bytes, 0 to 1
+    pub share: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FileTypeStats {
+    pub total_files: usize,
+    pub total_bytes: u64,
+    /// Largest first
+    pub by_extension: Vec<TypeShare>,
+    /// "image", "style", "script", "page", "font", "media", "document", "archive" or "other"; largest first
+    pub by_category: Vec<TypeShare>,
+}
+
+pub fn extension(path: &str) -> String {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    match name.rsplit_once('.') {
+        // ".htaccess" has no extension, it is a name
+        Some((stem, ext)) if !stem.is_empty() => ext.to_ascii_lowercase(),
+        _ => String::new(),
+    }
+}
+
+pub fn category(extension: &str) -> &'static str {
+    CATEGORIES
+        .iter()
+        .find(|(_, extensions)| extensions.contains(&extension))
+        .map(|(category, _)| *category)
+        .unwrap_or("other")
+}
+
+fn shares(groups: HashMap<String, (usize, u64)>, total_bytes: u64) -> Vec<TypeShare> {
+    let mut shares: Vec<TypeShare> = groups
+        .into_iter()
+        .map(|(key, (files, bytes))| TypeShare {
+            key,
+            files,
+            bytes,
+            share: if total_bytes > 0 { bytes as f64 / total_bytes as f64 } else { 0.0 },
+        })
+        .collect();
+    shares.sort_by(|a, b| b.bytes.cmp(&a.bytes).then(b.files.cmp(&a.files)).then(a.key.cmp(&b.key)));
+    shares
+}
+
+/// Composition of the files `diffs` uploads
+pub fn compute(diffs: &[FileDiff]) -> FileTypeStats {
+    from_files(
+        diffs
+            .iter()
+            .filter(|d| d.status == "added" || d.status == "modified")
+            .map(|d| (d.path.as_str(), d.local_size.unwrap_or(0))),
+    )
+}
+
+/// Composition of any set of files (relative path, size)
+pub fn from_files<'a>(files: impl Iterator<Item = (&'a str, u64)>) -> FileTypeStats {
+    let mut by_extension: HashMap<String, (usize, u64)> = HashMap::new();
+    let mut by_category: HashMap<String, (usize, u64)> = HashMap::new();
+    let mut stats = FileTypeStats::default();
+    for (path, size) in files {
+        let ext = extension(path);
+        let category = category(&ext);
+        for (groups, key) in [(&mut by_extension, ext), (&mut by_category, category.to_string())] {
+            let entry = groups.entry(key).or_default();
+            entry.0 += 1;
+            entry.1 += size;
+        }
+        stats.total_files += 1;
+        stats.total_bytes += size;
+    }
+    stats.by_extension = shares(by_extension, stats.total_bytes);
+    stats.by_category = shares(by_category, stats.total_bytes);
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(path: &str, status: &str, size: u64) -> FileDiff {
+        FileDiff {
+            path: path.to_string(),
+            status: status.to_string(),
+            local_size: Some(size),
+            remote_size: None,
+        }
+    }
+
+    #[test]
+    fn test_composition_of_uploaded_files() {
+        let diffs = vec![
+            diff("img/hero.JPG", "added", 600),
+            diff("img/logo.png", "modified", 220),
+            diff("css/site.css", "modified", 100),
+            diff(".htaccess", "added", 80),
+            diff("old.html", "deleted", 5000),
+            diff("index.html", "unchanged", 900),
+        ];
+        let stats = compute(&diffs);
+        assert_eq!((stats.total_files, stats.total_bytes), (4, 1000));
+        assert_eq!(stats.by_category[0].key, "image");
+        assert_eq!(stats.by_category[0].files, 2);
+        assert!((stats.by_category[0].share - 0.82).abs() < 1e-9);
+        let keys: Vec<&str> = stats.by_extension.iter().map(|s| s.key.as_str()).collect();
+        assert_eq!(keys, vec!["jpg", "png", "css", ""]);
+        assert_eq!(category(""), "other");
+    }
+}
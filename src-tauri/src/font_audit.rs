@@ -0,0 +1,277 @@
+//! Font Audit Module
+//!
+//! Inspects web fonts found while scraping: `@font-face` declarations,
+//! file formats, weights actually used by the stylesheets, licensing
+//! hints for commercial foundries, and optional woff2 subsetting through
+//! fonttools' `pyftsubset` when it is installed.
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Families distributed under commercial licenses (lowercase prefixes)
+const COMMERCIAL_FAMILIES: [&str; 32] = [
+    "helvetica", "neue haas", "avenir", "futura", "gotham", "proxima nova", "brandon grotesque",
+    "museo", "circular", "graphik", "freight", "akzidenz", "univers", "frutiger", "din pro",
+    "din next", "gill sans", "sofia pro", "gilroy", "tt norms", "aktiv grotesk", "acumin",
+    "canela", "gt walsheim", "gt america", "apercu", "calibre", "tiempos", "publico",
+    "optima", "trade gothic", "myriad",
+];
+
+/// Hosts serving fonts under a subscription license
+const LICENSED_FONT_HOSTS: [&str; 5] = [
+    "use.typekit.net", "p.typekit.net", "fast.fonts.net", "cloud.typography.com", "fonts.adobe.com",
+];
+
+/// Hosts serving open-licensed fonts
+const OPEN_FONT_HOSTS: [&str; 3] = ["fonts.gstatic.com", "fonts.googleapis.com", "fonts.bunny.net"];
+
+/// One `@font-face` declaration
+#[derive(Debug, Clone)]
+pub struct FontFace {
+    pub family: String,
+    pub weight: String,
+    pub style: String,
+    /// Absolute URLs of the `src` entries
+    pub sources: Vec<String>,
+}
+
+/// A downloaded font file
+#[derive(Debug, Clone, Serialize)]
+pub struct FontFileInfo {
+    pub file: String, // relative to the scrape output folder
+    pub format: String, // "woff2", "woff", "ttf", "otf", "eot", "unknown"
+    pub weight: String,
+    pub style: String,
+    pub size: u64,
+    /// Whether a stylesheet uses this weight for the family (None if unknown)
+    pub used: Option<bool>,
+    pub subset_file: Option<String>,
+    pub subset_size: Option<u64>,
+}
+
+/// Licensing hint for a font family
+#[derive(Debug, Clone, Serialize)]
+pub struct LicenseNote {
+    pub status: String, // "commercial", "open", "unknown"
+    pub reason: String,
+}
+
+/// Parse the `@font-face` blocks of a stylesheet, resolving sources against `base_url`
+pub fn parse_font_faces(css: &str, base_url: &url::Url) -> Vec<FontFace> {
+    let lower = css.to_lowercase();
+    let mut faces = Vec::new();
+    let mut search_from = 0;
+
+    while let Some(pos) = lower[search_from..].find("@font-face") {
+        let start = search_from + pos;
+        let open = match css[start..].find('{') {
+            Some(o) => start + o,
+            None => break,
+        };
+        let close = match css[open..].find('}') {
+            Some(c) => open + c,
+            None => break,
+        };
+        let block = &css[open + 1..close];
+        search_from = close;
+
+        let declarations = parse_declarations(block);
+        let family = match declarations.get("font-family") {
+            Some(f) => unquote(f),
+            None => continue,
+        };
+        let sources = declarations
+            .get("src")
+            .map(|src| extract_urls(src))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|u| !u.starts_with("data:"))
+            .filter_map(|u| base_url.join(&u).ok().map(|u| u.to_string()))
+            .collect();
+
+        faces.push(FontFace {
+            family,
+            weight: declarations
+                .get("font-weight")
+                .map(|w| normalize_weight(w))
+                .unwrap_or_else(|| "400".to_string()),
+            style: declarations.get("font-style").cloned().unwrap_or_else(|| "normal".to_string()),
+            sources,
+        });
+    }
+
+    faces
+}
+
+/// Record the weights each family is rendered with in regular style rules
+pub fn collect_used_weights(css: &str, used: &mut HashMap<String, HashSet<String>>) {
+    for block in css.split('}') {
+        // Nested @media blocks leave their own prelude before the inner rule
+        let (prelude, body) = match block.rsplit_once('{') {
+            Some(parts) => parts,
+            None => continue,
+        };
+        let selector = prelude.rsplit('{').next().unwrap_or(prelude);
+        if selector.trim_start().to_lowercase().starts_with("@font-face") {
+            continue;
+        }
+        let declarations = parse_declarations(body);
+        if let Some(families) = declarations.get("font-family") {
+            let family = unquote(families.split(',').next().unwrap_or(""));
+            if family.is_empty() {
+                continue;
+            }
+            let weight = declarations
+                .get("font-weight")
+                .map(|w| normalize_weight(w))
+                .unwrap_or_else(|| "400".to_string());
+            used.entry(family.to_lowercase()).or_default().insert(weight);
+        }
+    }
+}
+
+/// Detect a font file format from its magic bytes
+pub fn detect_format(path: &Path) -> String {
+    let header = fs::read(path).map(|b| b.into_iter().take(4).collect::<Vec<u8>>()).unwrap_or_default();
+    match header.as_slice() {
+        b"wOF2" => "woff2",
+        b"wOFF" => "woff",
+        b"OTTO" => "otf",
+        [0x00, 0x01, 0x00, 0x00] | b"true" => "ttf",
+        _ if path.extension().map(|e| e.eq_ignore_ascii_case("eot")).unwrap_or(false) => "eot",
+        _ => "unknown",
+    }
+    .to_string()
+}
+
+/// Licensing hint from the family name and where the files are served from
+pub fn license_note(family: &str, source_urls: &[String]) -> LicenseNote {
+    let hosts: Vec<String> = source_urls
+        .iter()
+        .filter_map(|u| url::Url::parse(u).ok()?.host_str().map(String::from))
+        .collect();
+
+    if let Some(host) = hosts.iter().find(|h| LICENSED_FONT_HOSTS.contains(&h.as_str())) {
+        return LicenseNote {
+            status: "commercial".to_string(),
+            reason: format!("Servie par {} (abonnement lie au domaine du client)", host),
+        };
+    }
+    let lower = family.to_lowercase();
+    if let Some(name) = COMMERCIAL_FAMILIES.iter().find(|name| lower.starts_with(*name)) {
+        return LicenseNote {
+            status: "commercial".to_string(),
+            reason: format!("Famille '{}' distribuee par une fonderie commerciale", name),
+        };
+    }
+    if hosts.iter().any(|h| OPEN_FONT_HOSTS.contains(&h.as_str())) {
+        return LicenseNote {
+            status: "open".to_string(),
+            reason: "Police Google Fonts / open source (SIL OFL ou Apache)".to_string(),
+        };
+    }
+    LicenseNote {
+        status: "unknown".to_string(),
+        reason: "Licence a verifier avant redistribution".to_string(),
+    }
+}
+
+/// Subset a woff2 file to the characters of `text` using `pyftsubset`.
+///
+/// Writes `<name>.subset.woff2` next to the original.
+pub fn subset_woff2(font_path: &Path, text: &str) -> Result<PathBuf, String> {
+    let stem = font_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .ok_or_else(|| "Invalid font path".to_string())?;
+    let output = font_path.with_file_name(format!("{}.subset.woff2", stem));
+    let text_file = font_path.with_file_name(format!("{}.subset-text.txt", stem));
+    fs::write(&text_file, text).map_err(|e| format!("Failed to write glyph list: {}", e))?;
+
+    let result = Command::new("pyftsubset")
+        .arg(font_path)
+        .arg(format!("--text-file={}", text_file.to_string_lossy()))
+        .arg("--flavor=woff2")
+        .arg("--layout-features=*")
+        .arg(format!("--output-file={}", output.to_string_lossy()))
+        .output();
+    let _ = fs::remove_file(&text_file);
+
+    match result {
+        Ok(out) if out.status.success() => Ok(output),
+        Ok(out) => Err(format!("pyftsubset failed: {}", String::from_utf8_lossy(&out.stderr).trim())),
+        Err(e) => Err(format!("pyftsubset not available: {}", e)),
+    }
+}
+
+fn parse_declarations(block: &str) -> HashMap<String, String> {
+    block
+        .split(';')
+        .filter_map(|decl| {
+            let (name, value) = decl.split_once(':')?;
+            Some((name.trim().to_lowercase(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+fn extract_urls(src: &str) -> Vec<String> {
+    let mut urls = Vec::new();
+    let mut rest = src;
+    while let Some(start) = rest.find("url(") {
+        rest = &rest[start + 4..];
+        let end = rest.find(')').unwrap_or(rest.len());
+        urls.push(unquote(&rest[..end]));
+        rest = &rest[end..];
+    }
+    urls
+}
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches('"').trim_matches('\'').to_string()
+}
+
+fn normalize_weight(weight: &str) -> String {
+    match weight.trim().to_lowercase().as_str() {
+        "normal" => "400".to_string(),
+        "bold" => "700".to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_faces_weights_and_licenses() {
+        let base = url::Url::parse("https://example.com/assets/css/site.css").unwrap();
+        let css = r#"
+            @font-face { font-family: "Inter"; font-weight: bold; src: url('../fonts/inter-700.woff2') format("woff2"), url(data:font/woff2;base64,AAAA) }
+            @FONT-FACE { font-family: Inter; font-style: italic; src: url(https://fonts.gstatic.com/s/inter.woff2) }
+            @font-face { src: url(orphan.woff2) }
+            h1 { font-family: 'Inter', sans-serif; font-weight: bold }
+            @media (min-width: 800px) { p { font-family: Inter } }
+        "#;
+        let faces = parse_font_faces(css, &base);
+        let summary: Vec<(&str, &str, &str, &[String])> =
+            faces.iter().map(|f| (f.family.as_str(), f.weight.as_str(), f.style.as_str(), f.sources.as_slice())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Inter", "700", "normal", &["https://example.com/assets/fonts/inter-700.woff2".to_string()][..]),
+                ("Inter", "400", "italic", &["https://fonts.gstatic.com/s/inter.woff2".to_string()][..]),
+            ]
+        );
+
+        let mut used = HashMap::new();
+        collect_used_weights(css, &mut used);
+        assert_eq!(used["inter"], HashSet::from(["700".to_string(), "400".to_string()]));
+
+        assert_eq!(license_note("Inter", &faces[1].sources).status, "open");
+        assert_eq!(license_note("Inter", &faces[0].sources).status, "unknown");
+        assert_eq!(license_note("Inter", &["https://use.typekit.net/abc.woff2".to_string()]).status, "commercial");
+    }
+}
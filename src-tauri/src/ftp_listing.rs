@@ -0,0 +1,198 @@
+//! FTP Listing Module
+//!
+//! Directory listings for the FTP scan. MLSD (RFC 3659) gives structured
+//! facts (type, size, modification time) and names verbatim, spaces
+//! included; servers without it fall back to LIST, whose output is parsed in
+//! the Unix `ls -l` and Windows/IIS (DOS) formats by locating the date
+//! columns rather than counting fields.
+
+use chrono::NaiveDateTime;
+
+/// Entry of a directory listing
+#[derive(Debug, Clone, PartialEq)]
+pub struct FtpEntry {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+    /// Unix seconds, from the MLSD `modify` fact
+    pub modified: Option<i64>,
+}
+
+const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+
+/// Lists directories with MLSD until the server refuses it, then with LIST
+pub struct FtpLister {
+    mlsd: bool,
+}
+
+impl Default for FtpLister {
+    fn default() -> Self {
+        Self { mlsd: true }
+    }
+}
+
+impl FtpLister {
+    /// Entries of `path`, "." and ".." left out; an unreadable folder lists empty
+    pub fn list(&mut self, ftp: &mut suppaftp::FtpStream, path: &str) -> Vec<FtpEntry> {
+        if self.mlsd {
+            match ftp.mlsd(Some(path)) {
+                Ok(lines) => return lines.iter().filter_map(|line| parse_mlsd_line(line)).collect(),
+                Err(e) => {
+                    println!("[FTP] MLSD unavailable, using LIST: {}", e);
+                    self.mlsd = false;
+                }
+            }
+        }
+        ftp.list(Some(path))
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|line| parse_list_line(line))
+            .filter(|entry| entry.name != "." && entry.name != "..")
+            .collect()
+    }
+}
+
+/// `type=file;size=1024;modify=20240115103000; index.html`
+pub fn parse_mlsd_line(line: &str) -> Option<FtpEntry> {
+    // The name follows the first space and is taken as is
+    let (facts, name) = line.trim_end_matches(['\r', '\n']).split_once(' ')?;
+    if name.is_empty() {
+        return None;
+    }
+    let mut kind = None;
+    let mut size = 0;
+    let mut modified = None;
+    for fact in facts.split(';') {
+        let (key, value) = match fact.split_once('=') {
+            Some(pair) => pair,
+            None => continue,
+        };
+        match key.to_ascii_lowercase().as_str() {
+            "type" => kind = Some(value.to_ascii_lowercase()),
+            "size" => size = value.parse().unwrap_or(0),
+            "modify" => {
+                // YYYYMMDDHHMMSS[.sss], UTC
+                modified = NaiveDateTime::parse_from_str(value.get(..14)?, "%Y%m%d%H%M%S")
+                    .ok()
+                    .map(|time| time.and_utc().timestamp());
+            }
+            _ => {}
+        }
+    }
+    let is_dir = match kind.as_deref() {
+        Some("file") => false,
+        Some("dir") => true,
+        // cdir, pdir and OS-specific types (links, devices)
+        _ => return None,
+    };
+    Some(FtpEntry { name: name.to_string(), is_dir, size, modified })
+}
+
+/// Whitespace-separated fields of `line` with the byte offset they start at
+fn fields(line: &str) -> Vec<(usize, &str)> {
+    let mut fields = Vec::new();
+    let mut start = None;
+    for (at, c) in line.char_indices() {
+        match (c.is_whitespace(), start) {
+            (true, Some(from)) => {
+                fields.push((from, &line[from..at]));
+                start = None;
+            }
+            (false, None) => start = Some(at),
+            _ => {}
+        }
+    }
+    if let Some(from) = start {
+        fields.push((from, &line[from..]));
+    }
+    fields
+}
+
+fn is_time_or_year(value: &str) -> bool {
+    let parts: Vec<&str> = value.split(':').collect();
+    match parts.as_slice() {
+        [year] => year.len() == 4 && year.chars().all(|c| c.is_ascii_digit()),
+        [hours, minutes] => [hours, minutes].iter().all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit())),
+        _ => false,
+    }
+}
+
+/// One line of a LIST reply, Unix or DOS format
+pub fn parse_list_line(line: &str) -> Option<FtpEntry> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    let fields = fields(line);
+    if fields.len() < 4 {
+        return None;
+    }
+    parse_dos_line(line, &fields).or_else(|| parse_unix_line(line, &fields))
+}
+
+/// `-rw-r--r-- 1 owner group 1024 Jan 15 10:30 my file.html`, the group being optional
+fn parse_unix_line(line: &str, fields: &[(usize, &str)]) -> Option<FtpEntry> {
+    let kind = fields[0].1.chars().next()?;
+    if !matches!(kind, '-' | 'd' | 'l') {
+        return None;
+    }
+    // Size, month, day and time (or year) are the last columns before the name
+    let month_at = (2..fields.len().saturating_sub(3)).find(|&i| {
+        MONTHS.contains(&fields[i].1.to_ascii_lowercase().as_str())
+            && fields[i - 1].1.chars().all(|c| c.is_ascii_digit())
+            && fields[i + 1].1.len() <= 2
+            && fields[i + 1].1.chars().all(|c| c.is_ascii_digit())
+            && is_time_or_year(fields[i + 2].1)
+    })?;
+    let mut name = &line[fields[month_at + 3].0..];
+    if kind == 'l' {
+        name = name.split(" -> ").next().unwrap_or(name);
+    }
+    Some(FtpEntry {
+        name: name.to_string(),
+        is_dir: kind == 'd',
+        size: fields[month_at - 1].1.parse().unwrap_or(0),
+        modified: None,
+    })
+}
+
+/// `01-15-24  10:30AM       <DIR>          my folder` or `... 1024 my file.html`
+fn parse_dos_line(line: &str, fields: &[(usize, &str)]) -> Option<FtpEntry> {
+    let date = fields[0].1;
+    let is_date = date.len() >= 8 && date.chars().all(|c| c.is_ascii_digit() || c == '-' || c == '/');
+    let time = fields[1].1.to_ascii_uppercase();
+    if !is_date || !(time.ends_with("AM") || time.ends_with("PM") || time.contains(':')) {
+        return None;
+    }
+    let is_dir = fields[2].1.eq_ignore_ascii_case("<DIR>");
+    let size = if is_dir { 0 } else { fields[2].1.replace(',', "").parse().ok()? };
+    Some(FtpEntry {
+        name: line[fields[3].0..].to_string(),
+        is_dir,
+        size,
+        modified: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mlsd_and_list_formats() {
+        let entry = parse_mlsd_line("type=file;size=2048;modify=20240115103000.123;perm=r; mon  fichier.html").unwrap();
+        assert_eq!(entry.name, "mon  fichier.html");
+        assert_eq!(entry.size, 2048);
+        assert_eq!(entry.modified, Some(1_705_314_600));
+        assert!(parse_mlsd_line("Type=dir;Modify=20240115103000; images").unwrap().is_dir);
+        assert_eq!(parse_mlsd_line("type=cdir; ."), None);
+
+        let unix = parse_list_line("-rw-r--r--   1 www  www   1024 Jan 15 10:30 mon  fichier.html").unwrap();
+        assert_eq!((unix.name.as_str(), unix.size, unix.is_dir), ("mon  fichier.html", 1024, false));
+        let no_group = parse_list_line("drwxr-xr-x 2 ftp 4096 Mar 3 2023 Nouveau dossier").unwrap();
+        assert_eq!((no_group.name.as_str(), no_group.is_dir), ("Nouveau dossier", true));
+        assert_eq!(parse_list_line("lrwxrwxrwx 1 a b 9 Jan 1 00:00 current -> v2").unwrap().name, "current");
+
+        let dos = parse_list_line("01-15-24  10:30AM              1,234 rapport final.pdf").unwrap();
+        assert_eq!((dos.name.as_str(), dos.size), ("rapport final.pdf", 1234));
+        assert!(parse_list_line("01-15-24  10:30AM       <DIR>          Mes images").unwrap().is_dir);
+        assert_eq!(parse_list_line("total 12"), None);
+    }
+}
@@ -4,8 +4,19 @@
 //! - Downloads HTML, CSS, JS, images, fonts
 //! - Rewrites URLs to work locally (relative paths)
 //! - Extracts design system (colors, fonts, typography)
-//! - Generates comprehensive scraping report
-
+//! - Generates comprehensive scraping report, with a content inventory
+
+use crate::color_palette::{self, ColorPalette, ContrastPair};
+use crate::content_inventory::{self, ContentInventory};
+use crate::css_usage::{self, StylesheetUsage};
+use crate::events::{self, FullScrapeEventKind};
+use crate::font_audit::{self, FontFace, FontFileInfo, LicenseNote};
+use crate::image_gallery;
+use crate::image_metadata::{self, ImageMetadataCollector};
+use crate::proxy::{self, ProxyConfig};
+use crate::scrape_attribution::{self, PageHints, SiteAttribution};
+use crate::scrape_refresh::{self, AssetManifest, AssetManifestEntry};
+use crate::shared_scrape_cache::SharedScrapeCache;
 use reqwest::blocking::Client;
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
@@ -21,8 +32,10 @@ use url::Url;
 /// Progress event for full site scraping
 #[derive(Debug, Clone, Serialize)]
 pub struct FullScrapeProgress {
+    /// events::SCHEMA_VERSION
+    pub schema_version: u32,
     pub project_id: String,
-    pub event_type: String, // "connecting", "page_start", "page_complete", "asset_download", "analyzing", "rewriting", "complete", "error"
+    pub event_type: FullScrapeEventKind,
     pub current_step: String,
     pub progress_percent: f32,
     pub pages_downloaded: usize,
@@ -52,27 +65,69 @@ pub struct FullScrapeConfig {
     pub rewrite_urls: bool,
     #[serde(default = "default_true")]
     pub generate_report: bool,
+    /// Write `gallery/index.html` with a thumbnail card per downloaded image
+    #[serde(default = "default_true")]
+    pub generate_gallery: bool,
+    /// Append a short content hash to asset filenames (`style.a1b2c3d4.css`)
+    #[serde(default)]
+    pub hash_asset_names: bool,
+    /// Maximum redirect hops followed per request
+    #[serde(default = "default_max_redirects")]
+    pub max_redirects: u32,
+    /// Save the site's 404 page design as `404.html`
+    #[serde(default)]
+    pub capture_error_page: bool,
+    /// Compare captured pages against the site's sitemap.xml
+    #[serde(default = "default_true")]
+    pub check_sitemap: bool,
+    /// Subset used woff2 fonts to the glyphs of the captured pages (needs `pyftsubset`)
+    #[serde(default)]
+    pub subset_fonts: bool,
+    /// Root of the cross-project asset cache (see shared_scrape_cache), None to disable
+    #[serde(default)]
+    pub shared_cache_dir: Option<String>,
+    /// SOCKS5 or HTTP proxy the requests go through
+    #[serde(default)]
+    pub proxy: Option<ProxyConfig>,
 }
 
 fn default_max_pages() -> u32 { 100 }
+fn default_max_redirects() -> u32 { 10 }
 fn default_true() -> bool { true }
 
 /// Design System extracted from the website
 #[derive(Debug, Clone, Serialize, Default)]
 pub struct DesignSystem {
     pub colors: Vec<ColorInfo>,
+    pub palette: ColorPalette,
+    /// WCAG contrast of every text/background pair of palette colors
+    pub contrast: Vec<ContrastPair>,
     pub fonts: Vec<FontInfo>,
     pub typography: TypographyInfo,
     pub spacing: Vec<String>,
     pub breakpoints: Vec<String>,
+    pub icons: Vec<IconInfo>,
+}
+
+/// Favicon, touch icon or web manifest icon found on the site
+#[derive(Debug, Clone, Serialize)]
+pub struct IconInfo {
+    pub url: String,
+    pub local_path: String, // relative to the scrape output folder
+    pub rel: String,        // "icon", "apple-touch-icon", "mask-icon", "manifest", ...
+    pub sizes: Option<String>,
+    pub mime_type: Option<String>,
+    pub source: String, // "link", "manifest", "default"
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct ColorInfo {
     pub hex: String,
     pub rgb: Option<String>,
-    pub usage: String, // "background", "text", "border", "accent"
+    pub usage: String, // "primary", "secondary", "accent", "neutral"
     pub occurrences: usize,
+    /// Near-identical shades merged into this color
+    pub variants: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -81,6 +136,9 @@ pub struct FontInfo {
     pub weights: Vec<String>,
     pub source: String, // "google", "local", "embedded"
     pub url: Option<String>,
+    /// Downloaded files declared by `@font-face`
+    pub files: Vec<FontFileInfo>,
+    pub license: LicenseNote,
 }
 
 #[derive(Debug, Clone, Serialize, Default)]
@@ -97,6 +155,41 @@ pub struct HeadingStyle {
     pub line_height: Option<String>,
 }
 
+/// A redirect chain met while crawling (old URL -> final URL)
+#[derive(Debug, Clone, Serialize)]
+pub struct RedirectRecord {
+    pub from: String,
+    pub to: Option<String>, // None for loops and too-long chains
+    pub hops: Vec<RedirectHop>,
+    pub is_loop: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RedirectHop {
+    pub url: String,
+    pub status: u16,
+}
+
+/// A page answering with a non-success HTTP status
+#[derive(Debug, Clone, Serialize)]
+pub struct HttpErrorRecord {
+    pub url: String,
+    pub status: u16,
+}
+
+/// Pages listed in the sitemap compared to the pages actually captured
+#[derive(Debug, Clone, Serialize)]
+pub struct SitemapCheck {
+    pub sitemaps: Vec<String>,
+    pub total_in_sitemap: usize,
+    pub captured: usize,
+    pub missing: Vec<String>,
+    pub coverage_percent: f32,
+}
+
+/// Maximum number of (nested) sitemap files read
+const MAX_SITEMAP_FILES: usize = 20;
+
 /// Result of full site scraping
 #[derive(Debug, Clone, Serialize)]
 pub struct FullScrapeResult {
@@ -107,7 +200,19 @@ pub struct FullScrapeResult {
     pub output_path: String,
     pub index_path: String,
     pub design_system: DesignSystem,
+    pub css_usage: Vec<StylesheetUsage>,
+    pub redirects: Vec<RedirectRecord>,
+    pub http_errors: Vec<HttpErrorRecord>,
+    pub error_page_path: Option<String>,
+    pub sitemap_check: Option<SitemapCheck>,
+    /// Words, images and language of every captured page
+    pub content_inventory: ContentInventory,
+    pub gallery_path: Option<String>,
+    /// `images/image-metadata.json`: alt, title and caption of each downloaded image
+    pub image_metadata_path: Option<String>,
     pub report_path: Option<String>,
+    /// Assets copied from the shared cache instead of downloaded
+    pub shared_cache_hits: usize,
     pub errors: Vec<String>,
     pub warnings: Vec<String>,
 }
@@ -119,6 +224,11 @@ struct DownloadedAsset {
     local_path: PathBuf,
     asset_type: AssetType,
     size: u64,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    captured_at: String,
+    robots_tag: Option<String>,
+    license_url: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -154,6 +264,17 @@ impl AssetType {
         }
     }
 
+    fn manifest_name(&self) -> &str {
+        match self {
+            AssetType::Html => "html",
+            AssetType::Css => "css",
+            AssetType::JavaScript => "js",
+            AssetType::Image => "image",
+            AssetType::Font => "font",
+            AssetType::Other => "other",
+        }
+    }
+
     fn directory(&self) -> &str {
         match self {
             AssetType::Html => "",
@@ -178,23 +299,52 @@ pub struct FullSiteScraper {
     colors_found: HashMap<String, usize>,
     fonts_found: HashMap<String, HashSet<String>>, // font_name -> weights
     font_urls: HashMap<String, String>,
+    font_faces: Vec<FontFace>,
+    /// Family (lowercase) -> weights used by style rules
+    used_font_weights: HashMap<String, HashSet<String>>,
+    icons: Vec<IconInfo>,
+    image_metadata: ImageMetadataCollector,
+    /// Copyright, author and license declared by the pages (see scrape_attribution)
+    page_hints: Vec<PageHints>,
+    /// Raw `href` attribute values of icon links -> absolute URL, so relative
+    /// references get rewritten too
+    icon_refs: HashMap<String, String>,
+    redirects: Vec<RedirectRecord>,
+    http_errors: Vec<HttpErrorRecord>,
+    error_page_path: Option<String>,
     errors: Vec<String>,
     warnings: Vec<String>,
+    shared_cache: Option<SharedScrapeCache>,
     cancel_flag: Arc<AtomicBool>,
 }
 
 impl FullSiteScraper {
     pub fn new(config: FullScrapeConfig, project_id: &str, cancel_flag: Arc<AtomicBool>) -> Result<Self, String> {
-        let client = Client::builder()
+        let builder = Client::builder()
             .timeout(Duration::from_secs(60))
             .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
             .danger_accept_invalid_certs(true)
+            // Redirects are followed manually so chains can be recorded
+            .redirect(reqwest::redirect::Policy::none());
+        let client = proxy::apply(builder, config.proxy.as_ref())?
             .build()
             .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
 
         let base_url = Url::parse(&config.url)
             .map_err(|e| format!("Invalid URL: {}", e))?;
 
+        let mut warnings = Vec::new();
+        let shared_cache = match &config.shared_cache_dir {
+            Some(root) => match SharedScrapeCache::open(Path::new(root), &config.url) {
+                Ok(cache) => Some(cache),
+                Err(e) => {
+                    warnings.push(format!("Cache partage indisponible: {}", e));
+                    None
+                }
+            },
+            None => None,
+        };
+
         Ok(Self {
             client,
             base_url,
@@ -206,8 +356,18 @@ impl FullSiteScraper {
             colors_found: HashMap::new(),
             fonts_found: HashMap::new(),
             font_urls: HashMap::new(),
+            font_faces: Vec::new(),
+            used_font_weights: HashMap::new(),
+            icons: Vec::new(),
+            image_metadata: ImageMetadataCollector::default(),
+            page_hints: Vec::new(),
+            icon_refs: HashMap::new(),
+            redirects: Vec::new(),
+            http_errors: Vec::new(),
+            error_page_path: None,
             errors: Vec::new(),
-            warnings: Vec::new(),
+            warnings,
+            shared_cache,
             cancel_flag,
         })
     }
@@ -232,7 +392,8 @@ impl FullSiteScraper {
         // Emit connecting event
         on_progress(FullScrapeProgress {
             project_id: self.project_id.clone(),
-            event_type: "connecting".to_string(),
+            schema_version: events::SCHEMA_VERSION,
+            event_type: FullScrapeEventKind::Connecting,
             current_step: "Connexion au site".to_string(),
             progress_percent: 0.0,
             pages_downloaded: 0,
@@ -278,7 +439,8 @@ impl FullSiteScraper {
             let progress = (self.visited_urls.len() as f32 / max_pages as f32) * 60.0; // 0-60% for pages
             on_progress(FullScrapeProgress {
                 project_id: self.project_id.clone(),
-                event_type: "page_start".to_string(),
+                schema_version: events::SCHEMA_VERSION,
+                event_type: FullScrapeEventKind::PageStart,
                 current_step: "Telechargement des pages".to_string(),
                 progress_percent: progress,
                 pages_downloaded: self.visited_urls.len(),
@@ -294,7 +456,8 @@ impl FullSiteScraper {
                     // Emit page complete event
                     on_progress(FullScrapeProgress {
                         project_id: self.project_id.clone(),
-                        event_type: "page_complete".to_string(),
+                        schema_version: events::SCHEMA_VERSION,
+                        event_type: FullScrapeEventKind::PageComplete,
                         current_step: "Telechargement des pages".to_string(),
                         progress_percent: progress,
                         pages_downloaded: self.visited_urls.len(),
@@ -315,7 +478,8 @@ impl FullSiteScraper {
                     self.errors.push(format!("Erreur sur {}: {}", url, e));
                     on_progress(FullScrapeProgress {
                         project_id: self.project_id.clone(),
-                        event_type: "error".to_string(),
+                        schema_version: events::SCHEMA_VERSION,
+                        event_type: FullScrapeEventKind::Error,
                         current_step: "Telechargement des pages".to_string(),
                         progress_percent: progress,
                         pages_downloaded: self.visited_urls.len(),
@@ -334,11 +498,21 @@ impl FullSiteScraper {
             return Err("Scraping annule par l'utilisateur".to_string());
         }
 
+        if self.config.capture_error_page {
+            self.capture_error_page(output_base);
+        }
+
+        // Browsers request /favicon.ico even without a <link>
+        if self.config.download_images && !self.icons.iter().any(|i| i.rel.split_whitespace().any(|r| r == "icon")) {
+            self.download_default_favicon(output_base);
+        }
+
         // Rewrite URLs in all HTML and CSS files
         if self.config.rewrite_urls {
             on_progress(FullScrapeProgress {
                 project_id: self.project_id.clone(),
-                event_type: "rewriting".to_string(),
+                schema_version: events::SCHEMA_VERSION,
+                event_type: FullScrapeEventKind::Rewriting,
                 current_step: "Reecriture des URLs".to_string(),
                 progress_percent: 70.0,
                 pages_downloaded: self.visited_urls.len(),
@@ -354,7 +528,8 @@ impl FullSiteScraper {
         // Build design system
         on_progress(FullScrapeProgress {
             project_id: self.project_id.clone(),
-            event_type: "analyzing".to_string(),
+            schema_version: events::SCHEMA_VERSION,
+            event_type: FullScrapeEventKind::Analyzing,
             current_step: "Analyse de la charte graphique".to_string(),
             progress_percent: 85.0,
             pages_downloaded: self.visited_urls.len(),
@@ -364,13 +539,38 @@ impl FullSiteScraper {
             message: "Extraction des couleurs et polices...".to_string(),
             bytes_downloaded: self.downloaded_assets.values().map(|a| a.size).sum(),
         });
-        let design_system = self.build_design_system();
+        let mut design_system = self.build_design_system();
+        if self.config.download_fonts {
+            self.audit_fonts(&mut design_system, output_base);
+        }
+        let sitemap_check = if self.config.check_sitemap {
+            self.check_sitemap()
+        } else {
+            None
+        };
+        let css_usage = if self.config.download_css {
+            self.analyze_css_usage(output_base)
+        } else {
+            Vec::new()
+        };
+        let content_inventory = self.build_content_inventory();
+        let gallery_path = if self.config.download_images && self.config.generate_gallery {
+            self.generate_gallery(output_base)
+        } else {
+            None
+        };
+        let image_metadata_path = if self.config.download_images {
+            self.write_image_metadata(output_base)
+        } else {
+            None
+        };
 
         // Generate report
         let report_path = if self.config.generate_report {
             on_progress(FullScrapeProgress {
                 project_id: self.project_id.clone(),
-                event_type: "analyzing".to_string(),
+                schema_version: events::SCHEMA_VERSION,
+                event_type: FullScrapeEventKind::Analyzing,
                 current_step: "Generation du rapport".to_string(),
                 progress_percent: 95.0,
                 pages_downloaded: self.visited_urls.len(),
@@ -380,11 +580,19 @@ impl FullSiteScraper {
                 message: "Generation du rapport...".to_string(),
                 bytes_downloaded: self.downloaded_assets.values().map(|a| a.size).sum(),
             });
-            Some(self.generate_report(output_base, &design_system)?)
+            Some(self.generate_report(output_base, &design_system, &css_usage, sitemap_check.as_ref(), &content_inventory, gallery_path.as_deref())?)
         } else {
             None
         };
 
+        // Remember asset validators so the capture can be refreshed later
+        if let Err(e) = self.write_asset_manifest(output_base) {
+            self.warnings.push(e);
+        }
+        if let Some(Err(e)) = self.shared_cache.as_ref().map(|cache| cache.save()) {
+            self.warnings.push(e);
+        }
+
         // Calculate totals
         let total_size: u64 = self.downloaded_assets.values().map(|a| a.size).sum();
         let index_path = output_base.join("index.html");
@@ -392,7 +600,8 @@ impl FullSiteScraper {
         // Emit complete event
         on_progress(FullScrapeProgress {
             project_id: self.project_id.clone(),
-            event_type: "complete".to_string(),
+            schema_version: events::SCHEMA_VERSION,
+            event_type: FullScrapeEventKind::Complete,
             current_step: "Termine".to_string(),
             progress_percent: 100.0,
             pages_downloaded: self.visited_urls.len(),
@@ -416,7 +625,16 @@ impl FullSiteScraper {
             output_path: output_base.to_string_lossy().to_string(),
             index_path: index_path.to_string_lossy().to_string(),
             design_system,
+            css_usage,
+            redirects: self.redirects.clone(),
+            http_errors: self.http_errors.clone(),
+            error_page_path: self.error_page_path.clone(),
+            sitemap_check,
+            content_inventory,
+            gallery_path,
+            image_metadata_path,
             report_path,
+            shared_cache_hits: self.shared_cache.as_ref().map(|cache| cache.hits()).unwrap_or(0),
             errors: self.errors.clone(),
             warnings: self.warnings.clone(),
         })
@@ -439,14 +657,80 @@ impl FullSiteScraper {
         }
     }
 
-    fn process_page(&mut self, url: &str, output_base: &Path) -> Result<Vec<String>, String> {
-        let response = self.client.get(url).send()
-            .map_err(|e| format!("Request failed: {}", e))?;
+    /// GET a URL, following redirects by hand and returning the hops taken
+    fn fetch(&self, url: &str) -> (Vec<RedirectHop>, Result<reqwest::blocking::Response, String>) {
+        let mut hops: Vec<RedirectHop> = Vec::new();
+        let mut current = url.to_string();
+
+        loop {
+            let response = match self.client.get(&current).send() {
+                Ok(r) => r,
+                Err(e) => return (hops, Err(format!("Request failed: {}", e))),
+            };
+            if !response.status().is_redirection() {
+                return (hops, Ok(response));
+            }
+
+            let next = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|location| response.url().join(location).ok())
+                .map(|u| u.to_string());
+            hops.push(RedirectHop {
+                url: current.clone(),
+                status: response.status().as_u16(),
+            });
+
+            let next = match next {
+                Some(next) => next,
+                None => return (hops, Err(format!("HTTP {} sans en-tete Location", response.status()))),
+            };
+            if next == current || hops.iter().any(|h| h.url == next) {
+                return (hops, Err(format!("Boucle de redirection vers {}", next)));
+            }
+            if hops.len() >= self.config.max_redirects as usize {
+                return (hops, Err(format!("Plus de {} redirections", self.config.max_redirects)));
+            }
+            current = next;
+        }
+    }
+
+    fn process_page(&mut self, requested_url: &str, output_base: &Path) -> Result<Vec<String>, String> {
+        let (hops, response) = self.fetch(requested_url);
+        if !hops.is_empty() {
+            self.redirects.push(RedirectRecord {
+                from: requested_url.to_string(),
+                to: response.as_ref().ok().map(|r| r.url().to_string()),
+                is_loop: matches!(&response, Err(e) if e.starts_with("Boucle")),
+                hops,
+            });
+        }
+        let response = response?;
 
         if !response.status().is_success() {
+            self.http_errors.push(HttpErrorRecord {
+                url: requested_url.to_string(),
+                status: response.status().as_u16(),
+            });
             return Err(format!("HTTP {}", response.status()));
         }
 
+        // A redirect may land on another page of the site: crawl it under its final URL
+        let final_url = response.url().to_string();
+        if final_url != requested_url {
+            if !self.is_same_domain(&final_url) {
+                return Ok(vec![]);
+            }
+            if !self.visited_urls.insert(final_url.clone()) {
+                if let Some(local) = self.url_to_local_path.get(&final_url).cloned() {
+                    self.url_to_local_path.insert(requested_url.to_string(), local);
+                }
+                return Ok(vec![]);
+            }
+        }
+        let url = final_url.as_str();
+
         let content_type = response
             .headers()
             .get("content-type")
@@ -461,11 +745,20 @@ impl FullSiteScraper {
         let html = response.text()
             .map_err(|e| format!("Failed to read response: {}", e))?;
 
-        let document = Html::parse_document(&html);
-        let base_url = Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
-
         // Save HTML file
         let html_path = self.url_to_local_html_path(url, output_base);
+        if url != requested_url {
+            self.url_to_local_path.insert(requested_url.to_string(), html_path.to_string_lossy().to_string());
+        }
+
+        self.process_html(url, &html, html_path, output_base)
+    }
+
+    /// Save a page, download its assets and return the same-domain links it contains
+    fn process_html(&mut self, url: &str, html: &str, html_path: PathBuf, output_base: &Path) -> Result<Vec<String>, String> {
+        let document = Html::parse_document(html);
+        let base_url = Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+
         self.save_content(&html_path, html.as_bytes())?;
 
         let size = html.len() as u64;
@@ -474,8 +767,14 @@ impl FullSiteScraper {
             local_path: html_path.clone(),
             asset_type: AssetType::Html,
             size,
+            etag: None,
+            last_modified: None,
+            captured_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            robots_tag: None,
+            license_url: None,
         });
         self.url_to_local_path.insert(url.to_string(), html_path.to_string_lossy().to_string());
+        self.page_hints.extend(scrape_attribution::page_hints(url, &document, &base_url));
 
         let mut new_urls = Vec::new();
 
@@ -509,6 +808,7 @@ impl FullSiteScraper {
                 let css_content: String = element.text().collect();
                 self.extract_colors_from_css(&css_content);
                 self.extract_fonts_from_css(&css_content);
+                self.collect_font_faces(&css_content, url);
             }
         }
 
@@ -526,6 +826,7 @@ impl FullSiteScraper {
 
         // Download images
         if self.config.download_images {
+            self.image_metadata.extend(image_metadata::page_usages(url, &document, &base_url));
             let img_selector = Selector::parse("img[src]").unwrap();
             for element in document.select(&img_selector) {
                 if let Some(src) = element.value().attr("src") {
@@ -559,24 +860,231 @@ impl FullSiteScraper {
             }
         }
 
+        // Favicons, touch icons and web manifest
+        if self.config.download_images {
+            self.collect_icons(&document, &base_url, output_base);
+        }
+
         // Extract inline colors
         self.extract_inline_colors(&document);
 
         Ok(new_urls)
     }
 
+    fn collect_icons(&mut self, document: &Html, base_url: &Url, output_base: &Path) {
+        let icon_selector = Selector::parse("link[rel][href]").unwrap();
+        for element in document.select(&icon_selector) {
+            let rel = element.value().attr("rel").unwrap_or("").to_lowercase();
+            let href = element.value().attr("href").unwrap_or("");
+            if href.starts_with("data:") {
+                continue;
+            }
+            let absolute_url = match base_url.join(href) {
+                Ok(u) => u.to_string(),
+                Err(_) => continue,
+            };
+
+            if rel.split_whitespace().any(|r| r == "manifest") {
+                self.icon_refs.insert(href.to_string(), absolute_url.clone());
+                self.process_web_manifest(&absolute_url, output_base);
+            } else if rel.split_whitespace().any(|r| r.contains("icon")) {
+                self.icon_refs.insert(href.to_string(), absolute_url.clone());
+                self.download_icon(
+                    &absolute_url,
+                    rel.trim(),
+                    element.value().attr("sizes"),
+                    element.value().attr("type"),
+                    "link",
+                    output_base,
+                );
+            }
+        }
+
+        // Windows tile image
+        let tile_selector = Selector::parse("meta[name='msapplication-TileImage'][content]").unwrap();
+        for element in document.select(&tile_selector) {
+            if let Some(content) = element.value().attr("content") {
+                if let Ok(absolute_url) = base_url.join(content) {
+                    self.icon_refs.insert(content.to_string(), absolute_url.to_string());
+                    self.download_icon(&absolute_url.to_string(), "msapplication-tileimage", None, None, "link", output_base);
+                }
+            }
+        }
+    }
+
+    fn download_icon(
+        &mut self,
+        url: &str,
+        rel: &str,
+        sizes: Option<&str>,
+        mime_type: Option<&str>,
+        source: &str,
+        output_base: &Path,
+    ) -> Option<PathBuf> {
+        if let Some(existing) = self.icons.iter().find(|i| i.url == url) {
+            return Some(output_base.join(&existing.local_path));
+        }
+
+        self.download_asset(url, output_base, AssetType::Image);
+        let local_path = PathBuf::from(self.url_to_local_path.get(url)?);
+
+        self.icons.push(IconInfo {
+            url: url.to_string(),
+            local_path: relative_to_base(&local_path, output_base),
+            rel: rel.to_string(),
+            sizes: sizes.map(String::from),
+            mime_type: mime_type.map(String::from),
+            source: source.to_string(),
+        });
+        Some(local_path)
+    }
+
+    /// Download a web manifest, its icons, and point the manifest at the local copies
+    fn process_web_manifest(&mut self, manifest_url: &str, output_base: &Path) {
+        if self.downloaded_assets.contains_key(manifest_url) {
+            return;
+        }
+        self.download_asset(manifest_url, output_base, AssetType::Other);
+        let manifest_path = match self.url_to_local_path.get(manifest_url) {
+            Some(p) => PathBuf::from(p),
+            None => return,
+        };
+        self.icons.push(IconInfo {
+            url: manifest_url.to_string(),
+            local_path: relative_to_base(&manifest_path, output_base),
+            rel: "manifest".to_string(),
+            sizes: None,
+            mime_type: Some("application/manifest+json".to_string()),
+            source: "link".to_string(),
+        });
+
+        let mut manifest: serde_json::Value = match fs::read_to_string(&manifest_path)
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok())
+        {
+            Some(v) => v,
+            None => {
+                self.warnings.push(format!("Manifest illisible: {}", manifest_url));
+                return;
+            }
+        };
+        let manifest_base = match Url::parse(manifest_url) {
+            Ok(u) => u,
+            Err(_) => return,
+        };
+
+        let icon_entries: Vec<(usize, String, Option<String>, Option<String>)> = manifest
+            .get("icons")
+            .and_then(|v| v.as_array())
+            .map(|icons| {
+                icons
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, icon)| {
+                        let src = icon.get("src")?.as_str()?.to_string();
+                        let sizes = icon.get("sizes").and_then(|v| v.as_str()).map(String::from);
+                        let mime = icon.get("type").and_then(|v| v.as_str()).map(String::from);
+                        Some((i, src, sizes, mime))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        for (index, src, sizes, mime) in icon_entries {
+            let icon_url = match manifest_base.join(&src) {
+                Ok(u) => u.to_string(),
+                Err(_) => continue,
+            };
+            if let Some(local_icon) =
+                self.download_icon(&icon_url, "manifest-icon", sizes.as_deref(), mime.as_deref(), "manifest", output_base)
+            {
+                if self.config.rewrite_urls {
+                    let relative = relative_between(&manifest_path, &local_icon, output_base);
+                    manifest["icons"][index]["src"] = serde_json::Value::String(relative);
+                }
+            }
+        }
+
+        if self.config.rewrite_urls {
+            if let Ok(content) = serde_json::to_string_pretty(&manifest) {
+                fs::write(&manifest_path, content).ok();
+            }
+        }
+    }
+
+    /// Request a page that cannot exist and save the site's error page design
+    fn capture_error_page(&mut self, output_base: &Path) {
+        let probe_url = match self.base_url.join(&format!("/laforge-404-{}", uuid::Uuid::new_v4().simple())) {
+            Ok(u) => u.to_string(),
+            Err(_) => return,
+        };
+
+        let response = match self.fetch(&probe_url).1 {
+            Ok(r) => r,
+            Err(e) => {
+                self.warnings.push(format!("Page 404 non capturee: {}", e));
+                return;
+            }
+        };
+        if response.status().is_success() {
+            self.warnings.push("Le site ne renvoie pas de page 404 (reponse HTTP 200 pour une page inexistante)".to_string());
+            return;
+        }
+        let is_html = response
+            .headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok())
+            .map(|ct| ct.contains("text/html"))
+            .unwrap_or(false);
+        let html = match response.text() {
+            Ok(html) if is_html => html,
+            _ => return,
+        };
+
+        let error_page = output_base.join("404.html");
+        match self.process_html(&probe_url, &html, error_page.clone(), output_base) {
+            Ok(_) => self.error_page_path = Some(error_page.to_string_lossy().to_string()),
+            Err(e) => self.warnings.push(format!("Page 404 non capturee: {}", e)),
+        }
+    }
+
+    fn download_default_favicon(&mut self, output_base: &Path) {
+        if let Ok(favicon_url) = self.base_url.join("/favicon.ico") {
+            let url = favicon_url.to_string();
+            let warnings_before = self.warnings.len();
+            if self.download_icon(&url, "icon", None, Some("image/x-icon"), "default", output_base).is_none() {
+                // Missing default favicon is common; don't report it
+                self.warnings.truncate(warnings_before);
+            }
+        }
+    }
+
     fn download_asset(&mut self, url: &str, output_base: &Path, asset_type: AssetType) {
         if self.downloaded_assets.contains_key(url) {
             return;
         }
 
-        match self.do_download_asset(url, output_base, &asset_type) {
+        let cached = match self.shared_cache.as_mut() {
+            Some(cache) => cache.lookup(url, &self.project_id),
+            None => None,
+        };
+        let from_cache = cached.is_some();
+        let result = match cached {
+            Some(cached) => self.write_asset(url, output_base, &asset_type, &cached.bytes, cached.etag, cached.last_modified),
+            None => self.do_download_asset(url, output_base, &asset_type),
+        };
+
+        match result {
             Ok(asset) => {
+                if !from_cache {
+                    self.store_in_shared_cache(&asset);
+                }
                 // If CSS, also extract fonts and colors
                 if asset_type == AssetType::Css {
                     if let Ok(content) = fs::read_to_string(&asset.local_path) {
                         self.extract_colors_from_css(&content);
                         self.extract_fonts_from_css(&content);
+                        self.collect_font_faces(&content, url);
 
                         // Download font files referenced in CSS
                         if self.config.download_fonts {
@@ -595,17 +1103,33 @@ impl FullSiteScraper {
     }
 
     fn do_download_asset(&self, url: &str, output_base: &Path, asset_type: &AssetType) -> Result<DownloadedAsset, String> {
-        let response = self.client.get(url).send()
-            .map_err(|e| format!("Request failed: {}", e))?;
+        let response = self.fetch(url).1?;
 
         if !response.status().is_success() {
             return Err(format!("HTTP {}", response.status()));
         }
 
+        let (etag, last_modified) = scrape_refresh::response_validators(response.headers());
+        let (robots_tag, license_url) = scrape_attribution::header_hints(response.headers());
         let bytes = response.bytes()
             .map_err(|e| format!("Failed to read bytes: {}", e))?;
 
-        let local_path = self.url_to_local_asset_path(url, output_base, asset_type);
+        let mut asset = self.write_asset(url, output_base, asset_type, &bytes, etag, last_modified)?;
+        asset.robots_tag = robots_tag;
+        asset.license_url = license_url;
+        Ok(asset)
+    }
+
+    fn write_asset(
+        &self,
+        url: &str,
+        output_base: &Path,
+        asset_type: &AssetType,
+        bytes: &[u8],
+        etag: Option<String>,
+        last_modified: Option<String>,
+    ) -> Result<DownloadedAsset, String> {
+        let local_path = self.url_to_local_asset_path(url, output_base, asset_type, bytes);
 
         // Create parent directories if needed
         if let Some(parent) = local_path.parent() {
@@ -615,7 +1139,7 @@ impl FullSiteScraper {
         let mut file = File::create(&local_path)
             .map_err(|e| format!("Failed to create file: {}", e))?;
 
-        file.write_all(&bytes)
+        file.write_all(bytes)
             .map_err(|e| format!("Failed to write file: {}", e))?;
 
         Ok(DownloadedAsset {
@@ -623,9 +1147,27 @@ impl FullSiteScraper {
             local_path,
             asset_type: asset_type.clone(),
             size: bytes.len() as u64,
+            etag,
+            last_modified,
+            captured_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            robots_tag: None,
+            license_url: None,
         })
     }
 
+    fn store_in_shared_cache(&mut self, asset: &DownloadedAsset) {
+        let cache = match self.shared_cache.as_mut() {
+            Some(cache) => cache,
+            None => return,
+        };
+        let stored = fs::read(&asset.local_path)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| cache.store(&asset.original_url, &self.project_id, &bytes, asset.etag.clone(), asset.last_modified.clone()));
+        if let Err(e) = stored {
+            self.warnings.push(format!("Mise en cache partagee impossible pour {}: {}", asset.original_url, e));
+        }
+    }
+
     fn url_to_local_html_path(&self, url: &str, output_base: &Path) -> PathBuf {
         let parsed = Url::parse(url).unwrap_or_else(|_| self.base_url.clone());
         let mut path = parsed.path().trim_start_matches('/').to_string();
@@ -639,9 +1181,9 @@ impl FullSiteScraper {
         output_base.join(sanitize_path(&path))
     }
 
-    fn url_to_local_asset_path(&self, url: &str, output_base: &Path, asset_type: &AssetType) -> PathBuf {
+    fn url_to_local_asset_path(&self, url: &str, output_base: &Path, asset_type: &AssetType, content: &[u8]) -> PathBuf {
         let parsed = Url::parse(url).ok();
-        let filename = parsed
+        let mut filename = parsed
             .as_ref()
             .and_then(|u| u.path_segments())
             .and_then(|mut s| s.next_back())
@@ -649,6 +1191,12 @@ impl FullSiteScraper {
             .map(|s| sanitize_filename(s))
             .unwrap_or_else(|| format!("asset_{}", self.downloaded_assets.len()));
 
+        // Same-named assets from different paths get distinct files,
+        // and the name changes whenever the content does
+        if self.config.hash_asset_names {
+            filename = with_content_hash(&filename, content);
+        }
+
         let dir = output_base.join(asset_type.directory());
         dir.join(filename)
     }
@@ -669,30 +1217,7 @@ impl FullSiteScraper {
     }
 
     fn extract_colors_from_css(&mut self, css: &str) {
-        // Extract hex colors
-        for word in css.split([' ', ';', ':', '{', '}', '\n', '\r', '(', ')', ',']) {
-            let trimmed = word.trim();
-            if trimmed.starts_with('#') && (trimmed.len() == 4 || trimmed.len() == 7) {
-                if trimmed.chars().skip(1).all(|c| c.is_ascii_hexdigit()) {
-                    let normalized = trimmed.to_lowercase();
-                    *self.colors_found.entry(normalized).or_insert(0) += 1;
-                }
-            }
-        }
-
-        // Extract rgb/rgba colors
-        let css_lower = css.to_lowercase();
-        for pattern in ["rgb(", "rgba("] {
-            let mut search_from = 0;
-            while let Some(start) = css_lower[search_from..].find(pattern) {
-                let actual_start = search_from + start;
-                if let Some(end) = css_lower[actual_start..].find(')') {
-                    let color = &css[actual_start..actual_start + end + 1];
-                    *self.colors_found.entry(color.to_string()).or_insert(0) += 1;
-                }
-                search_from = actual_start + 1;
-            }
-        }
+        color_palette::collect_css_colors(css, &mut self.colors_found);
     }
 
     fn extract_fonts_from_css(&mut self, css: &str) {
@@ -873,6 +1398,16 @@ impl FullSiteScraper {
             result = result.replace(&format!("src='{}'", original_url), &format!("src='{}'", relative_path));
         }
 
+        // Icon links are often relative (`/favicon.ico`); rewrite them by raw value
+        for (raw_href, absolute_url) in &self.icon_refs {
+            if let Some(local_path) = self.url_to_local_path.get(absolute_url) {
+                let relative_path = relative_between(current_file, Path::new(local_path), output_base);
+                result = result.replace(&format!("href=\"{}\"", raw_href), &format!("href=\"{}\"", relative_path));
+                result = result.replace(&format!("href='{}'", raw_href), &format!("href='{}'", relative_path));
+                result = result.replace(&format!("content=\"{}\"", raw_href), &format!("content=\"{}\"", relative_path));
+            }
+        }
+
         result
     }
 
@@ -913,18 +1448,20 @@ impl FullSiteScraper {
     }
 
     fn build_design_system(&self) -> DesignSystem {
-        // Sort colors by occurrence
-        let mut sorted_colors: Vec<_> = self.colors_found.iter().collect();
-        sorted_colors.sort_by(|a, b| b.1.cmp(a.1));
+        // Normalize notations and merge near-identical shades
+        let clusters = color_palette::cluster_colors(&self.colors_found);
+        let palette = color_palette::build_palette(&clusters);
+        let contrast = color_palette::contrast_matrix(&color_palette::contrast_colors(&palette));
 
-        let colors: Vec<ColorInfo> = sorted_colors
-            .into_iter()
+        let colors: Vec<ColorInfo> = clusters
+            .iter()
             .take(20)
-            .map(|(hex, count)| ColorInfo {
-                hex: hex.clone(),
-                rgb: hex_to_rgb(hex),
-                usage: guess_color_usage(hex),
-                occurrences: *count,
+            .map(|cluster| ColorInfo {
+                hex: cluster.hex.clone(),
+                rgb: hex_to_rgb(&cluster.hex),
+                usage: color_palette::palette_role(&palette, &cluster.hex).to_string(),
+                occurrences: cluster.occurrences,
+                variants: cluster.variants.clone(),
             })
             .collect();
 
@@ -939,19 +1476,318 @@ impl FullSiteScraper {
                     "system".to_string()
                 },
                 url: self.font_urls.get(name).cloned(),
+                files: Vec::new(),
+                license: font_audit::license_note(name, &[]),
             })
             .collect();
 
         DesignSystem {
             colors,
+            palette,
+            contrast,
             fonts,
             typography: TypographyInfo::default(),
             spacing: Vec::new(),
             breakpoints: Vec::new(),
+            icons: self.icons.clone(),
+        }
+    }
+
+    /// Remember `@font-face` declarations and the weights used by style rules
+    fn collect_font_faces(&mut self, css: &str, css_url: &str) {
+        if let Ok(base) = Url::parse(css_url) {
+            self.font_faces.extend(font_audit::parse_font_faces(css, &base));
+        }
+        font_audit::collect_used_weights(css, &mut self.used_font_weights);
+    }
+
+    /// Attach downloaded font files, used weights and licensing notes to the
+    /// design system, and subset woff2 files when enabled
+    fn audit_fonts(&mut self, design_system: &mut DesignSystem, output_base: &Path) {
+        let page_text = if self.config.subset_fonts {
+            self.collect_page_text()
+        } else {
+            String::new()
+        };
+
+        for face in self.font_faces.clone() {
+            let key = face.family.to_lowercase();
+            let font_index = match design_system.fonts.iter().position(|f| f.family.to_lowercase() == key) {
+                Some(index) => index,
+                None => {
+                    design_system.fonts.push(FontInfo {
+                        family: face.family.clone(),
+                        weights: vec![face.weight.clone()],
+                        source: "custom".to_string(),
+                        url: face.sources.first().cloned(),
+                        files: Vec::new(),
+                        license: font_audit::license_note(&face.family, &face.sources),
+                    });
+                    design_system.fonts.len() - 1
+                }
+            };
+            let font = &mut design_system.fonts[font_index];
+            font.license = font_audit::license_note(&font.family, &face.sources);
+
+            // Only sources that were actually downloaded
+            let asset = match face.sources.iter().find_map(|src| self.downloaded_assets.get(src)) {
+                Some(asset) => asset.clone(),
+                None => continue,
+            };
+            let file = relative_to_base(&asset.local_path, output_base);
+            if font.files.iter().any(|f| f.file == file) {
+                continue;
+            }
+
+            let used = self.used_font_weights.get(&key).map(|weights| weights.contains(&face.weight));
+            let mut file_info = FontFileInfo {
+                file,
+                format: font_audit::detect_format(&asset.local_path),
+                weight: face.weight.clone(),
+                style: face.style.clone(),
+                size: asset.size,
+                used,
+                subset_file: None,
+                subset_size: None,
+            };
+
+            if self.config.subset_fonts && file_info.format == "woff2" && used != Some(false) && !page_text.is_empty() {
+                match font_audit::subset_woff2(&asset.local_path, &page_text) {
+                    Ok(subset_path) => {
+                        file_info.subset_size = fs::metadata(&subset_path).ok().map(|m| m.len());
+                        file_info.subset_file = Some(relative_to_base(&subset_path, output_base));
+                    }
+                    Err(e) => self.warnings.push(format!("Subset impossible pour {}: {}", file_info.file, e)),
+                }
+            }
+            font.files.push(file_info);
+        }
+
+        for font in design_system.fonts.iter().filter(|f| f.license.status == "commercial") {
+            self.warnings.push(format!(
+                "Police sous licence commerciale: {} - ne pas redistribuer sans licence",
+                font.family
+            ));
+        }
+    }
+
+    /// Gallery of the downloaded images; failures become warnings
+    fn generate_gallery(&mut self, output_base: &Path) -> Option<String> {
+        let images: Vec<(PathBuf, String)> = self.downloaded_assets
+            .values()
+            .filter(|a| a.asset_type == AssetType::Image)
+            .map(|a| (a.local_path.clone(), a.original_url.clone()))
+            .collect();
+        if images.is_empty() {
+            return None;
+        }
+        match image_gallery::generate_gallery(output_base, &self.config.url, &images) {
+            Ok(gallery) => Some(gallery.path),
+            Err(e) => {
+                self.warnings.push(e);
+                None
+            }
+        }
+    }
+
+    /// Alt text sidecar of the downloaded images; failures become warnings
+    fn write_image_metadata(&mut self, output_base: &Path) -> Option<String> {
+        let images: Vec<(PathBuf, String)> = self.downloaded_assets
+            .values()
+            .filter(|a| a.asset_type == AssetType::Image)
+            .map(|a| (a.local_path.clone(), a.original_url.clone()))
+            .collect();
+        let images_dir = output_base.join(AssetType::Image.directory());
+        match self.image_metadata.write(&images_dir, &images) {
+            Ok(path) => path,
+            Err(e) => {
+                self.warnings.push(e);
+                None
+            }
+        }
+    }
+
+    /// Per-page word and image counts of the captured pages, plus the pages in error
+    fn build_content_inventory(&self) -> ContentInventory {
+        let pages = self.downloaded_assets
+            .values()
+            .filter(|a| a.asset_type == AssetType::Html)
+            .filter_map(|a| {
+                let html = fs::read_to_string(&a.local_path).ok()?;
+                Some(content_inventory::page_inventory_from_html(&a.original_url, &html))
+            })
+            .chain(self.http_errors.iter().map(|e| content_inventory::error_page(&e.url, e.status)))
+            .collect();
+        ContentInventory::from_pages(pages)
+    }
+
+    /// Every distinct character of the captured pages' text
+    fn collect_page_text(&self) -> String {
+        let mut chars: Vec<char> = self.downloaded_assets
+            .values()
+            .filter(|a| a.asset_type == AssetType::Html)
+            .filter_map(|a| fs::read_to_string(&a.local_path).ok())
+            .flat_map(|html| Html::parse_document(&html).root_element().text().collect::<String>().chars().collect::<Vec<_>>())
+            .filter(|c| !c.is_control())
+            .collect::<HashSet<char>>()
+            .into_iter()
+            .collect();
+        chars.sort_unstable();
+        chars.into_iter().collect()
+    }
+
+    /// `Disallow` rules of robots.txt, for the attribution summary
+    fn robots_disallow(&self) -> Vec<String> {
+        self.base_url.join("/robots.txt").ok()
+            .and_then(|u| self.fetch(u.as_str()).1.ok())
+            .filter(|r| r.status().is_success())
+            .and_then(|r| r.text().ok())
+            .map(|robots| scrape_attribution::robots_disallow(&robots))
+            .unwrap_or_default()
+    }
+
+    /// Save the downloaded assets with their ETag/Last-Modified headers and attribution hints
+    fn write_asset_manifest(&self, output_base: &Path) -> Result<(), String> {
+        let mut assets: Vec<AssetManifestEntry> = self.downloaded_assets
+            .values()
+            .filter(|a| a.asset_type != AssetType::Html)
+            .map(|a| AssetManifestEntry {
+                url: a.original_url.clone(),
+                local_path: relative_to_base(&a.local_path, output_base),
+                asset_type: a.asset_type.manifest_name().to_string(),
+                etag: a.etag.clone(),
+                last_modified: a.last_modified.clone(),
+                size: a.size,
+                captured_at: Some(a.captured_at.clone()),
+                robots_tag: a.robots_tag.clone(),
+                license_url: a.license_url.clone(),
+            })
+            .collect();
+        assets.sort_by(|a, b| a.local_path.cmp(&b.local_path));
+
+        AssetManifest {
+            site_url: self.config.url.clone(),
+            rewrite_urls: self.config.rewrite_urls,
+            updated_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            assets,
+            // Files added by hand survive a new scrape into the same folder
+            manual_assets: AssetManifest::load(output_base)
+                .map(|previous| previous.manual_assets)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|a| output_base.join(&a.local_path).is_file())
+                .collect(),
+            attribution: Some(SiteAttribution {
+                captured_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                robots_disallow: self.robots_disallow(),
+                pages: self.page_hints.clone(),
+            }),
         }
+        .save(output_base)
+    }
+
+    /// Match every downloaded stylesheet against the captured pages
+    fn analyze_css_usage(&self, output_base: &Path) -> Vec<StylesheetUsage> {
+        let documents: Vec<Html> = self.downloaded_assets
+            .values()
+            .filter(|a| a.asset_type == AssetType::Html)
+            .filter_map(|a| fs::read_to_string(&a.local_path).ok())
+            .map(|html| Html::parse_document(&html))
+            .collect();
+
+        let mut usage: Vec<StylesheetUsage> = self.downloaded_assets
+            .values()
+            .filter(|a| a.asset_type == AssetType::Css)
+            .filter_map(|a| {
+                let css = fs::read_to_string(&a.local_path).ok()?;
+                let name = relative_to_base(&a.local_path, output_base);
+                Some(css_usage::analyze_stylesheet(&name, &css, &documents))
+            })
+            .collect();
+        usage.sort_by(|a, b| b.unused_percent.total_cmp(&a.unused_percent));
+        usage
     }
 
-    fn generate_report(&self, output_base: &Path, design_system: &DesignSystem) -> Result<String, String> {
+    /// Find pages listed in the sitemap that link-following never reached
+    fn check_sitemap(&mut self) -> Option<SitemapCheck> {
+        // Sitemaps declared in robots.txt, falling back to /sitemap.xml
+        let mut pending: Vec<String> = self.base_url.join("/robots.txt").ok()
+            .and_then(|u| self.fetch(u.as_str()).1.ok())
+            .filter(|r| r.status().is_success())
+            .and_then(|r| r.text().ok())
+            .map(|robots| {
+                robots.lines()
+                    .filter_map(|line| {
+                        let (key, value) = line.split_once(':')?;
+                        if key.trim().eq_ignore_ascii_case("sitemap") {
+                            Some(value.trim().to_string())
+                        } else {
+                            None
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        if pending.is_empty() {
+            pending.push(self.base_url.join("/sitemap.xml").ok()?.to_string());
+        }
+
+        let mut sitemaps = Vec::new();
+        let mut page_urls: HashSet<String> = HashSet::new();
+        while let Some(sitemap_url) = pending.pop() {
+            if sitemaps.len() >= MAX_SITEMAP_FILES || sitemaps.contains(&sitemap_url) {
+                continue;
+            }
+            let xml = match self.fetch(&sitemap_url).1 {
+                Ok(r) if r.status().is_success() => r.text().unwrap_or_default(),
+                _ => continue,
+            };
+            sitemaps.push(sitemap_url);
+
+            // A sitemap index lists other sitemaps
+            if xml.contains("<sitemapindex") {
+                pending.extend(extract_xml_locs(&xml));
+            } else {
+                page_urls.extend(extract_xml_locs(&xml).into_iter().filter(|u| self.is_same_domain(u)));
+            }
+        }
+
+        if sitemaps.is_empty() {
+            self.warnings.push("Aucun sitemap.xml trouve - verification de completude impossible".to_string());
+            return None;
+        }
+
+        let captured_urls: HashSet<String> = self.visited_urls.iter()
+            .chain(self.redirects.iter().filter_map(|r| r.to.as_ref()))
+            .map(|u| normalize_page_url(u))
+            .collect();
+
+        let mut missing: Vec<String> = page_urls.iter()
+            .filter(|u| !captured_urls.contains(&normalize_page_url(u)))
+            .cloned()
+            .collect();
+        missing.sort();
+
+        let total = page_urls.len();
+        let captured = total - missing.len();
+        Some(SitemapCheck {
+            sitemaps,
+            total_in_sitemap: total,
+            captured,
+            missing,
+            coverage_percent: if total == 0 { 100.0 } else { captured as f32 / total as f32 * 100.0 },
+        })
+    }
+
+    fn generate_report(
+        &self,
+        output_base: &Path,
+        design_system: &DesignSystem,
+        css_usage: &[StylesheetUsage],
+        sitemap_check: Option<&SitemapCheck>,
+        content_inventory: &ContentInventory,
+        gallery_path: Option<&str>,
+    ) -> Result<String, String> {
         let report_path = output_base.join("scraping_report.md");
 
         let mut report = String::new();
@@ -964,33 +1800,154 @@ impl FullSiteScraper {
         report.push_str(&format!("- **Assets telecharges:** {}\n", self.downloaded_assets.len()));
 
         let total_size: u64 = self.downloaded_assets.values().map(|a| a.size).sum();
-        report.push_str(&format!("- **Taille totale:** {}\n\n", format_bytes(total_size)));
+        report.push_str(&format!("- **Taille totale:** {}\n", format_bytes(total_size)));
+        if let Some(gallery) = gallery_path {
+            report.push_str(&format!("- **Galerie d'images:** `{}`\n", relative_to_base(Path::new(gallery), output_base)));
+        }
+        report.push_str("\n");
+
+        if content_inventory.total_pages > 0 {
+            report.push_str(&content_inventory.to_markdown());
+        }
 
         // Design System
         report.push_str("## Charte Graphique\n\n");
 
+        report.push_str("### Palette\n\n");
+        let palette = &design_system.palette;
+        report.push_str(&format!("- **Principale:** {}\n", palette.primary.as_deref().map(|c| format!("`{}`", c)).unwrap_or_else(|| "-".to_string())));
+        report.push_str(&format!("- **Secondaires:** {}\n", format_color_list(&palette.secondary)));
+        report.push_str(&format!("- **Accents:** {}\n", format_color_list(&palette.accents)));
+        report.push_str(&format!("- **Neutres:** {}\n\n", format_color_list(&palette.neutrals)));
+
         report.push_str("### Couleurs\n\n");
-        report.push_str("| Couleur | Hex | Occurrences | Usage |\n");
-        report.push_str("|---------|-----|-------------|-------|\n");
+        report.push_str("| Couleur | Hex | Occurrences | Usage | Nuances fusionnees |\n");
+        report.push_str("|---------|-----|-------------|-------|--------------------|\n");
         for color in &design_system.colors {
-            report.push_str(&format!("| {} | `{}` | {} | {} |\n",
+            report.push_str(&format!("| {} | `{}` | {} | {} | {} |\n",
                 color_square(&color.hex),
                 color.hex,
                 color.occurrences,
-                color.usage
+                color.usage,
+                color.variants.join(", ")
             ));
         }
         report.push_str("\n");
 
+        if !design_system.contrast.is_empty() {
+            let contrast_colors = color_palette::contrast_colors(&design_system.palette);
+            let failing = design_system.contrast.iter().filter(|p| !p.aa_large).count();
+            report.push_str("### Contraste (WCAG 2.1)\n\n");
+            report.push_str("Texte en ligne, fond en colonne. AA: 4.5:1 (texte courant), AA large: 3:1 (texte de 24px ou 18.66px gras), AAA: 7:1.\n\n");
+            report.push_str(&color_palette::contrast_table(&contrast_colors, &design_system.contrast));
+            report.push_str(&format!("\n{} combinaison(s) inutilisable(s) pour du texte sur {}\n\n",
+                failing,
+                design_system.contrast.len()
+            ));
+        }
+
         report.push_str("### Polices\n\n");
         for font in &design_system.fonts {
             report.push_str(&format!("- **{}** ({})\n", font.family, font.source));
             if !font.weights.is_empty() {
                 report.push_str(&format!("  - Graisses: {}\n", font.weights.join(", ")));
             }
+            if font.license.status != "unknown" || !font.files.is_empty() {
+                let status = if font.license.status == "commercial" {
+                    "**commercial - ne pas redistribuer**".to_string()
+                } else {
+                    font.license.status.clone()
+                };
+                report.push_str(&format!("  - Licence: {} ({})\n", status, font.license.reason));
+            }
+            for file in &font.files {
+                let usage = match file.used {
+                    Some(true) => "utilisee",
+                    Some(false) => "non utilisee",
+                    None => "usage inconnu",
+                };
+                let subset = match (&file.subset_file, file.subset_size) {
+                    (Some(path), Some(size)) => format!(", subset `{}` ({})", path, format_bytes(size)),
+                    _ => String::new(),
+                };
+                report.push_str(&format!("  - `{}` {} {} {} - {}, {}{}\n",
+                    file.file, file.format, file.weight, file.style, format_bytes(file.size), usage, subset
+                ));
+            }
         }
         report.push_str("\n");
 
+        if !design_system.icons.is_empty() {
+            report.push_str("### Icones\n\n");
+            report.push_str("| Type | Tailles | Fichier | Source |\n");
+            report.push_str("|------|---------|---------|--------|\n");
+            for icon in &design_system.icons {
+                report.push_str(&format!("| {} | {} | `{}` | {} |\n",
+                    icon.rel,
+                    icon.sizes.as_deref().unwrap_or("-"),
+                    icon.local_path,
+                    icon.source
+                ));
+            }
+            report.push_str("\n");
+        }
+
+        if !css_usage.is_empty() {
+            report.push_str("## Utilisation du CSS\n\n");
+            report.push_str("| Feuille de style | Regles | Utilisees | CSS inutilise |\n");
+            report.push_str("|------------------|--------|-----------|---------------|\n");
+            for sheet in css_usage {
+                report.push_str(&format!("| `{}` | {} | {} | {:.0}% |\n",
+                    sheet.stylesheet,
+                    sheet.total_rules,
+                    sheet.used_rules,
+                    sheet.unused_percent
+                ));
+            }
+            report.push_str("\n_Estimation par correspondance des selecteurs sur les pages capturees (etats :hover/:focus inclus)._\n\n");
+        }
+
+        if !self.redirects.is_empty() {
+            report.push_str("## Redirections\n\n");
+            report.push_str("| Ancienne URL | Nouvelle URL | Chaine |\n");
+            report.push_str("|--------------|--------------|--------|\n");
+            for redirect in &self.redirects {
+                let chain = redirect.hops.iter().map(|h| h.status.to_string()).collect::<Vec<_>>().join(" > ");
+                let target = match (&redirect.to, redirect.is_loop) {
+                    (_, true) => "**boucle de redirection**".to_string(),
+                    (Some(to), _) => to.clone(),
+                    (None, _) => "-".to_string(),
+                };
+                report.push_str(&format!("| {} | {} | {} |\n", redirect.from, target, chain));
+            }
+            report.push_str("\n");
+        }
+
+        if !self.http_errors.is_empty() {
+            report.push_str("## Erreurs HTTP\n\n");
+            for error in &self.http_errors {
+                report.push_str(&format!("- `{}` {}\n", error.status, error.url));
+            }
+            report.push_str("\n");
+        }
+
+        if let Some(error_page) = &self.error_page_path {
+            report.push_str(&format!("**Page 404 capturee:** `{}`\n\n", relative_to_base(Path::new(error_page), output_base)));
+        }
+
+        if let Some(check) = sitemap_check {
+            report.push_str("## Completude (sitemap)\n\n");
+            report.push_str(&format!("- **Pages dans le sitemap:** {}\n", check.total_in_sitemap));
+            report.push_str(&format!("- **Pages capturees:** {} ({:.0}%)\n\n", check.captured, check.coverage_percent));
+            if !check.missing.is_empty() {
+                report.push_str("Pages du sitemap non atteintes par les liens:\n\n");
+                for url in &check.missing {
+                    report.push_str(&format!("- {}\n", url));
+                }
+                report.push_str("\n");
+            }
+        }
+
         // Pages downloaded
         report.push_str("## Pages Telechargees\n\n");
         for url in &self.visited_urls {
@@ -1036,6 +1993,65 @@ fn sanitize_filename(name: &str) -> String {
         .collect()
 }
 
+/// Insert a short content hash before the extension: `style.css` -> `style.a1b2c3d4.css`
+fn with_content_hash(filename: &str, content: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    let hash = format!("{:x}", Sha256::digest(content));
+    let short = &hash[..8];
+
+    match filename.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!("{}.{}.{}", stem, short, ext),
+        _ => format!("{}.{}", filename, short),
+    }
+}
+
+/// Values of all `<loc>` elements of a sitemap document
+fn extract_xml_locs(xml: &str) -> Vec<String> {
+    let mut locs = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find("<loc>") {
+        rest = &rest[start + 5..];
+        match rest.find("</loc>") {
+            Some(end) => {
+                let value = rest[..end].trim()
+                    .trim_start_matches("<![CDATA[")
+                    .trim_end_matches("]]>")
+                    .replace("&amp;", "&");
+                if !value.is_empty() {
+                    locs.push(value);
+                }
+                rest = &rest[end + 6..];
+            }
+            None => break,
+        }
+    }
+    locs
+}
+
+/// Compare page URLs regardless of fragment and trailing slash
+fn normalize_page_url(url: &str) -> String {
+    let without_fragment = url.split('#').next().unwrap_or(url);
+    without_fragment.trim_end_matches('/').to_lowercase()
+}
+
+/// Path of a file relative to the scrape output folder, with `/` separators
+fn relative_to_base(path: &Path, output_base: &Path) -> String {
+    path.strip_prefix(output_base)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Relative link from one file to another, both inside the output folder
+fn relative_between(from_file: &Path, to: &Path, output_base: &Path) -> String {
+    let depth = from_file
+        .parent()
+        .and_then(|dir| dir.strip_prefix(output_base).ok())
+        .map(|dir| dir.components().count())
+        .unwrap_or(0);
+    format!("{}{}", "../".repeat(depth), relative_to_base(to, output_base))
+}
+
 fn sanitize_path(path: &str) -> String {
     path.replace("..", "_")
         .chars()
@@ -1068,23 +2084,11 @@ fn hex_to_rgb(hex: &str) -> Option<String> {
     }
 }
 
-fn guess_color_usage(hex: &str) -> String {
-    let hex = hex.trim_start_matches('#').to_lowercase();
-    if hex.len() < 3 {
-        return "unknown".to_string();
-    }
-
-    // Very simplified color analysis
-    if hex == "fff" || hex == "ffffff" {
-        "background".to_string()
-    } else if hex == "000" || hex == "000000" {
-        "text".to_string()
-    } else if hex.starts_with("f") || hex.starts_with("e") {
-        "background-light".to_string()
-    } else if hex.starts_with("0") || hex.starts_with("1") || hex.starts_with("2") || hex.starts_with("3") {
-        "text-dark".to_string()
+fn format_color_list(colors: &[String]) -> String {
+    if colors.is_empty() {
+        "-".to_string()
     } else {
-        "accent".to_string()
+        colors.iter().map(|c| format!("`{}`", c)).collect::<Vec<_>>().join(", ")
     }
 }
 
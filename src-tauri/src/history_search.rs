@@ -0,0 +1,345 @@
+//! History Search Module
+//!
+//! One search over the history of every project: sync runs (and the files
+//! they sent), snapshot messages, titles of the pages of site captures and
+//! the other activity entries. Every word of the query must appear in a hit,
+//! case-insensitive; hits come back newest first with their project.
+
+use crate::activity_feed::{self, ActivityEvent};
+use crate::scrape_capture::ProjectCaptures;
+use crate::version_history;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+const DEFAULT_LIMIT: usize = 100;
+
+/// Bytes read from a captured page to find its title
+const TITLE_SCAN_BYTES: usize = 16 * 1024;
+
+/// Matching sync paths listed in a hit
+const MAX_MATCHED_PATHS: usize = 10;
+
+pub const HIT_KINDS: [&str; 4] = ["sync", "snapshot", "page", "activity"];
+
+/// Project searched, as known by the frontend
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchProject {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct SearchOptions {
+    /// Restrict to some of HIT_KINDS
+    pub kinds: Option<Vec<String>>,
+    /// Only hits at or after this timestamp (RFC 3339 or YYYY-MM-DD)
+    pub since: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum SearchHitDetail {
+    /// Sync run, with the sent files matching the query
+    Sync {
+        event_id: String,
+        status: Option<String>,
+        matched_paths: Vec<String>,
+        snapshot_id: Option<String>,
+    },
+    Snapshot {
+        snapshot_id: String,
+        files_count: usize,
+    },
+    /// Page of a site capture, `path` relative to the project
+    Page {
+        url: String,
+        path: String,
+        capture_id: String,
+    },
+    /// Any other activity entry (scrape, schedule, inbox, timer...)
+    Activity {
+        event_id: String,
+        activity_kind: String,
+        status: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub project_id: String,
+    pub project_name: String,
+    pub title: String,
+    pub snippet: Option<String>,
+    pub timestamp: String,
+    #[serde(flatten)]
+    pub detail: SearchHitDetail,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResults {
+    pub hits: Vec<SearchHit>,
+    /// Hits found before the limit was applied
+    pub total: usize,
+}
+
+fn terms_of(query: &str) -> Vec<String> {
+    query.split_whitespace().map(|t| t.to_lowercase()).collect()
+}
+
+fn matches(terms: &[String], haystack: &str) -> bool {
+    let haystack = haystack.to_lowercase();
+    terms.iter().all(|t| haystack.contains(t.as_str()))
+}
+
+fn event_paths(event: &ActivityEvent) -> Vec<String> {
+    event
+        .data
+        .get("paths")
+        .and_then(|p| p.as_array())
+        .map(|paths| paths.iter().filter_map(|p| p.as_str().map(String::from)).collect())
+        .unwrap_or_default()
+}
+
+fn activity_hit(project: &SearchProject, event: ActivityEvent, terms: &[String]) -> Option<SearchHit> {
+    let paths = event_paths(&event);
+    let text = format!("{} {} {}", event.title, event.detail.as_deref().unwrap_or(""), paths.join(" "));
+    if !matches(terms, &text) {
+        return None;
+    }
+    let detail = if event.kind == "sync" {
+        // A path matches on the terms not already found in the title/detail
+        let header = format!("{} {}", event.title, event.detail.as_deref().unwrap_or("")).to_lowercase();
+        let path_terms: Vec<String> = terms.iter().filter(|t| !header.contains(t.as_str())).cloned().collect();
+        let matched_paths = if path_terms.is_empty() {
+            Vec::new()
+        } else {
+            paths.into_iter().filter(|p| matches(&path_terms, p)).take(MAX_MATCHED_PATHS).collect()
+        };
+        SearchHitDetail::Sync {
+            event_id: event.id,
+            status: event.status,
+            matched_paths,
+            snapshot_id: event.data.get("snapshotId").and_then(|s| s.as_str()).map(String::from),
+        }
+    } else {
+        SearchHitDetail::Activity {
+            event_id: event.id,
+            activity_kind: event.kind,
+            status: event.status,
+        }
+    };
+    Some(SearchHit {
+        project_id: project.id.clone(),
+        project_name: project.name.clone(),
+        title: event.title,
+        snippet: event.detail,
+        timestamp: event.timestamp,
+        detail,
+    })
+}
+
+/// `<title>` of an HTML page, looked for in its first bytes
+fn page_title(path: &Path) -> Option<String> {
+    let bytes = fs::read(path).ok()?;
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(TITLE_SCAN_BYTES)]).to_string();
+    // ASCII lowercasing keeps the byte offsets of `head`
+    let lower = head.to_ascii_lowercase();
+    let open = lower.find("<title")?;
+    let start = open + lower[open..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    let title = head[start..end].split_whitespace().collect::<Vec<_>>().join(" ");
+    if title.is_empty() {
+        None
+    } else {
+        Some(title)
+    }
+}
+
+fn page_hits(project: &SearchProject, terms: &[String], hits: &mut Vec<SearchHit>) {
+    let root = Path::new(&project.path);
+    for capture in ProjectCaptures::load(&project.path).captures {
+        let capture_dir = root.join(&capture.relative_path);
+        for entry in walkdir::WalkDir::new(&capture_dir).into_iter().filter_map(|e| e.ok()) {
+            let is_html = entry
+                .path()
+                .extension()
+                .map(|ext| ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm"))
+                .unwrap_or(false);
+            if !entry.file_type().is_file() || !is_html {
+                continue;
+            }
+            let title = match page_title(entry.path()) {
+                Some(title) => title,
+                None => continue,
+            };
+            let relative = entry.path().strip_prefix(root).unwrap_or(entry.path()).to_string_lossy().replace('\\', "/");
+            let page = entry
+                .path()
+                .strip_prefix(&capture_dir)
+                .map(|p| p.to_string_lossy().replace('\\', "/"))
+                .unwrap_or_default();
+            if !matches(terms, &format!("{} {} {}", title, capture.domain, page)) {
+                continue;
+            }
+            hits.push(SearchHit {
+                project_id: project.id.clone(),
+                project_name: project.name.clone(),
+                title,
+                snippet: Some(format!("{}/{}", capture.domain, page)),
+                timestamp: capture.captured_at.clone(),
+                detail: SearchHitDetail::Page {
+                    url: capture.url.clone(),
+                    path: relative,
+                    capture_id: capture.id.clone(),
+                },
+            });
+        }
+    }
+}
+
+fn snapshot_hits(app_data_dir: &Path, project: &SearchProject, terms: &[String], hits: &mut Vec<SearchHit>) {
+    let history = match version_history::load_history(app_data_dir, &project.id) {
+        Ok(history) => history,
+        Err(e) => {
+            println!("[HistorySearch] Skipping snapshots of {}: {}", project.id, e);
+            return;
+        }
+    };
+    for snapshot in history.snapshots {
+        let message = match snapshot.message {
+            Some(message) if matches(terms, &message) => message,
+            _ => continue,
+        };
+        hits.push(SearchHit {
+            project_id: project.id.clone(),
+            project_name: project.name.clone(),
+            title: message,
+            snippet: Some(format!("{} fichier(s)", snapshot.files_count)),
+            timestamp: snapshot.timestamp,
+            detail: SearchHitDetail::Snapshot {
+                snapshot_id: snapshot.id,
+                files_count: snapshot.files_count,
+            },
+        });
+    }
+}
+
+fn hit_kind(hit: &SearchHit) -> &'static str {
+    match hit.detail {
+        SearchHitDetail::Sync { .. } => "sync",
+        SearchHitDetail::Snapshot { .. } => "snapshot",
+        SearchHitDetail::Page { .. } => "page",
+        SearchHitDetail::Activity { .. } => "activity",
+    }
+}
+
+/// Search the history of `projects`
+pub fn search(app_data_dir: &Path, projects: &[SearchProject], query: &str, options: &SearchOptions) -> Result<SearchResults, String> {
+    let terms = terms_of(query);
+    if terms.is_empty() {
+        return Err("Recherche vide".to_string());
+    }
+    if let Some(kind) = options.kinds.iter().flatten().find(|k| !HIT_KINDS.contains(&k.as_str())) {
+        return Err(format!("Type de resultat inconnu: {}", kind));
+    }
+    let wanted = |kind: &str| options.kinds.as_ref().map(|k| k.iter().any(|w| w == kind)).unwrap_or(true);
+
+    let mut hits = Vec::new();
+    for project in projects {
+        if wanted("sync") || wanted("activity") {
+            for event in activity_feed::load(app_data_dir, &project.id) {
+                hits.extend(activity_hit(project, event, &terms));
+            }
+        }
+        if wanted("snapshot") {
+            snapshot_hits(app_data_dir, project, &terms, &mut hits);
+        }
+        if wanted("page") {
+            page_hits(project, &terms, &mut hits);
+        }
+    }
+
+    // Timestamps are all ISO 8601 UTC, so they compare as strings
+    hits.retain(|hit| wanted(hit_kind(hit)) && options.since.as_deref().map(|since| hit.timestamp.as_str() >= since).unwrap_or(true));
+    hits.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    let total = hits.len();
+    hits.truncate(options.limit.unwrap_or(DEFAULT_LIMIT).max(1));
+    Ok(SearchResults { hits, total })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scrape_capture::CaptureRecord;
+    use crate::test_support::TempDir;
+
+    fn event(kind: &str, title: &str, timestamp: &str, data: serde_json::Value) -> ActivityEvent {
+        ActivityEvent {
+            timestamp: timestamp.to_string(),
+            ..activity_feed::new_event("shop", kind, title, None, Some("success"), data)
+        }
+    }
+
+    #[test]
+    fn test_every_word_matches_across_sources() {
+        let data = TempDir::new("history-search-data");
+        let project_dir = TempDir::new("history-search-project");
+        project_dir.write_files(&[
+            ("refs/example.com/index.html", "<html><head><title>\n  Panier   boutique </title></head></html>"),
+            ("refs/example.com/contact.html", "<p>No title</p>"),
+        ]);
+        let mut captures = ProjectCaptures::default();
+        captures.register(CaptureRecord {
+            id: "capture".to_string(),
+            url: "https://example.com/".to_string(),
+            domain: "example.com".to_string(),
+            relative_path: "refs/example.com".to_string(),
+            captured_at: "2026-03-03T09:00:00Z".to_string(),
+            pages_downloaded: 2,
+            assets_downloaded: 0,
+            total_size_bytes: 0,
+            snapshot_id: None,
+        });
+        captures.save(&project_dir.path_str()).unwrap();
+        let paths = serde_json::json!({ "paths": ["css/panier.css", "js/panier.js", "index.html"] });
+        for event in [
+            event("sync", "Synchronisation boutique", "2026-03-01T10:00:00Z", paths),
+            event("timer", "Session panier", "2026-03-02T10:00:00Z", serde_json::Value::Null),
+        ] {
+            activity_feed::append(data.path(), &event).unwrap();
+        }
+        let projects = [SearchProject {
+            id: "shop".to_string(),
+            name: "Boutique".to_string(),
+            path: project_dir.path_str(),
+        }];
+
+        let results = search(data.path(), &projects, "PANIER", &SearchOptions::default()).unwrap();
+        let titles: Vec<&str> = results.hits.iter().map(|h| h.title.as_str()).collect();
+        assert_eq!(titles, ["Panier boutique", "Session panier", "Synchronisation boutique"]);
+        match &results.hits[2].detail {
+            SearchHitDetail::Sync { matched_paths, .. } => {
+                assert_eq!(matched_paths, &["css/panier.css", "js/panier.js"])
+            }
+            other => panic!("unexpected hit {:?}", other),
+        }
+
+        // Words found in the title don't select paths; filters by kind and date
+        let options = SearchOptions {
+            kinds: Some(vec!["sync".to_string()]),
+            since: Some("2026-03-01".to_string()),
+            limit: None,
+        };
+        let syncs = search(data.path(), &projects, "boutique js", &options).unwrap();
+        assert_eq!(syncs.total, 1);
+        let options = SearchOptions { since: Some("2026-03-02".to_string()), ..options };
+        assert_eq!(search(data.path(), &projects, "boutique", &options).unwrap().total, 0);
+
+        assert_eq!(search(data.path(), &projects, "  ", &SearchOptions::default()).unwrap_err(), "Recherche vide");
+        let unknown = SearchOptions { kinds: Some(vec!["mail".to_string()]), ..Default::default() };
+        assert!(search(data.path(), &projects, "panier", &unknown).is_err());
+    }
+}
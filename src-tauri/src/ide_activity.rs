@@ -0,0 +1,209 @@
+//! IDE Activity Module
+//!
+//! Background monitor combining the IDE monitor with time tracking:
+//! suggests starting a timer when a registered project is open in an IDE
+//! and no timer runs for it, optionally auto-starts it after a delay of
+//! continuous activity, and auto-pauses it when the IDE closes the project.
+//! Timers live in the frontend, which keeps the running set up to date.
+
+use crate::ide_monitor;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::Manager;
+
+/// Monitoring settings (mirrors the frontend `IDEMonitoringSettings`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdeActivityConfig {
+    pub ides: Vec<String>,
+    #[serde(default = "default_interval")]
+    pub check_interval_secs: u64,
+    /// Start the timer automatically after this many minutes of activity
+    #[serde(default)]
+    pub auto_start_after_minutes: Option<u64>,
+    #[serde(default = "default_true")]
+    pub auto_pause: bool,
+}
+
+fn default_interval() -> u64 { 5 }
+fn default_true() -> bool { true }
+
+/// Event emitted on `suggest-timer-start`, `timer-auto-start` and `timer-auto-pause`
+#[derive(Debug, Clone, Serialize)]
+pub struct IdeActivityEvent {
+    pub project_id: String,
+    pub ide: String,
+    pub open_since: String,
+    pub timestamp: u64,
+}
+
+/// A watched project currently open in an IDE
+struct OpenProject {
+    ide: String,
+    since: Instant,
+    since_label: String,
+    suggested: bool,
+    auto_started: bool,
+}
+
+#[derive(Default)]
+struct ActivityState {
+    running: bool,
+    config: Option<IdeActivityConfig>,
+    /// Project ids (project folder paths) to watch
+    projects: Vec<String>,
+    /// Projects with a running timer, as reported by the frontend
+    running_timers: HashSet<String>,
+    open: HashMap<String, OpenProject>,
+}
+
+static ACTIVITY_STATE: Lazy<Mutex<ActivityState>> = Lazy::new(|| Mutex::new(ActivityState::default()));
+
+/// Start (or reconfigure) the IDE activity monitor
+pub fn start(config: IdeActivityConfig, app_handle: tauri::AppHandle) {
+    if let Ok(mut state) = ACTIVITY_STATE.lock() {
+        state.config = Some(config);
+        if state.running {
+            println!("[IDEActivity] Already running, configuration updated");
+            return;
+        }
+        state.running = true;
+    }
+
+    thread::spawn(move || {
+        println!("[IDEActivity] Background thread started");
+
+        loop {
+            let (config, projects) = match ACTIVITY_STATE.lock() {
+                Ok(state) if !state.running => break,
+                Ok(state) => match &state.config {
+                    Some(config) => (config.clone(), state.projects.clone()),
+                    None => break,
+                },
+                Err(_) => break,
+            };
+
+            // One process/state scan per IDE, then match every watched project
+            let open_by_ide: Vec<(String, Vec<String>)> = config
+                .ides
+                .iter()
+                .filter_map(|ide| ide_monitor::get_open_projects_for_ide(ide.clone()).ok().map(|open| (ide.clone(), open)))
+                .collect();
+            let now_open: HashMap<String, String> = projects
+                .iter()
+                .filter_map(|project| {
+                    open_by_ide
+                        .iter()
+                        .find(|(_, open)| ide_monitor::project_is_open(open, project))
+                        .map(|(ide, _)| (project.clone(), ide.clone()))
+                })
+                .collect();
+
+            for (event, payload) in update_activity(&config, &now_open) {
+                println!("[IDEActivity] {} for {}", event, payload.project_id);
+                let _ = app_handle.emit_all(event, payload);
+            }
+
+            thread::sleep(Duration::from_secs(config.check_interval_secs.max(1)));
+        }
+
+        println!("[IDEActivity] Stopping background thread");
+    });
+}
+
+/// Compare the open projects with the previous scan and decide which events to emit
+fn update_activity(config: &IdeActivityConfig, now_open: &HashMap<String, String>) -> Vec<(&'static str, IdeActivityEvent)> {
+    let mut events = Vec::new();
+    let mut guard = match ACTIVITY_STATE.lock() {
+        Ok(guard) => guard,
+        Err(_) => return events,
+    };
+    let state = &mut *guard;
+    let timestamp = chrono::Utc::now().timestamp_millis() as u64;
+
+    // Projects closed since the last scan
+    let closed: Vec<String> = state.open.keys().filter(|p| !now_open.contains_key(*p)).cloned().collect();
+    for project_id in closed {
+        if let Some(open) = state.open.remove(&project_id) {
+            if config.auto_pause && state.running_timers.remove(&project_id) {
+                events.push((crate::events::TIMER_AUTO_PAUSE, IdeActivityEvent {
+                    project_id,
+                    ide: open.ide,
+                    open_since: open.since_label,
+                    timestamp,
+                }));
+            }
+        }
+    }
+
+    for (project_id, ide) in now_open {
+        let timer_running = state.running_timers.contains(project_id);
+        let open = state.open.entry(project_id.clone()).or_insert_with(|| OpenProject {
+            ide: ide.clone(),
+            since: Instant::now(),
+            since_label: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            suggested: false,
+            auto_started: false,
+        });
+        if timer_running {
+            continue;
+        }
+
+        let event = IdeActivityEvent {
+            project_id: project_id.clone(),
+            ide: open.ide.clone(),
+            open_since: open.since_label.clone(),
+            timestamp,
+        };
+        let auto_start_due = config
+            .auto_start_after_minutes
+            .map(|minutes| open.since.elapsed() >= Duration::from_secs(minutes * 60))
+            .unwrap_or(false);
+
+        if auto_start_due && !open.auto_started {
+            open.auto_started = true;
+            state.running_timers.insert(project_id.clone());
+            events.push((crate::events::TIMER_AUTO_START, event));
+        } else if !open.suggested {
+            open.suggested = true;
+            events.push((crate::events::SUGGEST_TIMER_START, event));
+        }
+    }
+
+    events
+}
+
+/// Stop the monitor
+pub fn stop() {
+    if let Ok(mut state) = ACTIVITY_STATE.lock() {
+        state.running = false;
+        state.open.clear();
+    }
+}
+
+/// Set the projects to watch
+pub fn set_projects(projects: Vec<String>) {
+    if let Ok(mut state) = ACTIVITY_STATE.lock() {
+        state.open.retain(|p, _| projects.contains(p));
+        state.projects = projects;
+    }
+}
+
+/// Projects whose timer is currently running
+pub fn running_timers() -> Vec<String> {
+    ACTIVITY_STATE
+        .lock()
+        .map(|state| state.running_timers.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Set the projects whose timer is currently running
+pub fn set_running_timers(project_ids: Vec<String>) {
+    if let Ok(mut state) = ACTIVITY_STATE.lock() {
+        state.running_timers = project_ids.into_iter().collect();
+    }
+}
@@ -0,0 +1,423 @@
+//! IDE Monitor Module
+//!
+//! Detects which projects are open in supported IDEs (PyCharm and other
+//! JetBrains IDEs, VS Code, Cursor) by reading the editors' own state files:
+//! JetBrains `recentProjects.xml`, VS Code `storage.json` and per-workspace
+//! storage folders. Used by the automatic time tracking.
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::UNIX_EPOCH;
+
+/// Most recently active project of an IDE
+#[derive(Debug, Clone, Serialize)]
+pub struct IdeRecentProject {
+    pub ide: String,
+    pub project_path: String,
+    pub last_active: Option<String>,
+    pub is_open: bool,
+}
+
+/// A project known to an IDE, with its last activity (ms since epoch)
+#[derive(Debug, Clone)]
+struct KnownProject {
+    path: String,
+    is_open: bool,
+    last_active_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum IdeFamily {
+    JetBrains,
+    VsCode,
+}
+
+/// Supported IDE: family, config folder name/prefix and process names
+struct IdeSpec {
+    family: IdeFamily,
+    config_name: &'static str,
+    process_names: &'static [&'static str],
+}
+
+const SUPPORTED_IDES: [&str; 3] = ["pycharm", "vscode", "cursor"];
+
+fn ide_spec(ide: &str) -> Result<IdeSpec, String> {
+    match ide {
+        "pycharm" => Ok(IdeSpec {
+            family: IdeFamily::JetBrains,
+            config_name: "PyCharm",
+            process_names: &["pycharm", "pycharm64", "pycharm.sh"],
+        }),
+        "webstorm" => Ok(IdeSpec {
+            family: IdeFamily::JetBrains,
+            config_name: "WebStorm",
+            process_names: &["webstorm", "webstorm64", "webstorm.sh"],
+        }),
+        "phpstorm" => Ok(IdeSpec {
+            family: IdeFamily::JetBrains,
+            config_name: "PhpStorm",
+            process_names: &["phpstorm", "phpstorm64", "phpstorm.sh"],
+        }),
+        "vscode" => Ok(IdeSpec {
+            family: IdeFamily::VsCode,
+            config_name: "Code",
+            process_names: &["Code", "code", "Code.exe", "Electron"],
+        }),
+        "cursor" => Ok(IdeSpec {
+            family: IdeFamily::VsCode,
+            config_name: "Cursor",
+            process_names: &["Cursor", "cursor", "Cursor.exe"],
+        }),
+        other => Err(format!("IDE non supporte: {}", other)),
+    }
+}
+
+/// Check if the IDE is running with the project (or a folder inside it) open
+#[tauri::command]
+pub fn check_ide_for_project(ide: String, project_path: String) -> Result<bool, String> {
+    let open_projects = get_open_projects_for_ide(ide)?;
+    Ok(project_is_open(&open_projects, &project_path))
+}
+
+/// Whether the project folder (or a folder inside it) is among the open projects
+pub fn project_is_open(open_projects: &[String], project_path: &str) -> bool {
+    let project = normalize_path(project_path);
+    open_projects.iter().any(|p| {
+        let open = normalize_path(p);
+        open == project || open.starts_with(&format!("{}/", project))
+    })
+}
+
+/// List the project folders currently open in an IDE
+#[tauri::command]
+pub fn get_open_projects_for_ide(ide: String) -> Result<Vec<String>, String> {
+    let spec = ide_spec(&ide)?;
+    if !is_ide_running(&spec) {
+        return Ok(Vec::new());
+    }
+
+    let mut open: Vec<String> = known_projects(&spec)
+        .into_iter()
+        .filter(|p| p.is_open)
+        .map(|p| p.path)
+        .collect();
+    open.sort();
+    open.dedup();
+    Ok(open)
+}
+
+/// Most recently active project for each supported IDE
+#[tauri::command]
+pub fn get_recent_ide_projects() -> Result<Vec<IdeRecentProject>, String> {
+    let mut recent = Vec::new();
+
+    for ide in SUPPORTED_IDES {
+        let spec = ide_spec(ide)?;
+        let running = is_ide_running(&spec);
+        let latest = known_projects(&spec)
+            .into_iter()
+            .max_by_key(|p| (p.is_open && running, p.last_active_ms.unwrap_or(0)));
+
+        if let Some(project) = latest {
+            recent.push(IdeRecentProject {
+                ide: ide.to_string(),
+                project_path: project.path,
+                last_active: project.last_active_ms.map(format_timestamp_ms),
+                is_open: project.is_open && running,
+            });
+        }
+    }
+
+    Ok(recent)
+}
+
+fn known_projects(spec: &IdeSpec) -> Vec<KnownProject> {
+    match spec.family {
+        IdeFamily::JetBrains => jetbrains_projects(spec.config_name),
+        IdeFamily::VsCode => vscode_projects(spec.config_name),
+    }
+}
+
+/// Check the process list for one of the IDE's executables
+fn is_ide_running(spec: &IdeSpec) -> bool {
+    let output = if cfg!(target_os = "windows") {
+        Command::new("tasklist").args(["/FO", "CSV", "/NH"]).output()
+    } else {
+        Command::new("ps").args(["-axo", "comm"]).output()
+    };
+
+    match output {
+        Ok(out) => String::from_utf8_lossy(&out.stdout).lines().any(|line| {
+            let name = line.trim().trim_matches('"').split("\",\"").next().unwrap_or("");
+            let name = Path::new(name).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            spec.process_names.iter().any(|p| name.eq_ignore_ascii_case(p))
+        }),
+        Err(e) => {
+            println!("[IDEMonitor] Failed to list processes: {}", e);
+            false
+        }
+    }
+}
+
+// ============================================
+// JetBrains
+// ============================================
+
+/// Config folder of the most recent installed version (e.g. `PyCharm2024.2`)
+fn jetbrains_config_dir(product: &str) -> Option<PathBuf> {
+    let root = dirs::config_dir()?.join("JetBrains");
+    fs::read_dir(root)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().starts_with(product))
+        .filter(|e| e.path().join("options").join("recentProjects.xml").exists())
+        .max_by_key(|e| {
+            fs::metadata(e.path().join("options").join("recentProjects.xml"))
+                .and_then(|m| m.modified())
+                .ok()
+        })
+        .map(|e| e.path())
+}
+
+fn jetbrains_projects(product: &str) -> Vec<KnownProject> {
+    let xml = match jetbrains_config_dir(product)
+        .and_then(|dir| fs::read_to_string(dir.join("options").join("recentProjects.xml")).ok())
+    {
+        Some(xml) => xml,
+        None => return Vec::new(),
+    };
+    parse_recent_projects_xml(&xml, &home_dir())
+}
+
+/// Parse `recentProjects.xml`.
+///
+/// Recent versions store one `<entry key="path">` per project with a
+/// `RecentProjectMetaInfo` carrying `opened` and timestamps; older ones
+/// only list `openPaths`.
+fn parse_recent_projects_xml(xml: &str, home: &str) -> Vec<KnownProject> {
+    let mut projects = Vec::new();
+
+    let mut rest = xml;
+    while let Some(start) = rest.find("<entry key=\"") {
+        rest = &rest[start + 12..];
+        let key_end = match rest.find('"') {
+            Some(end) => end,
+            None => break,
+        };
+        let path = expand_jetbrains_path(&rest[..key_end], home);
+        let entry_end = rest.find("</entry>").unwrap_or(rest.len());
+        let entry = &rest[..entry_end];
+
+        let meta = entry.find("<RecentProjectMetaInfo").map(|pos| {
+            let tag_end = entry[pos..].find('>').map(|e| pos + e).unwrap_or(entry.len());
+            &entry[pos..tag_end]
+        });
+        let is_open = meta.and_then(|m| xml_attribute(m, "opened")).as_deref() == Some("true");
+        let last_active_ms = xml_option_value(entry, "activationTimestamp")
+            .or_else(|| xml_option_value(entry, "projectOpenTimestamp"))
+            .and_then(|t| t.parse::<u64>().ok());
+
+        projects.push(KnownProject { path, is_open, last_active_ms });
+        rest = &rest[entry_end..];
+    }
+
+    // Older format: <option name="openPaths"><list><option value="..." /></list></option>
+    if let Some(start) = xml.find("name=\"openPaths\"") {
+        let section = &xml[start..];
+        let section = &section[..section.find("</list>").unwrap_or(section.len())];
+        for value in section.split("<option value=\"").skip(1) {
+            let raw = &value[..value.find('"').unwrap_or(value.len())];
+            let path = expand_jetbrains_path(raw, home);
+            match projects.iter_mut().find(|p| p.path == path) {
+                Some(project) => project.is_open = true,
+                None => projects.push(KnownProject { path, is_open: true, last_active_ms: None }),
+            }
+        }
+    }
+
+    projects
+}
+
+pub(crate) fn expand_jetbrains_path(raw: &str, home: &str) -> String {
+    raw.replace("$USER_HOME$", home).replace("&amp;", "&")
+}
+
+pub(crate) fn xml_attribute(tag: &str, name: &str) -> Option<String> {
+    let pattern = format!(" {}=\"", name);
+    let start = tag.find(&pattern)? + pattern.len();
+    let end = tag[start..].find('"')?;
+    Some(tag[start..start + end].to_string())
+}
+
+/// Value of `<option name="name" value="..." />`
+pub(crate) fn xml_option_value(block: &str, name: &str) -> Option<String> {
+    let start = block.find(&format!("<option name=\"{}\"", name))?;
+    let tag_end = block[start..].find('>')? + start;
+    xml_attribute(&block[start..tag_end], "value")
+}
+
+// ============================================
+// VS Code / Cursor
+// ============================================
+
+fn vscode_projects(app_name: &str) -> Vec<KnownProject> {
+    let user_dir = match dirs::config_dir() {
+        Some(dir) => dir.join(app_name).join("User"),
+        None => return Vec::new(),
+    };
+
+    let mut projects = workspace_storage_projects(&user_dir.join("workspaceStorage"));
+
+    // storage.json moved to User/globalStorage in recent versions
+    let storage = [
+        user_dir.join("globalStorage").join("storage.json"),
+        user_dir.parent().map(|p| p.join("storage.json")).unwrap_or_default(),
+    ]
+    .into_iter()
+    .find_map(|path| fs::read_to_string(path).ok())
+    .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok());
+
+    if let Some(storage) = storage {
+        for path in opened_window_folders(&storage) {
+            match projects.iter_mut().find(|p| p.path == path) {
+                Some(project) => project.is_open = true,
+                None => projects.push(KnownProject { path, is_open: true, last_active_ms: None }),
+            }
+        }
+    }
+
+    projects
+}
+
+/// Folders of the windows listed in `windowsState` (last active + other opened windows)
+fn opened_window_folders(storage: &serde_json::Value) -> Vec<String> {
+    let windows_state = &storage["windowsState"];
+    let mut windows: Vec<&serde_json::Value> = vec![&windows_state["lastActiveWindow"]];
+    if let Some(opened) = windows_state["openedWindows"].as_array() {
+        windows.extend(opened.iter());
+    }
+
+    let mut folders = Vec::new();
+    for window in windows {
+        if let Some(uri) = window["folder"].as_str().or_else(|| window["folderUri"].as_str()) {
+            folders.extend(uri_to_path(uri));
+        }
+        let workspace = window["workspaceIdentifier"]["configURIPath"]
+            .as_str()
+            .or_else(|| window["workspace"]["configPath"].as_str())
+            .or_else(|| window["configURIPath"].as_str());
+        if let Some(path) = workspace.and_then(uri_to_path) {
+            folders.extend(code_workspace_folders(Path::new(&path)));
+        }
+    }
+    folders
+}
+
+/// Projects recorded in `workspaceStorage/<hash>/workspace.json`, with the
+/// last write of the workspace state as activity time
+fn workspace_storage_projects(storage_dir: &Path) -> Vec<KnownProject> {
+    let entries = match fs::read_dir(storage_dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut projects = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        let dir = entry.path();
+        let workspace = match fs::read_to_string(dir.join("workspace.json"))
+            .ok()
+            .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+        {
+            Some(workspace) => workspace,
+            None => continue,
+        };
+
+        let state_file = if dir.join("state.vscdb").exists() { dir.join("state.vscdb") } else { dir.clone() };
+        let last_active_ms = fs::metadata(&state_file)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as u64);
+
+        let mut paths: Vec<String> = workspace["folder"].as_str().and_then(uri_to_path).into_iter().collect();
+        if let Some(path) = workspace["workspace"].as_str().and_then(uri_to_path) {
+            paths.extend(code_workspace_folders(Path::new(&path)));
+        }
+        for path in paths {
+            match projects.iter_mut().find(|p: &&mut KnownProject| p.path == path) {
+                Some(project) => project.last_active_ms = project.last_active_ms.max(last_active_ms),
+                None => projects.push(KnownProject { path, is_open: false, last_active_ms }),
+            }
+        }
+    }
+    projects
+}
+
+/// Folders of a `.code-workspace` file, resolved against the file location
+fn code_workspace_folders(workspace_file: &Path) -> Vec<String> {
+    let content = match fs::read_to_string(workspace_file) {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+    // Workspace files are JSONC: drop whole-line comments and trailing commas
+    let cleaned: String = content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("//"))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .replace(",\n}", "\n}")
+        .replace(",\n]", "\n]");
+    let parsed: serde_json::Value = match serde_json::from_str(&cleaned) {
+        Ok(value) => value,
+        Err(_) => return Vec::new(),
+    };
+
+    let base = workspace_file.parent().unwrap_or(Path::new(""));
+    parsed["folders"]
+        .as_array()
+        .map(|folders| {
+            folders
+                .iter()
+                .filter_map(|f| {
+                    if let Some(uri) = f["uri"].as_str() {
+                        return uri_to_path(uri);
+                    }
+                    let path = Path::new(f["path"].as_str()?);
+                    let absolute = if path.is_absolute() { path.to_path_buf() } else { base.join(path) };
+                    Some(absolute.to_string_lossy().to_string())
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Convert a `file://` URI (or a plain path) to a local path
+fn uri_to_path(uri: &str) -> Option<String> {
+    if !uri.contains("://") {
+        return Some(uri.to_string());
+    }
+    url::Url::parse(uri)
+        .ok()
+        .filter(|u| u.scheme() == "file")
+        .and_then(|u| u.to_file_path().ok())
+        .map(|p| p.to_string_lossy().to_string())
+}
+
+fn normalize_path(path: &str) -> String {
+    let canonical = fs::canonicalize(path)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| path.to_string());
+    canonical.trim_end_matches('/').to_string()
+}
+
+fn home_dir() -> String {
+    dirs::home_dir().map(|h| h.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+fn format_timestamp_ms(ms: u64) -> String {
+    chrono::DateTime::from_timestamp_millis(ms as i64)
+        .map(|dt| dt.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+        .unwrap_or_default()
+}
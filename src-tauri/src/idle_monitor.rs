@@ -0,0 +1,142 @@
+//! Idle Monitor Module
+//!
+//! Detects system idle time (no keyboard/mouse input) and pauses running
+//! timers after a configurable delay. When activity resumes, an event asks
+//! the frontend whether to keep or discard the idle period.
+
+use crate::ide_activity;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::process::Command;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::Manager;
+
+/// How often the idle time is polled
+const POLL_INTERVAL_SECS: u64 = 15;
+
+/// Event emitted on `timer-idle-pause` and `timer-idle-resume`
+#[derive(Debug, Clone, Serialize)]
+pub struct IdleEvent {
+    pub project_ids: Vec<String>,
+    /// When the last input happened
+    pub idle_since: String,
+    pub idle_seconds: u64,
+}
+
+#[derive(Default)]
+struct IdleState {
+    running: bool,
+    threshold_minutes: u64,
+    /// Set while timers are paused for inactivity
+    paused: Option<IdleEvent>,
+}
+
+static IDLE_STATE: Lazy<Mutex<IdleState>> = Lazy::new(|| Mutex::new(IdleState::default()));
+
+/// Seconds since the last user input, if the platform exposes it
+pub fn system_idle_seconds() -> Option<u64> {
+    if cfg!(target_os = "macos") {
+        // HIDIdleTime is reported in nanoseconds
+        let output = Command::new("ioreg").args(["-c", "IOHIDSystem", "-d", "4"]).output().ok()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        let line = text.lines().find(|l| l.contains("\"HIDIdleTime\""))?;
+        let value = line.split('=').nth(1)?.trim().parse::<u64>().ok()?;
+        Some(value / 1_000_000_000)
+    } else if cfg!(target_os = "linux") {
+        // Requires xprintidle (X11), reported in milliseconds
+        let output = Command::new("xprintidle").output().ok()?;
+        let value = String::from_utf8_lossy(&output.stdout).trim().parse::<u64>().ok()?;
+        Some(value / 1000)
+    } else {
+        None
+    }
+}
+
+/// Start (or reconfigure) the idle monitor
+pub fn start(threshold_minutes: u64, app_handle: tauri::AppHandle) -> Result<(), String> {
+    if system_idle_seconds().is_none() {
+        return Err("Detection d'inactivite non disponible sur ce systeme".to_string());
+    }
+
+    if let Ok(mut state) = IDLE_STATE.lock() {
+        state.threshold_minutes = threshold_minutes.max(1);
+        if state.running {
+            println!("[Idle] Already running, threshold updated");
+            return Ok(());
+        }
+        state.running = true;
+    }
+
+    thread::spawn(move || {
+        println!("[Idle] Background thread started");
+
+        loop {
+            thread::sleep(Duration::from_secs(POLL_INTERVAL_SECS));
+
+            let threshold_secs = match IDLE_STATE.lock() {
+                Ok(state) if !state.running => break,
+                Ok(state) => state.threshold_minutes * 60,
+                Err(_) => break,
+            };
+            let idle_seconds = match system_idle_seconds() {
+                Some(seconds) => seconds,
+                None => continue,
+            };
+
+            if let Some((event_name, event)) = check_idle(idle_seconds, threshold_secs) {
+                println!("[Idle] {} ({}s idle)", event_name, event.idle_seconds);
+                let _ = app_handle.emit_all(event_name, event);
+            }
+        }
+
+        println!("[Idle] Stopping background thread");
+    });
+
+    Ok(())
+}
+
+/// Pause timers once the threshold is reached, and report the idle period on return
+fn check_idle(idle_seconds: u64, threshold_secs: u64) -> Option<(&'static str, IdleEvent)> {
+    let mut state = IDLE_STATE.lock().ok()?;
+
+    match state.paused.take() {
+        // Still idle: keep waiting for activity
+        Some(paused) if idle_seconds >= threshold_secs => {
+            state.paused = Some(paused);
+            None
+        }
+        // Activity resumed `idle_seconds` ago: ask whether to keep the idle period
+        Some(mut paused) => {
+            let idle_since = chrono::DateTime::parse_from_rfc3339(&paused.idle_since).ok()?;
+            let elapsed = (chrono::Utc::now() - idle_since.with_timezone(&chrono::Utc)).num_seconds().max(0) as u64;
+            paused.idle_seconds = elapsed.saturating_sub(idle_seconds);
+            Some((crate::events::TIMER_IDLE_RESUME, paused))
+        }
+        None if idle_seconds >= threshold_secs => {
+            let project_ids = ide_activity::running_timers();
+            if project_ids.is_empty() {
+                return None;
+            }
+            let idle_since = chrono::Utc::now() - chrono::Duration::seconds(idle_seconds as i64);
+            let event = IdleEvent {
+                project_ids,
+                idle_since: idle_since.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                idle_seconds,
+            };
+            // The frontend pauses these timers and reports the new running set
+            state.paused = Some(event.clone());
+            Some((crate::events::TIMER_IDLE_PAUSE, event))
+        }
+        None => None,
+    }
+}
+
+/// Stop the idle monitor
+pub fn stop() {
+    if let Ok(mut state) = IDLE_STATE.lock() {
+        state.running = false;
+        state.paused = None;
+    }
+}
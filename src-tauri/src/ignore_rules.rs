@@ -0,0 +1,278 @@
+//! Ignore Rules Module
+//!
+//! Reads the `.laforgeignore` file at the root of a project, written with the
+//! gitignore syntax: one pattern per line, `#` comments, `!` to re-include,
+//! a trailing `/` for folders only, a leading `/` (or any inner `/`) to anchor
+//! the pattern to the project root, `*`, `?`, `[...]` and `**` wildcards.
+//! Ignored files are left out of scans, diffs, snapshots and the watcher, so
+//! `node_modules`, build artifacts and `.env` are never uploaded. The same
+//! globs back the include/exclude lists of a single sync.
+
+use std::fs;
+use std::path::Path;
+
+/// Name of the ignore file at the project root
+pub const IGNORE_FILE_NAME: &str = ".laforgeignore";
+
+#[derive(Debug, Clone)]
+struct Rule {
+    pattern: String,
+    negated: bool,
+    dir_only: bool,
+    /// Matched against the whole relative path instead of the file name
+    anchored: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    rules: Vec<Rule>,
+}
+
+impl IgnoreRules {
+    /// Rules of `<project_root>/.laforgeignore`, empty when the file is missing
+    pub fn load(project_root: &Path) -> Self {
+        match fs::read_to_string(project_root.join(IGNORE_FILE_NAME)) {
+            Ok(content) => Self::parse(&content),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let rules = content
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim_end();
+                if line.trim().is_empty() || line.starts_with('#') {
+                    return None;
+                }
+                let (negated, line) = match line.strip_prefix('!') {
+                    Some(rest) => (true, rest),
+                    None => (false, line.strip_prefix('\\').unwrap_or(line)),
+                };
+                let (dir_only, line) = match line.strip_suffix('/') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                let anchored = line.contains('/');
+                let pattern = line.trim_start_matches('/');
+                if pattern.is_empty() {
+                    return None;
+                }
+                Some(Rule {
+                    pattern: pattern.to_string(),
+                    negated,
+                    dir_only,
+                    anchored,
+                })
+            })
+            .collect();
+        IgnoreRules { rules }
+    }
+
+    /// Filter for `WalkDir::filter_entry`: drops ignored files and doesn't enter ignored folders
+    pub fn keeps_entry(&self, base_path: &Path, entry: &walkdir::DirEntry) -> bool {
+        match entry.path().strip_prefix(base_path) {
+            Ok(relative) if entry.depth() > 0 => {
+                !self.is_ignored(&relative.to_string_lossy().replace('\\', "/"), entry.file_type().is_dir())
+            }
+            _ => true,
+        }
+    }
+
+    /// Whether `relative` (with `/` separators) is ignored, itself or through one of its folders
+    pub fn is_ignored(&self, relative: &str, is_dir: bool) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        let relative = relative.trim_matches('/');
+        // As with git, a file can't be re-included when its folder is excluded
+        let mut parent_end = 0;
+        while let Some(offset) = relative[parent_end..].find('/') {
+            parent_end += offset;
+            if self.matches(&relative[..parent_end], true) {
+                return true;
+            }
+            parent_end += 1;
+        }
+        self.matches(relative, is_dir)
+    }
+
+    /// Last matching rule wins
+    fn matches(&self, relative: &str, is_dir: bool) -> bool {
+        let name = relative.rsplit('/').next().unwrap_or(relative);
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let subject = if rule.anchored { relative } else { name };
+            if glob_match(&rule.pattern, subject) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
+
+/// Include/exclude globs given for a single sync. A pattern without `/`
+/// matches a file or folder name at any depth (`*.psd`, `node_modules`),
+/// one with a `/` the path from the project root (`dist/**`, `assets/img`).
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl PathFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Self {
+        let clean = |patterns: &[String]| -> Vec<String> {
+            patterns
+                .iter()
+                .map(|p| p.trim().trim_start_matches('/').to_string())
+                .filter(|p| !p.is_empty())
+                .collect()
+        };
+        PathFilter {
+            include: clean(include),
+            exclude: clean(exclude),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Whether a file is part of the sync: matched by an include (when there are any) and by no exclude
+    pub fn allows(&self, relative: &str) -> bool {
+        let included = self.include.is_empty() || self.include.iter().any(|p| pattern_matches(p, relative));
+        included && !self.exclude.iter().any(|p| pattern_matches(p, relative))
+    }
+}
+
+/// Match a file path against a pattern, or one of its folders
+fn pattern_matches(pattern: &str, relative: &str) -> bool {
+    let pattern = pattern.trim_end_matches('/');
+    if !pattern.contains('/') {
+        return relative.split('/').any(|part| glob_match(pattern, part));
+    }
+    let mut end = 0;
+    while let Some(offset) = relative[end..].find('/') {
+        end += offset;
+        if glob_match(pattern, &relative[..end]) {
+            return true;
+        }
+        end += 1;
+    }
+    glob_match(pattern, relative)
+}
+
+/// Match a path against a glob: `*` and `?` stay within a path segment, `**` crosses them
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let path: Vec<char> = path.chars().collect();
+    match_from(&pattern, &path)
+}
+
+fn match_from(pattern: &[char], path: &[char]) -> bool {
+    match pattern.first() {
+        None => path.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            let rest = &pattern[2..];
+            match rest.first() {
+                // "**/" also matches no folder at all
+                Some('/') => {
+                    let rest = &rest[1..];
+                    match_from(rest, path)
+                        || (0..path.len()).any(|i| path[i] == '/' && match_from(rest, &path[i + 1..]))
+                }
+                _ => (0..=path.len()).any(|i| match_from(rest, &path[i..])),
+            }
+        }
+        Some('*') => {
+            let rest = &pattern[1..];
+            for i in 0..=path.len() {
+                if match_from(rest, &path[i..]) {
+                    return true;
+                }
+                if i < path.len() && path[i] == '/' {
+                    break;
+                }
+            }
+            false
+        }
+        Some('?') => !path.is_empty() && path[0] != '/' && match_from(&pattern[1..], &path[1..]),
+        Some('[') => match (path.first(), class_match(&pattern[1..], path.first().copied())) {
+            (Some(c), Some((true, consumed))) if *c != '/' => match_from(&pattern[1 + consumed..], &path[1..]),
+            (_, Some(_)) => false,
+            // No closing bracket: a literal "["
+            (Some('['), None) => match_from(&pattern[1..], &path[1..]),
+            _ => false,
+        },
+        Some('\\') if pattern.len() > 1 => {
+            !path.is_empty() && path[0] == pattern[1] && match_from(&pattern[2..], &path[1..])
+        }
+        Some(c) => !path.is_empty() && path[0] == *c && match_from(&pattern[1..], &path[1..]),
+    }
+}
+
+/// Match `c` against the class starting after `[`; returns whether it matched and the
+/// length of the class including `]`, None when the class isn't closed
+fn class_match(class: &[char], c: Option<char>) -> Option<(bool, usize)> {
+    let (negated, start) = match class.first() {
+        Some('!') | Some('^') => (true, 1),
+        _ => (false, 0),
+    };
+    let mut matched = false;
+    let mut i = start;
+    while i < class.len() {
+        // A "]" right after the opening bracket is a literal
+        if class[i] == ']' && i > start {
+            return Some((matched != negated, i + 1));
+        }
+        if i + 2 < class.len() && class[i + 1] == '-' && class[i + 2] != ']' {
+            if let Some(c) = c {
+                matched |= class[i] <= c && c <= class[i + 2];
+            }
+            i += 3;
+        } else {
+            matched |= c == Some(class[i]);
+            i += 1;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gitignore_semantics() {
+        let rules = IgnoreRules::parse(
+            "# dependencies\nnode_modules/\n*.log\n!keep.log\n/dist\nassets/**/*.map\n.env*\nbuild/\n!build/public\n",
+        );
+        assert!(rules.is_ignored("node_modules/react/index.js", false));
+        assert!(rules.is_ignored("web/node_modules/x.js", false));
+        assert!(rules.is_ignored("logs/debug.log", false));
+        assert!(!rules.is_ignored("logs/keep.log", false));
+        assert!(rules.is_ignored("dist/app.js", false));
+        assert!(!rules.is_ignored("src/dist/app.js", false));
+        assert!(rules.is_ignored("assets/js/vendor/app.js.map", false));
+        assert!(rules.is_ignored("assets/app.js.map", false));
+        assert!(rules.is_ignored(".env.local", false));
+        // The folder is excluded, its content can't be re-included
+        assert!(rules.is_ignored("build/public/index.html", false));
+        assert!(!rules.is_ignored("index.php", false));
+
+        assert!(glob_match("img-[0-9].png", "img-3.png"));
+        assert!(!glob_match("img-[!0-9].png", "img-3.png"));
+        assert!(!glob_match("*.css", "css/site.css"));
+        assert!(glob_match("**/*.css", "site.css"));
+
+        let filter = PathFilter::new(&["dist/**".to_string(), "/assets".to_string()], &["*.psd".to_string()]);
+        assert!(filter.allows("dist/js/app.js"));
+        assert!(filter.allows("assets/img/logo.png"));
+        assert!(!filter.allows("assets/src/logo.psd"));
+        assert!(!filter.allows("index.php"));
+    }
+}
@@ -0,0 +1,263 @@
+//! Image Gallery Module
+//!
+//! After a scrape, writes `gallery/index.html` in the output folder: one card
+//! per downloaded image with a thumbnail, its original dimensions, file size
+//! and source URL, to pick the assets worth reusing in the rebuild.
+//! Dimensions are read from the file headers (PNG, GIF, JPEG, WebP, SVG).
+//! Thumbnails are made with `sips` on macOS; elsewhere, or when `sips`
+//! fails, the card shows the original scaled down by the browser.
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Folder of the gallery, in the scrape output folder
+pub const GALLERY_FOLDER: &str = "gallery";
+
+/// Longest side of a thumbnail, in pixels
+const THUMBNAIL_SIZE: u32 = 320;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GalleryImage {
+    /// Relative to the scrape output folder
+    pub file: String,
+    pub source_url: String,
+    pub size: u64,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Relative to the gallery folder; None when the original is shown
+    pub thumbnail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GallerySummary {
+    pub path: String,
+    pub images: usize,
+    pub thumbnails: usize,
+}
+
+fn be16(data: &[u8], at: usize) -> Option<u32> {
+    Some(u16::from_be_bytes([*data.get(at)?, *data.get(at + 1)?]) as u32)
+}
+
+fn le16(data: &[u8], at: usize) -> Option<u32> {
+    Some(u16::from_le_bytes([*data.get(at)?, *data.get(at + 1)?]) as u32)
+}
+
+fn le24(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes([*data.get(at)?, *data.get(at + 1)?, *data.get(at + 2)?, 0]))
+}
+
+/// Width and height from the image header
+pub fn image_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        let width = u32::from_be_bytes(data.get(16..20)?.try_into().ok()?);
+        let height = u32::from_be_bytes(data.get(20..24)?.try_into().ok()?);
+        return Some((width, height));
+    }
+    if data.starts_with(b"GIF8") {
+        return Some((le16(data, 6)?, le16(data, 8)?));
+    }
+    if data.starts_with(&[0xFF, 0xD8]) {
+        return jpeg_dimensions(data);
+    }
+    if data.len() > 30 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        return match &data[12..16] {
+            b"VP8 " => Some((le16(data, 26)? & 0x3FFF, le16(data, 28)? & 0x3FFF)),
+            b"VP8L" => {
+                let bits = u32::from_le_bytes(data.get(21..25)?.try_into().ok()?);
+                Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1))
+            }
+            b"VP8X" => Some((le24(data, 24)? + 1, le24(data, 27)? + 1)),
+            _ => None,
+        };
+    }
+    let head = String::from_utf8_lossy(&data[..data.len().min(4096)]);
+    if head.contains("<svg") {
+        return svg_dimensions(&head);
+    }
+    None
+}
+
+/// Size of the first start-of-frame segment
+fn jpeg_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let mut at = 2;
+    while at + 9 < data.len() {
+        if data[at] != 0xFF {
+            return None;
+        }
+        let marker = data[at + 1];
+        // SOF0..SOF15, except DHT (C4), JPG (C8) and DAC (CC)
+        if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            return Some((be16(data, at + 7)?, be16(data, at + 5)?));
+        }
+        at += 2 + be16(data, at + 2)? as usize;
+    }
+    None
+}
+
+/// `width`/`height` attributes of the root element, else its viewBox
+fn svg_dimensions(head: &str) -> Option<(u32, u32)> {
+    let start = head.find("<svg")?;
+    let tag = &head[start..start + head[start..].find('>')?];
+    let attr = |name: &str| -> Option<f64> {
+        let at = tag.find(&format!(" {}=", name))? + name.len() + 2;
+        let quote = tag[at..].chars().next()?;
+        let value = tag[at + 1..].split(quote).next()?;
+        value.trim().trim_end_matches("px").parse::<f64>().ok()
+    };
+    if let (Some(width), Some(height)) = (attr("width"), attr("height")) {
+        return Some((width.round() as u32, height.round() as u32));
+    }
+    let at = tag.find("viewBox=")? + 8;
+    let quote = tag[at..].chars().next()?;
+    let values: Vec<f64> = tag[at + 1..].split(quote).next()?.split([' ', ',']).filter_map(|v| v.parse().ok()).collect();
+    match values.as_slice() {
+        [_, _, width, height] => Some((width.round() as u32, height.round() as u32)),
+        _ => None,
+    }
+}
+
+/// Scale the image down with `sips`; None when unavailable or the image is already small
+fn make_thumbnail(source: &Path, thumbs_dir: &Path, index: usize, dimensions: Option<(u32, u32)>) -> Option<PathBuf> {
+    if !cfg!(target_os = "macos") {
+        return None;
+    }
+    let extension = source.extension()?.to_string_lossy().to_lowercase();
+    if !matches!(extension.as_str(), "png" | "jpg" | "jpeg" | "webp" | "gif") {
+        return None;
+    }
+    let (width, height) = dimensions?;
+    if width.max(height) <= THUMBNAIL_SIZE {
+        return None;
+    }
+    // Transparency survives in PNG, everything else becomes JPEG
+    let format = if extension == "png" { "png" } else { "jpeg" };
+    let target = thumbs_dir.join(format!("{}.{}", index, if format == "png" { "png" } else { "jpg" }));
+    let result = Command::new("sips")
+        .args(["-Z", &THUMBNAIL_SIZE.to_string(), "-s", "format", format])
+        .arg(source)
+        .arg("--out")
+        .arg(&target)
+        .output();
+    match result {
+        Ok(out) if out.status.success() && target.exists() => Some(target),
+        _ => None,
+    }
+}
+
+/// Path of `path` relative to `base`, with forward slashes
+fn relative(path: &Path, base: &Path) -> String {
+    path.strip_prefix(base).unwrap_or(path).to_string_lossy().replace('\\', "/")
+}
+
+fn escape(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn format_size(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{} o", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} Ko", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} Mo", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+fn render(site: &str, images: &[GalleryImage]) -> String {
+    let mut html = String::from("<!DOCTYPE html>\n<html lang=\"fr\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>Images - {}</title>\n", escape(site)));
+    html.push_str(
+        "<style>\
+body{font-family:-apple-system,sans-serif;margin:24px;background:#f5f5f7;color:#1d1d1f}\
+.grid{display:grid;grid-template-columns:repeat(auto-fill,minmax(220px,1fr));gap:16px}\
+.card{background:#fff;border-radius:8px;overflow:hidden;box-shadow:0 1px 3px rgba(0,0,0,.1)}\
+.thumb{display:flex;align-items:center;justify-content:center;height:180px;background:#e8e8ed}\
+.thumb img{max-width:100%;max-height:180px}\
+.meta{padding:8px 10px;font-size:12px;line-height:1.5;word-break:break-all}\
+.meta a{color:#0066cc;text-decoration:none}\
+</style>\n</head>\n<body>\n",
+    );
+    html.push_str(&format!("<h1>Images de {}</h1>\n<p>{} image(s)</p>\n<div class=\"grid\">\n", escape(site), images.len()));
+    for image in images {
+        let original = format!("../{}", image.file);
+        let preview = image.thumbnail.clone().unwrap_or_else(|| original.clone());
+        let dimensions = match (image.width, image.height) {
+            (Some(width), Some(height)) => format!("{} x {} px", width, height),
+            _ => "dimensions inconnues".to_string(),
+        };
+        html.push_str(&format!(
+            "<div class=\"card\"><a class=\"thumb\" href=\"{}\"><img src=\"{}\" loading=\"lazy\" alt=\"\"></a>\
+<div class=\"meta\"><strong>{}</strong><br>{} - {}<br><a href=\"{}\">{}</a></div></div>\n",
+            escape(&original),
+            escape(&preview),
+            escape(Path::new(&image.file).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default().as_str()),
+            dimensions,
+            format_size(image.size),
+            escape(&image.source_url),
+            escape(&image.source_url)
+        ));
+    }
+    html.push_str("</div>\n</body>\n</html>\n");
+    html
+}
+
+/// Write the gallery of `images` (local file, source URL) into `output_base/gallery/`
+pub fn generate_gallery(output_base: &Path, site: &str, images: &[(PathBuf, String)]) -> Result<GallerySummary, String> {
+    let gallery_dir = output_base.join(GALLERY_FOLDER);
+    let thumbs_dir = gallery_dir.join("thumbs");
+    fs::create_dir_all(&thumbs_dir).map_err(|e| format!("Failed to create gallery folder: {}", e))?;
+
+    let mut entries: Vec<GalleryImage> = Vec::new();
+    for (index, (local_file, source_url)) in images.iter().enumerate() {
+        let data = match fs::read(local_file) {
+            Ok(data) => data,
+            Err(_) => continue,
+        };
+        let dimensions = image_dimensions(&data);
+        let thumbnail = make_thumbnail(local_file, &thumbs_dir, index, dimensions);
+        entries.push(GalleryImage {
+            file: relative(local_file, output_base),
+            source_url: source_url.clone(),
+            size: data.len() as u64,
+            width: dimensions.map(|(width, _)| width),
+            height: dimensions.map(|(_, height)| height),
+            thumbnail: thumbnail.map(|thumb| relative(&thumb, &gallery_dir)),
+        });
+    }
+    entries.sort_by(|a, b| a.file.cmp(&b.file));
+
+    let path = gallery_dir.join("index.html");
+    fs::write(&path, render(site, &entries)).map_err(|e| format!("Failed to write gallery: {}", e))?;
+    let thumbnails = entries.iter().filter(|e| e.thumbnail.is_some()).count();
+    println!("[Gallery] {} image(s), {} thumbnail(s) in {}", entries.len(), thumbnails, path.display());
+    Ok(GallerySummary {
+        path: path.to_string_lossy().to_string(),
+        images: entries.len(),
+        thumbnails,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_dimensions() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        png.extend_from_slice(&640u32.to_be_bytes());
+        png.extend_from_slice(&480u32.to_be_bytes());
+        assert_eq!(image_dimensions(&png), Some((640, 480)));
+
+        assert_eq!(image_dimensions(b"GIF89a\x20\x03\x58\x02"), Some((800, 600)));
+
+        let jpeg = [0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xC0, 0x00, 0x11, 0x08, 0x01, 0x2C, 0x01, 0x90, 0x03];
+        assert_eq!(image_dimensions(&jpeg), Some((400, 300)));
+
+        assert_eq!(image_dimensions(br#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 16">"#), Some((24, 16)));
+        assert_eq!(image_dimensions(br#"<svg width="120px" height="40">"#), Some((120, 40)));
+        assert_eq!(image_dimensions(b"plain text"), None);
+    }
+}
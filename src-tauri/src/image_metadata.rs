@@ -0,0 +1,191 @@
+//! Image Metadata Module
+//!
+//! A downloaded image loses the alt text, title and caption it had on the
+//! page. They are collected while scraping and written next to the images as
+//! `image-metadata.json`: one entry per downloaded file, with every distinct
+//! way the pages used it, so a rebuild keeps its accessibility and SEO text.
+
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// Sidecar file, in the images folder
+pub const SIDECAR_FILE: &str = "image-metadata.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageUsage {
+    /// None when the attribute is missing; an empty alt marks a decorative image
+    pub alt: Option<String>,
+    pub title: Option<String>,
+    /// Text of the `figcaption` of the enclosing `figure`
+    pub caption: Option<String>,
+    /// Pages using the image with this text
+    pub pages: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageMetadata {
+    /// Relative to the images folder
+    pub file: String,
+    pub source_url: String,
+    pub usages: Vec<ImageUsage>,
+}
+
+/// Alt, title and caption of the images seen so far, by absolute image URL
+#[derive(Debug, Default)]
+pub struct ImageMetadataCollector {
+    by_url: HashMap<String, Vec<ImageUsage>>,
+}
+
+fn clean(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Caption of the closest `figure` holding the element
+fn caption_of(element: &ElementRef) -> Option<String> {
+    let figcaption = Selector::parse("figcaption").unwrap();
+    let figure = element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .find(|a| a.value().name() == "figure")?;
+    let caption = clean(&figure.select(&figcaption).next()?.text().collect::<String>());
+    (!caption.is_empty()).then_some(caption)
+}
+
+/// URLs of a `srcset` attribute, descriptors dropped
+fn srcset_urls(srcset: &str) -> impl Iterator<Item = &str> {
+    srcset.split(',').filter_map(|part| part.split_whitespace().next())
+}
+
+/// Every image of a page with its text; `srcset` variants and `picture` sources
+/// share the text of their `img`
+pub fn page_usages(page_url: &str, document: &Html, base_url: &Url) -> Vec<(String, ImageUsage)> {
+    let img_selector = Selector::parse("img").unwrap();
+    let source_selector = Selector::parse("source[srcset]").unwrap();
+    let mut usages = Vec::new();
+
+    for img in document.select(&img_selector) {
+        let usage = ImageUsage {
+            alt: img.value().attr("alt").map(clean),
+            title: img.value().attr("title").map(clean).filter(|t| !t.is_empty()),
+            caption: caption_of(&img),
+            pages: vec![page_url.to_string()],
+        };
+        let mut sources: Vec<&str> = img.value().attr("src").into_iter().collect();
+        sources.extend(img.value().attr("srcset").map(srcset_urls).into_iter().flatten());
+        let picture = img
+            .parent()
+            .and_then(ElementRef::wrap)
+            .filter(|parent| parent.value().name() == "picture");
+        if let Some(picture) = picture {
+            for source in picture.select(&source_selector) {
+                sources.extend(source.value().attr("srcset").map(srcset_urls).into_iter().flatten());
+            }
+        }
+        for src in sources {
+            if let Ok(absolute_url) = base_url.join(src.trim()) {
+                usages.push((absolute_url.to_string(), usage.clone()));
+            }
+        }
+    }
+    usages
+}
+
+impl ImageMetadataCollector {
+    /// Add usages, merging the pages of an image used with the same text
+    pub fn extend(&mut self, usages: Vec<(String, ImageUsage)>) {
+        for (url, usage) in usages {
+            let known = self.by_url.entry(url).or_default();
+            match known
+                .iter_mut()
+                .find(|k| k.alt == usage.alt && k.title == usage.title && k.caption == usage.caption)
+            {
+                Some(same) => {
+                    for page in usage.pages {
+                        if !same.pages.contains(&page) {
+                            same.pages.push(page);
+                        }
+                    }
+                }
+                None => known.push(usage),
+            }
+        }
+    }
+
+    /// Entries of the downloaded `images` (local file, source URL), sorted by file
+    pub fn entries(&self, images_dir: &Path, images: &[(PathBuf, String)]) -> Vec<ImageMetadata> {
+        let mut entries: Vec<ImageMetadata> = images
+            .iter()
+            .map(|(local_file, source_url)| ImageMetadata {
+                file: local_file
+                    .strip_prefix(images_dir)
+                    .unwrap_or(local_file)
+                    .to_string_lossy()
+                    .replace('\\', "/"),
+                source_url: source_url.clone(),
+                usages: self.by_url.get(source_url).cloned().unwrap_or_default(),
+            })
+            .collect();
+        entries.sort_by(|a, b| a.file.cmp(&b.file));
+        entries.dedup_by(|a, b| a.file == b.file);
+        entries
+    }
+
+    /// Write `images_dir/image-metadata.json`; None when no image was downloaded
+    pub fn write(&self, images_dir: &Path, images: &[(PathBuf, String)]) -> Result<Option<String>, String> {
+        if images.is_empty() {
+            return Ok(None);
+        }
+        let entries = self.entries(images_dir, images);
+        let path = images_dir.join(SIDECAR_FILE);
+        let json = serde_json::to_string_pretty(&entries)
+            .map_err(|e| format!("Failed to serialize image metadata: {}", e))?;
+        fs::write(&path, json).map_err(|e| format!("Failed to write image metadata: {}", e))?;
+        let missing_alt = entries.iter().filter(|e| e.usages.iter().any(|u| u.alt.is_none())).count();
+        println!("[ImageMetadata] {} image(s), {} without alt text, in {}", entries.len(), missing_alt, path.display());
+        Ok(Some(path.to_string_lossy().to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usages_with_caption_and_srcset() {
+        let html = r#"<html><body>
+            <figure><picture><source srcset="/img/hero.webp 1x"><img src="/img/hero.jpg" alt=" Vue  du port " title="Port"></picture>
+            <figcaption>Le port au lever du soleil</figcaption></figure>
+            <img src="/img/deco.png" alt="">
+            <img src="img/logo.svg">
+        </body></html>"#;
+        let base = Url::parse("https://example.com/page/").unwrap();
+        let usages = page_usages("https://example.com/page/", &Html::parse_document(html), &base);
+        let urls: Vec<&str> = usages.iter().map(|(url, _)| url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://example.com/img/hero.jpg",
+                "https://example.com/img/hero.webp",
+                "https://example.com/img/deco.png",
+                "https://example.com/page/img/logo.svg",
+            ]
+        );
+        assert_eq!(usages[0].1.alt.as_deref(), Some("Vue du port"));
+        assert_eq!(usages[1].1.caption.as_deref(), Some("Le port au lever du soleil"));
+        assert_eq!(usages[2].1.alt.as_deref(), Some(""));
+        assert_eq!(usages[3].1.alt, None);
+
+        let mut collector = ImageMetadataCollector::default();
+        collector.extend(usages.clone());
+        collector.extend(vec![(usages[0].0.clone(), ImageUsage { pages: vec!["https://example.com/".to_string()], ..usages[0].1.clone() })]);
+        let images_dir = Path::new("/out/images");
+        let entries = collector.entries(images_dir, &[(images_dir.join("hero.jpg"), usages[0].0.clone())]);
+        assert_eq!(entries[0].file, "hero.jpg");
+        assert_eq!(entries[0].usages.len(), 1);
+        assert_eq!(entries[0].usages[0].pages.len(), 2);
+    }
+}
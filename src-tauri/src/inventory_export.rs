@@ -0,0 +1,227 @@
+//! Inventory Export Module
+//!
+//! Writes the scraped content inventory (URL, title, meta description, H1,
+//! words, images, status) as CSV or XLSX, to hand to clients and SEO
+//! consultants. The XLSX file is a minimal workbook with a single sheet,
+//! zipped without compression so no archive library is needed.
+
+use crate::content_inventory::{ContentInventory, PageInventory};
+use std::fs;
+use std::path::Path;
+
+const HEADERS: [&str; 8] = ["URL", "Titre", "Meta description", "H1", "Langue", "Mots", "Images", "Statut"];
+
+enum Cell {
+    Text(String),
+    Number(u64),
+}
+
+fn row(page: &PageInventory) -> Vec<Cell> {
+    vec![
+        Cell::Text(page.url.clone()),
+        Cell::Text(page.title.clone()),
+        Cell::Text(page.meta_description.clone().unwrap_or_default()),
+        Cell::Text(page.h1.clone().unwrap_or_default()),
+        Cell::Text(page.language.clone().unwrap_or_default()),
+        Cell::Number(page.word_count as u64),
+        Cell::Number(page.image_count as u64),
+        Cell::Number(page.status as u64),
+    ]
+}
+
+/// Write the inventory to `output_path` as "csv" or "xlsx" (from the extension when None); returns the rows written
+pub fn export_inventory(inventory: &ContentInventory, output_path: &str, format: Option<&str>) -> Result<usize, String> {
+    let extension = Path::new(output_path).extension().and_then(|e| e.to_str()).map(str::to_lowercase);
+    let format = format.map(str::to_lowercase).or(extension).unwrap_or_else(|| "csv".to_string());
+    let content = match format.as_str() {
+        "csv" => build_csv(inventory).into_bytes(),
+        "xlsx" => build_xlsx(inventory),
+        other => return Err(format!("Format d'export inconnu: {} (csv ou xlsx)", other)),
+    };
+    fs::write(output_path, content).map_err(|e| format!("Failed to write inventory file: {}", e))?;
+    Ok(inventory.pages.len())
+}
+
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// UTF-8 with a BOM so Excel reads the accents right
+fn build_csv(inventory: &ContentInventory) -> String {
+    let mut csv = String::from("\u{feff}");
+    csv.push_str(&HEADERS.join(","));
+    csv.push_str("\r\n");
+    for page in &inventory.pages {
+        let fields: Vec<String> = row(page)
+            .into_iter()
+            .map(|cell| match cell {
+                Cell::Text(text) => csv_field(&text),
+                Cell::Number(n) => n.to_string(),
+            })
+            .collect();
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .chars()
+        // Control characters other than tab and newlines are invalid in XML 1.0
+        .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect::<String>()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Spreadsheet column name of a 0-based index (0 -> A, 26 -> AA)
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+fn sheet_xml(inventory: &ContentInventory) -> String {
+    let header: Vec<Cell> = HEADERS.iter().map(|h| Cell::Text(h.to_string())).collect();
+    let rows = std::iter::once(header).chain(inventory.pages.iter().map(row));
+
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#);
+    xml.push_str(r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>"#);
+    for (r, cells) in rows.enumerate() {
+        xml.push_str(&format!(r#"<row r="{}">"#, r + 1));
+        for (c, cell) in cells.into_iter().enumerate() {
+            let reference = format!("{}{}", column_name(c), r + 1);
+            match cell {
+                Cell::Text(text) => xml.push_str(&format!(
+                    r#"<c r="{}" t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#,
+                    reference,
+                    xml_escape(&text)
+                )),
+                Cell::Number(n) => xml.push_str(&format!(r#"<c r="{}"><v>{}</v></c>"#, reference, n)),
+            }
+        }
+        xml.push_str("</row>");
+    }
+    xml.push_str("</sheetData></worksheet>");
+    xml
+}
+
+fn build_xlsx(inventory: &ContentInventory) -> Vec<u8> {
+    let content_types = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/></Types>"#;
+    let root_rels = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#;
+    let workbook = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="Inventaire" sheetId="1" r:id="rId1"/></sheets></workbook>"#;
+    let workbook_rels = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#;
+    let sheet = sheet_xml(inventory);
+
+    zip_stored(&[
+        ("[Content_Types].xml", content_types.as_bytes()),
+        ("_rels/.rels", root_rels.as_bytes()),
+        ("xl/workbook.xml", workbook.as_bytes()),
+        ("xl/_rels/workbook.xml.rels", workbook_rels.as_bytes()),
+        ("xl/worksheets/sheet1.xml", sheet.as_bytes()),
+    ])
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// ZIP archive of uncompressed entries (method 0), dated 1980-01-01
+fn zip_stored(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    const DOS_DATE: u16 = 0x0021;
+    const UTF8_NAMES: u16 = 0x0800;
+    let mut out: Vec<u8> = Vec::new();
+    let mut central: Vec<u8> = Vec::new();
+
+    for (name, data) in entries {
+        let offset = out.len() as u32;
+        let crc = crc32(data);
+        let size = data.len() as u32;
+        let name = name.as_bytes();
+
+        out.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        out.extend_from_slice(&20u16.to_le_bytes());
+        out.extend_from_slice(&UTF8_NAMES.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes()); // stored
+        out.extend_from_slice(&0u16.to_le_bytes()); // time
+        out.extend_from_slice(&DOS_DATE.to_le_bytes());
+        out.extend_from_slice(&crc.to_le_bytes());
+        out.extend_from_slice(&size.to_le_bytes());
+        out.extend_from_slice(&size.to_le_bytes());
+        out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes());
+        out.extend_from_slice(name);
+        out.extend_from_slice(data);
+
+        central.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        central.extend_from_slice(&20u16.to_le_bytes());
+        central.extend_from_slice(&20u16.to_le_bytes());
+        central.extend_from_slice(&UTF8_NAMES.to_le_bytes());
+        central.extend_from_slice(&0u16.to_le_bytes());
+        central.extend_from_slice(&0u16.to_le_bytes());
+        central.extend_from_slice(&DOS_DATE.to_le_bytes());
+        central.extend_from_slice(&crc.to_le_bytes());
+        central.extend_from_slice(&size.to_le_bytes());
+        central.extend_from_slice(&size.to_le_bytes());
+        central.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        central.extend_from_slice(&[0u8; 12]); // extra, comment, disk, attributes
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name);
+    }
+
+    let central_offset = out.len() as u32;
+    out.extend_from_slice(&central);
+    out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    out.extend_from_slice(&[0u8; 4]); // disk numbers
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+    out.extend_from_slice(&central_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_and_xlsx_layout() {
+        let mut page = crate::content_inventory::error_page("https://example.com/a", 200);
+        page.title = "Tarifs, \"offres\"".to_string();
+        page.word_count = 120;
+        let inventory = ContentInventory::from_pages(vec![page]);
+
+        let csv = build_csv(&inventory);
+        assert!(csv.contains("\r\nhttps://example.com/a,\"Tarifs, \"\"offres\"\"\",,,,120,0,200\r\n"));
+
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(27), "AB");
+        let xlsx = build_xlsx(&inventory);
+        assert_eq!(&xlsx[..4], b"PK\x03\x04");
+        assert_eq!(&xlsx[xlsx.len() - 22..xlsx.len() - 18], b"PK\x05\x06");
+        assert!(sheet_xml(&inventory).contains(r#"<c r="F2"><v>120</v></c>"#));
+    }
+}
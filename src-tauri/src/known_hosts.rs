@@ -0,0 +1,228 @@
+//! Known Hosts Module
+//!
+//! Host keys of the SSH servers La Forge connects to, in `known_hosts.json`
+//! of the app data directory. A new server is only trusted once the user has
+//! confirmed its fingerprint (shown by `verify_host_key`); after that every
+//! SFTP connection fails hard if the server presents another key. Tools that
+//! run the system ssh get the trusted key as a known_hosts line to pin it.
+
+use crate::proxy::ProxyConfig;
+use crate::state_file;
+use base64::Engine;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Serializes read-modify-write of the store
+static STORE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnownHost {
+    pub host: String,
+    pub port: u16,
+    /// "ssh-ed25519", "ssh-rsa"...
+    pub key_type: String,
+    /// "SHA256:<base64>", as printed by ssh-keygen -l
+    pub fingerprint: String,
+    pub added_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HostKeyStatus {
+    /// Same key as the trusted one
+    Trusted,
+    /// Never connected, the fingerprint has to be confirmed
+    Unknown,
+    /// The server presents another key than the trusted one
+    Mismatch,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HostKeyCheck {
+    pub host: String,
+    pub port: u16,
+    pub key_type: String,
+    pub fingerprint: String,
+    pub status: HostKeyStatus,
+    /// Trusted fingerprint, when it differs
+    pub known_fingerprint: Option<String>,
+}
+
+fn store_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("known_hosts.json")
+}
+
+fn host_id(host: &str, port: u16) -> String {
+    format!("{}:{}", host.trim().to_lowercase(), port)
+}
+
+fn load(app_data_dir: &Path) -> BTreeMap<String, KnownHost> {
+    state_file::read_json(&store_path(app_data_dir))
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+/// SHA256 fingerprint as ssh-keygen prints it, base64 without padding
+fn fingerprint(hash: &[u8]) -> String {
+    format!("SHA256:{}", base64::engine::general_purpose::STANDARD_NO_PAD.encode(hash))
+}
+
+fn key_type_name(key_type: ssh2::HostKeyType) -> &'static str {
+    match key_type {
+        ssh2::HostKeyType::Rsa => "ssh-rsa",
+        ssh2::HostKeyType::Dss => "ssh-dss",
+        ssh2::HostKeyType::Ecdsa256 => "ecdsa-sha2-nistp256",
+        ssh2::HostKeyType::Ecdsa384 => "ecdsa-sha2-nistp384",
+        ssh2::HostKeyType::Ecdsa521 => "ecdsa-sha2-nistp521",
+        ssh2::HostKeyType::Ed25519 => "ssh-ed25519",
+        ssh2::HostKeyType::Unknown => "unknown",
+    }
+}
+
+/// Key type and fingerprint presented by a session after its handshake
+pub fn session_fingerprint(sess: &ssh2::Session) -> Result<(String, String), String> {
+    let key_type = sess
+        .host_key()
+        .map(|(_, key_type)| key_type_name(key_type).to_string())
+        .ok_or("Le serveur n'a pas presente de cle d'hote")?;
+    let hash = sess
+        .host_key_hash(ssh2::HashType::Sha256)
+        .ok_or("Empreinte de la cle d'hote indisponible")?;
+    Ok((key_type, fingerprint(hash)))
+}
+
+/// Compare a fingerprint with the trusted one
+pub fn check(app_data_dir: &Path, host: &str, port: u16, key_type: &str, fingerprint: &str) -> HostKeyCheck {
+    let known = load(app_data_dir).remove(&host_id(host, port));
+    let (status, known_fingerprint) = match known {
+        Some(k) if k.fingerprint == fingerprint => (HostKeyStatus::Trusted, None),
+        Some(k) => (HostKeyStatus::Mismatch, Some(k.fingerprint)),
+        None => (HostKeyStatus::Unknown, None),
+    };
+    HostKeyCheck {
+        host: host.to_string(),
+        port,
+        key_type: key_type.to_string(),
+        fingerprint: fingerprint.to_string(),
+        status,
+        known_fingerprint,
+    }
+}
+
+/// Verify the key of a session before authenticating: only a trusted key passes
+pub fn verify_session(sess: &ssh2::Session, host: &str, port: u16) -> Result<(), String> {
+    let app_dir = crate::data_location::current_dir().ok_or("Could not get app data directory")?;
+    let (key_type, fingerprint) = session_fingerprint(sess)?;
+    let result = check(&app_dir, host, port, &key_type, &fingerprint);
+    match result.status {
+        HostKeyStatus::Trusted => Ok(()),
+        HostKeyStatus::Unknown => Err(format!(
+            "Cle d'hote inconnue pour {}:{} ({} {}), confirmez l'empreinte avant de vous connecter",
+            host, port, key_type, fingerprint
+        )),
+        HostKeyStatus::Mismatch => {
+            println!("[KnownHosts] Host key mismatch for {}:{}", host, port);
+            Err(format!(
+                "ATTENTION: la cle d'hote de {}:{} a change (attendue {}, recue {}). Connexion refusee.",
+                host,
+                port,
+                result.known_fingerprint.unwrap_or_default(),
+                fingerprint
+            ))
+        }
+    }
+}
+
+fn handshake(host: &str, port: u16, proxy: Option<&ProxyConfig>) -> Result<ssh2::Session, String> {
+    let tcp = crate::proxy::connect(proxy, host, port, CONNECT_TIMEOUT)?;
+    let mut sess = ssh2::Session::new().map_err(|e| format!("Session creation failed: {}", e))?;
+    sess.set_tcp_stream(tcp);
+    sess.handshake().map_err(|e| format!("Handshake failed: {}", e))?;
+    Ok(sess)
+}
+
+/// Connect to a server and check the key it presents, without authenticating
+pub fn probe(app_data_dir: &Path, host: &str, port: u16, proxy: Option<&ProxyConfig>) -> Result<HostKeyCheck, String> {
+    let sess = handshake(host, port, proxy)?;
+    let (key_type, fingerprint) = session_fingerprint(&sess)?;
+    Ok(check(app_data_dir, host, port, &key_type, &fingerprint))
+}
+
+/// OpenSSH known_hosts line of a key, `[host]:port` outside port 22
+fn known_hosts_line(host: &str, port: u16, key_type: &str, key: &[u8]) -> String {
+    let host = host.trim().to_lowercase();
+    let pattern = if port == 22 { host } else { format!("[{}]:{}", host, port) };
+    format!("{} {} {}", pattern, key_type, base64::engine::general_purpose::STANDARD.encode(key))
+}
+
+/// Like `probe`, with the presented key as a known_hosts line to pin when
+/// the check says it is the trusted one
+pub fn probe_known_hosts_line(
+    app_data_dir: &Path,
+    host: &str,
+    port: u16,
+    proxy: Option<&ProxyConfig>,
+) -> Result<(HostKeyCheck, String), String> {
+    let sess = handshake(host, port, proxy)?;
+    let (key_type, fingerprint) = session_fingerprint(&sess)?;
+    let (key, _) = sess.host_key().ok_or("Le serveur n'a pas presente de cle d'hote")?;
+    let line = known_hosts_line(host, port, &key_type, key);
+    Ok((check(app_data_dir, host, port, &key_type, &fingerprint), line))
+}
+
+/// Trust a fingerprint confirmed by the user, replacing the previous key
+pub fn trust(app_data_dir: &Path, host: &str, port: u16, key_type: &str, fingerprint: &str) -> Result<KnownHost, String> {
+    let _guard = STORE_LOCK.lock().map_err(|_| "Failed to access known hosts".to_string())?;
+    let mut hosts = load(app_data_dir);
+    let known = KnownHost {
+        host: host.trim().to_lowercase(),
+        port,
+        key_type: key_type.to_string(),
+        fingerprint: fingerprint.to_string(),
+        added_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+    };
+    hosts.insert(host_id(host, port), known.clone());
+    state_file::write_json(&store_path(app_data_dir), &hosts)?;
+    println!("[KnownHosts] Trusted {} key of {}:{}", key_type, host, port);
+    Ok(known)
+}
+
+pub fn list(app_data_dir: &Path) -> Vec<KnownHost> {
+    load(app_data_dir).into_values().collect()
+}
+
+pub fn forget(app_data_dir: &Path, host: &str, port: u16) -> Result<(), String> {
+    let _guard = STORE_LOCK.lock().map_err(|_| "Failed to access known hosts".to_string())?;
+    let mut hosts = load(app_data_dir);
+    if hosts.remove(&host_id(host, port)).is_none() {
+        return Err(format!("Hote inconnu: {}:{}", host, port));
+    }
+    state_file::write_json(&store_path(app_data_dir), &hosts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_matches_ssh_keygen_format() {
+        assert_eq!(fingerprint(b""), "SHA256:");
+        assert_eq!(fingerprint(b"f"), "SHA256:Zg");
+        assert_eq!(fingerprint(b"fo"), "SHA256:Zm8");
+        assert_eq!(fingerprint(b"foo"), "SHA256:Zm9v");
+        assert_eq!(fingerprint(b"foobar"), "SHA256:Zm9vYmFy");
+    }
+
+    #[test]
+    fn test_known_hosts_line_brackets_non_default_ports() {
+        assert_eq!(known_hosts_line("Example.com", 22, "ssh-ed25519", b"fo"), "example.com ssh-ed25519 Zm8=");
+        assert_eq!(known_hosts_line("example.com", 2222, "ssh-rsa", b"foo"), "[example.com]:2222 ssh-rsa Zm9v");
+    }
+}
@@ -4,6 +4,7 @@ mod watcher;
 mod scraper;
 mod tray;
 mod parallel_sync;
+mod concurrency_tuning;
 mod version_history;
 mod scheduler;
 mod transfer_resume;
@@ -11,19 +12,98 @@ mod delta_sync;
 mod full_site_scraper;
 mod scrape_cache;
 mod ide_monitor;
+mod ide_activity;
+mod idle_monitor;
+mod pomodoro;
+mod calendar_export;
+mod remote_exec;
+mod db_dump;
+mod permissions_audit;
+mod sync_presets;
+mod site_migration;
+mod transfer_quota;
+mod delta_warmup;
+mod simulation;
+mod data_location;
+mod app_backup;
+mod onboarding;
+mod monitor_windows;
+mod notifications;
+mod app_update;
+mod state_file;
+mod storage;
+mod shared_scrape_cache;
+mod offline_queue;
+mod activity_feed;
+mod ssh_auth;
+mod events;
+mod history_search;
+mod deploy_guard;
+mod known_hosts;
+mod readonly_mode;
+mod webdav;
+mod webhook_receiver;
+mod scrape_convert;
+mod rsync_sync;
+mod pull_sync;
+mod two_way_sync;
+mod tar_upload;
+mod site_backup;
+mod ignore_rules;
+mod upload_verify;
+mod menu_config;
+mod notification_quiet;
+mod remote_trash;
+mod file_provenance;
+mod content_inventory;
+mod atomic_upload;
+mod remote_fs;
+mod automation_pause;
+mod file_attributes;
+mod file_type_stats;
+mod inventory_export;
+mod image_gallery;
+mod mtime_diff;
+mod remote_monitor;
+mod remote_listing_cache;
+mod remote_rules;
+mod connection_profiles;
+mod maintenance_mode;
+mod build_hook;
+mod project_env;
+mod sync_queue;
+mod sync_history;
+mod diff_report;
+mod folder_analysis;
+mod deploy_import;
+mod ftp_listing;
+mod image_metadata;
+mod protocol_detect;
+mod proxy;
+mod deploy_manifest;
+mod sync_lock;
+mod watchdog;
+mod scrape_capture;
+mod scrape_queue;
+mod css_usage;
+mod color_palette;
+mod font_audit;
+mod scrape_refresh;
+mod scrape_attribution;
+#[cfg(test)]
+mod test_support;
 
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::{self, File};
-use std::io::{Read, Write};
-use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::net::{SocketAddr, ToSocketAddrs};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
 use tauri::{
-    CustomMenuItem, Manager, Menu, MenuItem, State, Submenu, WindowMenuEvent,
+    Manager, Menu, MenuItem, State, Submenu, WindowMenuEvent,
 };
 use walkdir::WalkDir;
 
@@ -60,6 +140,8 @@ fn set_scrape_cancelled(project_id: &str, value: bool) {
 }
 
 fn is_cancelled(project_id: &str) -> bool {
+    // Sync loops check at every file and folder, which doubles as their heartbeat
+    watchdog::beat("sync", project_id);
     CANCEL_FLAGS
         .lock()
         .map(|flags| *flags.get(project_id).unwrap_or(&false))
@@ -76,14 +158,38 @@ fn set_cancelled(project_id: &str, value: bool) {
     }
 }
 
+/// Projects with a sync cancellation pending
+fn cancelled_syncs() -> Vec<String> {
+    CANCEL_FLAGS
+        .lock()
+        .map(|flags| flags.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Projects whose scrape cancel flag is set
+fn cancelled_scrapes() -> Vec<String> {
+    SCRAPE_CANCEL_FLAGS
+        .lock()
+        .map(|flags| {
+            flags
+                .iter()
+                .filter(|(_, flag)| flag.load(std::sync::atomic::Ordering::Relaxed))
+                .map(|(project_id, _)| project_id.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 // ============================================
 // Sync Progress Event Structure
 // ============================================
 
 #[derive(Clone, Serialize)]
 struct SyncProgressEvent {
+    /// events::SCHEMA_VERSION
+    schema_version: u32,
     project_id: String,
-    event: String, // "connecting", "file_start", "file_progress", "file_complete", "file_error", "complete", "error", "cancelled"
+    event: events::SyncEventKind,
     file: Option<String>,
     progress: u32,           // 0-100 overall progress
     file_progress: Option<u32>, // 0-100 for current file
@@ -110,18 +216,35 @@ struct DirectoryNode {
     modified: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct SFTPConfig {
     host: String,
     port: u16,
     username: String,
+    /// May be empty when authenticating with ssh-agent or a key
+    #[serde(default)]
     password: String,
     #[serde(rename = "remotePath")]
     remote_path: String,
     passive: Option<bool>,
+    /// "sftp", "ftp", "ftps" or "webdav"
     protocol: Option<String>,
     #[serde(rename = "acceptInvalidCerts")]
     accept_invalid_certs: Option<bool>,
+    /// SFTP: try ssh-agent, then the key file, then the password (see ssh_auth)
+    #[serde(rename = "useSshAgent")]
+    use_ssh_agent: Option<bool>,
+    /// Private key used after the agent, defaults to ~/.ssh/id_ed25519, id_ecdsa or id_rsa
+    #[serde(rename = "privateKeyPath")]
+    private_key_path: Option<String>,
+    #[serde(rename = "keyPassphrase")]
+    key_passphrase: Option<String>,
+    /// SFTP: ask for a one-time code (keyboard-interactive) through the UI, see ssh_auth
+    #[serde(rename = "keyboardInteractive")]
+    keyboard_interactive: Option<bool>,
+    /// Route SFTP, FTP and WebDAV traffic through a SOCKS5 or HTTP CONNECT proxy
+    #[serde(default)]
+    proxy: Option<proxy::ProxyConfig>,
 }
 
 /// Sync options for configuring upload behavior
@@ -133,13 +256,93 @@ struct SyncOptions {
     /// Number of parallel connections (default: 4, max: 8)
     #[serde(default = "default_parallel_connections")]
     parallel_connections: usize,
+    /// SFTP/FTP: start at 2 connections and tune up to `parallel_connections`
+    /// from the measured throughput and refused logins (see concurrency_tuning)
+    #[serde(default)]
+    adaptive_connections: bool,
     /// Create version snapshot before sync (default: false)
     #[serde(default)]
     create_snapshot: bool,
+    /// Dry runs: diff against the project's cached remote listing when recent (see remote_listing_cache)
+    #[serde(default)]
+    use_cached_remote: bool,
     /// Snapshot message/description
     snapshot_message: Option<String>,
+    /// Upload a `.laforge-manifest.json` integrity manifest after sync (default: false)
+    #[serde(default)]
+    upload_manifest: bool,
+    /// What started the sync ("manual", "scheduled", ...), shown when the project is busy
+    trigger: Option<String>,
+    /// Files larger than this (bytes) are not uploaded and reported as "oversized"
+    max_file_size: Option<u64>,
+    /// Relative paths uploaded even when larger than `max_file_size`
+    #[serde(default)]
+    large_file_overrides: Vec<String>,
+    /// Phrase typed by the user, required to sync a production target
+    confirmation: Option<String>,
+    /// Transfer engine of SFTP targets: "sftp" (default), "rsync" or "tar" (chunks over SSH)
+    engine: Option<String>,
+    /// Mirror mode: delete remote files (and emptied folders) missing locally
+    #[serde(default)]
+    delete_orphans: bool,
+    /// Preset whose protected paths and delete policy bound mirror mode: an id,
+    /// "none", or detected from the local folder when absent
+    sync_preset: Option<String>,
+    /// Mirror mode refuses the sync above this many deletions (default: 50)
+    max_deletions: Option<usize>,
+    /// Days the files deleted by mirror mode stay in the remote trash (default: 30)
+    trash_retention_days: Option<u32>,
+    /// After the upload, read back and hash this percentage (0-100) of the uploaded files
+    verify_sample_percent: Option<f64>,
+    /// Uploaded files above this size (bytes) are always read back when sampling (default: 20 MB)
+    verify_size_threshold: Option<u64>,
+    /// Write each file as `<name>.laforge-tmp` and rename it once complete (default: false);
+    /// rsync renames all files at the end, tar extracts into a staging folder first
+    #[serde(default)]
+    atomic_upload: bool,
+    /// SFTP: give uploaded files the mode of the local file, executable bits included (default: false)
+    #[serde(default)]
+    preserve_permissions: bool,
+    /// SFTP/FTP: set the remote modification time to the local one after upload (default: true)
+    preserve_mtime: Option<bool>,
+    /// Clock skew (seconds) allowed when comparing modification times of equal-size files (default: 120)
+    mtime_tolerance_secs: Option<u64>,
+    /// Only sync the files matching one of these globs (e.g. "dist/**")
+    #[serde(default)]
+    include: Vec<String>,
+    /// Leave the files matching these globs out of the sync (e.g. "*.psd")
+    #[serde(default)]
+    exclude: Vec<String>,
+    /// Put the site behind a maintenance page during the upload, lifted afterwards
+    /// even when the sync fails (see maintenance_mode)
+    maintenance: Option<maintenance_mode::MaintenanceOptions>,
+    /// Local command run before a real sync (e.g. "npm run build"), a failure aborts the sync
+    pre_sync_command: Option<String>,
+    /// Folder the command runs in (default: the synced folder)
+    pre_sync_cwd: Option<String>,
+    /// Seconds the command may run (default: 600)
+    pre_sync_timeout_secs: Option<u64>,
+    /// SFTP: command run over SSH from the remote root after a successful sync
+    /// (e.g. "php artisan cache:clear"); a failure is reported, the sync stays successful
+    post_sync_command: Option<String>,
+    /// Seconds the remote command may run (default: 120)
+    post_sync_timeout_secs: Option<u64>,
+}
+
+/// How the SFTP/FTP engines write each file
+#[derive(Debug, Clone, Copy, Default)]
+struct UploadOptions {
+    /// Upload under a temporary name, then rename (`atomic_upload`)
+    atomic: bool,
+    /// SFTP: apply the local mode after the upload (`preserve_permissions`)
+    preserve_permissions: bool,
+    /// Copy the local modification time to the remote file (`preserve_mtime`)
+    preserve_mtime: bool,
 }
 
+/// Deletions allowed by mirror mode when `max_deletions` isn't set
+const DEFAULT_MAX_DELETIONS: usize = 50;
+
 fn default_parallel_enabled() -> bool { true }
 fn default_parallel_connections() -> usize { 4 }
 
@@ -150,18 +353,21 @@ struct FileEntry {
     size: u64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct FileDiff {
     path: String,
-    status: String, // "added", "modified", "deleted", "unchanged"
+    status: String, // "added", "modified", "deleted", "unchanged", "oversized"
     #[serde(rename = "localSize")]
     local_size: Option<u64>,
     #[serde(rename = "remoteSize")]
     remote_size: Option<u64>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct RemoteFile {
     size: u64,
+    /// Unix seconds, when the protocol listing gives it
+    mtime: Option<i64>,
 }
 
 fn resolve_addr(host: &str, port: u16) -> Result<SocketAddr, String> {
@@ -237,6 +443,27 @@ fn sftp_test_connection(config: SFTPConfig) -> Result<bool, String> {
         match protocol {
             "sftp" => test_sftp_connection(&config),
             "ftp" | "ftps" => test_ftp_connection(&config),
+            "webdav" => webdav::test_connection(&config),
+            "detect" => {
+                let report = protocol_detect::detect_protocols(&config);
+                match report.suggested_protocol {
+                    Some(_) => Ok(true),
+                    None => Err(format!(
+                        "Aucun protocole n'a fonctionné: {}",
+                        report
+                            .probes
+                            .iter()
+                            .map(|p| format!(
+                                "{}:{} ({})",
+                                p.protocol,
+                                p.port,
+                                p.error.as_deref().unwrap_or("?")
+                            ))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )),
+                }
+            }
             _ => Err(format!("Unknown protocol: {}", protocol)),
         }
     }));
@@ -254,13 +481,18 @@ fn sftp_test_connection(config: SFTPConfig) -> Result<bool, String> {
     }
 }
 
+/// Probe SFTP/FTPS/FTP on the host and return which ones work,
+/// with banners, features and a suggested configuration
+#[tauri::command]
+fn sftp_detect_protocol(config: SFTPConfig) -> Result<protocol_detect::ProtocolDetectionResult, String> {
+    println!("[Rust] sftp_detect_protocol called with host: {}", config.host);
+    Ok(protocol_detect::detect_protocols(&config))
+}
+
 fn test_sftp_connection(config: &SFTPConfig) -> Result<bool, String> {
-    println!("[Rust] test_sftp_connection: resolving address...");
-    let addr = resolve_addr(&config.host, config.port)?;
-    println!("[Rust] test_sftp_connection: connecting to {:?}...", addr);
+    println!("[Rust] test_sftp_connection: connecting to {}:{}...", config.host, config.port);
 
-    let tcp = TcpStream::connect_timeout(&addr, Duration::from_secs(10))
-        .map_err(|e| format!("Connection failed: {}", e))?;
+    let tcp = proxy::connect(config.proxy.as_ref(), &config.host, config.port, Duration::from_secs(10))?;
     println!("[Rust] test_sftp_connection: TCP connected");
 
     tcp.set_read_timeout(Some(Duration::from_secs(60)))
@@ -277,8 +509,7 @@ fn test_sftp_connection(config: &SFTPConfig) -> Result<bool, String> {
         .map_err(|e| format!("SSH handshake failed: {}", e))?;
 
     println!("[Rust] test_sftp_connection: authenticating...");
-    sess.userauth_password(&config.username, &config.password)
-        .map_err(|e| format!("Authentication failed: {}", e))?;
+    ssh_auth::authenticate(&sess, config)?;
 
     if sess.authenticated() {
         println!("[Rust] test_sftp_connection: SUCCESS");
@@ -290,13 +521,10 @@ fn test_sftp_connection(config: &SFTPConfig) -> Result<bool, String> {
 }
 
 fn test_ftp_connection(config: &SFTPConfig) -> Result<bool, String> {
-    println!("[Rust] test_ftp_connection: resolving address...");
-    let addr = resolve_addr(&config.host, config.port)?;
     let passive = config.passive.unwrap_or(true);
-    println!("[Rust] test_ftp_connection: connecting to {:?} (passive={})", addr, passive);
+    println!("[Rust] test_ftp_connection: connecting to {}:{} (passive={})", config.host, config.port, passive);
 
-    let mut ftp = suppaftp::FtpStream::connect_timeout(addr, Duration::from_secs(10))
-        .map_err(|e| format!("FTP connection failed: {}", e))?;
+    let mut ftp = proxy::connect_ftp(config.proxy.as_ref(), &config.host, config.port, Duration::from_secs(10))?;
 
     // Set timeouts on FTP connection
     ftp.get_ref()
@@ -323,6 +551,460 @@ fn test_ftp_connection(config: &SFTPConfig) -> Result<bool, String> {
     Ok(true)
 }
 
+// ============================================
+// Remote Console Commands
+// ============================================
+
+/// Run a whitelisted command (or a project preset) on an SSH target
+#[tauri::command]
+fn ssh_exec_command(
+    config: SFTPConfig,
+    project_id: String,
+    command: String,
+    app_handle: tauri::AppHandle,
+) -> Result<remote_exec::RemoteCommandOutput, String> {
+    readonly_mode::ensure_writable("l'execution de commandes distantes")?;
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("No app dir")?;
+    let presets = remote_exec::load_presets(&app_dir, &project_id);
+    let env = project_env::resolve(&app_dir, &project_id);
+    remote_exec::exec_for_project(&config, &command, &presets, &env)
+}
+
+#[tauri::command]
+fn get_remote_command_presets(
+    project_id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<remote_exec::RemoteCommandPreset>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("No app dir")?;
+    Ok(remote_exec::load_presets(&app_dir, &project_id))
+}
+
+#[tauri::command]
+fn set_remote_command_presets(
+    project_id: String,
+    presets: Vec<remote_exec::RemoteCommandPreset>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("No app dir")?;
+    remote_exec::save_presets(&app_dir, &project_id, presets)
+}
+
+// ============================================
+// Database Dump Commands
+// ============================================
+
+/// Dump the site database over SSH into the project's backups/db folder
+#[tauri::command]
+async fn db_dump_run(
+    config: SFTPConfig,
+    db: db_dump::DbDumpConfig,
+    project_path: String,
+) -> Result<db_dump::DbDumpResult, String> {
+    readonly_mode::ensure_writable("le dump de base de donnees")?;
+    tokio::task::spawn_blocking(move || db_dump::dump_database(&config, &db, &project_path))
+        .await
+        .map_err(|e| format!("Dump task failed: {}", e))?
+}
+
+#[tauri::command]
+fn list_db_dumps(project_path: String) -> Vec<String> {
+    db_dump::list_dumps(&project_path)
+}
+
+// ============================================
+// Site Backup Commands
+// ============================================
+
+/// Archive the live site into the project's backups/site folder
+#[tauri::command]
+async fn backup_remote_site(
+    config: SFTPConfig,
+    project_path: String,
+    project_id: String,
+    keep: Option<usize>,
+    app_handle: tauri::AppHandle,
+) -> Result<site_backup::SiteBackupResult, String> {
+    readonly_mode::ensure_writable("la sauvegarde du site")?;
+    let handle = app_handle.clone();
+    let progress_project = project_id.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        site_backup::backup_remote_site(&config, &project_path, keep.unwrap_or(site_backup::DEFAULT_KEEP), |progress| {
+            let _ = handle.emit_all(
+                events::SITE_BACKUP_PROGRESS,
+                serde_json::json!({ "projectId": progress_project, "progress": progress }),
+            );
+        })
+    })
+    .await
+    .map_err(|e| format!("Backup task failed: {}", e))?;
+
+    let event = match &result {
+        Ok(backup) => activity_feed::new_event(
+            &project_id,
+            "snapshot",
+            "Sauvegarde du site distant",
+            Some(format!("{} ({} octets)", backup.path, backup.size)),
+            Some("success"),
+            serde_json::json!({ "path": backup.path, "method": backup.method, "bytes": backup.size }),
+        ),
+        Err(e) => activity_feed::new_event(
+            &project_id,
+            "snapshot",
+            "Sauvegarde du site distant",
+            Some(e.clone()),
+            Some("error"),
+            serde_json::Value::Null,
+        ),
+    };
+    activity_feed::record(&app_handle, event);
+    result
+}
+
+#[tauri::command]
+fn list_site_backups(project_path: String) -> Vec<String> {
+    site_backup::list_backups(&project_path)
+}
+
+// ============================================
+// Remote Trash Commands
+// ============================================
+
+/// Files deleted from the server by mirror mode and kept in the project's remote trash
+#[tauri::command]
+fn list_remote_trash(project_id: String, app_handle: tauri::AppHandle) -> Result<Vec<remote_trash::TrashEntry>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(remote_trash::list(&app_dir, &project_id))
+}
+
+/// Upload selected remote trash entries back to the server
+#[tauri::command]
+async fn restore_remote_trash(
+    project_id: String,
+    config: SFTPConfig,
+    entry_ids: Vec<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<remote_trash::TrashRestoreResult, String> {
+    readonly_mode::ensure_writable("la restauration de fichiers distants")?;
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    let _sync_lock = sync_lock::try_acquire(&project_id, "restore-trash")?;
+    let pid = project_id.clone();
+    let result = tokio::task::spawn_blocking(move || remote_trash::restore(&app_dir, &pid, &config, &entry_ids))
+        .await
+        .map_err(|e| format!("Trash restore task failed: {}", e))?;
+
+    let (status, detail) = match &result {
+        Ok(restored) if restored.failed.is_empty() => ("success", format!("{} fichier(s) restaure(s)", restored.restored.len())),
+        Ok(restored) => ("error", format!("{} fichier(s) en erreur", restored.failed.len())),
+        Err(e) => ("error", e.clone()),
+    };
+    activity_feed::record(
+        &app_handle,
+        activity_feed::new_event(
+            &project_id,
+            "sync",
+            "Restauration depuis la corbeille distante",
+            Some(detail),
+            Some(status),
+            serde_json::json!({
+                "restored": result.as_ref().map(|r| r.restored.len()).unwrap_or(0),
+            }),
+        ),
+    );
+    result
+}
+
+// ============================================
+// Remote Rules Commands
+// ============================================
+
+#[tauri::command]
+fn list_rule_recipes() -> Vec<remote_rules::RuleRecipe> {
+    remote_rules::RECIPES.to_vec()
+}
+
+/// Read a `.htaccess` or nginx redirect file from the server
+#[tauri::command]
+async fn read_remote_rules(
+    project_id: String,
+    config: SFTPConfig,
+    file: String,
+    app_handle: tauri::AppHandle,
+) -> Result<remote_rules::RemoteRules, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    tokio::task::spawn_blocking(move || remote_rules::read_remote(&app_dir, &project_id, &config, &file))
+        .await
+        .map_err(|e| format!("Rules task failed: {}", e))?
+}
+
+#[tauri::command]
+fn validate_remote_rules(file: String, content: String) -> Vec<remote_rules::RuleIssue> {
+    remote_rules::validate(remote_rules::flavor_of(&file), &content)
+}
+
+/// Add or remove a recipe's block in the edited content, nothing is uploaded
+#[tauri::command]
+fn apply_rule_recipe(
+    file: String,
+    content: String,
+    recipe: String,
+    options: Option<remote_rules::RecipeOptions>,
+    enabled: bool,
+) -> Result<String, String> {
+    remote_rules::apply_recipe(
+        remote_rules::flavor_of(&file),
+        &content,
+        &recipe,
+        &options.unwrap_or_default(),
+        enabled,
+    )
+}
+
+/// Check and upload a rules file, keeping the server's previous version locally
+#[tauri::command]
+async fn deploy_remote_rules(
+    project_id: String,
+    config: SFTPConfig,
+    file: String,
+    content: String,
+    app_handle: tauri::AppHandle,
+) -> Result<remote_rules::RulesDeployResult, String> {
+    readonly_mode::ensure_writable("le deploiement des regles serveur")?;
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    let _sync_lock = sync_lock::try_acquire(&project_id, "deploy-rules")?;
+    let (dir, pid, name) = (app_dir.clone(), project_id.clone(), file.clone());
+    let result = tokio::task::spawn_blocking(move || remote_rules::deploy(&dir, &pid, &config, &name, &content))
+        .await
+        .map_err(|e| format!("Rules task failed: {}", e))?;
+
+    if result.is_ok() {
+        remote_listing_cache::invalidate(&app_dir, &project_id);
+    }
+    let (status, detail) = match &result {
+        Ok(deployed) => ("success", format!("{} ({} octets)", deployed.file, deployed.version.size)),
+        Err(e) => ("error", e.clone()),
+    };
+    activity_feed::record(
+        &app_handle,
+        activity_feed::new_event(
+            &project_id,
+            "sync",
+            "Deploiement des regles serveur",
+            Some(detail),
+            Some(status),
+            serde_json::json!({ "file": file }),
+        ),
+    );
+    result
+}
+
+#[tauri::command]
+fn list_remote_rules_versions(
+    project_id: String,
+    file: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<remote_rules::RulesVersion>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(remote_rules::list_versions(&app_dir, &project_id, file.as_deref()))
+}
+
+/// Content of a kept version, to edit or deploy it back
+#[tauri::command]
+fn read_remote_rules_version(project_id: String, version_id: String, app_handle: tauri::AppHandle) -> Result<String, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    remote_rules::read_version(&app_dir, &project_id, &version_id)
+}
+
+// ============================================
+// Maintenance Mode Commands
+// ============================================
+
+#[tauri::command]
+fn get_maintenance_mode(
+    project_id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<Option<maintenance_mode::MaintenanceState>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(maintenance_mode::state(&app_dir, &project_id))
+}
+
+/// Put the remote site behind its maintenance page until disable_maintenance_mode
+#[tauri::command]
+async fn enable_maintenance_mode(
+    project_id: String,
+    config: SFTPConfig,
+    options: Option<maintenance_mode::MaintenanceOptions>,
+    app_handle: tauri::AppHandle,
+) -> Result<maintenance_mode::MaintenanceState, String> {
+    readonly_mode::ensure_writable("le mode maintenance")?;
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    let _sync_lock = sync_lock::try_acquire(&project_id, "maintenance")?;
+    let pid = project_id.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        maintenance_mode::enable(&app_dir, &pid, &config, &options.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| format!("Maintenance task failed: {}", e))?;
+    record_maintenance_activity(&app_handle, &project_id, "Mode maintenance active", result.as_ref().err());
+    result
+}
+
+/// Lift maintenance, also after a sync that couldn't; `local_path` deploys the project's rules file
+#[tauri::command]
+async fn disable_maintenance_mode(
+    project_id: String,
+    config: SFTPConfig,
+    local_path: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<maintenance_mode::MaintenanceCleanup, String> {
+    readonly_mode::ensure_writable("le mode maintenance")?;
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    let _sync_lock = sync_lock::try_acquire(&project_id, "maintenance")?;
+    let pid = project_id.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        maintenance_mode::disable(&app_dir, &pid, &config, local_path.as_deref().map(Path::new))
+    })
+    .await
+    .map_err(|e| format!("Maintenance task failed: {}", e))?;
+    if !matches!(result, Ok(ref cleanup) if !cleanup.was_enabled) {
+        record_maintenance_activity(&app_handle, &project_id, "Mode maintenance desactive", result.as_ref().err());
+    }
+    result
+}
+
+fn record_maintenance_activity(app_handle: &tauri::AppHandle, project_id: &str, title: &str, error: Option<&String>) {
+    activity_feed::record(
+        app_handle,
+        activity_feed::new_event(
+            project_id,
+            "sync",
+            title,
+            error.cloned(),
+            Some(if error.is_some() { "error" } else { "success" }),
+            serde_json::Value::Null,
+        ),
+    );
+}
+
+// ============================================
+// Project Env Commands
+// ============================================
+
+/// Variables of a project; secret values are never returned
+#[tauri::command]
+fn list_project_env(project_id: String, app_handle: tauri::AppHandle) -> Result<Vec<project_env::ProjectVariable>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(project_env::list(&app_dir, &project_id))
+}
+
+#[tauri::command]
+fn set_project_env(
+    project_id: String,
+    name: String,
+    value: String,
+    secret: Option<bool>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<project_env::ProjectVariable>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    project_env::set(&app_dir, &project_id, name.trim(), &value, secret.unwrap_or(false))?;
+    Ok(project_env::list(&app_dir, &project_id))
+}
+
+#[tauri::command]
+fn remove_project_env(
+    project_id: String,
+    name: String,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<project_env::ProjectVariable>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    project_env::remove(&app_dir, &project_id, &name)?;
+    Ok(project_env::list(&app_dir, &project_id))
+}
+
+/// Replace `${NAME}` in a config value or report template with the project's variables
+#[tauri::command]
+fn substitute_project_vars(project_id: String, text: String, app_handle: tauri::AppHandle) -> Result<String, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(project_env::substitute(&text, &project_env::resolve(&app_dir, &project_id)))
+}
+
+// ============================================
+// File Provenance Commands
+// ============================================
+
+/// Deploy and snapshot history of one project file, for the file inspector
+#[tauri::command]
+fn get_file_provenance(
+    project_id: String,
+    path: String,
+    local_path: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<Option<file_provenance::FileProvenance>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(file_provenance::get(&app_dir, &project_id, &path, local_path.as_deref()))
+}
+
+/// Tracked files of a project, most recently deployed first
+#[tauri::command]
+fn list_file_provenance(
+    project_id: String,
+    prefix: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<file_provenance::FileProvenance>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(file_provenance::list(&app_dir, &project_id, prefix.as_deref()))
+}
+
+// ============================================
+// Permissions Audit Commands
+// ============================================
+
+/// Report permission problems under a remote path, optionally fixing them with chmod
+#[tauri::command]
+async fn sftp_audit_permissions(
+    config: SFTPConfig,
+    path: Option<String>,
+    fix: bool,
+) -> Result<permissions_audit::PermissionAuditResult, String> {
+    if fix {
+        readonly_mode::ensure_writable("la correction des permissions")?;
+    }
+    tokio::task::spawn_blocking(move || {
+        permissions_audit::audit_permissions(&config, path.as_deref().unwrap_or(""), fix)
+    })
+    .await
+    .map_err(|e| format!("Audit task failed: {}", e))?
+}
+
+// ============================================
+// Sync Presets Commands
+// ============================================
+
+#[tauri::command]
+fn get_sync_presets() -> Vec<sync_presets::SyncPreset> {
+    sync_presets::all_presets()
+}
+
+/// Suggest a preset (WordPress, Prestashop...) from the local project files
+#[tauri::command]
+fn detect_sync_preset(local_path: String) -> Option<sync_presets::SyncPreset> {
+    sync_presets::detect_preset(&local_path)
+}
+
+/// Inspect a dropped folder (CMS, git, other editors' deploy settings, file types)
+/// and suggest the configuration of the new project
+#[tauri::command]
+async fn analyze_folder(path: String) -> Result<folder_analysis::FolderAnalysis, String> {
+    tokio::task::spawn_blocking(move || folder_analysis::analyze(&path))
+        .await
+        .map_err(|e| format!("Folder analysis task failed: {}", e))?
+}
+
+/// Sync targets from the `.vscode/sftp.json` or `.idea/deployment.xml` of a folder
+#[tauri::command]
+fn import_deploy_configs(path: String) -> Result<Vec<deploy_import::ImportedTarget>, String> {
+    deploy_import::import(&path)
+}
+
 #[tauri::command]
 fn sftp_list_files(config: SFTPConfig, path: String) -> Result<Vec<String>, String> {
     let protocol = config.protocol.as_deref().unwrap_or("ftp");
@@ -330,22 +1012,19 @@ fn sftp_list_files(config: SFTPConfig, path: String) -> Result<Vec<String>, Stri
     match protocol {
         "sftp" => list_sftp_files(&config, &path),
         "ftp" | "ftps" => list_ftp_files(&config, &path),
+        "webdav" => webdav::list_files(&config, &path),
         _ => Err(format!("Unknown protocol: {}", protocol)),
     }
 }
 
 fn list_sftp_files(config: &SFTPConfig, path: &str) -> Result<Vec<String>, String> {
-    let addr = resolve_addr(&config.host, config.port)?;
-
-    let tcp = TcpStream::connect_timeout(&addr, Duration::from_secs(10))
-        .map_err(|e| format!("Connection failed: {}", e))?;
+    let tcp = proxy::connect(config.proxy.as_ref(), &config.host, config.port, Duration::from_secs(10))?;
 
     let mut sess = ssh2::Session::new().map_err(|e| format!("Failed to create session: {}", e))?;
     sess.set_tcp_stream(tcp);
     sess.handshake()
         .map_err(|e| format!("SSH handshake failed: {}", e))?;
-    sess.userauth_password(&config.username, &config.password)
-        .map_err(|e| format!("Authentication failed: {}", e))?;
+    ssh_auth::authenticate(&sess, config)?;
 
     let sftp = sess
         .sftp()
@@ -370,11 +1049,9 @@ fn list_sftp_files(config: &SFTPConfig, path: &str) -> Result<Vec<String>, Strin
 }
 
 fn list_ftp_files(config: &SFTPConfig, path: &str) -> Result<Vec<String>, String> {
-    let addr = resolve_addr(&config.host, config.port)?;
     let passive = config.passive.unwrap_or(true);
 
-    let mut ftp = suppaftp::FtpStream::connect_timeout(addr, Duration::from_secs(10))
-        .map_err(|e| format!("FTP connection failed: {}", e))?;
+    let mut ftp = proxy::connect_ftp(config.proxy.as_ref(), &config.host, config.port, Duration::from_secs(10))?;
 
     ftp.login(&config.username, &config.password)
         .map_err(|e| format!("FTP login failed: {}", e))?;
@@ -422,8 +1099,10 @@ fn scan_local_files(local_path: &str) -> Result<HashMap<String, u64>, String> {
         return Err(format!("Local path does not exist: {}", local_path));
     }
 
+    let ignore = ignore_rules::IgnoreRules::load(base_path);
     for entry in WalkDir::new(local_path)
         .into_iter()
+        .filter_entry(|e| ignore.keeps_entry(base_path, e))
         .filter_map(|e| e.ok())
     {
         let path = entry.path();
@@ -453,25 +1132,111 @@ fn scan_local_files(local_path: &str) -> Result<HashMap<String, u64>, String> {
     Ok(files)
 }
 
+/// Progress reporting and cancellation for remote tree walks
+struct RemoteScanContext<'a> {
+    project_id: Option<&'a str>,
+    app_handle: Option<&'a tauri::AppHandle>,
+    dirs_visited: usize,
+    files_found: usize,
+    last_emit: std::time::Instant,
+    /// Also list dotfiles (.htaccess...), skipped by syncs
+    include_hidden: bool,
+}
+
+impl<'a> RemoteScanContext<'a> {
+    fn new(project_id: &'a str, app_handle: &'a tauri::AppHandle) -> Self {
+        Self {
+            project_id: Some(project_id),
+            app_handle: Some(app_handle),
+            dirs_visited: 0,
+            files_found: 0,
+            last_emit: std::time::Instant::now(),
+            include_hidden: false,
+        }
+    }
+
+    /// Context without progress events or cancellation
+    fn silent() -> Self {
+        Self {
+            project_id: None,
+            app_handle: None,
+            dirs_visited: 0,
+            files_found: 0,
+            last_emit: std::time::Instant::now(),
+            include_hidden: false,
+        }
+    }
+
+    fn with_hidden(mut self) -> Self {
+        self.include_hidden = true;
+        self
+    }
+
+    /// Called before listing a directory; fails if the project sync was cancelled
+    fn enter_dir(&mut self, path: &str) -> Result<(), String> {
+        if let Some(project_id) = self.project_id {
+            if is_cancelled(project_id) {
+                return Err("Synchronisation annulée".to_string());
+            }
+        }
+        self.dirs_visited += 1;
+        // Throttle events so huge trees don't flood the frontend
+        if self.last_emit.elapsed() >= Duration::from_millis(250) {
+            self.emit(Some(path));
+        }
+        Ok(())
+    }
+
+    fn add_file(&mut self) {
+        self.files_found += 1;
+    }
+
+    fn emit(&mut self, path: Option<&str>) {
+        self.last_emit = std::time::Instant::now();
+        if let (Some(project_id), Some(app_handle)) = (self.project_id, self.app_handle) {
+            let _ = app_handle.emit_all(
+                events::SYNC_PROGRESS,
+                SyncProgressEvent {
+                    project_id: project_id.to_string(),
+                    schema_version: events::SCHEMA_VERSION,
+                    event: events::SyncEventKind::Analyzing,
+                    file: path.map(|s| s.to_string()),
+                    progress: 10,
+                    file_progress: None,
+                    bytes_sent: None,
+                    bytes_total: None,
+                    message: Some(format!(
+                        "Analyse du serveur : {} dossiers, {} fichiers",
+                        self.dirs_visited, self.files_found
+                    )),
+                    timestamp: std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_millis() as u64,
+                },
+            );
+        }
+    }
+}
+
 // Scan remote directory via SFTP
 fn scan_sftp_remote_files(
     config: &SFTPConfig,
     remote_base: &str,
+    scan: &mut RemoteScanContext,
 ) -> Result<HashMap<String, RemoteFile>, String> {
-    let addr = resolve_addr(&config.host, config.port)?;
-    let tcp = TcpStream::connect_timeout(&addr, Duration::from_secs(10))
-        .map_err(|e| format!("Connection failed: {}", e))?;
+    let tcp = proxy::connect(config.proxy.as_ref(), &config.host, config.port, Duration::from_secs(10))?;
 
     let mut sess = ssh2::Session::new().map_err(|e| format!("Session error: {}", e))?;
     sess.set_tcp_stream(tcp);
     sess.handshake().map_err(|e| format!("Handshake failed: {}", e))?;
-    sess.userauth_password(&config.username, &config.password)
-        .map_err(|e| format!("Auth failed: {}", e))?;
+    ssh_auth::authenticate(&sess, config)?;
 
     let sftp = sess.sftp().map_err(|e| format!("SFTP error: {}", e))?;
 
     let mut files = HashMap::new();
-    scan_sftp_directory(&sftp, remote_base, remote_base, &mut files)?;
+    scan_sftp_directory(&sftp, remote_base, remote_base, &mut files, scan)?;
+    scan.emit(None);
 
     Ok(files)
 }
@@ -481,7 +1246,10 @@ fn scan_sftp_directory(
     base_path: &str,
     current_path: &str,
     files: &mut HashMap<String, RemoteFile>,
+    scan: &mut RemoteScanContext,
 ) -> Result<(), String> {
+    scan.enter_dir(current_path)?;
+
     let entries = sftp
         .readdir(Path::new(current_path))
         .map_err(|e| format!("Failed to read dir {}: {}", current_path, e))?;
@@ -489,14 +1257,14 @@ fn scan_sftp_directory(
     for (path_buf, stat) in entries {
         if let Some(name) = path_buf.file_name() {
             let name_str = name.to_string_lossy().to_string();
-            if name_str.starts_with('.') {
+            if name_str.starts_with('.') && !scan.include_hidden {
                 continue;
             }
 
             let full_path = path_buf.to_string_lossy().to_string();
 
             if stat.is_dir() {
-                scan_sftp_directory(sftp, base_path, &full_path, files)?;
+                scan_sftp_directory(sftp, base_path, &full_path, files, scan)?;
             } else if stat.is_file() {
                 // Get relative path
                 let relative = full_path
@@ -508,8 +1276,10 @@ fn scan_sftp_directory(
                     relative.to_string(),
                     RemoteFile {
                         size: stat.size.unwrap_or(0),
+                        mtime: stat.mtime.map(|t| t as i64),
                     },
                 );
+                scan.add_file();
             }
         }
     }
@@ -517,71 +1287,112 @@ fn scan_sftp_directory(
     Ok(())
 }
 
-// Scan remote directory via FTP
-fn scan_ftp_remote_files(
+// Scan remote directory via WebDAV
+fn scan_webdav_remote_files(
     config: &SFTPConfig,
     remote_base: &str,
+    scan: &mut RemoteScanContext,
 ) -> Result<HashMap<String, RemoteFile>, String> {
-    let addr = resolve_addr(&config.host, config.port)?;
-    let passive = config.passive.unwrap_or(true);
-
-    let mut ftp = suppaftp::FtpStream::connect_timeout(addr, Duration::from_secs(10))
-        .map_err(|e| format!("FTP connection failed: {}", e))?;
-
-    ftp.login(&config.username, &config.password)
-        .map_err(|e| format!("FTP login failed: {}", e))?;
-
-    if passive {
-        ftp.set_mode(suppaftp::Mode::Passive);
-    }
+    let client = webdav::WebDavClient::connect(config)?;
+    let base = remote_base.trim_end_matches('/');
 
     let mut files = HashMap::new();
-    scan_ftp_directory(&mut ftp, remote_base, "", &mut files)?;
+    scan_webdav_directory(&client, base, base, &mut files, scan)?;
+    scan.emit(None);
 
-    let _ = ftp.quit();
     Ok(files)
 }
 
-fn scan_ftp_directory(
-    ftp: &mut suppaftp::FtpStream,
+fn scan_webdav_directory(
+    client: &webdav::WebDavClient,
+    base_path: &str,
+    current_path: &str,
+    files: &mut HashMap<String, RemoteFile>,
+    scan: &mut RemoteScanContext,
+) -> Result<(), String> {
+    scan.enter_dir(current_path)?;
+
+    for entry in client.list(current_path)? {
+        if entry.name.starts_with('.') && !scan.include_hidden {
+            continue;
+        }
+
+        if entry.is_dir {
+            scan_webdav_directory(client, base_path, &entry.path, files, scan)?;
+        } else {
+            let relative = entry
+                .path
+                .strip_prefix(base_path)
+                .unwrap_or(&entry.path)
+                .trim_start_matches('/');
+
+            files.insert(relative.to_string(), RemoteFile { size: entry.size, mtime: entry.modified });
+            scan.add_file();
+        }
+    }
+
+    Ok(())
+}
+
+// Scan remote directory via FTP
+fn scan_ftp_remote_files(
+    config: &SFTPConfig,
+    remote_base: &str,
+    scan: &mut RemoteScanContext,
+) -> Result<HashMap<String, RemoteFile>, String> {
+    let passive = config.passive.unwrap_or(true);
+
+    let mut ftp = proxy::connect_ftp(config.proxy.as_ref(), &config.host, config.port, Duration::from_secs(10))?;
+
+    ftp.login(&config.username, &config.password)
+        .map_err(|e| format!("FTP login failed: {}", e))?;
+
+    if passive {
+        ftp.set_mode(suppaftp::Mode::Passive);
+    }
+
+    let mut files = HashMap::new();
+    let mut lister = ftp_listing::FtpLister::default();
+    let result = scan_ftp_directory(&mut ftp, &mut lister, remote_base, "", &mut files, scan);
+
+    let _ = ftp.quit();
+    result?;
+    scan.emit(None);
+    Ok(files)
+}
+
+fn scan_ftp_directory(
+    ftp: &mut suppaftp::FtpStream,
+    lister: &mut ftp_listing::FtpLister,
     base_path: &str,
     relative_path: &str,
     files: &mut HashMap<String, RemoteFile>,
+    scan: &mut RemoteScanContext,
 ) -> Result<(), String> {
     let current = if relative_path.is_empty() {
         base_path.to_string()
     } else {
         format!("{}/{}", base_path, relative_path)
     };
+    scan.enter_dir(&current)?;
 
-    // List files with details
-    let list = ftp.list(Some(&current)).unwrap_or_default();
-
-    for line in list {
-        // Parse FTP LIST format (simplified)
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 9 {
-            continue;
-        }
-
-        let name = parts[8..].join(" ");
-        if name.starts_with('.') || name == "." || name == ".." {
+    for entry in lister.list(ftp, &current) {
+        if entry.name.starts_with('.') && !scan.include_hidden {
             continue;
         }
 
-        let is_dir = line.starts_with('d');
-        let size: u64 = parts.get(4).and_then(|s| s.parse().ok()).unwrap_or(0);
-
         let file_relative = if relative_path.is_empty() {
-            name.clone()
+            entry.name.clone()
         } else {
-            format!("{}/{}", relative_path, name)
+            format!("{}/{}", relative_path, entry.name)
         };
 
-        if is_dir {
-            scan_ftp_directory(ftp, base_path, &file_relative, files)?;
+        if entry.is_dir {
+            scan_ftp_directory(ftp, lister, base_path, &file_relative, files, scan)?;
         } else {
-            files.insert(file_relative, RemoteFile { size });
+            // Without MLSD the date is unknown; MDTM is asked later when needed
+            files.insert(file_relative, RemoteFile { size: entry.size, mtime: entry.modified });
+            scan.add_file();
         }
     }
 
@@ -589,22 +1400,150 @@ fn scan_ftp_directory(
 }
 
 #[tauri::command]
-fn sftp_get_diff(local_path: String, config: SFTPConfig) -> Result<Vec<FileDiff>, String> {
-    let local_files = scan_local_files(&local_path)?;
+fn sftp_get_diff(
+    local_path: String,
+    config: SFTPConfig,
+    project_id: Option<String>,
+    mtime_tolerance_secs: Option<u64>,
+    use_cached_remote: Option<bool>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<FileDiff>, String> {
+    let tolerance = Some(mtime_tolerance_secs.unwrap_or(mtime_diff::DEFAULT_TOLERANCE_SECS));
+    let use_cached = use_cached_remote.unwrap_or(false);
+    // A standalone diff can be cancelled through sftp_cancel_sync too;
+    // leave the flag alone if a sync of this project owns it
+    match project_id {
+        Some(project_id) => {
+            let owns_flag = !sync_lock::is_locked(&project_id);
+            if owns_flag {
+                set_cancelled(&project_id, false);
+            }
+            let mut scan = RemoteScanContext::new(&project_id, &app_handle);
+            let result = compute_project_diff(&local_path, &config, &project_id, &mut scan, tolerance, use_cached);
+            if owns_flag {
+                set_cancelled(&project_id, false);
+            }
+            result
+        }
+        None => compute_diff(&local_path, &config, &mut RemoteScanContext::silent(), tolerance),
+    }
+}
+
+/// Extensions and categories of the files a diff would upload, with their share of the bytes
+#[tauri::command]
+fn get_diff_file_types(diffs: Vec<FileDiff>) -> file_type_stats::FileTypeStats {
+    file_type_stats::compute(&diffs)
+}
 
-    let protocol = config.protocol.as_deref().unwrap_or("ftp");
-    let remote_path = &config.remote_path;
+/// Write a dry run's diff as a Markdown, CSV or JSON report in the project folder
+#[tauri::command]
+fn export_diff_report(
+    diffs: Vec<FileDiff>,
+    project_path: String,
+    format: Option<String>,
+    project_name: Option<String>,
+    target: Option<sync_history::SyncTarget>,
+) -> Result<diff_report::DiffReport, String> {
+    let report = diff_report::export(&diffs, &project_path, format.as_deref(), project_name.as_deref(), target.as_ref())?;
+    println!("[DiffReport] {} report written to {}", report.format, report.path);
+    Ok(report)
+}
 
-    let remote_files = match protocol {
-        "sftp" => scan_sftp_remote_files(&config, remote_path)?,
-        "ftp" | "ftps" => scan_ftp_remote_files(&config, remote_path)?,
-        _ => return Err(format!("Unknown protocol: {}", protocol)),
+/// Diff the local folder against the remote one. With `mtime_tolerance`, files of
+/// equal size are "modified" when the local copy is newer; None compares sizes only
+fn compute_diff(
+    local_path: &str,
+    config: &SFTPConfig,
+    scan: &mut RemoteScanContext,
+    mtime_tolerance: Option<u64>,
+) -> Result<Vec<FileDiff>, String> {
+    let mut remote_files = scan_remote_files(config, scan)?;
+    diff_against_remote(local_path, config, &mut remote_files, mtime_tolerance)
+}
+
+/// compute_diff for a project, keeping the remote listing in its cache. With
+/// `use_cached`, a recent listing of the same target replaces the server walk.
+fn compute_project_diff(
+    local_path: &str,
+    config: &SFTPConfig,
+    project_id: &str,
+    scan: &mut RemoteScanContext,
+    mtime_tolerance: Option<u64>,
+    use_cached: bool,
+) -> Result<Vec<FileDiff>, String> {
+    let app_dir = scan.app_handle.and_then(data_location::app_data_dir);
+    let target = remote_listing_cache::target_key(config);
+    let cached = match &app_dir {
+        Some(app_dir) if use_cached => remote_listing_cache::load(app_dir, project_id, &target),
+        _ => None,
+    };
+    let scanned = cached.is_none();
+    let mut remote_files = match cached {
+        Some(listing) => {
+            println!("[Diff] Using the remote listing of {} scanned at {}", project_id, listing.scanned_at);
+            listing.files
+        }
+        None => scan_remote_files(config, scan)?,
     };
+    let diffs = diff_against_remote(local_path, config, &mut remote_files, mtime_tolerance)?;
+    if let (true, Some(app_dir)) = (scanned, &app_dir) {
+        if let Err(e) = remote_listing_cache::store(app_dir, project_id, &target, &remote_files) {
+            println!("[Diff] Warning: Failed to cache the remote listing: {}", e);
+        }
+    }
+    Ok(diffs)
+}
+
+fn scan_remote_files(config: &SFTPConfig, scan: &mut RemoteScanContext) -> Result<HashMap<String, RemoteFile>, String> {
+    let remote_path = &config.remote_path;
+    match config.protocol.as_deref().unwrap_or("ftp") {
+        "sftp" => scan_sftp_remote_files(config, remote_path, scan),
+        "ftp" | "ftps" => scan_ftp_remote_files(config, remote_path, scan),
+        "webdav" => scan_webdav_remote_files(config, remote_path, scan),
+        protocol => Err(format!("Unknown protocol: {}", protocol)),
+    }
+}
 
+/// Diff against a remote listing; FTP dates missing from it are filled in
+fn diff_against_remote(
+    local_path: &str,
+    config: &SFTPConfig,
+    remote_files: &mut HashMap<String, RemoteFile>,
+    mtime_tolerance: Option<u64>,
+) -> Result<Vec<FileDiff>, String> {
+    let local_files = scan_local_files(local_path)?;
+
+    let protocol = config.protocol.as_deref().unwrap_or("ftp");
+    let remote_path = &config.remote_path;
+    let ignore = ignore_rules::IgnoreRules::load(Path::new(local_path));
+    let mut diffs = diff_file_maps(&local_files, remote_files, &ignore);
+    if let Some(tolerance) = mtime_tolerance {
+        if protocol != "sftp" && protocol != "webdav" {
+            let wanted = mtime_diff::missing_mtimes(&diffs, remote_files);
+            if let Err(e) = mtime_diff::fill_ftp_mtimes(config, remote_path, &wanted, remote_files) {
+                println!("[Diff] Warning: Failed to read remote dates: {}", e);
+            }
+        }
+        let newer = mtime_diff::mark_newer_local(&mut diffs, Path::new(local_path), remote_files, tolerance);
+        if newer > 0 {
+            println!("[Diff] {} file(s) of unchanged size modified locally since the last upload", newer);
+        }
+    }
+    Ok(diffs)
+}
+
+/// Compare a local scan with a remote listing, sorted by path. Remote files the
+/// project ignores are left out: missing locally, they would count as deleted
+/// and mirror mode would remove them from the server.
+fn diff_file_maps(
+    local_files: &HashMap<String, u64>,
+    remote_files: &HashMap<String, RemoteFile>,
+    ignore: &ignore_rules::IgnoreRules,
+) -> Vec<FileDiff> {
     let mut diffs = Vec::new();
 
     // Check local files
-    for (path, local_size) in &local_files {
+    for (path, local_size) in local_files {
         let status = if let Some(remote) = remote_files.get(path) {
             if *local_size != remote.size {
                 "modified"
@@ -624,8 +1563,8 @@ fn sftp_get_diff(local_path: String, config: SFTPConfig) -> Result<Vec<FileDiff>
     }
 
     // Check for deleted files (on remote but not local)
-    for (path, remote) in &remote_files {
-        if !local_files.contains_key(path) {
+    for (path, remote) in remote_files {
+        if !local_files.contains_key(path) && !ignore.is_ignored(path, false) {
             diffs.push(FileDiff {
                 path: path.clone(),
                 status: "deleted".to_string(),
@@ -636,11 +1575,11 @@ fn sftp_get_diff(local_path: String, config: SFTPConfig) -> Result<Vec<FileDiff>
     }
 
     diffs.sort_by(|a, b| a.path.cmp(&b.path));
-    Ok(diffs)
+    diffs
 }
 
 #[tauri::command]
-fn sftp_sync(
+async fn sftp_sync(
     local_path: String,
     config: SFTPConfig,
     dry_run: bool,
@@ -648,18 +1587,62 @@ fn sftp_sync(
     app_handle: tauri::AppHandle,
     options: Option<SyncOptions>,
 ) -> Result<Vec<FileDiff>, String> {
-    // Clear any previous cancel flag
-    set_cancelled(&project_id, false);
+    // A sync can wait in the queue for up to 30 min, then upload for as long:
+    // off the main thread, which would otherwise freeze the whole app
+    tokio::task::spawn_blocking(move || sftp_sync_recorded(local_path, config, dry_run, project_id, app_handle, options))
+        .await
+        .map_err(|e| format!("Sync task failed: {}", e))?
+}
+
+/// Run a sync and record it in the sync history
+fn sftp_sync_recorded(
+    local_path: String,
+    config: SFTPConfig,
+    dry_run: bool,
+    project_id: String,
+    app_handle: tauri::AppHandle,
+    options: Option<SyncOptions>,
+) -> Result<Vec<FileDiff>, String> {
+    let started = std::time::Instant::now();
+    let started_at = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let target = sync_history::SyncTarget::from(&config);
+    let trigger = options.as_ref().and_then(|o| o.trigger.clone()).unwrap_or_else(|| "manual".to_string());
+
+    let result = run_sftp_sync(local_path, config, dry_run, project_id.clone(), app_handle.clone(), options);
+
+    // Runs refused before the queue, and dry runs, were never begun and are not recorded
+    if let Some(app_dir) = data_location::app_data_dir(&app_handle) {
+        let duration_ms = started.elapsed().as_millis() as u64;
+        if let Some(Err(e)) = sync_history::finish(&app_dir, &project_id, &trigger, target, started_at, duration_ms, &result) {
+            println!("[Sync] Warning: Failed to record sync history: {}", e);
+        }
+    }
+    result
+}
 
+fn run_sftp_sync(
+    local_path: String,
+    config: SFTPConfig,
+    dry_run: bool,
+    project_id: String,
+    app_handle: tauri::AppHandle,
+    options: Option<SyncOptions>,
+) -> Result<Vec<FileDiff>, String> {
     let sync_options = options.unwrap_or_default();
+    let simulate = simulation::is_enabled();
+    let dry_run = dry_run || simulate;
 
     // Helper to emit progress events
-    let emit_progress = |event: &str, file: Option<&str>, progress: u32, message: Option<&str>| {
+    let emit_progress = |event: events::SyncEventKind, file: Option<&str>, progress: u32, message: Option<&str>| {
+        if event == events::SyncEventKind::FileError {
+            sync_history::note_error(&project_id, file, message.unwrap_or(""));
+        }
         let _ = app_handle.emit_all(
-            "sync-progress",
+            events::SYNC_PROGRESS,
             SyncProgressEvent {
+                schema_version: events::SCHEMA_VERSION,
                 project_id: project_id.clone(),
-                event: event.to_string(),
+                event,
                 file: file.map(|s| s.to_string()),
                 progress,
                 file_progress: None,
@@ -674,11 +1657,54 @@ fn sftp_sync(
         );
     };
 
-    emit_progress("connecting", None, 5, Some("Connexion au serveur..."));
+    // Wait for the syncs already running, of this project or another (see sync_queue);
+    // dry runs only read the remote and don't need the lock
+    let _sync_lock = if dry_run {
+        None
+    } else {
+        readonly_mode::ensure_writable("la synchronisation")?;
+        // Frozen deploys and unconfirmed production syncs are refused whatever the trigger
+        let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+        deploy_guard::check_sync(&app_dir, &project_id, sync_options.confirmation.as_deref())?;
+        let trigger = sync_options.trigger.as_deref().unwrap_or("manual");
+        Some(sync_queue::acquire(&project_id, trigger, |position, running| {
+            emit_progress(
+                events::SyncEventKind::Queued,
+                None,
+                0,
+                Some(&format!("En attente: position {} dans la file, {} synchronisation(s) en cours", position, running)),
+            );
+        })?)
+    };
+    if !dry_run {
+        sync_history::begin(&project_id);
+    }
+
+    // Clear any previous cancel flag
+    set_cancelled(&project_id, false);
+
+    // Build first: the snapshot and the diff must see what the build produced
+    if let Some(command) = sync_options.pre_sync_command.as_deref().filter(|c| !c.trim().is_empty() && !dry_run) {
+        emit_progress(events::SyncEventKind::Building, None, 2, Some(&format!("Build: {}", command)));
+        let cwd = sync_options.pre_sync_cwd.as_deref().unwrap_or(&local_path);
+        let timeout = Duration::from_secs(sync_options.pre_sync_timeout_secs.unwrap_or(build_hook::DEFAULT_TIMEOUT_SECS));
+        let env = data_location::app_data_dir(&app_handle)
+            .map(|app_dir| project_env::resolve(&app_dir, &project_id))
+            .unwrap_or_default();
+        if let Err(e) = build_hook::run(command, Path::new(cwd), &env, timeout, &project_id, &app_handle) {
+            set_cancelled(&project_id, false);
+            let kind = if e.contains("annulée") { events::SyncEventKind::Cancelled } else { events::SyncEventKind::Error };
+            emit_progress(kind, None, 0, Some(&e));
+            return Err(e);
+        }
+    }
+
+    emit_progress(events::SyncEventKind::Connecting, None, 5, Some("Connexion au serveur..."));
 
     // Create version snapshot if requested
+    let mut snapshot_id: Option<String> = None;
     if sync_options.create_snapshot && !dry_run {
-        if let Ok(app_dir) = app_handle.path_resolver().app_data_dir().ok_or("No app dir") {
+        if let Ok(app_dir) = data_location::app_data_dir(&app_handle).ok_or("No app dir") {
             let backup_dir = version_history::get_backup_dir(&app_dir, &project_id);
             let backup_dir_str = backup_dir.to_string_lossy().to_string();
 
@@ -689,12 +1715,17 @@ fn sftp_sync(
                 sync_options.snapshot_message.as_deref(),
             ) {
                 Ok(snapshot) => {
+                    snapshot_id = Some(snapshot.id.clone());
+                    record_snapshot_activity(&app_handle, &snapshot);
+                    if let Err(e) = file_provenance::record_snapshot(&app_dir, &snapshot) {
+                        println!("[Sync] Warning: Failed to record file provenance: {}", e);
+                    }
                     // Load existing history, add snapshot, save
                     if let Ok(mut history) = version_history::load_history(&app_dir, &project_id) {
                         history.add_snapshot(snapshot);
                         let _ = version_history::save_history(&app_dir, &history);
                     }
-                    emit_progress("snapshot", None, 8, Some("Snapshot créé"));
+                    emit_progress(events::SyncEventKind::Snapshot, None, 8, Some("Snapshot créé"));
                 }
                 Err(e) => {
                     println!("[Sync] Warning: Failed to create snapshot: {}", e);
@@ -704,32 +1735,174 @@ fn sftp_sync(
     }
 
     // Get diff first
-    emit_progress("analyzing", None, 10, Some("Analyse des fichiers..."));
-    let diffs = sftp_get_diff(local_path.clone(), config.clone())?;
+    emit_progress(events::SyncEventKind::Analyzing, None, 10, Some("Analyse des fichiers..."));
+    let mtime_tolerance = sync_options.mtime_tolerance_secs.unwrap_or(mtime_diff::DEFAULT_TOLERANCE_SECS);
+    let mut scan = RemoteScanContext::new(&project_id, &app_handle);
+    // Only dry runs may trust a cached listing, a real sync needs the server as it is
+    let diffs = match if dry_run {
+        compute_project_diff(&local_path, &config, &project_id, &mut scan, Some(mtime_tolerance), sync_options.use_cached_remote)
+    } else {
+        compute_diff(&local_path, &config, &mut scan, Some(mtime_tolerance))
+    } {
+        Ok(diffs) => diffs,
+        Err(e) => {
+            set_cancelled(&project_id, false);
+            if e.contains("annulée") {
+                emit_progress(events::SyncEventKind::Cancelled, None, 0, Some(&e));
+            } else {
+                emit_progress(events::SyncEventKind::Error, None, 0, Some(&e));
+            }
+            return Err(e);
+        }
+    };
+
+    // Per-sync globs: filtered files are neither uploaded nor deleted by mirror mode
+    let path_filter = ignore_rules::PathFilter::new(&sync_options.include, &sync_options.exclude);
+    let diffs: Vec<FileDiff> = if path_filter.is_empty() {
+        diffs
+    } else {
+        diffs.into_iter().filter(|d| path_filter.allows(&d.path)).collect()
+    };
+
+    let mut diffs = match sync_options.max_file_size {
+        Some(max_size) => skip_oversized_files(diffs, max_size, &sync_options.large_file_overrides),
+        None => diffs,
+    };
+
+    // Mirror mode: remote files missing locally and not protected by the project's preset,
+    // capped so a wrong local folder can't wipe the site
+    let orphans: Vec<String> = if sync_options.delete_orphans {
+        let remote_only: Vec<String> = diffs.iter().filter(|d| d.status == "deleted").map(|d| d.path.clone()).collect();
+        let preset = sync_presets::resolve(sync_options.sync_preset.as_deref(), &local_path);
+        let deletable = sync_presets::deletable_orphans(preset.as_ref(), remote_only.clone());
+        if deletable.len() < remote_only.len() {
+            let preset_name = preset.map(|p| p.name).unwrap_or_default();
+            println!(
+                "[Sync] Mirror mode: {} remote file(s) kept by the {} preset",
+                remote_only.len() - deletable.len(),
+                preset_name
+            );
+        }
+        deletable
+    } else {
+        Vec::new()
+    };
+    let max_deletions = sync_options.max_deletions.unwrap_or(DEFAULT_MAX_DELETIONS);
+    let deletion_cap_message = format!(
+        "Mode miroir: {} fichier(s) distant(s) a supprimer, au-dela de la limite de {}",
+        orphans.len(),
+        max_deletions
+    );
 
     if dry_run {
-        emit_progress("complete", None, 100, Some("Analyse terminée"));
+        if simulate {
+            emit_simulation(&app_handle, &simulation::plan_sync(&diffs));
+        }
+        if !orphans.is_empty() {
+            let preview = if orphans.len() > max_deletions {
+                deletion_cap_message
+            } else {
+                format!("Mode miroir: {} fichier(s) distant(s) seront supprime(s)", orphans.len())
+            };
+            emit_progress(events::SyncEventKind::DeletePreview, None, 100, Some(&preview));
+        }
+        emit_progress(events::SyncEventKind::Complete, None, 100, Some("Analyse terminée"));
         return Ok(diffs);
     }
 
+    if orphans.len() > max_deletions {
+        emit_progress(events::SyncEventKind::Error, None, 0, Some(&deletion_cap_message));
+        return Err(deletion_cap_message);
+    }
+
     // Check cancellation
     if is_cancelled(&project_id) {
-        emit_progress("cancelled", None, 0, Some("Synchronisation annulée"));
+        emit_progress(events::SyncEventKind::Cancelled, None, 0, Some("Synchronisation annulée"));
         return Err("Synchronisation annulée".to_string());
     }
 
+    // Check the project's transfer quota before uploading anything
+    let app_data_dir = data_location::app_data_dir(&app_handle);
+    let planned_bytes: u64 = diffs
+        .iter()
+        .filter(|d| d.status == "added" || d.status == "modified")
+        .filter_map(|d| d.local_size)
+        .sum();
+    if let Some(app_dir) = &app_data_dir {
+        let quota = transfer_quota::check(app_dir, &project_id, planned_bytes);
+        for warning in &quota.warnings {
+            emit_progress(events::SyncEventKind::QuotaWarning, None, 10, Some(warning));
+        }
+        if !quota.allowed {
+            let e = format!("Quota de transfert depasse: {}", quota.warnings.join(" / "));
+            emit_progress(events::SyncEventKind::Error, None, 0, Some(&e));
+            return Err(e);
+        }
+    }
+
+    // Maintenance page for the length of the upload. The project's own rules file
+    // would drop the maintenance rule, so it is held back and deployed when lifting it.
+    let mut held_back: Option<FileDiff> = None;
+    let maintenance = match (&sync_options.maintenance, &app_data_dir) {
+        (Some(options), Some(app_dir)) => {
+            emit_progress(events::SyncEventKind::Maintenance, None, 12, Some("Activation du mode maintenance..."));
+            match maintenance_mode::enable(app_dir, &project_id, &config, options) {
+                Ok(state) => {
+                    if let Some(index) = diffs
+                        .iter()
+                        .position(|d| d.path == state.rules_file && (d.status == "added" || d.status == "modified"))
+                    {
+                        held_back = Some(diffs.remove(index));
+                    }
+                    Some(state)
+                }
+                Err(e) => {
+                    emit_progress(events::SyncEventKind::Error, None, 0, Some(&e));
+                    return Err(e);
+                }
+            }
+        }
+        _ => None,
+    };
+
     // Perform actual sync - use parallel or sequential based on options
     let protocol = config.protocol.as_deref().unwrap_or("ftp");
     let use_parallel = sync_options.parallel_enabled;
     let max_connections = sync_options.parallel_connections.min(parallel_sync::MAX_PARALLEL_CONNECTIONS);
+    let tuning_key = concurrency_tuning::target_key(protocol, &config.username, &config.host, config.port);
+    let adaptive = match data_location::app_data_dir(&app_handle) {
+        Some(app_dir) if sync_options.adaptive_connections => Some(concurrency_tuning::AdaptiveLimit::new(
+            concurrency_tuning::learned(&app_dir, &tuning_key).as_ref(),
+            max_connections,
+        )),
+        _ => None,
+    };
+    let connections = match adaptive.as_ref() {
+        Some(limit) => concurrency_tuning::Connections::Adaptive(limit),
+        None => concurrency_tuning::Connections::Fixed(max_connections),
+    };
+    let upload = UploadOptions {
+        atomic: sync_options.atomic_upload,
+        preserve_permissions: sync_options.preserve_permissions,
+        preserve_mtime: sync_options.preserve_mtime.unwrap_or(true),
+    };
 
-    let result = if use_parallel {
+    let result = if let Some(engine @ ("rsync" | "tar")) = sync_options.engine.as_deref() {
+        match (protocol, engine) {
+            ("sftp", "rsync") => rsync_sync::rsync_sync(&local_path, &config, &diffs, &project_id, &app_handle, upload),
+            ("sftp", _) => tar_upload::tar_sftp_sync(&local_path, &config, &diffs, &project_id, &app_handle, upload),
+            _ => Err(format!("Le moteur {} n'est disponible qu'en SFTP (protocole {})", engine, protocol)),
+        }
+    } else if use_parallel {
         // Use parallel sync
         match protocol {
             "sftp" => parallel_sync::parallel_sftp_sync(
-                &local_path, &config, &diffs, &project_id, &app_handle, max_connections
+                &local_path, &config, &diffs, &project_id, &app_handle, connections, upload
             ),
             "ftp" | "ftps" => parallel_sync::parallel_ftp_sync(
+                &local_path, &config, &diffs, &project_id, &app_handle, connections, upload
+            ),
+            "webdav" => parallel_sync::parallel_webdav_sync(
                 &local_path, &config, &diffs, &project_id, &app_handle, max_connections
             ),
             _ => Err(format!("Unknown protocol: {}", protocol)),
@@ -737,193 +1910,589 @@ fn sftp_sync(
     } else {
         // Use sequential sync (original behavior)
         match protocol {
-            "sftp" => sync_sftp_with_progress(&local_path, &config, &diffs, &project_id, &app_handle),
-            "ftp" | "ftps" => sync_ftp_with_progress(&local_path, &config, &diffs, &project_id, &app_handle),
+            "sftp" => sync_sftp_with_progress(&local_path, &config, &diffs, &project_id, &app_handle, upload),
+            "ftp" | "ftps" => sync_ftp_with_progress(&local_path, &config, &diffs, &project_id, &app_handle, upload),
+            // One upload at a time over the same client
+            "webdav" => parallel_sync::parallel_webdav_sync(&local_path, &config, &diffs, &project_id, &app_handle, 1),
             _ => Err(format!("Unknown protocol: {}", protocol)),
         }
     };
 
+    // Keep what the adaptive mode learned about the target for the next syncs
+    if let (Some(outcome), Some(app_dir)) = (
+        adaptive.as_ref().and_then(|limit| limit.outcome()),
+        data_location::app_data_dir(&app_handle),
+    ) {
+        println!("[Concurrency] {}: optimum {} connection(s)", tuning_key, outcome.optimum);
+        if let Err(e) = concurrency_tuning::save(&app_dir, &tuning_key, outcome) {
+            println!("[Concurrency] Failed to save learned optimum: {}", e);
+        }
+    }
+
+    // Sampling verification: a mismatch fails the sync, so orphans are kept
+    let mut verification: Option<upload_verify::SampleVerification> = None;
+    let result = match (result, sync_options.verify_sample_percent) {
+        (Ok(()), Some(percent)) if !is_cancelled(&project_id) => {
+            emit_progress(events::SyncEventKind::Verifying, None, 90, Some("Verification des fichiers envoyes..."));
+            let threshold = sync_options.verify_size_threshold.unwrap_or(upload_verify::DEFAULT_SIZE_THRESHOLD);
+            match upload_verify::verify_uploads(&local_path, &config, &diffs, percent, threshold, |file, done, total| {
+                emit_progress(events::SyncEventKind::Verifying, Some(file), 90, Some(&format!("Verification {}/{}", done, total)));
+            }) {
+                Ok(checked) => {
+                    emit_progress(
+                        events::SyncEventKind::Verification,
+                        None,
+                        91,
+                        Some(&format!(
+                            "{} fichier(s) verifie(s) sur {}, confiance {:.1}%",
+                            checked.large_checked + checked.sampled,
+                            checked.uploaded,
+                            checked.confidence * 100.0
+                        )),
+                    );
+                    for unreadable in &checked.unreadable {
+                        emit_progress(events::SyncEventKind::FileError, None, 91, Some(unreadable));
+                    }
+                    let outcome = if checked.mismatched.is_empty() {
+                        Ok(())
+                    } else {
+                        Err(format!(
+                            "Verification: {} fichier(s) different(s) sur le serveur: {}",
+                            checked.mismatched.len(),
+                            checked.mismatched.join(", ")
+                        ))
+                    };
+                    verification = Some(checked);
+                    outcome
+                }
+                Err(e) => {
+                    // The upload itself went through, only the check couldn't run
+                    emit_progress(events::SyncEventKind::FileError, None, 91, Some(&format!("Verification impossible: {}", e)));
+                    Ok(())
+                }
+            }
+        }
+        (result, _) => result,
+    };
+
+    // Orphans are only deleted once every upload went through
+    let mut deleted_orphans: Vec<String> = Vec::new();
+    if result.is_ok() && !orphans.is_empty() && !is_cancelled(&project_id) {
+        emit_progress(events::SyncEventKind::Deleting, None, 92, Some("Suppression des fichiers orphelins..."));
+        match &app_data_dir {
+            Some(app_dir) => {
+                let retention = sync_options.trash_retention_days.unwrap_or(remote_trash::DEFAULT_RETENTION_DAYS);
+                let mut trash = remote_trash::TrashBatch::open(app_dir, &project_id, &config, retention);
+                match delete_remote_orphans(&local_path, &config, &orphans, &mut trash) {
+                    Ok((deleted, failed)) => {
+                        for failure in &failed {
+                            emit_progress(events::SyncEventKind::FileError, None, 92, Some(failure));
+                        }
+                        deleted_orphans = deleted;
+                    }
+                    Err(e) => emit_progress(events::SyncEventKind::FileError, None, 92, Some(&e)),
+                }
+                if let Err(e) = trash.commit() {
+                    println!("[Sync] Warning: Failed to record the remote trash: {}", e);
+                }
+            }
+            None => emit_progress(events::SyncEventKind::FileError, None, 92, Some("Corbeille distante indisponible, aucun fichier supprime")),
+        }
+    }
+
+    // Lifted whatever the outcome; a failure leaves the state for disable_maintenance_mode
+    if let (Some(_), Some(app_dir)) = (&maintenance, &app_data_dir) {
+        emit_progress(events::SyncEventKind::Maintenance, None, 94, Some("Desactivation du mode maintenance..."));
+        match maintenance_mode::disable(app_dir, &project_id, &config, Some(Path::new(&local_path))) {
+            Ok(cleanup) => {
+                for warning in &cleanup.warnings {
+                    emit_progress(events::SyncEventKind::FileError, None, 94, Some(warning));
+                }
+                if let Some(diff) = held_back.take().filter(|_| cleanup.project_rules_deployed) {
+                    diffs.push(diff);
+                    diffs.sort_by(|a, b| a.path.cmp(&b.path));
+                }
+            }
+            Err(e) => emit_progress(
+                events::SyncEventKind::FileError,
+                None,
+                94,
+                Some(&format!("Le site est reste en maintenance: {}", e)),
+            ),
+        }
+    }
+
+    // Remote command once the files are in place and maintenance lifted
+    let mut post_command: Option<remote_exec::RemoteCommandOutput> = None;
+    if let Some(command) = sync_options.post_sync_command.as_deref().filter(|c| !c.trim().is_empty()) {
+        if result.is_ok() && !is_cancelled(&project_id) {
+            emit_progress(events::SyncEventKind::PostCommand, None, 96, Some(&format!("Commande distante: {}", command)));
+            let env = app_data_dir
+                .as_ref()
+                .map(|app_dir| project_env::resolve(app_dir, &project_id))
+                .unwrap_or_default();
+            let timeout = Duration::from_secs(sync_options.post_sync_timeout_secs.unwrap_or(remote_exec::POST_SYNC_TIMEOUT_SECS));
+            match remote_exec::exec_in_root(&config, command, &env, timeout) {
+                Ok(output) => {
+                    if output.exit_code != 0 {
+                        let reason = output
+                            .stderr
+                            .lines()
+                            .rev()
+                            .chain(output.stdout.lines().rev())
+                            .find(|l| !l.trim().is_empty());
+                        emit_progress(
+                            events::SyncEventKind::FileError,
+                            None,
+                            96,
+                            Some(&format!("La commande distante a echoue (code {}): {}", output.exit_code, reason.unwrap_or("").trim())),
+                        );
+                    }
+                    post_command = Some(output);
+                }
+                Err(e) => emit_progress(
+                    events::SyncEventKind::FileError,
+                    None,
+                    96,
+                    Some(&format!("Commande distante non executee: {}", e)),
+                ),
+            }
+        }
+    }
+
     // Clear cancel flag
     set_cancelled(&project_id, false);
 
+    let uploads = diffs.iter().filter(|d| d.status == "added" || d.status == "modified").count();
+    let (status, detail) = match &result {
+        Ok(_) if !deleted_orphans.is_empty() => (
+            "success",
+            format!("{} fichier(s) envoye(s), {} supprime(s)", uploads, deleted_orphans.len()),
+        ),
+        Ok(_) => ("success", format!("{} fichier(s) envoye(s)", uploads)),
+        Err(e) if e.contains("annulée") || e.contains("cancelled") => ("cancelled", e.clone()),
+        Err(e) => ("error", e.clone()),
+    };
+    sync_history::set_totals(&project_id, uploads, deleted_orphans.len(), planned_bytes, snapshot_id.clone());
+    activity_feed::record(
+        &app_handle,
+        activity_feed::new_event(
+            &project_id,
+            "sync",
+            "Synchronisation",
+            Some(detail),
+            Some(status),
+            serde_json::json!({
+                "files": uploads,
+                "deleted": deleted_orphans.len(),
+                "bytes": planned_bytes,
+                "trigger": sync_options.trigger.as_deref().unwrap_or("manual"),
+                "snapshotId": snapshot_id,
+                "verification": verification,
+                "postCommand": post_command,
+                "fileTypes": file_type_stats::compute(&diffs),
+                "paths": diffs
+                    .iter()
+                    .filter(|d| d.status == "added" || d.status == "modified")
+                    .take(activity_feed::MAX_SYNC_PATHS)
+                    .map(|d| d.path.as_str())
+                    .collect::<Vec<_>>(),
+            }),
+        ),
+    );
+    // Even a failed sync may have uploaded part of the files
+    remote_monitor::rebaseline(&project_id);
+    if let Some(app_dir) = &app_data_dir {
+        remote_listing_cache::invalidate(app_dir, &project_id);
+    }
+
     match result {
         Ok(_) => {
-            emit_progress("complete", None, 100, Some("Synchronisation terminée"));
+            if let Some(app_dir) = &app_data_dir {
+                if let Err(e) = transfer_quota::record_transfer(app_dir, &project_id, planned_bytes) {
+                    println!("[Sync] Warning: Failed to record transfer usage: {}", e);
+                }
+                let uploaded: Vec<&str> = diffs
+                    .iter()
+                    .filter(|d| d.status == "added" || d.status == "modified")
+                    .map(|d| d.path.as_str())
+                    .collect();
+                let trigger = sync_options.trigger.as_deref().unwrap_or("manual");
+                if let Err(e) = file_provenance::record_deploy(app_dir, &project_id, &local_path, &uploaded, trigger) {
+                    println!("[Sync] Warning: Failed to record file provenance: {}", e);
+                }
+            }
+            if sync_options.upload_manifest {
+                emit_progress(events::SyncEventKind::Manifest, None, 95, Some("Envoi du manifeste de déploiement..."));
+                let manifest_result = scan_local_files(&local_path)
+                    .and_then(|files| {
+                        deploy_manifest::build_manifest(&project_id, &local_path, &files, snapshot_id.as_deref())
+                    })
+                    .and_then(|manifest| deploy_manifest::upload_manifest(&config, &manifest));
+                if let Err(e) = manifest_result {
+                    println!("[Sync] Warning: Failed to upload deploy manifest: {}", e);
+                }
+            }
+            emit_progress(events::SyncEventKind::Complete, None, 100, Some("Synchronisation terminée"));
             Ok(diffs)
         }
         Err(e) => {
             if e.contains("annulée") || e.contains("cancelled") {
-                emit_progress("cancelled", None, 0, Some(&e));
+                emit_progress(events::SyncEventKind::Cancelled, None, 0, Some(&e));
             } else {
-                emit_progress("error", None, 0, Some(&e));
+                emit_progress(events::SyncEventKind::Error, None, 0, Some(&e));
             }
             Err(e)
         }
     }
 }
 
+/// Delete remote files missing locally, then the remote folders they leave empty.
+/// Each file is first saved into the remote trash, and kept on the server when that fails.
+/// Returns the deleted paths and the failures.
+fn delete_remote_orphans(
+    local_path: &str,
+    config: &SFTPConfig,
+    orphans: &[String],
+    trash: &mut remote_trash::TrashBatch,
+) -> Result<(Vec<String>, Vec<String>), String> {
+    let remote_base = config.remote_path.trim_end_matches('/');
+    let mut deleted = Vec::new();
+    let mut failed = Vec::new();
+
+    // Parent folders of the orphans that don't exist locally, deepest first
+    let mut folders: Vec<String> = orphans
+        .iter()
+        .flat_map(|path| {
+            Path::new(path)
+                .ancestors()
+                .skip(1)
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .map(|dir| dir.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+        })
+        .filter(|dir| !Path::new(local_path).join(dir).is_dir())
+        .collect();
+    folders.sort();
+    folders.dedup();
+    folders.sort_by_key(|dir| std::cmp::Reverse(dir.matches('/').count()));
+
+    if config.protocol.as_deref() == Some("webdav") {
+        let client = webdav::WebDavClient::connect(config)?;
+        for path in orphans {
+            let remote_file = format!("{}/{}", remote_base, path);
+            if let Err(e) = trash.save_from_webdav(&client, &remote_file, path) {
+                failed.push(format!("{} conserve, copie impossible: {}", path, e));
+                continue;
+            }
+            match client.delete(&remote_file, false) {
+                Ok(()) => deleted.push(path.clone()),
+                Err(e) => failed.push(e),
+            }
+        }
+        for dir in &folders {
+            let remote_dir = format!("{}/{}", remote_base, dir);
+            if client.list(&remote_dir).map(|entries| entries.is_empty()).unwrap_or(false) {
+                let _ = client.delete(&remote_dir, true);
+            }
+        }
+    } else {
+        let mut remote = pull_sync::RemoteEndpoint::connect(config)?;
+        for path in orphans {
+            let remote_file = format!("{}/{}", remote_base, path);
+            if let Err(e) = trash.save_from(&mut remote, &remote_file, path) {
+                failed.push(format!("{} conserve, copie impossible: {}", path, e));
+                continue;
+            }
+            match remote.remove(&remote_file) {
+                Ok(()) => deleted.push(path.clone()),
+                Err(e) => failed.push(e),
+            }
+        }
+        // rmdir refuses folders that still hold hidden or unlisted files
+        for dir in &folders {
+            let _ = remote.remove_dir(&format!("{}/{}", remote_base, dir));
+        }
+        remote.close();
+    }
+
+    println!("[Sync] Mirror mode deleted {} orphan(s), {} failed", deleted.len(), failed.len());
+    Ok((deleted, failed))
+}
+
+/// Mark files to upload that exceed `max_size` as "oversized" so the sync skips them
+fn skip_oversized_files(mut diffs: Vec<FileDiff>, max_size: u64, overrides: &[String]) -> Vec<FileDiff> {
+    for diff in diffs.iter_mut() {
+        let to_upload = diff.status == "added" || diff.status == "modified";
+        if to_upload && diff.local_size.unwrap_or(0) > max_size && !overrides.contains(&diff.path) {
+            println!("[Sync] Skipping oversized file: {} ({} bytes)", diff.path, diff.local_size.unwrap_or(0));
+            diff.status = "oversized".to_string();
+        }
+    }
+    diffs
+}
+
+/// Read the manifest of the last deploy from the remote root
+#[tauri::command]
+fn get_deploy_manifest(config: SFTPConfig) -> Result<deploy_manifest::DeployManifest, String> {
+    deploy_manifest::download_manifest(&config)
+}
+
+/// Compare the remote tree against the manifest of the last deploy
+#[tauri::command]
+fn verify_deploy_manifest(config: SFTPConfig) -> Result<deploy_manifest::ManifestVerification, String> {
+    let manifest = deploy_manifest::download_manifest(&config)?;
+
+    let protocol = config.protocol.as_deref().unwrap_or("ftp");
+    let remote_files = match protocol {
+        "sftp" => scan_sftp_remote_files(&config, &config.remote_path, &mut RemoteScanContext::silent())?,
+        "ftp" | "ftps" => scan_ftp_remote_files(&config, &config.remote_path, &mut RemoteScanContext::silent())?,
+        "webdav" => scan_webdav_remote_files(&config, &config.remote_path, &mut RemoteScanContext::silent())?,
+        _ => return Err(format!("Unknown protocol: {}", protocol)),
+    };
+
+    Ok(deploy_manifest::verify_against_remote(&manifest, &remote_files))
+}
+
 #[tauri::command]
 fn sftp_cancel_sync(project_id: String) -> Result<(), String> {
     println!("[Rust] sftp_cancel_sync called for project: {}", project_id);
+    // A sync still waiting for its turn is only dropped from the queue
+    if sync_queue::cancel(&project_id) {
+        return Ok(());
+    }
     set_cancelled(&project_id, true);
     Ok(())
 }
 
-fn sync_sftp_with_progress(
-    local_path: &str,
-    config: &SFTPConfig,
-    diffs: &[FileDiff],
-    project_id: &str,
-    app_handle: &tauri::AppHandle,
-) -> Result<(), String> {
-    let addr = resolve_addr(&config.host, config.port)?;
-    let tcp = TcpStream::connect_timeout(&addr, Duration::from_secs(10))
-        .map_err(|e| format!("Connection failed: {}", e))?;
+/// Version of the local rsync, None when the rsync engine can't be used
+#[tauri::command]
+fn get_rsync_version() -> Option<String> {
+    rsync_sync::local_version()
+}
 
-    // Set timeouts
-    tcp.set_read_timeout(Some(Duration::from_secs(60)))
-        .map_err(|e| format!("Failed to set read timeout: {}", e))?;
-    tcp.set_write_timeout(Some(Duration::from_secs(120)))
-        .map_err(|e| format!("Failed to set write timeout: {}", e))?;
+/// Check whether a sync is currently running for a project
+#[tauri::command]
+fn sync_in_progress(project_id: String) -> bool {
+    sync_lock::is_locked(&project_id)
+}
 
-    let mut sess = ssh2::Session::new().map_err(|e| format!("Session error: {}", e))?;
-    sess.set_tcp_stream(tcp);
-    sess.handshake().map_err(|e| format!("Handshake failed: {}", e))?;
-    sess.userauth_password(&config.username, &config.password)
-        .map_err(|e| format!("Auth failed: {}", e))?;
+/// List all syncs currently running
+#[tauri::command]
+fn get_active_syncs() -> Vec<sync_lock::ActiveSync> {
+    sync_lock::active_syncs()
+}
 
-    let sftp = sess.sftp().map_err(|e| format!("SFTP error: {}", e))?;
-    let remote_base = &config.remote_path;
+/// Running syncs and the ones waiting for their turn
+#[tauri::command]
+fn get_sync_queue() -> sync_queue::SyncQueueStatus {
+    sync_queue::status()
+}
 
-    // Filter to only files that need uploading
-    let files_to_upload: Vec<_> = diffs
-        .iter()
-        .filter(|d| d.status == "added" || d.status == "modified")
-        .collect();
+/// Last sync runs of a project (figures, target, file errors), newest first
+#[tauri::command]
+fn get_sync_history(project_id: String, limit: Option<usize>, app_handle: tauri::AppHandle) -> Result<Vec<sync_history::SyncRun>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(sync_history::query(&app_dir, &project_id, limit))
+}
 
-    let total_files = files_to_upload.len();
-    let mut completed = 0;
-    let mut errors: Vec<String> = Vec::new();
+#[tauri::command]
+fn clear_sync_history(project_id: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    sync_history::clear(&app_dir, &project_id)
+}
 
-    for diff in &files_to_upload {
-        // Check cancellation
-        if is_cancelled(project_id) {
-            return Err("Synchronisation annulée".to_string());
-        }
+/// Download remote-only and remote-newer files into the local project
+fn pull_remote_files(
+    local_path: &str,
+    config: &SFTPConfig,
+    paths: Option<Vec<String>>,
+    project_id: &str,
+    app_handle: &tauri::AppHandle,
+) -> Result<pull_sync::PullResult, String> {
+    readonly_mode::ensure_writable("la recuperation de fichiers")?;
+    let _sync_lock = sync_lock::try_acquire(project_id, "pull")?;
+    let result = pull_sync::pull(local_path, config, paths.as_deref(), project_id, app_handle);
+
+    let (status, detail) = match &result {
+        Ok(pulled) if pulled.failed.is_empty() => ("success", format!("{} fichier(s) recupere(s)", pulled.downloaded.len())),
+        Ok(pulled) => ("error", format!("{} fichier(s) en erreur", pulled.failed.len())),
+        Err(e) => ("error", e.clone()),
+    };
+    activity_feed::record(
+        app_handle,
+        activity_feed::new_event(
+            project_id,
+            "sync",
+            "Recuperation depuis le serveur",
+            Some(detail),
+            Some(status),
+            serde_json::json!({
+                "direction": "pull",
+                "files": result.as_ref().map(|r| r.downloaded.len()).unwrap_or(0),
+                "bytes": result.as_ref().map(|r| r.bytes).unwrap_or(0),
+            }),
+        ),
+    );
+    result
+}
 
-        let local_file = format!("{}/{}", local_path, diff.path);
-        let remote_file = format!("{}/{}", remote_base, diff.path);
-        let file_size = diff.local_size.unwrap_or(0);
+#[tauri::command]
+fn sftp_pull(
+    local_path: String,
+    config: SFTPConfig,
+    project_id: String,
+    paths: Option<Vec<String>>,
+    app_handle: tauri::AppHandle,
+) -> Result<pull_sync::PullResult, String> {
+    if config.protocol.as_deref().unwrap_or("ftp") != "sftp" {
+        return Err("sftp_pull requires an SFTP target".to_string());
+    }
+    pull_remote_files(&local_path, &config, paths, &project_id, &app_handle)
+}
 
-        // Emit file start event
-        let progress = 20 + ((completed as u32 * 70) / total_files.max(1) as u32);
-        let _ = app_handle.emit_all(
-            "sync-progress",
-            SyncProgressEvent {
-                project_id: project_id.to_string(),
-                event: "file_start".to_string(),
-                file: Some(diff.path.clone()),
-                progress,
-                file_progress: Some(0),
-                bytes_sent: Some(0),
-                bytes_total: Some(file_size),
-                message: None,
-                timestamp: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_millis() as u64,
-            },
-        );
+#[tauri::command]
+fn ftp_pull(
+    local_path: String,
+    config: SFTPConfig,
+    project_id: String,
+    paths: Option<Vec<String>>,
+    app_handle: tauri::AppHandle,
+) -> Result<pull_sync::PullResult, String> {
+    if !matches!(config.protocol.as_deref().unwrap_or("ftp"), "ftp" | "ftps") {
+        return Err("ftp_pull requires an FTP or FTPS target".to_string());
+    }
+    pull_remote_files(&local_path, &config, paths, &project_id, &app_handle)
+}
 
-        // Create parent directories if needed
-        if let Some(parent) = Path::new(&remote_file).parent() {
-            let _ = create_sftp_dirs(&sftp, parent);
-        }
+/// Compare both sides with the last-synced state, without changing anything
+#[tauri::command]
+fn plan_two_way_sync(
+    local_path: String,
+    config: SFTPConfig,
+    project_id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<two_way_sync::TwoWayPlan, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    two_way_sync::plan(&app_dir, &local_path, &config, &project_id, &app_handle)
+}
 
-        // Read and upload file
-        let result: Result<(), String> = (|| {
-            let mut file = File::open(&local_file)
-                .map_err(|e| format!("Failed to open {}: {}", local_file, e))?;
-            let mut contents = Vec::new();
-            file.read_to_end(&mut contents)
-                .map_err(|e| format!("Failed to read {}: {}", local_file, e))?;
+/// Guards of the two-way commands, which write on both sides
+fn begin_two_way_sync(
+    app_handle: &tauri::AppHandle,
+    project_id: &str,
+    confirmation: Option<&str>,
+) -> Result<(PathBuf, sync_lock::SyncLockGuard), String> {
+    readonly_mode::ensure_writable("la synchronisation")?;
+    let app_dir = data_location::app_data_dir(app_handle).ok_or("Could not get app data directory")?;
+    deploy_guard::check_sync(&app_dir, project_id, confirmation)?;
+    let lock = sync_lock::try_acquire(project_id, "two-way")?;
+    Ok((app_dir, lock))
+}
 
-            let mut remote = sftp
-                .create(Path::new(&remote_file))
-                .map_err(|e| format!("Failed to create {}: {}", remote_file, e))?;
-            remote
-                .write_all(&contents)
-                .map_err(|e| format!("Failed to write {}: {}", remote_file, e))?;
+fn record_two_way_activity(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    project_id: &str,
+    local_path: &str,
+    title: &str,
+    result: &Result<two_way_sync::TwoWayResult, String>,
+) {
+    remote_monitor::rebaseline(project_id);
+    remote_listing_cache::invalidate(app_dir, project_id);
+    if let Ok(r) = result {
+        let uploaded: Vec<&str> = r.uploaded.iter().map(String::as_str).collect();
+        if let Err(e) = file_provenance::record_deploy(app_dir, project_id, local_path, &uploaded, "two-way") {
+            println!("[TwoWay] Warning: Failed to record file provenance: {}", e);
+        }
+    }
+    let (status, detail) = match result {
+        Ok(r) if !r.failed.is_empty() => ("error", format!("{} fichier(s) en erreur", r.failed.len())),
+        Ok(r) => (
+            "success",
+            format!(
+                "{} envoye(s), {} recupere(s), {} conflit(s)",
+                r.uploaded.len(),
+                r.downloaded.len(),
+                r.conflicts.len()
+            ),
+        ),
+        Err(e) => ("error", e.clone()),
+    };
+    let data = match result {
+        Ok(r) => serde_json::json!({
+            "direction": "two-way",
+            "files": r.uploaded.len() + r.downloaded.len(),
+            "conflicts": r.conflicts.len(),
+            "paths": r.uploaded.iter().chain(&r.downloaded).take(activity_feed::MAX_SYNC_PATHS).collect::<Vec<_>>(),
+        }),
+        Err(_) => serde_json::json!({ "direction": "two-way" }),
+    };
+    activity_feed::record(
+        app_handle,
+        activity_feed::new_event(project_id, "sync", title, Some(detail), Some(status), data),
+    );
+}
 
-            Ok(())
-        })();
+/// Copy one-sided changes in both directions; conflicts are only reported
+#[tauri::command]
+fn two_way_sync(
+    local_path: String,
+    config: SFTPConfig,
+    project_id: String,
+    confirmation: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<two_way_sync::TwoWayResult, String> {
+    let (app_dir, _lock) = begin_two_way_sync(&app_handle, &project_id, confirmation.as_deref())?;
+    set_cancelled(&project_id, false);
+    let result = two_way_sync::sync(&app_dir, &local_path, &config, &project_id, &app_handle);
+    set_cancelled(&project_id, false);
+    record_two_way_activity(&app_handle, &app_dir, &project_id, &local_path, "Synchronisation bidirectionnelle", &result);
+    result
+}
 
-        match result {
-            Ok(_) => {
-                completed += 1;
-                let progress = 20 + ((completed as u32 * 70) / total_files.max(1) as u32);
-
-                // Emit file complete event
-                let _ = app_handle.emit_all(
-                    "sync-progress",
-                    SyncProgressEvent {
-                        project_id: project_id.to_string(),
-                        event: "file_complete".to_string(),
-                        file: Some(diff.path.clone()),
-                        progress,
-                        file_progress: Some(100),
-                        bytes_sent: Some(file_size),
-                        bytes_total: Some(file_size),
-                        message: None,
-                        timestamp: std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap_or_default()
-                            .as_millis() as u64,
-                    },
-                );
-            }
-            Err(e) => {
-                errors.push(format!("{}: {}", diff.path, e));
+/// Settle two-way conflicts with keep-local, keep-remote or keep-both per file
+#[tauri::command]
+fn resolve_conflicts(
+    local_path: String,
+    config: SFTPConfig,
+    project_id: String,
+    resolutions: Vec<two_way_sync::ConflictResolution>,
+    confirmation: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<two_way_sync::TwoWayResult, String> {
+    let (app_dir, _lock) = begin_two_way_sync(&app_handle, &project_id, confirmation.as_deref())?;
+    let result = two_way_sync::resolve_conflicts(&app_dir, &local_path, &config, &project_id, &resolutions, &app_handle);
+    record_two_way_activity(&app_handle, &app_dir, &project_id, &local_path, "Resolution de conflits", &result);
+    result
+}
 
-                // Emit file error event
-                let _ = app_handle.emit_all(
-                    "sync-progress",
-                    SyncProgressEvent {
-                        project_id: project_id.to_string(),
-                        event: "file_error".to_string(),
-                        file: Some(diff.path.clone()),
-                        progress: 20 + ((completed as u32 * 70) / total_files.max(1) as u32),
-                        file_progress: Some(0),
-                        bytes_sent: None,
-                        bytes_total: Some(file_size),
-                        message: Some(e),
-                        timestamp: std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap_or_default()
-                            .as_millis() as u64,
-                    },
-                );
+fn sync_sftp_with_progress(
+    local_path: &str,
+    config: &SFTPConfig,
+    diffs: &[FileDiff],
+    project_id: &str,
+    app_handle: &tauri::AppHandle,
+    upload: UploadOptions,
+) -> Result<(), String> {
+    let tcp = proxy::connect(config.proxy.as_ref(), &config.host, config.port, Duration::from_secs(10))?;
 
-                // After 3 consecutive errors, stop
-                if errors.len() >= 3 {
-                    return Err(format!(
-                        "Arrêt après 3 erreurs. Dernière erreur: {}",
-                        errors.last().unwrap_or(&String::new())
-                    ));
-                }
-            }
-        }
-    }
+    // Set timeouts
+    tcp.set_read_timeout(Some(Duration::from_secs(60)))
+        .map_err(|e| format!("Failed to set read timeout: {}", e))?;
+    tcp.set_write_timeout(Some(Duration::from_secs(120)))
+        .map_err(|e| format!("Failed to set write timeout: {}", e))?;
 
-    if !errors.is_empty() {
-        return Err(format!(
-            "{} fichier(s) en erreur: {}",
-            errors.len(),
-            errors.join(", ")
-        ));
-    }
+    let mut sess = ssh2::Session::new().map_err(|e| format!("Session error: {}", e))?;
+    sess.set_tcp_stream(tcp);
+    sess.handshake().map_err(|e| format!("Handshake failed: {}", e))?;
+    ssh_auth::authenticate(&sess, config)?;
 
-    Ok(())
+    let mut sftp = sess.sftp().map_err(|e| format!("SFTP error: {}", e))?;
+
+    upload_with_progress(&mut sftp, local_path, &config.remote_path, diffs, project_id, upload, &mut |event| {
+        let _ = app_handle.emit_all(events::SYNC_PROGRESS, event);
+    })
 }
 
 fn create_sftp_dirs(sftp: &ssh2::Sftp, path: &Path) -> Result<(), String> {
@@ -942,12 +2511,11 @@ fn sync_ftp_with_progress(
     diffs: &[FileDiff],
     project_id: &str,
     app_handle: &tauri::AppHandle,
+    upload: UploadOptions,
 ) -> Result<(), String> {
-    let addr = resolve_addr(&config.host, config.port)?;
     let passive = config.passive.unwrap_or(true);
 
-    let mut ftp = suppaftp::FtpStream::connect_timeout(addr, Duration::from_secs(10))
-        .map_err(|e| format!("FTP connection failed: {}", e))?;
+    let mut ftp = proxy::connect_ftp(config.proxy.as_ref(), &config.host, config.port, Duration::from_secs(10))?;
 
     // Set timeouts
     ftp.get_ref()
@@ -967,8 +2535,24 @@ fn sync_ftp_with_progress(
     ftp.transfer_type(suppaftp::types::FileType::Binary)
         .map_err(|e| format!("Failed to set binary mode: {}", e))?;
 
-    let remote_base = &config.remote_path;
+    let result = upload_with_progress(&mut ftp, local_path, &config.remote_path, diffs, project_id, upload, &mut |event| {
+        let _ = app_handle.emit_all(events::SYNC_PROGRESS, event);
+    });
+    let _ = ftp.quit();
+    result
+}
 
+/// Upload the added and modified files of `diffs` one after the other,
+/// reporting each through `emit`; stops after 3 errors or on cancellation
+fn upload_with_progress(
+    remote: &mut dyn remote_fs::RemoteFs,
+    local_path: &str,
+    remote_base: &str,
+    diffs: &[FileDiff],
+    project_id: &str,
+    upload: UploadOptions,
+    emit: &mut dyn FnMut(SyncProgressEvent),
+) -> Result<(), String> {
     // Filter to only files that need uploading
     let files_to_upload: Vec<_> = diffs
         .iter()
@@ -979,106 +2563,62 @@ fn sync_ftp_with_progress(
     let mut completed = 0;
     let mut errors: Vec<String> = Vec::new();
 
+    let event = |kind: events::SyncEventKind, diff: &FileDiff, completed: usize, done: bool, message: Option<String>| {
+        let file_size = diff.local_size.unwrap_or(0);
+        SyncProgressEvent {
+            project_id: project_id.to_string(),
+            schema_version: events::SCHEMA_VERSION,
+            event: kind,
+            file: Some(diff.path.clone()),
+            progress: 20 + ((completed as u32 * 70) / total_files.max(1) as u32),
+            file_progress: Some(if done { 100 } else { 0 }),
+            bytes_sent: if message.is_some() { None } else { Some(if done { file_size } else { 0 }) },
+            bytes_total: Some(file_size),
+            message,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+        }
+    };
+
     for diff in &files_to_upload {
         // Check cancellation
         if is_cancelled(project_id) {
-            let _ = ftp.quit();
             return Err("Synchronisation annulée".to_string());
         }
 
         let local_file = format!("{}/{}", local_path, diff.path);
         let remote_file = format!("{}/{}", remote_base, diff.path);
-        let file_size = diff.local_size.unwrap_or(0);
 
-        // Emit file start event
-        let progress = 20 + ((completed as u32 * 70) / total_files.max(1) as u32);
-        let _ = app_handle.emit_all(
-            "sync-progress",
-            SyncProgressEvent {
-                project_id: project_id.to_string(),
-                event: "file_start".to_string(),
-                file: Some(diff.path.clone()),
-                progress,
-                file_progress: Some(0),
-                bytes_sent: Some(0),
-                bytes_total: Some(file_size),
-                message: None,
-                timestamp: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_millis() as u64,
-            },
-        );
+        emit(event(events::SyncEventKind::FileStart, diff, completed, false, None));
 
         // Create parent directories if needed
         if let Some(parent) = Path::new(&diff.path).parent() {
-            let _ = create_ftp_dirs(&mut ftp, remote_base, parent);
+            remote_fs::create_dirs(remote, remote_base, parent);
         }
 
         // Read and upload file
         let result: Result<(), String> = (|| {
             let mut file = File::open(&local_file)
                 .map_err(|e| format!("Failed to open {}: {}", local_file, e))?;
-            let mut contents = Vec::new();
-            file.read_to_end(&mut contents)
-                .map_err(|e| format!("Failed to read {}: {}", local_file, e))?;
-
-            let mut cursor = std::io::Cursor::new(contents);
-            ftp.put_file(&remote_file, &mut cursor)
-                .map_err(|e| format!("Failed to upload {}: {}", remote_file, e))?;
-
+            atomic_upload::put(remote, &remote_file, upload.atomic, &mut file)?;
+            remote.apply_attributes(&local_file, &remote_file, upload);
             Ok(())
         })();
 
         match result {
             Ok(_) => {
                 completed += 1;
-                let progress = 20 + ((completed as u32 * 70) / total_files.max(1) as u32);
-
-                // Emit file complete event
-                let _ = app_handle.emit_all(
-                    "sync-progress",
-                    SyncProgressEvent {
-                        project_id: project_id.to_string(),
-                        event: "file_complete".to_string(),
-                        file: Some(diff.path.clone()),
-                        progress,
-                        file_progress: Some(100),
-                        bytes_sent: Some(file_size),
-                        bytes_total: Some(file_size),
-                        message: None,
-                        timestamp: std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap_or_default()
-                            .as_millis() as u64,
-                    },
-                );
+                emit(event(events::SyncEventKind::FileComplete, diff, completed, true, None));
             }
             Err(e) => {
                 errors.push(format!("{}: {}", diff.path, e));
-
-                // Emit file error event
-                let _ = app_handle.emit_all(
-                    "sync-progress",
-                    SyncProgressEvent {
-                        project_id: project_id.to_string(),
-                        event: "file_error".to_string(),
-                        file: Some(diff.path.clone()),
-                        progress: 20 + ((completed as u32 * 70) / total_files.max(1) as u32),
-                        file_progress: Some(0),
-                        bytes_sent: None,
-                        bytes_total: Some(file_size),
-                        message: Some(e),
-                        timestamp: std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap_or_default()
-                            .as_millis() as u64,
-                    },
-                );
+                sync_history::note_error(project_id, Some(&diff.path), &e);
+                emit(event(events::SyncEventKind::FileError, diff, completed, false, Some(e)));
 
                 // After 3 consecutive errors, stop
                 if errors.len() >= 3 {
-                    let _ = ftp.quit();
                     return Err(format!(
                         "Arrêt après 3 erreurs. Dernière erreur: {}",
                         errors.last().unwrap_or(&String::new())
@@ -1088,8 +2628,6 @@ fn sync_ftp_with_progress(
         }
     }
 
-    let _ = ftp.quit();
-
     if !errors.is_empty() {
         return Err(format!(
             "{} fichier(s) en erreur: {}",
@@ -1191,6 +2729,47 @@ fn delete_password(key: String) -> Result<(), String> {
     Ok(())
 }
 
+// ============================================
+// Connection Profile Commands
+// ============================================
+
+#[tauri::command]
+fn list_connection_profiles(app_handle: tauri::AppHandle) -> Result<Vec<connection_profiles::ConnectionProfile>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(connection_profiles::list(&app_dir))
+}
+
+/// Create (no id) or update a profile; a given password goes to the keyring
+#[tauri::command]
+fn save_connection_profile(
+    profile: connection_profiles::ProfileInput,
+    app_handle: tauri::AppHandle,
+) -> Result<connection_profiles::ConnectionProfile, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    connection_profiles::save(&app_dir, profile)
+}
+
+#[tauri::command]
+fn delete_connection_profile(id: String, remove_password: Option<bool>, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    connection_profiles::delete(&app_dir, &id, remove_password.unwrap_or(true))
+}
+
+/// Change the password of every project using the profile
+#[tauri::command]
+fn set_connection_profile_password(id: String, password: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    let profile = connection_profiles::get(&app_dir, &id)?;
+    connection_profiles::set_password(&profile, &password)
+}
+
+/// Full connection of a project referencing a profile, password included
+#[tauri::command]
+fn resolve_connection_profile(id: String, remote_path: String, app_handle: tauri::AppHandle) -> Result<SFTPConfig, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    connection_profiles::config_for(&app_dir, &id, &remote_path)
+}
+
 #[derive(Debug, Serialize)]
 struct WebpageContent {
     html: String,
@@ -1416,7 +2995,8 @@ fn create_folder(path: String) -> Result<(), String> {
 
 /// Delete a folder (optionally recursive)
 #[tauri::command]
-fn delete_folder(path: String, recursive: bool) -> Result<(), String> {
+fn delete_folder(path: String, recursive: bool, app_handle: tauri::AppHandle) -> Result<(), String> {
+    readonly_mode::ensure_writable("la suppression de dossiers")?;
     let folder_path = Path::new(&path);
 
     // Safety: Don't allow deleting system paths
@@ -1428,6 +3008,11 @@ fn delete_folder(path: String, recursive: bool) -> Result<(), String> {
         return Err("Folder does not exist".to_string());
     }
 
+    if simulation::is_enabled() {
+        emit_simulation(&app_handle, &simulation::plan_delete_path(&path, recursive));
+        return Ok(());
+    }
+
     if recursive {
         fs::remove_dir_all(folder_path)
             .map_err(|e| format!("Failed to delete folder recursively: {}", e))
@@ -1439,7 +3024,8 @@ fn delete_folder(path: String, recursive: bool) -> Result<(), String> {
 
 /// Delete a file
 #[tauri::command]
-fn delete_file(path: String) -> Result<(), String> {
+fn delete_file(path: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    readonly_mode::ensure_writable("la suppression de fichiers")?;
     let file_path = Path::new(&path);
 
     // Safety checks
@@ -1455,6 +3041,11 @@ fn delete_file(path: String) -> Result<(), String> {
         return Err("Path is a directory, not a file".to_string());
     }
 
+    if simulation::is_enabled() {
+        emit_simulation(&app_handle, &simulation::plan_delete_path(&path, false));
+        return Ok(());
+    }
+
     fs::remove_file(file_path)
         .map_err(|e| format!("Failed to delete file: {}", e))
 }
@@ -1586,6 +3177,8 @@ struct ScrapeConfigInput {
     download_css: bool,
     #[serde(rename = "extractText")]
     extract_text: bool,
+    #[serde(default)]
+    proxy: Option<proxy::ProxyConfig>,
 }
 
 #[tauri::command]
@@ -1597,6 +3190,7 @@ fn scrape_website(config: ScrapeConfigInput) -> Result<scraper::ScrapeResult, St
         download_images: config.download_images,
         download_css: config.download_css,
         extract_text: config.extract_text,
+        proxy: config.proxy,
     };
 
     scraper::scrape_website(scrape_config)
@@ -1605,8 +3199,10 @@ fn scrape_website(config: ScrapeConfigInput) -> Result<scraper::ScrapeResult, St
 // Scraping event for real-time progress
 #[derive(Debug, Clone, Serialize)]
 struct ScrapeProgressEvent {
+    /// events::SCHEMA_VERSION
+    schema_version: u32,
     project_id: String,
-    event_type: String, // "page_start", "page_complete", "image_download", "css_download", "complete", "error"
+    event_type: events::ScrapeEventKind,
     url: Option<String>,
     title: Option<String>,
     pages_scraped: usize,
@@ -1633,6 +3229,7 @@ async fn scrape_website_with_events(
         download_images: config.download_images,
         download_css: config.download_css,
         extract_text: config.extract_text,
+        proxy: config.proxy.clone(),
     };
 
     let project_id_for_callback = project_id.clone();
@@ -1652,7 +3249,8 @@ async fn scrape_website_with_events(
     // Process events from the channel in the async context
     let event_processor = tokio::task::spawn_blocking(move || {
         while let Ok(progress) = rx.recv() {
-            let _ = window_for_receiver.emit("scrape-progress", ScrapeProgressEvent {
+            let event = ScrapeProgressEvent {
+                schema_version: events::SCHEMA_VERSION,
                 project_id: project_id_for_callback.clone(),
                 event_type: progress.event_type,
                 url: progress.url,
@@ -1663,7 +3261,9 @@ async fn scrape_website_with_events(
                 css_downloaded: progress.css_downloaded,
                 progress: progress.progress_percent,
                 message: progress.message,
-            });
+            };
+            monitor_windows::forward(&window_for_receiver.app_handle(), events::SCRAPE_PROGRESS, &event.project_id, &event);
+            let _ = window_for_receiver.emit(events::SCRAPE_PROGRESS, event);
         }
     });
 
@@ -1684,7 +3284,7 @@ async fn scrape_website_with_events(
 #[derive(Debug, Clone, Deserialize)]
 struct FullScrapeConfigInput {
     url: String,
-    #[serde(rename = "outputPath")]
+    #[serde(rename = "outputPath", default)]
     output_path: String,
     #[serde(rename = "maxPages", default = "default_max_pages")]
     max_pages: u32,
@@ -1700,24 +3300,72 @@ struct FullScrapeConfigInput {
     rewrite_urls: bool,
     #[serde(rename = "generateReport", default = "default_true")]
     generate_report: bool,
+    #[serde(rename = "generateGallery", default = "default_true")]
+    generate_gallery: bool,
+    #[serde(rename = "hashAssetNames", default)]
+    hash_asset_names: bool,
+    #[serde(rename = "maxRedirects", default = "default_max_redirects")]
+    max_redirects: u32,
+    #[serde(rename = "captureErrorPage", default)]
+    capture_error_page: bool,
+    #[serde(rename = "checkSitemap", default = "default_true")]
+    check_sitemap: bool,
+    #[serde(rename = "subsetFonts", default)]
+    subset_fonts: bool,
+    /// Reuse assets already captured for another project from the same domain
+    #[serde(rename = "useSharedCache", default)]
+    use_shared_cache: bool,
+    /// Watch the asset folders once the scrape is done (see watch_scrape_capture)
+    #[serde(rename = "fileNewAssets", default)]
+    file_new_assets: bool,
+    #[serde(default)]
+    proxy: Option<proxy::ProxyConfig>,
+}
+
+impl FullScrapeConfigInput {
+    fn to_scrape_config(&self, output_path: &str) -> full_site_scraper::FullScrapeConfig {
+        full_site_scraper::FullScrapeConfig {
+            url: self.url.clone(),
+            output_path: output_path.to_string(),
+            max_pages: self.max_pages,
+            download_images: self.download_images,
+            download_css: self.download_css,
+            download_js: self.download_js,
+            download_fonts: self.download_fonts,
+            rewrite_urls: self.rewrite_urls,
+            generate_report: self.generate_report,
+            generate_gallery: self.generate_gallery,
+            hash_asset_names: self.hash_asset_names,
+            max_redirects: self.max_redirects,
+            capture_error_page: self.capture_error_page,
+            check_sitemap: self.check_sitemap,
+            subset_fonts: self.subset_fonts,
+            shared_cache_dir: None,
+            proxy: self.proxy.clone(),
+        }
+    }
+
+    /// Scrape config with the shared cache directory when enabled
+    fn to_scrape_config_for_app(&self, output_path: &str, app_handle: &tauri::AppHandle) -> full_site_scraper::FullScrapeConfig {
+        let mut scrape_config = self.to_scrape_config(output_path);
+        if self.use_shared_cache {
+            scrape_config.shared_cache_dir = shared_scrape_cache_root(app_handle).map(|dir| dir.to_string_lossy().to_string());
+        }
+        scrape_config
+    }
+}
+
+fn shared_scrape_cache_root(app_handle: &tauri::AppHandle) -> Option<PathBuf> {
+    data_location::app_data_dir(app_handle).map(|dir| dir.join("shared_scrape_cache"))
 }
 
 fn default_max_pages() -> u32 { 100 }
+fn default_max_redirects() -> u32 { 10 }
 fn default_true() -> bool { true }
 
 #[tauri::command]
 fn scrape_full_site(config: FullScrapeConfigInput) -> Result<full_site_scraper::FullScrapeResult, String> {
-    let scrape_config = full_site_scraper::FullScrapeConfig {
-        url: config.url,
-        output_path: config.output_path,
-        max_pages: config.max_pages,
-        download_images: config.download_images,
-        download_css: config.download_css,
-        download_js: config.download_js,
-        download_fonts: config.download_fonts,
-        rewrite_urls: config.rewrite_urls,
-        generate_report: config.generate_report,
-    };
+    let scrape_config = config.to_scrape_config(&config.output_path);
 
     full_site_scraper::scrape_full_site(scrape_config)
 }
@@ -1728,30 +3376,136 @@ async fn scrape_full_site_with_events(
     project_id: String,
     window: tauri::Window,
 ) -> Result<full_site_scraper::FullScrapeResult, String> {
-    use std::sync::mpsc;
-    use std::thread;
-
-    let scrape_config = full_site_scraper::FullScrapeConfig {
-        url: config.url.clone(),
-        output_path: config.output_path.clone(),
-        max_pages: config.max_pages,
-        download_images: config.download_images,
-        download_css: config.download_css,
-        download_js: config.download_js,
-        download_fonts: config.download_fonts,
-        rewrite_urls: config.rewrite_urls,
-        generate_report: config.generate_report,
-    };
+    let app_handle = window.app_handle();
+    let scrape_config = config.to_scrape_config_for_app(&config.output_path, &app_handle);
 
-    // Get or create cancel flag for this project
-    let cancel_flag = get_or_create_scrape_cancel_flag(&project_id);
-    // Reset the cancel flag before starting
-    cancel_flag.store(false, std::sync::atomic::Ordering::Relaxed);
+    let result = run_full_scrape_with_events(scrape_config, project_id.clone(), window).await?;
+    if config.file_new_assets {
+        start_scrape_asset_filing(&app_handle, &project_id, &result.output_path);
+    }
+    Ok(result)
+}
 
-    let project_id_for_callback = project_id.clone();
-    let window_for_receiver = window.clone();
+/// Register a finished capture with the watcher, failures are only logged
+fn start_scrape_asset_filing(app_handle: &tauri::AppHandle, project_id: &str, capture_path: &str) {
+    let state = app_handle.state::<Mutex<FileWatcherManager>>();
+    let started = state
+        .lock()
+        .map_err(|e| e.to_string())
+        .and_then(|manager| manager.watch_scrape_assets(project_id.to_string(), capture_path.to_string(), app_handle.clone()));
+    if let Err(e) = started {
+        println!("[Scraper] Warning: Failed to watch capture assets: {}", e);
+    }
+}
 
-    // Create a channel to send progress events from the scraping thread
+/// Scrape a site directly into the project folder structure and register the capture
+#[tauri::command]
+async fn scrape_site_into_project(
+    config: FullScrapeConfigInput,
+    project_id: String,
+    project_path: String,
+    target_folder: Option<String>,
+    create_snapshot: Option<bool>,
+    window: tauri::Window,
+) -> Result<scrape_capture::CaptureRecord, String> {
+    let (output_path, relative_path) =
+        scrape_capture::capture_output_path(&project_path, target_folder.as_deref(), &config.url)?;
+    fs::create_dir_all(&output_path)
+        .map_err(|e| format!("Failed to create capture folder: {}", e))?;
+    let output_path = output_path.to_string_lossy().to_string();
+
+    let app_handle = window.app_handle();
+    let scrape_config = config.to_scrape_config_for_app(&output_path, &app_handle);
+
+    let result = run_full_scrape_with_events(scrape_config, project_id.clone(), window).await?;
+
+    // Captures get their own history so they don't rotate out deploy snapshots
+    let mut snapshot_id = None;
+    if create_snapshot.unwrap_or(false) {
+        if let Some(app_dir) = data_location::app_data_dir(&app_handle) {
+            let history_id = format!("{}-captures", project_id);
+            let backup_dir = version_history::get_backup_dir(&app_dir, &history_id);
+            let backup_dir_str = backup_dir.to_string_lossy().to_string();
+            let message = format!("Capture de {}", config.url);
+
+            match version_history::create_snapshot(&history_id, &output_path, Some(&backup_dir_str), Some(&message)) {
+                Ok(snapshot) => {
+                    snapshot_id = Some(snapshot.id.clone());
+                    if let Ok(mut history) = version_history::load_history(&app_dir, &history_id) {
+                        history.add_snapshot(snapshot);
+                        let _ = version_history::save_history(&app_dir, &history);
+                    }
+                }
+                Err(e) => println!("[Scraper] Warning: Failed to snapshot capture: {}", e),
+            }
+        }
+    }
+
+    let record = scrape_capture::CaptureRecord {
+        id: uuid::Uuid::new_v4().to_string(),
+        url: config.url,
+        domain: relative_path.rsplit('/').next().unwrap_or_default().to_string(),
+        relative_path,
+        captured_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        pages_downloaded: result.pages_downloaded,
+        assets_downloaded: result.assets_downloaded,
+        total_size_bytes: result.total_size_bytes,
+        snapshot_id,
+    };
+
+    let mut captures = scrape_capture::ProjectCaptures::load(&project_path);
+    captures.register(record.clone());
+    captures.save(&project_path)?;
+
+    if config.file_new_assets {
+        start_scrape_asset_filing(&app_handle, &project_id, &output_path);
+    }
+
+    Ok(record)
+}
+
+/// List the site captures registered in a project
+#[tauri::command]
+fn list_scrape_captures(project_path: String) -> Vec<scrape_capture::CaptureRecord> {
+    scrape_capture::ProjectCaptures::load(&project_path).captures
+}
+
+/// Generate a rebuild skeleton (Markdown content, tokens, assets) from a site capture
+#[tauri::command]
+async fn convert_scrape_to_project(
+    capture_path: String,
+    target_path: Option<String>,
+    options: Option<scrape_convert::ConvertOptions>,
+) -> Result<scrape_convert::ConversionResult, String> {
+    tokio::task::spawn_blocking(move || {
+        let capture_dir = Path::new(&capture_path);
+        let target_dir = target_path
+            .map(PathBuf::from)
+            .unwrap_or_else(|| scrape_convert::default_target(capture_dir));
+        scrape_convert::convert(capture_dir, &target_dir, &options.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| format!("Conversion task failed: {}", e))?
+}
+
+async fn run_full_scrape_with_events(
+    scrape_config: full_site_scraper::FullScrapeConfig,
+    project_id: String,
+    window: tauri::Window,
+) -> Result<full_site_scraper::FullScrapeResult, String> {
+    use std::sync::mpsc;
+    use std::thread;
+
+    // Get or create cancel flag for this project
+    let cancel_flag = get_or_create_scrape_cancel_flag(&project_id);
+    // Reset the cancel flag before starting
+    cancel_flag.store(false, std::sync::atomic::Ordering::Relaxed);
+    let _watched = watchdog::track_scrape(&project_id);
+
+    let project_id_for_callback = project_id.clone();
+    let window_for_receiver = window.clone();
+
+    // Create a channel to send progress events from the scraping thread
     let (tx, rx) = mpsc::channel::<full_site_scraper::FullScrapeProgress>();
 
     // Spawn the scraping task in a separate thread
@@ -1761,6 +3515,7 @@ async fn scrape_full_site_with_events(
             &project_id_for_callback,
             cancel_flag,
             |progress| {
+                watchdog::beat("scrape", &project_id_for_callback);
                 // Send progress through channel
                 let _ = tx.send(progress);
             },
@@ -1768,9 +3523,16 @@ async fn scrape_full_site_with_events(
     });
 
     // Process events from the channel in the async context
+    let project_id_for_monitors = project_id.clone();
     let event_processor = tokio::task::spawn_blocking(move || {
         while let Ok(progress) = rx.recv() {
-            let _ = window_for_receiver.emit("full-scrape-progress", &progress);
+            monitor_windows::forward(
+                &window_for_receiver.app_handle(),
+                events::FULL_SCRAPE_PROGRESS,
+                &project_id_for_monitors,
+                &progress,
+            );
+            let _ = window_for_receiver.emit(events::FULL_SCRAPE_PROGRESS, &progress);
         }
     });
 
@@ -1781,9 +3543,77 @@ async fn scrape_full_site_with_events(
     // Wait for event processor to finish
     let _ = event_processor.await;
 
+    let event = match &result {
+        Ok(r) => activity_feed::new_event(
+            &project_id,
+            "scrape",
+            "Scraping",
+            Some(format!("{} page(s), {} ressource(s)", r.pages_downloaded, r.assets_downloaded)),
+            Some(if r.success { "success" } else { "error" }),
+            serde_json::json!({
+                "pages": r.pages_downloaded,
+                "assets": r.assets_downloaded,
+                "bytes": r.total_size_bytes,
+                "outputPath": r.output_path,
+            }),
+        ),
+        Err(e) => activity_feed::new_event(&project_id, "scrape", "Scraping", Some(e.clone()), Some("error"), serde_json::Value::Null),
+    };
+    activity_feed::record(&window.app_handle(), event);
+
     result
 }
 
+/// Re-download only the assets of an existing capture that changed remotely
+#[tauri::command]
+async fn refresh_scrape_capture(
+    output_path: String,
+    project_id: String,
+    window: tauri::Window,
+) -> Result<scrape_refresh::RefreshResult, String> {
+    println!("[Rust] refresh_scrape_capture called for: {}", output_path);
+
+    let cancel_flag = get_or_create_scrape_cancel_flag(&project_id);
+    cancel_flag.store(false, std::sync::atomic::Ordering::Relaxed);
+
+    tokio::task::spawn_blocking(move || {
+        let _watched = watchdog::track_scrape(&project_id);
+        scrape_refresh::refresh_capture(&output_path, &project_id, cancel_flag, |progress| {
+            watchdog::beat("scrape", &project_id);
+            let _ = window.emit(events::SCRAPE_REFRESH_PROGRESS, &progress);
+        })
+    })
+    .await
+    .map_err(|e| format!("Refresh task failed: {}", e))?
+}
+
+/// Write `ATTRIBUTION.md` (sources, capture dates, license and robots hints) in a capture folder
+#[tauri::command]
+fn generate_scrape_attribution(output_path: String) -> Result<scrape_attribution::AttributionSummary, String> {
+    scrape_attribution::write_summary(Path::new(&output_path))
+}
+
+/// Rename and flag files dropped later into the asset folders of a capture
+#[tauri::command]
+fn watch_scrape_capture(
+    project_id: String,
+    capture_path: String,
+    state: State<'_, Mutex<FileWatcherManager>>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let manager = state.lock().map_err(|e| e.to_string())?;
+    manager.watch_scrape_assets(project_id, capture_path, app_handle)
+}
+
+#[tauri::command]
+fn unwatch_scrape_capture(
+    capture_path: String,
+    state: State<'_, Mutex<FileWatcherManager>>,
+) -> Result<(), String> {
+    let manager = state.lock().map_err(|e| e.to_string())?;
+    manager.unwatch_scrape_assets(&capture_path)
+}
+
 #[tauri::command]
 fn cancel_full_site_scrape(project_id: String) -> Result<(), String> {
     println!("[Rust] cancel_full_site_scrape called for project: {}", project_id);
@@ -1791,6 +3621,48 @@ fn cancel_full_site_scrape(project_id: String) -> Result<(), String> {
     Ok(())
 }
 
+// ============================================
+// Scrape Queue Commands
+// ============================================
+
+/// Queue several URLs for sequential scraping into `<output_root>/<domain>`
+#[tauri::command]
+fn enqueue_scrape_jobs(
+    urls: Vec<String>,
+    output_root: String,
+    config: FullScrapeConfigInput,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<scrape_queue::ScrapeJob>, String> {
+    let base_config = config.to_scrape_config_for_app(&output_root, &app_handle);
+    scrape_queue::enqueue(urls, &output_root, base_config, app_handle)
+}
+
+#[tauri::command]
+fn get_scrape_queue() -> Vec<scrape_queue::ScrapeJob> {
+    scrape_queue::list_jobs()
+}
+
+#[tauri::command]
+fn cancel_scrape_job(job_id: String) -> Result<(), String> {
+    scrape_queue::cancel_job(&job_id)
+}
+
+#[tauri::command]
+fn cancel_scrape_queue() {
+    scrape_queue::cancel_all();
+}
+
+#[tauri::command]
+fn clear_scrape_queue() {
+    scrape_queue::clear_finished();
+}
+
+/// Compare the design systems of all completed queue jobs
+#[tauri::command]
+fn get_scrape_queue_comparison() -> scrape_queue::DesignComparison {
+    scrape_queue::comparison()
+}
+
 // ============================================
 // Version History Commands
 // ============================================
@@ -1802,9 +3674,7 @@ fn create_version_snapshot(
     message: Option<String>,
     app_handle: tauri::AppHandle,
 ) -> Result<version_history::SyncSnapshot, String> {
-    let app_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
+    let app_dir = data_location::app_data_dir(&app_handle)
         .ok_or("Could not get app data directory")?;
 
     let backup_dir = version_history::get_backup_dir(&app_dir, &project_id);
@@ -1821,6 +3691,10 @@ fn create_version_snapshot(
     let mut history = version_history::load_history(&app_dir, &project_id)?;
     history.add_snapshot(snapshot.clone());
     version_history::save_history(&app_dir, &history)?;
+    record_snapshot_activity(&app_handle, &snapshot);
+    if let Err(e) = file_provenance::record_snapshot(&app_dir, &snapshot) {
+        println!("[History] Warning: Failed to record file provenance: {}", e);
+    }
 
     Ok(snapshot)
 }
@@ -1830,9 +3704,7 @@ fn get_version_history(
     project_id: String,
     app_handle: tauri::AppHandle,
 ) -> Result<Vec<version_history::SnapshotSummary>, String> {
-    let app_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
+    let app_dir = data_location::app_data_dir(&app_handle)
         .ok_or("Could not get app data directory")?;
 
     let history = version_history::load_history(&app_dir, &project_id)?;
@@ -1845,9 +3717,7 @@ fn get_snapshot_details(
     snapshot_id: String,
     app_handle: tauri::AppHandle,
 ) -> Result<Option<version_history::SyncSnapshot>, String> {
-    let app_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
+    let app_dir = data_location::app_data_dir(&app_handle)
         .ok_or("Could not get app data directory")?;
 
     let history = version_history::load_history(&app_dir, &project_id)?;
@@ -1862,9 +3732,8 @@ fn restore_version(
     files: Option<Vec<String>>,
     app_handle: tauri::AppHandle,
 ) -> Result<Vec<String>, String> {
-    let app_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
+    readonly_mode::ensure_writable("la restauration de versions")?;
+    let app_dir = data_location::app_data_dir(&app_handle)
         .ok_or("Could not get app data directory")?;
 
     let history = version_history::load_history(&app_dir, &project_id)?;
@@ -1875,6 +3744,44 @@ fn restore_version(
     version_history::restore_snapshot(snapshot, &target_path, files)
 }
 
+/// Extract snapshot files into a throwaway folder (cleaned after a day) and show it,
+/// in `editor` when given, else in the Finder. The working tree is left alone,
+/// so this works in read-only mode too.
+#[tauri::command]
+fn inspect_snapshot_files(
+    project_id: String,
+    snapshot_id: String,
+    files: Vec<String>,
+    editor: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<version_history::InspectionFolder, String> {
+    let app_dir = data_location::app_data_dir(&app_handle)
+        .ok_or("Could not get app data directory")?;
+
+    let history = version_history::load_history(&app_dir, &project_id)?;
+    let snapshot = history
+        .get_snapshot(&snapshot_id)
+        .ok_or("Snapshot not found")?;
+
+    let inspection = version_history::restore_for_inspection(snapshot, files)?;
+    // A single file opens directly in the editor
+    let target = match inspection.files.as_slice() {
+        [single] if editor.is_some() => Path::new(&inspection.path).join(single).to_string_lossy().to_string(),
+        _ => inspection.path.clone(),
+    };
+    match editor {
+        Some(editor) => open_in_editor(target, editor)?,
+        None => open_in_finder(target)?,
+    }
+    Ok(inspection)
+}
+
+/// Remove every inspection folder now
+#[tauri::command]
+fn clear_inspection_folders() -> usize {
+    version_history::clean_inspection_folders(Duration::ZERO)
+}
+
 #[tauri::command]
 fn compare_snapshots(
     project_id: String,
@@ -1882,9 +3789,7 @@ fn compare_snapshots(
     new_snapshot_id: String,
     app_handle: tauri::AppHandle,
 ) -> Result<version_history::SnapshotDiff, String> {
-    let app_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
+    let app_dir = data_location::app_data_dir(&app_handle)
         .ok_or("Could not get app data directory")?;
 
     let history = version_history::load_history(&app_dir, &project_id)?;
@@ -1904,284 +3809,1384 @@ fn compare_snapshots(
 // ============================================
 
 #[tauri::command]
-fn start_sync_scheduler(app_handle: tauri::AppHandle) -> Result<(), String> {
-    scheduler::start_scheduler(app_handle);
-    Ok(())
+fn start_sync_scheduler(app_handle: tauri::AppHandle) -> Result<(), String> {
+    scheduler::start_scheduler(app_handle);
+    Ok(())
+}
+
+#[tauri::command]
+fn stop_sync_scheduler() -> Result<(), String> {
+    scheduler::stop_scheduler();
+    Ok(())
+}
+
+#[tauri::command]
+fn set_sync_schedule(schedule: scheduler::SyncSchedule) -> Result<scheduler::SyncSchedule, String> {
+    scheduler::set_schedule(schedule)
+}
+
+#[tauri::command]
+fn remove_sync_schedule(project_id: String) -> Result<(), String> {
+    scheduler::remove_schedule(&project_id)
+}
+
+#[tauri::command]
+fn get_sync_schedule(project_id: String) -> Result<Option<scheduler::SyncSchedule>, String> {
+    Ok(scheduler::get_schedule(&project_id))
+}
+
+#[tauri::command]
+fn get_all_sync_schedules() -> Result<Vec<scheduler::SyncSchedule>, String> {
+    Ok(scheduler::get_all_schedules())
+}
+
+#[tauri::command]
+fn set_schedule_enabled(project_id: String, enabled: bool) -> Result<(), String> {
+    scheduler::set_schedule_enabled(&project_id, enabled)
+}
+
+#[tauri::command]
+fn update_schedule_result(project_id: String, result: scheduler::ScheduleResult) -> Result<(), String> {
+    scheduler::update_schedule_result(&project_id, result);
+    Ok(())
+}
+
+/// Scheduled jobs allowed to run at once; the others wait by priority
+#[tauri::command]
+fn set_scheduler_concurrency(max_jobs: usize) -> Result<(), String> {
+    scheduler::set_max_concurrent_jobs(max_jobs);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_scheduler_queue() -> scheduler::SchedulerQueue {
+    scheduler::queue()
+}
+
+// ============================================
+// Watchdog Commands
+// ============================================
+
+/// Scheduler liveness, running jobs and the latest incidents
+#[tauri::command]
+fn get_watchdog_report() -> watchdog::WatchdogReport {
+    watchdog::report()
+}
+
+/// Minutes without progress before a sync or scrape is treated as stuck
+#[tauri::command]
+fn set_watchdog_threshold(minutes: u32) -> u32 {
+    watchdog::set_stuck_minutes(minutes)
+}
+
+// ============================================
+// Calendar Export Commands
+// ============================================
+
+/// Export tracked sessions (and optionally upcoming scheduled syncs) to an .ics file
+#[tauri::command]
+fn export_calendar_ics(
+    sessions: Vec<calendar_export::CalendarSession>,
+    options: calendar_export::CalendarExportOptions,
+    output_path: String,
+) -> Result<usize, String> {
+    let count = calendar_export::export_calendar(&sessions, &options, &output_path)?;
+    println!("[Calendar] Exported {} events to {}", count, output_path);
+    Ok(count)
+}
+
+// ============================================
+// Content Inventory Export Commands
+// ============================================
+
+/// Export a scrape's content inventory as CSV or XLSX ("csv"/"xlsx", from the extension when omitted)
+#[tauri::command]
+fn export_content_inventory(
+    inventory: content_inventory::ContentInventory,
+    output_path: String,
+    format: Option<String>,
+) -> Result<usize, String> {
+    let count = inventory_export::export_inventory(&inventory, &output_path, format.as_deref())?;
+    println!("[Inventory] Exported {} pages to {}", count, output_path);
+    Ok(count)
+}
+
+// ============================================
+// IDE Activity Commands
+// ============================================
+
+#[tauri::command]
+fn start_ide_activity_monitor(config: ide_activity::IdeActivityConfig, app_handle: tauri::AppHandle) -> Result<(), String> {
+    ide_activity::start(config, app_handle);
+    Ok(())
+}
+
+#[tauri::command]
+fn stop_ide_activity_monitor() -> Result<(), String> {
+    ide_activity::stop();
+    Ok(())
+}
+
+#[tauri::command]
+fn set_ide_watched_projects(project_ids: Vec<String>) -> Result<(), String> {
+    ide_activity::set_projects(project_ids);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_running_timers(project_ids: Vec<String>) -> Result<(), String> {
+    ide_activity::set_running_timers(project_ids);
+    Ok(())
+}
+
+#[tauri::command]
+fn start_idle_monitor(threshold_minutes: u64, app_handle: tauri::AppHandle) -> Result<(), String> {
+    idle_monitor::start(threshold_minutes, app_handle)
+}
+
+#[tauri::command]
+fn stop_idle_monitor() -> Result<(), String> {
+    idle_monitor::stop();
+    Ok(())
+}
+
+#[tauri::command]
+fn get_system_idle_seconds() -> Option<u64> {
+    idle_monitor::system_idle_seconds()
+}
+
+// ============================================
+// Pomodoro Commands
+// ============================================
+
+#[tauri::command]
+fn pomodoro_get_settings(app_handle: tauri::AppHandle) -> Result<pomodoro::PomodoroSettings, String> {
+    pomodoro::get_settings(&app_handle)
+}
+
+#[tauri::command]
+fn pomodoro_set_settings(settings: pomodoro::PomodoroSettings, app_handle: tauri::AppHandle) -> Result<(), String> {
+    pomodoro::set_settings(&app_handle, settings)
+}
+
+#[tauri::command]
+fn pomodoro_start(project_id: String, app_handle: tauri::AppHandle) -> Result<pomodoro::PomodoroStatus, String> {
+    pomodoro::start(app_handle, project_id)
+}
+
+#[tauri::command]
+fn pomodoro_pause() -> Result<(), String> {
+    pomodoro::set_paused(true)
+}
+
+#[tauri::command]
+fn pomodoro_resume() -> Result<(), String> {
+    pomodoro::set_paused(false)
+}
+
+#[tauri::command]
+fn pomodoro_skip() -> Result<(), String> {
+    pomodoro::skip()
+}
+
+#[tauri::command]
+fn pomodoro_stop(app_handle: tauri::AppHandle) -> Result<(), String> {
+    pomodoro::stop(&app_handle);
+    Ok(())
+}
+
+#[tauri::command]
+fn pomodoro_status() -> Option<pomodoro::PomodoroStatus> {
+    pomodoro::status()
+}
+
+#[tauri::command]
+fn pomodoro_get_stats(app_handle: tauri::AppHandle) -> Result<pomodoro::PomodoroStats, String> {
+    pomodoro::get_stats(&app_handle)
+}
+
+// ============================================
+// Site Migration Commands
+// ============================================
+
+/// Copy a site between two targets through the local machine, resuming an interrupted run
+#[tauri::command]
+async fn migrate_site(
+    source: SFTPConfig,
+    destination: SFTPConfig,
+    window: tauri::Window,
+) -> Result<site_migration::MigrationResult, String> {
+    readonly_mode::ensure_writable("la migration de site")?;
+    let app_dir = data_location::app_data_dir(&window.app_handle()).ok_or("No app dir")?;
+    tokio::task::spawn_blocking(move || {
+        site_migration::migrate(&app_dir, &source, &destination, |progress| {
+            let _ = window.emit(events::MIGRATION_PROGRESS, &progress);
+        })
+    })
+    .await
+    .map_err(|e| format!("Migration task failed: {}", e))?
+}
+
+/// Interrupted migration between two targets, if any
+#[tauri::command]
+fn get_site_migration(
+    source: SFTPConfig,
+    destination: SFTPConfig,
+    app_handle: tauri::AppHandle,
+) -> Result<Option<site_migration::MigrationState>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("No app dir")?;
+    let id = site_migration::migration_id(&source, &destination);
+    Ok(site_migration::load_state(&app_dir, &id))
+}
+
+#[tauri::command]
+fn cancel_site_migration(migration_id: String) {
+    site_migration::cancel_migration(&migration_id);
+}
+
+#[tauri::command]
+fn discard_site_migration(migration_id: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("No app dir")?;
+    site_migration::discard_migration(&app_dir, &migration_id)
+}
+
+// ============================================
+// Transfer Quota Commands
+// ============================================
+
+#[tauri::command]
+fn get_transfer_quota(
+    project_id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<Option<transfer_quota::TransferQuota>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("No app dir")?;
+    Ok(transfer_quota::get_quota(&app_dir, &project_id))
+}
+
+/// Set the project's quota, or remove it with `null`
+#[tauri::command]
+fn set_transfer_quota(
+    project_id: String,
+    quota: Option<transfer_quota::TransferQuota>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("No app dir")?;
+    transfer_quota::set_quota(&app_dir, &project_id, quota)
+}
+
+#[tauri::command]
+fn get_transfer_usage(
+    project_id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<transfer_quota::TransferUsage, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("No app dir")?;
+    Ok(transfer_quota::get_usage(&app_dir, &project_id))
+}
+
+/// Check a planned upload (e.g. the added/modified files of a dry run) against the quota
+#[tauri::command]
+fn check_transfer_quota(
+    project_id: String,
+    planned_bytes: u64,
+    app_handle: tauri::AppHandle,
+) -> Result<transfer_quota::QuotaCheck, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("No app dir")?;
+    Ok(transfer_quota::check(&app_dir, &project_id, planned_bytes))
+}
+
+// ============================================
+// Transfer Resume Commands
+// ============================================
+
+#[tauri::command]
+fn get_transfer_session(
+    project_id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<Option<transfer_resume::TransferSession>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle)
+        .ok_or("Could not get app data directory")?;
+
+    let store = transfer_resume::load_sessions(&app_dir)?;
+    Ok(store.get_session(&project_id).cloned())
+}
+
+#[tauri::command]
+fn create_transfer_session(
+    project_id: String,
+    files: Vec<(String, String, String, u64)>, // (path, local_path, remote_path, size)
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let app_dir = data_location::app_data_dir(&app_handle)
+        .ok_or("Could not get app data directory")?;
+
+    let mut store = transfer_resume::load_sessions(&app_dir)?;
+    let session_id = store.create_session(&project_id);
+
+    if let Some(session) = store.sessions.get_mut(&session_id) {
+        for (path, local_path, remote_path, size) in files {
+            session.add_file(&path, &local_path, &remote_path, size);
+        }
+    }
+
+    transfer_resume::save_sessions(&app_dir, &store)?;
+    Ok(session_id)
+}
+
+#[tauri::command]
+fn update_transfer_progress(
+    session_id: String,
+    file_path: String,
+    transferred: u64,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let app_dir = data_location::app_data_dir(&app_handle)
+        .ok_or("Could not get app data directory")?;
+
+    let mut store = transfer_resume::load_sessions(&app_dir)?;
+
+    if let Some(session) = store.sessions.get_mut(&session_id) {
+        session.update_progress(&file_path, transferred);
+        transfer_resume::save_sessions(&app_dir, &store)?;
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+fn complete_transfer_file(
+    session_id: String,
+    file_path: String,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let app_dir = data_location::app_data_dir(&app_handle)
+        .ok_or("Could not get app data directory")?;
+
+    let mut store = transfer_resume::load_sessions(&app_dir)?;
+
+    if let Some(session) = store.sessions.get_mut(&session_id) {
+        session.mark_completed(&file_path);
+        transfer_resume::save_sessions(&app_dir, &store)?;
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+fn complete_transfer_session(
+    session_id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let app_dir = data_location::app_data_dir(&app_handle)
+        .ok_or("Could not get app data directory")?;
+
+    let mut store = transfer_resume::load_sessions(&app_dir)?;
+    store.complete_session(&session_id);
+    let cleanup_hours = store.cleanup_after_hours;
+    store.cleanup_old_sessions(cleanup_hours);
+    transfer_resume::save_sessions(&app_dir, &store)?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn get_resumable_files(
+    project_id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<transfer_resume::FileTransferState>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle)
+        .ok_or("Could not get app data directory")?;
+
+    let store = transfer_resume::load_sessions(&app_dir)?;
+
+    Ok(store
+        .get_session(&project_id)
+        .map(|s| s.get_resumable_files().into_iter().cloned().collect())
+        .unwrap_or_default())
+}
+
+/// Incomplete syncs whose local files are unchanged (also emitted as `interrupted-syncs` at startup)
+#[tauri::command]
+fn get_interrupted_syncs(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<transfer_resume::InterruptedSync>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle)
+        .ok_or("Could not get app data directory")?;
+
+    transfer_resume::detect_interrupted_syncs(&app_dir)
+}
+
+#[tauri::command]
+fn list_transfer_sessions(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<transfer_resume::TransferSessionSummary>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle)
+        .ok_or("Could not get app data directory")?;
+
+    Ok(transfer_resume::load_sessions(&app_dir)?.summaries())
+}
+
+#[tauri::command]
+fn delete_transfer_session(
+    session_id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<bool, String> {
+    let app_dir = data_location::app_data_dir(&app_handle)
+        .ok_or("Could not get app data directory")?;
+
+    let mut store = transfer_resume::load_sessions(&app_dir)?;
+    if simulation::is_enabled() {
+        emit_simulation(&app_handle, &simulation::plan_delete_session(&store, &session_id));
+        return Ok(false);
+    }
+    let deleted = store.delete_session(&session_id);
+    if deleted {
+        transfer_resume::save_sessions(&app_dir, &store)?;
+    }
+    Ok(deleted)
+}
+
+#[tauri::command]
+fn get_transfer_cleanup_hours(app_handle: tauri::AppHandle) -> Result<i64, String> {
+    let app_dir = data_location::app_data_dir(&app_handle)
+        .ok_or("Could not get app data directory")?;
+
+    Ok(transfer_resume::load_sessions(&app_dir)?.cleanup_after_hours)
+}
+
+/// Set the age (hours) after which completed sessions are removed
+#[tauri::command]
+fn set_transfer_cleanup_hours(hours: i64, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let app_dir = data_location::app_data_dir(&app_handle)
+        .ok_or("Could not get app data directory")?;
+
+    if hours < 1 {
+        return Err("La duree de conservation doit etre d'au moins une heure".to_string());
+    }
+    let mut store = transfer_resume::load_sessions(&app_dir)?;
+    if simulation::is_enabled() {
+        emit_simulation(&app_handle, &simulation::plan_session_cleanup(&store, hours));
+        return Ok(());
+    }
+    store.cleanup_after_hours = hours;
+    store.cleanup_old_sessions(hours);
+    transfer_resume::save_sessions(&app_dir, &store)
+}
+
+/// Verify a partially transferred file chunk by chunk and re-send only the bad or missing chunks
+#[tauri::command]
+async fn repair_partial_upload(
+    config: SFTPConfig,
+    session_id: String,
+    file_path: String,
+    app_handle: tauri::AppHandle,
+) -> Result<transfer_resume::ChunkRepairResult, String> {
+    readonly_mode::ensure_writable("la reparation d'envois")?;
+    let app_dir = data_location::app_data_dir(&app_handle)
+        .ok_or("Could not get app data directory")?;
+
+    tokio::task::spawn_blocking(move || {
+        let mut store = transfer_resume::load_sessions(&app_dir)?;
+        let state = store
+            .sessions
+            .get(&session_id)
+            .and_then(|s| s.files.get(&file_path))
+            .cloned()
+            .ok_or_else(|| format!("Transfer not found: {}", file_path))?;
+
+        let result = match config.protocol.as_deref().unwrap_or("ftp") {
+            "sftp" => {
+                let sess = deploy_manifest::connect_sftp(&config)?;
+                transfer_resume::repair_sftp_upload(&sess, &state.local_path, &state.remote_path)?
+            }
+            "ftp" | "ftps" => {
+                let mut ftp = deploy_manifest::connect_ftp(&config)?;
+                let result = transfer_resume::repair_ftp_upload(&mut ftp, &state.local_path, &state.remote_path);
+                let _ = ftp.quit();
+                result?
+            }
+            other => return Err(format!("Unknown protocol: {}", other)),
+        };
+
+        if let Some(session) = store.sessions.get_mut(&session_id) {
+            session.mark_completed(&file_path);
+        }
+        transfer_resume::save_sessions(&app_dir, &store)?;
+        Ok(result)
+    })
+    .await
+    .map_err(|e| format!("Repair task failed: {}", e))?
+}
+
+// ============================================
+// Delta Sync Commands
+// ============================================
+
+/// Compare local files to a baseline: the last sync (default), a snapshot or the remote deploy manifest
+#[tauri::command]
+fn analyze_delta_sync(
+    project_id: String,
+    local_path: String,
+    baseline: Option<delta_sync::DeltaBaseline>,
+    config: Option<SFTPConfig>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<delta_sync::FileDelta>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle)
+        .ok_or("Could not get app data directory")?;
+
+    let baseline = baseline.unwrap_or(delta_sync::DeltaBaseline::LastSync);
+    let cache = delta_sync::load_baseline(&app_dir, &project_id, &baseline, config.as_ref())?;
+    delta_sync::analyze_delta_sync(&local_path, &cache)
+}
+
+#[tauri::command]
+fn get_delta_transfer_stats(
+    deltas: Vec<delta_sync::FileDelta>,
+) -> Result<delta_sync::DeltaTransferStats, String> {
+    Ok(delta_sync::calculate_transfer_stats(&deltas))
+}
+
+#[tauri::command]
+fn update_delta_cache_after_sync(
+    project_id: String,
+    local_path: String,
+    synced_files: Vec<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let app_dir = data_location::app_data_dir(&app_handle)
+        .ok_or("Could not get app data directory")?;
+
+    delta_sync::update_cache_after_sync(&app_dir, &project_id, &local_path, &synced_files)?;
+    Ok(())
+}
+
+#[tauri::command]
+fn generate_file_signature(
+    file_path: String,
+    relative_path: String,
+) -> Result<delta_sync::FileSignature, String> {
+    let path = Path::new(&file_path);
+    delta_sync::generate_file_signature(path, &relative_path)
+}
+
+#[tauri::command]
+fn get_delta_cache_info(
+    project_id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<delta_sync::SignatureCache, String> {
+    let app_dir = data_location::app_data_dir(&app_handle)
+        .ok_or("Could not get app data directory")?;
+
+    delta_sync::load_cache(&app_dir, &project_id)
+}
+
+#[tauri::command]
+fn clear_delta_cache(
+    project_id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let app_dir = data_location::app_data_dir(&app_handle)
+        .ok_or("Could not get app data directory")?;
+
+    if simulation::is_enabled() {
+        let cache = delta_sync::load_cache(&app_dir, &project_id)?;
+        emit_simulation(&app_handle, &simulation::plan_clear_delta_cache(&cache));
+        return Ok(());
+    }
+    let cache = delta_sync::SignatureCache::new(&project_id);
+    delta_sync::save_cache(&app_dir, &cache)
+}
+
+/// Pre-compute the signatures of the whole project in the background
+#[tauri::command]
+fn warm_delta_cache(
+    project_id: String,
+    local_path: String,
+    only_when_idle: Option<bool>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    delta_warmup::start(app_handle, project_id, local_path, only_when_idle.unwrap_or(true))
+}
+
+#[tauri::command]
+fn cancel_delta_cache_warmup(project_id: String) {
+    delta_warmup::cancel(&project_id);
+}
+
+#[tauri::command]
+fn is_delta_cache_warming(project_id: String) -> bool {
+    delta_warmup::is_running(&project_id)
+}
+
+// ============================================
+// Scrape Cache Commands
+// ============================================
+
+#[tauri::command]
+fn get_scrape_cache(project_path: String) -> Result<Option<scrape_cache::ScrapeCache>, String> {
+    Ok(scrape_cache::ScrapeCache::load(&project_path))
+}
+
+#[tauri::command]
+fn get_scrape_cache_stats(project_path: String) -> Result<Option<scrape_cache::CacheStats>, String> {
+    Ok(scrape_cache::ScrapeCache::load(&project_path).map(|c| c.stats()))
+}
+
+#[tauri::command]
+fn clear_scrape_cache(project_path: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    if simulation::is_enabled() {
+        let cache = scrape_cache::ScrapeCache::load(&project_path);
+        emit_simulation(&app_handle, &simulation::plan_clear_scrape_cache(cache.as_ref()));
+        return Ok(());
+    }
+    if let Some(mut cache) = scrape_cache::ScrapeCache::load(&project_path) {
+        cache.clear();
+        cache.save(&project_path)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn is_url_cached(project_path: String, url: String) -> Result<bool, String> {
+    Ok(scrape_cache::ScrapeCache::load(&project_path)
+        .map(|c| c.is_cached(&url))
+        .unwrap_or(false))
+}
+
+#[tauri::command]
+fn set_scrape_cache_ttl(project_path: String, days: u64) -> Result<(), String> {
+    if let Some(mut cache) = scrape_cache::ScrapeCache::load(&project_path) {
+        cache.set_ttl_days(days);
+        cache.save(&project_path)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn list_shared_scrape_cache(app_handle: tauri::AppHandle) -> Result<Vec<shared_scrape_cache::DomainSummary>, String> {
+    let root = shared_scrape_cache_root(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(shared_scrape_cache::list_domains(&root))
+}
+
+/// Shared cache entries a project has used, by domain
+#[tauri::command]
+fn get_project_shared_cache(
+    project_id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<shared_scrape_cache::ProjectCacheView>, String> {
+    let root = shared_scrape_cache_root(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(shared_scrape_cache::project_view(&root, &project_id))
+}
+
+/// Copy the cached assets of a domain into a project folder
+#[tauri::command]
+async fn copy_shared_cache_into_project(
+    domain: String,
+    project_id: String,
+    target_dir: String,
+    app_handle: tauri::AppHandle,
+) -> Result<shared_scrape_cache::CopySummary, String> {
+    let root = shared_scrape_cache_root(&app_handle).ok_or("Could not get app data directory")?;
+    tokio::task::spawn_blocking(move || {
+        shared_scrape_cache::copy_into_project(&root, &domain, &project_id, &target_dir)
+    })
+    .await
+    .map_err(|e| format!("Shared cache copy task failed: {}", e))?
+}
+
+#[tauri::command]
+fn clear_shared_scrape_cache(domain: String, app_handle: tauri::AppHandle) -> Result<u64, String> {
+    let root = shared_scrape_cache_root(&app_handle).ok_or("Could not get app data directory")?;
+    if simulation::is_enabled() {
+        let path = root.join(&domain).to_string_lossy().to_string();
+        emit_simulation(&app_handle, &simulation::plan_delete_path(&path, true));
+        return Ok(0);
+    }
+    shared_scrape_cache::clear_domain(&root, &domain)
+}
+
+// ============================================
+// Notification Commands
+// ============================================
+
+/// Show a templated notification ("sync_complete", "scrape_error"...) marked with the project's identity
+#[tauri::command]
+fn notify_project_event(
+    kind: String,
+    project_id: Option<String>,
+    vars: Option<HashMap<String, String>>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let mut all_vars: HashMap<String, String> = match (&project_id, data_location::app_data_dir(&app_handle)) {
+        (Some(pid), Some(app_dir)) => project_env::resolve(&app_dir, pid).into_iter().collect(),
+        _ => HashMap::new(),
+    };
+    // The event's own values win over project variables of the same name
+    all_vars.extend(vars.unwrap_or_default());
+    notifications::notify(&app_handle, &kind, project_id.as_deref(), &all_vars)
+}
+
+#[tauri::command]
+fn get_notification_quiet_rules() -> notification_quiet::QuietRules {
+    notification_quiet::get_rules()
+}
+
+/// Save the quiet rules (Focus, screen sharing, quiet hours)
+#[tauri::command]
+fn set_notification_quiet_rules(
+    rules: notification_quiet::QuietRules,
+    app_handle: tauri::AppHandle,
+) -> Result<notification_quiet::QuietRules, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    notification_quiet::set_rules(&app_dir, rules)
+}
+
+/// Whether notifications are held right now and how many wait
+#[tauri::command]
+fn get_notification_quiet_status() -> notification_quiet::QuietStatus {
+    notification_quiet::status()
+}
+
+// ============================================
+// Monitor Window Commands
+// ============================================
+
+/// Open a detached sync, scrape or log monitor (focused if already open)
+#[tauri::command]
+fn open_monitor_window(
+    kind: String,
+    project_id: Option<String>,
+    project_name: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<monitor_windows::MonitorWindow, String> {
+    monitor_windows::open(&app_handle, &kind, project_id, project_name)
+}
+
+#[tauri::command]
+fn list_monitor_windows() -> Vec<monitor_windows::MonitorWindow> {
+    monitor_windows::list()
+}
+
+#[tauri::command]
+fn close_monitor_window(label: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    monitor_windows::close(&app_handle, &label)
+}
+
+// ============================================
+// Onboarding Commands
+// ============================================
+
+/// Detect editors, terminals, git, keychain health and network access for the first-run setup
+#[tauri::command]
+async fn run_onboarding_probes() -> Result<onboarding::CapabilityReport, String> {
+    tokio::task::spawn_blocking(onboarding::probe_capabilities)
+        .await
+        .map_err(|e| format!("Onboarding probe task failed: {}", e))
+}
+
+// ============================================
+// Activity Feed Commands
+// ============================================
+
+fn record_snapshot_activity(app_handle: &tauri::AppHandle, snapshot: &version_history::SyncSnapshot) {
+    activity_feed::record(
+        app_handle,
+        activity_feed::new_event(
+            &snapshot.project_id,
+            "snapshot",
+            "Snapshot",
+            Some(snapshot.message.clone().unwrap_or_else(|| format!("{} fichier(s)", snapshot.files_count))),
+            None,
+            serde_json::json!({
+                "snapshotId": snapshot.id,
+                "files": snapshot.files_count,
+                "bytes": snapshot.total_size,
+            }),
+        ),
+    );
+}
+
+/// Page of a project's activity, newest first
+#[tauri::command]
+fn get_activity_feed(
+    project_id: String,
+    kinds: Option<Vec<String>>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    app_handle: tauri::AppHandle,
+) -> Result<activity_feed::ActivityPage, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(activity_feed::query(&app_dir, &project_id, kinds.as_deref(), offset.unwrap_or(0), limit))
+}
+
+/// Record an event tracked by the frontend (timer sessions)
+#[tauri::command]
+fn record_activity(
+    project_id: String,
+    kind: String,
+    title: String,
+    detail: Option<String>,
+    data: Option<serde_json::Value>,
+    app_handle: tauri::AppHandle,
+) -> Result<activity_feed::ActivityEvent, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    let event = activity_feed::new_event(&project_id, &kind, &title, detail, None, data.unwrap_or_default());
+    activity_feed::append(&app_dir, &event)?;
+    let _ = app_handle.emit_all(events::ACTIVITY_RECORDED, &event);
+    Ok(event)
+}
+
+#[tauri::command]
+fn clear_activity_feed(project_id: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    activity_feed::clear(&app_dir, &project_id)
+}
+
+// ============================================
+// Known Hosts Commands
+// ============================================
+
+/// Connect to an SSH server and return its host key fingerprint and whether it is trusted
+#[tauri::command]
+async fn verify_host_key(
+    host: String,
+    port: u16,
+    proxy: Option<proxy::ProxyConfig>,
+    app_handle: tauri::AppHandle,
+) -> Result<known_hosts::HostKeyCheck, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    tokio::task::spawn_blocking(move || known_hosts::probe(&app_dir, &host, port, proxy.as_ref()))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Trust a host key after the user confirmed its fingerprint
+#[tauri::command]
+fn trust_host_key(
+    host: String,
+    port: u16,
+    key_type: String,
+    fingerprint: String,
+    app_handle: tauri::AppHandle,
+) -> Result<known_hosts::KnownHost, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    known_hosts::trust(&app_dir, &host, port, &key_type, &fingerprint)
+}
+
+#[tauri::command]
+fn get_known_hosts(app_handle: tauri::AppHandle) -> Result<Vec<known_hosts::KnownHost>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(known_hosts::list(&app_dir))
+}
+
+#[tauri::command]
+fn forget_host_key(host: String, port: u16, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    known_hosts::forget(&app_dir, &host, port)
+}
+
+// ============================================
+// Remote Listing Cache Commands
+// ============================================
+
+#[tauri::command]
+fn get_remote_listing_cache(
+    project_id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<Option<remote_listing_cache::CacheInfo>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(remote_listing_cache::info(&app_dir, &project_id))
+}
+
+/// Forget the cached listing, the next diff scans the server
+#[tauri::command]
+fn clear_remote_listing_cache(project_id: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    remote_listing_cache::invalidate(&app_dir, &project_id);
+    Ok(())
+}
+
+// ============================================
+// Concurrency Tuning Commands
+// ============================================
+
+/// Connection optimum learned by the adaptive mode, by target
+#[tauri::command]
+fn get_learned_concurrency(
+    app_handle: tauri::AppHandle,
+) -> Result<HashMap<String, concurrency_tuning::LearnedConcurrency>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(concurrency_tuning::list(&app_dir))
+}
+
+/// Forget a target's optimum (key from get_learned_concurrency), its next
+/// adaptive sync starts over at 2 connections
+#[tauri::command]
+fn forget_learned_concurrency(target: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    concurrency_tuning::forget(&app_dir, &target)
+}
+
+// ============================================
+// Event Schema Commands
+// ============================================
+
+/// Names, payloads and kinds of every emitted event, with the schema version
+#[tauri::command]
+fn get_event_schema() -> events::EventSchema {
+    events::schema()
+}
+
+// ============================================
+// SSH Auth Commands
+// ============================================
+
+/// Answer a `ssh-auth-prompt` event, `answers: null` cancels the connection
+#[tauri::command]
+fn ssh_auth_answer(request_id: String, answers: Option<Vec<String>>) -> Result<(), String> {
+    ssh_auth::answer(&request_id, answers)
+}
+
+// ============================================
+// Deploy Guard Commands
+// ============================================
+
+#[tauri::command]
+fn get_deploy_guard(project_id: String, app_handle: tauri::AppHandle) -> Result<deploy_guard::DeployGuard, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(deploy_guard::get(&app_dir, &project_id))
+}
+
+/// Production targets and frozen projects
+#[tauri::command]
+fn get_deploy_guards(app_handle: tauri::AppHandle) -> Result<Vec<deploy_guard::DeployGuard>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(deploy_guard::list(&app_dir))
 }
 
 #[tauri::command]
-fn stop_sync_scheduler() -> Result<(), String> {
-    scheduler::stop_scheduler();
-    Ok(())
+fn set_production_target(
+    project_id: String,
+    production: bool,
+    confirmation_phrase: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<deploy_guard::DeployGuard, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    deploy_guard::set_production(&app_dir, &project_id, production, confirmation_phrase)
 }
 
+/// Block every sync of a project until `until` (RFC 3339)
 #[tauri::command]
-fn set_sync_schedule(schedule: scheduler::SyncSchedule) -> Result<scheduler::SyncSchedule, String> {
-    scheduler::set_schedule(schedule)
+fn lock_deploys(
+    project_id: String,
+    until: String,
+    reason: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<deploy_guard::DeployGuard, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    deploy_guard::lock(&app_dir, &project_id, &until, reason)
 }
 
 #[tauri::command]
-fn remove_sync_schedule(project_id: String) -> Result<(), String> {
-    scheduler::remove_schedule(&project_id)
+fn unlock_deploys(project_id: String, app_handle: tauri::AppHandle) -> Result<deploy_guard::DeployGuard, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    deploy_guard::unlock(&app_dir, &project_id)
 }
 
+// ============================================
+// Automation Pause Commands
+// ============================================
+
+/// Pause periods not over yet, global and per project
 #[tauri::command]
-fn get_sync_schedule(project_id: String) -> Result<Option<scheduler::SyncSchedule>, String> {
-    Ok(scheduler::get_schedule(&project_id))
+fn list_automation_pauses(app_handle: tauri::AppHandle) -> Result<Vec<automation_pause::PausePeriod>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(automation_pause::list(&app_dir))
 }
 
+/// Pause scheduled, webhook and automatic syncs from `start` to `end` (RFC 3339); all projects without `project_id`
 #[tauri::command]
-fn get_all_sync_schedules() -> Result<Vec<scheduler::SyncSchedule>, String> {
-    Ok(scheduler::get_all_schedules())
+fn add_automation_pause(
+    project_id: Option<String>,
+    start: String,
+    end: String,
+    reason: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<automation_pause::PausePeriod, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    automation_pause::add(&app_dir, project_id, &start, &end, reason)
 }
 
 #[tauri::command]
-fn set_schedule_enabled(project_id: String, enabled: bool) -> Result<(), String> {
-    scheduler::set_schedule_enabled(&project_id, enabled)
+fn remove_automation_pause(id: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    automation_pause::remove(&app_dir, &id)
 }
 
+/// Period pausing the project now, if any
 #[tauri::command]
-fn update_schedule_result(project_id: String, result: scheduler::ScheduleResult) -> Result<(), String> {
-    scheduler::update_schedule_result(&project_id, result);
-    Ok(())
+fn is_automation_paused(
+    project_id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<Option<automation_pause::PausePeriod>, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(automation_pause::active_pause(&app_dir, &project_id))
 }
 
 // ============================================
-// Transfer Resume Commands
+// Remote Monitor Commands
 // ============================================
 
+/// Poll the target every `interval_minutes` for changes made outside La Forge
 #[tauri::command]
-fn get_transfer_session(
+fn start_remote_monitor(
     project_id: String,
+    config: SFTPConfig,
+    interval_minutes: Option<u32>,
     app_handle: tauri::AppHandle,
-) -> Result<Option<transfer_resume::TransferSession>, String> {
-    let app_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or("Could not get app data directory")?;
+) -> Result<remote_monitor::MonitorStatus, String> {
+    remote_monitor::start(app_handle, &project_id, config, interval_minutes)
+}
 
-    let store = transfer_resume::load_sessions(&app_dir)?;
-    Ok(store.get_session(&project_id).cloned())
+#[tauri::command]
+fn stop_remote_monitor(project_id: String) -> Result<(), String> {
+    remote_monitor::stop(&project_id)
 }
 
 #[tauri::command]
-fn create_transfer_session(
-    project_id: String,
-    files: Vec<(String, String, String, u64)>, // (path, local_path, remote_path, size)
-    app_handle: tauri::AppHandle,
-) -> Result<String, String> {
-    let app_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or("Could not get app data directory")?;
+fn get_remote_monitors() -> Vec<remote_monitor::MonitorStatus> {
+    remote_monitor::statuses()
+}
 
-    let mut store = transfer_resume::load_sessions(&app_dir)?;
-    let session_id = store.create_session(&project_id);
+// ============================================
+// History Search Commands
+// ============================================
 
-    if let Some(session) = store.sessions.get_mut(&session_id) {
-        for (path, local_path, remote_path, size) in files {
-            session.add_file(&path, &local_path, &remote_path, size);
-        }
-    }
+/// Search sync runs, snapshot messages, captured page titles and activity of all projects
+#[tauri::command]
+fn search_history(
+    projects: Vec<history_search::SearchProject>,
+    query: String,
+    options: Option<history_search::SearchOptions>,
+    app_handle: tauri::AppHandle,
+) -> Result<history_search::SearchResults, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    history_search::search(&app_dir, &projects, &query, &options.unwrap_or_default())
+}
 
-    transfer_resume::save_sessions(&app_dir, &store)?;
-    Ok(session_id)
+// ============================================
+// Webhook Receiver Commands
+// ============================================
+
+#[tauri::command]
+fn get_webhook_receiver_status() -> webhook_receiver::WebhookStatus {
+    webhook_receiver::status()
 }
 
+/// Save the receiver settings and (re)start or stop the listener (requests on "webhook-deploy")
 #[tauri::command]
-fn update_transfer_progress(
-    session_id: String,
-    file_path: String,
-    transferred: u64,
+fn configure_webhook_receiver(
+    settings: webhook_receiver::WebhookSettings,
     app_handle: tauri::AppHandle,
-) -> Result<(), String> {
-    let app_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or("Could not get app data directory")?;
+) -> Result<webhook_receiver::WebhookStatus, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    webhook_receiver::configure(app_handle, &app_dir, settings)
+}
 
-    let mut store = transfer_resume::load_sessions(&app_dir)?;
+// ============================================
+// Offline Sync Queue Commands
+// ============================================
 
-    if let Some(session) = store.sessions.get_mut(&session_id) {
-        session.update_progress(&file_path, transferred);
-        transfer_resume::save_sessions(&app_dir, &store)?;
-    }
+/// Queue a sync until its server is reachable again (progress on "sync-queue-status")
+#[tauri::command]
+fn queue_offline_sync(
+    request: offline_queue::QueueSyncRequest,
+    app_handle: tauri::AppHandle,
+) -> Result<offline_queue::QueuedSync, String> {
+    readonly_mode::ensure_writable("la synchronisation")?;
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    offline_queue::enqueue(app_handle, &app_dir, request)
+}
 
-    Ok(())
+#[tauri::command]
+fn get_offline_sync_queue() -> Vec<offline_queue::QueuedSync> {
+    offline_queue::list()
 }
 
 #[tauri::command]
-fn complete_transfer_file(
-    session_id: String,
-    file_path: String,
-    app_handle: tauri::AppHandle,
-) -> Result<(), String> {
-    let app_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or("Could not get app data directory")?;
+fn remove_queued_sync(id: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    offline_queue::remove(&app_handle, &id)
+}
 
-    let mut store = transfer_resume::load_sessions(&app_dir)?;
+/// Called by the frontend after running a ready queued sync
+#[tauri::command]
+fn complete_queued_sync(id: String, success: bool, app_handle: tauri::AppHandle) -> Result<(), String> {
+    offline_queue::complete(app_handle, &id, success)
+}
 
-    if let Some(session) = store.sessions.get_mut(&session_id) {
-        session.mark_completed(&file_path);
-        transfer_resume::save_sessions(&app_dir, &store)?;
-    }
+// ============================================
+// Storage Commands
+// ============================================
 
-    Ok(())
+#[tauri::command]
+fn get_storage_overview(
+    app_handle: tauri::AppHandle,
+    projects: Vec<storage::ProjectRef>,
+) -> Result<storage::StorageOverview, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    let log_dir = app_handle.path_resolver().app_log_dir();
+    Ok(storage::overview(&app_dir, log_dir.as_deref(), &projects))
 }
 
+/// Prune the selected storage categories older than `options.older_than_days`
 #[tauri::command]
-fn complete_transfer_session(
-    session_id: String,
+fn cleanup_storage(
     app_handle: tauri::AppHandle,
-) -> Result<(), String> {
-    let app_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or("Could not get app data directory")?;
+    projects: Vec<storage::ProjectRef>,
+    options: storage::CleanupOptions,
+) -> Result<storage::CleanupReport, String> {
+    readonly_mode::ensure_writable("le nettoyage du stockage")?;
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    let log_dir = app_handle.path_resolver().app_log_dir();
+    let simulate = simulation::is_enabled();
+    let report = storage::cleanup(&app_dir, log_dir.as_deref(), &projects, &options, simulate)?;
+    if simulate {
+        emit_simulation(&app_handle, &simulation::plan_storage_cleanup(&report));
+    }
+    Ok(report)
+}
 
-    let mut store = transfer_resume::load_sessions(&app_dir)?;
-    store.complete_session(&session_id);
-    store.cleanup_old_sessions(24); // Clean up sessions older than 24 hours
-    transfer_resume::save_sessions(&app_dir, &store)?;
+// ============================================
+// App Update Commands
+// ============================================
 
-    Ok(())
+#[tauri::command]
+fn get_update_status(app_handle: tauri::AppHandle) -> Result<app_update::UpdateStatus, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(app_update::status(&app_dir))
 }
 
+/// Switch between the "stable" and "beta" update channels
 #[tauri::command]
-fn get_resumable_files(
-    project_id: String,
-    app_handle: tauri::AppHandle,
-) -> Result<Vec<transfer_resume::FileTransferState>, String> {
-    let app_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or("Could not get app data directory")?;
+fn set_update_channel(app_handle: tauri::AppHandle, channel: String) -> Result<app_update::UpdateSettings, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    app_update::set_channel(&app_dir, &channel)
+}
 
-    let store = transfer_resume::load_sessions(&app_dir)?;
+#[tauri::command]
+async fn check_for_update(app_handle: tauri::AppHandle) -> Result<app_update::UpdateInfo, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    app_update::check(&app_handle, &app_dir).await
+}
 
-    Ok(store
-        .get_session(&project_id)
-        .map(|s| s.get_resumable_files().into_iter().cloned().collect())
-        .unwrap_or_default())
+/// Download and install the update; it runs after `restart_to_update`
+#[tauri::command]
+async fn install_update(app_handle: tauri::AppHandle) -> Result<String, String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    app_update::download_and_install(&app_handle, &app_dir).await
+}
+
+#[tauri::command]
+fn restart_to_update(app_handle: tauri::AppHandle) -> Result<(), String> {
+    app_update::restart(&app_handle)
 }
 
 // ============================================
-// Delta Sync Commands
+// Data Location Commands
 // ============================================
 
 #[tauri::command]
-fn analyze_delta_sync(
-    project_id: String,
-    local_path: String,
-    app_handle: tauri::AppHandle,
-) -> Result<Vec<delta_sync::FileDelta>, String> {
-    let app_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or("Could not get app data directory")?;
-
-    let cache = delta_sync::load_cache(&app_dir, &project_id)?;
-    delta_sync::analyze_delta_sync(&local_path, &cache)
+fn get_data_location(app_handle: tauri::AppHandle) -> Result<data_location::DataLocation, String> {
+    data_location::get_location(&app_handle)
 }
 
+/// Move La Forge's data to `target` (back to the default directory when None)
 #[tauri::command]
-fn get_delta_transfer_stats(
-    deltas: Vec<delta_sync::FileDelta>,
-) -> Result<delta_sync::DeltaTransferStats, String> {
-    Ok(delta_sync::calculate_transfer_stats(&deltas))
+async fn move_data_location(
+    target: Option<String>,
+    window: tauri::Window,
+) -> Result<data_location::DataMigrationResult, String> {
+    let app_handle = window.app_handle();
+    tokio::task::spawn_blocking(move || {
+        data_location::migrate(&app_handle, target.as_deref(), |progress| {
+            let _ = window.emit(events::DATA_LOCATION_PROGRESS, &progress);
+        })
+    })
+    .await
+    .map_err(|e| format!("Data migration task failed: {}", e))?
 }
 
-#[tauri::command]
-fn update_delta_cache_after_sync(
-    project_id: String,
-    local_path: String,
-    synced_files: Vec<String>,
-    app_handle: tauri::AppHandle,
-) -> Result<(), String> {
-    let app_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or("Could not get app data directory")?;
+// ============================================
+// Menu Commands
+// ============================================
 
-    delta_sync::update_cache_after_sync(&app_dir, &project_id, &local_path, &synced_files)?;
-    Ok(())
+#[tauri::command]
+fn get_menu_config(app_handle: tauri::AppHandle) -> Result<menu_config::MenuConfig, String> {
+    let default_dir = data_location::default_dir(&app_handle).ok_or("Could not get app data directory")?;
+    Ok(menu_config::load(&default_dir))
 }
 
+/// Save the menu customization; `restart_required` tells whether the menu changes at next launch only
 #[tauri::command]
-fn generate_file_signature(
-    file_path: String,
-    relative_path: String,
-) -> Result<delta_sync::FileSignature, String> {
-    let path = Path::new(&file_path);
-    delta_sync::generate_file_signature(path, &relative_path)
+fn set_menu_config(
+    config: menu_config::MenuConfig,
+    app_handle: tauri::AppHandle,
+) -> Result<menu_config::MenuConfigApplied, String> {
+    let default_dir = data_location::default_dir(&app_handle).ok_or("Could not get app data directory")?;
+    menu_config::apply(&app_handle, &default_dir, config)
 }
 
+// ============================================
+// App Backup Commands
+// ============================================
+
+/// Bundle La Forge's configuration into one file; credentials and keyring secrets are included, encrypted, only with a passphrase
 #[tauri::command]
-fn get_delta_cache_info(
-    project_id: String,
+fn export_app_backup(
+    output_path: String,
+    passphrase: Option<String>,
     app_handle: tauri::AppHandle,
-) -> Result<delta_sync::SignatureCache, String> {
-    let app_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or("Could not get app data directory")?;
-
-    delta_sync::load_cache(&app_dir, &project_id)
+) -> Result<app_backup::BackupSummary, String> {
+    let default_dir = data_location::default_dir(&app_handle).ok_or("Could not get app data directory")?;
+    let data_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    let version = app_handle.package_info().version.to_string();
+    app_backup::export_backup(&default_dir, &data_dir, &output_path, passphrase.as_deref(), &version, &app_backup::OsKeyring)
 }
 
 #[tauri::command]
-fn clear_delta_cache(
-    project_id: String,
+fn import_app_backup(
+    input_path: String,
+    passphrase: Option<String>,
     app_handle: tauri::AppHandle,
-) -> Result<(), String> {
-    let app_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or("Could not get app data directory")?;
-
-    let cache = delta_sync::SignatureCache::new(&project_id);
-    delta_sync::save_cache(&app_dir, &cache)
+) -> Result<app_backup::RestoreSummary, String> {
+    readonly_mode::ensure_writable("la restauration de sauvegarde")?;
+    let default_dir = data_location::default_dir(&app_handle).ok_or("Could not get app data directory")?;
+    let data_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    let version = app_handle.package_info().version.to_string();
+    app_backup::import_backup(&default_dir, &data_dir, &input_path, passphrase.as_deref(), &version, &app_backup::OsKeyring)
 }
 
 // ============================================
-// Scrape Cache Commands
+// Readonly Mode Commands
 // ============================================
 
 #[tauri::command]
-fn get_scrape_cache(project_path: String) -> Result<Option<scrape_cache::ScrapeCache>, String> {
-    Ok(scrape_cache::ScrapeCache::load(&project_path))
+fn get_readonly_mode() -> bool {
+    readonly_mode::is_enabled()
 }
 
+/// While enabled, syncs, deletions, restores and remote operations are refused
 #[tauri::command]
-fn get_scrape_cache_stats(project_path: String) -> Result<Option<scrape_cache::CacheStats>, String> {
-    Ok(scrape_cache::ScrapeCache::load(&project_path).map(|c| c.stats()))
+fn set_readonly_mode(enabled: bool, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let app_dir = data_location::app_data_dir(&app_handle).ok_or("Could not get app data directory")?;
+    readonly_mode::set_enabled(&app_dir, enabled)
+}
+
+// ============================================
+// Simulation Commands
+// ============================================
+
+/// Destructive operation to describe without running it
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum SimulatedOperation {
+    Sync {
+        local_path: String,
+        config: SFTPConfig,
+        options: Option<SyncOptions>,
+    },
+    /// Snapshots pruned when one more is created, or with a lower limit
+    SnapshotPrune {
+        project_id: String,
+        max_snapshots: Option<usize>,
+    },
+    TransferSessionCleanup {
+        hours: Option<i64>,
+    },
+    DeleteTransferSession {
+        session_id: String,
+    },
+    ClearDeltaCache {
+        project_id: String,
+    },
+    ClearScrapeCache {
+        project_path: String,
+    },
+    DeletePath {
+        path: String,
+        #[serde(default)]
+        recursive: bool,
+    },
+}
+
+fn emit_simulation(app_handle: &tauri::AppHandle, report: &simulation::SimulationReport) {
+    println!(
+        "[Simulation] {}: {} actions, {} bytes",
+        report.operation,
+        report.actions.len(),
+        report.total_bytes
+    );
+    let _ = app_handle.emit_all(events::SIMULATION_REPORT, report);
 }
 
 #[tauri::command]
-fn clear_scrape_cache(project_path: String) -> Result<(), String> {
-    if let Some(mut cache) = scrape_cache::ScrapeCache::load(&project_path) {
-        cache.clear();
-        cache.save(&project_path)?;
-    }
-    Ok(())
+fn get_simulation_mode() -> bool {
+    simulation::is_enabled()
 }
 
+/// While enabled, destructive commands report what they would do instead of doing it
 #[tauri::command]
-fn is_url_cached(project_path: String, url: String) -> Result<bool, String> {
-    Ok(scrape_cache::ScrapeCache::load(&project_path)
-        .map(|c| c.is_cached(&url))
-        .unwrap_or(false))
+fn set_simulation_mode(enabled: bool) {
+    simulation::set_enabled(enabled);
 }
 
+/// Describe what a destructive operation would do, whatever the simulation mode
 #[tauri::command]
-fn set_scrape_cache_ttl(project_path: String, days: u64) -> Result<(), String> {
-    if let Some(mut cache) = scrape_cache::ScrapeCache::load(&project_path) {
-        cache.set_ttl_days(days);
-        cache.save(&project_path)?;
-    }
-    Ok(())
+async fn simulate_operation(
+    operation: SimulatedOperation,
+    app_handle: tauri::AppHandle,
+) -> Result<simulation::SimulationReport, String> {
+    let app_dir = data_location::app_data_dir(&app_handle)
+        .ok_or("Could not get app data directory")?;
+
+    tokio::task::spawn_blocking(move || match operation {
+        SimulatedOperation::Sync { local_path, config, options } => {
+            let options = options.unwrap_or_default();
+            let tolerance = options.mtime_tolerance_secs.unwrap_or(mtime_diff::DEFAULT_TOLERANCE_SECS);
+            let diffs = compute_diff(&local_path, &config, &mut RemoteScanContext::silent(), Some(tolerance))?;
+            let diffs = match options.max_file_size {
+                Some(max_size) => skip_oversized_files(diffs, max_size, &options.large_file_overrides),
+                None => diffs,
+            };
+            Ok(simulation::plan_sync(&diffs))
+        }
+        SimulatedOperation::SnapshotPrune { project_id, max_snapshots } => {
+            let history = version_history::load_history(&app_dir, &project_id)?;
+            Ok(match max_snapshots {
+                Some(max) => simulation::plan_snapshot_prune(&history, max, 0),
+                None => simulation::plan_snapshot_prune(&history, history.max_snapshots, 1),
+            })
+        }
+        SimulatedOperation::TransferSessionCleanup { hours } => {
+            let store = transfer_resume::load_sessions(&app_dir)?;
+            let hours = hours.unwrap_or(store.cleanup_after_hours);
+            Ok(simulation::plan_session_cleanup(&store, hours))
+        }
+        SimulatedOperation::DeleteTransferSession { session_id } => {
+            let store = transfer_resume::load_sessions(&app_dir)?;
+            Ok(simulation::plan_delete_session(&store, &session_id))
+        }
+        SimulatedOperation::ClearDeltaCache { project_id } => {
+            let cache = delta_sync::load_cache(&app_dir, &project_id)?;
+            Ok(simulation::plan_clear_delta_cache(&cache))
+        }
+        SimulatedOperation::ClearScrapeCache { project_path } => {
+            let cache = scrape_cache::ScrapeCache::load(&project_path);
+            Ok(simulation::plan_clear_scrape_cache(cache.as_ref()))
+        }
+        SimulatedOperation::DeletePath { path, recursive } => Ok(simulation::plan_delete_path(&path, recursive)),
+    })
+    .await
+    .map_err(|e| format!("Simulation task failed: {}", e))?
 }
 
 // ============================================
@@ -2192,6 +5197,8 @@ fn set_scrape_cache_ttl(project_path: String, days: u64) -> Result<(), String> {
 struct SyncConfig {
     parallel_enabled: bool,
     parallel_connections: usize,
+    #[serde(default)]
+    adaptive_connections: bool,
     auto_snapshot: bool,
 }
 
@@ -2199,6 +5206,7 @@ static SYNC_CONFIG: Lazy<Mutex<SyncConfig>> = Lazy::new(|| {
     Mutex::new(SyncConfig {
         parallel_enabled: true,
         parallel_connections: 4,
+        adaptive_connections: false,
         auto_snapshot: false,
     })
 });
@@ -2290,42 +5298,37 @@ fn set_autostart_enabled(enabled: bool) -> Result<(), String> {
 // Menu Configuration (French labels)
 // ============================================
 
-fn create_menu() -> Menu {
+fn create_menu(config: &menu_config::MenuConfig) -> Menu {
+    use menu_config::MenuBuilder;
+
     // Menu La Forge (Application)
     let app_menu = Submenu::new(
         "La Forge",
-        Menu::new()
-            .add_item(CustomMenuItem::new("about", "À propos de La Forge"))
-            .add_native_item(MenuItem::Separator)
-            .add_item(CustomMenuItem::new("check_updates", "Vérifier les mises à jour..."))
-            .add_native_item(MenuItem::Separator)
-            .add_item(
-                CustomMenuItem::new("preferences", "Préférences...")
-                    .accelerator("CmdOrCtrl+,"),
-            )
-            .add_native_item(MenuItem::Separator)
-            .add_native_item(MenuItem::Services)
-            .add_native_item(MenuItem::Separator)
-            .add_native_item(MenuItem::Hide)
-            .add_native_item(MenuItem::HideOthers)
-            .add_native_item(MenuItem::ShowAll)
-            .add_native_item(MenuItem::Separator)
-            .add_native_item(MenuItem::Quit),
+        MenuBuilder::new(config)
+            .item("about")
+            .separator()
+            .item("check_updates")
+            .separator()
+            .item("preferences")
+            .separator()
+            .native(MenuItem::Services)
+            .separator()
+            .native(MenuItem::Hide)
+            .native(MenuItem::HideOthers)
+            .native(MenuItem::ShowAll)
+            .separator()
+            .native(MenuItem::Quit)
+            .build(),
     );
 
     // Menu Fichier
     let file_menu = Submenu::new(
         "Fichier",
-        Menu::new()
-            .add_item(
-                CustomMenuItem::new("new_project", "Nouveau projet...")
-                    .accelerator("CmdOrCtrl+N"),
-            )
-            .add_native_item(MenuItem::Separator)
-            .add_item(
-                CustomMenuItem::new("close_window", "Fermer la fenêtre")
-                    .accelerator("CmdOrCtrl+W"),
-            ),
+        MenuBuilder::new(config)
+            .item("new_project")
+            .separator()
+            .item("close_window")
+            .build(),
     );
 
     // Menu Édition
@@ -2344,35 +5347,28 @@ fn create_menu() -> Menu {
     // Menu Affichage
     let view_menu = Submenu::new(
         "Affichage",
-        Menu::new()
-            .add_item(
-                CustomMenuItem::new("refresh", "Actualiser")
-                    .accelerator("CmdOrCtrl+R"),
-            )
-            .add_native_item(MenuItem::Separator)
-            .add_native_item(MenuItem::EnterFullScreen),
+        MenuBuilder::new(config)
+            .item("refresh")
+            .separator()
+            .native(MenuItem::EnterFullScreen)
+            .build(),
     );
 
     // Menu Projet
-    let project_menu = Submenu::new(
-        "Projet",
-        Menu::new()
-            .add_item(
-                CustomMenuItem::new("open_in_finder", "Ouvrir dans le Finder")
-                    .accelerator("CmdOrCtrl+Shift+O"),
-            )
-            .add_item(
-                CustomMenuItem::new("open_in_browser", "Ouvrir le site")
-                    .accelerator("CmdOrCtrl+Shift+B"),
-            )
-            .add_native_item(MenuItem::Separator)
-            .add_item(
-                CustomMenuItem::new("sync_project", "Synchroniser")
-                    .accelerator("CmdOrCtrl+Shift+S"),
-            )
+    let mut project_menu = MenuBuilder::new(config)
+        .item("open_in_finder")
+        .item("open_in_browser")
+        .separator()
+        .item("sync_project")
+        .separator()
+        .item("scrape_site")
+        .build();
+    if !config.quick_actions.is_empty() {
+        project_menu = project_menu
             .add_native_item(MenuItem::Separator)
-            .add_item(CustomMenuItem::new("scrape_site", "Scraper le site...")),
-    );
+            .add_submenu(Submenu::new("Actions rapides", MenuBuilder::new(config).quick_actions().build()));
+    }
+    let project_menu = Submenu::new("Projet", project_menu);
 
     // Menu Fenêtre
     let window_menu = Submenu::new(
@@ -2387,9 +5383,10 @@ fn create_menu() -> Menu {
     // Menu Aide
     let help_menu = Submenu::new(
         "Aide",
-        Menu::new()
-            .add_item(CustomMenuItem::new("documentation", "Documentation"))
-            .add_item(CustomMenuItem::new("report_issue", "Signaler un problème...")),
+        MenuBuilder::new(config)
+            .item("documentation")
+            .item("report_issue")
+            .build(),
     );
 
     Menu::new()
@@ -2408,36 +5405,44 @@ fn handle_menu_event(event: WindowMenuEvent) {
     match event.menu_item_id() {
         "about" => {
             // Emit event to frontend to show about dialog
-            let _ = window.emit("menu-about", ());
+            let _ = window.emit(events::MENU_ABOUT, ());
         }
         "check_updates" => {
-            // Emit event to frontend to check for updates
-            let _ = window.emit("menu-check-updates", ());
+            // Check the update channel, then let the frontend show the result
+            let window = window.clone();
+            tauri::async_runtime::spawn(async move {
+                let app_handle = window.app_handle();
+                let result = match data_location::app_data_dir(&app_handle) {
+                    Some(app_dir) => app_update::check(&app_handle, &app_dir).await,
+                    None => Err("Could not get app data directory".to_string()),
+                };
+                let _ = window.emit(events::MENU_CHECK_UPDATES, result);
+            });
         }
         "preferences" => {
             // Navigate to settings
-            let _ = window.emit("menu-preferences", ());
+            let _ = window.emit(events::MENU_PREFERENCES, ());
         }
         "new_project" => {
-            let _ = window.emit("menu-new-project", ());
+            let _ = window.emit(events::MENU_NEW_PROJECT, ());
         }
         "close_window" => {
             let _ = window.close();
         }
         "refresh" => {
-            let _ = window.emit("menu-refresh", ());
+            let _ = window.emit(events::MENU_REFRESH, ());
         }
         "open_in_finder" => {
-            let _ = window.emit("menu-open-finder", ());
+            let _ = window.emit(events::MENU_OPEN_FINDER, ());
         }
         "open_in_browser" => {
-            let _ = window.emit("menu-open-browser", ());
+            let _ = window.emit(events::MENU_OPEN_BROWSER, ());
         }
         "sync_project" => {
-            let _ = window.emit("menu-sync", ());
+            let _ = window.emit(events::MENU_SYNC, ());
         }
         "scrape_site" => {
-            let _ = window.emit("menu-scrape", ());
+            let _ = window.emit(events::MENU_SCRAPE, ());
         }
         "documentation" => {
             let _ = Command::new("open")
@@ -2449,19 +5454,71 @@ fn handle_menu_event(event: WindowMenuEvent) {
                 .arg("https://github.com/anthropics/forge/issues")
                 .spawn();
         }
+        id if id.starts_with(menu_config::QUICK_ACTION_PREFIX) => {
+            if let Some(quick_action) = menu_config::quick_action(id) {
+                let _ = window.emit(events::MENU_QUICK_ACTION, quick_action);
+            }
+        }
         _ => {}
     }
 }
 
 fn main() {
+    // The menu is built before any window exists, from the config in the default data directory
+    let context = tauri::generate_context!();
+    let menu = tauri::api::path::app_data_dir(context.config())
+        .map(|dir| menu_config::load(&dir))
+        .unwrap_or_default();
+    menu_config::set_active(&menu);
+
     tauri::Builder::default()
-        .menu(create_menu())
+        .menu(create_menu(&menu))
         .on_menu_event(handle_menu_event)
         .plugin(tauri_plugin_store::Builder::default().build())
         .manage(Mutex::new(FileWatcherManager::new()))
         .system_tray(tray::create_system_tray())
         .on_system_tray_event(tray::handle_tray_event)
+        .setup(|app| {
+            // Resolve the data directory now: SSH host key checks read it without an AppHandle,
+            // and the readonly switch has to be on before any command runs
+            if let Some(app_dir) = data_location::app_data_dir(&app.handle()) {
+                readonly_mode::restore(&app_dir);
+                notification_quiet::restore(&app_dir);
+            }
+
+            watchdog::start(app.handle());
+            std::thread::spawn(|| version_history::clean_inspection_folders(version_history::INSPECTION_MAX_AGE));
+            ssh_auth::set_app_handle(app.handle());
+
+            // Look for syncs interrupted by a crash or a quit, once the window listens
+            let app_handle = app.handle();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_secs(3));
+                let app_dir = match data_location::app_data_dir(&app_handle) {
+                    Some(dir) => dir,
+                    None => return,
+                };
+                offline_queue::restore(app_handle.clone(), &app_dir);
+                webhook_receiver::restore(app_handle.clone(), &app_dir);
+                match transfer_resume::detect_interrupted_syncs(&app_dir) {
+                    Ok(interrupted) if !interrupted.is_empty() => {
+                        println!("[TransferResume] {} interrupted sync(s) can be resumed", interrupted.len());
+                        let _ = app_handle.emit_all(events::INTERRUPTED_SYNCS, &interrupted);
+                    }
+                    Ok(_) => {}
+                    Err(e) => println!("[TransferResume] Failed to check stored sessions: {}", e),
+                }
+            });
+            Ok(())
+        })
         .on_window_event(|event| {
+            // Monitor windows really close
+            if event.window().label().starts_with(monitor_windows::LABEL_PREFIX) {
+                if let tauri::WindowEvent::Destroyed = event.event() {
+                    monitor_windows::forget(event.window().label());
+                }
+                return;
+            }
             // Hide window instead of closing when red button is clicked
             if let tauri::WindowEvent::CloseRequested { api, .. } = event.event() {
                 // Prevent the window from being destroyed
@@ -2474,10 +5531,162 @@ fn main() {
             open_in_finder,
             open_in_editor,
             sftp_test_connection,
+            sftp_detect_protocol,
             sftp_list_files,
             sftp_get_diff,
+            get_diff_file_types,
+            export_diff_report,
             sftp_sync,
             sftp_cancel_sync,
+            sync_in_progress,
+            get_rsync_version,
+            sftp_pull,
+            ftp_pull,
+            plan_two_way_sync,
+            two_way_sync,
+            resolve_conflicts,
+            get_active_syncs,
+            get_sync_queue,
+            get_sync_history,
+            clear_sync_history,
+            get_deploy_manifest,
+            verify_deploy_manifest,
+            // Remote console commands
+            ssh_exec_command,
+            get_remote_command_presets,
+            set_remote_command_presets,
+            // Database dump commands
+            db_dump_run,
+            list_db_dumps,
+            // Site backup commands
+            backup_remote_site,
+            list_site_backups,
+            // Remote trash commands
+            list_remote_trash,
+            restore_remote_trash,
+            // Remote rules commands
+            list_rule_recipes,
+            read_remote_rules,
+            validate_remote_rules,
+            apply_rule_recipe,
+            deploy_remote_rules,
+            list_remote_rules_versions,
+            read_remote_rules_version,
+            // Connection profile commands
+            list_connection_profiles,
+            save_connection_profile,
+            delete_connection_profile,
+            set_connection_profile_password,
+            resolve_connection_profile,
+            // Maintenance mode commands
+            get_maintenance_mode,
+            enable_maintenance_mode,
+            disable_maintenance_mode,
+            // Project env commands
+            list_project_env,
+            set_project_env,
+            remove_project_env,
+            substitute_project_vars,
+            // File provenance commands
+            get_file_provenance,
+            list_file_provenance,
+            // Permissions audit commands
+            sftp_audit_permissions,
+            // Sync presets commands
+            get_sync_presets,
+            detect_sync_preset,
+            analyze_folder,
+            import_deploy_configs,
+            // Site migration commands
+            migrate_site,
+            get_site_migration,
+            cancel_site_migration,
+            discard_site_migration,
+            // Transfer quota commands
+            get_transfer_quota,
+            set_transfer_quota,
+            get_transfer_usage,
+            check_transfer_quota,
+            // Notification commands
+            notify_project_event,
+            get_notification_quiet_rules,
+            set_notification_quiet_rules,
+            get_notification_quiet_status,
+            // Monitor window commands
+            open_monitor_window,
+            list_monitor_windows,
+            close_monitor_window,
+            // Onboarding commands
+            run_onboarding_probes,
+            // Activity feed commands
+            get_activity_feed,
+            record_activity,
+            clear_activity_feed,
+            // Known hosts commands
+            verify_host_key,
+            trust_host_key,
+            get_known_hosts,
+            forget_host_key,
+            // Remote listing cache commands
+            get_remote_listing_cache,
+            clear_remote_listing_cache,
+            // Concurrency tuning commands
+            get_learned_concurrency,
+            forget_learned_concurrency,
+            // Event schema commands
+            get_event_schema,
+            // SSH auth commands
+            ssh_auth_answer,
+            // Deploy guard commands
+            get_deploy_guard,
+            get_deploy_guards,
+            set_production_target,
+            lock_deploys,
+            unlock_deploys,
+            // Automation pause commands
+            list_automation_pauses,
+            add_automation_pause,
+            remove_automation_pause,
+            is_automation_paused,
+            // Remote monitor commands
+            start_remote_monitor,
+            stop_remote_monitor,
+            get_remote_monitors,
+            // History search commands
+            search_history,
+            // Webhook receiver commands
+            get_webhook_receiver_status,
+            configure_webhook_receiver,
+            // Offline sync queue commands
+            queue_offline_sync,
+            get_offline_sync_queue,
+            remove_queued_sync,
+            complete_queued_sync,
+            // Storage commands
+            get_storage_overview,
+            cleanup_storage,
+            // App update commands
+            get_update_status,
+            set_update_channel,
+            check_for_update,
+            install_update,
+            restart_to_update,
+            // Data location commands
+            get_data_location,
+            move_data_location,
+            // Menu commands
+            get_menu_config,
+            set_menu_config,
+            // App backup commands
+            export_app_backup,
+            import_app_backup,
+            // Readonly mode commands
+            get_readonly_mode,
+            set_readonly_mode,
+            // Simulation commands
+            get_simulation_mode,
+            set_simulation_mode,
+            simulate_operation,
             save_password,
             get_password,
             delete_password,
@@ -2501,7 +5710,21 @@ fn main() {
             scrape_website_with_events,
             scrape_full_site,
             scrape_full_site_with_events,
+            scrape_site_into_project,
+            list_scrape_captures,
+            convert_scrape_to_project,
             cancel_full_site_scrape,
+            refresh_scrape_capture,
+            generate_scrape_attribution,
+            watch_scrape_capture,
+            unwatch_scrape_capture,
+            // Scrape queue commands
+            enqueue_scrape_jobs,
+            get_scrape_queue,
+            cancel_scrape_job,
+            cancel_scrape_queue,
+            clear_scrape_queue,
+            get_scrape_queue_comparison,
             // System tray commands
             tray::tray_update_recent_projects,
             tray::tray_is_available,
@@ -2511,6 +5734,8 @@ fn main() {
             get_version_history,
             get_snapshot_details,
             restore_version,
+            inspect_snapshot_files,
+            clear_inspection_folders,
             compare_snapshots,
             // Scheduler commands
             start_sync_scheduler,
@@ -2521,6 +5746,11 @@ fn main() {
             get_all_sync_schedules,
             set_schedule_enabled,
             update_schedule_result,
+            set_scheduler_concurrency,
+            get_scheduler_queue,
+            // Watchdog commands
+            get_watchdog_report,
+            set_watchdog_threshold,
             // Sync configuration commands
             get_sync_config,
             set_sync_config,
@@ -2531,6 +5761,12 @@ fn main() {
             complete_transfer_file,
             complete_transfer_session,
             get_resumable_files,
+            repair_partial_upload,
+            get_interrupted_syncs,
+            list_transfer_sessions,
+            delete_transfer_session,
+            get_transfer_cleanup_hours,
+            set_transfer_cleanup_hours,
             // Delta sync commands
             analyze_delta_sync,
             get_delta_transfer_stats,
@@ -2538,16 +5774,47 @@ fn main() {
             generate_file_signature,
             get_delta_cache_info,
             clear_delta_cache,
+            warm_delta_cache,
+            cancel_delta_cache_warmup,
+            is_delta_cache_warming,
             // Scrape cache commands
             get_scrape_cache,
             get_scrape_cache_stats,
             clear_scrape_cache,
             is_url_cached,
             set_scrape_cache_ttl,
+            // Shared scrape cache commands
+            list_shared_scrape_cache,
+            get_project_shared_cache,
+            copy_shared_cache_into_project,
+            clear_shared_scrape_cache,
             // IDE monitor commands
             ide_monitor::check_ide_for_project,
-            ide_monitor::get_open_projects_for_ide
+            ide_monitor::get_open_projects_for_ide,
+            ide_monitor::get_recent_ide_projects,
+            // IDE activity commands
+            start_ide_activity_monitor,
+            stop_ide_activity_monitor,
+            set_ide_watched_projects,
+            set_running_timers,
+            start_idle_monitor,
+            stop_idle_monitor,
+            get_system_idle_seconds,
+            // Calendar export commands
+            export_calendar_ics,
+            // Content inventory export commands
+            export_content_inventory,
+            // Pomodoro commands
+            pomodoro_get_settings,
+            pomodoro_set_settings,
+            pomodoro_start,
+            pomodoro_pause,
+            pomodoro_resume,
+            pomodoro_skip,
+            pomodoro_stop,
+            pomodoro_status,
+            pomodoro_get_stats
         ])
-        .run(tauri::generate_context!())
+        .run(context)
         .expect("error while running tauri application");
 }
@@ -0,0 +1,195 @@
+//! Maintenance Mode Module
+//!
+//! Puts the remote site behind a maintenance page for the length of a large
+//! sync: the page is uploaded, the `maintenance` recipe of remote_rules is
+//! added to the rules file (`.htaccess` by default), and both are removed
+//! afterwards, whether the sync worked or not. The state is written before
+//! the server is touched, so a crash or a failed cleanup leaves something
+//! `disable` can finish later. The remote root is taken as the web root.
+
+use crate::remote_rules::{self, RecipeOptions};
+use crate::{project_env, state_file, SFTPConfig};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_RULES_FILE: &str = ".htaccess";
+pub const DEFAULT_PAGE_PATH: &str = "maintenance.html";
+
+const DEFAULT_PAGE: &str = r#"<!DOCTYPE html>
+<html lang="fr">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Maintenance en cours</title>
+<style>body{font-family:system-ui,sans-serif;display:flex;min-height:100vh;margin:0;align-items:center;justify-content:center;background:#f5f5f7;color:#1d1d1f}main{text-align:center;padding:2rem}</style>
+</head>
+<body>
+<main>
+<h1>Maintenance en cours</h1>
+<p>Le site est en cours de mise a jour et sera de retour dans quelques minutes.</p>
+</main>
+</body>
+</html>
+"#;
+
+/// What to put in place, from the sync options or the maintenance commands
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceOptions {
+    /// Rules file relative to the remote root (default ".htaccess", ".conf" for nginx)
+    pub rules_file: Option<String>,
+    /// Page relative to the remote root (default "maintenance.html")
+    pub page_path: Option<String>,
+    /// HTML of the page, `${NAME}` replaced by project variables; without it
+    /// the site's own page is kept, or a default one uploaded
+    pub page_html: Option<String>,
+    /// Addresses still served the site
+    #[serde(default)]
+    pub allowed_ips: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceState {
+    pub rules_file: String,
+    pub page_path: String,
+    /// The page didn't exist on the server and is removed afterwards
+    pub page_created: bool,
+    /// The rules file didn't exist on the server and is removed afterwards
+    pub rules_created: bool,
+    pub enabled_at: String,
+}
+
+fn state_path(app_data_dir: &Path, project_id: &str) -> PathBuf {
+    app_data_dir.join("maintenance").join(format!("{}.json", project_id))
+}
+
+/// Maintenance currently (or still, after a failed cleanup) on for the project
+pub fn state(app_data_dir: &Path, project_id: &str) -> Option<MaintenanceState> {
+    state_file::read_json(&state_path(app_data_dir, project_id)).ok().flatten()
+}
+
+fn remote_file(config: &SFTPConfig, file: &str) -> String {
+    format!("{}/{}", config.remote_path.trim_end_matches('/'), file)
+}
+
+/// Upload the page and add the maintenance rule
+pub fn enable(
+    app_data_dir: &Path,
+    project_id: &str,
+    config: &SFTPConfig,
+    options: &MaintenanceOptions,
+) -> Result<MaintenanceState, String> {
+    if let Some(current) = state(app_data_dir, project_id) {
+        return Err(format!("Le mode maintenance est deja actif depuis {}", current.enabled_at));
+    }
+    let rules_file = remote_rules::check_file(options.rules_file.as_deref().unwrap_or(DEFAULT_RULES_FILE))?;
+    let page_path = remote_rules::check_file(options.page_path.as_deref().unwrap_or(DEFAULT_PAGE_PATH))?;
+
+    let rules = remote_rules::read_remote(app_data_dir, project_id, config, &rules_file)?;
+    let existing_page = remote_rules::download(config, &remote_file(config, &page_path))?;
+    let state = MaintenanceState {
+        page_created: existing_page.is_none(),
+        rules_created: !rules.exists,
+        rules_file,
+        page_path,
+        enabled_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+    };
+    state_file::write_json(&state_path(app_data_dir, project_id), &state)?;
+
+    let applied = (|| {
+        if options.page_html.is_some() || existing_page.is_none() {
+            let page = state_path(app_data_dir, project_id).with_extension("html");
+            let html = match &options.page_html {
+                Some(html) => project_env::substitute(html, &project_env::resolve(app_data_dir, project_id)),
+                None => DEFAULT_PAGE.to_string(),
+            };
+            state_file::write_atomic(&page, html.as_bytes())?;
+            remote_rules::upload(config, &page, &state.page_path)?;
+        }
+        let recipe = RecipeOptions {
+            domain: None,
+            allowed_ips: options.allowed_ips.clone(),
+            maintenance_page: Some(format!("/{}", state.page_path)),
+        };
+        let content = remote_rules::apply_recipe(rules.flavor, &rules.content, "maintenance", &recipe, true)?;
+        remote_rules::deploy(app_data_dir, project_id, config, &state.rules_file, &content)
+    })();
+
+    if let Err(e) = applied {
+        // Nothing half-done stays on the server
+        if let Err(cleanup) = disable(app_data_dir, project_id, config, None) {
+            println!("[Maintenance] Cleanup after a failed enable failed: {}", cleanup);
+        }
+        return Err(format!("Mode maintenance impossible: {}", e));
+    }
+    println!("[Maintenance] Enabled on {} ({})", project_id, state.rules_file);
+    Ok(state)
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceCleanup {
+    /// False when maintenance wasn't on
+    pub was_enabled: bool,
+    /// The project's rules file replaced the remote one
+    pub project_rules_deployed: bool,
+    /// What couldn't be put back; maintenance itself is off
+    pub warnings: Vec<String>,
+}
+
+/// Remove the maintenance rule and the page it added. With `local_root`, the
+/// project's own rules file (held back by the sync) replaces the remote one,
+/// and a page the project has is left in place.
+pub fn disable(
+    app_data_dir: &Path,
+    project_id: &str,
+    config: &SFTPConfig,
+    local_root: Option<&Path>,
+) -> Result<MaintenanceCleanup, String> {
+    let state = match state(app_data_dir, project_id) {
+        Some(state) => state,
+        None => return Ok(MaintenanceCleanup::default()),
+    };
+    let mut cleanup = MaintenanceCleanup { was_enabled: true, ..Default::default() };
+    let flavor = remote_rules::flavor_of(&state.rules_file);
+    let without_rule = |content: &str| {
+        remote_rules::apply_recipe(flavor, content, "maintenance", &RecipeOptions::default(), false)
+    };
+
+    let local_rules = local_root.and_then(|root| fs::read_to_string(root.join(&state.rules_file)).ok());
+    if let Some(local) = &local_rules {
+        match without_rule(local).and_then(|content| {
+            remote_rules::deploy(app_data_dir, project_id, config, &state.rules_file, &content)
+        }) {
+            Ok(_) => cleanup.project_rules_deployed = true,
+            // Lifting maintenance comes first, the project's file can be deployed later
+            Err(e) => cleanup.warnings.push(format!("{} du projet non deploye: {}", state.rules_file, e)),
+        }
+    }
+    if !cleanup.project_rules_deployed {
+        if let Some(current) = remote_rules::download(config, &remote_file(config, &state.rules_file))? {
+            let content = without_rule(&current)?;
+            if content.trim().is_empty() && state.rules_created {
+                remote_rules::remove(config, &state.rules_file)?;
+            } else if content != current {
+                remote_rules::put_back(app_data_dir, project_id, config, &state.rules_file, &content)?;
+            }
+        }
+    }
+
+    let page_in_project = local_root.map(|root| root.join(&state.page_path).exists()).unwrap_or(false);
+    if state.page_created && !page_in_project {
+        if let Err(e) = remote_rules::remove(config, &state.page_path) {
+            cleanup.warnings.push(format!("Page {} non supprimee: {}", state.page_path, e));
+        }
+    }
+
+    let path = state_path(app_data_dir, project_id);
+    let _ = fs::remove_file(path.with_extension("html"));
+    let _ = fs::remove_file(state_file::backup_path(&path));
+    fs::remove_file(&path).map_err(|e| format!("Failed to clear maintenance state: {}", e))?;
+    println!("[Maintenance] Disabled on {}", project_id);
+    Ok(cleanup)
+}
@@ -0,0 +1,323 @@
+//! Menu Config Module
+//!
+//! User customization of the native menu: items can be hidden, their
+//! shortcuts replaced, and project quick actions ("sync project X",
+//! "open site Y") added under Projet > Actions rapides. The configuration
+//! lives next to the settings stores in the default app data directory, so
+//! it can be read before the app starts and the menu is built from it.
+//! Tauri can't swap the menu of an open window: renamed or retargeted quick
+//! actions are updated live, other changes apply at the next launch.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{CustomMenuItem, Manager, Menu, MenuItem};
+
+/// Config file, in the default app data directory
+pub const MENU_CONFIG_FILE: &str = "menu-config.json";
+
+/// Quick actions shown in the menu
+pub const MAX_QUICK_ACTIONS: usize = 9;
+
+/// Prefix of the quick action item ids, followed by their index
+pub const QUICK_ACTION_PREFIX: &str = "quick_action:";
+
+/// Actions a quick action can run on its project
+pub const QUICK_ACTION_KINDS: [&str; 4] = ["sync", "open_in_browser", "open_in_finder", "scrape"];
+
+/// Customizable items: id, title and default shortcut
+pub const MENU_ITEMS: [(&str, &str, Option<&str>); 12] = [
+    ("about", "À propos de La Forge", None),
+    ("check_updates", "Vérifier les mises à jour...", None),
+    ("preferences", "Préférences...", Some("CmdOrCtrl+,")),
+    ("new_project", "Nouveau projet...", Some("CmdOrCtrl+N")),
+    ("close_window", "Fermer la fenêtre", Some("CmdOrCtrl+W")),
+    ("refresh", "Actualiser", Some("CmdOrCtrl+R")),
+    ("open_in_finder", "Ouvrir dans le Finder", Some("CmdOrCtrl+Shift+O")),
+    ("open_in_browser", "Ouvrir le site", Some("CmdOrCtrl+Shift+B")),
+    ("sync_project", "Synchroniser", Some("CmdOrCtrl+Shift+S")),
+    ("scrape_site", "Scraper le site...", None),
+    ("documentation", "Documentation", None),
+    ("report_issue", "Signaler un problème...", None),
+];
+
+const MODIFIERS: [&str; 12] = [
+    "cmdorctrl", "commandorcontrol", "cmd", "command", "ctrl", "control", "alt", "option", "shift", "super", "meta", "altgr",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickAction {
+    pub label: String,
+    pub project_id: String,
+    /// One of QUICK_ACTION_KINDS
+    pub action: String,
+    pub accelerator: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MenuConfig {
+    /// Ids of the hidden items
+    #[serde(default)]
+    pub hidden: Vec<String>,
+    /// Shortcut per item id, an empty string removes the default one
+    #[serde(default)]
+    pub accelerators: HashMap<String, String>,
+    #[serde(default)]
+    pub quick_actions: Vec<QuickAction>,
+}
+
+/// Payload of "menu-quick-action"
+#[derive(Debug, Clone, Serialize)]
+pub struct QuickActionEvent {
+    pub project_id: String,
+    pub action: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MenuConfigApplied {
+    pub config: MenuConfig,
+    /// Items hidden or added and shortcuts only change when the menu is rebuilt at launch
+    pub restart_required: bool,
+}
+
+/// Config the running menu was built from
+static ACTIVE: Lazy<Mutex<MenuConfig>> = Lazy::new(|| Mutex::new(MenuConfig::default()));
+
+pub fn load(default_dir: &Path) -> MenuConfig {
+    match crate::state_file::read_json(&default_dir.join(MENU_CONFIG_FILE)) {
+        Ok(Some(config)) => config,
+        Ok(None) => MenuConfig::default(),
+        Err(e) => {
+            println!("[Menu] Ignoring unreadable menu config: {}", e);
+            MenuConfig::default()
+        }
+    }
+}
+
+fn is_valid_accelerator(accelerator: &str) -> bool {
+    let parts: Vec<&str> = accelerator.split('+').map(str::trim).collect();
+    match parts.split_last() {
+        Some((key, modifiers)) => {
+            !key.is_empty() && modifiers.iter().all(|m| MODIFIERS.contains(&m.to_lowercase().as_str()))
+        }
+        None => false,
+    }
+}
+
+/// Normalized form used to find two items sharing a shortcut
+fn accelerator_key(accelerator: &str) -> String {
+    let mut parts: Vec<String> = accelerator
+        .split('+')
+        .map(|p| match p.trim().to_lowercase().as_str() {
+            "commandorcontrol" | "cmd" | "command" | "ctrl" | "control" => "cmdorctrl".to_string(),
+            "option" => "alt".to_string(),
+            other => other.to_string(),
+        })
+        .collect();
+    parts.sort();
+    parts.join("+")
+}
+
+impl MenuConfig {
+    /// Shortcut of an item, with the user's override
+    pub fn accelerator(&self, id: &str) -> Option<String> {
+        match self.accelerators.get(id) {
+            Some(custom) if custom.is_empty() => None,
+            Some(custom) => Some(custom.clone()),
+            None => MENU_ITEMS
+                .iter()
+                .find(|(item, _, _)| *item == id)
+                .and_then(|(_, _, default)| default.map(String::from)),
+        }
+    }
+
+    pub fn is_hidden(&self, id: &str) -> bool {
+        self.hidden.iter().any(|hidden| hidden == id)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let known: HashSet<&str> = MENU_ITEMS.iter().map(|(id, _, _)| *id).collect();
+        for id in self.hidden.iter().chain(self.accelerators.keys()) {
+            if !known.contains(id.as_str()) {
+                return Err(format!("Element de menu inconnu: {}", id));
+            }
+        }
+        if self.quick_actions.len() > MAX_QUICK_ACTIONS {
+            return Err(format!("{} actions rapides au maximum", MAX_QUICK_ACTIONS));
+        }
+        for quick in &self.quick_actions {
+            if quick.label.trim().is_empty() || quick.project_id.is_empty() {
+                return Err("Une action rapide doit avoir un libelle et un projet".to_string());
+            }
+            if !QUICK_ACTION_KINDS.contains(&quick.action.as_str()) {
+                return Err(format!("Action rapide inconnue: {}", quick.action));
+            }
+        }
+
+        // Shortcuts of the visible items and quick actions must be valid and distinct
+        let mut used: HashMap<String, String> = HashMap::new();
+        let shortcuts = MENU_ITEMS
+            .iter()
+            .filter(|(id, _, _)| !self.is_hidden(id))
+            .filter_map(|(id, title, _)| self.accelerator(id).map(|a| (a, title.to_string())))
+            .chain(
+                self.quick_actions
+                    .iter()
+                    .filter_map(|q| q.accelerator.clone().filter(|a| !a.is_empty()).map(|a| (a, q.label.clone()))),
+            );
+        for (accelerator, title) in shortcuts {
+            if !is_valid_accelerator(&accelerator) {
+                return Err(format!("Raccourci invalide pour {}: {}", title, accelerator));
+            }
+            if let Some(other) = used.insert(accelerator_key(&accelerator), title.clone()) {
+                return Err(format!("Raccourci {} utilise par {} et {}", accelerator, other, title));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether going from `self` to `next` changes more than what a live update can show
+    fn needs_rebuild(&self, next: &MenuConfig) -> bool {
+        let accelerators = |config: &MenuConfig| -> Vec<Option<String>> {
+            MENU_ITEMS.iter().map(|(id, _, _)| config.accelerator(id)).collect()
+        };
+        let quick_shape = |config: &MenuConfig| -> Vec<Option<String>> {
+            config.quick_actions.iter().map(|q| q.accelerator.clone()).collect()
+        };
+        let hidden = |config: &MenuConfig| -> HashSet<String> { config.hidden.iter().cloned().collect() };
+        accelerators(self) != accelerators(next) || quick_shape(self) != quick_shape(next) || hidden(self) != hidden(next)
+    }
+}
+
+/// Builds a menu, skipping hidden items and the separators they'd leave dangling
+pub struct MenuBuilder<'a> {
+    config: &'a MenuConfig,
+    menu: Menu,
+    has_items: bool,
+    pending_separator: bool,
+}
+
+impl<'a> MenuBuilder<'a> {
+    pub fn new(config: &'a MenuConfig) -> Self {
+        MenuBuilder {
+            config,
+            menu: Menu::new(),
+            has_items: false,
+            pending_separator: false,
+        }
+    }
+
+    fn push(mut self, add: impl FnOnce(Menu) -> Menu) -> Self {
+        if self.pending_separator && self.has_items {
+            self.menu = self.menu.add_native_item(MenuItem::Separator);
+        }
+        self.pending_separator = false;
+        self.has_items = true;
+        self.menu = add(self.menu);
+        self
+    }
+
+    /// Customizable item of MENU_ITEMS
+    pub fn item(self, id: &str) -> Self {
+        let title = match MENU_ITEMS.iter().find(|(item, _, _)| *item == id) {
+            Some((_, title, _)) => *title,
+            None => return self,
+        };
+        if self.config.is_hidden(id) {
+            return self;
+        }
+        let mut item = CustomMenuItem::new(id, title);
+        if let Some(accelerator) = self.config.accelerator(id) {
+            item = item.accelerator(accelerator);
+        }
+        self.push(|menu| menu.add_item(item))
+    }
+
+    pub fn native(self, item: MenuItem) -> Self {
+        self.push(|menu| menu.add_native_item(item))
+    }
+
+    pub fn separator(mut self) -> Self {
+        self.pending_separator = true;
+        self
+    }
+
+    /// Quick action items, one per configured action
+    pub fn quick_actions(self) -> Self {
+        let config = self.config;
+        config.quick_actions.iter().enumerate().fold(self, |builder, (index, quick)| {
+            let mut item = CustomMenuItem::new(format!("{}{}", QUICK_ACTION_PREFIX, index), quick.label.clone());
+            if let Some(accelerator) = quick.accelerator.as_deref().filter(|a| !a.is_empty()) {
+                item = item.accelerator(accelerator);
+            }
+            builder.push(|menu| menu.add_item(item))
+        })
+    }
+
+    pub fn build(self) -> Menu {
+        self.menu
+    }
+}
+
+/// Remember the config the menu was built from, at launch
+pub fn set_active(config: &MenuConfig) {
+    if let Ok(mut active) = ACTIVE.lock() {
+        *active = config.clone();
+    }
+}
+
+/// Quick action behind a menu item id
+pub fn quick_action(id: &str) -> Option<QuickActionEvent> {
+    let index: usize = id.strip_prefix(QUICK_ACTION_PREFIX)?.parse().ok()?;
+    let active = ACTIVE.lock().ok()?;
+    active.quick_actions.get(index).map(|quick| QuickActionEvent {
+        project_id: quick.project_id.clone(),
+        action: quick.action.clone(),
+    })
+}
+
+/// Save the config and update the open menus as far as Tauri allows
+pub fn apply(app: &tauri::AppHandle, default_dir: &Path, config: MenuConfig) -> Result<MenuConfigApplied, String> {
+    config.validate()?;
+    crate::state_file::write_json(&default_dir.join(MENU_CONFIG_FILE), &config)?;
+
+    let mut active = ACTIVE.lock().map_err(|e| e.to_string())?;
+    let restart_required = active.needs_rebuild(&config);
+    if !restart_required {
+        // Same items and shortcuts: quick action titles and targets can change in place
+        for window in app.windows().values() {
+            let handle = window.menu_handle();
+            for (index, quick) in config.quick_actions.iter().enumerate() {
+                let _ = handle.get_item(&format!("{}{}", QUICK_ACTION_PREFIX, index)).set_title(quick.label.clone());
+            }
+        }
+        *active = config.clone();
+    }
+    println!("[Menu] Saved menu config (restart required: {})", restart_required);
+    Ok(MenuConfigApplied { config, restart_required })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_shortcuts() {
+        let mut config = MenuConfig::default();
+        assert!(config.validate().is_ok());
+
+        config.accelerators.insert("refresh".to_string(), "Ctrl+Shift+S".to_string());
+        assert!(config.validate().unwrap_err().contains("Synchroniser"));
+
+        config.hidden.push("sync_project".to_string());
+        assert!(config.validate().is_ok());
+
+        config.accelerators.insert("about".to_string(), "Hyper+A".to_string());
+        assert!(config.validate().is_err());
+        config.accelerators.insert("about".to_string(), String::new());
+        assert_eq!(config.accelerator("about"), None);
+        assert!(config.validate().is_ok());
+    }
+}
@@ -0,0 +1,155 @@
+//! Monitor Windows Module
+//!
+//! Detached secondary windows (sync monitor, scrape monitor, log viewer)
+//! that can be moved to another display. Each window loads the app with
+//! `?monitor=<kind>&project=<id>` and listens to the events of its kind;
+//! events normally sent only to the window that started the operation
+//! (scrape progress) are forwarded to the matching monitors.
+
+use crate::events;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, WindowBuilder, WindowUrl};
+
+/// Label prefix of every monitor window
+pub const LABEL_PREFIX: &str = "monitor-";
+
+/// Supported monitors: (kind, title, events the window listens to)
+const MONITOR_KINDS: [(&str, &str, &[&str]); 3] = [
+    ("sync", "Suivi de synchronisation", &[events::SYNC_PROGRESS]),
+    (
+        "scrape",
+        "Suivi du scraping",
+        &[events::SCRAPE_PROGRESS, events::FULL_SCRAPE_PROGRESS, events::SCRAPE_QUEUE_PROGRESS],
+    ),
+    (
+        "logs",
+        "Journal",
+        &[
+            events::SYNC_PROGRESS,
+            events::SCRAPE_PROGRESS,
+            events::FULL_SCRAPE_PROGRESS,
+            events::SIMULATION_REPORT,
+            events::WATCHDOG_INCIDENT,
+        ],
+    ),
+];
+
+/// Open monitors by window label
+static MONITORS: Lazy<Mutex<HashMap<String, MonitorWindow>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitorWindow {
+    pub label: String,
+    /// "sync", "scrape" or "logs"
+    pub kind: String,
+    /// Project followed, None for all projects
+    pub project_id: Option<String>,
+    pub events: Vec<String>,
+    pub opened_at: String,
+}
+
+fn window_label(kind: &str, project_id: Option<&str>) -> String {
+    let target: String = project_id
+        .unwrap_or("all")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}{}-{}", LABEL_PREFIX, kind, target)
+}
+
+/// Open a monitor window, or focus it when it is already open
+pub fn open(app: &AppHandle, kind: &str, project_id: Option<String>, project_name: Option<String>) -> Result<MonitorWindow, String> {
+    let (title, events) = match MONITOR_KINDS.iter().find(|(k, _, _)| *k == kind) {
+        Some((_, title, events)) => (*title, *events),
+        None => return Err(format!("Type de moniteur inconnu: {}", kind)),
+    };
+    let label = window_label(kind, project_id.as_deref());
+
+    if let Some(window) = app.get_window(&label) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+        if let Some(monitor) = MONITORS.lock().ok().and_then(|m| m.get(&label).cloned()) {
+            return Ok(monitor);
+        }
+    }
+
+    let mut url = format!("index.html?monitor={}", kind);
+    if let Some(project_id) = &project_id {
+        url.push_str(&format!("&project={}", url::form_urlencoded::byte_serialize(project_id.as_bytes()).collect::<String>()));
+    }
+    let window_title = match &project_name {
+        Some(name) => format!("{} - {}", title, name),
+        None => title.to_string(),
+    };
+    WindowBuilder::new(app, label.clone(), WindowUrl::App(url.into()))
+        .title(window_title)
+        .inner_size(720.0, 520.0)
+        .min_inner_size(420.0, 300.0)
+        .resizable(true)
+        .build()
+        .map_err(|e| format!("Failed to open monitor window: {}", e))?;
+
+    let monitor = MonitorWindow {
+        label: label.clone(),
+        kind: kind.to_string(),
+        project_id,
+        events: events.iter().map(|e| e.to_string()).collect(),
+        opened_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+    };
+    if let Ok(mut monitors) = MONITORS.lock() {
+        monitors.insert(label.clone(), monitor.clone());
+    }
+    println!("[Monitor] Opened {}", label);
+    Ok(monitor)
+}
+
+/// Send a window-scoped event to the monitors subscribed to it for this project
+pub fn forward<S: Serialize + Clone>(app: &AppHandle, event: &str, project_id: &str, payload: &S) {
+    let labels: Vec<String> = match MONITORS.lock() {
+        Ok(monitors) => monitors
+            .values()
+            .filter(|m| m.events.iter().any(|e| e == event))
+            .filter(|m| m.project_id.as_deref().map(|p| p == project_id).unwrap_or(true))
+            .map(|m| m.label.clone())
+            .collect(),
+        Err(_) => return,
+    };
+    for label in labels {
+        if let Some(window) = app.get_window(&label) {
+            let _ = window.emit(event, payload.clone());
+        }
+    }
+}
+
+pub fn list() -> Vec<MonitorWindow> {
+    let mut monitors: Vec<MonitorWindow> = MONITORS
+        .lock()
+        .map(|m| m.values().cloned().collect())
+        .unwrap_or_default();
+    monitors.sort_by(|a, b| a.opened_at.cmp(&b.opened_at));
+    monitors
+}
+
+pub fn close(app: &AppHandle, label: &str) -> Result<(), String> {
+    if !label.starts_with(LABEL_PREFIX) {
+        return Err("Seules les fenetres de suivi peuvent etre fermees".to_string());
+    }
+    if let Some(window) = app.get_window(label) {
+        window.close().map_err(|e| format!("Failed to close monitor window: {}", e))?;
+    }
+    forget(label);
+    Ok(())
+}
+
+/// Called when a monitor window is destroyed
+pub fn forget(label: &str) {
+    if let Ok(mut monitors) = MONITORS.lock() {
+        if monitors.remove(label).is_some() {
+            println!("[Monitor] Closed {}", label);
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Mtime Diff Module
+//!
+//! Comparing sizes misses the edits that keep a file's length (a typo fixed,
+//! a colour changed). Files of equal size are also compared by modification
+//! time: a local file newer than its remote copy by more than the clock-skew
+//! tolerance is reported "modified". Remote times come from the SFTP stat,
+//! the WebDAV `getlastmodified` property, or on FTP the MLSD listing, with
+//! MDTM for servers that only answer LIST.
+
+use crate::{proxy, FileDiff, RemoteFile, SFTPConfig};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Clock skew allowed between this machine and the server when none is configured
+pub const DEFAULT_TOLERANCE_SECS: u64 = 120;
+
+/// Modification time of a local file, in unix seconds
+pub fn local_mtime(path: &Path) -> Option<i64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs() as i64)
+}
+
+/// The local copy was saved after the remote one, beyond the tolerance
+pub fn is_local_newer(local: i64, remote: i64, tolerance_secs: u64) -> bool {
+    local > remote.saturating_add(tolerance_secs as i64)
+}
+
+/// Paths reported "unchanged" whose remote time is unknown
+pub fn missing_mtimes(diffs: &[FileDiff], remote_files: &HashMap<String, RemoteFile>) -> Vec<String> {
+    diffs
+        .iter()
+        .filter(|d| d.status == "unchanged")
+        .filter(|d| remote_files.get(&d.path).map(|r| r.mtime.is_none()).unwrap_or(false))
+        .map(|d| d.path.clone())
+        .collect()
+}
+
+/// Read the remote time of `paths` with MDTM; files the server can't date keep None
+pub fn fill_ftp_mtimes(
+    config: &SFTPConfig,
+    remote_base: &str,
+    paths: &[String],
+    remote_files: &mut HashMap<String, RemoteFile>,
+) -> Result<(), String> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+    let mut ftp = proxy::connect_ftp(config.proxy.as_ref(), &config.host, config.port, Duration::from_secs(10))?;
+    ftp.login(&config.username, &config.password)
+        .map_err(|e| format!("FTP login failed: {}", e))?;
+    if config.passive.unwrap_or(true) {
+        ftp.set_mode(suppaftp::Mode::Passive);
+    }
+
+    let base = remote_base.trim_end_matches('/');
+    for path in paths {
+        // MDTM answers in UTC
+        match ftp.mdtm(format!("{}/{}", base, path)) {
+            Ok(time) => {
+                if let Some(remote) = remote_files.get_mut(path) {
+                    remote.mtime = Some(time.and_utc().timestamp());
+                }
+            }
+            Err(e) => {
+                println!("[Diff] MDTM unavailable, falling back to sizes: {}", e);
+                break;
+            }
+        }
+    }
+    let _ = ftp.quit();
+    Ok(())
+}
+
+/// Turn the "unchanged" files whose local copy is newer into "modified"; returns how many changed
+pub fn mark_newer_local(
+    diffs: &mut [FileDiff],
+    local_base: &Path,
+    remote_files: &HashMap<String, RemoteFile>,
+    tolerance_secs: u64,
+) -> usize {
+    let mut changed = 0;
+    for diff in diffs.iter_mut().filter(|d| d.status == "unchanged") {
+        let remote = match remote_files.get(&diff.path).and_then(|r| r.mtime) {
+            Some(remote) => remote,
+            None => continue,
+        };
+        let local = match local_mtime(&local_base.join(&diff.path)) {
+            Some(local) => local,
+            None => continue,
+        };
+        if is_local_newer(local, remote, tolerance_secs) {
+            diff.status = "modified".to_string();
+            changed += 1;
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_equal_size_newer_local_is_modified() {
+        let dir = TempDir::new("mtime-diff");
+        dir.write_files(&[("index.html", "<p>a</p>"), ("style.css", "b{}")]);
+        let now = local_mtime(&dir.path().join("index.html")).unwrap();
+
+        let mut remote_files = HashMap::new();
+        remote_files.insert("index.html".to_string(), RemoteFile { size: 8, mtime: Some(now - 3600) });
+        // Within the tolerance: a server clock a minute ahead or behind
+        remote_files.insert("style.css".to_string(), RemoteFile { size: 3, mtime: Some(now - 60) });
+        let mut diffs = crate::diff_file_maps(&crate::scan_local_files(&dir.path_str()).unwrap(), &remote_files, &Default::default());
+
+        assert_eq!(mark_newer_local(&mut diffs, dir.path(), &remote_files, DEFAULT_TOLERANCE_SECS), 1);
+        assert_eq!(diffs[0].status, "modified");
+        assert_eq!(diffs[1].status, "unchanged");
+        assert!(!is_local_newer(now, now + 10, 0));
+    }
+}
@@ -0,0 +1,266 @@
+//! Notification Quiet Module
+//!
+//! Decides when notifications have to wait: macOS Focus / Do Not Disturb is
+//! on, the screen is being shared, or the clock is inside the user's quiet
+//! hours. Notifications raised meanwhile are queued, then delivered as one
+//! summary notification once the quiet period ends (the full list goes to
+//! the frontend). The rules are kept across restarts.
+
+use crate::state_file;
+use chrono::NaiveTime;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::api::notification::Notification;
+use tauri::{AppHandle, Manager};
+
+/// How often the end of a quiet period is checked while notifications wait
+const POLL_INTERVAL_SECS: u64 = 30;
+
+/// Deferred notifications kept, the oldest are dropped beyond
+const MAX_DEFERRED: usize = 100;
+
+/// Titles listed in the summary notification, the rest is counted
+const SUMMARY_TITLES: usize = 3;
+
+/// Processes running while the screen is shared (macOS Screen Sharing, Zoom)
+const SCREEN_SHARING_PROCESSES: [&str; 2] = ["ScreensharingAgent", "CptHost"];
+
+fn default_true() -> bool { true }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuietRules {
+    /// Hold notifications while macOS Focus / Do Not Disturb is on
+    #[serde(default = "default_true")]
+    pub respect_system_dnd: bool,
+    /// Hold notifications while the screen is shared
+    #[serde(default = "default_true")]
+    pub during_screen_sharing: bool,
+    /// Start of the quiet hours, local "HH:MM"
+    pub quiet_from: Option<String>,
+    /// End of the quiet hours, local "HH:MM"; the range may cross midnight
+    pub quiet_until: Option<String>,
+}
+
+impl Default for QuietRules {
+    fn default() -> Self {
+        QuietRules {
+            respect_system_dnd: true,
+            during_screen_sharing: true,
+            quiet_from: None,
+            quiet_until: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeferredNotification {
+    pub project_id: Option<String>,
+    pub title: String,
+    pub body: String,
+    pub deferred_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuietStatus {
+    /// "focus", "screen_sharing" or "quiet_hours"; None when notifications go through
+    pub reason: Option<String>,
+    pub deferred: usize,
+}
+
+#[derive(Default)]
+struct QuietState {
+    rules: QuietRules,
+    deferred: Vec<DeferredNotification>,
+    /// Set while the thread waiting for the end of the quiet period runs
+    flushing: bool,
+}
+
+static QUIET_STATE: Lazy<Mutex<QuietState>> = Lazy::new(|| Mutex::new(QuietState::default()));
+
+fn rules_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("notification_quiet.json")
+}
+
+/// Load the rules saved by a previous run
+pub fn restore(app_data_dir: &Path) {
+    let rules: QuietRules = state_file::read_json(&rules_path(app_data_dir)).ok().flatten().unwrap_or_default();
+    if let Ok(mut state) = QUIET_STATE.lock() {
+        state.rules = rules;
+    }
+}
+
+pub fn get_rules() -> QuietRules {
+    QUIET_STATE.lock().map(|state| state.rules.clone()).unwrap_or_default()
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").map_err(|_| format!("Heure invalide (HH:MM attendu): {}", value))
+}
+
+pub fn set_rules(app_data_dir: &Path, rules: QuietRules) -> Result<QuietRules, String> {
+    match (&rules.quiet_from, &rules.quiet_until) {
+        (Some(from), Some(until)) => {
+            parse_time(from)?;
+            parse_time(until)?;
+        }
+        (None, None) => {}
+        _ => return Err("Les heures calmes demandent un debut et une fin".to_string()),
+    }
+    state_file::write_json(&rules_path(app_data_dir), &rules)?;
+    if let Ok(mut state) = QUIET_STATE.lock() {
+        state.rules = rules.clone();
+    }
+    println!("[Notifications] Quiet rules updated");
+    Ok(rules)
+}
+
+/// Whether `now` falls in [from, until), the range wrapping past midnight when until <= from
+fn in_quiet_hours(from: NaiveTime, until: NaiveTime, now: NaiveTime) -> bool {
+    if from <= until {
+        from <= now && now < until
+    } else {
+        now >= from || now < until
+    }
+}
+
+/// macOS Focus (Monterey and later) or Do Not Disturb (older releases)
+fn system_dnd_active() -> bool {
+    if !cfg!(target_os = "macos") {
+        return false;
+    }
+    // Active Focus modes are listed as assertions; unreadable without Full Disk Access
+    let assertions = dirs::home_dir().map(|home| home.join("Library/DoNotDisturb/DB/Assertions.json"));
+    if let Some(content) = assertions.and_then(|path| std::fs::read_to_string(path).ok()) {
+        return serde_json::from_str::<serde_json::Value>(&content)
+            .ok()
+            .and_then(|json| {
+                json["data"]
+                    .as_array()
+                    .map(|data| data.iter().any(|entry| entry["storeAssertionRecords"].as_array().map(|r| !r.is_empty()).unwrap_or(false)))
+            })
+            .unwrap_or(false);
+    }
+    Command::new("defaults")
+        .args(["-currentHost", "read", "com.apple.notificationcenterui", "doNotDisturb"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "1")
+        .unwrap_or(false)
+}
+
+fn screen_sharing_active() -> bool {
+    SCREEN_SHARING_PROCESSES.iter().any(|name| {
+        Command::new("pgrep")
+            .args(["-x", name])
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    })
+}
+
+/// Why notifications are held right now, if they are
+fn quiet_reason(rules: &QuietRules) -> Option<&'static str> {
+    if let (Some(from), Some(until)) = (&rules.quiet_from, &rules.quiet_until) {
+        if let (Ok(from), Ok(until)) = (parse_time(from), parse_time(until)) {
+            if in_quiet_hours(from, until, chrono::Local::now().time()) {
+                return Some("quiet_hours");
+            }
+        }
+    }
+    if rules.during_screen_sharing && screen_sharing_active() {
+        return Some("screen_sharing");
+    }
+    if rules.respect_system_dnd && system_dnd_active() {
+        return Some("focus");
+    }
+    None
+}
+
+pub fn status() -> QuietStatus {
+    let rules = get_rules();
+    QuietStatus {
+        reason: quiet_reason(&rules).map(String::from),
+        deferred: QUIET_STATE.lock().map(|state| state.deferred.len()).unwrap_or(0),
+    }
+}
+
+/// Queue the notification when notifications are held; false when it can be shown now
+pub fn defer_if_quiet(app: &AppHandle, project_id: Option<&str>, title: &str, body: &str) -> bool {
+    let reason = match quiet_reason(&get_rules()) {
+        Some(reason) => reason,
+        None => return false,
+    };
+    let start_flush = match QUIET_STATE.lock() {
+        Ok(mut state) => {
+            if state.deferred.len() >= MAX_DEFERRED {
+                state.deferred.remove(0);
+            }
+            state.deferred.push(DeferredNotification {
+                project_id: project_id.map(String::from),
+                title: title.to_string(),
+                body: body.to_string(),
+                deferred_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            });
+            !std::mem::replace(&mut state.flushing, true)
+        }
+        Err(_) => return false,
+    };
+    println!("[Notifications] Deferred \"{}\" ({})", title, reason);
+    if start_flush {
+        start_flush_thread(app.clone());
+    }
+    true
+}
+
+/// Wait for the quiet period to end, then deliver the summary
+fn start_flush_thread(app: AppHandle) {
+    thread::spawn(move || loop {
+        thread::sleep(Duration::from_secs(POLL_INTERVAL_SECS));
+        if quiet_reason(&get_rules()).is_some() {
+            continue;
+        }
+        let deferred = match QUIET_STATE.lock() {
+            Ok(mut state) => {
+                state.flushing = false;
+                std::mem::take(&mut state.deferred)
+            }
+            Err(_) => break,
+        };
+        if !deferred.is_empty() {
+            deliver_summary(&app, &deferred);
+        }
+        break;
+    });
+}
+
+fn deliver_summary(app: &AppHandle, deferred: &[DeferredNotification]) {
+    let mut lines: Vec<String> = deferred.iter().rev().take(SUMMARY_TITLES).map(|n| n.title.clone()).collect();
+    if deferred.len() > SUMMARY_TITLES {
+        lines.push(format!("et {} autre(s)", deferred.len() - SUMMARY_TITLES));
+    }
+    let _ = Notification::new(&app.config().tauri.bundle.identifier)
+        .title(format!("{} notification(s) pendant la pause", deferred.len()))
+        .body(lines.join("\n"))
+        .show();
+    let _ = app.emit_all(crate::events::NOTIFICATIONS_DEFERRED_SUMMARY, deferred);
+    println!("[Notifications] Delivered summary of {} deferred notification(s)", deferred.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quiet_hours_across_midnight() {
+        let t = |value: &str| parse_time(value).unwrap();
+        assert!(in_quiet_hours(t("22:00"), t("08:00"), t("23:30")));
+        assert!(in_quiet_hours(t("22:00"), t("08:00"), t("07:59")));
+        assert!(!in_quiet_hours(t("22:00"), t("08:00"), t("08:00")));
+        assert!(in_quiet_hours(t("12:00"), t("14:00"), t("12:30")));
+        assert!(!in_quiet_hours(t("12:00"), t("14:00"), t("18:00")));
+    }
+}
@@ -0,0 +1,132 @@
+//! Notifications Module
+//!
+//! Native notifications built from templates and marked with the project's
+//! identity (emoji, or a colored dot derived from its color), the same badge
+//! shown in the tray menu, so the client concerned is recognizable at a
+//! glance. Tauri 1 has no dock badge API, so the tray and notifications carry it.
+//! Notifications raised during Focus, screen sharing or quiet hours wait for a
+//! summary (see `notification_quiet`).
+
+use crate::notification_quiet;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::api::notification::Notification;
+use tauri::AppHandle;
+
+/// Templates by kind: (kind, title, body); `{name}` placeholders are filled from the variables
+const TEMPLATES: [(&str, &str, &str); 7] = [
+    ("sync_complete", "Synchronisation terminee", "{files} fichier(s) envoye(s)"),
+    ("sync_error", "Echec de la synchronisation", "{message}"),
+    ("scrape_complete", "Scraping termine", "{pages} page(s) recuperee(s)"),
+    ("scrape_error", "Echec du scraping", "{message}"),
+    ("interrupted_sync", "Synchronisation interrompue", "{files} fichier(s) restent a envoyer, reprise possible"),
+    ("schedule_due", "Synchronisation planifiee", "La synchronisation planifiee demarre"),
+    ("remote_change", "Modifications sur le serveur", "{files} fichier(s) modifie(s) hors de La Forge"),
+];
+
+/// Colored dots available in menus and notification titles, with their RGB
+const COLOR_DOTS: [(&str, (i32, i32, i32)); 9] = [
+    ("🔴", (221, 46, 68)),
+    ("🟠", (244, 144, 12)),
+    ("🟡", (253, 203, 88)),
+    ("🟢", (120, 177, 89)),
+    ("🔵", (85, 172, 238)),
+    ("🟣", (170, 142, 214)),
+    ("🟤", (193, 105, 79)),
+    ("⚫", (49, 55, 61)),
+    ("⚪", (230, 231, 232)),
+];
+
+/// Visual identity of a project
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ProjectIdentity {
+    pub name: String,
+    /// Hex color, e.g. "#4fc3f7"
+    pub color: Option<String>,
+    pub emoji: Option<String>,
+}
+
+/// Identities of the projects known to the tray, by project id
+static IDENTITIES: Lazy<Mutex<HashMap<String, ProjectIdentity>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn set_identity(project_id: &str, identity: ProjectIdentity) {
+    if let Ok(mut identities) = IDENTITIES.lock() {
+        identities.insert(project_id.to_string(), identity);
+    }
+}
+
+pub fn get_identity(project_id: &str) -> Option<ProjectIdentity> {
+    IDENTITIES.lock().ok().and_then(|i| i.get(project_id).cloned())
+}
+
+/// Emoji of the project, or the dot closest to its color
+pub fn badge(color: Option<&str>, emoji: Option<&str>) -> Option<String> {
+    if let Some(emoji) = emoji.map(str::trim).filter(|e| !e.is_empty()) {
+        return Some(emoji.to_string());
+    }
+    let (r, g, b) = parse_hex(color?)?;
+    COLOR_DOTS
+        .iter()
+        .min_by_key(|(_, (dr, dg, db))| (r - dr).pow(2) + (g - dg).pow(2) + (b - db).pow(2))
+        .map(|(dot, _)| dot.to_string())
+}
+
+fn parse_hex(color: &str) -> Option<(i32, i32, i32)> {
+    let hex = color.trim().trim_start_matches('#');
+    let hex = match hex.len() {
+        3 => hex.chars().flat_map(|c| [c, c]).collect::<String>(),
+        6 => hex.to_string(),
+        _ => return None,
+    };
+    let channel = |i: usize| i32::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Title prefixed with the project's badge and name, e.g. "🟢 Boulangerie - Scraping termine"
+fn project_title(project_id: Option<&str>, title: &str) -> String {
+    match project_id.and_then(get_identity) {
+        Some(identity) => match badge(identity.color.as_deref(), identity.emoji.as_deref()) {
+            Some(badge) => format!("{} {} - {}", badge, identity.name, title),
+            None => format!("{} - {}", identity.name, title),
+        },
+        None => title.to_string(),
+    }
+}
+
+/// Title and body of a template for a project
+pub fn render(kind: &str, project_id: Option<&str>, vars: &HashMap<String, String>) -> Result<(String, String), String> {
+    let (_, title, body) = TEMPLATES
+        .iter()
+        .find(|(k, _, _)| *k == kind)
+        .ok_or_else(|| format!("Modele de notification inconnu: {}", kind))?;
+    let mut body = body.to_string();
+    for (name, value) in vars {
+        body = body.replace(&format!("{{{}}}", name), value);
+    }
+    Ok((project_title(project_id, title), body))
+}
+
+/// Show the notification, or queue it while notifications are held
+fn deliver(app: &AppHandle, project_id: Option<&str>, title: String, body: &str) {
+    if notification_quiet::defer_if_quiet(app, project_id, &title, body) {
+        return;
+    }
+    let _ = Notification::new(&app.config().tauri.bundle.identifier)
+        .title(title)
+        .body(body)
+        .show();
+}
+
+/// Show a notification, marked with the project's identity when known
+pub fn show(app: &AppHandle, project_id: Option<&str>, title: &str, body: &str) {
+    deliver(app, project_id, project_title(project_id, title), body);
+}
+
+/// Show a templated notification
+pub fn notify(app: &AppHandle, kind: &str, project_id: Option<&str>, vars: &HashMap<String, String>) -> Result<(), String> {
+    let (title, body) = render(kind, project_id, vars)?;
+    deliver(app, project_id, title, &body);
+    Ok(())
+}
@@ -0,0 +1,297 @@
+//! Offline Queue Module
+//!
+//! Syncs requested while their server is unreachable. The intent (project,
+//! host, local folder, never the credentials) is stored on disk, a background
+//! thread probes the hosts, and when one answers again the frontend is told
+//! to run the sync, directly or after asking, like a scheduled sync.
+
+use crate::proxy::{self, ProxyConfig};
+use crate::state_file;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::Manager;
+
+/// Delay between two reachability probes
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum QueuedSyncStatus {
+    /// Server still unreachable
+    Waiting,
+    /// Server reachable, the frontend was asked to run the sync
+    Ready,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedSync {
+    pub id: String,
+    pub project_id: String,
+    pub project_name: Option<String>,
+    pub host: String,
+    pub port: u16,
+    /// Proxy the server is reached through, used for the reachability checks
+    #[serde(default)]
+    pub proxy: Option<ProxyConfig>,
+    pub local_path: String,
+    /// Run the sync without asking once the server is back
+    pub auto_run: bool,
+    pub status: QueuedSyncStatus,
+    pub queued_at: String,
+    pub last_check: Option<String>,
+    pub checks: u32,
+}
+
+/// Request to queue a sync
+#[derive(Debug, Clone, Deserialize)]
+pub struct QueueSyncRequest {
+    pub project_id: String,
+    pub project_name: Option<String>,
+    pub host: String,
+    pub port: u16,
+    #[serde(default)]
+    pub proxy: Option<ProxyConfig>,
+    pub local_path: String,
+    #[serde(default)]
+    pub auto_run: bool,
+}
+
+/// Event emitted on "sync-queue-status"
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncQueueEvent {
+    /// "queued", "checked", "ready", "requeued" or "removed"
+    pub event_type: String,
+    pub item: QueuedSync,
+    pub message: String,
+}
+
+#[derive(Default)]
+struct QueueState {
+    items: Vec<QueuedSync>,
+    data_dir: Option<PathBuf>,
+    monitoring: bool,
+}
+
+static QUEUE: Lazy<Mutex<QueueState>> = Lazy::new(|| Mutex::new(QueueState::default()));
+
+fn queue_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("offline_sync_queue.json")
+}
+
+fn now() -> String {
+    chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+fn persist(state: &QueueState) {
+    if let Some(dir) = &state.data_dir {
+        if let Err(e) = state_file::write_json(&queue_path(dir), &state.items) {
+            println!("[OfflineQueue] Failed to save queue: {}", e);
+        }
+    }
+}
+
+fn emit(app_handle: &tauri::AppHandle, event_type: &str, item: &QueuedSync, message: String) {
+    let _ = app_handle.emit_all(
+        crate::events::SYNC_QUEUE_STATUS,
+        SyncQueueEvent {
+            event_type: event_type.to_string(),
+            item: item.clone(),
+            message,
+        },
+    );
+}
+
+/// Load the queue saved by a previous run and resume monitoring
+pub fn restore(app_handle: tauri::AppHandle, app_data_dir: &Path) {
+    let items: Vec<QueuedSync> = state_file::read_json(&queue_path(app_data_dir))
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    let pending = {
+        let mut state = match QUEUE.lock() {
+            Ok(state) => state,
+            Err(_) => return,
+        };
+        state.data_dir = Some(app_data_dir.to_path_buf());
+        // Ready items were never run: probe them again
+        state.items = items
+            .into_iter()
+            .map(|mut item| {
+                item.status = QueuedSyncStatus::Waiting;
+                item
+            })
+            .collect();
+        state.items.len()
+    };
+    if pending > 0 {
+        println!("[OfflineQueue] {} queued sync(s) restored", pending);
+        start_monitor(app_handle);
+    }
+}
+
+/// Queue a sync for when its server is back; replaces a queued sync of the same project
+pub fn enqueue(app_handle: tauri::AppHandle, app_data_dir: &Path, request: QueueSyncRequest) -> Result<QueuedSync, String> {
+    if request.host.trim().is_empty() {
+        return Err("Hote manquant".to_string());
+    }
+    let item = QueuedSync {
+        id: uuid::Uuid::new_v4().to_string(),
+        project_id: request.project_id,
+        project_name: request.project_name,
+        host: request.host,
+        port: request.port,
+        proxy: request.proxy,
+        local_path: request.local_path,
+        auto_run: request.auto_run,
+        status: QueuedSyncStatus::Waiting,
+        queued_at: now(),
+        last_check: None,
+        checks: 0,
+    };
+    {
+        let mut state = QUEUE.lock().map_err(|_| "Failed to access sync queue".to_string())?;
+        state.data_dir = Some(app_data_dir.to_path_buf());
+        state.items.retain(|i| i.project_id != item.project_id);
+        state.items.push(item.clone());
+        persist(&state);
+    }
+    println!("[OfflineQueue] Queued sync of {} until {} is reachable", item.project_id, item.host);
+    emit(&app_handle, "queued", &item, format!("Synchronisation en attente du serveur {}", item.host));
+    start_monitor(app_handle);
+    Ok(item)
+}
+
+pub fn list() -> Vec<QueuedSync> {
+    QUEUE.lock().map(|state| state.items.clone()).unwrap_or_default()
+}
+
+pub fn remove(app_handle: &tauri::AppHandle, id: &str) -> Result<(), String> {
+    let removed = {
+        let mut state = QUEUE.lock().map_err(|_| "Failed to access sync queue".to_string())?;
+        let position = state
+            .items
+            .iter()
+            .position(|i| i.id == id)
+            .ok_or_else(|| "Synchronisation en attente introuvable".to_string())?;
+        let removed = state.items.remove(position);
+        persist(&state);
+        removed
+    };
+    emit(app_handle, "removed", &removed, "Synchronisation retiree de la file".to_string());
+    Ok(())
+}
+
+/// Report the outcome of a sync run for a ready item: removed on success,
+/// put back to waiting on failure
+pub fn complete(app_handle: tauri::AppHandle, id: &str, success: bool) -> Result<(), String> {
+    if success {
+        return remove(&app_handle, id);
+    }
+    let item = {
+        let mut state = QUEUE.lock().map_err(|_| "Failed to access sync queue".to_string())?;
+        let item = state
+            .items
+            .iter_mut()
+            .find(|i| i.id == id)
+            .ok_or_else(|| "Synchronisation en attente introuvable".to_string())?;
+        item.status = QueuedSyncStatus::Waiting;
+        let item = item.clone();
+        persist(&state);
+        item
+    };
+    emit(&app_handle, "requeued", &item, "Echec de la synchronisation, nouvelle tentative au retour du serveur".to_string());
+    start_monitor(app_handle);
+    Ok(())
+}
+
+fn is_reachable(host: &str, port: u16, proxy: Option<&ProxyConfig>) -> bool {
+    proxy::connect(proxy, host, port, CONNECT_TIMEOUT).is_ok()
+}
+
+fn start_monitor(app_handle: tauri::AppHandle) {
+    match QUEUE.lock() {
+        Ok(mut state) if !state.monitoring => state.monitoring = true,
+        _ => return,
+    }
+
+    thread::spawn(move || {
+        println!("[OfflineQueue] Monitor started");
+        loop {
+            thread::sleep(CHECK_INTERVAL);
+
+            let waiting: Vec<(String, String, u16, Option<ProxyConfig>)> = match QUEUE.lock() {
+                Ok(mut state) => {
+                    let waiting: Vec<_> = state
+                        .items
+                        .iter()
+                        .filter(|i| i.status == QueuedSyncStatus::Waiting)
+                        .map(|i| (i.id.clone(), i.host.clone(), i.port, i.proxy.clone()))
+                        .collect();
+                    if waiting.is_empty() {
+                        state.monitoring = false;
+                        println!("[OfflineQueue] Monitor stopped, nothing waiting");
+                        break;
+                    }
+                    waiting
+                }
+                Err(_) => break,
+            };
+
+            // Probe outside the lock, several items may share a host
+            let mut results: Vec<(String, bool)> = Vec::new();
+            let mut probed: Vec<((String, u16), bool)> = Vec::new();
+            for (id, host, port, proxy) in waiting {
+                let key = (host.clone(), port);
+                let reachable = match probed.iter().find(|(k, _)| *k == key) {
+                    Some((_, reachable)) => *reachable,
+                    None => {
+                        let reachable = is_reachable(&host, port, proxy.as_ref());
+                        probed.push((key, reachable));
+                        reachable
+                    }
+                };
+                results.push((id, reachable));
+            }
+
+            let mut events = Vec::new();
+            let app_dir = crate::data_location::app_data_dir(&app_handle);
+            if let Ok(mut state) = QUEUE.lock() {
+                let checked_at = now();
+                for (id, reachable) in results {
+                    if let Some(item) = state.items.iter_mut().find(|i| i.id == id) {
+                        item.last_check = Some(checked_at.clone());
+                        item.checks += 1;
+                        // A sync that would run on its own waits for the end of a pause
+                        let paused = item.auto_run
+                            && app_dir
+                                .as_deref()
+                                .and_then(|dir| crate::automation_pause::active_pause(dir, &item.project_id))
+                                .is_some();
+                        if reachable && !paused {
+                            item.status = QueuedSyncStatus::Ready;
+                            events.push(("ready", item.clone()));
+                        } else {
+                            events.push(("checked", item.clone()));
+                        }
+                    }
+                }
+                persist(&state);
+            }
+            for (event_type, item) in events {
+                let message = if event_type == "ready" {
+                    println!("[OfflineQueue] {} is reachable, sync of {} can run", item.host, item.project_id);
+                    format!("Le serveur {} est de nouveau joignable", item.host)
+                } else {
+                    format!("Serveur {} toujours injoignable", item.host)
+                };
+                emit(&app_handle, event_type, &item, message);
+            }
+        }
+    });
+}
@@ -0,0 +1,206 @@
+//! Onboarding Module
+//!
+//! Probes run at first launch: installed editors and terminal apps, git,
+//! keychain health and network reachability. The capability report lets
+//! the onboarding screens pre-configure the integrations that will work.
+
+use crate::{resolve_addr, KEYRING_SERVICE};
+use serde::Serialize;
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// Timeout of each network probe
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Keychain entry written and removed by the keyring probe
+const KEYRING_PROBE_KEY: &str = "laforge-onboarding-probe";
+
+/// Editors supported by `open_in_editor`: (id, name, CLI, app bundles)
+const EDITORS: [(&str, &str, &str, &[&str]); 3] = [
+    ("vscode", "Visual Studio Code", "code", &["Visual Studio Code.app"]),
+    ("cursor", "Cursor", "cursor", &["Cursor.app"]),
+    ("pycharm", "PyCharm", "pycharm", &["PyCharm.app", "PyCharm CE.app", "PyCharm Professional Edition.app"]),
+];
+
+/// Terminal apps: (id, name, app bundle)
+const TERMINALS: [(&str, &str, &str); 5] = [
+    ("terminal", "Terminal", "Utilities/Terminal.app"),
+    ("iterm", "iTerm2", "iTerm.app"),
+    ("warp", "Warp", "Warp.app"),
+    ("ghostty", "Ghostty", "Ghostty.app"),
+    ("alacritty", "Alacritty", "Alacritty.app"),
+];
+
+/// Hosts whose reachability is checked: (label, host, port)
+const NETWORK_TARGETS: [(&str, &str, u16); 2] = [("internet", "one.one.one.one", 443), ("github", "github.com", 443)];
+
+/// Folders searched for CLIs on top of PATH (apps launched from the Dock get a minimal PATH)
+const EXTRA_BIN_DIRS: [&str; 3] = ["/usr/local/bin", "/opt/homebrew/bin", "/usr/bin"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DetectedApp {
+    pub id: String,
+    pub name: String,
+    pub installed: bool,
+    /// App bundle or binary found
+    pub path: Option<String>,
+    /// Command-line launcher available (needed to open a folder directly)
+    pub cli_available: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GitProbe {
+    pub available: bool,
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyringProbe {
+    /// A password could be written, read back and removed
+    pub healthy: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkProbe {
+    pub target: String,
+    pub reachable: bool,
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CapabilityReport {
+    pub platform: String,
+    pub editors: Vec<DetectedApp>,
+    pub terminals: Vec<DetectedApp>,
+    pub git: GitProbe,
+    pub keyring: KeyringProbe,
+    pub network: Vec<NetworkProbe>,
+    /// Editor id to preselect, first installed one
+    pub suggested_editor: Option<String>,
+}
+
+/// Run every probe
+pub fn probe_capabilities() -> CapabilityReport {
+    let editors: Vec<DetectedApp> = EDITORS
+        .iter()
+        .map(|(id, name, cli, bundles)| {
+            let cli_path = find_in_path(cli);
+            let bundle = bundles.iter().find_map(|bundle| find_app_bundle(bundle));
+            DetectedApp {
+                id: id.to_string(),
+                name: name.to_string(),
+                installed: cli_path.is_some() || bundle.is_some(),
+                path: bundle.or_else(|| cli_path.clone()).map(|p| p.to_string_lossy().to_string()),
+                cli_available: cli_path.is_some(),
+            }
+        })
+        .collect();
+
+    let terminals = TERMINALS
+        .iter()
+        .map(|(id, name, bundle)| {
+            let path = find_app_bundle(bundle);
+            DetectedApp {
+                id: id.to_string(),
+                name: name.to_string(),
+                installed: path.is_some(),
+                path: path.map(|p| p.to_string_lossy().to_string()),
+                cli_available: false,
+            }
+        })
+        .collect();
+
+    let suggested_editor = editors.iter().find(|e| e.installed).map(|e| e.id.clone());
+    let report = CapabilityReport {
+        platform: std::env::consts::OS.to_string(),
+        editors,
+        terminals,
+        git: probe_git(),
+        keyring: probe_keyring(),
+        network: NETWORK_TARGETS
+            .iter()
+            .map(|(label, host, port)| probe_network(label, host, *port))
+            .collect(),
+        suggested_editor,
+    };
+
+    println!(
+        "[Onboarding] {} editors, {} terminals, git: {}, keyring: {}, network: {}/{}",
+        report.editors.iter().filter(|e| e.installed).count(),
+        report.terminals.iter().filter(|t| t.installed).count(),
+        report.git.available,
+        report.keyring.healthy,
+        report.network.iter().filter(|n| n.reachable).count(),
+        report.network.len()
+    );
+    report
+}
+
+fn find_in_path(binary: &str) -> Option<PathBuf> {
+    let path_var = std::env::var_os("PATH").unwrap_or_default();
+    std::env::split_paths(&path_var)
+        .chain(EXTRA_BIN_DIRS.iter().map(PathBuf::from))
+        .map(|dir| dir.join(binary))
+        .find(|candidate| candidate.is_file())
+}
+
+fn find_app_bundle(bundle: &str) -> Option<PathBuf> {
+    let mut roots = vec![PathBuf::from("/Applications"), PathBuf::from("/System/Applications")];
+    if let Some(home) = dirs::home_dir() {
+        roots.push(home.join("Applications"));
+    }
+    roots
+        .into_iter()
+        .map(|root| root.join(bundle))
+        .find(|candidate| candidate.exists())
+}
+
+fn probe_git() -> GitProbe {
+    let binary = find_in_path("git").unwrap_or_else(|| PathBuf::from("git"));
+    match Command::new(binary).arg("--version").output() {
+        Ok(output) if output.status.success() => GitProbe {
+            available: true,
+            version: Some(
+                String::from_utf8_lossy(&output.stdout)
+                    .trim()
+                    .trim_start_matches("git version ")
+                    .to_string(),
+            ),
+        },
+        // On macOS, /usr/bin/git fails until the command line tools are installed
+        _ => GitProbe { available: false, version: None },
+    }
+}
+
+fn probe_keyring() -> KeyringProbe {
+    let result = (|| -> Result<(), String> {
+        let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_PROBE_KEY).map_err(|e| e.to_string())?;
+        let value = format!("probe-{}", chrono::Utc::now().timestamp());
+        entry.set_password(&value).map_err(|e| format!("Ecriture impossible: {}", e))?;
+        let read = entry.get_password().map_err(|e| format!("Lecture impossible: {}", e));
+        let _ = entry.delete_credential();
+        if read? != value {
+            return Err("Le mot de passe relu ne correspond pas".to_string());
+        }
+        Ok(())
+    })();
+    KeyringProbe {
+        healthy: result.is_ok(),
+        error: result.err(),
+    }
+}
+
+fn probe_network(label: &str, host: &str, port: u16) -> NetworkProbe {
+    let start = Instant::now();
+    let result = resolve_addr(host, port).and_then(|addr| TcpStream::connect_timeout(&addr, NETWORK_TIMEOUT).map_err(|e| e.to_string()));
+    NetworkProbe {
+        target: label.to_string(),
+        reachable: result.is_ok(),
+        latency_ms: result.as_ref().ok().map(|_| start.elapsed().as_millis() as u64),
+        error: result.err(),
+    }
+}
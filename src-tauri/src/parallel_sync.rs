@@ -1,15 +1,18 @@
 //! Parallel File Upload Module
 //!
-//! Implements multi-connection parallel file uploads for FTP/SFTP
-//! with configurable concurrency and progress tracking.
-
-use crate::{is_cancelled, FileDiff, SFTPConfig, SyncProgressEvent};
+//! Implements multi-connection parallel file uploads for FTP/SFTP/WebDAV
+//! with configurable concurrency and progress tracking. FTP and SFTP
+//! uploads go through a pool of persistent connections fed from a queue.
+
+use crate::concurrency_tuning::Connections;
+use crate::remote_fs::RemoteFs;
+use crate::{atomic_upload, events, proxy, sync_history};
+use crate::{is_cancelled, FileDiff, SFTPConfig, SyncProgressEvent, UploadOptions};
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fs::File;
-use std::io::{Read, Write as IoWrite};
-use std::net::{SocketAddr, TcpStream};
-use std::path::Path;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
@@ -59,10 +62,11 @@ impl ParallelProgressTracker {
     pub fn emit_file_start(&self, file: &str, file_size: u64) {
         let progress = self.current_progress.load(Ordering::SeqCst);
         let _ = self.app_handle.emit_all(
-            "sync-progress",
+            events::SYNC_PROGRESS,
             SyncProgressEvent {
                 project_id: self.project_id.clone(),
-                event: "file_start".to_string(),
+                schema_version: events::SCHEMA_VERSION,
+                event: events::SyncEventKind::FileStart,
                 file: Some(file.to_string()),
                 progress,
                 file_progress: Some(0),
@@ -82,10 +86,11 @@ impl ParallelProgressTracker {
             100
         };
         let _ = self.app_handle.emit_all(
-            "sync-progress",
+            events::SYNC_PROGRESS,
             SyncProgressEvent {
                 project_id: self.project_id.clone(),
-                event: "file_progress".to_string(),
+                schema_version: events::SCHEMA_VERSION,
+                event: events::SyncEventKind::FileProgress,
                 file: Some(file.to_string()),
                 progress,
                 file_progress: Some(file_progress),
@@ -103,10 +108,11 @@ impl ParallelProgressTracker {
         self.current_progress.store(progress, Ordering::SeqCst);
 
         let _ = self.app_handle.emit_all(
-            "sync-progress",
+            events::SYNC_PROGRESS,
             SyncProgressEvent {
                 project_id: self.project_id.clone(),
-                event: "file_complete".to_string(),
+                schema_version: events::SCHEMA_VERSION,
+                event: events::SyncEventKind::FileComplete,
                 file: Some(file.to_string()),
                 progress,
                 file_progress: Some(100),
@@ -124,13 +130,15 @@ impl ParallelProgressTracker {
         if let Ok(mut errors) = self.errors.lock() {
             errors.push(format!("{}: {}", file, error));
         }
+        sync_history::note_error(&self.project_id, Some(file), error);
 
         let progress = self.current_progress.load(Ordering::SeqCst);
         let _ = self.app_handle.emit_all(
-            "sync-progress",
+            events::SYNC_PROGRESS,
             SyncProgressEvent {
                 project_id: self.project_id.clone(),
-                event: "file_error".to_string(),
+                schema_version: events::SCHEMA_VERSION,
+                event: events::SyncEventKind::FileError,
                 file: Some(file.to_string()),
                 progress,
                 file_progress: Some(0),
@@ -174,24 +182,129 @@ fn timestamp_now() -> u64 {
         .as_millis() as u64
 }
 
-fn resolve_addr(host: &str, port: u16) -> Result<SocketAddr, String> {
-    use std::net::ToSocketAddrs;
-    let addr_str = format!("{}:{}", host, port);
-    addr_str
-        .to_socket_addrs()
-        .map_err(|e| format!("DNS resolution failed: {}", e))?
-        .next()
-        .ok_or_else(|| "No address found".to_string())
+/// Upload `files` over `connections` persistent connections. Each worker opens
+/// its connection once and takes files from a shared queue until it is empty,
+/// instead of paying a TCP and auth handshake per file. A file failing on a
+/// reused connection is retried once on a fresh one (the server may have
+/// dropped an idle connection). With adaptive connections, only the workers
+/// under the current level take files; a file whose login was refused goes
+/// to a next pass on the connections left.
+fn run_workers<C>(
+    files: &[&FileDiff],
+    connections: Connections,
+    project_id: &str,
+    tracker: &ParallelProgressTracker,
+    connect: impl Fn() -> Result<C, String> + Sync,
+    upload: impl Fn(&mut C, &FileDiff) -> Result<(), String> + Sync,
+    close: impl Fn(C) + Sync,
+) {
+    let adaptive = connections.adaptive();
+    let mut pending: Vec<&FileDiff> = files.to_vec();
+    while !pending.is_empty() && !is_cancelled(project_id) {
+        let next = AtomicUsize::new(0);
+        let refused: Mutex<Vec<&FileDiff>> = Mutex::new(Vec::new());
+        std::thread::scope(|scope| {
+            for index in 0..connections.max() {
+                let (pending, next, refused) = (&pending, &next, &refused);
+                let (connect, upload, close) = (&connect, &upload, &close);
+                scope.spawn(move || {
+                    let mut connection: Option<C> = None;
+                    loop {
+                        if is_cancelled(project_id) || tracker.should_stop() {
+                            break;
+                        }
+                        if adaptive.map(|limit| !limit.allows(index)).unwrap_or(false) {
+                            // Parked: release the login, shared hosts count idle ones too
+                            if let Some(open) = connection.take() {
+                                close(open);
+                            }
+                            if next.load(Ordering::SeqCst) >= pending.len() {
+                                break;
+                            }
+                            std::thread::sleep(Duration::from_millis(200));
+                            continue;
+                        }
+                        let diff = match pending.get(next.fetch_add(1, Ordering::SeqCst)) {
+                            Some(diff) => *diff,
+                            None => break,
+                        };
+                        let file_size = diff.local_size.unwrap_or(0);
+                        tracker.emit_file_start(&diff.path, file_size);
+
+                        let reused = connection.is_some();
+                        let mut result = upload_on(&mut connection, connect, upload, diff);
+                        if result.is_err() && reused {
+                            if let Some(stale) = connection.take() {
+                                close(stale);
+                            }
+                            result = upload_on(&mut connection, connect, upload, diff);
+                        }
+
+                        match result {
+                            Ok(_) => {
+                                tracker.emit_file_complete(&diff.path, file_size);
+                                if let Some(limit) = adaptive {
+                                    limit.record_file(file_size, true);
+                                }
+                            }
+                            // No connection could be opened: over the host's login limit
+                            Err(_) if connection.is_none() && adaptive.map(|l| l.record_refused(index)).unwrap_or(false) => {
+                                if let Ok(mut refused) = refused.lock() {
+                                    refused.push(diff);
+                                }
+                            }
+                            Err(e) => {
+                                // The connection may be unusable, the next file opens a new one
+                                if let Some(broken) = connection.take() {
+                                    close(broken);
+                                }
+                                tracker.emit_file_error(&diff.path, &e, file_size);
+                                if let Some(limit) = adaptive {
+                                    limit.record_file(file_size, false);
+                                }
+                            }
+                        }
+                    }
+                    if let Some(open) = connection {
+                        close(open);
+                    }
+                });
+            }
+        });
+        pending = refused.into_inner().unwrap_or_default();
+    }
+}
+
+/// No more workers than files, nor than MAX_PARALLEL_CONNECTIONS
+fn limit_connections(connections: Connections, total_files: usize) -> Connections {
+    match connections {
+        Connections::Fixed(count) => Connections::Fixed(count.min(total_files).clamp(1, MAX_PARALLEL_CONNECTIONS)),
+        adaptive => adaptive,
+    }
 }
 
-/// Parallel SFTP sync using multiple SSH sessions
+fn upload_on<C>(
+    connection: &mut Option<C>,
+    connect: &impl Fn() -> Result<C, String>,
+    upload: &impl Fn(&mut C, &FileDiff) -> Result<(), String>,
+    diff: &FileDiff,
+) -> Result<(), String> {
+    let open = match connection {
+        Some(open) => open,
+        None => connection.insert(connect()?),
+    };
+    upload(open, diff)
+}
+
+/// Parallel SFTP sync over a pool of persistent SSH sessions
 pub fn parallel_sftp_sync(
     local_path: &str,
     config: &SFTPConfig,
     diffs: &[FileDiff],
     project_id: &str,
     app_handle: &tauri::AppHandle,
-    max_connections: usize,
+    connections: Connections,
+    upload: UploadOptions,
 ) -> Result<(), String> {
     let files_to_upload: Vec<_> = diffs
         .iter()
@@ -212,40 +325,25 @@ pub fn parallel_sftp_sync(
     );
 
     // Limit connections to file count or max
-    let actual_connections = max_connections.min(total_files).min(MAX_PARALLEL_CONNECTIONS);
-
-    // Configure rayon thread pool for this operation
-    let pool = rayon::ThreadPoolBuilder::new()
-        .num_threads(actual_connections)
-        .build()
-        .map_err(|e| format!("Failed to create thread pool: {}", e))?;
-
-    let local_path = local_path.to_string();
+    let actual_connections = limit_connections(connections, total_files);
     let remote_base = config.remote_path.clone();
-    let config = config.clone();
 
-    pool.install(|| {
-        files_to_upload.par_iter().for_each(|diff| {
-            // Check cancellation
-            if is_cancelled(project_id) || tracker.should_stop() {
-                return;
-            }
+    // Folders already created, shared by the workers
+    let created_dirs: Mutex<HashSet<PathBuf>> = Mutex::new(HashSet::new());
 
+    run_workers(
+        &files_to_upload,
+        actual_connections,
+        project_id,
+        &tracker,
+        || connect_sftp_worker(config),
+        |sftp, diff| {
             let local_file = format!("{}/{}", local_path, diff.path);
             let remote_file = format!("{}/{}", remote_base, diff.path);
-            let file_size = diff.local_size.unwrap_or(0);
-
-            tracker.emit_file_start(&diff.path, file_size);
-
-            // Each thread creates its own SSH connection
-            let result = upload_single_sftp_file(&config, &local_file, &remote_file, &diff.path, file_size, &tracker);
-
-            match result {
-                Ok(_) => tracker.emit_file_complete(&diff.path, file_size),
-                Err(e) => tracker.emit_file_error(&diff.path, &e, file_size),
-            }
-        });
-    });
+            upload_single_sftp_file(sftp, &local_file, &remote_file, diff, &tracker, &created_dirs, upload)
+        },
+        drop,
+    );
 
     // Check results
     let errors = tracker.get_errors();
@@ -264,18 +362,9 @@ pub fn parallel_sftp_sync(
     Ok(())
 }
 
-fn upload_single_sftp_file(
-    config: &SFTPConfig,
-    local_file: &str,
-    remote_file: &str,
-    display_path: &str,
-    file_size: u64,
-    tracker: &ParallelProgressTracker,
-) -> Result<(), String> {
-    // Create new SSH connection for this thread
-    let addr = resolve_addr(&config.host, config.port)?;
-    let tcp = TcpStream::connect_timeout(&addr, Duration::from_secs(10))
-        .map_err(|e| format!("Connection failed: {}", e))?;
+/// SSH session of an upload worker
+fn connect_sftp_worker(config: &SFTPConfig) -> Result<ssh2::Sftp, String> {
+    let tcp = proxy::connect(config.proxy.as_ref(), &config.host, config.port, Duration::from_secs(10))?;
 
     tcp.set_read_timeout(Some(Duration::from_secs(60)))
         .map_err(|e| format!("Failed to set read timeout: {}", e))?;
@@ -285,58 +374,149 @@ fn upload_single_sftp_file(
     let mut sess = ssh2::Session::new().map_err(|e| format!("Session error: {}", e))?;
     sess.set_tcp_stream(tcp);
     sess.handshake().map_err(|e| format!("Handshake failed: {}", e))?;
-    sess.userauth_password(&config.username, &config.password)
-        .map_err(|e| format!("Auth failed: {}", e))?;
+    crate::ssh_auth::authenticate(&sess, config)?;
 
-    let sftp = sess.sftp().map_err(|e| format!("SFTP error: {}", e))?;
+    sess.sftp().map_err(|e| format!("SFTP error: {}", e))
+}
 
+fn upload_single_sftp_file(
+    sftp: &mut ssh2::Sftp,
+    local_file: &str,
+    remote_file: &str,
+    diff: &FileDiff,
+    tracker: &ParallelProgressTracker,
+    created_dirs: &Mutex<HashSet<PathBuf>>,
+    upload: UploadOptions,
+) -> Result<(), String> {
+    let file_size = diff.local_size.unwrap_or(0);
     // Create parent directories if needed
     if let Some(parent) = Path::new(remote_file).parent() {
-        create_sftp_dirs_for_path(&sftp, parent)?;
+        create_sftp_dirs_for_path(sftp, parent, created_dirs)?;
     }
 
-    // Read local file
-    let mut file = File::open(local_file)
+    let file = File::open(local_file)
         .map_err(|e| format!("Failed to open {}: {}", local_file, e))?;
-    let mut contents = Vec::new();
-    file.read_to_end(&mut contents)
-        .map_err(|e| format!("Failed to read {}: {}", local_file, e))?;
 
     // Upload with chunked progress (64KB chunks)
-    let chunk_size = 65536;
-    let mut remote = sftp
-        .create(Path::new(remote_file))
-        .map_err(|e| format!("Failed to create {}: {}", remote_file, e))?;
-
-    let mut bytes_sent = 0u64;
-    for chunk in contents.chunks(chunk_size) {
-        remote
-            .write_all(chunk)
-            .map_err(|e| format!("Failed to write {}: {}", remote_file, e))?;
-
-        bytes_sent += chunk.len() as u64;
-
-        // Emit progress every chunk
-        if file_size > chunk_size as u64 {
-            tracker.emit_file_progress(display_path, bytes_sent, file_size);
+    let mut reader = ProgressReader {
+        inner: file,
+        bytes_sent: 0,
+        report: |bytes_sent: u64| {
+            if file_size > CHUNK_SIZE as u64 {
+                tracker.emit_file_progress(&diff.path, bytes_sent, file_size);
+            }
+        },
+    };
+    atomic_upload::put(sftp, remote_file, upload.atomic, &mut reader)?;
+    sftp.apply_attributes(local_file, remote_file, upload);
+    Ok(())
+}
+
+const CHUNK_SIZE: usize = 65536;
+
+/// Reads at most a chunk at a time, reporting the bytes read so far after each
+struct ProgressReader<R, F> {
+    inner: R,
+    bytes_sent: u64,
+    report: F,
+}
+
+impl<R: Read, F: FnMut(u64)> Read for ProgressReader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = buf.len().min(CHUNK_SIZE);
+        let read = self.inner.read(&mut buf[..len])?;
+        if read > 0 {
+            self.bytes_sent += read as u64;
+            (self.report)(self.bytes_sent);
         }
+        Ok(read)
     }
-
-    Ok(())
 }
 
-fn create_sftp_dirs_for_path(sftp: &ssh2::Sftp, path: &Path) -> Result<(), String> {
-    let mut current = std::path::PathBuf::new();
+fn create_sftp_dirs_for_path(sftp: &ssh2::Sftp, path: &Path, created_dirs: &Mutex<HashSet<PathBuf>>) -> Result<(), String> {
+    let mut current = PathBuf::new();
     for component in path.components() {
         current.push(component);
-        // Try to create, ignore if exists
-        let _ = sftp.mkdir(&current, 0o755);
+        let should_create = created_dirs.lock().map(|dirs| !dirs.contains(&current)).unwrap_or(true);
+        if should_create {
+            // Try to create, ignore if exists
+            let _ = sftp.mkdir(&current, 0o755);
+            if let Ok(mut dirs) = created_dirs.lock() {
+                dirs.insert(current.clone());
+            }
+        }
     }
     Ok(())
 }
 
-/// Parallel FTP sync using multiple FTP connections
+/// Parallel FTP sync over a pool of persistent FTP connections
 pub fn parallel_ftp_sync(
+    local_path: &str,
+    config: &SFTPConfig,
+    diffs: &[FileDiff],
+    project_id: &str,
+    app_handle: &tauri::AppHandle,
+    connections: Connections,
+    upload: UploadOptions,
+) -> Result<(), String> {
+    let files_to_upload: Vec<_> = diffs
+        .iter()
+        .filter(|d| d.status == "added" || d.status == "modified")
+        .collect();
+
+    if files_to_upload.is_empty() {
+        return Ok(());
+    }
+
+    let total_files = files_to_upload.len();
+    let tracker = ParallelProgressTracker::new(
+        project_id.to_string(),
+        total_files,
+        app_handle.clone(),
+        20,
+        70,
+    );
+
+    let actual_connections = limit_connections(connections, total_files);
+    let remote_base = config.remote_path.clone();
+
+    // Track which directories have been created (thread-safe)
+    let created_dirs: Arc<Mutex<HashSet<String>>> = Arc::new(Mutex::new(HashSet::new()));
+
+    run_workers(
+        &files_to_upload,
+        actual_connections,
+        project_id,
+        &tracker,
+        || connect_ftp_worker(config),
+        |ftp, diff| {
+            let local_file = format!("{}/{}", local_path, diff.path);
+            let remote_file = format!("{}/{}", remote_base, diff.path);
+            upload_single_ftp_file(ftp, &local_file, &remote_file, &diff.path, &remote_base, &created_dirs, upload)
+        },
+        |mut ftp| {
+            let _ = ftp.quit();
+        },
+    );
+
+    let errors = tracker.get_errors();
+    if !errors.is_empty() {
+        return Err(format!(
+            "{} fichier(s) en erreur: {}",
+            errors.len(),
+            errors.join(", ")
+        ));
+    }
+
+    if is_cancelled(project_id) {
+        return Err("Synchronisation annulée".to_string());
+    }
+
+    Ok(())
+}
+
+/// Parallel WebDAV sync, the HTTP client being shared by the upload threads
+pub fn parallel_webdav_sync(
     local_path: &str,
     config: &SFTPConfig,
     diffs: &[FileDiff],
@@ -369,13 +549,9 @@ pub fn parallel_ftp_sync(
         .build()
         .map_err(|e| format!("Failed to create thread pool: {}", e))?;
 
-    let local_path = local_path.to_string();
-    let remote_base = config.remote_path.clone();
-    let config = config.clone();
-
-    // Track which directories have been created (thread-safe)
-    let created_dirs: Arc<Mutex<std::collections::HashSet<String>>> =
-        Arc::new(Mutex::new(std::collections::HashSet::new()));
+    let client = crate::webdav::WebDavClient::connect(config)?;
+    let remote_base = config.remote_path.trim_end_matches('/').to_string();
+    let created_dirs: Mutex<std::collections::HashSet<String>> = Mutex::new(std::collections::HashSet::new());
 
     pool.install(|| {
         files_to_upload.par_iter().for_each(|diff| {
@@ -389,16 +565,22 @@ pub fn parallel_ftp_sync(
 
             tracker.emit_file_start(&diff.path, file_size);
 
-            let result = upload_single_ftp_file(
-                &config,
-                &local_file,
-                &remote_file,
-                &diff.path,
-                &remote_base,
-                file_size,
-                &tracker,
-                &created_dirs,
-            );
+            let result: Result<(), String> = (|| {
+                if let Some(parent) = Path::new(&remote_file).parent() {
+                    // Folders are created one at a time so two threads don't MKCOL the same one
+                    let mut created = created_dirs.lock().map_err(|_| "Failed to access created folders".to_string())?;
+                    client.mkdir_all(&parent.to_string_lossy(), &mut created)?;
+                }
+                let contents = std::fs::read(&local_file)
+                    .map_err(|e| format!("Failed to read {}: {}", local_file, e))?;
+                let progress_tracker = tracker.clone();
+                let display_path = diff.path.clone();
+                client.upload(&remote_file, contents, move |sent| {
+                    if file_size > 65536 {
+                        progress_tracker.emit_file_progress(&display_path, sent, file_size);
+                    }
+                })
+            })();
 
             match result {
                 Ok(_) => tracker.emit_file_complete(&diff.path, file_size),
@@ -423,21 +605,11 @@ pub fn parallel_ftp_sync(
     Ok(())
 }
 
-fn upload_single_ftp_file(
-    config: &SFTPConfig,
-    local_file: &str,
-    remote_file: &str,
-    display_path: &str,
-    remote_base: &str,
-    file_size: u64,
-    tracker: &ParallelProgressTracker,
-    created_dirs: &Arc<Mutex<std::collections::HashSet<String>>>,
-) -> Result<(), String> {
-    let addr = resolve_addr(&config.host, config.port)?;
+/// FTP connection of an upload worker
+fn connect_ftp_worker(config: &SFTPConfig) -> Result<suppaftp::FtpStream, String> {
     let passive = config.passive.unwrap_or(true);
 
-    let mut ftp = suppaftp::FtpStream::connect_timeout(addr, Duration::from_secs(10))
-        .map_err(|e| format!("FTP connection failed: {}", e))?;
+    let mut ftp = proxy::connect_ftp(config.proxy.as_ref(), &config.host, config.port, Duration::from_secs(10))?;
 
     ftp.get_ref()
         .set_read_timeout(Some(Duration::from_secs(60)))
@@ -456,25 +628,28 @@ fn upload_single_ftp_file(
     ftp.transfer_type(suppaftp::types::FileType::Binary)
         .map_err(|e| format!("Failed to set binary mode: {}", e))?;
 
+    Ok(ftp)
+}
+
+fn upload_single_ftp_file(
+    ftp: &mut suppaftp::FtpStream,
+    local_file: &str,
+    remote_file: &str,
+    display_path: &str,
+    remote_base: &str,
+    created_dirs: &Arc<Mutex<HashSet<String>>>,
+    upload: UploadOptions,
+) -> Result<(), String> {
     // Create parent directories if needed (with deduplication)
     if let Some(parent) = Path::new(display_path).parent() {
-        create_ftp_dirs_with_cache(&mut ftp, remote_base, parent, created_dirs)?;
+        create_ftp_dirs_with_cache(ftp, remote_base, parent, created_dirs)?;
     }
 
-    // Read local file
+    // Upload file
     let mut file = File::open(local_file)
         .map_err(|e| format!("Failed to open {}: {}", local_file, e))?;
-    let mut contents = Vec::new();
-    file.read_to_end(&mut contents)
-        .map_err(|e| format!("Failed to read {}: {}", local_file, e))?;
-
-    // Upload file
-    let mut cursor = std::io::Cursor::new(contents);
-    ftp.put_file(remote_file, &mut cursor)
-        .map_err(|e| format!("Failed to upload {}: {}", remote_file, e))?;
-
-    let _ = ftp.quit();
-
+    atomic_upload::put(ftp, remote_file, upload.atomic, &mut file)?;
+    ftp.apply_attributes(local_file, remote_file, upload);
     Ok(())
 }
 
@@ -482,7 +657,7 @@ fn create_ftp_dirs_with_cache(
     ftp: &mut suppaftp::FtpStream,
     base: &str,
     path: &Path,
-    created_dirs: &Arc<Mutex<std::collections::HashSet<String>>>,
+    created_dirs: &Arc<Mutex<HashSet<String>>>,
 ) -> Result<(), String> {
     let mut current = base.to_string();
 
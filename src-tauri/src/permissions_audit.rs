@@ -0,0 +1,242 @@
+//! Permissions Audit Module
+//!
+//! Walks a remote tree over SFTP and reports world-writable entries, files
+//! owned by another user than the rest of the site and directories missing
+//! execute bits, which often break uploads on shared hosting. The mode
+//! problems can optionally be fixed with chmod.
+
+use crate::remote_exec;
+use crate::SFTPConfig;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Stop walking after this many entries
+const MAX_AUDITED_ENTRIES: usize = 50_000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionIssue {
+    pub path: String,
+    pub is_dir: bool,
+    /// "world_writable", "owner_mismatch" or "dir_not_executable"
+    pub kind: String,
+    /// Current mode in octal, e.g. "0777"
+    pub mode: String,
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    /// Mode the auto-fix applies, None when chmod cannot fix the issue
+    pub suggested_mode: Option<String>,
+    pub fixed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionAuditResult {
+    pub root: String,
+    pub scanned_files: usize,
+    pub scanned_dirs: usize,
+    /// Owner of most entries, used as the expected owner
+    pub expected_uid: Option<u32>,
+    pub issues: Vec<PermissionIssue>,
+    pub fixed_count: usize,
+    /// The walk stopped at MAX_AUDITED_ENTRIES
+    pub truncated: bool,
+}
+
+struct AuditedEntry {
+    path: String,
+    is_dir: bool,
+    perm: u32,
+    uid: Option<u32>,
+    gid: Option<u32>,
+}
+
+/// Audit `root` (the project's remote path when empty), chmod-fixing the mode issues when `fix` is set
+pub fn audit_permissions(config: &SFTPConfig, root: &str, fix: bool) -> Result<PermissionAuditResult, String> {
+    if config.protocol.as_deref() != Some("sftp") {
+        return Err("L'audit des permissions necessite une connexion SFTP".to_string());
+    }
+    let root = if root.is_empty() { config.remote_path.as_str() } else { root };
+    let root = if root.is_empty() { "." } else { root };
+
+    let sess = remote_exec::connect_ssh(config)?;
+    let sftp = sess.sftp().map_err(|e| format!("SFTP error: {}", e))?;
+
+    let mut entries = Vec::new();
+    let mut truncated = false;
+    walk(&sftp, root, &mut entries, &mut truncated)?;
+
+    let expected_uid = majority_uid(&entries);
+    let mut issues = Vec::new();
+    for entry in &entries {
+        issues.extend(entry_issues(entry, expected_uid));
+    }
+
+    let mut fixed_count = 0;
+    if fix {
+        // Several issues can target the same entry: apply the combined mode once
+        let mut fixes: HashMap<&str, u32> = HashMap::new();
+        for entry in &entries {
+            let fixed_mode = fixed_mode(entry);
+            if fixed_mode != entry.perm & 0o7777 {
+                fixes.insert(entry.path.as_str(), fixed_mode);
+            }
+        }
+        for (path, mode) in &fixes {
+            let stat = ssh2::FileStat {
+                size: None,
+                uid: None,
+                gid: None,
+                perm: Some(*mode),
+                atime: None,
+                mtime: None,
+            };
+            match sftp.setstat(Path::new(path), stat) {
+                Ok(()) => {
+                    fixed_count += 1;
+                    for issue in issues.iter_mut().filter(|i| i.path == *path && i.suggested_mode.is_some()) {
+                        issue.fixed = true;
+                    }
+                }
+                Err(e) => println!("[PermissionsAudit] chmod {:o} {} failed: {}", mode, path, e),
+            }
+        }
+    }
+
+    println!(
+        "[PermissionsAudit] {} entries audited under {}, {} issues, {} fixed",
+        entries.len(),
+        root,
+        issues.len(),
+        fixed_count
+    );
+
+    Ok(PermissionAuditResult {
+        root: root.to_string(),
+        scanned_files: entries.iter().filter(|e| !e.is_dir).count(),
+        scanned_dirs: entries.iter().filter(|e| e.is_dir).count(),
+        expected_uid,
+        issues,
+        fixed_count,
+        truncated,
+    })
+}
+
+fn walk(sftp: &ssh2::Sftp, dir: &str, entries: &mut Vec<AuditedEntry>, truncated: &mut bool) -> Result<(), String> {
+    let listing = sftp
+        .readdir(Path::new(dir))
+        .map_err(|e| format!("Failed to read dir {}: {}", dir, e))?;
+
+    for (path_buf, stat) in listing {
+        if entries.len() >= MAX_AUDITED_ENTRIES {
+            *truncated = true;
+            return Ok(());
+        }
+        // Symlink modes are meaningless and their target may be outside the site
+        if stat.file_type().is_symlink() {
+            continue;
+        }
+        let path = path_buf.to_string_lossy().to_string();
+        let is_dir = stat.is_dir();
+        entries.push(AuditedEntry {
+            path: path.clone(),
+            is_dir,
+            perm: stat.perm.unwrap_or(0),
+            uid: stat.uid,
+            gid: stat.gid,
+        });
+        if is_dir {
+            walk(sftp, &path, entries, truncated)?;
+        }
+    }
+    Ok(())
+}
+
+fn majority_uid(entries: &[AuditedEntry]) -> Option<u32> {
+    let mut counts: HashMap<u32, usize> = HashMap::new();
+    for uid in entries.iter().filter_map(|e| e.uid) {
+        *counts.entry(uid).or_default() += 1;
+    }
+    counts.into_iter().max_by_key(|(_, count)| *count).map(|(uid, _)| uid)
+}
+
+fn entry_issues(entry: &AuditedEntry, expected_uid: Option<u32>) -> Vec<PermissionIssue> {
+    let mode = entry.perm & 0o7777;
+    let fixed_mode = fixed_mode(entry);
+    let issue = |kind: &str, suggested_mode: Option<String>| PermissionIssue {
+        path: entry.path.clone(),
+        is_dir: entry.is_dir,
+        kind: kind.to_string(),
+        mode: format!("{:04o}", mode),
+        uid: entry.uid,
+        gid: entry.gid,
+        suggested_mode,
+        fixed: false,
+    };
+
+    let mut issues = Vec::new();
+    if mode & 0o002 != 0 {
+        issues.push(issue("world_writable", Some(format!("{:04o}", fixed_mode))));
+    }
+    if entry.is_dir && mode & 0o111 != 0o111 {
+        issues.push(issue("dir_not_executable", Some(format!("{:04o}", fixed_mode))));
+    }
+    if let (Some(uid), Some(expected)) = (entry.uid, expected_uid) {
+        if uid != expected {
+            // chown needs root, only report it
+            issues.push(issue("owner_mismatch", None));
+        }
+    }
+    issues
+}
+
+/// Mode without world write and, for directories, with every execute bit
+fn fixed_mode(entry: &AuditedEntry) -> u32 {
+    let mut mode = entry.perm & 0o7777 & !0o002;
+    if entry.is_dir {
+        mode |= 0o111;
+    }
+    mode
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, is_dir: bool, perm: u32, uid: u32) -> AuditedEntry {
+        AuditedEntry { path: path.to_string(), is_dir, perm, uid: Some(uid), gid: Some(33) }
+    }
+
+    #[test]
+    fn test_issues_and_their_fixes() {
+        let entries = [
+            entry("/www", true, 0o40755, 1000),
+            entry("/www/index.php", false, 0o100644, 1000),
+            entry("/www/uploads", true, 0o40777, 1000),
+            entry("/www/cache", true, 0o40750, 1000),
+            entry("/www/config.php", false, 0o100666, 33),
+        ];
+        let expected = majority_uid(&entries);
+        assert_eq!(expected, Some(1000));
+
+        let issues: Vec<(String, String, Option<String>)> = entries
+            .iter()
+            .flat_map(|e| entry_issues(e, expected))
+            .map(|i| (i.path, i.kind, i.suggested_mode))
+            .collect();
+        let issue = |path: &str, kind: &str, mode: Option<&str>| {
+            (path.to_string(), kind.to_string(), mode.map(str::to_string))
+        };
+        assert_eq!(
+            issues,
+            vec![
+                issue("/www/uploads", "world_writable", Some("0775")),
+                issue("/www/cache", "dir_not_executable", Some("0751")),
+                issue("/www/config.php", "world_writable", Some("0664")),
+                // chown needs root: reported without a fix
+                issue("/www/config.php", "owner_mismatch", None),
+            ]
+        );
+        // Special bits are kept
+        assert_eq!(fixed_mode(&entry("/www/tmp", true, 0o41777, 1000)), 0o1775);
+    }
+}
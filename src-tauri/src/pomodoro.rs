@@ -0,0 +1,352 @@
+//! Pomodoro Module
+//!
+//! Optional work/break interval mode for time tracking: counts down work
+//! sessions and breaks, shows the countdown in the tray, notifies at each
+//! phase change and keeps per-day and per-project session statistics.
+
+use crate::{notifications, state_file};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// User settings for the work/break cycle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PomodoroSettings {
+    pub work_minutes: u64,
+    pub short_break_minutes: u64,
+    pub long_break_minutes: u64,
+    /// Work sessions before a long break
+    pub sessions_before_long_break: u32,
+    pub auto_start_breaks: bool,
+    pub auto_start_work: bool,
+    pub notifications: bool,
+    pub show_in_tray: bool,
+}
+
+impl Default for PomodoroSettings {
+    fn default() -> Self {
+        Self {
+            work_minutes: 25,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            sessions_before_long_break: 4,
+            auto_start_breaks: true,
+            auto_start_work: false,
+            notifications: true,
+            show_in_tray: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PomodoroPhase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+/// Current state of the cycle, also the payload of `pomodoro-tick`
+#[derive(Debug, Clone, Serialize)]
+pub struct PomodoroStatus {
+    pub project_id: String,
+    pub phase: PomodoroPhase,
+    pub remaining_seconds: u64,
+    pub paused: bool,
+    /// Work sessions completed since the last long break
+    pub completed_in_cycle: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DailyPomodoroStats {
+    pub work_sessions: u32,
+    pub focus_seconds: u64,
+    pub breaks_taken: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ProjectPomodoroStats {
+    pub work_sessions: u32,
+    pub focus_seconds: u64,
+    pub last_session: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PomodoroStats {
+    pub total_work_sessions: u32,
+    pub total_focus_seconds: u64,
+    /// Keyed by `YYYY-MM-DD`
+    pub daily: BTreeMap<String, DailyPomodoroStats>,
+    pub projects: HashMap<String, ProjectPomodoroStats>,
+}
+
+/// Completed work session, emitted on `pomodoro-work-complete` so the
+/// frontend records it with the regular time tracking
+#[derive(Debug, Clone, Serialize)]
+pub struct PomodoroWorkEvent {
+    pub project_id: String,
+    pub duration_seconds: u64,
+    pub completed_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct PomodoroData {
+    settings: PomodoroSettings,
+    stats: PomodoroStats,
+}
+
+#[derive(Default)]
+struct PomodoroState {
+    data: Option<PomodoroData>,
+    current: Option<PomodoroStatus>,
+    /// Incremented on every start so a previous countdown thread exits
+    generation: u64,
+}
+
+static POMODORO: Lazy<Mutex<PomodoroState>> = Lazy::new(|| Mutex::new(PomodoroState::default()));
+
+fn data_path(app: &AppHandle) -> Result<PathBuf, String> {
+    crate::data_location::app_data_dir(app)
+        .map(|dir| dir.join("pomodoro.json"))
+        .ok_or_else(|| "Could not resolve app data directory".to_string())
+}
+
+fn load_data(path: &Path) -> PomodoroData {
+    state_file::read_json(path).ok().flatten().unwrap_or_default()
+}
+
+fn save_data(app: &AppHandle, data: &PomodoroData) -> Result<(), String> {
+    state_file::write_json(&data_path(app)?, data)
+}
+
+/// Run `f` on the loaded data, reading it from disk on first use
+fn with_data<T>(app: &AppHandle, f: impl FnOnce(&mut PomodoroData) -> T) -> Result<T, String> {
+    let mut state = POMODORO.lock().map_err(|e| e.to_string())?;
+    if state.data.is_none() {
+        state.data = Some(load_data(&data_path(app)?));
+    }
+    Ok(f(state.data.get_or_insert_with(PomodoroData::default)))
+}
+
+fn phase_seconds(settings: &PomodoroSettings, phase: PomodoroPhase) -> u64 {
+    let minutes = match phase {
+        PomodoroPhase::Work => settings.work_minutes,
+        PomodoroPhase::ShortBreak => settings.short_break_minutes,
+        PomodoroPhase::LongBreak => settings.long_break_minutes,
+    };
+    minutes.max(1) * 60
+}
+
+pub fn get_settings(app: &AppHandle) -> Result<PomodoroSettings, String> {
+    with_data(app, |data| data.settings.clone())
+}
+
+pub fn set_settings(app: &AppHandle, settings: PomodoroSettings) -> Result<(), String> {
+    let data = with_data(app, |data| {
+        data.settings = settings;
+        data.clone()
+    })?;
+    save_data(app, &data)
+}
+
+pub fn get_stats(app: &AppHandle) -> Result<PomodoroStats, String> {
+    with_data(app, |data| data.stats.clone())
+}
+
+pub fn status() -> Option<PomodoroStatus> {
+    POMODORO.lock().ok().and_then(|state| state.current.clone())
+}
+
+/// Start a work session for a project and the countdown thread
+pub fn start(app: AppHandle, project_id: String) -> Result<PomodoroStatus, String> {
+    let settings = get_settings(&app)?;
+    let (status, generation) = {
+        let mut state = POMODORO.lock().map_err(|e| e.to_string())?;
+        state.generation += 1;
+        let status = PomodoroStatus {
+            project_id,
+            phase: PomodoroPhase::Work,
+            remaining_seconds: phase_seconds(&settings, PomodoroPhase::Work),
+            paused: false,
+            completed_in_cycle: 0,
+        };
+        state.current = Some(status.clone());
+        (status, state.generation)
+    };
+
+    println!("[Pomodoro] Started for project: {}", status.project_id);
+    thread::spawn(move || run_countdown(app, generation));
+    Ok(status)
+}
+
+pub fn set_paused(paused: bool) -> Result<(), String> {
+    let mut state = POMODORO.lock().map_err(|e| e.to_string())?;
+    match state.current.as_mut() {
+        Some(current) => {
+            current.paused = paused;
+            Ok(())
+        }
+        None => Err("Aucune session Pomodoro en cours".to_string()),
+    }
+}
+
+/// End the current phase now (e.g. skip a break)
+pub fn skip() -> Result<(), String> {
+    let mut state = POMODORO.lock().map_err(|e| e.to_string())?;
+    match state.current.as_mut() {
+        Some(current) => {
+            current.remaining_seconds = 0;
+            current.paused = false;
+            Ok(())
+        }
+        None => Err("Aucune session Pomodoro en cours".to_string()),
+    }
+}
+
+pub fn stop(app: &AppHandle) {
+    if let Ok(mut state) = POMODORO.lock() {
+        state.current = None;
+        state.generation += 1;
+    }
+    let _ = app.tray_handle().set_title("");
+}
+
+fn run_countdown(app: AppHandle, generation: u64) {
+    loop {
+        thread::sleep(Duration::from_secs(1));
+
+        let (status, finished) = {
+            let mut state = match POMODORO.lock() {
+                Ok(state) => state,
+                Err(_) => return,
+            };
+            if state.generation != generation {
+                return;
+            }
+            let current = match state.current.as_mut() {
+                Some(current) => current,
+                None => return,
+            };
+            if !current.paused {
+                current.remaining_seconds = current.remaining_seconds.saturating_sub(1);
+            }
+            (current.clone(), current.remaining_seconds == 0)
+        };
+
+        let settings = get_settings(&app).unwrap_or_default();
+        if settings.show_in_tray {
+            let _ = app.tray_handle().set_title(&tray_label(&status));
+        }
+        let _ = app.emit_all(crate::events::POMODORO_TICK, &status);
+
+        if finished {
+            complete_phase(&app, &settings, &status);
+        }
+    }
+}
+
+/// Record the finished phase and move to the next one
+fn complete_phase(app: &AppHandle, settings: &PomodoroSettings, finished: &PomodoroStatus) {
+    let completed_at = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let work_seconds = phase_seconds(settings, PomodoroPhase::Work);
+
+    let recorded = with_data(app, |data| {
+        let day = data.stats.daily.entry(today).or_default();
+        if finished.phase == PomodoroPhase::Work {
+            day.work_sessions += 1;
+            day.focus_seconds += work_seconds;
+            data.stats.total_work_sessions += 1;
+            data.stats.total_focus_seconds += work_seconds;
+            let project = data.stats.projects.entry(finished.project_id.clone()).or_default();
+            project.work_sessions += 1;
+            project.focus_seconds += work_seconds;
+            project.last_session = Some(completed_at.clone());
+        } else {
+            day.breaks_taken += 1;
+        }
+        data.clone()
+    });
+    if let Ok(data) = recorded {
+        if let Err(e) = save_data(app, &data) {
+            println!("[Pomodoro] {}", e);
+        }
+    }
+
+    let (next_phase, completed_in_cycle) = match finished.phase {
+        PomodoroPhase::Work => {
+            let completed = finished.completed_in_cycle + 1;
+            if completed >= settings.sessions_before_long_break.max(1) {
+                (PomodoroPhase::LongBreak, completed)
+            } else {
+                (PomodoroPhase::ShortBreak, completed)
+            }
+        }
+        PomodoroPhase::LongBreak => (PomodoroPhase::Work, 0),
+        PomodoroPhase::ShortBreak => (PomodoroPhase::Work, finished.completed_in_cycle),
+    };
+    let auto_start = match next_phase {
+        PomodoroPhase::Work => settings.auto_start_work,
+        _ => settings.auto_start_breaks,
+    };
+
+    if let Ok(mut state) = POMODORO.lock() {
+        if let Some(current) = state.current.as_mut() {
+            current.phase = next_phase;
+            current.remaining_seconds = phase_seconds(settings, next_phase);
+            current.completed_in_cycle = completed_in_cycle;
+            current.paused = !auto_start;
+        }
+    }
+
+    if finished.phase == PomodoroPhase::Work {
+        crate::activity_feed::record(
+            app,
+            crate::activity_feed::new_event(
+                &finished.project_id,
+                "timer",
+                "Session pomodoro terminee",
+                Some(format!("{} min", work_seconds / 60)),
+                None,
+                serde_json::json!({ "durationSeconds": work_seconds }),
+            ),
+        );
+        let _ = app.emit_all(crate::events::POMODORO_WORK_COMPLETE, PomodoroWorkEvent {
+            project_id: finished.project_id.clone(),
+            duration_seconds: work_seconds,
+            completed_at,
+        });
+    }
+    let _ = app.emit_all(crate::events::POMODORO_PHASE_CHANGE, status());
+
+    if settings.notifications {
+        let (title, body) = match next_phase {
+            PomodoroPhase::Work => ("Pause terminee", "C'est reparti pour une session de travail."),
+            PomodoroPhase::ShortBreak => ("Session terminee", "Prenez une courte pause."),
+            PomodoroPhase::LongBreak => ("Cycle termine", "Prenez une longue pause."),
+        };
+        notifications::show(app, Some(&finished.project_id), title, body);
+    }
+}
+
+/// Tray countdown, e.g. `🍅 24:59` or `☕ 04:12`
+fn tray_label(status: &PomodoroStatus) -> String {
+    let icon = match status.phase {
+        PomodoroPhase::Work => "🍅",
+        _ => "☕",
+    };
+    let pause = if status.paused { " ⏸" } else { "" };
+    format!(
+        "{} {:02}:{:02}{}",
+        icon,
+        status.remaining_seconds / 60,
+        status.remaining_seconds % 60,
+        pause
+    )
+}
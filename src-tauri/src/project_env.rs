@@ -0,0 +1,173 @@
+//! Project Env Module
+//!
+//! Per-project variables (`API_URL`, `DEPLOY_TOKEN`...) handed to the local
+//! build command and remote commands as environment variables. `${NAME}` is
+//! replaced in the maintenance page and in config values sent through
+//! `substitute_project_vars`, `{NAME}` in notification templates. Plain
+//! values are kept in `project_env.json`; secret ones only in the keyring,
+//! the listing never returns them.
+
+use crate::{state_file, KEYRING_SERVICE};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectVariable {
+    pub name: String,
+    /// Always `None` for secrets
+    pub value: Option<String>,
+    pub secret: bool,
+}
+
+fn store_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("project_env.json")
+}
+
+fn load_all(app_data_dir: &Path) -> HashMap<String, Vec<ProjectVariable>> {
+    state_file::read_json(&store_path(app_data_dir)).ok().flatten().unwrap_or_default()
+}
+
+/// Keyring key of a secret variable
+pub fn keyring_key(project_id: &str, name: &str) -> String {
+    format!("env-{}-{}", project_id, name)
+}
+
+fn keyring_entry(project_id: &str, name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, &keyring_key(project_id, name)).map_err(|e| format!("Keyring error: {}", e))
+}
+
+/// Shell-compatible names only, so they can be exported as-is
+pub fn check_name(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let valid = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!("Nom de variable invalide: {} (lettres, chiffres et _)", name));
+    }
+    Ok(())
+}
+
+/// Variables of a project sorted by name, secret values left out
+pub fn list(app_data_dir: &Path, project_id: &str) -> Vec<ProjectVariable> {
+    let mut variables = load_all(app_data_dir).remove(project_id).unwrap_or_default();
+    variables.sort_by(|a, b| a.name.cmp(&b.name));
+    variables
+}
+
+/// Add or replace a variable; a secret value goes to the keyring only
+pub fn set(app_data_dir: &Path, project_id: &str, name: &str, value: &str, secret: bool) -> Result<(), String> {
+    check_name(name)?;
+    let mut all = load_all(app_data_dir);
+    let variables = all.entry(project_id.to_string()).or_default();
+    let was_secret = variables.iter().any(|v| v.name == name && v.secret);
+
+    if secret {
+        keyring_entry(project_id, name)?
+            .set_password(value)
+            .map_err(|e| format!("Failed to save secret: {}", e))?;
+    } else if was_secret {
+        let _ = keyring_entry(project_id, name).and_then(|entry| entry.delete_credential().map_err(|e| e.to_string()));
+    }
+
+    variables.retain(|v| v.name != name);
+    variables.push(ProjectVariable {
+        name: name.to_string(),
+        value: if secret { None } else { Some(value.to_string()) },
+        secret,
+    });
+    state_file::write_json(&store_path(app_data_dir), &all)
+}
+
+pub fn remove(app_data_dir: &Path, project_id: &str, name: &str) -> Result<(), String> {
+    let mut all = load_all(app_data_dir);
+    let variables = match all.get_mut(project_id) {
+        Some(variables) => variables,
+        None => return Ok(()),
+    };
+    if variables.iter().any(|v| v.name == name && v.secret) {
+        let _ = keyring_entry(project_id, name).and_then(|entry| entry.delete_credential().map_err(|e| e.to_string()));
+    }
+    variables.retain(|v| v.name != name);
+    if variables.is_empty() {
+        all.remove(project_id);
+    }
+    state_file::write_json(&store_path(app_data_dir), &all)
+}
+
+/// Every variable with its value, secrets read from the keyring.
+/// A secret missing from the keyring is left out rather than failing the caller.
+pub fn resolve(app_data_dir: &Path, project_id: &str) -> Vec<(String, String)> {
+    list(app_data_dir, project_id)
+        .into_iter()
+        .filter_map(|variable| {
+            let value = match (variable.secret, variable.value) {
+                (false, value) => value.unwrap_or_default(),
+                (true, _) => match keyring_entry(project_id, &variable.name).and_then(|entry| {
+                    entry.get_password().map_err(|e| e.to_string())
+                }) {
+                    Ok(value) => value,
+                    Err(e) => {
+                        println!("[ProjectEnv] Secret {} of {} unavailable: {}", variable.name, project_id, e);
+                        return None;
+                    }
+                },
+            };
+            Some((variable.name, value))
+        })
+        .collect()
+}
+
+/// Replace `${NAME}` with the variable's value; unknown names are left as written
+pub fn substitute(text: &str, variables: &[(String, String)]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find('}') {
+            Some(end) => {
+                let name = &after[..end];
+                match variables.iter().find(|(n, _)| n == name) {
+                    Some((_, value)) => result.push_str(value),
+                    None => result.push_str(&rest[start..start + 3 + end]),
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                result.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// `export NAME='value'; ` prefix handing the variables to a remote POSIX shell
+pub fn export_prefix(variables: &[(String, String)]) -> String {
+    variables
+        .iter()
+        .map(|(name, value)| format!("export {}={}; ", name, crate::remote_exec::shell_quote(value)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substitution_and_export() {
+        let variables = vec![
+            ("DOMAIN".to_string(), "example.com".to_string()),
+            ("TOKEN".to_string(), "it's".to_string()),
+        ];
+        assert_eq!(
+            substitute("/home/${DOMAIN}/www ${MISSING} ${DOMAIN", &variables),
+            "/home/example.com/www ${MISSING} ${DOMAIN"
+        );
+        assert_eq!(export_prefix(&variables[1..]), "export TOKEN='it'\\''s'; ");
+        assert!(check_name("API_URL").is_ok());
+        assert!(check_name("9LIVES").is_err() && check_name("A-B").is_err() && check_name("").is_err());
+    }
+}
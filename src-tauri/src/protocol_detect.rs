@@ -0,0 +1,260 @@
+//! Protocol Detection Module
+//!
+//! Probes a host to find which transfer protocols it accepts
+//! (SFTP on 22, FTPS/FTP on 21), collects server banners and
+//! advertised features, and suggests a project configuration.
+
+use crate::{proxy, SFTPConfig};
+use serde::Serialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Common document roots looked up after a successful login
+const COMMON_DOCROOTS: [&str; 4] = ["public_html", "www", "htdocs", "httpdocs"];
+
+/// Result of probing a single protocol/port combination
+#[derive(Debug, Clone, Serialize)]
+pub struct ProtocolProbe {
+    pub protocol: String,
+    pub port: u16,
+    pub reachable: bool,
+    pub authenticated: bool,
+    pub banner: Option<String>,
+    pub features: Vec<String>,
+    pub remote_home: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+impl ProtocolProbe {
+    fn new(protocol: &str, port: u16) -> Self {
+        Self {
+            protocol: protocol.to_string(),
+            port,
+            reachable: false,
+            authenticated: false,
+            banner: None,
+            features: Vec::new(),
+            remote_home: None,
+            error: None,
+            duration_ms: 0,
+        }
+    }
+}
+
+/// Full detection report with the configuration to pre-fill
+#[derive(Debug, Clone, Serialize)]
+pub struct ProtocolDetectionResult {
+    pub host: String,
+    pub probes: Vec<ProtocolProbe>,
+    pub suggested_protocol: Option<String>,
+    pub suggested_port: Option<u16>,
+    pub suggested_remote_path: Option<String>,
+}
+
+/// Probe all candidate protocols for the configured host.
+///
+/// Protocols are tried in order of preference (SFTP, FTPS, FTP); the first
+/// one that authenticates becomes the suggestion. A non-standard port from
+/// the config is probed before the well-known ports.
+pub fn detect_protocols(config: &SFTPConfig) -> ProtocolDetectionResult {
+    let mut candidates: Vec<(&str, u16)> = Vec::new();
+    if config.port != 0 && config.port != 21 && config.port != 22 {
+        candidates.push(("sftp", config.port));
+        candidates.push(("ftps", config.port));
+        candidates.push(("ftp", config.port));
+    }
+    candidates.push(("sftp", 22));
+    candidates.push(("ftps", 21));
+    candidates.push(("ftp", 21));
+
+    let mut probes = Vec::new();
+    for (protocol, port) in candidates {
+        println!("[Detect] Probing {} on {}:{}", protocol, config.host, port);
+        let started = Instant::now();
+        let mut probe = match protocol {
+            "sftp" => probe_sftp(config, port),
+            "ftps" => probe_ftp(config, port, true),
+            _ => probe_ftp(config, port, false),
+        };
+        probe.duration_ms = started.elapsed().as_millis() as u64;
+        probes.push(probe);
+    }
+
+    let best = probes.iter().find(|p| p.authenticated);
+
+    ProtocolDetectionResult {
+        host: config.host.clone(),
+        suggested_protocol: best.map(|p| p.protocol.clone()),
+        suggested_port: best.map(|p| p.port),
+        suggested_remote_path: best.and_then(|p| p.remote_home.clone()),
+        probes,
+    }
+}
+
+fn probe_sftp(config: &SFTPConfig, port: u16) -> ProtocolProbe {
+    let mut probe = ProtocolProbe::new("sftp", port);
+
+    let result: Result<(), String> = (|| {
+        let tcp = proxy::connect(config.proxy.as_ref(), &config.host, port, Duration::from_secs(5))?;
+        tcp.set_read_timeout(Some(Duration::from_secs(15)))
+            .map_err(|e| format!("Failed to set read timeout: {}", e))?;
+
+        let mut sess = ssh2::Session::new().map_err(|e| format!("Failed to create session: {}", e))?;
+        sess.set_tcp_stream(tcp);
+        sess.handshake()
+            .map_err(|e| format!("SSH handshake failed: {}", e))?;
+        probe.reachable = true;
+        probe.banner = sess.banner().map(|b| b.to_string());
+
+        if let Some(kex) = sess.methods(ssh2::MethodType::Kex) {
+            probe.features.push(format!("kex: {}", kex));
+        }
+        if let Some(host_key) = sess.methods(ssh2::MethodType::HostKey) {
+            probe.features.push(format!("hostkey: {}", host_key));
+        }
+
+        // The host key is checked against the probed port, not the configured one
+        let probed = SFTPConfig { port, ..config.clone() };
+        crate::ssh_auth::authenticate(&sess, &probed)?;
+        probe.authenticated = sess.authenticated();
+
+        if let Ok(sftp) = sess.sftp() {
+            if let Ok(home) = sftp.realpath(Path::new(".")) {
+                let home = home.to_string_lossy().to_string();
+                let docroot = COMMON_DOCROOTS.iter().find_map(|dir| {
+                    let candidate = format!("{}/{}", home.trim_end_matches('/'), dir);
+                    match sftp.stat(Path::new(&candidate)) {
+                        Ok(stat) if stat.is_dir() => Some(candidate),
+                        _ => None,
+                    }
+                });
+                probe.remote_home = Some(docroot.unwrap_or(home));
+            }
+        }
+
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        probe.error = Some(e);
+    }
+    probe
+}
+
+fn probe_ftp(config: &SFTPConfig, port: u16, secure: bool) -> ProtocolProbe {
+    let mut probe = ProtocolProbe::new(if secure { "ftps" } else { "ftp" }, port);
+
+    let result: Result<(), String> = (|| {
+        if secure {
+            let timeout = Duration::from_secs(5);
+            let ftp = match &config.proxy {
+                Some(proxy) => {
+                    let tcp = proxy::connect(Some(proxy), &config.host, port, timeout)?;
+                    suppaftp::NativeTlsFtpStream::connect_with_stream(tcp)
+                        .map(|ftp| ftp.passive_stream_builder(proxy::passive_stream_builder(proxy, timeout)))
+                }
+                None => suppaftp::NativeTlsFtpStream::connect_timeout(crate::resolve_addr(&config.host, port)?, timeout),
+            }
+            .map_err(|e| format!("FTP connection failed: {}", e))?;
+            ftp.get_ref()
+                .set_read_timeout(Some(Duration::from_secs(15)))
+                .map_err(|e| format!("Failed to set read timeout: {}", e))?;
+            probe.reachable = true;
+            probe.banner = ftp.get_welcome_msg().map(|m| m.trim().to_string());
+
+            let connector = native_tls::TlsConnector::builder()
+                .danger_accept_invalid_certs(config.accept_invalid_certs.unwrap_or(false))
+                .build()
+                .map_err(|e| format!("TLS setup failed: {}", e))?;
+            let mut ftp = ftp
+                .into_secure(suppaftp::NativeTlsConnector::from(connector), &config.host)
+                .map_err(|e| format!("AUTH TLS failed: {}", e))?;
+
+            if let Ok(features) = ftp.feat() {
+                probe.features = features_to_list(features);
+            }
+            ftp.login(&config.username, &config.password)
+                .map_err(|e| format!("FTP login failed: {}", e))?;
+            probe.authenticated = true;
+            ftp.set_mode(suppaftp::Mode::Passive);
+            if let Ok(pwd) = ftp.pwd() {
+                let entries = ftp.nlst(None).unwrap_or_default();
+                probe.remote_home = Some(pick_ftp_docroot(&pwd, &entries));
+            }
+            let _ = ftp.quit();
+        } else {
+            let mut ftp = proxy::connect_ftp(config.proxy.as_ref(), &config.host, port, Duration::from_secs(5))?;
+            ftp.get_ref()
+                .set_read_timeout(Some(Duration::from_secs(15)))
+                .map_err(|e| format!("Failed to set read timeout: {}", e))?;
+            probe.reachable = true;
+            probe.banner = ftp.get_welcome_msg().map(|m| m.trim().to_string());
+
+            if let Ok(features) = ftp.feat() {
+                probe.features = features_to_list(features);
+            }
+            ftp.login(&config.username, &config.password)
+                .map_err(|e| format!("FTP login failed: {}", e))?;
+            probe.authenticated = true;
+            ftp.set_mode(suppaftp::Mode::Passive);
+            if let Ok(pwd) = ftp.pwd() {
+                let entries = ftp.nlst(None).unwrap_or_default();
+                probe.remote_home = Some(pick_ftp_docroot(&pwd, &entries));
+            }
+            let _ = ftp.quit();
+        }
+
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        probe.error = Some(e);
+    }
+    probe
+}
+
+fn features_to_list(features: suppaftp::Features) -> Vec<String> {
+    let mut list: Vec<String> = features
+        .into_iter()
+        .map(|(name, value)| match value {
+            Some(v) => format!("{} {}", name, v),
+            None => name,
+        })
+        .collect();
+    list.sort();
+    list
+}
+
+fn pick_ftp_docroot(pwd: &str, entries: &[String]) -> String {
+    let base = pwd.trim_end_matches('/');
+    for dir in COMMON_DOCROOTS {
+        if entries
+            .iter()
+            .any(|e| e.trim_end_matches('/').rsplit('/').next() == Some(dir))
+        {
+            return format!("{}/{}", base, dir);
+        }
+    }
+    if base.is_empty() {
+        "/".to_string()
+    } else {
+        base.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ftp_docroot_guess() {
+        let entries = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        let listing = entries(&["/home/client/logs", "/home/client/www/"]);
+        assert_eq!(pick_ftp_docroot("/home/client/", &listing), "/home/client/www");
+        // The first known name wins, whatever the listing order
+        assert_eq!(pick_ftp_docroot("/", &entries(&["htdocs", "public_html"])), "/public_html");
+        assert_eq!(pick_ftp_docroot("/", &entries(&["logs"])), "/");
+        assert_eq!(pick_ftp_docroot("/srv/site", &[]), "/srv/site");
+    }
+}
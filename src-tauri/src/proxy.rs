@@ -0,0 +1,308 @@
+//! Proxy Module
+//!
+//! Servers only reachable through a jump proxy. A target's `proxy` routes its
+//! SFTP and FTP connections through a SOCKS5 (RFC 1928, with the
+//! username/password method of RFC 1929) or HTTP CONNECT tunnel. The target
+//! host name is handed to the proxy, which resolves it. FTP passive data
+//! connections take the same tunnel; active mode can't cross a proxy. WebDAV
+//! and the scrapers give the proxy to reqwest, and rsync to ssh as a
+//! `ProxyCommand`.
+
+use crate::resolve_addr;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpStream};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    /// "socks5" or "http" (CONNECT)
+    pub protocol: String,
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl ProxyConfig {
+    fn credentials(&self) -> Option<(&str, &str)> {
+        let username = self.username.as_deref().filter(|u| !u.is_empty())?;
+        Some((username, self.password.as_deref().unwrap_or("")))
+    }
+}
+
+/// TCP connection to `host:port`, tunnelled through the proxy when there is one
+pub fn connect(proxy: Option<&ProxyConfig>, host: &str, port: u16, timeout: Duration) -> Result<TcpStream, String> {
+    let proxy = match proxy {
+        Some(proxy) => proxy,
+        None => {
+            let addr = resolve_addr(host, port)?;
+            return TcpStream::connect_timeout(&addr, timeout).map_err(|e| format!("Connection failed: {}", e));
+        }
+    };
+    let addr = resolve_addr(&proxy.host, proxy.port)?;
+    let mut stream =
+        TcpStream::connect_timeout(&addr, timeout).map_err(|e| format!("Proxy connection failed: {}", e))?;
+    // Bound the handshake; callers set their own timeouts afterwards
+    let _ = stream.set_read_timeout(Some(timeout));
+    let _ = stream.set_write_timeout(Some(timeout));
+    match proxy.protocol.as_str() {
+        "socks5" => socks5_handshake(&mut stream, proxy, host, port)?,
+        "http" => http_connect(&mut stream, proxy, host, port)?,
+        other => return Err(format!("Unknown proxy protocol: {}", other)),
+    }
+    let _ = stream.set_read_timeout(None);
+    let _ = stream.set_write_timeout(None);
+    Ok(stream)
+}
+
+/// FTP control connection; passive data connections go through the same proxy
+pub fn connect_ftp(
+    proxy: Option<&ProxyConfig>,
+    host: &str,
+    port: u16,
+    timeout: Duration,
+) -> Result<suppaftp::FtpStream, String> {
+    let ftp = match proxy {
+        None => suppaftp::FtpStream::connect_timeout(resolve_addr(host, port)?, timeout),
+        Some(proxy) => suppaftp::FtpStream::connect_with_stream(connect(Some(proxy), host, port, timeout)?)
+            .map(|ftp| ftp.passive_stream_builder(passive_stream_builder(proxy, timeout))),
+    };
+    ftp.map_err(|e| format!("FTP connection failed: {}", e))
+}
+
+/// Opens the data connections announced by PASV/EPSV through the proxy
+pub fn passive_stream_builder(
+    proxy: &ProxyConfig,
+    timeout: Duration,
+) -> impl Fn(&SocketAddr) -> suppaftp::FtpResult<TcpStream> + Send + Sync + 'static {
+    let proxy = proxy.clone();
+    move |addr: &SocketAddr| {
+        connect(Some(&proxy), &addr.ip().to_string(), addr.port(), timeout)
+            .map_err(|e| suppaftp::FtpError::ConnectionError(std::io::Error::other(e)))
+    }
+}
+
+fn io_error(e: std::io::Error) -> String {
+    format!("Proxy handshake failed: {}", e)
+}
+
+fn socks5_handshake(stream: &mut TcpStream, proxy: &ProxyConfig, host: &str, port: u16) -> Result<(), String> {
+    let credentials = proxy.credentials();
+    let methods: &[u8] = if credentials.is_some() { &[0x00, 0x02] } else { &[0x00] };
+    let mut greeting = vec![0x05, methods.len() as u8];
+    greeting.extend_from_slice(methods);
+    stream.write_all(&greeting).map_err(io_error)?;
+
+    let mut choice = [0u8; 2];
+    stream.read_exact(&mut choice).map_err(io_error)?;
+    match choice {
+        [0x05, 0x00] => {}
+        [0x05, 0x02] => {
+            let (username, password) = credentials.ok_or("SOCKS5 proxy requires a username")?;
+            if username.len() > 255 || password.len() > 255 {
+                return Err("SOCKS5 credentials are limited to 255 bytes".to_string());
+            }
+            let mut auth = vec![0x01, username.len() as u8];
+            auth.extend_from_slice(username.as_bytes());
+            auth.push(password.len() as u8);
+            auth.extend_from_slice(password.as_bytes());
+            stream.write_all(&auth).map_err(io_error)?;
+            let mut status = [0u8; 2];
+            stream.read_exact(&mut status).map_err(io_error)?;
+            if status[1] != 0x00 {
+                return Err("SOCKS5 proxy rejected the credentials".to_string());
+            }
+        }
+        _ => return Err("SOCKS5 proxy accepts none of the offered authentication methods".to_string()),
+    }
+
+    stream.write_all(&socks5_request(host, port)?).map_err(io_error)?;
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply).map_err(io_error)?;
+    if reply[1] != 0x00 {
+        return Err(format!("SOCKS5 proxy could not reach {}:{}: {}", host, port, socks5_error(reply[1])));
+    }
+    // Skip the bound address the proxy reports
+    let address_len = match reply[3] {
+        0x01 => 4,
+        0x04 => 16,
+        0x03 => {
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len).map_err(io_error)?;
+            len[0] as usize
+        }
+        other => return Err(format!("SOCKS5 proxy sent an unknown address type {}", other)),
+    };
+    let mut bound = vec![0u8; address_len + 2];
+    stream.read_exact(&mut bound).map_err(io_error)
+}
+
+/// CONNECT request for an IP address or, resolved by the proxy, a host name
+fn socks5_request(host: &str, port: u16) -> Result<Vec<u8>, String> {
+    let mut request = vec![0x05, 0x01, 0x00];
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            request.push(0x01);
+            request.extend_from_slice(&ip.octets());
+        }
+        Ok(IpAddr::V6(ip)) => {
+            request.push(0x04);
+            request.extend_from_slice(&ip.octets());
+        }
+        Err(_) => {
+            if host.len() > 255 {
+                return Err(format!("Host name too long for SOCKS5: {}", host));
+            }
+            request.push(0x03);
+            request.push(host.len() as u8);
+            request.extend_from_slice(host.as_bytes());
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    Ok(request)
+}
+
+fn socks5_error(code: u8) -> &'static str {
+    match code {
+        0x01 => "general failure",
+        0x02 => "connection not allowed by ruleset",
+        0x03 => "network unreachable",
+        0x04 => "host unreachable",
+        0x05 => "connection refused",
+        0x06 => "TTL expired",
+        0x07 => "command not supported",
+        0x08 => "address type not supported",
+        _ => "unknown error",
+    }
+}
+
+fn http_connect(stream: &mut TcpStream, proxy: &ProxyConfig, host: &str, port: u16) -> Result<(), String> {
+    let authority = match host.parse::<IpAddr>() {
+        Ok(IpAddr::V6(_)) => format!("[{}]:{}", host, port),
+        _ => format!("{}:{}", host, port),
+    };
+    let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", authority);
+    if let Some((username, password)) = proxy.credentials() {
+        request.push_str(&format!(
+            "Proxy-Authorization: Basic {}\r\n",
+            BASE64.encode(format!("{}:{}", username, password))
+        ));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).map_err(io_error)?;
+
+    // Read the reply headers byte by byte: what follows belongs to the tunnelled protocol
+    let mut head = Vec::new();
+    let mut byte = [0u8; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() > 16 * 1024 {
+            return Err("HTTP proxy reply too long".to_string());
+        }
+        stream.read_exact(&mut byte).map_err(io_error)?;
+        head.push(byte[0]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    let status_line = head.lines().next().unwrap_or("");
+    match status_line.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(()),
+        Some("407") => Err("HTTP proxy requires authentication".to_string()),
+        _ => Err(format!("HTTP proxy refused the tunnel to {}: {}", authority, status_line.trim())),
+    }
+}
+
+/// Proxy for reqwest clients; SOCKS5 host names are resolved by the proxy
+pub fn reqwest_proxy(proxy: &ProxyConfig) -> Result<reqwest::Proxy, String> {
+    let scheme = match proxy.protocol.as_str() {
+        "socks5" => "socks5h",
+        "http" => "http",
+        other => return Err(format!("Unknown proxy protocol: {}", other)),
+    };
+    let mut url = url::Url::parse(&format!("{}://{}:{}", scheme, proxy.host, proxy.port))
+        .map_err(|e| format!("Invalid proxy address: {}", e))?;
+    if let Some((username, password)) = proxy.credentials() {
+        let _ = url.set_username(username);
+        let _ = url.set_password(Some(password));
+    }
+    reqwest::Proxy::all(url.as_str()).map_err(|e| format!("Invalid proxy: {}", e))
+}
+
+/// Route a reqwest client through `proxy`, if any
+pub fn apply(
+    builder: reqwest::blocking::ClientBuilder,
+    proxy: Option<&ProxyConfig>,
+) -> Result<reqwest::blocking::ClientBuilder, String> {
+    match proxy {
+        Some(proxy) => Ok(builder.proxy(reqwest_proxy(proxy)?)),
+        None => Ok(builder),
+    }
+}
+
+/// ssh `ProxyCommand` through the BSD netcat shipped with macOS; proxy credentials aren't supported
+pub fn ssh_proxy_command(proxy: &ProxyConfig) -> String {
+    let kind = if proxy.protocol == "http" { "connect" } else { "5" };
+    format!("ProxyCommand=nc -X {} -x {}:{} %h %p", kind, proxy.host, proxy.port)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_socks5_and_http_connect_handshakes() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            // SOCKS5 proxy asking for credentials
+            let (mut client, _) = listener.accept().unwrap();
+            let mut greeting = [0u8; 4];
+            client.read_exact(&mut greeting).unwrap();
+            assert_eq!(greeting, [0x05, 0x02, 0x00, 0x02]);
+            client.write_all(&[0x05, 0x02]).unwrap();
+            let mut auth = [0u8; 11];
+            client.read_exact(&mut auth).unwrap();
+            assert_eq!(&auth, b"\x01\x03bob\x05s3cr3");
+            client.write_all(&[0x01, 0x00]).unwrap();
+            let mut request = vec![0u8; 5 + "srv.example".len() + 2];
+            client.read_exact(&mut request).unwrap();
+            assert_eq!(&request[..5], &[0x05, 0x01, 0x00, 0x03, 11]);
+            assert_eq!(&request[request.len() - 2..], &22u16.to_be_bytes());
+            client.write_all(&[0x05, 0x00, 0x00, 0x01, 10, 0, 0, 1, 0, 22]).unwrap();
+            client.write_all(b"SSH-2.0-test\r\n").unwrap();
+
+            // HTTP proxy
+            let (mut client, _) = listener.accept().unwrap();
+            let mut head = Vec::new();
+            let mut byte = [0u8; 1];
+            while !head.ends_with(b"\r\n\r\n") {
+                client.read_exact(&mut byte).unwrap();
+                head.push(byte[0]);
+            }
+            let head = String::from_utf8(head).unwrap();
+            assert!(head.starts_with("CONNECT srv.example:21 HTTP/1.1\r\n"));
+            assert!(head.contains("Proxy-Authorization: Basic Ym9iOnMzY3Iz\r\n"));
+            client.write_all(b"HTTP/1.1 200 Connection established\r\n\r\n220 FTP ready\r\n").unwrap();
+        });
+
+        let mut proxy = ProxyConfig {
+            protocol: "socks5".to_string(),
+            host: "127.0.0.1".to_string(),
+            port,
+            username: Some("bob".to_string()),
+            password: Some("s3cr3".to_string()),
+        };
+        let timeout = Duration::from_secs(5);
+        let mut banner = [0u8; 14];
+        connect(Some(&proxy), "srv.example", 22, timeout).unwrap().read_exact(&mut banner).unwrap();
+        assert_eq!(&banner, b"SSH-2.0-test\r\n");
+
+        proxy.protocol = "http".to_string();
+        let mut welcome = [0u8; 15];
+        connect(Some(&proxy), "srv.example", 21, timeout).unwrap().read_exact(&mut welcome).unwrap();
+        assert_eq!(&welcome, b"220 FTP ready\r\n");
+        server.join().unwrap();
+    }
+}
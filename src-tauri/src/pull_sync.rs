@@ -0,0 +1,248 @@
+//! Pull Sync Module
+//!
+//! The reverse of a sync: files that only exist on the server ("deleted" in
+//! the diff) or that are newer there ("remote-newer") are downloaded into
+//! the local project, to recover work done directly on the server. Each
+//! file goes to a temporary name first, so a failed download never leaves
+//! a truncated file in the project. The endpoint is shared with the
+//! two-way sync.
+
+use crate::deploy_manifest::{connect_ftp, connect_sftp};
+use crate::events::{self, SyncEventKind};
+use crate::{
+    compute_diff, create_ftp_dirs, create_sftp_dirs, is_cancelled, set_cancelled, FileDiff, RemoteScanContext,
+    SFTPConfig, SyncProgressEvent,
+};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PullResult {
+    pub downloaded: Vec<String>,
+    pub failed: Vec<String>,
+    pub bytes: u64,
+}
+
+/// Open SFTP or FTP connection to a target
+pub enum RemoteEndpoint {
+    Sftp(ssh2::Sftp),
+    Ftp(suppaftp::FtpStream),
+}
+
+impl RemoteEndpoint {
+    pub fn connect(config: &SFTPConfig) -> Result<Self, String> {
+        match config.protocol.as_deref().unwrap_or("ftp") {
+            "sftp" => {
+                let sess = connect_sftp(config)?;
+                Ok(RemoteEndpoint::Sftp(sess.sftp().map_err(|e| format!("SFTP error: {}", e))?))
+            }
+            "ftp" | "ftps" => Ok(RemoteEndpoint::Ftp(connect_ftp(config)?)),
+            other => Err(format!("Unknown protocol: {}", other)),
+        }
+    }
+
+    pub fn download(&mut self, remote_file: &str, local_file: &Path) -> Result<u64, String> {
+        let mut local = File::create(local_file)
+            .map_err(|e| format!("Failed to create {}: {}", local_file.display(), e))?;
+        self.download_to(remote_file, &mut local)
+    }
+
+    /// Stream a remote file into `writer`
+    pub fn download_to(&mut self, remote_file: &str, writer: &mut impl Write) -> Result<u64, String> {
+        match self {
+            RemoteEndpoint::Sftp(sftp) => {
+                let mut remote = sftp
+                    .open(Path::new(remote_file))
+                    .map_err(|e| format!("Failed to open {}: {}", remote_file, e))?;
+                std::io::copy(&mut remote, writer).map_err(|e| format!("Failed to download {}: {}", remote_file, e))
+            }
+            RemoteEndpoint::Ftp(ftp) => {
+                let content = ftp
+                    .retr_as_buffer(remote_file)
+                    .map_err(|e| format!("Failed to download {}: {}", remote_file, e))?
+                    .into_inner();
+                writer
+                    .write_all(&content)
+                    .map_err(|e| format!("Failed to write {}: {}", remote_file, e))?;
+                Ok(content.len() as u64)
+            }
+        }
+    }
+
+    /// Download through a temporary file, so `local_file` is either replaced or left untouched
+    pub fn download_replacing(&mut self, remote_file: &str, local_file: &Path) -> Result<u64, String> {
+        if let Some(parent) = local_file.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let temp_file = temp_path(local_file);
+        let downloaded = self.download(remote_file, &temp_file).and_then(|bytes| {
+            fs::rename(&temp_file, local_file)
+                .map(|_| bytes)
+                .map_err(|e| format!("Failed to write {}: {}", local_file.display(), e))
+        });
+        if downloaded.is_err() {
+            let _ = fs::remove_file(&temp_file);
+        }
+        downloaded
+    }
+
+    /// Upload `local_file` to `<remote_base>/<relative>`, creating the missing folders
+    pub fn upload(&mut self, local_file: &Path, remote_base: &str, relative: &str) -> Result<(), String> {
+        let remote_file = format!("{}/{}", remote_base, relative);
+        let mut local = File::open(local_file).map_err(|e| format!("Failed to open {}: {}", local_file.display(), e))?;
+        match self {
+            RemoteEndpoint::Sftp(sftp) => {
+                if let Some(parent) = Path::new(&remote_file).parent() {
+                    let _ = create_sftp_dirs(sftp, parent);
+                }
+                let mut remote = sftp
+                    .create(Path::new(&remote_file))
+                    .map_err(|e| format!("Failed to create {}: {}", remote_file, e))?;
+                std::io::copy(&mut local, &mut remote)
+                    .map(|_| ())
+                    .map_err(|e| format!("Failed to upload {}: {}", remote_file, e))
+            }
+            RemoteEndpoint::Ftp(ftp) => {
+                if let Some(parent) = Path::new(relative).parent() {
+                    let _ = create_ftp_dirs(ftp, remote_base, parent);
+                }
+                ftp.put_file(&remote_file, &mut local)
+                    .map(|_| ())
+                    .map_err(|e| format!("Failed to upload {}: {}", remote_file, e))
+            }
+        }
+    }
+
+    pub fn remove(&mut self, remote_file: &str) -> Result<(), String> {
+        let result = match self {
+            RemoteEndpoint::Sftp(sftp) => sftp.unlink(Path::new(remote_file)).map_err(|e| e.to_string()),
+            RemoteEndpoint::Ftp(ftp) => ftp.rm(remote_file).map_err(|e| e.to_string()),
+        };
+        result.map_err(|e| format!("Failed to delete {}: {}", remote_file, e))
+    }
+
+    /// Remove an empty folder; fails when it still has files
+    pub fn remove_dir(&mut self, remote_dir: &str) -> Result<(), String> {
+        let result = match self {
+            RemoteEndpoint::Sftp(sftp) => sftp.rmdir(Path::new(remote_dir)).map_err(|e| e.to_string()),
+            RemoteEndpoint::Ftp(ftp) => ftp.rmdir(remote_dir).map_err(|e| e.to_string()),
+        };
+        result.map_err(|e| format!("Failed to delete {}: {}", remote_dir, e))
+    }
+
+    pub fn close(self) {
+        if let RemoteEndpoint::Ftp(mut ftp) = self {
+            let _ = ftp.quit();
+        }
+    }
+}
+
+/// Whether a diff entry can be pulled: missing locally or newer on the server
+pub fn is_pullable(diff: &FileDiff) -> bool {
+    diff.status == "deleted" || diff.status == "remote-newer"
+}
+
+fn temp_path(local_file: &Path) -> PathBuf {
+    let name = local_file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    local_file.with_file_name(format!(".{}.laforge-pull", name))
+}
+
+fn emit(app_handle: &tauri::AppHandle, project_id: &str, event: SyncEventKind, file: Option<&str>, progress: u32, message: Option<String>) {
+    let _ = app_handle.emit_all(
+        events::PULL_PROGRESS,
+        SyncProgressEvent {
+            schema_version: events::SCHEMA_VERSION,
+            project_id: project_id.to_string(),
+            event,
+            file: file.map(|s| s.to_string()),
+            progress,
+            file_progress: None,
+            bytes_sent: None,
+            bytes_total: None,
+            message,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+        },
+    );
+}
+
+/// Download the pullable files of the diff, or only `paths` among them
+pub fn pull(
+    local_path: &str,
+    config: &SFTPConfig,
+    paths: Option<&[String]>,
+    project_id: &str,
+    app_handle: &tauri::AppHandle,
+) -> Result<PullResult, String> {
+    set_cancelled(project_id, false);
+    emit(app_handle, project_id, SyncEventKind::Analyzing, None, 5, Some("Analyse des fichiers distants...".to_string()));
+    let diffs = compute_diff(local_path, config, &mut RemoteScanContext::new(project_id, app_handle), None)?;
+    let to_pull: Vec<&FileDiff> = diffs
+        .iter()
+        .filter(|d| is_pullable(d))
+        .filter(|d| paths.map(|p| p.contains(&d.path)).unwrap_or(true))
+        .collect();
+
+    let mut result = PullResult {
+        downloaded: Vec::new(),
+        failed: Vec::new(),
+        bytes: 0,
+    };
+    if to_pull.is_empty() {
+        emit(app_handle, project_id, SyncEventKind::Complete, None, 100, Some("Aucun fichier a recuperer".to_string()));
+        return Ok(result);
+    }
+
+    emit(app_handle, project_id, SyncEventKind::Connecting, None, 15, Some("Connexion au serveur...".to_string()));
+    let mut remote = RemoteEndpoint::connect(config)?;
+    let remote_base = config.remote_path.trim_end_matches('/');
+    let total = to_pull.len();
+
+    for (index, diff) in to_pull.iter().enumerate() {
+        if is_cancelled(project_id) {
+            remote.close();
+            set_cancelled(project_id, false);
+            emit(app_handle, project_id, SyncEventKind::Cancelled, None, 0, Some("Recuperation annulee".to_string()));
+            return Err("Recuperation annulee".to_string());
+        }
+        let progress = 20 + (index as u32 * 75) / total as u32;
+        emit(app_handle, project_id, SyncEventKind::FileStart, Some(&diff.path), progress, None);
+
+        let local_file = Path::new(local_path).join(&diff.path);
+        let remote_file = format!("{}/{}", remote_base, diff.path);
+        match remote.download_replacing(&remote_file, &local_file) {
+            Ok(bytes) => {
+                result.bytes += bytes;
+                result.downloaded.push(diff.path.clone());
+                emit(app_handle, project_id, SyncEventKind::FileComplete, Some(&diff.path), progress, None);
+            }
+            Err(e) => {
+                println!("[Pull] Failed to download {}: {}", diff.path, e);
+                emit(app_handle, project_id, SyncEventKind::FileError, Some(&diff.path), progress, Some(e.clone()));
+                result.failed.push(format!("{}: {}", diff.path, e));
+            }
+        }
+    }
+    remote.close();
+
+    println!(
+        "[Pull] Downloaded {} file(s) ({} bytes) for project {}",
+        result.downloaded.len(),
+        result.bytes,
+        project_id
+    );
+    emit(
+        app_handle,
+        project_id,
+        SyncEventKind::Complete,
+        None,
+        100,
+        Some(format!("{} fichier(s) recupere(s)", result.downloaded.len())),
+    );
+    Ok(result)
+}
@@ -0,0 +1,77 @@
+//! Readonly Mode Module
+//!
+//! App-wide switch for when someone else uses the machine (a client
+//! reviewing the work). While it is on, the commands that write somewhere,
+//! syncs, deletions, restores and remote operations, are refused; browsing,
+//! diffs and reports keep working. The switch is kept across restarts.
+
+use crate::state_file;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static READONLY: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct ReadonlySettings {
+    enabled: bool,
+}
+
+fn settings_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("readonly_mode.json")
+}
+
+/// Load the switch saved by a previous run
+pub fn restore(app_data_dir: &Path) {
+    let settings: ReadonlySettings = state_file::read_json(&settings_path(app_data_dir))
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    READONLY.store(settings.enabled, Ordering::Relaxed);
+    if settings.enabled {
+        println!("[Readonly] Readonly mode restored");
+    }
+}
+
+pub fn is_enabled() -> bool {
+    READONLY.load(Ordering::Relaxed)
+}
+
+pub fn set_enabled(app_data_dir: &Path, enabled: bool) -> Result<(), String> {
+    state_file::write_json(&settings_path(app_data_dir), &ReadonlySettings { enabled })?;
+    READONLY.store(enabled, Ordering::Relaxed);
+    println!("[Readonly] Readonly mode {}", if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+/// Refuse `action` (e.g. "la synchronisation") while the readonly mode is on
+pub fn ensure_writable(action: &str) -> Result<(), String> {
+    if is_enabled() {
+        return Err(format!("Mode lecture seule actif: {} est desactive(e)", action));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_writes_refused_until_disabled_across_restarts() {
+        let data = TempDir::new("readonly");
+        set_enabled(data.path(), true).unwrap();
+        assert!(ensure_writable("la synchronisation").unwrap_err().contains("la synchronisation"));
+
+        // Restart: the flag comes back from disk
+        READONLY.store(false, Ordering::Relaxed);
+        restore(data.path());
+        assert!(is_enabled());
+        assert!(ensure_writable("la correction des permissions").is_err());
+
+        set_enabled(data.path(), false).unwrap();
+        READONLY.store(true, Ordering::Relaxed);
+        restore(data.path());
+        assert_eq!(ensure_writable("la synchronisation"), Ok(()));
+    }
+}
@@ -0,0 +1,286 @@
+//! Remote Exec Module
+//!
+//! Runs a constrained set of commands on SSH targets (`php -v`, `ls -la`,
+//! per-project presets...) from the remote project root and captures
+//! their output, to check a deployment without leaving the app. Outside
+//! the presets, only read-only commands pass: listings and file views with
+//! any path, and vetted forms of the interpreters and git (`git status`,
+//! `git log -n 5`...), since their other arguments can run arbitrary code
+//! (`php script.php`, `git -c alias.x=!cmd x`). The project's post-sync
+//! command goes through the same channel exec.
+
+use crate::{project_env, proxy, state_file, SFTPConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Characters that would let a command chain, redirect, expand or re-split its words
+const SHELL_METACHARACTERS: [char; 14] = [';', '|', '&', '>', '<', '`', '$', '(', ')', '\n', '\r', '\\', '\'', '"'];
+
+/// Maximum captured output per stream
+const MAX_OUTPUT_BYTES: usize = 512 * 1024;
+
+/// Default command timeout
+const COMMAND_TIMEOUT_SECS: u64 = 30;
+
+/// Default timeout of the command run after a sync
+pub const POST_SYNC_TIMEOUT_SECS: u64 = 120;
+
+/// A custom command declared for a project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteCommandPreset {
+    pub label: String,
+    pub command: String,
+}
+
+/// Output of a remote command
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteCommandOutput {
+    pub command: String,
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: i32,
+    pub duration_ms: u64,
+    pub truncated: bool,
+}
+
+/// Open an authenticated SSH session
+pub fn connect_ssh(config: &SFTPConfig) -> Result<ssh2::Session, String> {
+    let tcp = proxy::connect(config.proxy.as_ref(), &config.host, config.port, Duration::from_secs(10))?;
+
+    let mut sess = ssh2::Session::new().map_err(|e| format!("Session error: {}", e))?;
+    sess.set_tcp_stream(tcp);
+    sess.handshake().map_err(|e| format!("SSH handshake failed: {}", e))?;
+    crate::ssh_auth::authenticate(&sess, config)?;
+    Ok(sess)
+}
+
+/// Run a command on an open session and capture its output
+pub fn run_command(sess: &ssh2::Session, command: &str, timeout: Duration) -> Result<RemoteCommandOutput, String> {
+    let started = Instant::now();
+    sess.set_timeout(timeout.as_millis() as u32);
+
+    let mut channel = sess.channel_session().map_err(|e| format!("Failed to open channel: {}", e))?;
+    channel.exec(command).map_err(|e| format!("Failed to run command: {}", e))?;
+
+    let (stdout, stdout_truncated) = read_limited(&mut channel)?;
+    let (stderr, stderr_truncated) = read_limited(&mut channel.stderr())?;
+    channel.wait_close().map_err(|e| format!("Failed to close channel: {}", e))?;
+    let exit_code = channel.exit_status().map_err(|e| format!("Failed to read exit status: {}", e))?;
+    sess.set_timeout(0);
+
+    Ok(RemoteCommandOutput {
+        command: command.to_string(),
+        stdout,
+        stderr,
+        exit_code,
+        duration_ms: started.elapsed().as_millis() as u64,
+        truncated: stdout_truncated || stderr_truncated,
+    })
+}
+
+fn read_limited(reader: &mut impl Read) -> Result<(String, bool), String> {
+    let mut buffer = Vec::new();
+    reader
+        .take(MAX_OUTPUT_BYTES as u64 + 1)
+        .read_to_end(&mut buffer)
+        .map_err(|e| format!("Failed to read output: {}", e))?;
+    let truncated = buffer.len() > MAX_OUTPUT_BYTES;
+    buffer.truncate(MAX_OUTPUT_BYTES);
+    // Drain the rest so the channel can close
+    if truncated {
+        let _ = std::io::copy(reader, &mut std::io::sink());
+    }
+    Ok((String::from_utf8_lossy(&buffer).to_string(), truncated))
+}
+
+/// Quote a value for a POSIX shell
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Check that a command is a project preset or one of the allowed read-only commands
+pub fn validate_command(command: &str, presets: &[RemoteCommandPreset]) -> Result<(), String> {
+    let command = command.trim();
+    if command.is_empty() {
+        return Err("Commande vide".to_string());
+    }
+    if presets.iter().any(|p| p.command.trim() == command) {
+        return Ok(());
+    }
+    if command.contains(SHELL_METACHARACTERS) {
+        return Err("Les enchainements, redirections et guillemets ne sont autorises que dans les commandes du projet".to_string());
+    }
+    let words: Vec<&str> = command.split_whitespace().collect();
+    if !is_allowed(&words) {
+        return Err(format!(
+            "Commande non autorisee: {} (declarez-la dans les commandes du projet)",
+            command
+        ));
+    }
+    Ok(())
+}
+
+fn is_allowed(words: &[&str]) -> bool {
+    match words {
+        ["pwd" | "whoami" | "id" | "uptime"] => true,
+        // Listings and file views read, whatever the path
+        ["ls" | "df" | "du" | "cat" | "head" | "tail", ..] => true,
+        ["php", "-v" | "--version" | "-m" | "-i"] => true,
+        ["node", "-v" | "--version"] => true,
+        ["python3", "-V" | "--version"] => true,
+        ["composer", "-V" | "--version"] => true,
+        ["git", "status", options @ ..] => options
+            .iter()
+            .all(|o| matches!(*o, "-s" | "--short" | "-b" | "--branch" | "--porcelain")),
+        ["git", "log", options @ ..] => is_git_log_options(options),
+        ["git", "rev-parse", "HEAD"] | ["git", "rev-parse", "--short" | "--abbrev-ref", "HEAD"] => true,
+        ["git", "branch"] | ["git", "branch", "--show-current"] => true,
+        _ => false,
+    }
+}
+
+/// `--oneline`, `-n N`, `-N` and `--max-count=N`
+fn is_git_log_options(options: &[&str]) -> bool {
+    let is_count = |value: &str| !value.is_empty() && value.chars().all(|c| c.is_ascii_digit());
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let valid = match *option {
+            "--oneline" => true,
+            "-n" => options.next().map(|count| is_count(count)).unwrap_or(false),
+            other => other
+                .strip_prefix("--max-count=")
+                .or_else(|| other.strip_prefix('-'))
+                .map(is_count)
+                .unwrap_or(false),
+        };
+        if !valid {
+            return false;
+        }
+    }
+    true
+}
+
+/// Run a validated command from the project's remote root, the project
+/// variables `env` exported first
+pub fn exec_for_project(
+    config: &SFTPConfig,
+    command: &str,
+    presets: &[RemoteCommandPreset],
+    env: &[(String, String)],
+) -> Result<RemoteCommandOutput, String> {
+    if config.protocol.as_deref() != Some("sftp") {
+        return Err("Les commandes distantes necessitent une connexion SFTP (SSH)".to_string());
+    }
+    validate_command(command, presets)?;
+    exec_in_root(config, command, env, Duration::from_secs(COMMAND_TIMEOUT_SECS))
+}
+
+/// Run a command from the project's remote root without checking it against the
+/// allowed programs; for commands the project declares itself (post-sync command)
+pub fn exec_in_root(
+    config: &SFTPConfig,
+    command: &str,
+    env: &[(String, String)],
+    timeout: Duration,
+) -> Result<RemoteCommandOutput, String> {
+    if config.protocol.as_deref() != Some("sftp") {
+        return Err("Les commandes distantes necessitent une connexion SFTP (SSH)".to_string());
+    }
+    let sess = connect_ssh(config)?;
+    let full_command = if config.remote_path.is_empty() {
+        format!("{}{}", project_env::export_prefix(env), command.trim())
+    } else {
+        format!("{}cd {} && {}", project_env::export_prefix(env), shell_quote(&config.remote_path), command.trim())
+    };
+
+    println!("[RemoteExec] {}@{}: {}", config.username, config.host, command.trim());
+    let mut output = run_command(&sess, &full_command, timeout)?;
+    output.command = command.trim().to_string();
+    Ok(output)
+}
+
+// ============================================
+// Per-project presets
+// ============================================
+
+fn presets_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("remote_commands.json")
+}
+
+fn load_all_presets(app_data_dir: &Path) -> HashMap<String, Vec<RemoteCommandPreset>> {
+    state_file::read_json(&presets_path(app_data_dir))
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+/// Custom commands declared for a project
+pub fn load_presets(app_data_dir: &Path, project_id: &str) -> Vec<RemoteCommandPreset> {
+    load_all_presets(app_data_dir).remove(project_id).unwrap_or_default()
+}
+
+/// Replace the custom commands of a project
+pub fn save_presets(app_data_dir: &Path, project_id: &str, presets: Vec<RemoteCommandPreset>) -> Result<(), String> {
+    let mut all = load_all_presets(app_data_dir);
+    if presets.is_empty() {
+        all.remove(project_id);
+    } else {
+        all.insert(project_id.to_string(), presets);
+    }
+    state_file::write_json(&presets_path(app_data_dir), &all)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_read_only_commands_pass() {
+        for command in ["php -v", "ls -la public", "tail -n 50 logs/error.log", "git status -s", "git log --oneline -n 5", "git log -3", "whoami"] {
+            assert_eq!(validate_command(command, &[]), Ok(()), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_code_running_arguments_are_refused() {
+        for command in [
+            "git -c alias.x=!rm\\ -rf\\ . x",
+            "git -c alias.x=!id x",
+            "git -c core.pager=sh log",
+            "git log --output=index.php",
+            "git log -n 5 --format=%H",
+            "git status --ignored -- .",
+            "php some.php",
+            "php -r phpinfo();",
+            "composer run-script deploy",
+            "node server.js",
+            "python3 -c print(1)",
+            "ls; rm -rf .",
+            "cat 'a' && id",
+            "ls $HOME",
+            "rm -rf .",
+            "pwd -P",
+        ] {
+            assert!(validate_command(command, &[]).is_err(), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_presets_are_stored_and_allowed_as_declared() {
+        let data = TempDir::new("remote-exec");
+        let preset = RemoteCommandPreset {
+            label: "Migrations".to_string(),
+            command: "php artisan migrate --force && php artisan cache:clear".to_string(),
+        };
+        save_presets(data.path(), "site", vec![preset]).unwrap();
+        let presets = load_presets(data.path(), "site");
+
+        assert_eq!(validate_command(" php artisan migrate --force && php artisan cache:clear ", &presets), Ok(()));
+        assert!(validate_command("php artisan migrate --force", &presets).is_err());
+        assert!(load_presets(data.path(), "autre").is_empty());
+    }
+}
@@ -0,0 +1,130 @@
+//! Remote FS Module
+//!
+//! The few file operations the upload, atomic upload and resume paths need
+//! from a server, behind one trait implemented for an SFTP session and an
+//! FTP stream. The sync engines stay protocol-agnostic past the connection,
+//! and the tests drive the same code against a local folder standing in for
+//! the server (`test_support::FolderRemote`).
+
+use crate::{file_attributes, UploadOptions};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+pub trait RemoteFs {
+    /// Create one folder; fails when it exists
+    fn create_dir(&mut self, path: &str) -> Result<(), String>;
+    /// Write `reader` to `path`, replacing it, or after its content when `append`
+    fn write_file(&mut self, path: &str, append: bool, reader: &mut dyn Read) -> Result<u64, String>;
+    fn rename_file(&mut self, from: &str, to: &str) -> Result<(), String>;
+    fn remove_file(&mut self, path: &str) -> Result<(), String>;
+    /// Size of a file, None when it doesn't exist
+    fn file_size(&mut self, path: &str) -> Option<u64>;
+    /// Carry the attributes of the local file over (mode, modification time)
+    fn apply_attributes(&mut self, local_file: &str, remote_file: &str, upload: UploadOptions);
+}
+
+impl RemoteFs for ssh2::Sftp {
+    fn create_dir(&mut self, path: &str) -> Result<(), String> {
+        self.mkdir(Path::new(path), 0o755)
+            .map_err(|e| format!("Failed to create {}: {}", path, e))
+    }
+
+    fn write_file(&mut self, path: &str, append: bool, reader: &mut dyn Read) -> Result<u64, String> {
+        let mut file = if append {
+            let mut file = self
+                .open_mode(Path::new(path), ssh2::OpenFlags::WRITE, 0o644, ssh2::OpenType::File)
+                .map_err(|e| format!("Failed to open {}: {}", path, e))?;
+            file.seek(SeekFrom::End(0))
+                .map_err(|e| format!("Failed to seek in {}: {}", path, e))?;
+            file
+        } else {
+            self.create(Path::new(path))
+                .map_err(|e| format!("Failed to create {}: {}", path, e))?
+        };
+        std::io::copy(reader, &mut file).map_err(|e| format!("Failed to write {}: {}", path, e))
+    }
+
+    fn rename_file(&mut self, from: &str, to: &str) -> Result<(), String> {
+        self.rename(Path::new(from), Path::new(to), None)
+            .map_err(|e| format!("Failed to rename {} to {}: {}", from, to, e))
+    }
+
+    fn remove_file(&mut self, path: &str) -> Result<(), String> {
+        self.unlink(Path::new(path))
+            .map_err(|e| format!("Failed to remove {}: {}", path, e))
+    }
+
+    fn file_size(&mut self, path: &str) -> Option<u64> {
+        self.stat(Path::new(path)).ok().and_then(|stat| stat.size)
+    }
+
+    fn apply_attributes(&mut self, local_file: &str, remote_file: &str, upload: UploadOptions) {
+        file_attributes::apply_sftp(self, local_file, remote_file, upload);
+    }
+}
+
+impl RemoteFs for suppaftp::FtpStream {
+    fn create_dir(&mut self, path: &str) -> Result<(), String> {
+        self.mkdir(path).map_err(|e| format!("Failed to create {}: {}", path, e))
+    }
+
+    fn write_file(&mut self, path: &str, append: bool, mut reader: &mut dyn Read) -> Result<u64, String> {
+        let written = if append {
+            self.append_file(path, &mut reader)
+        } else {
+            self.put_file(path, &mut reader)
+        };
+        written.map_err(|e| format!("Failed to upload {}: {}", path, e))
+    }
+
+    fn rename_file(&mut self, from: &str, to: &str) -> Result<(), String> {
+        self.rename(from, to)
+            .map_err(|e| format!("Failed to rename {} to {}: {}", from, to, e))
+    }
+
+    fn remove_file(&mut self, path: &str) -> Result<(), String> {
+        self.rm(path).map_err(|e| format!("Failed to remove {}: {}", path, e))
+    }
+
+    fn file_size(&mut self, path: &str) -> Option<u64> {
+        self.size(path).ok().map(|size| size as u64)
+    }
+
+    fn apply_attributes(&mut self, local_file: &str, remote_file: &str, upload: UploadOptions) {
+        if upload.preserve_mtime {
+            file_attributes::apply_ftp_mtime(self, local_file, remote_file);
+        }
+    }
+}
+
+/// Create the folders of `relative` under `base`, one level at a time; existing ones are fine
+pub fn create_dirs(remote: &mut dyn RemoteFs, base: &str, relative: &Path) {
+    let mut current = base.trim_end_matches('/').to_string();
+    for component in relative.components() {
+        if let std::path::Component::Normal(name) = component {
+            current = format!("{}/{}", current, name.to_string_lossy());
+            let _ = remote.create_dir(&current);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{FolderRemote, TempDir};
+
+    #[test]
+    fn test_create_dirs_level_by_level() {
+        let server = TempDir::new("remote-fs-dirs");
+        server.write_files(&[("www/css/site.css", "body{}")]);
+        let mut remote = FolderRemote::new(server.path());
+
+        // Existing levels are skipped, missing ones created
+        create_dirs(&mut remote, "/www/", Path::new("css/vendor/fonts"));
+        assert!(server.path().join("www/css/vendor/fonts").is_dir());
+        assert!(server.path().join("www/css/site.css").exists());
+        create_dirs(&mut remote, "/www", Path::new("../outside"));
+        assert!(server.path().join("www/outside").is_dir());
+        assert!(!server.path().join("outside").exists());
+    }
+}
@@ -0,0 +1,123 @@
+//! Remote Listing Cache Module
+//!
+//! Last remote scan of each project (path -> size and date), so repeated
+//! dry runs and diffs can skip walking the whole server. A listing is only
+//! reused when asked, for the same target, and while it is recent; syncs,
+//! two-way syncs and changes seen by the remote monitor drop it since the
+//! server no longer matches.
+
+use crate::{state_file, RemoteFile, SFTPConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Listings older than this are scanned again even when the cache is asked for
+const MAX_AGE_HOURS: i64 = 24;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedListing {
+    /// protocol://user@host:port/remote/path the listing was taken from
+    pub target: String,
+    pub scanned_at: String,
+    pub files: HashMap<String, RemoteFile>,
+}
+
+/// What the UI shows about a project's cache
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheInfo {
+    pub target: String,
+    pub scanned_at: String,
+    pub file_count: usize,
+    pub expired: bool,
+}
+
+pub fn target_key(config: &SFTPConfig) -> String {
+    format!(
+        "{}://{}@{}:{}{}",
+        config.protocol.as_deref().unwrap_or("ftp"),
+        config.username,
+        config.host,
+        config.port,
+        config.remote_path
+    )
+}
+
+fn cache_path(app_data_dir: &Path, project_id: &str) -> PathBuf {
+    let name: String = project_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    app_data_dir.join("remote_listings").join(format!("{}.json", name))
+}
+
+// No backup kept: a stale listing is worse than a new scan
+fn read(app_data_dir: &Path, project_id: &str) -> Option<CachedListing> {
+    let content = fs::read_to_string(cache_path(app_data_dir, project_id)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn expired(listing: &CachedListing) -> bool {
+    chrono::DateTime::parse_from_rfc3339(&listing.scanned_at)
+        .map(|at| chrono::Utc::now().signed_duration_since(at) > chrono::Duration::hours(MAX_AGE_HOURS))
+        .unwrap_or(true)
+}
+
+/// Cached listing of `target`, unless it is missing, for another target or too old
+pub fn load(app_data_dir: &Path, project_id: &str, target: &str) -> Option<CachedListing> {
+    read(app_data_dir, project_id).filter(|listing| listing.target == target && !expired(listing))
+}
+
+pub fn store(app_data_dir: &Path, project_id: &str, target: &str, files: &HashMap<String, RemoteFile>) -> Result<(), String> {
+    let listing = CachedListing {
+        target: target.to_string(),
+        scanned_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        files: files.clone(),
+    };
+    let content = serde_json::to_vec(&listing).map_err(|e| format!("Failed to serialize remote listing: {}", e))?;
+    state_file::write_atomic(&cache_path(app_data_dir, project_id), &content)
+}
+
+/// Drop a project's listing once the server has changed
+pub fn invalidate(app_data_dir: &Path, project_id: &str) {
+    let path = cache_path(app_data_dir, project_id);
+    if path.exists() {
+        match fs::remove_file(&path) {
+            Ok(()) => println!("[RemoteListingCache] Dropped the listing of {}", project_id),
+            Err(e) => println!("[RemoteListingCache] Failed to drop {}: {}", path.display(), e),
+        }
+    }
+}
+
+pub fn info(app_data_dir: &Path, project_id: &str) -> Option<CacheInfo> {
+    read(app_data_dir, project_id).map(|listing| CacheInfo {
+        expired: expired(&listing),
+        file_count: listing.files.len(),
+        target: listing.target,
+        scanned_at: listing.scanned_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_listing_reused_for_its_target_only() {
+        let dir = TempDir::new("remote-listing-cache");
+        let mut files = HashMap::new();
+        files.insert("index.html".to_string(), RemoteFile { size: 120, mtime: Some(1_700_000_000) });
+        store(dir.path(), "p1", "sftp://deploy@example.com:22/www", &files).unwrap();
+
+        let listing = load(dir.path(), "p1", "sftp://deploy@example.com:22/www").unwrap();
+        assert_eq!(listing.files["index.html"].size, 120);
+        assert!(load(dir.path(), "p1", "sftp://deploy@example.com:22/staging").is_none());
+
+        invalidate(dir.path(), "p1");
+        assert!(load(dir.path(), "p1", "sftp://deploy@example.com:22/www").is_none());
+        assert!(info(dir.path(), "p1").is_none());
+    }
+}
@@ -0,0 +1,418 @@
+//! Remote Monitor Module
+//!
+//! Optional poller catching edits made on a server outside La Forge, by a
+//! client or another agency working on production directly. When a monitor
+//! starts, one full scan picks a sample of folders: the root and the
+//! shallowest folders holding code (PHP, HTML, JS, .htaccess). Each poll then
+//! lists only those folders and compares sizes and modification times with
+//! the previous poll. Changes raise a notification, an activity entry and a
+//! "remote-change-detected" event. Polls are skipped while the project syncs,
+//! and a finished sync resets the baseline so La Forge's own uploads are not
+//! reported. Monitors live in memory and stop with the app.
+
+use crate::deploy_manifest::{connect_ftp, connect_sftp};
+use crate::ftp_listing::FtpLister;
+use crate::{activity_feed, data_location, notifications, remote_listing_cache, sync_lock, webdav, RemoteScanContext, SFTPConfig};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::Manager;
+
+pub const DEFAULT_INTERVAL_MINUTES: u32 = 30;
+const MIN_INTERVAL_MINUTES: u32 = 5;
+/// Folders listed at each poll, the root included
+const MAX_FOLDERS: usize = 20;
+const CODE_EXTENSIONS: [&str; 6] = ["php", "phtml", "html", "htm", "js", "htaccess"];
+
+/// Size and modification time of a remote file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stamp {
+    pub size: u64,
+    pub mtime: Option<i64>,
+}
+
+/// Files of the watched folders, by path relative to the remote root
+pub type Listing = BTreeMap<String, Stamp>;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RemoteChange {
+    pub path: String,
+    /// "added", "modified" or "deleted"
+    pub change: String,
+    pub previous_size: Option<u64>,
+    pub size: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitorStatus {
+    pub project_id: String,
+    pub interval_minutes: u32,
+    /// Relative folders listed at each poll, "" for the root
+    pub watched_folders: Vec<String>,
+    pub watched_files: usize,
+    pub last_check: Option<String>,
+    pub last_error: Option<String>,
+    pub last_changes: Vec<RemoteChange>,
+    pub last_change_at: Option<String>,
+}
+
+/// Event emitted on "remote-change-detected"
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteChangeEvent {
+    pub project_id: String,
+    pub changes: Vec<RemoteChange>,
+    pub detected_at: String,
+}
+
+struct Monitor {
+    status: MonitorStatus,
+    stop: Arc<AtomicBool>,
+    /// Take the next listing as the new baseline instead of comparing it
+    rebaseline: bool,
+}
+
+static MONITORS: Lazy<Mutex<HashMap<String, Monitor>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn now() -> String {
+    chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+fn parent_folder(path: &str) -> &str {
+    path.rfind('/').map(|at| &path[..at]).unwrap_or("")
+}
+
+fn is_code_file(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name.rsplit_once('.')
+        .map(|(_, ext)| CODE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// The root, then the shallowest folders with the most code files
+pub fn pick_folders<'a>(paths: impl Iterator<Item = &'a String>) -> Vec<String> {
+    let mut code_files: HashMap<&str, usize> = HashMap::new();
+    for path in paths.filter(|p| is_code_file(p)) {
+        *code_files.entry(parent_folder(path)).or_default() += 1;
+    }
+    let mut folders: Vec<(&str, usize)> = code_files.into_iter().filter(|(folder, _)| !folder.is_empty()).collect();
+    folders.sort_by(|a, b| {
+        let depth = |folder: &str| folder.matches('/').count();
+        depth(a.0)
+            .cmp(&depth(b.0))
+            .then(b.1.cmp(&a.1))
+            .then(a.0.cmp(b.0))
+    });
+    std::iter::once(String::new())
+        .chain(folders.into_iter().map(|(folder, _)| folder.to_string()))
+        .take(MAX_FOLDERS)
+        .collect()
+}
+
+/// Files added, deleted or changed (size, or modification time when both are known)
+pub fn compare(baseline: &Listing, current: &Listing) -> Vec<RemoteChange> {
+    let mut changes = Vec::new();
+    for (path, stamp) in current {
+        match baseline.get(path) {
+            None => changes.push(RemoteChange {
+                path: path.clone(),
+                change: "added".to_string(),
+                previous_size: None,
+                size: Some(stamp.size),
+            }),
+            Some(before) => {
+                let touched = matches!((before.mtime, stamp.mtime), (Some(a), Some(b)) if a != b);
+                if before.size != stamp.size || touched {
+                    changes.push(RemoteChange {
+                        path: path.clone(),
+                        change: "modified".to_string(),
+                        previous_size: Some(before.size),
+                        size: Some(stamp.size),
+                    });
+                }
+            }
+        }
+    }
+    for (path, before) in baseline {
+        if !current.contains_key(path) {
+            changes.push(RemoteChange {
+                path: path.clone(),
+                change: "deleted".to_string(),
+                previous_size: Some(before.size),
+                size: None,
+            });
+        }
+    }
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    changes
+}
+
+/// Full scan of the target, dotfiles included, to choose the watched folders
+fn sample_folders(config: &SFTPConfig) -> Result<Vec<String>, String> {
+    let remote_path = &config.remote_path;
+    let mut scan = RemoteScanContext::silent().with_hidden();
+    let files = match config.protocol.as_deref().unwrap_or("ftp") {
+        "sftp" => crate::scan_sftp_remote_files(config, remote_path, &mut scan)?,
+        "ftp" | "ftps" => crate::scan_ftp_remote_files(config, remote_path, &mut scan)?,
+        "webdav" => crate::scan_webdav_remote_files(config, remote_path, &mut scan)?,
+        other => return Err(format!("Unknown protocol: {}", other)),
+    };
+    Ok(pick_folders(files.keys()))
+}
+
+fn join(base: &str, folder: &str) -> String {
+    if folder.is_empty() {
+        base.to_string()
+    } else {
+        format!("{}/{}", base, folder)
+    }
+}
+
+fn relative(folder: &str, name: &str) -> String {
+    if folder.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", folder, name)
+    }
+}
+
+/// Files directly inside the watched folders; a folder gone lists empty
+fn list_folders(config: &SFTPConfig, folders: &[String]) -> Result<Listing, String> {
+    let base = config.remote_path.trim_end_matches('/');
+    let mut listing = Listing::new();
+    match config.protocol.as_deref().unwrap_or("ftp") {
+        "sftp" => {
+            let sess = connect_sftp(config)?;
+            let sftp = sess.sftp().map_err(|e| format!("SFTP error: {}", e))?;
+            for folder in folders {
+                for (path, stat) in sftp.readdir(Path::new(&join(base, folder))).unwrap_or_default() {
+                    if !stat.is_file() {
+                        continue;
+                    }
+                    if let Some(name) = path.file_name() {
+                        listing.insert(
+                            relative(folder, &name.to_string_lossy()),
+                            Stamp { size: stat.size.unwrap_or(0), mtime: stat.mtime.map(|t| t as i64) },
+                        );
+                    }
+                }
+            }
+        }
+        "ftp" | "ftps" => {
+            let mut ftp = connect_ftp(config)?;
+            let mut lister = FtpLister::default();
+            for folder in folders {
+                for entry in lister.list(&mut ftp, &join(base, folder)).into_iter().filter(|e| !e.is_dir) {
+                    listing.insert(relative(folder, &entry.name), Stamp { size: entry.size, mtime: entry.modified });
+                }
+            }
+            let _ = ftp.quit();
+        }
+        "webdav" => {
+            let client = webdav::WebDavClient::connect(config)?;
+            for folder in folders {
+                for entry in client.list(&join(base, folder)).unwrap_or_default().into_iter().filter(|e| !e.is_dir) {
+                    listing.insert(relative(folder, &entry.name), Stamp { size: entry.size, mtime: entry.modified });
+                }
+            }
+        }
+        other => return Err(format!("Unknown protocol: {}", other)),
+    }
+    Ok(listing)
+}
+
+fn update(project_id: &str, stop: &Arc<AtomicBool>, apply: impl FnOnce(&mut Monitor)) {
+    if let Ok(mut monitors) = MONITORS.lock() {
+        // A monitor restarted meanwhile has its own stop flag
+        if let Some(monitor) = monitors.get_mut(project_id).filter(|m| Arc::ptr_eq(&m.stop, stop)) {
+            apply(monitor);
+        }
+    }
+}
+
+fn report(app_handle: &tauri::AppHandle, project_id: &str, changes: &[RemoteChange]) {
+    println!("[RemoteMonitor] {} file(s) changed on the server of {}", changes.len(), project_id);
+    if let Some(app_dir) = data_location::app_data_dir(app_handle) {
+        remote_listing_cache::invalidate(&app_dir, project_id);
+    }
+    let event = RemoteChangeEvent {
+        project_id: project_id.to_string(),
+        changes: changes.to_vec(),
+        detected_at: now(),
+    };
+    let _ = app_handle.emit_all(crate::events::REMOTE_CHANGE_DETECTED, &event);
+
+    let mut vars = HashMap::new();
+    vars.insert("files".to_string(), changes.len().to_string());
+    let _ = notifications::notify(app_handle, "remote_change", Some(project_id), &vars);
+
+    activity_feed::record(
+        app_handle,
+        activity_feed::new_event(
+            project_id,
+            "remote_change",
+            "Modifications sur le serveur hors de La Forge",
+            Some(changes.iter().take(5).map(|c| c.path.as_str()).collect::<Vec<_>>().join(", ")),
+            Some("warning"),
+            serde_json::json!({ "changes": changes }),
+        ),
+    );
+}
+
+fn run(app_handle: tauri::AppHandle, project_id: String, config: SFTPConfig, interval: Duration, stop: Arc<AtomicBool>) {
+    let folders = match sample_folders(&config) {
+        Ok(folders) => folders,
+        Err(e) => {
+            println!("[RemoteMonitor] Initial scan of {} failed: {}", project_id, e);
+            update(&project_id, &stop, |m| m.status.last_error = Some(e));
+            return;
+        }
+    };
+    update(&project_id, &stop, |m| m.status.watched_folders = folders.clone());
+
+    let mut baseline: Option<Listing> = None;
+    while !stop.load(Ordering::SeqCst) {
+        if sync_lock::is_locked(&project_id) {
+            // La Forge is changing the files itself; compare after the sync
+            update(&project_id, &stop, |m| m.rebaseline = true);
+        } else {
+            let result = list_folders(&config, &folders);
+            let mut rebaseline = false;
+            update(&project_id, &stop, |m| {
+                rebaseline = m.rebaseline;
+                m.rebaseline = false;
+            });
+            let checked_at = now();
+            match result {
+                Ok(current) => {
+                    let changes = match &baseline {
+                        Some(previous) if !rebaseline => compare(previous, &current),
+                        _ => Vec::new(),
+                    };
+                    if !changes.is_empty() {
+                        report(&app_handle, &project_id, &changes);
+                    }
+                    update(&project_id, &stop, |m| {
+                        m.status.watched_files = current.len();
+                        m.status.last_check = Some(checked_at.clone());
+                        m.status.last_error = None;
+                        if !changes.is_empty() {
+                            m.status.last_changes = changes;
+                            m.status.last_change_at = Some(checked_at);
+                        }
+                    });
+                    baseline = Some(current);
+                }
+                Err(e) => {
+                    println!("[RemoteMonitor] Poll of {} failed: {}", project_id, e);
+                    update(&project_id, &stop, |m| {
+                        m.status.last_check = Some(checked_at);
+                        m.status.last_error = Some(e);
+                    });
+                }
+            }
+        }
+
+        let mut waited = Duration::ZERO;
+        while waited < interval && !stop.load(Ordering::SeqCst) {
+            std::thread::sleep(Duration::from_secs(1));
+            waited += Duration::from_secs(1);
+        }
+    }
+    println!("[RemoteMonitor] Stopped for {}", project_id);
+}
+
+/// Start (or restart) polling the target of a project
+pub fn start(
+    app_handle: tauri::AppHandle,
+    project_id: &str,
+    config: SFTPConfig,
+    interval_minutes: Option<u32>,
+) -> Result<MonitorStatus, String> {
+    let interval_minutes = interval_minutes.unwrap_or(DEFAULT_INTERVAL_MINUTES).max(MIN_INTERVAL_MINUTES);
+    let stop = Arc::new(AtomicBool::new(false));
+    let status = MonitorStatus {
+        project_id: project_id.to_string(),
+        interval_minutes,
+        watched_folders: Vec::new(),
+        watched_files: 0,
+        last_check: None,
+        last_error: None,
+        last_changes: Vec::new(),
+        last_change_at: None,
+    };
+    {
+        let mut monitors = MONITORS.lock().map_err(|e| format!("Lock error: {}", e))?;
+        if let Some(previous) = monitors.insert(
+            project_id.to_string(),
+            Monitor { status: status.clone(), stop: stop.clone(), rebaseline: false },
+        ) {
+            previous.stop.store(true, Ordering::SeqCst);
+        }
+    }
+
+    let pid = project_id.to_string();
+    let interval = Duration::from_secs(interval_minutes as u64 * 60);
+    std::thread::spawn(move || run(app_handle, pid, config, interval, stop));
+    println!("[RemoteMonitor] Watching {} every {} min", project_id, interval_minutes);
+    Ok(status)
+}
+
+pub fn stop(project_id: &str) -> Result<(), String> {
+    let mut monitors = MONITORS.lock().map_err(|e| format!("Lock error: {}", e))?;
+    match monitors.remove(project_id) {
+        Some(monitor) => {
+            monitor.stop.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+        None => Err("Aucune surveillance pour ce projet".to_string()),
+    }
+}
+
+pub fn statuses() -> Vec<MonitorStatus> {
+    let mut statuses: Vec<MonitorStatus> = MONITORS
+        .lock()
+        .map(|monitors| monitors.values().map(|m| m.status.clone()).collect())
+        .unwrap_or_default();
+    statuses.sort_by(|a, b| a.project_id.cmp(&b.project_id));
+    statuses
+}
+
+/// Forget the pending comparison after La Forge changed the remote files itself
+pub fn rebaseline(project_id: &str) {
+    if let Ok(mut monitors) = MONITORS.lock() {
+        if let Some(monitor) = monitors.get_mut(project_id) {
+            monitor.rebaseline = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sampled_folders_and_changes() {
+        let paths: Vec<String> = ["index.php", "css/style.css", "blog/index.php", "blog/a/b.php", "inc/db.php", "inc/lib.php", ".htaccess"]
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        assert_eq!(pick_folders(paths.iter()), vec!["", "inc", "blog", "blog/a"]);
+
+        let stamp = |size, mtime| Stamp { size, mtime: Some(mtime) };
+        let baseline: Listing = [("index.php", stamp(10, 100)), ("inc/db.php", stamp(5, 100)), ("old.html", stamp(3, 100))]
+            .into_iter()
+            .map(|(p, s)| (p.to_string(), s))
+            .collect();
+        let mut current = baseline.clone();
+        current.remove("old.html");
+        current.insert("inc/db.php".to_string(), stamp(5, 200));
+        current.insert("shell.php".to_string(), stamp(700, 200));
+        let changes = compare(&baseline, &current);
+        let summary: Vec<(&str, &str)> = changes.iter().map(|c| (c.path.as_str(), c.change.as_str())).collect();
+        assert_eq!(summary, vec![("inc/db.php", "modified"), ("old.html", "deleted"), ("shell.php", "added")]);
+    }
+}
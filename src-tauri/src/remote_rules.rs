@@ -0,0 +1,864 @@
+//! Remote Rules Module
+//!
+//! Managed `.htaccess` (Apache) and nginx-style redirect files on the remote
+//! target: read, check the syntax, apply common recipes and deploy. Every
+//! version read from or deployed to the server is kept locally
+//! (`remote_rules/<project>/` in the app data), so a broken deploy can be
+//! rolled back. Recipes live between `# BEGIN Laforge <recipe>` and
+//! `# END Laforge <recipe>` markers and never touch the rest of the file.
+//! Nginx files are uploaded as-is: the server configuration has to include
+//! them.
+
+use crate::pull_sync::RemoteEndpoint;
+use crate::webdav::WebDavClient;
+use crate::{state_file, SFTPConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Folder of the local copies, in the app data directory
+pub const RULES_FOLDER: &str = "remote_rules";
+
+/// Versions kept per file, oldest dropped first
+const MAX_VERSIONS: usize = 30;
+
+const BLOCK_BEGIN: &str = "# BEGIN Laforge ";
+const BLOCK_END: &str = "# END Laforge ";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RulesFlavor {
+    Apache,
+    Nginx,
+}
+
+/// `.conf` files are nginx includes, anything else (`.htaccess`) is Apache
+pub fn flavor_of(file: &str) -> RulesFlavor {
+    if file.to_ascii_lowercase().ends_with(".conf") {
+        RulesFlavor::Nginx
+    } else {
+        RulesFlavor::Apache
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RuleIssue {
+    /// From 1
+    pub line: usize,
+    /// "error" blocks a deploy, "warning" doesn't
+    pub severity: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteRules {
+    pub file: String,
+    pub flavor: RulesFlavor,
+    /// False when the server has no such file yet, `content` is then empty
+    pub exists: bool,
+    pub content: String,
+    pub issues: Vec<RuleIssue>,
+    /// Recipes found in the file
+    pub recipes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RulesVersion {
+    /// "<file key>/<stamp>-<source>"
+    pub id: String,
+    pub file: String,
+    /// "remote" when read from the server, "deploy" when sent to it
+    pub source: String,
+    pub size: u64,
+    pub saved_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct VersionIndex {
+    versions: Vec<RulesVersion>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RulesDeployResult {
+    pub file: String,
+    pub version: RulesVersion,
+    /// Version of what the server had before, `None` when the file was new or unchanged
+    pub previous: Option<RulesVersion>,
+    pub warnings: Vec<RuleIssue>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleRecipe {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Recipes removed when this one is applied
+    pub conflicts: &'static [&'static str],
+}
+
+pub const RECIPES: [RuleRecipe; 4] = [
+    RuleRecipe {
+        id: "maintenance",
+        name: "Mode maintenance",
+        description: "Renvoie une page de maintenance (503) sauf aux adresses IP autorisees",
+        conflicts: &[],
+    },
+    RuleRecipe {
+        id: "force-https",
+        name: "Forcer HTTPS",
+        description: "Redirige (301) toutes les requetes HTTP vers HTTPS",
+        conflicts: &[],
+    },
+    RuleRecipe {
+        id: "www",
+        name: "Forcer www",
+        description: "Redirige (301) le domaine nu vers www",
+        conflicts: &["no-www"],
+    },
+    RuleRecipe {
+        id: "no-www",
+        name: "Supprimer www",
+        description: "Redirige (301) www vers le domaine nu",
+        conflicts: &["www"],
+    },
+];
+
+/// Settings of the recipes that need some
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecipeOptions {
+    /// Bare domain ("example.com"); without it the www recipes work on any host
+    pub domain: Option<String>,
+    /// Addresses still served the site in maintenance mode
+    #[serde(default)]
+    pub allowed_ips: Vec<String>,
+    /// Defaults to "/maintenance.html"
+    pub maintenance_page: Option<String>,
+}
+
+// ============================================
+// Validation
+// ============================================
+
+fn issue(line: usize, severity: &str, message: String) -> RuleIssue {
+    RuleIssue { line, severity: severity.to_string(), message }
+}
+
+/// Split a directive into arguments, double quotes keeping spaces
+fn arguments(line: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                current.push(c);
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if quoted {
+        return Err("guillemet non ferme".to_string());
+    }
+    if !current.is_empty() {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+const REWRITE_FLAGS: [&str; 43] = [
+    "b", "bnp", "backrefnoplus", "c", "chain", "co", "cookie", "dpi", "discardpath", "e", "env", "end", "f", "forbidden",
+    "g", "gone", "h", "handler", "l", "last", "n", "next", "nc", "nocase", "ne", "noescape", "ns", "nosubreq", "p",
+    "proxy", "pt", "passthrough", "qsa", "qsappend", "qsd", "qsdiscard", "qsl", "r", "redirect", "s", "skip", "t", "type",
+];
+const COND_FLAGS: [&str; 6] = ["nc", "nocase", "or", "ornext", "nv", "novary"];
+
+/// Flag names of a `[R=301,L]` argument, `None` when it isn't bracketed
+fn flag_names(arg: &str) -> Option<Vec<String>> {
+    let inner = arg.strip_prefix('[')?.strip_suffix(']')?;
+    Some(
+        inner
+            .split(',')
+            .map(|flag| flag.split('=').next().unwrap_or("").trim().to_ascii_lowercase())
+            .collect(),
+    )
+}
+
+fn check_apache_directive(number: usize, args: &[String], rewrite_on: bool, issues: &mut Vec<RuleIssue>) {
+    let name = args[0].to_ascii_lowercase();
+    match name.as_str() {
+        "rewriteengine" if args.len() != 2 || !["on", "off"].contains(&args[1].to_ascii_lowercase().as_str()) => {
+            issues.push(issue(number, "error", "RewriteEngine attend On ou Off".to_string()));
+        }
+        "rewriterule" => {
+            if args.len() < 3 || args.len() > 4 {
+                issues.push(issue(number, "error", "RewriteRule attend un motif, une cible et des options".to_string()));
+            } else if let Some(flags) = args.get(3) {
+                match flag_names(flags) {
+                    None => issues.push(issue(number, "error", format!("Options RewriteRule invalides: {}", flags))),
+                    Some(names) => {
+                        for flag in names.iter().filter(|f| !REWRITE_FLAGS.contains(&f.as_str())) {
+                            issues.push(issue(number, "error", format!("Option RewriteRule inconnue: {}", flag)));
+                        }
+                    }
+                }
+            }
+            if !rewrite_on {
+                issues.push(issue(number, "warning", "RewriteRule sans RewriteEngine On au-dessus".to_string()));
+            }
+        }
+        "rewritecond" => {
+            if args.len() < 3 || args.len() > 4 {
+                issues.push(issue(number, "error", "RewriteCond attend une valeur, une condition et des options".to_string()));
+            } else if let Some(flags) = args.get(3) {
+                match flag_names(flags) {
+                    Some(names) if names.iter().all(|f| COND_FLAGS.contains(&f.as_str())) => {}
+                    _ => issues.push(issue(number, "error", format!("Options RewriteCond invalides: {}", flags))),
+                }
+            }
+        }
+        "redirect" => {
+            let valid = match args.len() {
+                3 => args[1].starts_with('/'),
+                4 => {
+                    let status = args[1].to_ascii_lowercase();
+                    ["permanent", "temp", "seeother", "gone"].contains(&status.as_str())
+                        || (status.len() == 3 && status.starts_with('3') && status.parse::<u16>().is_ok())
+                }
+                _ => false,
+            };
+            if !valid {
+                issues.push(issue(number, "error", "Redirect attend [statut] /chemin URL".to_string()));
+            }
+        }
+        "redirectmatch" if args.len() < 3 || args.len() > 4 => {
+            issues.push(issue(number, "error", "RedirectMatch attend [statut] motif URL".to_string()));
+        }
+        "redirectpermanent" | "redirecttemp" if args.len() != 3 => {
+            issues.push(issue(number, "error", format!("{} attend /chemin URL", args[0])));
+        }
+        "errordocument" if args.len() < 3 || args[1].len() != 3 || args[1].parse::<u16>().is_err() => {
+            issues.push(issue(number, "error", "ErrorDocument attend un code et un document".to_string()));
+        }
+        _ => {}
+    }
+}
+
+fn validate_apache(content: &str) -> Vec<RuleIssue> {
+    let mut issues = Vec::new();
+    let mut sections: Vec<(String, usize)> = Vec::new();
+    let mut rewrite_on = false;
+    let mut continued = String::new();
+    let mut start = 0;
+
+    for (index, raw) in content.lines().enumerate() {
+        let line = raw.trim();
+        if continued.is_empty() {
+            start = index + 1;
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+        }
+        // A trailing backslash continues the directive on the next line
+        if let Some(head) = line.strip_suffix('\\') {
+            continued.push_str(head);
+            continued.push(' ');
+            continue;
+        }
+        continued.push_str(line);
+        let directive = std::mem::take(&mut continued);
+
+        if let Some(tag) = directive.strip_prefix("</") {
+            let name = tag.trim_end_matches('>').trim().to_ascii_lowercase();
+            match sections.pop() {
+                Some((open, _)) if open == name => {}
+                Some((open, open_line)) => issues.push(issue(
+                    start,
+                    "error",
+                    format!("</{}> ferme <{}> ouvert ligne {}", name, open, open_line),
+                )),
+                None => issues.push(issue(start, "error", format!("</{}> sans section ouverte", name))),
+            }
+            continue;
+        }
+        if let Some(tag) = directive.strip_prefix('<') {
+            if !tag.ends_with('>') {
+                issues.push(issue(start, "error", "Section sans '>' final".to_string()));
+                continue;
+            }
+            let name = tag.split(|c: char| c.is_whitespace() || c == '>').next().unwrap_or("").to_ascii_lowercase();
+            sections.push((name, start));
+            continue;
+        }
+
+        match arguments(&directive) {
+            Ok(args) if !args.is_empty() => {
+                if args[0].eq_ignore_ascii_case("RewriteEngine") {
+                    rewrite_on = args.get(1).map(|v| v.eq_ignore_ascii_case("on")).unwrap_or(false);
+                }
+                check_apache_directive(start, &args, rewrite_on, &mut issues);
+            }
+            Ok(_) => {}
+            Err(e) => issues.push(issue(start, "error", format!("Directive invalide: {}", e))),
+        }
+    }
+
+    if !continued.is_empty() {
+        issues.push(issue(start, "error", "Directive coupee par '\\' en fin de fichier".to_string()));
+    }
+    for (name, line) in sections {
+        issues.push(issue(line, "error", format!("<{}> n'est jamais ferme", name)));
+    }
+    issues
+}
+
+fn check_nginx_statement(line: usize, args: &[String], issues: &mut Vec<RuleIssue>) {
+    match args[0].as_str() {
+        "rewrite" => {
+            if args.len() < 3 || args.len() > 4 {
+                issues.push(issue(line, "error", "rewrite attend un motif, une cible et un drapeau".to_string()));
+            } else if let Some(flag) = args.get(3) {
+                if !["last", "break", "redirect", "permanent"].contains(&flag.as_str()) {
+                    issues.push(issue(line, "error", format!("Drapeau rewrite inconnu: {}", flag)));
+                }
+            }
+        }
+        "return" => match args.get(1).map(|code| code.parse::<u16>()) {
+            Some(Ok(code)) => {
+                if [301, 302, 303, 307, 308].contains(&code) && args.len() != 3 {
+                    issues.push(issue(line, "error", format!("return {} attend une URL", code)));
+                }
+            }
+            Some(Err(_)) if args.len() == 2 => {}
+            _ => issues.push(issue(line, "error", "return attend un code et une URL ou un texte".to_string())),
+        },
+        _ => {}
+    }
+}
+
+fn validate_nginx(content: &str) -> Vec<RuleIssue> {
+    let mut issues = Vec::new();
+    let mut blocks: Vec<usize> = Vec::new();
+    let mut statement = String::new();
+    let mut start = 0;
+
+    for (index, raw) in content.lines().enumerate() {
+        let line = raw.split('#').next().unwrap_or("");
+        for c in line.chars() {
+            if statement.trim().is_empty() && !c.is_whitespace() {
+                start = index + 1;
+            }
+            match c {
+                ';' | '{' => {
+                    match arguments(statement.trim()) {
+                        Ok(args) if args.is_empty() => {
+                            if c == ';' {
+                                issues.push(issue(index + 1, "warning", "';' sans instruction".to_string()));
+                            } else {
+                                issues.push(issue(index + 1, "error", "'{' sans instruction".to_string()));
+                            }
+                        }
+                        Ok(args) if c == ';' => check_nginx_statement(start, &args, &mut issues),
+                        Ok(_) => {}
+                        Err(e) => issues.push(issue(start, "error", format!("Instruction invalide: {}", e))),
+                    }
+                    if c == '{' {
+                        blocks.push(index + 1);
+                    }
+                    statement.clear();
+                }
+                '}' => {
+                    if !statement.trim().is_empty() {
+                        issues.push(issue(start, "error", "Instruction sans ';' final".to_string()));
+                        statement.clear();
+                    }
+                    if blocks.pop().is_none() {
+                        issues.push(issue(index + 1, "error", "'}' sans bloc ouvert".to_string()));
+                    }
+                }
+                c => statement.push(c),
+            }
+        }
+        statement.push(' ');
+    }
+
+    if !statement.trim().is_empty() {
+        issues.push(issue(start, "error", "Instruction sans ';' final".to_string()));
+    }
+    for line in blocks {
+        issues.push(issue(line, "error", "Bloc '{' jamais ferme".to_string()));
+    }
+    issues
+}
+
+/// Syntax problems of a rules file, in line order
+pub fn validate(flavor: RulesFlavor, content: &str) -> Vec<RuleIssue> {
+    let mut issues = match flavor {
+        RulesFlavor::Apache => validate_apache(content),
+        RulesFlavor::Nginx => validate_nginx(content),
+    };
+    issues.sort_by_key(|issue| issue.line);
+    issues
+}
+
+// ============================================
+// Recipes
+// ============================================
+
+fn escape_regex(value: &str) -> String {
+    value.chars().fold(String::new(), |mut escaped, c| {
+        if ".^$*+?()[]{}|\\".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+        escaped
+    })
+}
+
+fn recipe_body(flavor: RulesFlavor, recipe: &str, options: &RecipeOptions) -> Result<String, String> {
+    let domain = options.domain.as_deref().map(|d| d.trim().trim_start_matches("www.")).filter(|d| !d.is_empty());
+    let page = options.maintenance_page.as_deref().unwrap_or("/maintenance.html");
+    let body = match (flavor, recipe) {
+        (RulesFlavor::Apache, "maintenance") => {
+            let mut lines = vec!["<IfModule mod_rewrite.c>".to_string(), "RewriteEngine On".to_string()];
+            for ip in &options.allowed_ips {
+                lines.push(format!("RewriteCond %{{REMOTE_ADDR}} !^{}$", escape_regex(ip.trim())));
+            }
+            lines.push(format!("RewriteCond %{{REQUEST_URI}} !^{}$", escape_regex(page)));
+            lines.push("RewriteRule ^ - [R=503,L]".to_string());
+            lines.push("</IfModule>".to_string());
+            lines.push(format!("ErrorDocument 503 {}", page));
+            lines.join("\n")
+        }
+        (RulesFlavor::Apache, "force-https") => [
+            "<IfModule mod_rewrite.c>",
+            "RewriteEngine On",
+            "RewriteCond %{HTTPS} !=on",
+            "RewriteRule ^ https://%{HTTP_HOST}%{REQUEST_URI} [R=301,L]",
+            "</IfModule>",
+        ]
+        .join("\n"),
+        (RulesFlavor::Apache, "www") => {
+            let (condition, target) = match domain {
+                Some(d) => (format!("^{}$", escape_regex(d)), format!("https://www.{}%{{REQUEST_URI}}", d)),
+                None => ("!^www\\.".to_string(), "https://www.%{HTTP_HOST}%{REQUEST_URI}".to_string()),
+            };
+            format!(
+                "<IfModule mod_rewrite.c>\nRewriteEngine On\nRewriteCond %{{HTTP_HOST}} {} [NC]\nRewriteRule ^ {} [R=301,L]\n</IfModule>",
+                condition, target
+            )
+        }
+        (RulesFlavor::Apache, "no-www") => {
+            let (condition, target) = match domain {
+                Some(d) => (format!("^www\\.{}$", escape_regex(d)), format!("https://{}%{{REQUEST_URI}}", d)),
+                None => ("^www\\.(.+)$".to_string(), "https://%1%{REQUEST_URI}".to_string()),
+            };
+            format!(
+                "<IfModule mod_rewrite.c>\nRewriteEngine On\nRewriteCond %{{HTTP_HOST}} {} [NC]\nRewriteRule ^ {} [R=301,L]\n</IfModule>",
+                condition, target
+            )
+        }
+        (RulesFlavor::Nginx, "maintenance") => {
+            let mut lines = vec!["set $laforge_maintenance 1;".to_string()];
+            for ip in &options.allowed_ips {
+                lines.push(format!("if ($remote_addr = \"{}\") {{ set $laforge_maintenance 0; }}", ip.trim()));
+            }
+            lines.push(format!("if ($uri = \"{}\") {{ set $laforge_maintenance 0; }}", page));
+            lines.push("if ($laforge_maintenance = 1) { return 503; }".to_string());
+            lines.push(format!("error_page 503 {};", page));
+            lines.join("\n")
+        }
+        (RulesFlavor::Nginx, "force-https") => "if ($scheme = http) { return 301 https://$host$request_uri; }".to_string(),
+        (RulesFlavor::Nginx, "www") => match domain {
+            Some(d) => format!("if ($host = \"{}\") {{ return 301 https://www.{}$request_uri; }}", d, d),
+            None => "if ($host !~* \"^www\\.\") { return 301 https://www.$host$request_uri; }".to_string(),
+        },
+        (RulesFlavor::Nginx, "no-www") => match domain {
+            Some(d) => format!("if ($host = \"www.{}\") {{ return 301 https://{}$request_uri; }}", d, d),
+            None => "if ($host ~* \"^www\\.(.+)$\") { return 301 https://$1$request_uri; }".to_string(),
+        },
+        (_, other) => return Err(format!("Recette inconnue: {}", other)),
+    };
+    Ok(body)
+}
+
+/// (recipe, body) of each managed block
+type Blocks = Vec<(String, String)>;
+
+/// Managed blocks and the other lines of the file
+fn split_blocks(content: &str) -> Result<(Blocks, Vec<String>), String> {
+    let known: HashSet<&str> = RECIPES.iter().map(|r| r.id).collect();
+    let mut blocks = Vec::new();
+    let mut rest = Vec::new();
+    let mut current: Option<(String, Vec<String>)> = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if let Some((id, body)) = current.as_mut() {
+            if trimmed.strip_prefix(BLOCK_END) == Some(id.as_str()) {
+                blocks.push((id.clone(), body.join("\n")));
+                current = None;
+            } else {
+                body.push(line.to_string());
+            }
+            continue;
+        }
+        match trimmed.strip_prefix(BLOCK_BEGIN) {
+            Some(id) if known.contains(id) => current = Some((id.to_string(), Vec::new())),
+            _ => rest.push(line.to_string()),
+        }
+    }
+    if let Some((id, _)) = current {
+        return Err(format!("Bloc Laforge {} sans marqueur de fin", id));
+    }
+    Ok((blocks, rest))
+}
+
+/// Recipes present in a rules file
+pub fn recipes_in(content: &str) -> Vec<String> {
+    split_blocks(content)
+        .map(|(blocks, _)| blocks.into_iter().map(|(id, _)| id).collect())
+        .unwrap_or_default()
+}
+
+/// Add (or refresh) a recipe's block, or remove it when `enabled` is false.
+/// Managed blocks sit at the top, in recipe order, so redirects run before
+/// the site's own rules.
+pub fn apply_recipe(
+    flavor: RulesFlavor,
+    content: &str,
+    recipe: &str,
+    options: &RecipeOptions,
+    enabled: bool,
+) -> Result<String, String> {
+    let definition = RECIPES
+        .iter()
+        .find(|r| r.id == recipe)
+        .ok_or_else(|| format!("Recette inconnue: {}", recipe))?;
+    let (mut blocks, rest) = split_blocks(content)?;
+    blocks.retain(|(id, _)| id != recipe && !(enabled && definition.conflicts.contains(&id.as_str())));
+    if enabled {
+        blocks.push((recipe.to_string(), recipe_body(flavor, recipe, options)?));
+    }
+
+    let mut output = Vec::new();
+    for known in RECIPES.iter() {
+        if let Some((id, body)) = blocks.iter().find(|(id, _)| id == known.id) {
+            output.push(format!("{}{}\n{}\n{}{}", BLOCK_BEGIN, id, body, BLOCK_END, id));
+        }
+    }
+    let rest = rest.join("\n");
+    let rest = rest.trim_start_matches('\n');
+    if !rest.trim().is_empty() {
+        output.push(rest.to_string());
+    }
+    let mut result = output.join("\n\n");
+    if !result.is_empty() && !result.ends_with('\n') {
+        result.push('\n');
+    }
+    Ok(result)
+}
+
+// ============================================
+// Local Versions
+// ============================================
+
+/// Remote file relative to the remote root, refusing escapes from it
+pub fn check_file(file: &str) -> Result<String, String> {
+    let file = file.trim().trim_start_matches('/');
+    if file.is_empty() || file.split('/').any(|part| part == ".." || part.is_empty()) {
+        return Err(format!("Chemin de fichier invalide: {}", file));
+    }
+    Ok(file.to_string())
+}
+
+fn file_key(file: &str) -> String {
+    file.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' }).collect()
+}
+
+fn project_dir(app_data_dir: &Path, project_id: &str) -> PathBuf {
+    app_data_dir.join(RULES_FOLDER).join(project_id)
+}
+
+fn index_path(app_data_dir: &Path, project_id: &str) -> PathBuf {
+    project_dir(app_data_dir, project_id).join("index.json")
+}
+
+fn load_index(app_data_dir: &Path, project_id: &str) -> VersionIndex {
+    state_file::read_json(&index_path(app_data_dir, project_id)).ok().flatten().unwrap_or_default()
+}
+
+fn version_path(app_data_dir: &Path, project_id: &str, id: &str) -> PathBuf {
+    project_dir(app_data_dir, project_id).join(format!("{}.txt", id))
+}
+
+/// Versions of a project's rules files, newest first; `file` keeps one file's only
+pub fn list_versions(app_data_dir: &Path, project_id: &str, file: Option<&str>) -> Vec<RulesVersion> {
+    let mut versions: Vec<RulesVersion> = load_index(app_data_dir, project_id)
+        .versions
+        .into_iter()
+        .filter(|v| file.map(|f| v.file == f).unwrap_or(true))
+        .collect();
+    versions.sort_by(|a, b| b.saved_at.cmp(&a.saved_at).then_with(|| b.id.cmp(&a.id)));
+    versions
+}
+
+pub fn read_version(app_data_dir: &Path, project_id: &str, id: &str) -> Result<String, String> {
+    if !load_index(app_data_dir, project_id).versions.iter().any(|v| v.id == id) {
+        return Err(format!("Version introuvable: {}", id));
+    }
+    let path = version_path(app_data_dir, project_id, id);
+    fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+/// Keep `content` as a new version of `file`, unless the last one already has it.
+/// Returns the version when one was written.
+fn save_version(
+    app_data_dir: &Path,
+    project_id: &str,
+    file: &str,
+    source: &str,
+    content: &str,
+) -> Result<Option<RulesVersion>, String> {
+    let mut index = load_index(app_data_dir, project_id);
+    let last = index.versions.iter().filter(|v| v.file == file).max_by(|a, b| a.saved_at.cmp(&b.saved_at).then_with(|| a.id.cmp(&b.id)));
+    if let Some(last) = last {
+        let unchanged = fs::read_to_string(version_path(app_data_dir, project_id, &last.id))
+            .map(|previous| previous == content)
+            .unwrap_or(false);
+        if unchanged {
+            return Ok(None);
+        }
+    }
+
+    let now = chrono::Utc::now();
+    let version = RulesVersion {
+        id: format!("{}/{}-{}", file_key(file), now.format("%Y%m%d-%H%M%S%3f"), source),
+        file: file.to_string(),
+        source: source.to_string(),
+        size: content.len() as u64,
+        saved_at: now.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+    };
+    let path = version_path(app_data_dir, project_id, &version.id);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    state_file::write_atomic(&path, content.as_bytes())?;
+    index.versions.push(version.clone());
+
+    // Oldest versions of this file beyond the limit go
+    let mut of_file: Vec<RulesVersion> = index.versions.iter().filter(|v| v.file == file).cloned().collect();
+    if of_file.len() > MAX_VERSIONS {
+        of_file.sort_by(|a, b| a.saved_at.cmp(&b.saved_at).then_with(|| a.id.cmp(&b.id)));
+        let dropped: HashSet<String> = of_file[..of_file.len() - MAX_VERSIONS].iter().map(|v| v.id.clone()).collect();
+        for id in &dropped {
+            let _ = fs::remove_file(version_path(app_data_dir, project_id, id));
+        }
+        index.versions.retain(|v| !dropped.contains(&v.id));
+    }
+    state_file::write_json(&index_path(app_data_dir, project_id), &index)?;
+    Ok(Some(version))
+}
+
+// ============================================
+// Remote Access
+// ============================================
+
+/// Content of a remote file, `None` when the server can't give it (usually missing)
+pub fn download(config: &SFTPConfig, remote_file: &str) -> Result<Option<String>, String> {
+    let mut buffer = Vec::new();
+    let downloaded = match config.protocol.as_deref() {
+        Some("webdav") => WebDavClient::connect(config)?.download(remote_file, &mut buffer),
+        _ => {
+            let mut remote = RemoteEndpoint::connect(config)?;
+            let downloaded = remote.download_to(remote_file, &mut buffer);
+            remote.close();
+            downloaded
+        }
+    };
+    match downloaded {
+        Ok(_) => String::from_utf8(buffer)
+            .map(Some)
+            .map_err(|_| format!("{} n'est pas un fichier texte UTF-8", remote_file)),
+        Err(e) => {
+            println!("[RemoteRules] {} treated as missing: {}", remote_file, e);
+            Ok(None)
+        }
+    }
+}
+
+/// Upload `local_file` to `file` under the remote root
+pub fn upload(config: &SFTPConfig, local_file: &Path, file: &str) -> Result<(), String> {
+    let remote_base = config.remote_path.trim_end_matches('/');
+    match config.protocol.as_deref() {
+        Some("webdav") => {
+            let client = WebDavClient::connect(config)?;
+            let remote_file = format!("{}/{}", remote_base, file);
+            if let Some(parent) = Path::new(&remote_file).parent() {
+                client.mkdir_all(&parent.to_string_lossy(), &mut HashSet::new())?;
+            }
+            let contents = fs::read(local_file).map_err(|e| format!("Failed to read {}: {}", local_file.display(), e))?;
+            client.upload(&remote_file, contents, |_| {})
+        }
+        _ => {
+            let mut remote = RemoteEndpoint::connect(config)?;
+            let uploaded = remote.upload(local_file, remote_base, file);
+            remote.close();
+            uploaded
+        }
+    }
+}
+
+/// Delete `file` under the remote root
+pub fn remove(config: &SFTPConfig, file: &str) -> Result<(), String> {
+    let remote_file = format!("{}/{}", config.remote_path.trim_end_matches('/'), file);
+    match config.protocol.as_deref() {
+        Some("webdav") => WebDavClient::connect(config)?.delete(&remote_file, false),
+        _ => {
+            let mut remote = RemoteEndpoint::connect(config)?;
+            let removed = remote.remove(&remote_file);
+            remote.close();
+            removed
+        }
+    }
+}
+
+/// Read a rules file from the server, keeping a local version when it changed
+pub fn read_remote(app_data_dir: &Path, project_id: &str, config: &SFTPConfig, file: &str) -> Result<RemoteRules, String> {
+    let file = check_file(file)?;
+    let flavor = flavor_of(&file);
+    let remote_file = format!("{}/{}", config.remote_path.trim_end_matches('/'), file);
+    let content = download(config, &remote_file)?;
+    if let Some(content) = &content {
+        save_version(app_data_dir, project_id, &file, "remote", content)?;
+    }
+    let exists = content.is_some();
+    let content = content.unwrap_or_default();
+    Ok(RemoteRules {
+        issues: validate(flavor, &content),
+        recipes: recipes_in(&content),
+        exists,
+        file,
+        flavor,
+        content,
+    })
+}
+
+/// Check and upload a rules file. Syntax errors refuse the deploy; what the
+/// server had is kept as a version first, so it can be deployed back.
+pub fn deploy(
+    app_data_dir: &Path,
+    project_id: &str,
+    config: &SFTPConfig,
+    file: &str,
+    content: &str,
+) -> Result<RulesDeployResult, String> {
+    let file = check_file(file)?;
+    let issues = validate(flavor_of(&file), content);
+    let errors: Vec<&RuleIssue> = issues.iter().filter(|i| i.severity == "error").collect();
+    if let Some(first) = errors.first() {
+        return Err(format!(
+            "{} erreur(s) de syntaxe, deploiement annule (ligne {}: {})",
+            errors.len(),
+            first.line,
+            first.message
+        ));
+    }
+    send(app_data_dir, project_id, config, file, content, issues)
+}
+
+/// Upload content the server already had (minus a recipe) without the syntax
+/// check, so a rule Laforge added can always be taken out again
+pub fn put_back(
+    app_data_dir: &Path,
+    project_id: &str,
+    config: &SFTPConfig,
+    file: &str,
+    content: &str,
+) -> Result<RulesDeployResult, String> {
+    let file = check_file(file)?;
+    let issues = validate(flavor_of(&file), content);
+    send(app_data_dir, project_id, config, file, content, issues)
+}
+
+fn send(
+    app_data_dir: &Path,
+    project_id: &str,
+    config: &SFTPConfig,
+    file: String,
+    content: &str,
+    issues: Vec<RuleIssue>,
+) -> Result<RulesDeployResult, String> {
+    let remote_file = format!("{}/{}", config.remote_path.trim_end_matches('/'), file);
+    let previous = match download(config, &remote_file)? {
+        Some(current) => save_version(app_data_dir, project_id, &file, "remote", &current)?,
+        None => None,
+    };
+    let version = match save_version(app_data_dir, project_id, &file, "deploy", content)? {
+        Some(version) => version,
+        // Same content deployed again: upload the existing copy
+        None => list_versions(app_data_dir, project_id, Some(&file))
+            .into_iter()
+            .next()
+            .ok_or("Version locale introuvable")?,
+    };
+    upload(config, &version_path(app_data_dir, project_id, &version.id), &file)?;
+    println!("[RemoteRules] Deployed {} of {} ({} octets)", file, project_id, version.size);
+
+    Ok(RulesDeployResult {
+        file,
+        version,
+        previous,
+        warnings: issues,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recipes_and_validation() {
+        let site = "RewriteEngine On\nRewriteRule ^old$ /new [R=301,L]\n";
+        let options = RecipeOptions { allowed_ips: vec!["203.0.113.7".to_string()], ..Default::default() };
+        let with_https = apply_recipe(RulesFlavor::Apache, site, "force-https", &options, true).unwrap();
+        let with_www = apply_recipe(RulesFlavor::Apache, &with_https, "www", &options, true).unwrap();
+        let content = apply_recipe(RulesFlavor::Apache, &with_www, "no-www", &options, true).unwrap();
+        let content = apply_recipe(RulesFlavor::Apache, &content, "maintenance", &options, true).unwrap();
+        assert_eq!(recipes_in(&content), vec!["maintenance", "force-https", "no-www"]);
+        assert!(content.ends_with(site));
+        assert!(content.contains("!^203\\.0\\.113\\.7$"));
+        assert!(validate(RulesFlavor::Apache, &content).is_empty());
+
+        let removed = apply_recipe(RulesFlavor::Apache, &content, "maintenance", &options, false).unwrap();
+        assert_eq!(recipes_in(&removed), vec!["force-https", "no-www"]);
+
+        for recipe in RECIPES.iter() {
+            let nginx = apply_recipe(RulesFlavor::Nginx, "", recipe.id, &options, true).unwrap();
+            assert!(validate(RulesFlavor::Nginx, &nginx).is_empty(), "{}", nginx);
+        }
+
+        let broken = "<IfModule mod_rewrite.c>\nRewriteRule ^a$ /b [R=301,X]\n";
+        let lines: Vec<(usize, &str)> = validate(RulesFlavor::Apache, broken)
+            .iter()
+            .map(|i| (i.line, if i.severity == "error" { "error" } else { "warning" }))
+            .collect();
+        assert_eq!(lines, vec![(1, "error"), (2, "error"), (2, "warning")]);
+        assert_eq!(validate(RulesFlavor::Nginx, "return 301;\nlocation / {\n")[0].line, 1);
+        assert!(check_file("../etc/passwd").is_err());
+    }
+}
@@ -0,0 +1,258 @@
+//! Remote Trash Module
+//!
+//! Safety net for mirror mode: before a remote file is deleted because it
+//! is missing locally, a copy is downloaded into the project's remote trash
+//! (`remote_trash/<project>/<batch>/` in the app data). Files that couldn't be
+//! saved are not deleted. Entries can be re-uploaded later, and batches older
+//! than the retention period are purged.
+
+use crate::pull_sync::RemoteEndpoint;
+use crate::webdav::WebDavClient;
+use crate::{state_file, SFTPConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Folder of the trash, in the app data directory
+pub const TRASH_FOLDER: &str = "remote_trash";
+
+/// Days a deleted file stays in the trash when the sync doesn't say
+pub const DEFAULT_RETENTION_DAYS: u32 = 30;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    /// "<batch>/<path>"
+    pub id: String,
+    pub batch: String,
+    /// Relative to the remote root
+    pub path: String,
+    pub size: u64,
+    pub host: String,
+    pub deleted_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TrashIndex {
+    entries: Vec<TrashEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrashRestoreResult {
+    pub restored: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+fn project_dir(app_data_dir: &Path, project_id: &str) -> PathBuf {
+    app_data_dir.join(TRASH_FOLDER).join(project_id)
+}
+
+fn index_path(app_data_dir: &Path, project_id: &str) -> PathBuf {
+    project_dir(app_data_dir, project_id).join("index.json")
+}
+
+fn load_index(app_data_dir: &Path, project_id: &str) -> TrashIndex {
+    state_file::read_json(&index_path(app_data_dir, project_id)).ok().flatten().unwrap_or_default()
+}
+
+/// Drop the batches older than `retention_days`, files included
+fn prune(app_data_dir: &Path, project_id: &str, index: &mut TrashIndex, retention_days: u32) {
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(retention_days as i64))
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string();
+    let expired: HashSet<String> = index
+        .entries
+        .iter()
+        .filter(|entry| entry.deleted_at < cutoff)
+        .map(|entry| entry.batch.clone())
+        .collect();
+    for batch in &expired {
+        let _ = fs::remove_dir_all(project_dir(app_data_dir, project_id).join(batch));
+    }
+    if !expired.is_empty() {
+        println!("[RemoteTrash] Purged {} expired batch(es) of {}", expired.len(), project_id);
+    }
+    index.entries.retain(|entry| !expired.contains(&entry.batch));
+}
+
+/// Files saved before one mirror-mode deletion run
+pub struct TrashBatch {
+    app_data_dir: PathBuf,
+    project_id: String,
+    batch: String,
+    host: String,
+    retention_days: u32,
+    entries: Vec<TrashEntry>,
+}
+
+impl TrashBatch {
+    pub fn open(app_data_dir: &Path, project_id: &str, config: &SFTPConfig, retention_days: u32) -> Self {
+        TrashBatch {
+            app_data_dir: app_data_dir.to_path_buf(),
+            project_id: project_id.to_string(),
+            batch: chrono::Utc::now().format("%Y%m%d-%H%M%S").to_string(),
+            host: config.host.clone(),
+            retention_days,
+            entries: Vec::new(),
+        }
+    }
+
+    fn file_path(&self, relative: &str) -> PathBuf {
+        project_dir(&self.app_data_dir, &self.project_id).join(&self.batch).join(relative)
+    }
+
+    fn record(&mut self, relative: &str, size: u64) {
+        self.entries.push(TrashEntry {
+            id: format!("{}/{}", self.batch, relative),
+            batch: self.batch.clone(),
+            path: relative.to_string(),
+            size,
+            host: self.host.clone(),
+            deleted_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        });
+    }
+
+    /// Download a remote file into the batch before it is deleted
+    pub fn save_from(&mut self, remote: &mut RemoteEndpoint, remote_file: &str, relative: &str) -> Result<(), String> {
+        let size = remote.download_replacing(remote_file, &self.file_path(relative))?;
+        self.record(relative, size);
+        Ok(())
+    }
+
+    pub fn save_from_webdav(&mut self, client: &WebDavClient, remote_file: &str, relative: &str) -> Result<(), String> {
+        let target = self.file_path(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let mut file = fs::File::create(&target).map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+        let size = client.download(remote_file, &mut file).inspect_err(|_| {
+            let _ = fs::remove_file(&target);
+        })?;
+        self.record(relative, size);
+        Ok(())
+    }
+
+    /// Add the batch to the project's trash index and purge the expired batches
+    pub fn commit(self) -> Result<(), String> {
+        let mut index = load_index(&self.app_data_dir, &self.project_id);
+        let saved = self.entries.len();
+        index.entries.extend(self.entries);
+        prune(&self.app_data_dir, &self.project_id, &mut index, self.retention_days);
+        state_file::write_json(&index_path(&self.app_data_dir, &self.project_id), &index)?;
+        if saved > 0 {
+            println!("[RemoteTrash] Saved {} file(s) of {} before deletion", saved, self.project_id);
+        }
+        Ok(())
+    }
+}
+
+/// Files in the project's trash, most recent first
+pub fn list(app_data_dir: &Path, project_id: &str) -> Vec<TrashEntry> {
+    let mut entries = load_index(app_data_dir, project_id).entries;
+    entries.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at).then_with(|| a.path.cmp(&b.path)));
+    entries
+}
+
+/// Upload the selected entries back to the server; restored entries leave the trash
+pub fn restore(app_data_dir: &Path, project_id: &str, config: &SFTPConfig, ids: &[String]) -> Result<TrashRestoreResult, String> {
+    let mut index = load_index(app_data_dir, project_id);
+    let selected: Vec<TrashEntry> = index.entries.iter().filter(|e| ids.contains(&e.id)).cloned().collect();
+    if selected.is_empty() {
+        return Err("Aucun fichier de la corbeille ne correspond a la selection".to_string());
+    }
+
+    let remote_base = config.remote_path.trim_end_matches('/');
+    let trash_dir = project_dir(app_data_dir, project_id);
+    let mut result = TrashRestoreResult {
+        restored: Vec::new(),
+        failed: Vec::new(),
+    };
+
+    let mut upload: Box<dyn FnMut(&Path, &str) -> Result<(), String>> = match config.protocol.as_deref() {
+        Some("webdav") => {
+            let client = WebDavClient::connect(config)?;
+            let mut created = HashSet::new();
+            Box::new(move |local_file: &Path, relative: &str| {
+                let remote_file = format!("{}/{}", remote_base, relative);
+                if let Some(parent) = Path::new(&remote_file).parent() {
+                    client.mkdir_all(&parent.to_string_lossy(), &mut created)?;
+                }
+                let contents = fs::read(local_file).map_err(|e| format!("Failed to read {}: {}", local_file.display(), e))?;
+                client.upload(&remote_file, contents, |_| {})
+            })
+        }
+        _ => {
+            let mut remote = RemoteEndpoint::connect(config)?;
+            Box::new(move |local_file: &Path, relative: &str| remote.upload(local_file, remote_base, relative))
+        }
+    };
+
+    for entry in &selected {
+        let local_file = trash_dir.join(&entry.batch).join(&entry.path);
+        match upload(&local_file, &entry.path) {
+            Ok(()) => {
+                let _ = fs::remove_file(&local_file);
+                result.restored.push(entry.id.clone());
+            }
+            Err(e) => result.failed.push(format!("{}: {}", entry.path, e)),
+        }
+    }
+    drop(upload);
+
+    index.entries.retain(|e| !result.restored.contains(&e.id));
+    state_file::write_json(&index_path(app_data_dir, project_id), &index)?;
+    println!(
+        "[RemoteTrash] Restored {} file(s) of {}, {} failed",
+        result.restored.len(),
+        project_id,
+        result.failed.len()
+    );
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{sftp_config, HttpFixture, TempDir};
+
+    #[test]
+    fn test_deleted_files_kept_until_restored_or_expired() {
+        let data = TempDir::new("remote-trash");
+        let server = HttpFixture::serve(&[("/dav/www/css/old.css", "text/css", "body{}")]);
+        let config = sftp_config("webdav", &server.url("/dav"), 0, "/www");
+        let client = WebDavClient::connect(&config).unwrap();
+
+        // A batch saved before the retention period
+        data.write_files(&[("remote_trash/shop/20000101-000000/old.js", "0")]);
+        let expired = TrashEntry {
+            id: "20000101-000000/old.js".to_string(),
+            batch: "20000101-000000".to_string(),
+            path: "old.js".to_string(),
+            size: 1,
+            host: config.host.clone(),
+            deleted_at: "2000-01-01T00:00:00Z".to_string(),
+        };
+        state_file::write_json(&index_path(data.path(), "shop"), &TrashIndex { entries: vec![expired] }).unwrap();
+
+        let mut batch = TrashBatch::open(data.path(), "shop", &config, DEFAULT_RETENTION_DAYS);
+        batch.save_from_webdav(&client, "/www/css/old.css", "css/old.css").unwrap();
+        // A file that couldn't be saved is neither recorded nor left half written
+        assert!(batch.save_from_webdav(&client, "/www/gone.css", "gone.css").is_err());
+        let saved_in = project_dir(data.path(), "shop").join(&batch.batch);
+        batch.commit().unwrap();
+
+        let entries = list(data.path(), "shop");
+        assert_eq!(entries.iter().map(|e| (e.path.as_str(), e.size)).collect::<Vec<_>>(), vec![("css/old.css", 6)]);
+        assert_eq!(fs::read_to_string(saved_in.join("css/old.css")).unwrap(), "body{}");
+        assert!(!saved_in.join("gone.css").exists());
+        assert!(!project_dir(data.path(), "shop").join("20000101-000000").exists());
+
+        // The server refuses the upload: the file stays in the trash
+        let result = restore(data.path(), "shop", &config, &[entries[0].id.clone()]).unwrap();
+        assert!(result.restored.is_empty());
+        assert!(result.failed[0].starts_with("css/old.css: "));
+        assert_eq!(list(data.path(), "shop").len(), 1);
+        assert!(saved_in.join("css/old.css").exists());
+        assert!(restore(data.path(), "shop", &config, &["other".to_string()]).is_err());
+    }
+}
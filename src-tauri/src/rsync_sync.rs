@@ -0,0 +1,266 @@
+//! Rsync Sync Module
+//!
+//! Optional transfer engine for SFTP targets: the files to upload are handed
+//! to the system `rsync` over ssh, which only sends the changed blocks of
+//! each file, keeping timestamps and permissions as the sync options ask
+//! (`preserve_mtime`, `preserve_permissions`). The remote server needs
+//! rsync too, and the ssh client can't type a password, so the target has to
+//! accept a key or ssh-agent. The host key is checked against the trusted
+//! store before ssh is started, and ssh is given only that key, in a
+//! temporary known_hosts file, so it can't accept another one. With
+//! `atomic_upload`, rsync keeps every updated file aside until the end of
+//! the transfer and then renames them all (`--delay-updates`).
+
+use crate::known_hosts::{self, HostKeyStatus};
+use crate::proxy;
+use crate::parallel_sync::ParallelProgressTracker;
+use crate::remote_exec::shell_quote;
+use crate::{is_cancelled, FileDiff, SFTPConfig, UploadOptions};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Version line of the local rsync, None when it isn't installed
+pub fn local_version() -> Option<String> {
+    let output = Command::new("rsync").arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).lines().next().map(|line| line.trim().to_string())
+}
+
+/// ssh for rsync, trusting only the keys of `known_hosts_file`
+fn ssh_command(config: &SFTPConfig, known_hosts_file: &Path) -> String {
+    let mut ssh = format!(
+        "ssh -p {} -o BatchMode=yes -o ConnectTimeout=10 -o StrictHostKeyChecking=yes -o GlobalKnownHostsFile=/dev/null -o {}",
+        config.port,
+        shell_quote(&format!("UserKnownHostsFile={}", known_hosts_file.to_string_lossy()))
+    );
+    // rsync splits the remote shell on spaces but honours quotes
+    if let Some(key) = config.private_key_path.as_deref().filter(|k| !k.is_empty()) {
+        ssh.push_str(&format!(" -i {}", shell_quote(key)));
+    }
+    if let Some(proxy) = &config.proxy {
+        ssh.push_str(&format!(" -o {}", shell_quote(&proxy::ssh_proxy_command(proxy))));
+    }
+    ssh
+}
+
+fn destination(config: &SFTPConfig) -> String {
+    let remote = config.remote_path.trim_end_matches('/');
+    format!("{}@{}:{}/", config.username, config.host, if remote.is_empty() { "." } else { remote })
+}
+
+/// Upload the added and modified files of `diffs` with rsync
+pub fn rsync_sync(
+    local_path: &str,
+    config: &SFTPConfig,
+    diffs: &[FileDiff],
+    project_id: &str,
+    app_handle: &tauri::AppHandle,
+    upload: UploadOptions,
+) -> Result<(), String> {
+    let files_to_upload: Vec<_> = diffs
+        .iter()
+        .filter(|d| d.status == "added" || d.status == "modified")
+        .collect();
+
+    if files_to_upload.is_empty() {
+        return Ok(());
+    }
+    if local_version().is_none() {
+        return Err("rsync n'est pas installe sur cette machine".to_string());
+    }
+
+    // ssh doesn't read our trusted store: refuse an unconfirmed key, then pin the trusted one
+    let app_dir = crate::data_location::current_dir().ok_or("Could not get app data directory")?;
+    let (host_key, key_line) = known_hosts::probe_known_hosts_line(&app_dir, &config.host, config.port, config.proxy.as_ref())?;
+    if host_key.status != HostKeyStatus::Trusted {
+        return Err(format!(
+            "Cle d'hote non confirmee pour {}:{} ({}), verifiez l'empreinte avant d'utiliser rsync",
+            config.host, config.port, host_key.fingerprint
+        ));
+    }
+    let known_hosts_file = PinnedKnownHosts::write(&key_line)?;
+    run_rsync(local_path, config, &files_to_upload, project_id, app_handle, upload, &known_hosts_file.path)
+}
+
+/// Temporary known_hosts file holding only the trusted key, removed on drop
+struct PinnedKnownHosts {
+    path: PathBuf,
+}
+
+impl PinnedKnownHosts {
+    fn write(key_line: &str) -> Result<Self, String> {
+        let path = std::env::temp_dir().join(format!("laforge-known-hosts-{}", uuid::Uuid::new_v4()));
+        fs::write(&path, format!("{}\n", key_line)).map_err(|e| format!("Failed to write known hosts file: {}", e))?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for PinnedKnownHosts {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Arguments of rsync: permissions and times only kept when the sync asks for them
+fn rsync_args(local_path: &str, config: &SFTPConfig, upload: UploadOptions, known_hosts_file: &Path) -> Vec<String> {
+    // Recursive for the listed folders, links; compressed
+    let mut flags = "-rlz".to_string();
+    if upload.preserve_permissions {
+        flags.push('p');
+    }
+    if upload.preserve_mtime {
+        flags.push('t');
+    }
+    vec![
+        flags,
+        "--files-from=-".to_string(),
+        "--out-format=%n".to_string(),
+        // Kept partial files would land under their final name
+        if upload.atomic { "--delay-updates" } else { "--partial" }.to_string(),
+        "-e".to_string(),
+        ssh_command(config, known_hosts_file),
+        format!("{}/", local_path.trim_end_matches('/')),
+        destination(config),
+    ]
+}
+
+fn run_rsync(
+    local_path: &str,
+    config: &SFTPConfig,
+    files_to_upload: &[&FileDiff],
+    project_id: &str,
+    app_handle: &tauri::AppHandle,
+    upload: UploadOptions,
+    known_hosts_file: &Path,
+) -> Result<(), String> {
+    let tracker = ParallelProgressTracker::new(
+        project_id.to_string(),
+        files_to_upload.len(),
+        app_handle.clone(),
+        20,
+        70,
+    );
+    let sizes: HashMap<&str, u64> = files_to_upload
+        .iter()
+        .map(|d| (d.path.as_str(), d.local_size.unwrap_or(0)))
+        .collect();
+
+    println!("[Rsync] Uploading {} file(s) to {}", files_to_upload.len(), config.host);
+    let mut child = Command::new("rsync")
+        .args(rsync_args(local_path, config, upload, known_hosts_file))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start rsync: {}", e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let list: String = files_to_upload.iter().map(|d| format!("{}\n", d.path)).collect();
+        stdin
+            .write_all(list.as_bytes())
+            .map_err(|e| format!("Failed to send file list to rsync: {}", e))?;
+    }
+
+    // One line per transferred file; read on a thread so cancellation is checked meanwhile
+    let (sender, receiver) = mpsc::channel();
+    if let Some(stdout) = child.stdout.take() {
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    let stderr_reader = child.stderr.take().map(|mut stderr| {
+        std::thread::spawn(move || {
+            let mut output = String::new();
+            let _ = stderr.read_to_string(&mut output);
+            output
+        })
+    });
+
+    loop {
+        if is_cancelled(project_id) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err("Synchronisation annulée".to_string());
+        }
+        match receiver.recv_timeout(Duration::from_millis(200)) {
+            Ok(line) => {
+                let path = line.trim();
+                // Folders created on the way are listed too
+                if let Some(size) = sizes.get(path) {
+                    tracker.emit_file_start(path, *size);
+                    tracker.emit_file_complete(path, *size);
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    let status = child.wait().map_err(|e| format!("Failed to wait for rsync: {}", e))?;
+    let stderr = stderr_reader.and_then(|reader| reader.join().ok()).unwrap_or_default();
+    match status.code() {
+        Some(0) => Ok(()),
+        Some(255) => Err(format!(
+            "Connexion ssh refusee (rsync necessite une cle ou ssh-agent): {}",
+            stderr.trim()
+        )),
+        Some(23) | Some(24) => Err(format!("Transfert partiel: {}", stderr.trim())),
+        code => Err(format!(
+            "rsync a echoue (code {}): {}",
+            code.map(|c| c.to_string()).unwrap_or_else(|| "?".to_string()),
+            stderr.trim()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::sftp_config;
+
+    #[test]
+    fn test_flags_follow_the_sync_options() {
+        let config = sftp_config("sftp", "srv.example", 2222, "/var/www/");
+        let known_hosts = Path::new("/tmp/known hosts");
+        let args = rsync_args("/home/me/site/", &config, UploadOptions::default(), known_hosts);
+        assert_eq!(args[0], "-rlz");
+        assert_eq!(args[3], "--partial");
+        assert_eq!(&args[6..], ["/home/me/site/", "deploy@srv.example:/var/www/"]);
+
+        let upload = UploadOptions { atomic: true, preserve_permissions: true, preserve_mtime: true };
+        let args = rsync_args("/home/me/site", &config, upload, known_hosts);
+        assert_eq!(args[0], "-rlzpt");
+        assert_eq!(args[3], "--delay-updates");
+        let upload = UploadOptions { preserve_mtime: true, ..Default::default() };
+        assert_eq!(rsync_args("/site", &sftp_config("sftp", "h", 22, ""), upload, known_hosts)[0], "-rlzt");
+        assert_eq!(destination(&sftp_config("sftp", "h", 22, "")), "deploy@h:./");
+    }
+
+    #[test]
+    fn test_ssh_only_trusts_the_pinned_key() {
+        let line = "[srv.example]:2222 ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIExample";
+        let pinned = PinnedKnownHosts::write(line).unwrap();
+        assert_eq!(fs::read_to_string(&pinned.path).unwrap(), format!("{}\n", line));
+
+        let ssh = ssh_command(&sftp_config("sftp", "srv.example", 2222, "/var/www"), &pinned.path);
+        assert!(ssh.starts_with("ssh -p 2222 -o BatchMode=yes "));
+        assert!(ssh.contains("-o StrictHostKeyChecking=yes -o GlobalKnownHostsFile=/dev/null"));
+        assert!(ssh.contains(&format!("-o 'UserKnownHostsFile={}'", pinned.path.display())));
+        assert!(!ssh.contains("accept-new"));
+
+        let path = pinned.path.clone();
+        drop(pinned);
+        assert!(!path.exists());
+    }
+}
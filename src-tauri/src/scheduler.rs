@@ -2,6 +2,12 @@
 //!
 //! Implements automatic sync scheduling using cron expressions.
 //! Supports daily, weekly, and custom schedules per project.
+//!
+//! Due jobs wait in a queue and start by priority class (high, normal, low)
+//! within a global limit of jobs running at once, so a production deploy
+//! isn't held back by captures that fell due at the same minute. A job
+//! holds its slot until its result is reported, or for at most
+//! `JOB_SLOT_TIMEOUT` if it never is.
 
 use cron::Schedule;
 use once_cell::sync::Lazy;
@@ -10,9 +16,15 @@ use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Mutex;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tauri::Manager;
 
+/// Scheduled jobs running at once when no limit is set
+const DEFAULT_MAX_CONCURRENT_JOBS: usize = 2;
+
+/// A job that never reports its result frees its slot after this long
+const JOB_SLOT_TIMEOUT: Duration = Duration::from_secs(3 * 60 * 60);
+
 /// Schedule configuration for a project
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncSchedule {
@@ -23,6 +35,35 @@ pub struct SyncSchedule {
     pub next_run: Option<String>,
     pub last_run: Option<String>,
     pub last_result: Option<ScheduleResult>,
+    /// Also dump the project database when the schedule fires
+    #[serde(default)]
+    pub include_db_dump: bool,
+    /// Database dumped with `include_db_dump`
+    #[serde(default)]
+    pub db_dump: Option<crate::db_dump::ScheduledDbDump>,
+    /// What the schedule starts (default: a sync)
+    #[serde(default)]
+    pub job: ScheduledJob,
+    /// None: high for a production sync, normal for a sync, low for a scrape
+    #[serde(default)]
+    pub priority: Option<JobPriority>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduledJob {
+    #[default]
+    Sync,
+    Scrape,
+}
+
+/// Priority class; due jobs start high first, then in the order they fell due
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum JobPriority {
+    High,
+    Normal,
+    Low,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -64,31 +105,99 @@ static SCHEDULER_STATE: Lazy<Mutex<SchedulerState>> = Lazy::new(|| {
     Mutex::new(SchedulerState {
         schedules: HashMap::new(),
         running: false,
+        generation: 0,
+        last_tick: None,
+        pending: Vec::new(),
+        active: HashMap::new(),
+        max_concurrent: DEFAULT_MAX_CONCURRENT_JOBS,
     })
 });
 
 struct SchedulerState {
     schedules: HashMap<String, SyncSchedule>,
     running: bool,
+    /// Incremented at each start; an older thread still looping stops itself
+    generation: u64,
+    /// Last pass of the background thread
+    last_tick: Option<Instant>,
+    /// Due jobs waiting for a free slot
+    pending: Vec<QueuedJob>,
+    /// Project -> job started and not reported yet
+    active: HashMap<String, (QueuedJob, Instant)>,
+    max_concurrent: usize,
 }
 
-/// Schedule event emitted when a scheduled sync should run
+/// A job that fell due
+#[derive(Debug, Clone, Serialize)]
+pub struct QueuedJob {
+    pub project_id: String,
+    pub job: ScheduledJob,
+    pub priority: JobPriority,
+    pub include_db_dump: bool,
+    pub due_at: String,
+}
+
+/// Jobs running and waiting, for the UI
+#[derive(Debug, Clone, Serialize)]
+pub struct SchedulerQueue {
+    pub max_concurrent: usize,
+    pub running: Vec<QueuedJob>,
+    pub pending: Vec<QueuedJob>,
+}
+
+/// Schedule event emitted when a scheduled sync (or scrape) should run
 #[derive(Clone, Serialize)]
 pub struct ScheduleEvent {
     pub project_id: String,
     pub schedule_type: String,
     pub timestamp: u64,
+    pub include_db_dump: bool,
+    pub priority: JobPriority,
+}
+
+/// Priority of a schedule, from its settings or what it runs
+fn effective_priority(app_handle: &tauri::AppHandle, schedule: &SyncSchedule) -> JobPriority {
+    if let Some(priority) = schedule.priority {
+        return priority;
+    }
+    match schedule.job {
+        ScheduledJob::Scrape => JobPriority::Low,
+        ScheduledJob::Sync => {
+            let production = crate::data_location::app_data_dir(app_handle)
+                .map(|dir| crate::deploy_guard::get(&dir, &schedule.project_id).production)
+                .unwrap_or(false);
+            if production {
+                JobPriority::High
+            } else {
+                JobPriority::Normal
+            }
+        }
+    }
+}
+
+/// Take the jobs to start from `pending`: highest priority first, oldest first within a class
+fn take_startable(pending: &mut Vec<QueuedJob>, running: usize, limit: usize) -> Vec<QueuedJob> {
+    // Stable: jobs of a class keep the order they fell due in
+    pending.sort_by_key(|job| job.priority);
+    let free = limit.max(1).saturating_sub(running).min(pending.len());
+    pending.drain(..free).collect()
 }
 
 /// Start the scheduler background thread
 pub fn start_scheduler(app_handle: tauri::AppHandle) {
-    if let Ok(mut state) = SCHEDULER_STATE.lock() {
-        if state.running {
-            println!("[Scheduler] Already running");
-            return;
+    let generation = match SCHEDULER_STATE.lock() {
+        Ok(mut state) => {
+            if state.running {
+                println!("[Scheduler] Already running");
+                return;
+            }
+            state.running = true;
+            state.generation += 1;
+            state.last_tick = Some(Instant::now());
+            state.generation
         }
-        state.running = true;
-    }
+        Err(_) => return,
+    };
 
     thread::spawn(move || {
         println!("[Scheduler] Background thread started");
@@ -97,16 +206,17 @@ pub fn start_scheduler(app_handle: tauri::AppHandle) {
             // Check every minute
             thread::sleep(Duration::from_secs(60));
 
-            let schedules_to_run = {
-                let state = match SCHEDULER_STATE.lock() {
+            let due_schedules = {
+                let mut state = match SCHEDULER_STATE.lock() {
                     Ok(s) => s,
                     Err(_) => continue,
                 };
 
-                if !state.running {
+                if !state.running || state.generation != generation {
                     println!("[Scheduler] Stopping background thread");
                     break;
                 }
+                state.last_tick = Some(Instant::now());
 
                 let now = chrono::Utc::now();
                 let mut to_run = Vec::new();
@@ -128,7 +238,7 @@ pub fn start_scheduler(app_handle: tauri::AppHandle) {
                             // Check if the next run time is within the last minute
                             let diff = (next - now).num_seconds().abs();
                             if diff < 60 {
-                                to_run.push(schedule.project_id.clone());
+                                to_run.push(schedule.clone());
                             }
                         }
                     }
@@ -137,19 +247,97 @@ pub fn start_scheduler(app_handle: tauri::AppHandle) {
                 to_run
             };
 
-            // Emit events for schedules that should run
-            for project_id in schedules_to_run {
-                println!("[Scheduler] Triggering scheduled sync for project: {}", project_id);
+            // Queue the due jobs, then start as many as the limit allows
+            let due_at = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+            let due_jobs: Vec<QueuedJob> = due_schedules
+                .iter()
+                .map(|schedule| QueuedJob {
+                    project_id: schedule.project_id.clone(),
+                    job: schedule.job,
+                    priority: effective_priority(&app_handle, schedule),
+                    include_db_dump: schedule.include_db_dump,
+                    due_at: due_at.clone(),
+                })
+                .collect();
+
+            let jobs_to_start = {
+                let mut state = match SCHEDULER_STATE.lock() {
+                    Ok(s) => s,
+                    Err(_) => continue,
+                };
+                state.active.retain(|project_id, (_, started)| {
+                    let stale = started.elapsed() > JOB_SLOT_TIMEOUT;
+                    if stale {
+                        println!("[Scheduler] No result from {} after {:?}, freeing its slot", project_id, JOB_SLOT_TIMEOUT);
+                    }
+                    !stale
+                });
+                for job in due_jobs {
+                    let known = state.active.contains_key(&job.project_id)
+                        || state.pending.iter().any(|p| p.project_id == job.project_id);
+                    if !known {
+                        state.pending.push(job);
+                    }
+                }
+                let running = state.active.len();
+                let limit = state.max_concurrent;
+                let to_start = take_startable(&mut state.pending, running, limit);
+                for job in &to_start {
+                    state.active.insert(job.project_id.clone(), (job.clone(), Instant::now()));
+                }
+                if !state.pending.is_empty() {
+                    println!("[Scheduler] {} job(s) waiting for a free slot ({} running)", state.pending.len(), state.active.len());
+                }
+                to_start
+            };
+
+            // Emit events for the jobs that start
+            for job in jobs_to_start {
+                let project_id = job.project_id.clone();
+                let include_db_dump = job.include_db_dump;
+                if !crate::automation_pause::allow_automation(&app_handle, &project_id, "scheduled") {
+                    release_slot(&project_id);
+                    continue;
+                }
+                if job.job == ScheduledJob::Sync
+                    && !crate::deploy_guard::allow_triggered_sync(&app_handle, &project_id, "scheduled")
+                {
+                    release_slot(&project_id);
+                    continue;
+                }
+                let (event_name, title) = match job.job {
+                    ScheduledJob::Sync => ("scheduled-sync", "Synchronisation planifiee declenchee"),
+                    ScheduledJob::Scrape => ("scheduled-scrape", "Capture planifiee declenchee"),
+                };
+                println!("[Scheduler] Triggering {} for project: {} ({:?})", event_name, project_id, job.priority);
 
                 let _ = app_handle.emit_all(
-                    "scheduled-sync",
+                    event_name,
                     ScheduleEvent {
                         project_id: project_id.clone(),
                         schedule_type: "scheduled".to_string(),
                         timestamp: chrono::Utc::now().timestamp_millis() as u64,
+                        include_db_dump,
+                        priority: job.priority,
                     },
                 );
 
+                crate::activity_feed::record(
+                    &app_handle,
+                    crate::activity_feed::new_event(
+                        &project_id,
+                        "schedule",
+                        title,
+                        None,
+                        None,
+                        serde_json::json!({ "includeDbDump": include_db_dump, "priority": job.priority }),
+                    ),
+                );
+
+                if include_db_dump && job.job == ScheduledJob::Sync {
+                    start_db_dump(&app_handle, &project_id);
+                }
+
                 // Update last run timestamp
                 if let Ok(mut state) = SCHEDULER_STATE.lock() {
                     if let Some(schedule) = state.schedules.get_mut(&project_id) {
@@ -161,6 +349,38 @@ pub fn start_scheduler(app_handle: tauri::AppHandle) {
     });
 }
 
+/// Dump the database of a schedule on its own thread, next to the sync the
+/// frontend runs; the outcome goes to the activity feed
+fn start_db_dump(app_handle: &tauri::AppHandle, project_id: &str) {
+    let dump = get_schedule(project_id).and_then(|schedule| schedule.db_dump);
+    let app_handle = app_handle.clone();
+    let project_id = project_id.to_string();
+    thread::spawn(move || {
+        let result = crate::readonly_mode::ensure_writable("le dump de base de donnees")
+            .and_then(|_| dump.ok_or_else(|| "Aucune base de donnees configuree pour ce planning".to_string()))
+            .and_then(|dump| {
+                let app_dir = crate::data_location::app_data_dir(&app_handle)
+                    .ok_or_else(|| "Dossier de donnees introuvable".to_string())?;
+                crate::db_dump::run_scheduled(&app_dir, &dump)
+            });
+        let event = match result {
+            Ok(done) => crate::activity_feed::new_event(
+                &project_id,
+                "db_dump",
+                "Dump de base planifie termine",
+                Some(done.file_path.clone()),
+                Some("success"),
+                serde_json::json!({ "size": done.size, "durationMs": done.duration_ms, "removed": done.removed.len() }),
+            ),
+            Err(e) => {
+                println!("[Scheduler] Scheduled database dump of {} failed: {}", project_id, e);
+                crate::activity_feed::new_event(&project_id, "db_dump", "Echec du dump de base planifie", Some(e), Some("error"), serde_json::json!({}))
+            }
+        };
+        crate::activity_feed::record(&app_handle, event);
+    });
+}
+
 /// Stop the scheduler
 pub fn stop_scheduler() {
     if let Ok(mut state) = SCHEDULER_STATE.lock() {
@@ -168,6 +388,38 @@ pub fn stop_scheduler() {
     }
 }
 
+/// Liveness of the background thread, for the watchdog
+#[derive(Debug, Clone, Serialize)]
+pub struct SchedulerHealth {
+    pub running: bool,
+    /// Seconds since the thread last went through its loop
+    pub seconds_since_tick: Option<u64>,
+    /// A thread panicked while holding the scheduler state
+    pub poisoned: bool,
+}
+
+pub fn health() -> SchedulerHealth {
+    match SCHEDULER_STATE.lock() {
+        Ok(state) => SchedulerHealth {
+            running: state.running,
+            seconds_since_tick: state.last_tick.map(|tick| tick.elapsed().as_secs()),
+            poisoned: false,
+        },
+        Err(_) => SchedulerHealth {
+            running: false,
+            seconds_since_tick: None,
+            poisoned: true,
+        },
+    }
+}
+
+/// Replace a stalled or dead background thread; schedules and queued jobs are kept
+pub fn restart_scheduler(app_handle: tauri::AppHandle) {
+    SCHEDULER_STATE.clear_poison();
+    stop_scheduler();
+    start_scheduler(app_handle);
+}
+
 /// Add or update a schedule for a project
 pub fn set_schedule(schedule: SyncSchedule) -> Result<SyncSchedule, String> {
     // Validate cron expression if provided
@@ -175,6 +427,9 @@ pub fn set_schedule(schedule: SyncSchedule) -> Result<SyncSchedule, String> {
         Schedule::from_str(cron)
             .map_err(|e| format!("Invalid cron expression: {}", e))?;
     }
+    if schedule.include_db_dump && schedule.db_dump.is_none() {
+        return Err("Choisissez la base de donnees a dumper avec ce planning".to_string());
+    }
 
     // Calculate next run time
     let mut updated_schedule = schedule.clone();
@@ -193,10 +448,42 @@ pub fn set_schedule(schedule: SyncSchedule) -> Result<SyncSchedule, String> {
     Ok(updated_schedule)
 }
 
+/// Free the slot of a job and drop it from the queue
+fn release_slot(project_id: &str) {
+    if let Ok(mut state) = SCHEDULER_STATE.lock() {
+        state.active.remove(project_id);
+        state.pending.retain(|job| job.project_id != project_id);
+    }
+}
+
+/// Limit of scheduled jobs running at once (at least 1)
+pub fn set_max_concurrent_jobs(max_jobs: usize) {
+    if let Ok(mut state) = SCHEDULER_STATE.lock() {
+        state.max_concurrent = max_jobs.max(1);
+    }
+}
+
+/// Running and waiting jobs, in start order
+pub fn queue() -> SchedulerQueue {
+    SCHEDULER_STATE
+        .lock()
+        .map(|state| {
+            let mut pending = state.pending.clone();
+            pending.sort_by_key(|job| job.priority);
+            SchedulerQueue {
+                max_concurrent: state.max_concurrent,
+                running: state.active.values().map(|(job, _)| job.clone()).collect(),
+                pending,
+            }
+        })
+        .unwrap_or(SchedulerQueue { max_concurrent: DEFAULT_MAX_CONCURRENT_JOBS, running: Vec::new(), pending: Vec::new() })
+}
+
 /// Remove a schedule for a project
 pub fn remove_schedule(project_id: &str) -> Result<(), String> {
     if let Ok(mut state) = SCHEDULER_STATE.lock() {
         state.schedules.remove(project_id);
+        state.pending.retain(|job| job.project_id != project_id);
         Ok(())
     } else {
         Err("Failed to access scheduler state".to_string())
@@ -219,9 +506,10 @@ pub fn get_all_schedules() -> Vec<SyncSchedule> {
         .unwrap_or_default()
 }
 
-/// Update the last result of a scheduled sync
+/// Update the last result of a scheduled sync, which frees its slot
 pub fn update_schedule_result(project_id: &str, result: ScheduleResult) {
     if let Ok(mut state) = SCHEDULER_STATE.lock() {
+        state.active.remove(project_id);
         if let Some(schedule) = state.schedules.get_mut(project_id) {
             schedule.last_result = Some(result);
 
@@ -240,6 +528,9 @@ pub fn update_schedule_result(project_id: &str, result: ScheduleResult) {
 /// Enable or disable a schedule
 pub fn set_schedule_enabled(project_id: &str, enabled: bool) -> Result<(), String> {
     if let Ok(mut state) = SCHEDULER_STATE.lock() {
+        if !enabled {
+            state.pending.retain(|job| job.project_id != project_id);
+        }
         if let Some(schedule) = state.schedules.get_mut(project_id) {
             schedule.enabled = enabled;
             Ok(())
@@ -264,3 +555,34 @@ pub fn load_schedules(schedules: Vec<SyncSchedule>) {
 pub fn export_schedules() -> Vec<SyncSchedule> {
     get_all_schedules()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(project_id: &str, priority: JobPriority) -> QueuedJob {
+        QueuedJob {
+            project_id: project_id.to_string(),
+            job: ScheduledJob::Sync,
+            priority,
+            include_db_dump: false,
+            due_at: "2026-01-05T09:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_high_priority_jobs_start_first_within_the_limit() {
+        let mut pending = vec![
+            job("capture", JobPriority::Low),
+            job("blog", JobPriority::Normal),
+            job("shop", JobPriority::High),
+            job("docs", JobPriority::Normal),
+        ];
+        let started: Vec<String> = take_startable(&mut pending, 0, 2).into_iter().map(|j| j.project_id).collect();
+        assert_eq!(started, vec!["shop", "blog"]);
+        assert_eq!(take_startable(&mut pending, 2, 2).len(), 0);
+        let started: Vec<String> = take_startable(&mut pending, 1, 2).into_iter().map(|j| j.project_id).collect();
+        assert_eq!(started, vec!["docs"]);
+        assert_eq!(pending[0].project_id, "capture");
+    }
+}
@@ -0,0 +1,302 @@
+//! Scrape Attribution Module
+//!
+//! Where each asset of a full site capture came from and what the site said
+//! about reusing it: capture date, `X-Robots-Tag` and `Link: rel="license"`
+//! headers of the asset, copyright, author and license declared by the pages,
+//! and the robots.txt rules covering it. The hints are kept in the asset
+//! manifest of the capture (see scrape_refresh) and summarized on demand as
+//! `ATTRIBUTION.md`, for when captured files end up in a deliverable. They
+//! are hints to check with the client, not a license.
+
+use crate::scrape_refresh::{AssetManifest, AssetManifestEntry};
+use reqwest::header::HeaderMap;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use url::Url;
+
+pub const SUMMARY_FILE: &str = "ATTRIBUTION.md";
+
+/// `X-Robots-Tag` directives that ask not to reuse or index a file
+const RESTRICTIVE_DIRECTIVES: [&str; 4] = ["noimageindex", "noarchive", "noindex", "none"];
+
+/// What a page declares about its content
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PageHints {
+    pub page: String,
+    /// `<meta name="copyright">` or `dcterms.rights`
+    pub copyright: Option<String>,
+    pub author: Option<String>,
+    /// `<link rel="license">` (or `<a rel="license">`), absolute
+    pub license_url: Option<String>,
+    /// `<meta name="robots">`
+    pub robots: Option<String>,
+}
+
+/// Site-wide hints recorded with the capture
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SiteAttribution {
+    pub captured_at: String,
+    /// `Disallow` rules of the `User-agent: *` group of robots.txt
+    pub robots_disallow: Vec<String>,
+    /// Pages declaring something, in crawl order
+    pub pages: Vec<PageHints>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AttributionSummary {
+    pub path: String,
+    pub assets: usize,
+    /// Assets with a restrictive header or a robots.txt rule
+    pub flagged: usize,
+    /// Hosts the assets were downloaded from
+    pub origins: Vec<String>,
+}
+
+/// `X-Robots-Tag` and license URL (`Link: <...>; rel="license"`) of an asset response
+pub fn header_hints(headers: &HeaderMap) -> (Option<String>, Option<String>) {
+    let robots_tag = headers
+        .get_all("x-robots-tag")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let license_url = headers
+        .get_all(reqwest::header::LINK)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .find_map(|link| {
+            let (target, params) = link.split_once(';')?;
+            let is_license = params.split(';').any(|p| p.trim().replace('"', "").eq_ignore_ascii_case("rel=license"));
+            is_license.then(|| target.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+        });
+    ((!robots_tag.is_empty()).then_some(robots_tag), license_url)
+}
+
+fn meta_content(document: &Html, names: &[&str]) -> Option<String> {
+    let selector = Selector::parse("meta[name][content]").unwrap();
+    document
+        .select(&selector)
+        .find(|meta| names.iter().any(|n| meta.value().attr("name").map(|v| v.eq_ignore_ascii_case(n)).unwrap_or(false)))
+        .and_then(|meta| meta.value().attr("content"))
+        .map(|content| content.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|content| !content.is_empty())
+}
+
+/// Copyright, author, license and robots declared by a page; None when it declares nothing
+pub fn page_hints(page_url: &str, document: &Html, base_url: &Url) -> Option<PageHints> {
+    let license_selector = Selector::parse("link[rel~='license'][href], a[rel~='license'][href]").unwrap();
+    let hints = PageHints {
+        page: page_url.to_string(),
+        copyright: meta_content(document, &["copyright", "dcterms.rights", "dc.rights"]),
+        author: meta_content(document, &["author", "dcterms.creator"]),
+        license_url: document
+            .select(&license_selector)
+            .filter_map(|e| e.value().attr("href"))
+            .find_map(|href| base_url.join(href.trim()).ok())
+            .map(|u| u.to_string()),
+        robots: meta_content(document, &["robots"]),
+    };
+    let declared = hints.copyright.is_some() || hints.author.is_some() || hints.license_url.is_some() || hints.robots.is_some();
+    declared.then_some(hints)
+}
+
+/// `Disallow` rules of the `User-agent: *` group
+pub fn robots_disallow(robots_txt: &str) -> Vec<String> {
+    let mut rules = Vec::new();
+    let mut in_star_group = false;
+    let mut group_has_rules = false;
+    for line in robots_txt.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) => (key.trim().to_lowercase(), value.trim()),
+            None => continue,
+        };
+        match key.as_str() {
+            "user-agent" => {
+                // Consecutive user-agent lines share the rules that follow
+                if group_has_rules {
+                    in_star_group = false;
+                    group_has_rules = false;
+                }
+                in_star_group |= value == "*";
+            }
+            "disallow" | "allow" => {
+                group_has_rules = true;
+                if in_star_group && key == "disallow" && !value.is_empty() && !rules.iter().any(|r| r == value) {
+                    rules.push(value.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    rules
+}
+
+fn rule_matches(path: &str, rule: &str) -> bool {
+    let (pattern, anchored) = match rule.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (rule, false),
+    };
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if !path.starts_with(parts[0]) {
+        return false;
+    }
+    let mut position = parts[0].len();
+    for (index, part) in parts.iter().enumerate().skip(1) {
+        if anchored && index == parts.len() - 1 {
+            return path.len() >= position + part.len() && path.ends_with(part);
+        }
+        match path[position..].find(part) {
+            Some(found) => position += found + part.len(),
+            None => return false,
+        }
+    }
+    !anchored || position == path.len()
+}
+
+/// First robots.txt rule covering a URL path (`*` wildcards and a final `$` supported)
+pub fn disallowed_by<'a>(path: &str, rules: &'a [String]) -> Option<&'a str> {
+    rules.iter().map(String::as_str).find(|rule| rule_matches(path, rule))
+}
+
+fn restrictive(robots_tag: &str) -> bool {
+    robots_tag
+        .split([',', ' '])
+        .map(|d| d.trim().to_lowercase())
+        .any(|d| RESTRICTIVE_DIRECTIVES.contains(&d.as_str()))
+}
+
+/// Why an asset should be checked before reuse, if anything
+fn asset_flags(entry: &AssetManifestEntry, site_url: &str, site: &SiteAttribution) -> Vec<String> {
+    let mut flags = Vec::new();
+    if let Some(tag) = entry.robots_tag.as_deref().filter(|tag| restrictive(tag)) {
+        flags.push(format!("X-Robots-Tag: {}", tag));
+    }
+    // robots.txt only speaks for the captured host, not for CDNs
+    let same_host = host_of(&entry.url) == host_of(site_url);
+    if let Some(rule) = Url::parse(&entry.url).ok().filter(|_| same_host).and_then(|u| disallowed_by(u.path(), &site.robots_disallow)) {
+        flags.push(format!("robots.txt: Disallow {}", rule));
+    }
+    flags
+}
+
+/// Markdown table cells can't hold a pipe
+fn cell(text: &str) -> String {
+    text.replace('|', "/")
+}
+
+fn host_of(url: &str) -> String {
+    Url::parse(url).ok().and_then(|u| u.host_str().map(String::from)).unwrap_or_else(|| "inconnu".to_string())
+}
+
+/// Write `ATTRIBUTION.md` in the capture folder from its asset manifest
+pub fn write_summary(output_path: &Path) -> Result<AttributionSummary, String> {
+    let manifest = AssetManifest::load(output_path)?;
+    let site = manifest.attribution.clone().unwrap_or_default();
+    let captured_at = if site.captured_at.is_empty() { manifest.updated_at.as_str() } else { site.captured_at.as_str() };
+
+    let mut by_origin: BTreeMap<String, Vec<&AssetManifestEntry>> = BTreeMap::new();
+    for entry in &manifest.assets {
+        by_origin.entry(host_of(&entry.url)).or_default().push(entry);
+    }
+
+    let mut md = String::new();
+    md.push_str("# Sources et mentions\n\n");
+    md.push_str(&format!("- Site capture: {}\n", manifest.site_url));
+    md.push_str(&format!("- Date de capture: {}\n", captured_at));
+    md.push_str(&format!("- Fichiers: {}\n\n", manifest.assets.len()));
+    md.push_str(
+        "Les fichiers ci-dessous proviennent d'un site tiers. Leur reutilisation dans un livrable \
+         suppose l'accord du titulaire des droits; les indications relevees pendant la capture \
+         sont reprises ici sans valeur de licence.\n\n",
+    );
+
+    md.push_str("## Mentions declarees par le site\n\n");
+    if site.pages.is_empty() {
+        md.push_str("Aucune mention de droits, d'auteur ou de licence dans les pages capturees.\n\n");
+    } else {
+        md.push_str("| Page | Copyright | Auteur | Licence | Robots |\n|---|---|---|---|---|\n");
+        for page in &site.pages {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                page.page,
+                cell(page.copyright.as_deref().unwrap_or("-")),
+                cell(page.author.as_deref().unwrap_or("-")),
+                page.license_url.as_deref().unwrap_or("-"),
+                cell(page.robots.as_deref().unwrap_or("-")),
+            ));
+        }
+        md.push('\n');
+    }
+    if !site.robots_disallow.is_empty() {
+        md.push_str(&format!("robots.txt (User-agent: *) interdit: {}\n\n", site.robots_disallow.join(", ")));
+    }
+
+    let mut flagged = 0;
+    md.push_str("## Fichiers par origine\n\n");
+    for (origin, entries) in &by_origin {
+        md.push_str(&format!("### {} ({} fichier(s))\n\n", origin, entries.len()));
+        md.push_str("| Fichier | Source | Capture | Licence | A verifier |\n|---|---|---|---|---|\n");
+        for entry in entries {
+            let flags = asset_flags(entry, &manifest.site_url, &site);
+            if !flags.is_empty() {
+                flagged += 1;
+            }
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                entry.local_path,
+                entry.url,
+                entry.captured_at.as_deref().unwrap_or(captured_at),
+                entry.license_url.as_deref().unwrap_or("-"),
+                if flags.is_empty() { "-".to_string() } else { cell(&flags.join("; ")) },
+            ));
+        }
+        md.push('\n');
+    }
+    if !manifest.manual_assets.is_empty() {
+        md.push_str("## Fichiers ajoutes a la main\n\n");
+        for asset in &manifest.manual_assets {
+            md.push_str(&format!("- {} (ajoute le {}, source non capturee)\n", asset.local_path, asset.added_at));
+        }
+        md.push('\n');
+    }
+
+    let path = output_path.join(SUMMARY_FILE);
+    fs::write(&path, md).map_err(|e| format!("Failed to write attribution summary: {}", e))?;
+    println!("[Attribution] {} assets, {} to check: {}", manifest.assets.len(), flagged, path.display());
+    Ok(AttributionSummary {
+        path: path.to_string_lossy().to_string(),
+        assets: manifest.assets.len(),
+        flagged,
+        origins: by_origin.into_keys().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_robots_rules_and_page_hints() {
+        let robots = "User-agent: Googlebot\nDisallow: /private\n\nUser-agent: *\nDisallow: /wp-content/uploads/clients/\nDisallow: /*.pdf$\nAllow: /\n";
+        let rules = robots_disallow(robots);
+        assert_eq!(rules, vec!["/wp-content/uploads/clients/", "/*.pdf$"]);
+        assert!(disallowed_by("/wp-content/uploads/clients/logo.png", &rules).is_some());
+        assert!(disallowed_by("/docs/tarifs.pdf", &rules).is_some());
+        assert!(disallowed_by("/docs/tarifs.pdf.png", &rules).is_none());
+        assert!(disallowed_by("/private/a.png", &rules).is_none());
+
+        let html = Html::parse_document(
+            r#"<html><head><meta name="copyright" content="© Atelier  Dupont"><link rel="license" href="/mentions"></head></html>"#,
+        );
+        let base = Url::parse("https://example.com/a/").unwrap();
+        let hints = page_hints("https://example.com/a/", &html, &base).unwrap();
+        assert_eq!(hints.copyright.as_deref(), Some("© Atelier Dupont"));
+        assert_eq!(hints.license_url.as_deref(), Some("https://example.com/mentions"));
+        assert!(page_hints("https://example.com/", &Html::parse_document("<p>x</p>"), &base).is_none());
+    }
+}
@@ -1,7 +1,7 @@
+use crate::state_file;
 use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
 use std::collections::HashMap;
-use std::fs;
 use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -45,34 +45,12 @@ impl ScrapeCache {
 
     /// Load cache from disk
     pub fn load(project_path: &str) -> Option<Self> {
-        let cache_path = Self::cache_file_path(project_path);
-        if !Path::new(&cache_path).exists() {
-            return None;
-        }
-
-        match fs::read_to_string(&cache_path) {
-            Ok(content) => serde_json::from_str(&content).ok(),
-            Err(_) => None,
-        }
+        state_file::read_json(Path::new(&Self::cache_file_path(project_path))).ok().flatten()
     }
 
     /// Save cache to disk
     pub fn save(&self, project_path: &str) -> Result<(), String> {
-        let cache_path = Self::cache_file_path(project_path);
-
-        // Ensure directory exists
-        if let Some(parent) = Path::new(&cache_path).parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create cache directory: {}", e))?;
-        }
-
-        let content = serde_json::to_string_pretty(self)
-            .map_err(|e| format!("Failed to serialize cache: {}", e))?;
-
-        fs::write(&cache_path, content)
-            .map_err(|e| format!("Failed to write cache file: {}", e))?;
-
-        Ok(())
+        state_file::write_json(Path::new(&Self::cache_file_path(project_path)), self)
     }
 
     /// Get cache file path for a project
@@ -0,0 +1,125 @@
+//! Scrape Capture Module
+//!
+//! Places full site scrapes inside the project folder structure
+//! (e.g. `References/reference-site/<domain>`) and keeps a registry
+//! of captures in the project so they can be listed and versioned.
+
+use crate::state_file;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Default folder (relative to the project root) receiving site captures
+pub const DEFAULT_CAPTURE_FOLDER: &str = "References/reference-site";
+
+/// A site capture registered in the project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureRecord {
+    pub id: String,
+    pub url: String,
+    pub domain: String,
+    /// Capture folder, relative to the project root
+    pub relative_path: String,
+    pub captured_at: String,
+    pub pages_downloaded: usize,
+    pub assets_downloaded: usize,
+    pub total_size_bytes: u64,
+    /// Version snapshot of the capture, if one was taken
+    pub snapshot_id: Option<String>,
+}
+
+/// Registry of all captures of a project
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ProjectCaptures {
+    pub captures: Vec<CaptureRecord>,
+}
+
+impl ProjectCaptures {
+    /// Load the registry from the project folder
+    pub fn load(project_path: &str) -> Self {
+        state_file::read_json(&Self::file_path(project_path)).ok().flatten().unwrap_or_default()
+    }
+
+    /// Save the registry to the project folder
+    pub fn save(&self, project_path: &str) -> Result<(), String> {
+        state_file::write_json(&Self::file_path(project_path), self)
+    }
+
+    /// Add a capture, replacing a previous one stored in the same folder
+    pub fn register(&mut self, record: CaptureRecord) {
+        self.captures.retain(|c| c.relative_path != record.relative_path);
+        self.captures.push(record);
+    }
+
+    fn file_path(project_path: &str) -> PathBuf {
+        Path::new(project_path).join(".scrape_captures.json")
+    }
+}
+
+/// Compute the output folder of a capture inside the project.
+///
+/// Captures go to `<target_folder>/<domain>` and return both the absolute
+/// path and the path relative to the project root.
+pub fn capture_output_path(
+    project_path: &str,
+    target_folder: Option<&str>,
+    url: &str,
+) -> Result<(PathBuf, String), String> {
+    let parsed = url::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    let domain = parsed
+        .host_str()
+        .ok_or_else(|| format!("URL has no host: {}", url))?
+        .trim_start_matches("www.")
+        .to_string();
+
+    let folder = target_folder
+        .map(|f| f.trim_matches('/'))
+        .filter(|f| !f.is_empty())
+        .unwrap_or(DEFAULT_CAPTURE_FOLDER);
+    if folder.split('/').any(|part| part == "..") {
+        return Err(format!("Invalid capture folder: {}", folder));
+    }
+
+    let relative = format!("{}/{}", folder, domain);
+    Ok((Path::new(project_path).join(&relative), relative))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn record(id: &str, relative_path: &str) -> CaptureRecord {
+        CaptureRecord {
+            id: id.to_string(),
+            url: "https://www.example.com/".to_string(),
+            domain: "example.com".to_string(),
+            relative_path: relative_path.to_string(),
+            captured_at: "2026-03-02T10:00:00Z".to_string(),
+            pages_downloaded: 3,
+            assets_downloaded: 12,
+            total_size_bytes: 4096,
+            snapshot_id: None,
+        }
+    }
+
+    #[test]
+    fn test_captures_land_in_the_project() {
+        let (path, relative) = capture_output_path("/projects/shop", None, "https://www.example.com/fr/").unwrap();
+        assert_eq!(relative, "References/reference-site/example.com");
+        assert_eq!(path, Path::new("/projects/shop/References/reference-site/example.com"));
+        assert_eq!(capture_output_path("/p", Some("/refs/"), "http://example.com").unwrap().1, "refs/example.com");
+        assert!(capture_output_path("/p", Some("../outside"), "http://example.com").is_err());
+        assert!(capture_output_path("/p", None, "not a url").is_err());
+
+        let project = TempDir::new("scrape-capture");
+        let mut captures = ProjectCaptures::load(&project.path_str());
+        captures.register(record("first", &relative));
+        captures.register(record("other", "refs/other.com"));
+        // A new capture into the same folder replaces the previous one
+        captures.register(record("second", &relative));
+        captures.save(&project.path_str()).unwrap();
+
+        let ids: Vec<String> = ProjectCaptures::load(&project.path_str()).captures.into_iter().map(|c| c.id).collect();
+        assert_eq!(ids, ["other", "second"]);
+    }
+}
@@ -0,0 +1,503 @@
+//! Scrape Convert Module
+//!
+//! Turns a full site capture into the skeleton of a rebuild project:
+//!
+//! - `src/content/<page>.md`: the text of each page in Markdown, with its
+//!   title and source URL in a front matter
+//! - `src/assets/{images,fonts,files}`: the assets, renamed after their
+//!   original file names
+//! - `src/styles/tokens.css`: the palette and fonts as CSS variables
+//! - `asset-manifest.json`: where each original asset went
+//!
+//! Navigation, headers, footers and scripts are left out of the content;
+//! the capture itself is not modified.
+
+use crate::color_palette;
+use crate::scrape_refresh::AssetManifest;
+use scraper::{ElementRef, Html, Node, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Elements whose content is not page text
+const SKIPPED_ELEMENTS: [&str; 10] = ["script", "style", "noscript", "nav", "header", "footer", "form", "svg", "iframe", "template"];
+
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct ConvertOptions {
+    /// Replace files already in the target folder
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConvertedAsset {
+    /// Original URL when known from the capture manifest
+    pub url: Option<String>,
+    /// Path in the capture
+    pub source: String,
+    /// Path in the new project
+    pub path: String,
+    pub kind: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConvertedPage {
+    pub title: String,
+    pub source: String,
+    pub path: String,
+    pub words: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversionResult {
+    pub target_dir: String,
+    pub pages: Vec<ConvertedPage>,
+    pub assets: Vec<ConvertedAsset>,
+    pub tokens_path: String,
+    pub manifest_path: String,
+    pub warnings: Vec<String>,
+}
+
+/// Markdown of an element's content
+fn to_markdown(element: ElementRef, assets: &HashMap<String, String>, out: &mut String) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => {
+                let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                if !collapsed.is_empty() {
+                    if text.starts_with(char::is_whitespace) && !out.ends_with([' ', '\n']) {
+                        out.push(' ');
+                    }
+                    out.push_str(&collapsed);
+                    if text.ends_with(char::is_whitespace) {
+                        out.push(' ');
+                    }
+                }
+            }
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    element_to_markdown(child, assets, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn block(out: &mut String) {
+    let trimmed = out.trim_end_matches([' ', '\n']).len();
+    out.truncate(trimmed);
+    if !out.is_empty() {
+        out.push_str("\n\n");
+    }
+}
+
+fn inline<F: FnOnce(&mut String)>(out: &mut String, marker: &str, content: F) {
+    let mut inner = String::new();
+    content(&mut inner);
+    let inner = inner.trim();
+    if !inner.is_empty() {
+        out.push_str(&format!("{}{}{}", marker, inner, marker));
+    }
+}
+
+fn element_to_markdown(element: ElementRef, assets: &HashMap<String, String>, out: &mut String) {
+    let name = element.value().name();
+    if SKIPPED_ELEMENTS.contains(&name) {
+        return;
+    }
+    match name {
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            block(out);
+            let level = name[1..].parse::<usize>().unwrap_or(1);
+            out.push_str(&format!("{} ", "#".repeat(level)));
+            to_markdown(element, assets, out);
+            block(out);
+        }
+        "p" | "div" | "section" | "article" | "main" | "figure" | "table" => {
+            block(out);
+            to_markdown(element, assets, out);
+            block(out);
+        }
+        "br" => out.push_str("  \n"),
+        "hr" => {
+            block(out);
+            out.push_str("---");
+            block(out);
+        }
+        "strong" | "b" => inline(out, "**", |inner| to_markdown(element, assets, inner)),
+        "em" | "i" => inline(out, "*", |inner| to_markdown(element, assets, inner)),
+        "code" => inline(out, "`", |inner| to_markdown(element, assets, inner)),
+        "pre" => {
+            block(out);
+            out.push_str("```\n");
+            out.push_str(element.text().collect::<String>().trim_end());
+            out.push_str("\n```");
+            block(out);
+        }
+        "blockquote" => {
+            block(out);
+            let mut inner = String::new();
+            to_markdown(element, assets, &mut inner);
+            let quoted: Vec<String> = inner.trim().lines().map(|l| format!("> {}", l)).collect();
+            out.push_str(&quoted.join("\n"));
+            block(out);
+        }
+        "ul" | "ol" => {
+            block(out);
+            let ordered = name == "ol";
+            let items = element.children().filter_map(ElementRef::wrap).filter(|c| c.value().name() == "li");
+            for (index, item) in items.enumerate() {
+                let mut inner = String::new();
+                to_markdown(item, assets, &mut inner);
+                let marker = if ordered { format!("{}.", index + 1) } else { "-".to_string() };
+                out.push_str(&format!("{} {}\n", marker, inner.trim().replace("\n\n", "\n  ")));
+            }
+            block(out);
+        }
+        "a" => {
+            let mut inner = String::new();
+            to_markdown(element, assets, &mut inner);
+            let text = inner.trim();
+            match element.value().attr("href") {
+                Some(href) if !text.is_empty() && !href.starts_with('#') && !href.starts_with("javascript:") => {
+                    out.push_str(&format!("[{}]({})", text, href));
+                }
+                _ => out.push_str(text),
+            }
+        }
+        "img" => {
+            if let Some(src) = element.value().attr("src") {
+                let alt = element.value().attr("alt").unwrap_or("");
+                let src = assets.get(&capture_relative(src)).map(String::as_str).unwrap_or(src);
+                out.push_str(&format!("![{}]({})", alt, src));
+            }
+        }
+        _ => to_markdown(element, assets, out),
+    }
+}
+
+/// Capture-relative path of a local `src` (`../images/a.png` -> `images/a.png`)
+fn capture_relative(src: &str) -> String {
+    let mut path = src.trim_start_matches('/');
+    loop {
+        match path.strip_prefix("./").or_else(|| path.strip_prefix("../")) {
+            Some(rest) => path = rest,
+            None => return path.to_string(),
+        }
+    }
+}
+
+/// Markdown file name of a captured page (`about/team.html` -> `about-team.md`)
+fn page_slug(relative: &str) -> String {
+    let stem = relative.trim_end_matches(".html").trim_end_matches(".htm");
+    let stem = stem.strip_suffix("/index").unwrap_or(stem);
+    let slug: String = stem
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        "index".to_string()
+    } else {
+        slug
+    }
+}
+
+fn yaml_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn asset_kind(relative: &str) -> Option<&'static str> {
+    let top = relative.split('/').next().unwrap_or("");
+    match top {
+        "images" => Some("images"),
+        "fonts" => Some("fonts"),
+        "assets" => Some("files"),
+        _ => None,
+    }
+}
+
+/// Original file name of an asset, without the content hash added by the scraper
+fn clean_asset_name(file_name: &str, url: Option<&str>) -> String {
+    url.and_then(|u| url::Url::parse(u).ok())
+        .and_then(|u| u.path_segments().and_then(|mut s| s.next_back()).map(String::from))
+        .filter(|name| !name.is_empty())
+        .map(|name| name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_' { c } else { '_' }).collect())
+        .unwrap_or_else(|| file_name.to_string())
+}
+
+/// Most used `font-family` in the rules matching `selector_filter`
+fn main_font(stylesheets: &[String], selector_filter: impl Fn(&str) -> bool) -> Option<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for css in stylesheets {
+        for rule in css.split('}') {
+            let (selector, body) = match rule.split_once('{') {
+                Some(parts) => parts,
+                None => continue,
+            };
+            if !selector_filter(selector.trim()) {
+                continue;
+            }
+            for declaration in body.split(';') {
+                if let Some((property, value)) = declaration.split_once(':') {
+                    if property.trim().eq_ignore_ascii_case("font-family") && !value.contains("var(") {
+                        *counts.entry(value.trim().to_string()).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+    }
+    counts.into_iter().max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0))).map(|(font, _)| font)
+}
+
+fn tokens_css(stylesheets: &[String], site_url: &str) -> String {
+    let mut raw_colors = HashMap::new();
+    for css in stylesheets {
+        color_palette::collect_css_colors(css, &mut raw_colors);
+    }
+    let palette = color_palette::build_palette(&color_palette::cluster_colors(&raw_colors));
+
+    let mut css = format!("/* Design tokens extraits de {} */\n:root {{\n", site_url);
+    if let Some(primary) = &palette.primary {
+        css.push_str(&format!("  --color-primary: {};\n", primary));
+    }
+    for (index, color) in palette.secondary.iter().enumerate() {
+        css.push_str(&format!("  --color-secondary-{}: {};\n", index + 1, color));
+    }
+    for (index, color) in palette.accents.iter().enumerate() {
+        css.push_str(&format!("  --color-accent-{}: {};\n", index + 1, color));
+    }
+    // Neutrals from lightest (100) to darkest
+    for (index, color) in palette.neutrals.iter().enumerate() {
+        css.push_str(&format!("  --color-neutral-{}: {};\n", (index + 1) * 100, color));
+    }
+    let is_heading = |selector: &str| {
+        selector
+            .split([',', ' ', '>', '.', ':'])
+            .any(|part| matches!(part.trim(), "h1" | "h2" | "h3" | "h4" | "h5" | "h6"))
+    };
+    let body_font = main_font(stylesheets, |selector| !is_heading(selector));
+    let heading_font = main_font(stylesheets, is_heading).or_else(|| body_font.clone());
+    if let Some(font) = body_font {
+        css.push_str(&format!("  --font-body: {};\n", font));
+    }
+    if let Some(font) = heading_font {
+        css.push_str(&format!("  --font-heading: {};\n", font));
+    }
+    css.push_str("}\n");
+
+    let colors = color_palette::contrast_colors(&palette);
+    let pairs = color_palette::contrast_matrix(&colors);
+    if !pairs.is_empty() {
+        css.push_str("\n/* Contraste WCAG 2.1 (texte sur fond)\n");
+        for pair in &pairs {
+            css.push_str(&format!(
+                " * {} sur {}: {:.2}:1 {}\n",
+                pair.foreground,
+                pair.background,
+                pair.ratio,
+                pair.grade()
+            ));
+        }
+        css.push_str(" */\n");
+    }
+    css
+}
+
+fn write_file(path: &Path, content: &[u8], overwrite: bool) -> Result<(), String> {
+    if path.exists() && !overwrite {
+        return Err(format!("{} existe deja", path.display()));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Convert the capture in `capture_dir` into a project skeleton in `target_dir`
+pub fn convert(capture_dir: &Path, target_dir: &Path, options: &ConvertOptions) -> Result<ConversionResult, String> {
+    if !capture_dir.join("index.html").exists() {
+        return Err(format!("{} n'est pas une capture de site (index.html absent)", capture_dir.display()));
+    }
+    let manifest = AssetManifest::load(capture_dir).unwrap_or_default();
+    let urls_by_path: HashMap<String, String> =
+        manifest.assets.iter().map(|a| (a.local_path.replace('\\', "/"), a.url.clone())).collect();
+    let mut warnings = Vec::new();
+
+    // Assets first, so pages can point to their new paths
+    let mut assets = Vec::new();
+    let mut new_paths: HashMap<String, String> = HashMap::new();
+    let mut used_names: BTreeMap<String, usize> = BTreeMap::new();
+    let mut stylesheets = Vec::new();
+    for entry in WalkDir::new(capture_dir).into_iter().filter_map(|e| e.ok()).filter(|e| e.file_type().is_file()) {
+        let relative = match entry.path().strip_prefix(capture_dir) {
+            Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+            Err(_) => continue,
+        };
+        if relative.starts_with("css/") && relative.ends_with(".css") {
+            if let Ok(css) = fs::read_to_string(entry.path()) {
+                stylesheets.push(css);
+            }
+            continue;
+        }
+        let kind = match asset_kind(&relative) {
+            Some(kind) => kind,
+            None => continue,
+        };
+        let url = urls_by_path.get(&relative).cloned();
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let mut name = clean_asset_name(&file_name, url.as_deref());
+        // Two assets with the same original name keep distinct files
+        let seen = used_names.entry(format!("{}/{}", kind, name)).or_insert(0);
+        *seen += 1;
+        if *seen > 1 {
+            name = match name.rsplit_once('.') {
+                Some((stem, ext)) => format!("{}-{}.{}", stem, seen, ext),
+                None => format!("{}-{}", name, seen),
+            };
+        }
+        let path = format!("src/assets/{}/{}", kind, name);
+        let content = fs::read(entry.path()).map_err(|e| format!("Failed to read {}: {}", relative, e))?;
+        write_file(&target_dir.join(&path), &content, options.overwrite)?;
+        new_paths.insert(relative.clone(), format!("../assets/{}/{}", kind, name));
+        assets.push(ConvertedAsset {
+            url,
+            source: relative,
+            path,
+            kind: kind.to_string(),
+            size: content.len() as u64,
+        });
+    }
+
+    let body_selector = Selector::parse("main, article, body").map_err(|e| format!("Invalid selector: {:?}", e))?;
+    let title_selector = Selector::parse("title, h1").map_err(|e| format!("Invalid selector: {:?}", e))?;
+    let mut pages = Vec::new();
+    let mut used_slugs: BTreeMap<String, usize> = BTreeMap::new();
+    for entry in WalkDir::new(capture_dir).into_iter().filter_map(|e| e.ok()).filter(|e| e.file_type().is_file()) {
+        let relative = match entry.path().strip_prefix(capture_dir) {
+            Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+            Err(_) => continue,
+        };
+        let is_page = (relative.ends_with(".html") || relative.ends_with(".htm")) && asset_kind(&relative).is_none();
+        if !is_page || relative == "404.html" {
+            continue;
+        }
+        let html = match fs::read_to_string(entry.path()) {
+            Ok(html) => html,
+            Err(e) => {
+                warnings.push(format!("{} ignoree: {}", relative, e));
+                continue;
+            }
+        };
+        let document = Html::parse_document(&html);
+        let title = document
+            .select(&title_selector)
+            .map(|t| t.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" "))
+            .find(|t| !t.is_empty())
+            .unwrap_or_else(|| relative.clone());
+        let mut body = String::new();
+        if let Some(root) = document.select(&body_selector).next() {
+            to_markdown(root, &new_paths, &mut body);
+        }
+        let body = body.trim().to_string();
+        if body.is_empty() {
+            warnings.push(format!("{}: aucun contenu texte", relative));
+        }
+
+        let mut slug = page_slug(&relative);
+        let seen = used_slugs.entry(slug.clone()).or_insert(0);
+        *seen += 1;
+        if *seen > 1 {
+            slug = format!("{}-{}", slug, seen);
+        }
+        let path = format!("src/content/{}.md", slug);
+        let source_url = url::Url::parse(&manifest.site_url)
+            .and_then(|base| base.join(relative.trim_end_matches("index.html")))
+            .map(|u| u.to_string())
+            .unwrap_or_default();
+        let markdown = format!(
+            "---\ntitle: {}\nsource: {}\ncapture: {}\n---\n\n{}\n",
+            yaml_string(&title),
+            yaml_string(&source_url),
+            yaml_string(&relative),
+            body
+        );
+        write_file(&target_dir.join(&path), markdown.as_bytes(), options.overwrite)?;
+        pages.push(ConvertedPage {
+            words: body.split_whitespace().count(),
+            title,
+            source: relative,
+            path,
+        });
+    }
+    if stylesheets.is_empty() {
+        warnings.push("Aucune feuille de style dans la capture, tokens limites".to_string());
+    }
+
+    let tokens_path = "src/styles/tokens.css".to_string();
+    write_file(&target_dir.join(&tokens_path), tokens_css(&stylesheets, &manifest.site_url).as_bytes(), options.overwrite)?;
+
+    let manifest_path = "asset-manifest.json".to_string();
+    let manifest_json = serde_json::to_string_pretty(&serde_json::json!({
+        "siteUrl": manifest.site_url,
+        "generatedAt": chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        "assets": assets,
+    }))
+    .map_err(|e| format!("Failed to serialize asset manifest: {}", e))?;
+    write_file(&target_dir.join(&manifest_path), manifest_json.as_bytes(), options.overwrite)?;
+
+    println!(
+        "[ScrapeConvert] {} page(s), {} asset(s) converted into {}",
+        pages.len(),
+        assets.len(),
+        target_dir.display()
+    );
+    Ok(ConversionResult {
+        target_dir: target_dir.to_string_lossy().to_string(),
+        pages,
+        assets,
+        tokens_path,
+        manifest_path,
+        warnings,
+    })
+}
+
+/// Default target of a conversion: a `rebuild` folder next to the capture
+pub fn default_target(capture_dir: &Path) -> PathBuf {
+    let name = capture_dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "site".to_string());
+    capture_dir.with_file_name(format!("{}-rebuild", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_content_to_markdown() {
+        let html = r#"<html><head><title>Tarifs</title></head><body>
+<nav><a href="/">Accueil</a></nav>
+<main><h1>Nos <em>tarifs</em></h1>
+<p>Offre <strong>Pro</strong> : voir <a href="/contact">le contact</a>.</p>
+<ul><li>Un</li><li>Deux</li></ul>
+<img src="../images/logo-1a2b.png" alt="Logo"></main>
+<script>track()</script></body></html>"#;
+        let document = Html::parse_document(html);
+        let main = document.select(&Selector::parse("main").unwrap()).next().unwrap();
+        let assets = HashMap::from([("images/logo-1a2b.png".to_string(), "../assets/images/logo.png".to_string())]);
+        let mut out = String::new();
+        to_markdown(main, &assets, &mut out);
+        assert_eq!(
+            out.trim(),
+            "# Nos *tarifs*\n\nOffre **Pro** : voir [le contact](/contact).\n\n- Un\n- Deux\n\n![Logo](../assets/images/logo.png)"
+        );
+        assert_eq!(page_slug("offres/tarifs.html"), "offres-tarifs");
+        assert_eq!(page_slug("blog/index.html"), "blog");
+    }
+}
@@ -0,0 +1,394 @@
+//! Scrape Queue Module
+//!
+//! Sequential queue of full site scrapes (e.g. competitor sites for a pitch)
+//! with per-job progress and cancellation, and a combined comparison
+//! report of the extracted design systems.
+
+use crate::full_site_scraper::{self, DesignSystem, FullScrapeConfig, FullScrapeProgress};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tauri::Manager;
+
+/// A single URL waiting in or processed by the queue
+#[derive(Debug, Clone, Serialize)]
+pub struct ScrapeJob {
+    pub id: String,
+    pub url: String,
+    pub output_path: String,
+    pub status: String, // "pending", "running", "completed", "failed", "cancelled"
+    pub progress_percent: f32,
+    pub pages_downloaded: usize,
+    pub assets_downloaded: usize,
+    pub error: Option<String>,
+    pub design_system: Option<DesignSystem>,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+/// Event emitted on `scrape-queue-progress`
+#[derive(Debug, Clone, Serialize)]
+pub struct ScrapeQueueEvent {
+    pub job_id: Option<String>,
+    pub event_type: String, // "job_start", "job_progress", "job_complete", "job_error", "job_cancelled", "queue_complete"
+    pub progress: Option<FullScrapeProgress>,
+    pub message: String,
+}
+
+/// Design system highlights of one site
+#[derive(Debug, Clone, Serialize)]
+pub struct SiteDesignSummary {
+    pub url: String,
+    pub top_colors: Vec<String>,
+    pub fonts: Vec<String>,
+    pub base_font_size: Option<String>,
+    pub breakpoints: Vec<String>,
+}
+
+/// Side-by-side comparison of the completed jobs
+#[derive(Debug, Clone, Serialize)]
+pub struct DesignComparison {
+    pub sites: Vec<SiteDesignSummary>,
+    pub shared_colors: Vec<String>,
+    pub shared_fonts: Vec<String>,
+    pub report_path: Option<String>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    jobs: Vec<ScrapeJob>,
+    running: bool,
+    output_root: Option<String>,
+    current_cancel: Option<(String, Arc<AtomicBool>)>,
+}
+
+static QUEUE: Lazy<Mutex<QueueState>> = Lazy::new(|| Mutex::new(QueueState::default()));
+
+/// Number of colors listed per site in the comparison
+const TOP_COLORS: usize = 8;
+
+/// Add URLs to the queue and start processing if idle.
+///
+/// Each job writes to `<output_root>/<domain>` using `base_config` for
+/// all other scraping options.
+pub fn enqueue(
+    urls: Vec<String>,
+    output_root: &str,
+    base_config: FullScrapeConfig,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<ScrapeJob>, String> {
+    let mut added = Vec::new();
+    for url in urls {
+        let domain = url::Url::parse(&url)
+            .map_err(|e| format!("Invalid URL {}: {}", url, e))?
+            .host_str()
+            .map(|h| h.trim_start_matches("www.").to_string())
+            .ok_or_else(|| format!("URL has no host: {}", url))?;
+
+        added.push(ScrapeJob {
+            id: uuid::Uuid::new_v4().to_string(),
+            url,
+            output_path: Path::new(output_root).join(domain).to_string_lossy().to_string(),
+            status: "pending".to_string(),
+            progress_percent: 0.0,
+            pages_downloaded: 0,
+            assets_downloaded: 0,
+            error: None,
+            design_system: None,
+            started_at: None,
+            finished_at: None,
+        });
+    }
+
+    let start_worker = {
+        let mut state = QUEUE.lock().map_err(|_| "Failed to access scrape queue".to_string())?;
+        state.jobs.extend(added.iter().cloned());
+        state.output_root = Some(output_root.to_string());
+        let idle = !state.running;
+        state.running = true;
+        idle
+    };
+
+    if start_worker {
+        thread::spawn(move || run_worker(base_config, app_handle));
+    }
+
+    Ok(added)
+}
+
+/// Current state of all jobs
+pub fn list_jobs() -> Vec<ScrapeJob> {
+    QUEUE.lock().map(|state| state.jobs.clone()).unwrap_or_default()
+}
+
+/// Cancel a pending or running job
+pub fn cancel_job(job_id: &str) -> Result<(), String> {
+    let mut state = QUEUE.lock().map_err(|_| "Failed to access scrape queue".to_string())?;
+
+    if let Some((running_id, flag)) = &state.current_cancel {
+        if running_id == job_id {
+            flag.store(true, Ordering::Relaxed);
+            return Ok(());
+        }
+    }
+
+    match state.jobs.iter_mut().find(|j| j.id == job_id) {
+        Some(job) if job.status == "pending" => {
+            job.status = "cancelled".to_string();
+            Ok(())
+        }
+        Some(_) => Err("Job already finished".to_string()),
+        None => Err(format!("Job not found: {}", job_id)),
+    }
+}
+
+/// Cancel the running job and every pending one
+pub fn cancel_all() {
+    if let Ok(mut state) = QUEUE.lock() {
+        for job in state.jobs.iter_mut().filter(|j| j.status == "pending") {
+            job.status = "cancelled".to_string();
+        }
+        if let Some((_, flag)) = &state.current_cancel {
+            flag.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Remove finished jobs from the queue
+pub fn clear_finished() {
+    if let Ok(mut state) = QUEUE.lock() {
+        state.jobs.retain(|j| j.status == "pending" || j.status == "running");
+    }
+}
+
+/// Build the comparison of all completed jobs
+pub fn comparison() -> DesignComparison {
+    let (jobs, output_root) = QUEUE
+        .lock()
+        .map(|state| (state.jobs.clone(), state.output_root.clone()))
+        .unwrap_or_default();
+
+    let report_path = output_root
+        .map(|root| Path::new(&root).join("design_comparison.md"))
+        .filter(|p| p.exists())
+        .map(|p| p.to_string_lossy().to_string());
+
+    let mut result = build_comparison(&jobs);
+    result.report_path = report_path;
+    result
+}
+
+fn run_worker(base_config: FullScrapeConfig, app_handle: tauri::AppHandle) {
+    let emit = |event: ScrapeQueueEvent| {
+        let _ = app_handle.emit_all(crate::events::SCRAPE_QUEUE_PROGRESS, event);
+    };
+
+    loop {
+        // Pick the next pending job
+        let next = match QUEUE.lock() {
+            Ok(mut state) => match state.jobs.iter_mut().find(|j| j.status == "pending") {
+                Some(job) => {
+                    job.status = "running".to_string();
+                    job.started_at = Some(chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string());
+                    let flag = Arc::new(AtomicBool::new(false));
+                    let job = job.clone();
+                    state.current_cancel = Some((job.id.clone(), flag.clone()));
+                    Some((job, flag))
+                }
+                None => {
+                    state.running = false;
+                    state.current_cancel = None;
+                    None
+                }
+            },
+            Err(_) => return,
+        };
+
+        let (job, cancel_flag) = match next {
+            Some(next) => next,
+            None => break,
+        };
+
+        println!("[ScrapeQueue] Starting job {} ({})", job.id, job.url);
+        emit(ScrapeQueueEvent {
+            job_id: Some(job.id.clone()),
+            event_type: "job_start".to_string(),
+            progress: None,
+            message: format!("Scraping de {}...", job.url),
+        });
+
+        let config = FullScrapeConfig {
+            url: job.url.clone(),
+            output_path: job.output_path.clone(),
+            ..base_config.clone()
+        };
+
+        let job_id = job.id.clone();
+        let result = full_site_scraper::scrape_full_site_with_callback(config, &job.id, cancel_flag.clone(), |progress| {
+            if let Ok(mut state) = QUEUE.lock() {
+                if let Some(job) = state.jobs.iter_mut().find(|j| j.id == job_id) {
+                    job.progress_percent = progress.progress_percent;
+                    job.pages_downloaded = progress.pages_downloaded;
+                    job.assets_downloaded = progress.assets_downloaded;
+                }
+            }
+            emit(ScrapeQueueEvent {
+                job_id: Some(job_id.clone()),
+                event_type: "job_progress".to_string(),
+                message: progress.message.clone(),
+                progress: Some(progress),
+            });
+        });
+
+        let cancelled = cancel_flag.load(Ordering::Relaxed);
+        let (event_type, message) = finish_job(&job, result, cancelled);
+
+        emit(ScrapeQueueEvent {
+            job_id: Some(job.id.clone()),
+            event_type: event_type.to_string(),
+            progress: None,
+            message,
+        });
+    }
+
+    // Write the combined report once the queue drains
+    let (jobs, output_root) = QUEUE
+        .lock()
+        .map(|state| (state.jobs.clone(), state.output_root.clone()))
+        .unwrap_or_default();
+    let completed = jobs.iter().filter(|j| j.status == "completed").count();
+    if completed >= 2 {
+        if let Some(root) = output_root {
+            if let Err(e) = write_comparison_report(&root, &build_comparison(&jobs)) {
+                println!("[ScrapeQueue] Warning: Failed to write comparison report: {}", e);
+            }
+        }
+    }
+
+    emit(ScrapeQueueEvent {
+        job_id: None,
+        event_type: "queue_complete".to_string(),
+        progress: None,
+        message: format!("File de scraping terminee ({} sites)", completed),
+    });
+}
+
+/// Record the outcome of a job and return the event to emit
+fn finish_job(
+    job: &ScrapeJob,
+    result: Result<full_site_scraper::FullScrapeResult, String>,
+    cancelled: bool,
+) -> (&'static str, String) {
+    let mut state = match QUEUE.lock() {
+        Ok(state) => state,
+        Err(_) => return ("job_error", "Failed to access scrape queue".to_string()),
+    };
+    state.current_cancel = None;
+
+    let (status, event) = match &result {
+        Ok(_) => ("completed", ("job_complete", format!("{} termine", job.url))),
+        Err(e) if cancelled => ("cancelled", ("job_cancelled", e.clone())),
+        Err(e) => ("failed", ("job_error", e.clone())),
+    };
+
+    // The job may have been cleared from the queue while running
+    if let Some(entry) = state.jobs.iter_mut().find(|j| j.id == job.id) {
+        entry.status = status.to_string();
+        entry.finished_at = Some(chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string());
+        match result {
+            Ok(result) => {
+                entry.progress_percent = 100.0;
+                entry.pages_downloaded = result.pages_downloaded;
+                entry.assets_downloaded = result.assets_downloaded;
+                entry.design_system = Some(result.design_system);
+            }
+            Err(e) if !cancelled => entry.error = Some(e),
+            Err(_) => {}
+        }
+    }
+
+    event
+}
+
+fn build_comparison(jobs: &[ScrapeJob]) -> DesignComparison {
+    let sites: Vec<SiteDesignSummary> = jobs
+        .iter()
+        .filter(|j| j.status == "completed")
+        .filter_map(|j| {
+            let design = j.design_system.as_ref()?;
+            let mut colors = design.colors.clone();
+            colors.sort_by(|a, b| b.occurrences.cmp(&a.occurrences));
+            Some(SiteDesignSummary {
+                url: j.url.clone(),
+                top_colors: colors.into_iter().take(TOP_COLORS).map(|c| c.hex).collect(),
+                fonts: design.fonts.iter().map(|f| f.family.clone()).collect(),
+                base_font_size: design.typography.base_font_size.clone(),
+                breakpoints: design.breakpoints.clone(),
+            })
+        })
+        .collect();
+
+    DesignComparison {
+        shared_colors: shared_values(sites.iter().map(|s| &s.top_colors)),
+        shared_fonts: shared_values(sites.iter().map(|s| &s.fonts)),
+        sites,
+        report_path: None,
+    }
+}
+
+/// Values appearing on at least two sites
+fn shared_values<'a>(lists: impl Iterator<Item = &'a Vec<String>>) -> Vec<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for list in lists {
+        let mut seen: Vec<String> = list.iter().map(|v| v.to_lowercase()).collect();
+        seen.sort();
+        seen.dedup();
+        for value in seen {
+            *counts.entry(value).or_insert(0) += 1;
+        }
+    }
+    let mut shared: Vec<String> = counts.into_iter().filter(|(_, n)| *n >= 2).map(|(v, _)| v).collect();
+    shared.sort();
+    shared
+}
+
+fn write_comparison_report(output_root: &str, comparison: &DesignComparison) -> Result<(), String> {
+    let mut report = String::new();
+    report.push_str("# Comparaison des design systems\n\n");
+    report.push_str(&format!(
+        "Généré le {}\n\n",
+        chrono::Utc::now().format("%Y-%m-%d %H:%M UTC")
+    ));
+
+    report.push_str("| Site | Couleurs principales | Polices | Taille de base | Breakpoints |\n");
+    report.push_str("|------|----------------------|---------|----------------|-------------|\n");
+    for site in &comparison.sites {
+        report.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            site.url,
+            site.top_colors.join(", "),
+            site.fonts.join(", "),
+            site.base_font_size.as_deref().unwrap_or("-"),
+            site.breakpoints.join(", "),
+        ));
+    }
+
+    report.push_str("\n## Éléments communs\n\n");
+    report.push_str(&format!(
+        "- **Couleurs partagées** : {}\n",
+        if comparison.shared_colors.is_empty() { "aucune".to_string() } else { comparison.shared_colors.join(", ") }
+    ));
+    report.push_str(&format!(
+        "- **Polices partagées** : {}\n",
+        if comparison.shared_fonts.is_empty() { "aucune".to_string() } else { comparison.shared_fonts.join(", ") }
+    ));
+
+    fs::create_dir_all(output_root).map_err(|e| format!("Failed to create report directory: {}", e))?;
+    fs::write(Path::new(output_root).join("design_comparison.md"), report)
+        .map_err(|e| format!("Failed to write report: {}", e))
+}
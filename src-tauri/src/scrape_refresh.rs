@@ -0,0 +1,386 @@
+//! Scrape Refresh Module
+//!
+//! Keeps a manifest of the assets of a full site scrape with their HTTP
+//! validators (ETag, Last-Modified, size) so an existing capture can be
+//! refreshed by re-downloading only the assets that changed remotely.
+//! Files added by hand to the asset folders afterwards (see the watcher)
+//! are kept in the manifest as manual assets and reported by the refresh.
+
+use crate::scrape_attribution::{self, SiteAttribution};
+use crate::state_file;
+use reqwest::blocking::Client;
+use reqwest::header::{CONTENT_LENGTH, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const MANIFEST_FILE: &str = ".scrape_assets.json";
+
+/// A downloaded asset and the validators returned by the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetManifestEntry {
+    pub url: String,
+    pub local_path: String, // relative to the scrape output folder
+    pub asset_type: String, // "css", "js", "image", "font", "other"
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// Size of the remote file (before any URL rewriting)
+    pub size: u64,
+    /// When the file was downloaded (see scrape_attribution)
+    #[serde(default)]
+    pub captured_at: Option<String>,
+    /// `X-Robots-Tag` header of the file
+    #[serde(default)]
+    pub robots_tag: Option<String>,
+    /// `Link: rel="license"` header of the file
+    #[serde(default)]
+    pub license_url: Option<String>,
+}
+
+/// Assets of a scrape output folder
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AssetManifest {
+    pub site_url: String,
+    /// Whether stylesheets were rewritten to point to local files
+    pub rewrite_urls: bool,
+    pub updated_at: String,
+    pub assets: Vec<AssetManifestEntry>,
+    /// Files dropped into the asset folders after the scrape
+    #[serde(default)]
+    pub manual_assets: Vec<ManualAsset>,
+    /// Copyright, license and robots.txt hints of the site
+    #[serde(default)]
+    pub attribution: Option<SiteAttribution>,
+}
+
+/// File added by hand to a capture, under a normalized name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManualAsset {
+    pub local_path: String, // relative to the scrape output folder
+    pub asset_type: String,
+    /// Name of the file before it was normalized
+    pub original_name: String,
+    pub added_at: String,
+}
+
+impl AssetManifest {
+    pub fn load(output_path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(output_path.join(MANIFEST_FILE))
+            .map_err(|e| format!("Aucun manifeste d'assets dans cette capture: {}", e))?;
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse asset manifest: {}", e))
+    }
+
+    pub fn save(&self, output_path: &Path) -> Result<(), String> {
+        state_file::write_json(&output_path.join(MANIFEST_FILE), self)
+    }
+}
+
+/// Lowercase ASCII file name with dashes, e.g. "Photo Équipe (2).JPG" -> "photo-equipe-2.jpg"
+pub fn normalized_file_name(name: &str) -> String {
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, Some(extension.to_lowercase())),
+        _ => (name, None),
+    };
+    let mut normalized = String::new();
+    for c in stem.to_lowercase().chars() {
+        let c = match c {
+            'à' | 'á' | 'â' | 'ä' | 'ã' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'î' | 'ï' | 'í' => 'i',
+            'ô' | 'ö' | 'ó' | 'õ' => 'o',
+            'ù' | 'û' | 'ü' | 'ú' => 'u',
+            'ç' => 'c',
+            'ñ' => 'n',
+            c => c,
+        };
+        if c.is_ascii_alphanumeric() {
+            normalized.push(c);
+        } else if !normalized.ends_with('-') {
+            normalized.push('-');
+        }
+    }
+    let stem = normalized.trim_matches('-');
+    let stem = if stem.is_empty() { "fichier" } else { stem };
+    match extension {
+        Some(extension) => format!("{}.{}", stem, extension),
+        None => stem.to_string(),
+    }
+}
+
+/// Record a file added by hand to a capture; false when it was already known
+pub fn flag_manual_asset(output_path: &Path, local_path: &str, asset_type: &str, original_name: &str) -> Result<bool, String> {
+    let mut manifest = AssetManifest::load(output_path)?;
+    let known = manifest.assets.iter().any(|a| a.local_path == local_path)
+        || manifest.manual_assets.iter().any(|a| a.local_path == local_path);
+    if known {
+        return Ok(false);
+    }
+    manifest.manual_assets.push(ManualAsset {
+        local_path: local_path.to_string(),
+        asset_type: asset_type.to_string(),
+        original_name: original_name.to_string(),
+        added_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+    });
+    manifest.save(output_path)?;
+    Ok(true)
+}
+
+/// Progress event for a capture refresh
+#[derive(Debug, Clone, Serialize)]
+pub struct RefreshProgress {
+    pub project_id: String,
+    pub checked: usize,
+    pub total: usize,
+    pub current_url: String,
+}
+
+/// Outcome of a capture refresh
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct RefreshResult {
+    pub checked: usize,
+    pub unchanged: usize,
+    /// Local paths of the files updated in place
+    pub updated: Vec<String>,
+    pub failed: Vec<String>,
+    pub bytes_downloaded: u64,
+    pub cancelled: bool,
+    /// Files added by hand, kept as they are in the capture
+    pub manual_assets: Vec<String>,
+}
+
+/// Validators of a response
+pub fn response_validators(headers: &reqwest::header::HeaderMap) -> (Option<String>, Option<String>) {
+    let etag = headers.get(ETAG).and_then(|v| v.to_str().ok()).map(String::from);
+    let last_modified = headers.get(LAST_MODIFIED).and_then(|v| v.to_str().ok()).map(String::from);
+    (etag, last_modified)
+}
+
+/// Re-download the assets of a capture whose remote validators changed.
+///
+/// Files are updated in place (names stay the same, even with hashed
+/// asset names) and stylesheets are re-pointed to the local copies.
+/// HTML pages are not refreshed: a changed page needs a full re-crawl.
+pub fn refresh_capture<F>(
+    output_path: &str,
+    project_id: &str,
+    cancel_flag: Arc<AtomicBool>,
+    on_progress: F,
+) -> Result<RefreshResult, String>
+where
+    F: Fn(RefreshProgress),
+{
+    let output_base = Path::new(output_path);
+    let mut manifest = AssetManifest::load(output_base)?;
+    let client = Client::builder()
+        .timeout(Duration::from_secs(60))
+        .danger_accept_invalid_certs(true)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    // Remote URL -> local file, used to re-point refreshed stylesheets
+    let local_files: HashMap<String, PathBuf> = manifest
+        .assets
+        .iter()
+        .map(|a| (a.url.clone(), output_base.join(&a.local_path)))
+        .collect();
+
+    let mut result = RefreshResult::default();
+    let total = manifest.assets.len();
+
+    for entry in manifest.assets.iter_mut() {
+        if cancel_flag.load(Ordering::Relaxed) {
+            result.cancelled = true;
+            break;
+        }
+        on_progress(RefreshProgress {
+            project_id: project_id.to_string(),
+            checked: result.checked,
+            total,
+            current_url: entry.url.clone(),
+        });
+        result.checked += 1;
+
+        match refresh_entry(&client, entry, output_base, manifest.rewrite_urls, &local_files) {
+            Ok(Some(bytes)) => {
+                result.bytes_downloaded += bytes;
+                result.updated.push(entry.local_path.clone());
+            }
+            Ok(None) => result.unchanged += 1,
+            Err(e) => result.failed.push(format!("{}: {}", entry.url, e)),
+        }
+    }
+
+    // Manual additions deleted since then are forgotten
+    manifest.manual_assets.retain(|a| output_base.join(&a.local_path).is_file());
+    result.manual_assets = manifest.manual_assets.iter().map(|a| a.local_path.clone()).collect();
+
+    manifest.updated_at = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    manifest.save(output_base)?;
+
+    println!(
+        "[ScrapeRefresh] {} checked, {} updated, {} failed, {} manual",
+        result.checked,
+        result.updated.len(),
+        result.failed.len(),
+        result.manual_assets.len()
+    );
+    Ok(result)
+}
+
+/// `Some(bytes)` when the asset changed and was rewritten, `None` when unchanged
+fn refresh_entry(
+    client: &Client,
+    entry: &mut AssetManifestEntry,
+    output_base: &Path,
+    rewrite_urls: bool,
+    local_files: &HashMap<String, PathBuf>,
+) -> Result<Option<u64>, String> {
+    let has_validators = entry.etag.is_some() || entry.last_modified.is_some();
+
+    // Without validators, a HEAD request tells whether the size changed
+    if !has_validators {
+        let head = client.head(&entry.url).send().map_err(|e| format!("Request failed: {}", e))?;
+        let remote_size = head
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
+        if head.status().is_success() && remote_size == Some(entry.size) {
+            return Ok(None);
+        }
+    }
+
+    let mut request = client.get(&entry.url);
+    if let Some(etag) = &entry.etag {
+        request = request.header(IF_NONE_MATCH, etag.as_str());
+    }
+    if let Some(last_modified) = &entry.last_modified {
+        request = request.header(IF_MODIFIED_SINCE, last_modified.as_str());
+    }
+    let response = request.send().map_err(|e| format!("Request failed: {}", e))?;
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+
+    let (etag, last_modified) = response_validators(response.headers());
+    let (robots_tag, license_url) = scrape_attribution::header_hints(response.headers());
+    // Servers ignoring conditional requests still send the same ETag
+    if etag.is_some() && etag == entry.etag {
+        return Ok(None);
+    }
+    let bytes = response.bytes().map_err(|e| format!("Failed to read bytes: {}", e))?;
+    entry.etag = etag;
+    entry.last_modified = last_modified;
+
+    if !has_validators && bytes.len() as u64 == entry.size {
+        return Ok(None);
+    }
+    entry.size = bytes.len() as u64;
+    entry.captured_at = Some(chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string());
+    entry.robots_tag = robots_tag;
+    entry.license_url = license_url;
+
+    let local_path = output_base.join(&entry.local_path);
+    let content = if entry.asset_type == "css" && rewrite_urls {
+        let css = String::from_utf8_lossy(&bytes);
+        rewrite_stylesheet(&css, &local_path, local_files).into_bytes()
+    } else {
+        bytes.to_vec()
+    };
+    fs::write(&local_path, &content).map_err(|e| format!("Failed to write file: {}", e))?;
+
+    Ok(Some(bytes.len() as u64))
+}
+
+/// Point `url(...)` references of a refreshed stylesheet to the local copies
+fn rewrite_stylesheet(css: &str, css_path: &Path, local_files: &HashMap<String, PathBuf>) -> String {
+    let css_dir = css_path.parent().unwrap_or(Path::new(""));
+    let mut result = css.to_string();
+
+    for (url, local_path) in local_files {
+        let relative = match relative_path(local_path, css_dir) {
+            Some(relative) => relative,
+            None => continue,
+        };
+        result = result.replace(&format!("url(\"{}\")", url), &format!("url(\"{}\")", relative));
+        result = result.replace(&format!("url('{}')", url), &format!("url('{}')", relative));
+        result = result.replace(&format!("url({})", url), &format!("url({})", relative));
+    }
+
+    result
+}
+
+/// Relative path from directory `from` to file `to` (both inside the capture)
+fn relative_path(to: &Path, from: &Path) -> Option<String> {
+    let to_parts: Vec<_> = to.components().collect();
+    let from_parts: Vec<_> = from.components().collect();
+    let common = to_parts.iter().zip(&from_parts).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        return None;
+    }
+
+    let mut parts: Vec<String> = vec!["..".to_string(); from_parts.len() - common];
+    parts.extend(to_parts[common..].iter().map(|c| c.as_os_str().to_string_lossy().to_string()));
+    Some(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_manual_files_named_and_recorded_once() {
+        assert_eq!(normalized_file_name("Photo Équipe (2).JPG"), "photo-equipe-2.jpg");
+        assert_eq!(normalized_file_name(".htaccess"), "htaccess");
+        assert_eq!(normalized_file_name("!!!.png"), "fichier.png");
+
+        let output = TempDir::new("scrape-refresh-manual");
+        assert!(flag_manual_asset(output.path(), "images/logo.png", "image", "Logo.PNG").is_err());
+        let manifest = AssetManifest {
+            site_url: "https://example.com".to_string(),
+            assets: vec![AssetManifestEntry {
+                url: "https://example.com/css/site.css".to_string(),
+                local_path: "css/site.css".to_string(),
+                asset_type: "css".to_string(),
+                etag: None,
+                last_modified: None,
+                size: 6,
+                captured_at: None,
+                robots_tag: None,
+                license_url: None,
+            }],
+            ..Default::default()
+        };
+        manifest.save(output.path()).unwrap();
+
+        assert!(!flag_manual_asset(output.path(), "css/site.css", "css", "site.css").unwrap());
+        assert!(flag_manual_asset(output.path(), "images/logo.png", "image", "Logo.PNG").unwrap());
+        assert!(!flag_manual_asset(output.path(), "images/logo.png", "image", "Logo.PNG").unwrap());
+        let manual = AssetManifest::load(output.path()).unwrap().manual_assets;
+        assert_eq!((manual.len(), manual[0].original_name.as_str()), (1, "Logo.PNG"));
+    }
+
+    #[test]
+    fn test_refreshed_stylesheet_points_to_local_files() {
+        let local_files = HashMap::from([
+            ("https://cdn.example.com/fonts/inter.woff2".to_string(), PathBuf::from("out/fonts/inter.woff2")),
+            ("/img/bg.png".to_string(), PathBuf::from("out/images/bg.png")),
+        ]);
+        let css = "@font-face{src:url('https://cdn.example.com/fonts/inter.woff2')} body{background:url(/img/bg.png)}";
+        assert_eq!(
+            rewrite_stylesheet(css, Path::new("out/css/site.css"), &local_files),
+            "@font-face{src:url('../fonts/inter.woff2')} body{background:url(../images/bg.png)}"
+        );
+        assert_eq!(relative_path(Path::new("other/a.png"), Path::new("out/css")), None);
+    }
+}
@@ -1,10 +1,15 @@
+use crate::content_inventory::{self, ContentInventory, PageInventory};
+use crate::events::ScrapeEventKind;
+use crate::image_gallery;
+use crate::proxy::{self, ProxyConfig};
+use crate::image_metadata::{self, ImageMetadataCollector, ImageUsage};
 use reqwest::blocking::Client;
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use url::Url;
 
@@ -18,6 +23,12 @@ pub struct ScrapeResult {
     pub colors: Vec<String>,
     pub texts: Vec<ExtractedText>,
     pub site_structure: Vec<SiteLink>,
+    /// Pages, words and images per page, for quoting a migration
+    pub content_inventory: ContentInventory,
+    /// `scraped/gallery/index.html` when images were downloaded
+    pub gallery_path: Option<String>,
+    /// `scraped/images/image-metadata.json`: alt, title and caption of each image
+    pub image_metadata_path: Option<String>,
     pub errors: Vec<String>,
 }
 
@@ -40,6 +51,9 @@ pub struct ExtractedText {
     pub page_url: String,
     pub element_type: String, // h1, h2, p, etc.
     pub content: String,
+    /// ISO 639-1 code, the page's language when the text is too short to tell
+    pub language: Option<String>,
+    pub word_count: usize,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -58,6 +72,9 @@ pub struct ScrapeConfig {
     pub download_images: bool,
     pub download_css: bool,
     pub extract_text: bool,
+    /// SOCKS5 or HTTP proxy the requests go through
+    #[serde(default)]
+    pub proxy: Option<ProxyConfig>,
 }
 
 /// Scraper state for tracking progress
@@ -70,9 +87,10 @@ pub struct Scraper {
 
 impl Scraper {
     pub fn new(config: ScrapeConfig) -> Result<Self, String> {
-        let client = Client::builder()
+        let builder = Client::builder()
             .timeout(Duration::from_secs(30))
-            .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
+            .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36");
+        let client = proxy::apply(builder, config.proxy.as_ref())?
             .build()
             .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
 
@@ -96,8 +114,13 @@ impl Scraper {
             colors: HashSet::new().into_iter().collect(),
             texts: Vec::new(),
             site_structure: Vec::new(),
+            content_inventory: ContentInventory::default(),
+            gallery_path: None,
+            image_metadata_path: None,
             errors: Vec::new(),
         };
+        let mut inventory_pages: Vec<PageInventory> = Vec::new();
+        let mut image_metadata = ImageMetadataCollector::default();
 
         let mut colors_set: HashSet<String> = HashSet::new();
         let mut fonts_set: HashSet<String> = HashSet::new();
@@ -137,6 +160,7 @@ impl Scraper {
                 Ok(page_result) => {
                     // Add page
                     result.pages.push(page_result.page);
+                    inventory_pages.push(page_result.inventory);
 
                     // Add new URLs to visit
                     for link in &page_result.links {
@@ -148,6 +172,7 @@ impl Scraper {
 
                     // Process images
                     if self.config.download_images {
+                        image_metadata.extend(page_result.image_usages);
                         for image_url in page_result.images {
                             match self.download_asset(&image_url, &images_dir, "image") {
                                 Ok(asset) => result.images.push(asset),
@@ -186,10 +211,13 @@ impl Scraper {
 
         result.colors = colors_set.into_iter().collect();
         result.fonts = fonts_set.into_iter().collect();
+        result.content_inventory = ContentInventory::from_pages(inventory_pages);
+        self.write_gallery(&mut result, &scraped_dir);
+        write_image_metadata(&mut result, &image_metadata, &images_dir);
 
         // Save extracted texts to file
         if !result.texts.is_empty() {
-            if let Err(e) = self.save_texts(&result.texts, &texts_dir) {
+            if let Err(e) = self.save_texts(&result.texts, &result.content_inventory, &texts_dir) {
                 result.errors.push(format!("Failed to save texts: {}", e));
             }
         }
@@ -210,8 +238,13 @@ impl Scraper {
             colors: HashSet::new().into_iter().collect(),
             texts: Vec::new(),
             site_structure: Vec::new(),
+            content_inventory: ContentInventory::default(),
+            gallery_path: None,
+            image_metadata_path: None,
             errors: Vec::new(),
         };
+        let mut inventory_pages: Vec<PageInventory> = Vec::new();
+        let mut image_metadata = ImageMetadataCollector::default();
 
         let mut colors_set: HashSet<String> = HashSet::new();
         let mut fonts_set: HashSet<String> = HashSet::new();
@@ -234,7 +267,7 @@ impl Scraper {
 
         // Emit start event
         on_progress(ScrapeProgress {
-            event_type: "start".to_string(),
+            event_type: ScrapeEventKind::Start,
             url: Some(self.config.url.clone()),
             title: None,
             pages_scraped: 0,
@@ -266,7 +299,7 @@ impl Scraper {
             // Emit page start event
             let progress = (self.visited_urls.len() as f32 / max_pages as f32 * 100.0).min(100.0);
             on_progress(ScrapeProgress {
-                event_type: "page_start".to_string(),
+                event_type: ScrapeEventKind::PageStart,
                 url: Some(url.clone()),
                 title: None,
                 pages_scraped: self.visited_urls.len(),
@@ -281,6 +314,7 @@ impl Scraper {
                 Ok(page_result) => {
                     let page_title = page_result.page.title.clone();
                     result.pages.push(page_result.page);
+                    inventory_pages.push(page_result.inventory);
 
                     // Add new URLs to visit
                     for link in &page_result.links {
@@ -292,13 +326,14 @@ impl Scraper {
 
                     // Process images
                     if self.config.download_images {
+                        image_metadata.extend(page_result.image_usages);
                         for image_url in page_result.images {
                             match self.download_asset(&image_url, &images_dir, "image") {
                                 Ok(asset) => {
                                     result.images.push(asset);
                                     images_count += 1;
                                     on_progress(ScrapeProgress {
-                                        event_type: "image_download".to_string(),
+                                        event_type: ScrapeEventKind::ImageDownload,
                                         url: Some(image_url),
                                         title: None,
                                         pages_scraped: self.visited_urls.len(),
@@ -324,7 +359,7 @@ impl Scraper {
                                     fonts_set.extend(new_fonts);
                                     css_count += 1;
                                     on_progress(ScrapeProgress {
-                                        event_type: "css_download".to_string(),
+                                        event_type: ScrapeEventKind::CssDownload,
                                         url: Some(css_url),
                                         title: None,
                                         pages_scraped: self.visited_urls.len(),
@@ -350,7 +385,7 @@ impl Scraper {
 
                     // Emit page complete event
                     on_progress(ScrapeProgress {
-                        event_type: "page_complete".to_string(),
+                        event_type: ScrapeEventKind::PageComplete,
                         url: Some(url.clone()),
                         title: Some(page_title),
                         pages_scraped: self.visited_urls.len(),
@@ -364,7 +399,7 @@ impl Scraper {
                 Err(e) => {
                     result.errors.push(format!("Failed to scrape {}: {}", url, e));
                     on_progress(ScrapeProgress {
-                        event_type: "error".to_string(),
+                        event_type: ScrapeEventKind::Error,
                         url: Some(url.clone()),
                         title: None,
                         pages_scraped: self.visited_urls.len(),
@@ -380,17 +415,20 @@ impl Scraper {
 
         result.colors = colors_set.into_iter().collect();
         result.fonts = fonts_set.into_iter().collect();
+        result.content_inventory = ContentInventory::from_pages(inventory_pages);
+        self.write_gallery(&mut result, &scraped_dir);
+        write_image_metadata(&mut result, &image_metadata, &images_dir);
 
         // Save extracted texts to file
         if !result.texts.is_empty() {
-            if let Err(e) = self.save_texts(&result.texts, &texts_dir) {
+            if let Err(e) = self.save_texts(&result.texts, &result.content_inventory, &texts_dir) {
                 result.errors.push(format!("Failed to save texts: {}", e));
             }
         }
 
         // Emit complete event
         on_progress(ScrapeProgress {
-            event_type: "complete".to_string(),
+            event_type: ScrapeEventKind::Complete,
             url: None,
             title: None,
             pages_scraped: result.pages.len(),
@@ -458,6 +496,9 @@ impl Scraper {
             }
         }
 
+        // Alt, title and caption of every image, for the metadata sidecar
+        let image_usages = image_metadata::page_usages(url, &document, &base_url);
+
         // Extract stylesheets
         let css_selector = Selector::parse("link[rel='stylesheet'][href]").unwrap();
         let mut stylesheets = Vec::new();
@@ -487,12 +528,21 @@ impl Scraper {
                     texts.push(ExtractedText {
                         page_url: url.to_string(),
                         element_type: element_type.to_string(),
+                        language: content_inventory::detect_language(&content),
+                        word_count: content_inventory::word_count(&content),
                         content,
                     });
                 }
             }
         }
 
+        // Page totals; short texts take the language of their page
+        let blocks: Vec<&str> = texts.iter().map(|t| t.content.as_str()).collect();
+        let inventory = content_inventory::page_inventory(url, &document, &blocks);
+        for text in texts.iter_mut().filter(|t| t.language.is_none()) {
+            text.language = inventory.language.clone();
+        }
+
         // Extract inline style colors
         let style_selector = Selector::parse("[style]").unwrap();
         let mut inline_colors = Vec::new();
@@ -527,9 +577,11 @@ impl Scraper {
             },
             links,
             images,
+            image_usages,
             stylesheets,
             texts,
             inline_colors,
+            inventory,
         })
     }
 
@@ -661,8 +713,23 @@ impl Scraper {
         Ok((asset, colors, fonts))
     }
 
-    fn save_texts(&self, texts: &[ExtractedText], output_dir: &Path) -> Result<(), String> {
-        let mut content = String::new();
+    fn write_gallery(&self, result: &mut ScrapeResult, scraped_dir: &Path) {
+        if result.images.is_empty() {
+            return;
+        }
+        let images: Vec<(PathBuf, String)> = result
+            .images
+            .iter()
+            .map(|asset| (PathBuf::from(&asset.local_path), asset.url.clone()))
+            .collect();
+        match image_gallery::generate_gallery(scraped_dir, &self.config.url, &images) {
+            Ok(gallery) => result.gallery_path = Some(gallery.path),
+            Err(e) => result.errors.push(e),
+        }
+    }
+
+    fn save_texts(&self, texts: &[ExtractedText], inventory: &ContentInventory, output_dir: &Path) -> Result<(), String> {
+        let mut content = inventory.to_markdown();
         let mut current_page = String::new();
 
         for text in texts {
@@ -690,13 +757,27 @@ impl Scraper {
     }
 }
 
+fn write_image_metadata(result: &mut ScrapeResult, collector: &ImageMetadataCollector, images_dir: &Path) {
+    let images: Vec<(PathBuf, String)> = result
+        .images
+        .iter()
+        .map(|asset| (PathBuf::from(&asset.local_path), asset.url.clone()))
+        .collect();
+    match collector.write(images_dir, &images) {
+        Ok(path) => result.image_metadata_path = path,
+        Err(e) => result.errors.push(e),
+    }
+}
+
 struct PageScrapeResult {
     page: ScrapedPage,
     links: Vec<SiteLink>,
     images: Vec<String>,
+    image_usages: Vec<(String, ImageUsage)>,
     stylesheets: Vec<String>,
     texts: Vec<ExtractedText>,
     inline_colors: Vec<String>,
+    inventory: PageInventory,
 }
 
 fn sanitize_filename(name: &str) -> String {
@@ -708,7 +789,7 @@ fn sanitize_filename(name: &str) -> String {
 // Progress callback data
 #[derive(Debug, Clone, Serialize)]
 pub struct ScrapeProgress {
-    pub event_type: String,  // "page_start", "page_complete", "image_download", "css_download", "complete", "error"
+    pub event_type: ScrapeEventKind,
     pub url: Option<String>,
     pub title: Option<String>,
     pub pages_scraped: usize,
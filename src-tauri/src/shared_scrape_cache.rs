@@ -0,0 +1,351 @@
+//! Shared Scrape Cache Module
+//!
+//! Optional asset cache shared by every project, keyed by domain, so
+//! capturing the same site for two related projects downloads each asset
+//! once. Contents are stored by hash under `<root>/<domain>/blobs` and
+//! always copied into the project folder: a project never points into the
+//! cache, which can be cleared at any time.
+
+use crate::state_file;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use url::Url;
+
+/// Cached assets older than this are downloaded again (same TTL as the project cache)
+const TTL_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedEntry {
+    pub url: String,
+    /// SHA-256 of the content, name of the blob
+    pub blob: String,
+    pub size: u64,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub fetched_at: u64,
+    /// Projects that used this asset
+    pub projects: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct DomainIndex {
+    domain: String,
+    /// Entries by URL
+    entries: BTreeMap<String, SharedEntry>,
+    updated_at: u64,
+}
+
+/// Cached content returned to the scraper
+pub struct CachedAsset {
+    pub bytes: Vec<u8>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DomainSummary {
+    pub domain: String,
+    pub entries: usize,
+    /// Size of the stored blobs (shared contents counted once)
+    pub bytes: u64,
+    pub projects: Vec<String>,
+    pub updated_at: u64,
+}
+
+/// The part of a domain's cache a project has used
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectCacheView {
+    pub domain: String,
+    pub entries: Vec<SharedEntry>,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CopySummary {
+    pub domain: String,
+    pub target_dir: String,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// Cache of one domain, opened for the duration of a scrape
+pub struct SharedScrapeCache {
+    root: PathBuf,
+    index: DomainIndex,
+    hits: usize,
+}
+
+fn current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Cache key of a site: its host without "www."
+pub fn domain_of(url: &str) -> Option<String> {
+    let parsed = Url::parse(url).ok()?;
+    let host = parsed.host_str()?.to_lowercase();
+    Some(host.trim_start_matches("www.").to_string())
+}
+
+fn domain_dir(root: &Path, domain: &str) -> Result<PathBuf, String> {
+    let valid = !domain.is_empty()
+        && domain != ".."
+        && domain.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+    if !valid {
+        return Err(format!("Domaine invalide: {}", domain));
+    }
+    Ok(root.join(domain))
+}
+
+fn index_path(dir: &Path) -> PathBuf {
+    dir.join("index.json")
+}
+
+fn load_index(dir: &Path, domain: &str) -> Result<DomainIndex, String> {
+    Ok(state_file::read_json(&index_path(dir))?.unwrap_or_else(|| DomainIndex {
+        domain: domain.to_string(),
+        ..Default::default()
+    }))
+}
+
+impl SharedScrapeCache {
+    /// Open the cache of the domain of `site_url`
+    pub fn open(root: &Path, site_url: &str) -> Result<Self, String> {
+        let domain = domain_of(site_url).ok_or_else(|| format!("URL sans domaine: {}", site_url))?;
+        let dir = domain_dir(root, &domain)?;
+        Ok(Self {
+            index: load_index(&dir, &domain)?,
+            root: dir,
+            hits: 0,
+        })
+    }
+
+    fn blob_path(&self, blob: &str) -> PathBuf {
+        self.root.join("blobs").join(blob)
+    }
+
+    /// Fresh cached content of `url`, recorded as used by the project
+    pub fn lookup(&mut self, url: &str, project_id: &str) -> Option<CachedAsset> {
+        let now = current_timestamp();
+        let entry = self.index.entries.get(url)?;
+        if now.saturating_sub(entry.fetched_at) >= TTL_SECONDS {
+            return None;
+        }
+        let bytes = fs::read(self.blob_path(&entry.blob)).ok()?;
+        let asset = CachedAsset {
+            bytes,
+            etag: entry.etag.clone(),
+            last_modified: entry.last_modified.clone(),
+        };
+        if let Some(entry) = self.index.entries.get_mut(url) {
+            entry.projects.insert(project_id.to_string());
+        }
+        self.hits += 1;
+        Some(asset)
+    }
+
+    /// Add freshly downloaded content
+    pub fn store(&mut self, url: &str, project_id: &str, bytes: &[u8], etag: Option<String>, last_modified: Option<String>) -> Result<(), String> {
+        let blob = format!("{:x}", Sha256::digest(bytes));
+        let blob_path = self.blob_path(&blob);
+        if !blob_path.exists() {
+            state_file::write_atomic(&blob_path, bytes)?;
+        }
+        let now = current_timestamp();
+        let mut projects = self.index.entries.remove(url).map(|e| e.projects).unwrap_or_default();
+        projects.insert(project_id.to_string());
+        self.index.entries.insert(
+            url.to_string(),
+            SharedEntry {
+                url: url.to_string(),
+                blob,
+                size: bytes.len() as u64,
+                etag,
+                last_modified,
+                fetched_at: now,
+                projects,
+            },
+        );
+        self.index.updated_at = now;
+        Ok(())
+    }
+
+    /// Assets served from the cache since it was opened
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        state_file::write_json(&index_path(&self.root), &self.index)
+    }
+}
+
+fn list_indexes(root: &Path) -> Vec<(PathBuf, DomainIndex)> {
+    let mut indexes: Vec<(PathBuf, DomainIndex)> = fs::read_dir(root)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().is_dir())
+                .filter_map(|e| {
+                    let domain = e.file_name().to_string_lossy().to_string();
+                    load_index(&e.path(), &domain).ok().map(|index| (e.path(), index))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    indexes.sort_by(|a, b| a.1.domain.cmp(&b.1.domain));
+    indexes
+}
+
+/// Size of the distinct blobs of some entries
+fn blob_bytes<'a>(entries: impl Iterator<Item = &'a SharedEntry>) -> u64 {
+    let mut seen = BTreeSet::new();
+    entries.filter(|e| seen.insert(e.blob.clone())).map(|e| e.size).sum()
+}
+
+pub fn list_domains(root: &Path) -> Vec<DomainSummary> {
+    list_indexes(root)
+        .into_iter()
+        .map(|(_, index)| DomainSummary {
+            entries: index.entries.len(),
+            bytes: blob_bytes(index.entries.values()),
+            projects: index
+                .entries
+                .values()
+                .flat_map(|e| e.projects.iter().cloned())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect(),
+            updated_at: index.updated_at,
+            domain: index.domain,
+        })
+        .collect()
+}
+
+/// Cached assets used by a project, by domain
+pub fn project_view(root: &Path, project_id: &str) -> Vec<ProjectCacheView> {
+    list_indexes(root)
+        .into_iter()
+        .filter_map(|(_, index)| {
+            let entries: Vec<SharedEntry> = index
+                .entries
+                .into_values()
+                .filter(|e| e.projects.contains(project_id))
+                .collect();
+            if entries.is_empty() {
+                return None;
+            }
+            Some(ProjectCacheView {
+                bytes: blob_bytes(entries.iter()),
+                domain: index.domain,
+                entries,
+            })
+        })
+        .collect()
+}
+
+/// Relative path of an asset in a copy, mirroring its URL path
+fn relative_path(url: &str) -> Option<PathBuf> {
+    let parsed = Url::parse(url).ok()?;
+    let segments: Vec<&str> = parsed
+        .path_segments()?
+        .filter(|s| !s.is_empty() && *s != "." && *s != "..")
+        .collect();
+    if segments.is_empty() {
+        return None;
+    }
+    Some(segments.iter().collect())
+}
+
+/// Copy every cached asset of a domain into `target_dir`, recording the project as a user
+pub fn copy_into_project(root: &Path, domain: &str, project_id: &str, target_dir: &str) -> Result<CopySummary, String> {
+    let dir = domain_dir(root, domain)?;
+    let mut index = load_index(&dir, domain)?;
+    if index.entries.is_empty() {
+        return Err(format!("Aucun element en cache pour {}", domain));
+    }
+
+    let target = Path::new(target_dir);
+    let mut files = 0;
+    let mut bytes = 0;
+    for entry in index.entries.values_mut() {
+        let relative = match relative_path(&entry.url) {
+            Some(relative) => relative,
+            None => continue,
+        };
+        let destination = target.join(relative);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        fs::copy(dir.join("blobs").join(&entry.blob), &destination)
+            .map_err(|e| format!("Failed to copy {}: {}", entry.url, e))?;
+        entry.projects.insert(project_id.to_string());
+        files += 1;
+        bytes += entry.size;
+    }
+    state_file::write_json(&index_path(&dir), &index)?;
+
+    println!("[SharedScrapeCache] Copied {} assets of {} into {}", files, domain, target_dir);
+    Ok(CopySummary {
+        domain: domain.to_string(),
+        target_dir: target_dir.to_string(),
+        files,
+        bytes,
+    })
+}
+
+/// Remove a domain's cache, returns the freed bytes
+pub fn clear_domain(root: &Path, domain: &str) -> Result<u64, String> {
+    let dir = domain_dir(root, domain)?;
+    if !dir.exists() {
+        return Ok(0);
+    }
+    let freed = blob_bytes(load_index(&dir, domain)?.entries.values());
+    fs::remove_dir_all(&dir).map_err(|e| format!("Failed to clear shared cache: {}", e))?;
+    println!("[SharedScrapeCache] Cleared {}", domain);
+    Ok(freed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_assets_shared_between_projects() {
+        let root = TempDir::new("shared-scrape-cache");
+        let mut cache = SharedScrapeCache::open(root.path(), "https://www.Example.com/fr/").unwrap();
+        assert!(cache.lookup("https://example.com/css/site.css", "shop").is_none());
+        cache.store("https://example.com/css/site.css", "shop", b"body{}", Some("\"v1\"".to_string()), None).unwrap();
+        // Same content under another URL: one blob
+        cache.store("https://example.com/css/copy.css", "shop", b"body{}", None, None).unwrap();
+        cache.save().unwrap();
+
+        let mut reopened = SharedScrapeCache::open(root.path(), "http://example.com").unwrap();
+        let asset = reopened.lookup("https://example.com/css/site.css", "blog").unwrap();
+        assert_eq!((asset.bytes.as_slice(), asset.etag.as_deref()), (&b"body{}"[..], Some("\"v1\"")));
+        assert_eq!(reopened.hits(), 1);
+        reopened.save().unwrap();
+
+        let domains = list_domains(root.path());
+        assert_eq!(domains.len(), 1);
+        assert_eq!((domains[0].domain.as_str(), domains[0].entries, domains[0].bytes), ("example.com", 2, 6));
+        assert_eq!(domains[0].projects, ["blog", "shop"]);
+        assert_eq!(project_view(root.path(), "blog")[0].entries.len(), 1);
+
+        let target = TempDir::new("shared-scrape-cache-copy");
+        let copied = copy_into_project(root.path(), "example.com", "docs", &target.path_str()).unwrap();
+        assert_eq!((copied.files, copied.bytes), (2, 12));
+        assert_eq!(fs::read_to_string(target.path().join("css/copy.css")).unwrap(), "body{}");
+
+        assert!(clear_domain(root.path(), "../etc").is_err());
+        assert_eq!(clear_domain(root.path(), "example.com").unwrap(), 6);
+        assert!(list_domains(root.path()).is_empty());
+    }
+}
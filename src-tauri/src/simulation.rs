@@ -0,0 +1,200 @@
+//! Simulation Module
+//!
+//! Global "simulate" switch for destructive operations (sync, snapshot
+//! pruning, cache and session cleanup, file deletions). While it is on, those
+//! operations leave everything in place and describe what they would have
+//! done as a `SimulationReport`.
+
+use crate::delta_sync::SignatureCache;
+use crate::file_type_stats::{self, FileTypeStats};
+use crate::scrape_cache::ScrapeCache;
+use crate::storage::CleanupReport;
+use crate::transfer_resume::TransferSessionStore;
+use crate::version_history::ProjectVersionHistory;
+use crate::FileDiff;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use walkdir::WalkDir;
+
+static SIMULATE: AtomicBool = AtomicBool::new(false);
+
+pub fn is_enabled() -> bool {
+    SIMULATE.load(Ordering::Relaxed)
+}
+
+pub fn set_enabled(enabled: bool) {
+    SIMULATE.store(enabled, Ordering::Relaxed);
+    println!("[Simulation] Simulation mode {}", if enabled { "enabled" } else { "disabled" });
+}
+
+/// One change an operation would make
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulatedAction {
+    /// "upload", "delete_file", "delete_folder", "prune_snapshot",
+    /// "delete_session", "clear_cache_entry" or "keep"
+    pub kind: String,
+    pub target: String,
+    pub bytes: Option<u64>,
+    pub detail: Option<String>,
+}
+
+/// What an operation would do, returned instead of doing it
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationReport {
+    pub operation: String,
+    pub actions: Vec<SimulatedAction>,
+    /// Bytes uploaded or freed by the actions
+    pub total_bytes: u64,
+    pub notes: Vec<String>,
+    /// Composition of the uploads of a sync
+    pub file_types: Option<FileTypeStats>,
+}
+
+impl SimulationReport {
+    fn new(operation: &str) -> Self {
+        Self {
+            operation: operation.to_string(),
+            actions: Vec::new(),
+            total_bytes: 0,
+            notes: Vec::new(),
+            file_types: None,
+        }
+    }
+
+    fn push(&mut self, kind: &str, target: &str, bytes: Option<u64>, detail: Option<String>) {
+        if kind != "keep" {
+            self.total_bytes += bytes.unwrap_or(0);
+        }
+        self.actions.push(SimulatedAction {
+            kind: kind.to_string(),
+            target: target.to_string(),
+            bytes,
+            detail,
+        });
+    }
+}
+
+/// Uploads of a sync; remote-only files are listed as kept since sync never deletes them
+pub fn plan_sync(diffs: &[FileDiff]) -> SimulationReport {
+    let mut report = SimulationReport::new("sync");
+    for diff in diffs {
+        match diff.status.as_str() {
+            "added" | "modified" => report.push("upload", &diff.path, diff.local_size, Some(diff.status.clone())),
+            "deleted" => report.push(
+                "keep",
+                &diff.path,
+                diff.remote_size,
+                Some("Present uniquement sur le serveur, non supprime".to_string()),
+            ),
+            "oversized" => report.push("keep", &diff.path, diff.local_size, Some("Fichier trop volumineux, ignore".to_string())),
+            _ => {}
+        }
+    }
+    report.file_types = Some(file_type_stats::compute(diffs));
+    report
+}
+
+/// Snapshots (and their backup files) dropped to respect `max_snapshots` after `incoming` new ones
+pub fn plan_snapshot_prune(history: &ProjectVersionHistory, max_snapshots: usize, incoming: usize) -> SimulationReport {
+    let mut report = SimulationReport::new("snapshot_prune");
+    let excess = (history.snapshots.len() + incoming).saturating_sub(max_snapshots);
+    for snapshot in history.snapshots.iter().take(excess) {
+        let backups: Vec<_> = snapshot.files.iter().filter(|f| f.backup_path.is_some()).collect();
+        report.push(
+            "prune_snapshot",
+            &snapshot.id,
+            Some(backups.iter().map(|f| f.size).sum()),
+            Some(format!("{} ({} fichiers sauvegardes)", snapshot.timestamp, backups.len())),
+        );
+    }
+    report
+}
+
+/// Transfer sessions removed by a cleanup with the given retention
+pub fn plan_session_cleanup(store: &TransferSessionStore, max_age_hours: i64) -> SimulationReport {
+    let mut report = SimulationReport::new("transfer_session_cleanup");
+    let mut pruned = store.clone();
+    pruned.cleanup_old_sessions(max_age_hours);
+    for session in store.sessions.values().filter(|s| !pruned.sessions.contains_key(&s.id)) {
+        report.push("delete_session", &session.id, None, Some(session.started_at.clone()));
+    }
+    report
+}
+
+pub fn plan_delete_session(store: &TransferSessionStore, session_id: &str) -> SimulationReport {
+    let mut report = SimulationReport::new("delete_transfer_session");
+    match store.sessions.get(session_id) {
+        Some(session) => {
+            let pending = session.files.values().filter(|f| f.transferred_bytes < f.total_size).count();
+            report.push(
+                "delete_session",
+                session_id,
+                None,
+                Some(format!("{} fichiers, {} non termines", session.files.len(), pending)),
+            );
+        }
+        None => report.notes.push("Session introuvable, rien a supprimer".to_string()),
+    }
+    report
+}
+
+pub fn plan_clear_delta_cache(cache: &SignatureCache) -> SimulationReport {
+    let mut report = SimulationReport::new("clear_delta_cache");
+    let mut paths: Vec<_> = cache.signatures.keys().collect();
+    paths.sort();
+    for path in paths {
+        report.push("clear_cache_entry", path, None, None);
+    }
+    report
+}
+
+pub fn plan_clear_scrape_cache(cache: Option<&ScrapeCache>) -> SimulationReport {
+    let mut report = SimulationReport::new("clear_scrape_cache");
+    if let Some(cache) = cache {
+        let mut urls: Vec<_> = cache.entries.values().map(|e| e.url.as_str()).collect();
+        urls.sort();
+        for url in urls {
+            report.push("clear_cache_entry", url, None, None);
+        }
+    }
+    report
+}
+
+/// Storage cleanup computed as a dry run
+pub fn plan_storage_cleanup(cleanup: &CleanupReport) -> SimulationReport {
+    let mut report = SimulationReport::new("storage_cleanup");
+    for item in &cleanup.items {
+        let kind = match item.category.as_str() {
+            "backups" => "prune_snapshot",
+            "transfer_sessions" => "delete_session",
+            _ => "delete_file",
+        };
+        report.push(kind, &item.target, Some(item.bytes), Some(item.category.clone()));
+    }
+    report.notes.extend(cleanup.errors.iter().cloned());
+    report
+}
+
+/// Local file or folder removal, listing every file a recursive delete would remove
+pub fn plan_delete_path(path: &str, recursive: bool) -> SimulationReport {
+    let mut report = SimulationReport::new("delete_path");
+    let root = Path::new(path);
+    if !root.exists() {
+        report.notes.push("Chemin introuvable, rien a supprimer".to_string());
+    } else if root.is_file() {
+        report.push("delete_file", path, root.metadata().ok().map(|m| m.len()), None);
+    } else if !recursive {
+        report.push("delete_folder", path, None, Some("Echoue si le dossier n'est pas vide".to_string()));
+    } else {
+        for entry in WalkDir::new(root).contents_first(true).into_iter().filter_map(|e| e.ok()) {
+            let target = entry.path().to_string_lossy().to_string();
+            if entry.file_type().is_dir() {
+                report.push("delete_folder", &target, None, None);
+            } else {
+                report.push("delete_file", &target, entry.metadata().ok().map(|m| m.len()), None);
+            }
+        }
+    }
+    report
+}
@@ -0,0 +1,243 @@
+//! Site Backup Module
+//!
+//! Backs up the live site into the project's `backups/site/` folder. With
+//! SSH access the docroot is archived server-side (`tar -cz`) and the
+//! archive streamed down; FTP targets, and SSH accounts without a shell,
+//! get a recursive download of every file instead (dotfiles included).
+//! Only the most recent backups are kept.
+
+use crate::pull_sync::RemoteEndpoint;
+use crate::remote_exec::{self, shell_quote};
+use crate::{scan_ftp_remote_files, scan_sftp_remote_files, RemoteScanContext, SFTPConfig};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Folder (relative to the project root) receiving the backups
+pub const SITE_BACKUP_FOLDER: &str = "backups/site";
+
+/// Backups kept when the caller doesn't say
+pub const DEFAULT_KEEP: usize = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SiteBackupProgress {
+    /// "archiving" (server-side tar) or "downloading" (file by file)
+    pub phase: String,
+    pub files_done: usize,
+    pub files_total: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SiteBackupResult {
+    /// `.tar.gz` archive or folder of the backup
+    pub path: String,
+    /// "archive" or "files"
+    pub method: String,
+    pub size: u64,
+    pub files: usize,
+    pub duration_ms: u64,
+    /// Older backups removed by the rotation
+    pub removed: Vec<String>,
+}
+
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '_' || c == '-' || c == '.' { c } else { '_' })
+        .collect()
+}
+
+/// Stream `tar -cz` of the remote root into `archive`; Err when the account has no usable shell
+fn archive_over_ssh(config: &SFTPConfig, archive: &Path, on_progress: &impl Fn(SiteBackupProgress)) -> Result<u64, String> {
+    let sess = remote_exec::connect_ssh(config)?;
+    let root = if config.remote_path.is_empty() { "." } else { &config.remote_path };
+    let mut channel = sess.channel_session().map_err(|e| format!("Failed to open channel: {}", e))?;
+    channel
+        .exec(&format!("tar -czf - -C {} .", shell_quote(root)))
+        .map_err(|e| format!("Failed to run tar: {}", e))?;
+
+    let mut file = File::create(archive).map_err(|e| format!("Failed to create archive: {}", e))?;
+    let mut buffer = [0u8; 64 * 1024];
+    let mut total = 0u64;
+    let mut last_report = 0u64;
+    loop {
+        let read = channel.read(&mut buffer).map_err(|e| format!("Failed to read archive: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        file.write_all(&buffer[..read]).map_err(|e| format!("Failed to write archive: {}", e))?;
+        total += read as u64;
+        if total - last_report >= 4 * 1024 * 1024 {
+            last_report = total;
+            on_progress(SiteBackupProgress {
+                phase: "archiving".to_string(),
+                files_done: 0,
+                files_total: 0,
+                bytes: total,
+            });
+        }
+    }
+
+    let mut stderr = String::new();
+    let _ = channel.stderr().read_to_string(&mut stderr);
+    channel.wait_close().map_err(|e| format!("Failed to close channel: {}", e))?;
+    // tar exits with 1 when files changed while being read, the archive is still usable
+    match channel.exit_status().unwrap_or(-1) {
+        0 | 1 if total > 0 => Ok(total),
+        code => Err(format!("tar distant indisponible (code {}): {}", code, stderr.trim())),
+    }
+}
+
+/// Download every remote file into `target_dir`
+fn download_files(config: &SFTPConfig, target_dir: &Path, on_progress: &impl Fn(SiteBackupProgress)) -> Result<(u64, usize), String> {
+    let mut scan = RemoteScanContext::silent().with_hidden();
+    let files = match config.protocol.as_deref().unwrap_or("ftp") {
+        "sftp" => scan_sftp_remote_files(config, &config.remote_path, &mut scan)?,
+        "ftp" | "ftps" => scan_ftp_remote_files(config, &config.remote_path, &mut scan)?,
+        other => return Err(format!("Sauvegarde non disponible en {}", other)),
+    };
+    let mut paths: Vec<&String> = files.keys().collect();
+    paths.sort();
+
+    let mut remote = RemoteEndpoint::connect(config)?;
+    let remote_base = config.remote_path.trim_end_matches('/');
+    let mut bytes = 0u64;
+    let mut failed = Vec::new();
+    for (index, path) in paths.iter().enumerate() {
+        match remote.download_replacing(&format!("{}/{}", remote_base, path), &target_dir.join(path)) {
+            Ok(size) => bytes += size,
+            Err(e) => failed.push(e),
+        }
+        on_progress(SiteBackupProgress {
+            phase: "downloading".to_string(),
+            files_done: index + 1,
+            files_total: paths.len(),
+            bytes,
+        });
+    }
+    remote.close();
+
+    if !failed.is_empty() {
+        return Err(format!("{} fichier(s) non sauvegarde(s): {}", failed.len(), failed.join(", ")));
+    }
+    Ok((bytes, paths.len()))
+}
+
+/// Keep the `keep` most recent backups of a host, return the removed ones
+fn rotate_backups(backup_dir: &Path, prefix: &str, keep: usize) -> Vec<String> {
+    let mut backups: Vec<PathBuf> = fs::read_dir(backup_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.file_name().map(|n| n.to_string_lossy().starts_with(prefix)).unwrap_or(false))
+                .collect()
+        })
+        .unwrap_or_default();
+    // Timestamped names sort chronologically
+    backups.sort();
+
+    let excess = backups.len().saturating_sub(keep.max(1));
+    let mut removed = Vec::new();
+    for path in backups.into_iter().take(excess) {
+        let deleted = if path.is_dir() { fs::remove_dir_all(&path) } else { fs::remove_file(&path) };
+        if deleted.is_ok() {
+            removed.push(path.to_string_lossy().to_string());
+        }
+    }
+    removed
+}
+
+/// Back up the remote site into `<project>/backups/site/<host>-<timestamp>[.tar.gz]`
+pub fn backup_remote_site(
+    config: &SFTPConfig,
+    project_path: &str,
+    keep: usize,
+    on_progress: impl Fn(SiteBackupProgress),
+) -> Result<SiteBackupResult, String> {
+    let started = Instant::now();
+    let backup_dir = Path::new(project_path).join(SITE_BACKUP_FOLDER);
+    fs::create_dir_all(&backup_dir).map_err(|e| format!("Failed to create backup directory: {}", e))?;
+    let prefix = format!("{}-", sanitize(&config.host));
+    let name = format!("{}{}", prefix, chrono::Local::now().format("%Y%m%d-%H%M%S"));
+
+    let mut archived = None;
+    if config.protocol.as_deref() == Some("sftp") {
+        let archive = backup_dir.join(format!("{}.tar.gz", name));
+        match archive_over_ssh(config, &archive, &on_progress) {
+            Ok(size) => archived = Some((archive, size)),
+            Err(e) => {
+                let _ = fs::remove_file(&archive);
+                println!("[SiteBackup] Server-side archive failed, downloading file by file: {}", e);
+            }
+        }
+    }
+
+    let (path, method, size, files) = match archived {
+        Some((archive, size)) => (archive, "archive", size, 0),
+        None => {
+            let folder = backup_dir.join(&name);
+            match download_files(config, &folder, &on_progress) {
+                Ok((size, files)) => (folder, "files", size, files),
+                Err(e) => {
+                    let _ = fs::remove_dir_all(&folder);
+                    return Err(e);
+                }
+            }
+        }
+    };
+
+    let removed = rotate_backups(&backup_dir, &prefix, keep);
+    println!("[SiteBackup] Saved {} ({} bytes, {})", path.display(), size, method);
+    Ok(SiteBackupResult {
+        path: path.to_string_lossy().to_string(),
+        method: method.to_string(),
+        size,
+        files,
+        duration_ms: started.elapsed().as_millis() as u64,
+        removed,
+    })
+}
+
+/// List the site backups stored in the project, most recent first
+pub fn list_backups(project_path: &str) -> Vec<String> {
+    let mut backups: Vec<String> = fs::read_dir(Path::new(project_path).join(SITE_BACKUP_FOLDER))
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .filter(|name| !name.starts_with('.'))
+                .collect()
+        })
+        .unwrap_or_default();
+    backups.sort_by(|a, b| b.cmp(a));
+    backups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_rotation_keeps_the_latest_backups_of_a_host() {
+        let dir = TempDir::new("site-backup-rotation");
+        dir.write_files(&[
+            ("example.com-20260301-100000.tar.gz", "1"),
+            ("example.com-20260302-100000.tar.gz", "2"),
+            ("example.com-20260303-100000/index.html", "3"),
+            ("other.com-20260101-100000.tar.gz", "0"),
+        ]);
+        let removed = rotate_backups(dir.path(), "example.com-", 2);
+        assert_eq!(removed.len(), 1);
+        assert!(removed[0].ends_with("example.com-20260301-100000.tar.gz"));
+        assert!(rotate_backups(dir.path(), "example.com-", 2).is_empty());
+        // At least one backup is kept
+        rotate_backups(dir.path(), "example.com-", 0);
+        assert!(dir.path().join("example.com-20260303-100000").is_dir());
+        assert!(dir.path().join("other.com-20260101-100000.tar.gz").exists());
+        assert_eq!(sanitize("ftp.example.com:21"), "ftp.example.com_21");
+    }
+}
@@ -0,0 +1,414 @@
+//! Site Migration Module
+//!
+//! Copies a site from one configured target to another (old host to new
+//! host) by streaming every file through a local staging folder. Progress
+//! is persisted per file so an interrupted migration resumes where it
+//! stopped, and the destination is compared to the source at the end.
+
+use crate::deploy_manifest::{connect_ftp, connect_sftp};
+use crate::{
+    create_ftp_dirs, create_sftp_dirs, scan_ftp_remote_files, scan_sftp_remote_files, state_file,
+    RemoteFile, RemoteScanContext, SFTPConfig,
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Persist the state every N transferred files
+const STATE_SAVE_INTERVAL: usize = 20;
+
+/// Migrations asked to stop
+static CANCELLED: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MigrationFileStatus {
+    Pending,
+    Downloaded,
+    Uploaded,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationFile {
+    pub size: u64,
+    pub status: MigrationFileStatus,
+    pub error: Option<String>,
+}
+
+/// Persisted state of a migration, keyed by source and destination
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationState {
+    pub id: String,
+    pub source: String,
+    pub destination: String,
+    pub started_at: String,
+    pub updated_at: String,
+    pub files: BTreeMap<String, MigrationFile>,
+    pub completed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationProgress {
+    pub migration_id: String,
+    /// "scanning", "transferring", "verifying" or "done"
+    pub phase: String,
+    pub file: Option<String>,
+    pub files_done: usize,
+    pub files_total: usize,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationVerification {
+    pub verified: usize,
+    pub missing: Vec<String>,
+    pub size_mismatch: Vec<String>,
+    pub is_valid: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationResult {
+    pub migration_id: String,
+    pub files_total: usize,
+    pub files_transferred: usize,
+    /// Files already copied by a previous run
+    pub files_resumed: usize,
+    pub bytes_transferred: u64,
+    pub failed: Vec<String>,
+    pub verification: MigrationVerification,
+}
+
+/// Identifier shared by every run between the same source and destination
+pub fn migration_id(source: &SFTPConfig, destination: &SFTPConfig) -> String {
+    let sanitize = |value: String| -> String {
+        value
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+            .collect()
+    };
+    format!(
+        "{}-to-{}",
+        sanitize(target_label(source)),
+        sanitize(target_label(destination))
+    )
+}
+
+fn target_label(config: &SFTPConfig) -> String {
+    format!("{}{}", config.host, config.remote_path)
+}
+
+fn migrations_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("migrations")
+}
+
+fn state_path(app_data_dir: &Path, id: &str) -> PathBuf {
+    migrations_dir(app_data_dir).join(format!("{}.json", id))
+}
+
+fn staging_dir(app_data_dir: &Path, id: &str) -> PathBuf {
+    migrations_dir(app_data_dir).join(id)
+}
+
+/// Interrupted migration between these targets, if any
+pub fn load_state(app_data_dir: &Path, id: &str) -> Option<MigrationState> {
+    state_file::read_json(&state_path(app_data_dir, id)).ok().flatten()
+}
+
+fn save_state(app_data_dir: &Path, state: &mut MigrationState) -> Result<(), String> {
+    state.updated_at = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    state_file::write_json(&state_path(app_data_dir, &state.id), state)
+}
+
+/// Forget an interrupted migration and its staged files
+pub fn discard_migration(app_data_dir: &Path, id: &str) -> Result<(), String> {
+    let _ = fs::remove_dir_all(staging_dir(app_data_dir, id));
+    match fs::remove_file(state_path(app_data_dir, id)) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove migration state: {}", e)),
+    }
+}
+
+pub fn cancel_migration(id: &str) {
+    if let Ok(mut cancelled) = CANCELLED.lock() {
+        cancelled.insert(id.to_string());
+    }
+}
+
+fn is_cancelled(id: &str) -> bool {
+    CANCELLED.lock().map(|c| c.contains(id)).unwrap_or(false)
+}
+
+/// Full listing of a target, dotfiles (.htaccess...) included
+fn scan_target(config: &SFTPConfig) -> Result<HashMap<String, RemoteFile>, String> {
+    match config.protocol.as_deref().unwrap_or("ftp") {
+        "sftp" => scan_sftp_remote_files(config, &config.remote_path, &mut RemoteScanContext::silent().with_hidden()),
+        "ftp" | "ftps" => scan_ftp_remote_files(config, &config.remote_path, &mut RemoteScanContext::silent().with_hidden()),
+        other => Err(format!("Unknown protocol: {}", other)),
+    }
+}
+
+/// Open connection to one side of the migration
+enum Endpoint {
+    Sftp(ssh2::Sftp),
+    Ftp(suppaftp::FtpStream),
+}
+
+impl Endpoint {
+    fn connect(config: &SFTPConfig) -> Result<Self, String> {
+        match config.protocol.as_deref().unwrap_or("ftp") {
+            "sftp" => {
+                let sess = connect_sftp(config)?;
+                let sftp = sess.sftp().map_err(|e| format!("SFTP error: {}", e))?;
+                Ok(Endpoint::Sftp(sftp))
+            }
+            "ftp" | "ftps" => Ok(Endpoint::Ftp(connect_ftp(config)?)),
+            other => Err(format!("Unknown protocol: {}", other)),
+        }
+    }
+
+    fn download(&mut self, remote_file: &str, local_file: &Path) -> Result<u64, String> {
+        if let Some(parent) = local_file.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create staging directory: {}", e))?;
+        }
+        let mut local = File::create(local_file)
+            .map_err(|e| format!("Failed to create {}: {}", local_file.display(), e))?;
+        match self {
+            Endpoint::Sftp(sftp) => {
+                let mut remote = sftp
+                    .open(Path::new(remote_file))
+                    .map_err(|e| format!("Failed to open {}: {}", remote_file, e))?;
+                std::io::copy(&mut remote, &mut local).map_err(|e| format!("Failed to download {}: {}", remote_file, e))
+            }
+            Endpoint::Ftp(ftp) => {
+                let content = ftp
+                    .retr_as_buffer(remote_file)
+                    .map_err(|e| format!("Failed to download {}: {}", remote_file, e))?
+                    .into_inner();
+                local
+                    .write_all(&content)
+                    .map_err(|e| format!("Failed to write {}: {}", local_file.display(), e))?;
+                Ok(content.len() as u64)
+            }
+        }
+    }
+
+    fn upload(&mut self, local_file: &Path, remote_base: &str, relative: &str) -> Result<(), String> {
+        let remote_file = format!("{}/{}", remote_base, relative);
+        let mut local = File::open(local_file)
+            .map_err(|e| format!("Failed to open {}: {}", local_file.display(), e))?;
+        match self {
+            Endpoint::Sftp(sftp) => {
+                if let Some(parent) = Path::new(&remote_file).parent() {
+                    let _ = create_sftp_dirs(sftp, parent);
+                }
+                let mut remote = sftp
+                    .create(Path::new(&remote_file))
+                    .map_err(|e| format!("Failed to create {}: {}", remote_file, e))?;
+                std::io::copy(&mut local, &mut remote)
+                    .map(|_| ())
+                    .map_err(|e| format!("Failed to upload {}: {}", remote_file, e))
+            }
+            Endpoint::Ftp(ftp) => {
+                if let Some(parent) = Path::new(relative).parent() {
+                    let _ = create_ftp_dirs(ftp, remote_base, parent);
+                }
+                ftp.put_file(&remote_file, &mut local)
+                    .map(|_| ())
+                    .map_err(|e| format!("Failed to upload {}: {}", remote_file, e))
+            }
+        }
+    }
+
+    fn close(self) {
+        if let Endpoint::Ftp(mut ftp) = self {
+            let _ = ftp.quit();
+        }
+    }
+}
+
+/// Copy every file of `source` to `destination`, resuming a previous run between the same targets
+pub fn migrate(
+    app_data_dir: &Path,
+    source: &SFTPConfig,
+    destination: &SFTPConfig,
+    on_progress: impl Fn(MigrationProgress),
+) -> Result<MigrationResult, String> {
+    let id = migration_id(source, destination);
+    if let Ok(mut cancelled) = CANCELLED.lock() {
+        cancelled.remove(&id);
+    }
+    let progress = |phase: &str, file: Option<&str>, files_done: usize, files_total: usize, bytes_done: u64, bytes_total: u64| {
+        on_progress(MigrationProgress {
+            migration_id: id.clone(),
+            phase: phase.to_string(),
+            file: file.map(String::from),
+            files_done,
+            files_total,
+            bytes_done,
+            bytes_total,
+        })
+    };
+
+    progress("scanning", None, 0, 0, 0, 0);
+    let source_files = scan_target(source)?;
+
+    let mut state = match load_state(app_data_dir, &id) {
+        Some(state) if !state.completed => state,
+        _ => MigrationState {
+            id: id.clone(),
+            source: target_label(source),
+            destination: target_label(destination),
+            started_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            updated_at: String::new(),
+            files: BTreeMap::new(),
+            completed: false,
+        },
+    };
+    // Files changed on the source since the interrupted run are copied again
+    state.files.retain(|path, file| source_files.get(path).map(|r| r.size) == Some(file.size));
+    for (path, remote) in &source_files {
+        state.files.entry(path.clone()).or_insert(MigrationFile {
+            size: remote.size,
+            status: MigrationFileStatus::Pending,
+            error: None,
+        });
+    }
+    save_state(app_data_dir, &mut state)?;
+
+    let files_total = state.files.len();
+    let bytes_total: u64 = state.files.values().map(|f| f.size).sum();
+    let files_resumed = state.files.values().filter(|f| f.status == MigrationFileStatus::Uploaded).count();
+    let mut files_done = files_resumed;
+    let mut bytes_done: u64 = state
+        .files
+        .values()
+        .filter(|f| f.status == MigrationFileStatus::Uploaded)
+        .map(|f| f.size)
+        .sum();
+    println!(
+        "[Migration] {} -> {}: {} files, {} already copied",
+        state.source, state.destination, files_total, files_resumed
+    );
+
+    let staging = staging_dir(app_data_dir, &id);
+    let pending: Vec<String> = state
+        .files
+        .iter()
+        .filter(|(_, f)| f.status != MigrationFileStatus::Uploaded)
+        .map(|(path, _)| path.clone())
+        .collect();
+
+    let mut from = Endpoint::connect(source)?;
+    let mut to = Endpoint::connect(destination)?;
+    let mut files_transferred = 0;
+    let mut bytes_transferred = 0;
+
+    for (index, path) in pending.iter().enumerate() {
+        if is_cancelled(&id) {
+            save_state(app_data_dir, &mut state)?;
+            from.close();
+            to.close();
+            return Err("Migration annulee, elle reprendra au prochain lancement".to_string());
+        }
+        progress("transferring", Some(path), files_done, files_total, bytes_done, bytes_total);
+
+        let staged = staging.join(path);
+        let size = state.files[path].size;
+        let already_staged = state.files[path].status == MigrationFileStatus::Downloaded
+            && fs::metadata(&staged).map(|m| m.len() == size).unwrap_or(false);
+
+        let result = (|| {
+            if !already_staged {
+                from.download(&format!("{}/{}", source.remote_path, path), &staged)?;
+                if let Some(file) = state.files.get_mut(path) {
+                    file.status = MigrationFileStatus::Downloaded;
+                }
+            }
+            to.upload(&staged, &destination.remote_path, path)
+        })();
+
+        if let Some(file) = state.files.get_mut(path) {
+            match result {
+                Ok(()) => {
+                    file.status = MigrationFileStatus::Uploaded;
+                    file.error = None;
+                    let _ = fs::remove_file(&staged);
+                    files_done += 1;
+                    files_transferred += 1;
+                    bytes_done += size;
+                    bytes_transferred += size;
+                }
+                Err(e) => {
+                    println!("[Migration] {}: {}", path, e);
+                    file.status = MigrationFileStatus::Failed;
+                    file.error = Some(e);
+                }
+            }
+        }
+
+        if (index + 1) % STATE_SAVE_INTERVAL == 0 {
+            save_state(app_data_dir, &mut state)?;
+        }
+    }
+    from.close();
+    to.close();
+
+    progress("verifying", None, files_done, files_total, bytes_done, bytes_total);
+    let destination_files = scan_target(destination)?;
+    let verification = verify(&source_files, &destination_files);
+
+    let failed: Vec<String> = state
+        .files
+        .iter()
+        .filter(|(_, f)| f.status == MigrationFileStatus::Failed)
+        .map(|(path, _)| path.clone())
+        .collect();
+
+    if failed.is_empty() && verification.is_valid {
+        state.completed = true;
+        let _ = fs::remove_dir_all(&staging);
+    }
+    save_state(app_data_dir, &mut state)?;
+    progress("done", None, files_done, files_total, bytes_done, bytes_total);
+
+    Ok(MigrationResult {
+        migration_id: id,
+        files_total,
+        files_transferred,
+        files_resumed,
+        bytes_transferred,
+        failed,
+        verification,
+    })
+}
+
+/// Final diff between the source and the destination
+fn verify(source: &HashMap<String, RemoteFile>, destination: &HashMap<String, RemoteFile>) -> MigrationVerification {
+    let mut verified = 0;
+    let mut missing = Vec::new();
+    let mut size_mismatch = Vec::new();
+
+    for (path, remote) in source {
+        match destination.get(path) {
+            Some(copy) if copy.size == remote.size => verified += 1,
+            Some(_) => size_mismatch.push(path.clone()),
+            None => missing.push(path.clone()),
+        }
+    }
+    missing.sort();
+    size_mismatch.sort();
+
+    MigrationVerification {
+        verified,
+        is_valid: missing.is_empty() && size_mismatch.is_empty(),
+        missing,
+        size_mismatch,
+    }
+}
@@ -0,0 +1,330 @@
+//! SSH Auth Module
+//!
+//! Authentication of SSH/SFTP sessions. With `useSshAgent`, the local
+//! ssh-agent is tried first, then a private key file, then the password,
+//! so no password has to be stored; every failed method is listed in the
+//! error to tell what to fix. The host key is checked against the known
+//! hosts before any credential is sent.
+//!
+//! Hosts asking for a one-time code (keyboard-interactive, often after a
+//! key or password) get their prompts relayed to the frontend with a
+//! `ssh-auth-prompt` event; the connection waits for `ssh_auth_answer`.
+
+use crate::SFTPConfig;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Manager;
+
+/// Keys tried when no key file is configured
+const DEFAULT_KEYS: [&str; 3] = ["id_ed25519", "id_ecdsa", "id_rsa"];
+
+/// How long a prompt waits for its answer, under OpenSSH's default 120s LoginGraceTime
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(110);
+
+/// Set once at startup: connections are opened from places without an AppHandle
+static APP_HANDLE: Lazy<Mutex<Option<tauri::AppHandle>>> = Lazy::new(|| Mutex::new(None));
+
+/// Answers typed in the frontend, `None` when the prompt was cancelled
+type AnswerSender = mpsc::Sender<Option<Vec<String>>>;
+
+/// Prompts waiting for their answer by request id
+static PENDING: Lazy<Mutex<HashMap<String, AnswerSender>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// One prompt at a time, parallel workers of a 2FA host queue up behind it
+static PROMPT_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthPromptField {
+    pub text: String,
+    /// false for secrets, typed masked
+    pub echo: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthPromptEvent {
+    pub request_id: String,
+    pub host: String,
+    pub username: String,
+    pub instructions: String,
+    pub prompts: Vec<AuthPromptField>,
+}
+
+pub fn set_app_handle(app_handle: tauri::AppHandle) {
+    *APP_HANDLE.lock().unwrap_or_else(|e| e.into_inner()) = Some(app_handle);
+}
+
+/// Answer a pending prompt, `None` when the user cancelled it
+pub fn answer(request_id: &str, answers: Option<Vec<String>>) -> Result<(), String> {
+    let sender = PENDING
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(request_id)
+        .ok_or_else(|| format!("No pending SSH authentication prompt {}", request_id))?;
+    sender
+        .send(answers)
+        .map_err(|_| "SSH authentication prompt expired".to_string())
+}
+
+/// Relays the server's prompts to the frontend and returns what the user typed
+struct FrontendPrompter<'a> {
+    config: &'a SFTPConfig,
+    /// Why the prompts went unanswered, reported instead of libssh2's generic error
+    failure: Option<String>,
+}
+
+impl FrontendPrompter<'_> {
+    fn ask(&mut self, event: AuthPromptEvent) -> Result<Vec<String>, String> {
+        let app_handle = APP_HANDLE
+            .lock()
+            .map_err(|e| e.to_string())?
+            .clone()
+            .ok_or("aucune fenetre pour saisir le code")?;
+        let _turn = PROMPT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let (sender, receiver) = mpsc::channel();
+        let request_id = event.request_id.clone();
+        PENDING.lock().map_err(|e| e.to_string())?.insert(request_id.clone(), sender);
+        let _ = app_handle.emit_all(crate::events::SSH_AUTH_PROMPT, &event);
+        let answer = receiver.recv_timeout(ANSWER_TIMEOUT);
+        if let Ok(mut pending) = PENDING.lock() {
+            pending.remove(&request_id);
+        }
+        match answer {
+            Ok(Some(answers)) => Ok(answers),
+            Ok(None) => Err("saisie annulee".to_string()),
+            Err(_) => Err("aucune reponse saisie a temps".to_string()),
+        }
+    }
+}
+
+impl ssh2::KeyboardInteractivePrompt for FrontendPrompter<'_> {
+    fn prompt<'a>(&mut self, username: &str, instructions: &str, prompts: &[ssh2::Prompt<'a>]) -> Vec<String> {
+        // Servers may send rounds without prompts, only to show their instructions
+        if prompts.is_empty() || self.failure.is_some() {
+            return vec![String::new(); prompts.len()];
+        }
+        let event = AuthPromptEvent {
+            request_id: uuid::Uuid::new_v4().to_string(),
+            host: self.config.host.clone(),
+            username: if username.is_empty() { self.config.username.clone() } else { username.to_string() },
+            instructions: instructions.to_string(),
+            prompts: prompts
+                .iter()
+                .map(|p| AuthPromptField { text: p.text.to_string(), echo: p.echo })
+                .collect(),
+        };
+        match self.ask(event) {
+            Ok(mut answers) => {
+                answers.resize(prompts.len(), String::new());
+                answers
+            }
+            Err(e) => {
+                self.failure = Some(e);
+                vec![String::new(); prompts.len()]
+            }
+        }
+    }
+}
+
+/// Keyboard-interactive is used when configured, or when it is all the server
+/// still accepts (a key or password was accepted and a second factor is due)
+fn wants_keyboard_interactive(sess: &ssh2::Session, config: &SFTPConfig) -> bool {
+    if config.keyboard_interactive.unwrap_or(false) {
+        return true;
+    }
+    match sess.auth_methods(&config.username) {
+        Ok(methods) => methods.split(',').all(|m| m == "keyboard-interactive"),
+        Err(_) => false,
+    }
+}
+
+fn try_keyboard_interactive(sess: &ssh2::Session, config: &SFTPConfig) -> Result<(), String> {
+    let mut prompter = FrontendPrompter { config, failure: None };
+    let result = sess.userauth_keyboard_interactive(&config.username, &mut prompter);
+    if let Some(failure) = prompter.failure {
+        return Err(failure);
+    }
+    match result {
+        Ok(()) if sess.authenticated() => {
+            println!("[SSH] Authenticated {}@{} with keyboard-interactive", config.username, config.host);
+            Ok(())
+        }
+        Ok(()) => Err("authentification incomplete".to_string()),
+        Err(e) => Err(e.message().to_string()),
+    }
+}
+
+fn key_candidates(config: &SFTPConfig) -> Vec<PathBuf> {
+    if let Some(path) = config.private_key_path.as_deref().filter(|p| !p.trim().is_empty()) {
+        let path = match (path.strip_prefix("~/"), dirs::home_dir()) {
+            (Some(rest), Some(home)) => home.join(rest),
+            _ => PathBuf::from(path),
+        };
+        return vec![path];
+    }
+    match dirs::home_dir() {
+        Some(home) => DEFAULT_KEYS
+            .iter()
+            .map(|name| home.join(".ssh").join(name))
+            .filter(|path| path.exists())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+fn try_agent(sess: &ssh2::Session, username: &str) -> Result<(), String> {
+    let mut agent = sess.agent().map_err(|e| format!("agent indisponible ({})", e))?;
+    agent
+        .connect()
+        .map_err(|_| "agent SSH injoignable (SSH_AUTH_SOCK absent ou invalide)".to_string())?;
+    agent.list_identities().map_err(|e| format!("lecture des cles de l'agent impossible ({})", e))?;
+    let identities = agent.identities().map_err(|e| format!("lecture des cles de l'agent impossible ({})", e))?;
+    if identities.is_empty() {
+        let _ = agent.disconnect();
+        return Err("aucune cle chargee dans l'agent (ssh-add)".to_string());
+    }
+    let accepted = identities.iter().any(|identity| agent.userauth(username, identity).is_ok());
+    let _ = agent.disconnect();
+    if accepted {
+        Ok(())
+    } else {
+        Err(format!("{} cle(s) de l'agent refusee(s) par le serveur", identities.len()))
+    }
+}
+
+/// Authenticate an SSH session after its handshake, once its host key is verified
+pub fn authenticate(sess: &ssh2::Session, config: &SFTPConfig) -> Result<(), String> {
+    crate::known_hosts::verify_session(sess, &config.host, config.port)?;
+
+    if !config.use_ssh_agent.unwrap_or(false) {
+        let password = if config.password.is_empty() && config.keyboard_interactive.unwrap_or(false) {
+            Ok(())
+        } else {
+            sess.userauth_password(&config.username, &config.password)
+        };
+        if sess.authenticated() {
+            return Ok(());
+        }
+        if wants_keyboard_interactive(sess, config) {
+            return try_keyboard_interactive(sess, config)
+                .map_err(|e| format!("Authentication failed: code de verification: {}", e));
+        }
+        return password
+            .map_err(|e| format!("Authentication failed: {}", e))
+            .and(Err("Authentication failed: authentification incomplete".to_string()));
+    }
+
+    let mut failures = Vec::new();
+    match try_agent(sess, &config.username) {
+        Ok(()) if sess.authenticated() => {
+            println!("[SSH] Authenticated {}@{} with ssh-agent", config.username, config.host);
+            return Ok(());
+        }
+        Ok(()) => failures.push("agent SSH: authentification incomplete".to_string()),
+        Err(e) => failures.push(format!("agent SSH: {}", e)),
+    }
+
+    let keys = key_candidates(config);
+    if keys.is_empty() {
+        failures.push("cle privee: aucune cle trouvee dans ~/.ssh".to_string());
+    }
+    let passphrase = config.key_passphrase.as_deref().filter(|p| !p.is_empty());
+    for key in keys {
+        match sess.userauth_pubkey_file(&config.username, None, &key, passphrase) {
+            Ok(()) if sess.authenticated() => {
+                println!("[SSH] Authenticated {}@{} with {}", config.username, config.host, key.display());
+                return Ok(());
+            }
+            Ok(()) => failures.push(format!("cle {}: authentification incomplete", key.display())),
+            Err(e) => failures.push(format!("cle {}: {}", key.display(), e.message())),
+        }
+    }
+
+    if config.password.is_empty() {
+        failures.push("mot de passe: non renseigne".to_string());
+    } else {
+        match sess.userauth_password(&config.username, &config.password) {
+            Ok(()) if sess.authenticated() => return Ok(()),
+            Ok(()) => failures.push("mot de passe: authentification incomplete".to_string()),
+            Err(e) => failures.push(format!("mot de passe: {}", e.message())),
+        }
+    }
+
+    if wants_keyboard_interactive(sess, config) {
+        match try_keyboard_interactive(sess, config) {
+            Ok(()) => return Ok(()),
+            Err(e) => failures.push(format!("code de verification: {}", e)),
+        }
+    }
+
+    Err(format!("Authentication failed: {}", failures.join(" ; ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::sftp_config;
+    use ssh2::KeyboardInteractivePrompt;
+    use std::borrow::Cow;
+
+    fn prompt(text: &str, echo: bool) -> ssh2::Prompt<'_> {
+        ssh2::Prompt { text: Cow::Borrowed(text), echo }
+    }
+
+    #[test]
+    fn test_answers_reach_the_waiting_prompt() {
+        assert!(answer("unknown", Some(vec!["123456".to_string()]))
+            .unwrap_err()
+            .starts_with("No pending SSH authentication prompt"));
+
+        let (sender, receiver) = mpsc::channel();
+        PENDING.lock().unwrap().insert("code".to_string(), sender);
+        answer("code", Some(vec!["123456".to_string()])).unwrap();
+        assert_eq!(receiver.recv().unwrap(), Some(vec!["123456".to_string()]));
+        // Answered once only
+        assert!(answer("code", None).is_err());
+
+        let (sender, receiver) = mpsc::channel();
+        PENDING.lock().unwrap().insert("cancelled".to_string(), sender);
+        drop(receiver);
+        assert_eq!(answer("cancelled", None).unwrap_err(), "SSH authentication prompt expired");
+    }
+
+    #[test]
+    fn test_unanswerable_prompt_fails_with_its_reason() {
+        let config = sftp_config("sftp", "srv.example", 22, "/var/www");
+        let mut prompter = FrontendPrompter { config: &config, failure: None };
+
+        // Rounds without prompts only carry instructions
+        assert!(prompter.prompt("deploy", "Bienvenue", &[]).is_empty());
+        assert!(prompter.failure.is_none());
+
+        // No window to ask: empty answers, and the reason is kept for the error
+        let answers = prompter.prompt("", "", &[prompt("Verification code: ", false), prompt("Token: ", true)]);
+        assert_eq!(answers, vec![String::new(), String::new()]);
+        assert_eq!(prompter.failure.as_deref(), Some("aucune fenetre pour saisir le code"));
+        assert_eq!(prompter.prompt("", "", &[prompt("Code: ", false)]), vec![String::new()]);
+    }
+
+    #[test]
+    fn test_configured_key_is_the_only_candidate() {
+        let mut config = sftp_config("sftp", "srv.example", 22, "/var/www");
+        config.private_key_path = Some("/keys/deploy_ed25519".to_string());
+        assert_eq!(key_candidates(&config), vec![PathBuf::from("/keys/deploy_ed25519")]);
+
+        config.private_key_path = Some("~/.ssh/deploy".to_string());
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(key_candidates(&config), vec![home.join(".ssh").join("deploy")]);
+        }
+
+        // Only the default keys that exist are tried
+        config.private_key_path = Some("  ".to_string());
+        assert!(key_candidates(&config).iter().all(|key| key.exists()));
+    }
+}
@@ -0,0 +1,113 @@
+//! State File Module
+//!
+//! Crash-safe reads and writes of the JSON state files (histories, caches,
+//! sessions). Writes go to a temporary file that is synced then renamed over
+//! the previous version, which is kept as `<name>.bak`. A file that fails to
+//! parse is set aside as `<name>.corrupt` and the backup is restored, so one
+//! interrupted write never makes a load fail forever.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+pub fn backup_path(path: &Path) -> PathBuf {
+    sibling(path, ".bak")
+}
+
+/// Write to a temporary file, sync it, then rename it over `path`
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let tmp_path = sibling(path, ".tmp");
+    let result = fs::File::create(&tmp_path)
+        .and_then(|mut file| {
+            file.write_all(content)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp_path, path));
+    if let Err(e) = result {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(format!("Failed to write {}: {}", path.display(), e));
+    }
+    Ok(())
+}
+
+/// Serialize `value` to `path`, keeping the previous version as a backup
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+    // Only a readable previous version is worth keeping
+    if fs::read_to_string(path)
+        .map(|previous| serde_json::from_str::<serde_json::Value>(&previous).is_ok())
+        .unwrap_or(false)
+    {
+        let _ = fs::copy(path, backup_path(path));
+    }
+    write_atomic(path, content.as_bytes())
+}
+
+/// Read a JSON state file, falling back to its backup when it is corrupted.
+/// Returns None when there is no usable version, so the caller starts afresh.
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, String> {
+    let backup = backup_path(path);
+    if !path.exists() {
+        return Ok(read_backup(path, &backup));
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    match serde_json::from_str(&content) {
+        Ok(value) => Ok(Some(value)),
+        Err(e) => {
+            println!("[StateFile] {} is corrupted ({}), restoring backup", path.display(), e);
+            let corrupt = sibling(path, ".corrupt");
+            let _ = fs::rename(path, &corrupt);
+            Ok(read_backup(path, &backup))
+        }
+    }
+}
+
+fn read_backup<T: DeserializeOwned>(path: &Path, backup: &Path) -> Option<T> {
+    let content = fs::read_to_string(backup).ok()?;
+    let value = serde_json::from_str(&content).ok()?;
+    if write_atomic(path, content.as_bytes()).is_ok() {
+        println!("[StateFile] Restored {} from backup", path.display());
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_recover_from_truncated_write() {
+        let dir = TempDir::new("state-file");
+        let path = dir.path().join("sessions.json");
+
+        let first: HashMap<String, u32> = [("a".to_string(), 1)].into_iter().collect();
+        let second: HashMap<String, u32> = [("a".to_string(), 2)].into_iter().collect();
+        write_json(&path, &first).unwrap();
+        write_json(&path, &second).unwrap();
+
+        // A crash in the middle of a plain write leaves a truncated file
+        fs::write(&path, "{\"a\": ").unwrap();
+        let recovered: HashMap<String, u32> = read_json(&path).unwrap().unwrap();
+        assert_eq!(recovered, first);
+        assert!(sibling(&path, ".corrupt").exists());
+
+        // The restored version loads directly afterwards
+        let reloaded: HashMap<String, u32> = read_json(&path).unwrap().unwrap();
+        assert_eq!(reloaded, first);
+    }
+}
@@ -0,0 +1,374 @@
+//! Storage Module
+//!
+//! Disk usage of everything La Forge keeps besides the projects themselves:
+//! delta signature caches, scrape caches, version history backups, logs and
+//! transfer sessions, per project, and cleanup of the selected categories
+//! older than a threshold.
+
+use crate::transfer_resume;
+use crate::version_history;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+pub const CATEGORIES: [&str; 5] = ["delta_cache", "scrape_cache", "backups", "logs", "transfer_sessions"];
+
+/// A project whose storage is reported (scrape caches live in the project folder)
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProjectRef {
+    pub id: String,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct CategoryUsage {
+    pub bytes: u64,
+    pub files: usize,
+    /// Oldest item, RFC 3339
+    pub oldest: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectStorage {
+    pub project_id: String,
+    /// Usage by category
+    pub categories: BTreeMap<String, CategoryUsage>,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageOverview {
+    pub data_dir: String,
+    pub projects: Vec<ProjectStorage>,
+    /// Totals by category, including items of unknown projects and logs
+    pub totals: BTreeMap<String, CategoryUsage>,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CleanupOptions {
+    pub categories: Vec<String>,
+    /// Only items last modified more than this many days ago
+    pub older_than_days: u32,
+    /// Limit to one project
+    pub project_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanupItem {
+    pub category: String,
+    pub project_id: Option<String>,
+    pub target: String,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanupReport {
+    pub dry_run: bool,
+    pub items: Vec<CleanupItem>,
+    pub freed_bytes: u64,
+    pub errors: Vec<String>,
+}
+
+fn delta_cache_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("delta_cache")
+}
+
+/// Project of a delta cache file, including its state_file backup and corrupt copies
+fn cache_project_id(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_string_lossy().to_string();
+    let name = name
+        .strip_suffix(".bak")
+        .or_else(|| name.strip_suffix(".corrupt"))
+        .unwrap_or(&name);
+    name.strip_suffix(".json").map(String::from)
+}
+
+fn scrape_cache_path(project_path: &str) -> PathBuf {
+    Path::new(project_path).join("_Inbox").join(".scrape_cache.json")
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn to_rfc3339(time: SystemTime) -> String {
+    let datetime: chrono::DateTime<chrono::Utc> = time.into();
+    datetime.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+/// Size, file count and oldest modification of a file or folder
+fn measure(path: &Path) -> CategoryUsage {
+    let mut usage = CategoryUsage::default();
+    let mut oldest: Option<SystemTime> = None;
+    for entry in WalkDir::new(path).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        if let Ok(metadata) = entry.metadata() {
+            usage.bytes += metadata.len();
+            usage.files += 1;
+            if let Ok(time) = metadata.modified() {
+                oldest = Some(oldest.map_or(time, |o| o.min(time)));
+            }
+        }
+    }
+    usage.oldest = oldest.map(to_rfc3339);
+    usage
+}
+
+fn add(total: &mut CategoryUsage, usage: &CategoryUsage) {
+    total.bytes += usage.bytes;
+    total.files += usage.files;
+    total.oldest = match (total.oldest.take(), usage.oldest.clone()) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+}
+
+fn record(per_project: &mut BTreeMap<String, BTreeMap<String, CategoryUsage>>, project_id: &str, category: &str, usage: &CategoryUsage) {
+    if usage.files > 0 {
+        let categories = per_project.entry(project_id.to_string()).or_default();
+        add(categories.entry(category.to_string()).or_default(), usage);
+    }
+}
+
+/// Storage used by each category, per project
+pub fn overview(app_data_dir: &Path, log_dir: Option<&Path>, projects: &[ProjectRef]) -> StorageOverview {
+    let mut per_project: BTreeMap<String, BTreeMap<String, CategoryUsage>> = BTreeMap::new();
+    // Delta caches and backups are named after the project id
+    if let Ok(entries) = fs::read_dir(delta_cache_dir(app_data_dir)) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if let Some(project_id) = cache_project_id(&path) {
+                record(&mut per_project, &project_id, "delta_cache", &measure(&path));
+            }
+        }
+    }
+    if let Ok(entries) = fs::read_dir(app_data_dir.join("backups")) {
+        for entry in entries.filter_map(|e| e.ok()).filter(|e| e.path().is_dir()) {
+            let project_id = entry.file_name().to_string_lossy().to_string();
+            let mut usage = measure(&entry.path());
+            add(&mut usage, &measure(&version_history::get_history_path(app_data_dir, &project_id)));
+            record(&mut per_project, &project_id, "backups", &usage);
+        }
+    }
+    for project in projects {
+        record(&mut per_project, &project.id, "scrape_cache", &measure(&scrape_cache_path(&project.path)));
+    }
+
+    // Sessions share one file, each is counted by its serialized size
+    if let Ok(store) = transfer_resume::load_sessions(app_data_dir) {
+        for session in store.sessions.values() {
+            let usage = CategoryUsage {
+                bytes: serde_json::to_vec(session).map(|v| v.len() as u64).unwrap_or(0),
+                files: 1,
+                oldest: Some(session.started_at.clone()),
+            };
+            record(&mut per_project, &session.project_id, "transfer_sessions", &usage);
+        }
+    }
+
+    let mut totals: BTreeMap<String, CategoryUsage> = BTreeMap::new();
+    let projects: Vec<ProjectStorage> = per_project
+        .into_iter()
+        .map(|(project_id, categories)| {
+            for (category, usage) in &categories {
+                add(totals.entry(category.clone()).or_default(), usage);
+            }
+            ProjectStorage {
+                total_bytes: categories.values().map(|u| u.bytes).sum(),
+                project_id,
+                categories,
+            }
+        })
+        .collect();
+    if let Some(log_dir) = log_dir {
+        totals.insert("logs".to_string(), measure(log_dir));
+    }
+
+    StorageOverview {
+        data_dir: app_data_dir.to_string_lossy().to_string(),
+        total_bytes: totals.values().map(|u| u.bytes).sum(),
+        projects,
+        totals,
+    }
+}
+
+/// Remove the items of the selected categories older than the threshold.
+/// With `dry_run`, only lists them.
+pub fn cleanup(
+    app_data_dir: &Path,
+    log_dir: Option<&Path>,
+    projects: &[ProjectRef],
+    options: &CleanupOptions,
+    dry_run: bool,
+) -> Result<CleanupReport, String> {
+    for category in &options.categories {
+        if !CATEGORIES.contains(&category.as_str()) {
+            return Err(format!("Categorie de stockage inconnue: {}", category));
+        }
+    }
+    let cutoff = SystemTime::now() - Duration::from_secs(options.older_than_days as u64 * 24 * 60 * 60);
+    let selected = |category: &str| options.categories.iter().any(|c| c == category);
+    let wanted = |project_id: &str| options.project_id.as_deref().map(|p| p == project_id).unwrap_or(true);
+
+    let mut report = CleanupReport {
+        dry_run,
+        items: Vec::new(),
+        freed_bytes: 0,
+        errors: Vec::new(),
+    };
+    let remove_file = |report: &mut CleanupReport, category: &str, project_id: Option<&str>, path: &Path| {
+        if !modified(path).map(|t| t < cutoff).unwrap_or(false) {
+            return;
+        }
+        let bytes = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        if !dry_run {
+            if let Err(e) = fs::remove_file(path) {
+                report.errors.push(format!("{}: {}", path.display(), e));
+                return;
+            }
+        }
+        report.freed_bytes += bytes;
+        report.items.push(CleanupItem {
+            category: category.to_string(),
+            project_id: project_id.map(String::from),
+            target: path.to_string_lossy().to_string(),
+            bytes,
+        });
+    };
+
+    if selected("delta_cache") {
+        if let Ok(entries) = fs::read_dir(delta_cache_dir(app_data_dir)) {
+            for entry in entries.filter_map(|e| e.ok()) {
+                let path = entry.path();
+                if let Some(project_id) = cache_project_id(&path).filter(|id| wanted(id)) {
+                    remove_file(&mut report, "delta_cache", Some(&project_id), &path);
+                }
+            }
+        }
+    }
+    if selected("scrape_cache") {
+        for project in projects.iter().filter(|p| wanted(&p.id)) {
+            let path = scrape_cache_path(&project.path);
+            if path.exists() {
+                remove_file(&mut report, "scrape_cache", Some(&project.id), &path);
+            }
+        }
+    }
+    if selected("logs") && options.project_id.is_none() {
+        if let Some(log_dir) = log_dir {
+            for entry in WalkDir::new(log_dir).into_iter().filter_map(|e| e.ok()).filter(|e| e.file_type().is_file()) {
+                remove_file(&mut report, "logs", None, entry.path());
+            }
+        }
+    }
+    if selected("backups") {
+        prune_snapshots(app_data_dir, cutoff, &wanted, dry_run, &mut report);
+    }
+    if selected("transfer_sessions") {
+        prune_sessions(app_data_dir, cutoff, &wanted, dry_run, &mut report)?;
+    }
+
+    println!(
+        "[Storage] Cleanup{}: {} items, {} bytes",
+        if dry_run { " (dry run)" } else { "" },
+        report.items.len(),
+        report.freed_bytes
+    );
+    Ok(report)
+}
+
+/// Drop snapshots older than the cutoff with their backup files
+fn prune_snapshots(
+    app_data_dir: &Path,
+    cutoff: SystemTime,
+    wanted: &dyn Fn(&str) -> bool,
+    dry_run: bool,
+    report: &mut CleanupReport,
+) {
+    let cutoff = to_rfc3339(cutoff);
+    let entries = match fs::read_dir(app_data_dir.join("backups")) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.filter_map(|e| e.ok()).filter(|e| e.path().is_dir()) {
+        let project_id = entry.file_name().to_string_lossy().to_string();
+        if !wanted(&project_id) {
+            continue;
+        }
+        let mut history = match version_history::load_history(app_data_dir, &project_id) {
+            Ok(history) => history,
+            Err(e) => {
+                report.errors.push(e);
+                continue;
+            }
+        };
+        let (old, kept): (Vec<_>, Vec<_>) = history.snapshots.into_iter().partition(|s| s.timestamp < cutoff);
+        if old.is_empty() {
+            continue;
+        }
+        for snapshot in &old {
+            let backup = entry.path().join(&snapshot.id);
+            let bytes = measure(&backup).bytes;
+            if !dry_run && backup.exists() {
+                if let Err(e) = fs::remove_dir_all(&backup) {
+                    report.errors.push(format!("{}: {}", backup.display(), e));
+                }
+            }
+            report.freed_bytes += bytes;
+            report.items.push(CleanupItem {
+                category: "backups".to_string(),
+                project_id: Some(project_id.clone()),
+                target: format!("{} ({})", snapshot.id, snapshot.timestamp),
+                bytes,
+            });
+        }
+        history.snapshots = kept;
+        if !dry_run {
+            if let Err(e) = version_history::save_history(app_data_dir, &history) {
+                report.errors.push(e);
+            }
+        }
+    }
+}
+
+fn prune_sessions(
+    app_data_dir: &Path,
+    cutoff: SystemTime,
+    wanted: &dyn Fn(&str) -> bool,
+    dry_run: bool,
+    report: &mut CleanupReport,
+) -> Result<(), String> {
+    let cutoff = to_rfc3339(cutoff);
+    let mut store = transfer_resume::load_sessions(app_data_dir)?;
+    let old: Vec<String> = store
+        .sessions
+        .values()
+        .filter(|s| wanted(&s.project_id) && s.started_at < cutoff)
+        .map(|s| s.id.clone())
+        .collect();
+    if old.is_empty() {
+        return Ok(());
+    }
+    for id in &old {
+        if let Some(session) = store.sessions.remove(id) {
+            let bytes = serde_json::to_vec(&session).map(|v| v.len() as u64).unwrap_or(0);
+            report.freed_bytes += bytes;
+            report.items.push(CleanupItem {
+                category: "transfer_sessions".to_string(),
+                project_id: Some(session.project_id.clone()),
+                target: format!("{} ({})", session.id, session.started_at),
+                bytes,
+            });
+        }
+    }
+    if !dry_run {
+        transfer_resume::save_sessions(app_data_dir, &store)?;
+    }
+    Ok(())
+}
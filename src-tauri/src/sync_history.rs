@@ -0,0 +1,257 @@
+//! Sync History Module
+//!
+//! One entry per sync run that got past the queue: when it started, the
+//! server it went to, how many files and bytes it sent, how long it took,
+//! its outcome and the errors of single files. The activity feed keeps a
+//! line per run for the timeline; this log keeps the figures and failures.
+//! Runs are kept per project in `sync_history/<project_id>.json`, newest
+//! last and capped. File errors are collected while the run goes, keyed by
+//! project: the sync lock ensures a project has one run at a time.
+
+use crate::{state_file, SFTPConfig};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Runs kept per project, the oldest are dropped
+const MAX_RUNS: usize = 1000;
+
+/// File errors kept per run
+const MAX_FILE_ERRORS: usize = 200;
+
+const DEFAULT_LIMIT: usize = 50;
+
+/// Serializes read-modify-write of the history files
+static HISTORY_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// Runs in progress, by project
+static RUNNING: Lazy<Mutex<HashMap<String, PendingRun>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Server a run went to; never the password
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncTarget {
+    pub protocol: String,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub remote_path: String,
+}
+
+impl From<&SFTPConfig> for SyncTarget {
+    fn from(config: &SFTPConfig) -> Self {
+        SyncTarget {
+            protocol: config.protocol.clone().unwrap_or_else(|| "ftp".to_string()),
+            host: config.host.clone(),
+            port: config.port,
+            username: config.username.clone(),
+            remote_path: config.remote_path.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileError {
+    /// None for errors about the run rather than one file (verification, remote command)
+    pub path: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncRun {
+    pub id: String,
+    pub project_id: String,
+    pub started_at: String,
+    pub duration_ms: u64,
+    /// "manual", "watch", "schedule", "tray"...
+    pub trigger: String,
+    pub target: SyncTarget,
+    /// "success", "error" or "cancelled"
+    pub status: String,
+    /// Why the run failed
+    pub message: Option<String>,
+    pub files_uploaded: usize,
+    pub files_deleted: usize,
+    pub bytes: u64,
+    pub snapshot_id: Option<String>,
+    pub file_errors: Vec<FileError>,
+    /// More file errors than MAX_FILE_ERRORS happened
+    #[serde(default)]
+    pub file_errors_truncated: bool,
+}
+
+#[derive(Debug, Default)]
+struct PendingRun {
+    files_uploaded: usize,
+    files_deleted: usize,
+    bytes: u64,
+    snapshot_id: Option<String>,
+    file_errors: Vec<FileError>,
+    file_errors_truncated: bool,
+}
+
+fn history_path(app_data_dir: &Path, project_id: &str) -> PathBuf {
+    let name: String = project_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    app_data_dir.join("sync_history").join(format!("{}.json", name))
+}
+
+/// Every run of a project, oldest first
+fn load(app_data_dir: &Path, project_id: &str) -> Vec<SyncRun> {
+    state_file::read_json(&history_path(app_data_dir, project_id))
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+/// A run of the project starts collecting its figures and file errors
+pub fn begin(project_id: &str) {
+    if let Ok(mut running) = RUNNING.lock() {
+        running.insert(project_id.to_string(), PendingRun::default());
+    }
+}
+
+/// Note the failure of a file (or of a step when `path` is None); ignored outside a run
+pub fn note_error(project_id: &str, path: Option<&str>, message: &str) {
+    if let Ok(mut running) = RUNNING.lock() {
+        if let Some(run) = running.get_mut(project_id) {
+            if run.file_errors.len() < MAX_FILE_ERRORS {
+                run.file_errors.push(FileError {
+                    path: path.map(String::from),
+                    message: message.to_string(),
+                });
+            } else {
+                run.file_errors_truncated = true;
+            }
+        }
+    }
+}
+
+/// What the run sent, once the upload is over
+pub fn set_totals(project_id: &str, files_uploaded: usize, files_deleted: usize, bytes: u64, snapshot_id: Option<String>) {
+    if let Ok(mut running) = RUNNING.lock() {
+        if let Some(run) = running.get_mut(project_id) {
+            run.files_uploaded = files_uploaded;
+            run.files_deleted = files_deleted;
+            run.bytes = bytes;
+            run.snapshot_id = snapshot_id;
+        }
+    }
+}
+
+/// Close the run of a project and add it to its history; None when no run was begun
+pub fn finish<T>(
+    app_data_dir: &Path,
+    project_id: &str,
+    trigger: &str,
+    target: SyncTarget,
+    started_at: String,
+    duration_ms: u64,
+    result: &Result<T, String>,
+) -> Option<Result<SyncRun, String>> {
+    let pending = RUNNING.lock().ok()?.remove(project_id)?;
+    let (status, message) = match result {
+        Ok(_) => ("success", None),
+        Err(e) if e.contains("annulée") || e.contains("cancelled") => ("cancelled", Some(e.clone())),
+        Err(e) => ("error", Some(e.clone())),
+    };
+    let run = SyncRun {
+        id: uuid::Uuid::new_v4().to_string(),
+        project_id: project_id.to_string(),
+        started_at,
+        duration_ms,
+        trigger: trigger.to_string(),
+        target,
+        status: status.to_string(),
+        message,
+        files_uploaded: pending.files_uploaded,
+        files_deleted: pending.files_deleted,
+        bytes: pending.bytes,
+        snapshot_id: pending.snapshot_id,
+        file_errors: pending.file_errors,
+        file_errors_truncated: pending.file_errors_truncated,
+    };
+    Some(append(app_data_dir, &run).map(|_| run))
+}
+
+fn append(app_data_dir: &Path, run: &SyncRun) -> Result<(), String> {
+    let _guard = HISTORY_LOCK.lock().map_err(|_| "Failed to access sync history".to_string())?;
+    let mut runs = load(app_data_dir, &run.project_id);
+    runs.push(run.clone());
+    if runs.len() > MAX_RUNS {
+        runs.drain(..runs.len() - MAX_RUNS);
+    }
+    state_file::write_json(&history_path(app_data_dir, &run.project_id), &runs)
+}
+
+/// Runs of a project, newest first
+pub fn query(app_data_dir: &Path, project_id: &str, limit: Option<usize>) -> Vec<SyncRun> {
+    load(app_data_dir, project_id)
+        .into_iter()
+        .rev()
+        .take(limit.unwrap_or(DEFAULT_LIMIT).max(1))
+        .collect()
+}
+
+/// Remove a project's history
+pub fn clear(app_data_dir: &Path, project_id: &str) -> Result<(), String> {
+    let _guard = HISTORY_LOCK.lock().map_err(|_| "Failed to access sync history".to_string())?;
+    let path = history_path(app_data_dir, project_id);
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| format!("Failed to clear sync history: {}", e))?;
+    }
+    let _ = std::fs::remove_file(state_file::backup_path(&path));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn target() -> SyncTarget {
+        SyncTarget {
+            protocol: "sftp".to_string(),
+            host: "example.com".to_string(),
+            port: 22,
+            username: "deploy".to_string(),
+            remote_path: "/var/www".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_runs_recorded_with_file_errors() {
+        let dir = TempDir::new("sync-history");
+        let app_dir = dir.path();
+
+        // Errors outside a run are not kept
+        note_error("history-test", Some("early.css"), "ignored");
+        begin("history-test");
+        note_error("history-test", Some("img/logo.png"), "Permission denied");
+        set_totals("history-test", 12, 1, 4096, None);
+        let run = finish(app_dir, "history-test", "manual", target(), "2026-10-17T09:00:00Z".to_string(), 1500, &Ok::<(), String>(()))
+            .unwrap()
+            .unwrap();
+        assert_eq!((run.status.as_str(), run.files_uploaded, run.files_deleted, run.bytes), ("success", 12, 1, 4096));
+        assert_eq!(run.file_errors.len(), 1);
+        assert_eq!(run.file_errors[0].path.as_deref(), Some("img/logo.png"));
+
+        // A run that was never begun (refused before the queue) is not recorded
+        assert!(finish(app_dir, "history-test", "manual", target(), String::new(), 0, &Err::<(), String>("refusee".to_string())).is_none());
+
+        begin("history-test");
+        finish(app_dir, "history-test", "watch", target(), "2026-10-17T10:00:00Z".to_string(), 300, &Err::<(), String>("Synchronisation annulée".to_string()));
+        let runs = query(app_dir, "history-test", None);
+        assert_eq!(runs.iter().map(|r| r.status.as_str()).collect::<Vec<_>>(), vec!["cancelled", "success"]);
+        assert_eq!(query(app_dir, "history-test", Some(1)).len(), 1);
+
+        clear(app_dir, "history-test").unwrap();
+        assert!(query(app_dir, "history-test", None).is_empty());
+    }
+}
@@ -0,0 +1,166 @@
+//! Sync Lock Module
+//!
+//! Per-project lock preventing two syncs of the same project
+//! (e.g. manual + scheduled) from racing on the same remote files.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Information about a sync currently holding a project lock
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveSync {
+    pub project_id: String,
+    pub trigger: String,
+    pub started_at: String,
+    /// Identifies the guard holding the lock
+    #[serde(skip)]
+    token: u64,
+}
+
+/// Registry of the running syncs; clones share the same locks
+#[derive(Clone, Default)]
+pub struct SyncLocks {
+    active: Arc<Mutex<HashMap<String, ActiveSync>>>,
+}
+
+static LOCKS: Lazy<SyncLocks> = Lazy::new(SyncLocks::default);
+static NEXT_TOKEN: AtomicU64 = AtomicU64::new(1);
+
+/// Lock held for the duration of a sync, released on drop
+pub struct SyncLockGuard {
+    locks: SyncLocks,
+    project_id: String,
+    token: u64,
+}
+
+impl Drop for SyncLockGuard {
+    fn drop(&mut self) {
+        if let Ok(mut active) = self.locks.active.lock() {
+            // A lock force-released by the watchdog may belong to a newer sync by now
+            if active.get(&self.project_id).map(|a| a.token == self.token).unwrap_or(false) {
+                active.remove(&self.project_id);
+            }
+        }
+    }
+}
+
+impl SyncLocks {
+    /// Try to acquire the sync lock for a project.
+    ///
+    /// Fails if another sync of the same project is already running.
+    pub fn try_acquire(&self, project_id: &str, trigger: &str) -> Result<SyncLockGuard, String> {
+        let mut active = self
+            .active
+            .lock()
+            .map_err(|_| "Failed to access sync lock state".to_string())?;
+
+        if let Some(existing) = active.get(project_id) {
+            return Err(format!(
+                "Une synchronisation ({}) est déjà en cours pour ce projet depuis {}",
+                existing.trigger, existing.started_at
+            ));
+        }
+
+        let token = NEXT_TOKEN.fetch_add(1, Ordering::Relaxed);
+        active.insert(
+            project_id.to_string(),
+            ActiveSync {
+                project_id: project_id.to_string(),
+                trigger: trigger.to_string(),
+                started_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                token,
+            },
+        );
+
+        Ok(SyncLockGuard {
+            locks: self.clone(),
+            project_id: project_id.to_string(),
+            token,
+        })
+    }
+
+    pub fn is_locked(&self, project_id: &str) -> bool {
+        self.active
+            .lock()
+            .map(|active| active.contains_key(project_id))
+            .unwrap_or(false)
+    }
+
+    pub fn active_syncs(&self) -> Vec<ActiveSync> {
+        self.active
+            .lock()
+            .map(|active| active.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn force_release(&self, project_id: &str) -> Option<ActiveSync> {
+        self.active.lock().ok()?.remove(project_id)
+    }
+}
+
+/// Locks of the app's syncs
+pub fn global() -> SyncLocks {
+    LOCKS.clone()
+}
+
+/// Try to acquire the sync lock for a project.
+///
+/// Fails if another sync of the same project is already running.
+pub fn try_acquire(project_id: &str, trigger: &str) -> Result<SyncLockGuard, String> {
+    LOCKS.try_acquire(project_id, trigger)
+}
+
+/// Check whether a sync is running for a project
+pub fn is_locked(project_id: &str) -> bool {
+    LOCKS.is_locked(project_id)
+}
+
+/// List all syncs currently running
+pub fn active_syncs() -> Vec<ActiveSync> {
+    LOCKS.active_syncs()
+}
+
+/// Release the lock of a sync that stopped responding; its guard won't release a newer lock
+pub fn force_release(project_id: &str) -> Option<ActiveSync> {
+    LOCKS.force_release(project_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_sync_per_project() {
+        let locks = SyncLocks::default();
+        let guard = locks.try_acquire("shop", "manual").unwrap();
+        let error = locks.try_acquire("shop", "schedule").err().unwrap();
+        assert!(error.contains("(manual)"));
+        let other = locks.try_acquire("blog", "tray").unwrap();
+        assert!(locks.is_locked("shop") && locks.is_locked("blog"));
+        assert_eq!(locks.active_syncs().len(), 2);
+
+        drop(guard);
+        assert!(!locks.is_locked("shop"));
+        assert!(locks.try_acquire("shop", "schedule").is_ok());
+        drop(other);
+        assert!(locks.active_syncs().is_empty());
+    }
+
+    #[test]
+    fn test_stale_guard_keeps_a_newer_lock() {
+        let locks = SyncLocks::default();
+        let stuck = locks.try_acquire("shop", "manual").unwrap();
+        assert_eq!(locks.force_release("shop").unwrap().trigger, "manual");
+        assert!(locks.force_release("shop").is_none());
+
+        let newer = locks.try_acquire("shop", "schedule").unwrap();
+        // The stuck sync finishes after the watchdog released it
+        drop(stuck);
+        assert_eq!(locks.active_syncs()[0].trigger, "schedule");
+        drop(newer);
+        assert!(!locks.is_locked("shop"));
+    }
+}
@@ -0,0 +1,190 @@
+//! Sync Presets Module
+//!
+//! Built-in sync presets for common CMSs (WordPress, Prestashop, static
+//! sites): exclude rules, paths that must never be deleted on the server
+//! and recommended remote paths, selectable when configuring a project
+//! target. Presets are protocol-agnostic. Mirror mode goes through the
+//! preset of the project, chosen or detected: it never deletes a protected
+//! path, and deletes nothing at all with the "never" policy.
+
+use crate::ignore_rules::IgnoreRules;
+use serde::Serialize;
+use std::path::Path;
+
+/// A preset applied to a project's sync rules
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncPreset {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// Gitignore-style patterns, same format as the project's sync rules
+    pub exclude_patterns: Vec<String>,
+    /// Remote paths never deleted by a sync (user uploads, generated files)
+    pub protected_paths: Vec<String>,
+    /// What mirror mode may delete: "never" (nothing), "confirm" (files outside the
+    /// protected paths, after the dry-run preview) or "mirror" (every remote-only file)
+    pub delete_policy: String,
+    /// Usual remote document roots, most common first
+    pub recommended_remote_paths: Vec<String>,
+}
+
+struct PresetSpec {
+    id: &'static str,
+    name: &'static str,
+    description: &'static str,
+    /// Files or folders whose presence in the local project identifies the CMS
+    markers: &'static [&'static str],
+    exclude_patterns: &'static [&'static str],
+    protected_paths: &'static [&'static str],
+    delete_policy: &'static str,
+    recommended_remote_paths: &'static [&'static str],
+}
+
+/// Patterns excluded by every preset
+const COMMON_EXCLUDES: [&str; 8] = [
+    ".DS_Store",
+    "Thumbs.db",
+    ".git/",
+    "node_modules/",
+    "*.log",
+    "*.map",
+    ".env",
+    ".env.local",
+];
+
+const PRESETS: [PresetSpec; 3] = [
+    PresetSpec {
+        id: "wordpress",
+        name: "WordPress",
+        description: "Theme et plugins synchronises, configuration, cache et medias du serveur preserves",
+        markers: &["wp-config.php", "wp-config-sample.php", "wp-content"],
+        exclude_patterns: &[
+            "wp-config.php",
+            "wp-content/cache/",
+            "wp-content/uploads/",
+            "wp-content/upgrade/",
+            "wp-content/backup*/",
+            "wp-content/debug.log",
+            ".htaccess",
+        ],
+        protected_paths: &["wp-config.php", "wp-content/uploads/", "wp-content/cache/", ".htaccess"],
+        delete_policy: "confirm",
+        recommended_remote_paths: &["/public_html", "/www", "/htdocs", "/var/www/html"],
+    },
+    PresetSpec {
+        id: "prestashop",
+        name: "Prestashop",
+        description: "Modules et themes synchronises, parametres, cache et images produits du serveur preserves",
+        markers: &["app/config/parameters.php", "config/settings.inc.php", "classes/PrestaShopAutoload.php"],
+        exclude_patterns: &[
+            "app/config/parameters.php",
+            "config/settings.inc.php",
+            "var/",
+            "cache/",
+            "img/p/",
+            "upload/",
+            "download/",
+        ],
+        protected_paths: &[
+            "app/config/parameters.php",
+            "config/settings.inc.php",
+            "var/",
+            "img/",
+            "upload/",
+            "download/",
+        ],
+        delete_policy: "never",
+        recommended_remote_paths: &["/public_html", "/www", "/httpdocs"],
+    },
+    PresetSpec {
+        id: "static",
+        name: "Site statique",
+        description: "Le dossier local est la reference, le serveur en est le miroir",
+        markers: &["index.html"],
+        exclude_patterns: &["*.psd", "*.sketch", "src/", "package.json", "package-lock.json"],
+        protected_paths: &[],
+        delete_policy: "mirror",
+        recommended_remote_paths: &["/public_html", "/www", "/htdocs"],
+    },
+];
+
+fn to_strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+fn build_preset(spec: &PresetSpec) -> SyncPreset {
+    let mut exclude_patterns = to_strings(&COMMON_EXCLUDES);
+    exclude_patterns.extend(to_strings(spec.exclude_patterns));
+    SyncPreset {
+        id: spec.id.to_string(),
+        name: spec.name.to_string(),
+        description: spec.description.to_string(),
+        exclude_patterns,
+        protected_paths: to_strings(spec.protected_paths),
+        delete_policy: spec.delete_policy.to_string(),
+        recommended_remote_paths: to_strings(spec.recommended_remote_paths),
+    }
+}
+
+/// All built-in presets
+pub fn all_presets() -> Vec<SyncPreset> {
+    PRESETS.iter().map(build_preset).collect()
+}
+
+/// Suggest a preset from the files of the local folder (checked in preset order)
+pub fn detect_preset(local_path: &str) -> Option<SyncPreset> {
+    let root = Path::new(local_path);
+    PRESETS
+        .iter()
+        .find(|spec| spec.markers.iter().any(|marker| root.join(marker).exists()))
+        .map(build_preset)
+}
+
+/// Preset of a sync: the one chosen by id, "none" for no preset, detected from the local folder when None
+pub fn resolve(preset_id: Option<&str>, local_path: &str) -> Option<SyncPreset> {
+    match preset_id {
+        Some("none") => None,
+        Some(id) => PRESETS.iter().find(|spec| spec.id == id).map(build_preset),
+        None => detect_preset(local_path),
+    }
+}
+
+/// The remote-only files mirror mode may delete under `preset`
+pub fn deletable_orphans(preset: Option<&SyncPreset>, orphans: Vec<String>) -> Vec<String> {
+    let preset = match preset {
+        Some(preset) => preset,
+        None => return orphans,
+    };
+    if preset.delete_policy == "never" {
+        return Vec::new();
+    }
+    // Same syntax as the exclude patterns; a protected folder keeps everything under it
+    let protected = IgnoreRules::parse(&preset.protected_paths.join("\n"));
+    orphans.into_iter().filter(|path| !protected.is_ignored(path, false)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn orphans() -> Vec<String> {
+        ["old.html", "wp-content/uploads/2024/photo.jpg", "wp-config.php", "wp-content/themes/old/style.css"]
+            .iter()
+            .map(|p| p.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_mirror_deletions_follow_the_preset() {
+        let wordpress = resolve(Some("wordpress"), "").unwrap();
+        assert_eq!(
+            deletable_orphans(Some(&wordpress), orphans()),
+            vec!["old.html".to_string(), "wp-content/themes/old/style.css".to_string()]
+        );
+        let prestashop = resolve(Some("prestashop"), "").unwrap();
+        assert!(deletable_orphans(Some(&prestashop), orphans()).is_empty());
+        assert_eq!(deletable_orphans(None, orphans()).len(), 4);
+        assert!(resolve(Some("none"), "").is_none());
+    }
+}
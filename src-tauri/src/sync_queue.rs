@@ -0,0 +1,281 @@
+//! Sync Queue Module
+//!
+//! Syncs asked for while another one runs wait their turn instead of being
+//! refused or running side by side: the tray icon, the dock badge and the
+//! sync panel follow one upload at a time, and a tray sync landing during a
+//! scheduled one of the same project would upload the same files. Waiting
+//! syncs start in the order they were asked, get a `queued` progress event
+//! whenever their position changes, and can be dropped before they start.
+//! Running syncs are the holders of the sync_lock.
+
+use crate::sync_lock::{self, ActiveSync, SyncLockGuard, SyncLocks};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Syncs (and other lock holders: pulls, maintenance...) running at once
+pub const MAX_RUNNING_SYNCS: usize = 1;
+
+/// A sync still waiting after this long gives up
+const MAX_WAIT: Duration = Duration::from_secs(30 * 60);
+
+/// Locks are also released outside the queue, so waiters check again this often
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone)]
+struct Ticket {
+    id: u64,
+    project_id: String,
+    trigger: String,
+    queued_at: String,
+}
+
+/// A sync waiting for its turn
+#[derive(Debug, Clone, Serialize)]
+pub struct QueuedSync {
+    pub id: u64,
+    pub project_id: String,
+    pub trigger: String,
+    pub queued_at: String,
+    /// 1 for the next sync to start
+    pub position: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncQueueStatus {
+    pub running: Vec<ActiveSync>,
+    pub waiting: Vec<QueuedSync>,
+    pub max_running: usize,
+}
+
+/// Waiting syncs in front of a set of sync locks
+pub struct SyncQueue {
+    waiting: Mutex<Vec<Ticket>>,
+    wake: Condvar,
+    locks: SyncLocks,
+    max_running: usize,
+}
+
+static QUEUE: Lazy<SyncQueue> = Lazy::new(|| SyncQueue::new(sync_lock::global(), MAX_RUNNING_SYNCS));
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+impl SyncQueue {
+    pub fn new(locks: SyncLocks, max_running: usize) -> Self {
+        Self {
+            waiting: Mutex::new(Vec::new()),
+            wake: Condvar::new(),
+            locks,
+            max_running,
+        }
+    }
+
+    fn can_start(&self, project_id: &str) -> bool {
+        !self.locks.is_locked(project_id) && self.locks.active_syncs().len() < self.max_running
+    }
+
+    fn lock_waiting(&self) -> Result<MutexGuard<'_, Vec<Ticket>>, String> {
+        self.waiting.lock().map_err(|_| "Failed to access sync queue".to_string())
+    }
+
+    /// See [`acquire`]
+    pub fn acquire(&self, project_id: &str, trigger: &str, on_queued: impl Fn(usize, usize)) -> Result<SyncLockGuard, String> {
+        let id = {
+            let mut waiting = self.lock_waiting()?;
+            if waiting.iter().any(|t| t.project_id == project_id) {
+                return Err("Une synchronisation de ce projet est deja en attente".to_string());
+            }
+            if waiting.is_empty() && self.can_start(project_id) {
+                return self.locks.try_acquire(project_id, trigger);
+            }
+
+            let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+            waiting.push(Ticket {
+                id,
+                project_id: project_id.to_string(),
+                trigger: trigger.to_string(),
+                queued_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            });
+            println!(
+                "[SyncQueue] {} ({}) queued behind {} sync(s)",
+                project_id,
+                trigger,
+                waiting.len() - 1 + self.locks.active_syncs().len()
+            );
+            id
+        };
+
+        let started = Instant::now();
+        let mut reported_position = 0;
+        loop {
+            let mut waiting = self.lock_waiting()?;
+            let index = match waiting.iter().position(|t| t.id == id) {
+                Some(index) => index,
+                None => return Err("Synchronisation retiree de la file d'attente".to_string()),
+            };
+            // First come, first served among the syncs able to start
+            let next = waiting.iter().find(|t| self.can_start(&t.project_id)).map(|t| t.id);
+            if next == Some(id) {
+                waiting.remove(index);
+                self.wake.notify_all();
+                println!("[SyncQueue] {} leaves the queue after {:?}", project_id, started.elapsed());
+                return self.locks.try_acquire(project_id, trigger);
+            }
+            if started.elapsed() > MAX_WAIT {
+                waiting.remove(index);
+                self.wake.notify_all();
+                return Err(format!("Synchronisation abandonnee apres {} min d'attente", MAX_WAIT.as_secs() / 60));
+            }
+            if index + 1 != reported_position {
+                reported_position = index + 1;
+                // Called with the queue unlocked: it may emit events or look at the queue
+                drop(waiting);
+                on_queued(reported_position, self.locks.active_syncs().len());
+                continue;
+            }
+            drop(
+                self.wake
+                    .wait_timeout(waiting, POLL_INTERVAL)
+                    .map_err(|_| "Failed to access sync queue".to_string())?,
+            );
+        }
+    }
+
+    /// See [`cancel`]
+    pub fn cancel(&self, project_id: &str) -> bool {
+        let removed = match self.waiting.lock() {
+            Ok(mut waiting) => {
+                let before = waiting.len();
+                waiting.retain(|t| t.project_id != project_id);
+                waiting.len() != before
+            }
+            Err(_) => false,
+        };
+        if removed {
+            println!("[SyncQueue] Queued sync of {} cancelled", project_id);
+            self.wake.notify_all();
+        }
+        removed
+    }
+
+    /// See [`status`]
+    pub fn status(&self) -> SyncQueueStatus {
+        let waiting = self
+            .waiting
+            .lock()
+            .map(|waiting| {
+                waiting
+                    .iter()
+                    .enumerate()
+                    .map(|(index, t)| QueuedSync {
+                        id: t.id,
+                        project_id: t.project_id.clone(),
+                        trigger: t.trigger.clone(),
+                        queued_at: t.queued_at.clone(),
+                        position: index + 1,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        SyncQueueStatus {
+            running: self.locks.active_syncs(),
+            waiting,
+            max_running: self.max_running,
+        }
+    }
+}
+
+/// Take the sync lock of a project, waiting behind the running and queued syncs.
+///
+/// `on_queued(position, running)` is called when the sync has to wait and
+/// each time its position changes. A project has at most one waiting sync.
+pub fn acquire(project_id: &str, trigger: &str, on_queued: impl Fn(usize, usize)) -> Result<SyncLockGuard, String> {
+    QUEUE.acquire(project_id, trigger, on_queued)
+}
+
+/// Drop the waiting sync of a project; false when none was waiting
+pub fn cancel(project_id: &str) -> bool {
+    QUEUE.cancel(project_id)
+}
+
+/// Running syncs and the waiting ones in start order
+pub fn status() -> SyncQueueStatus {
+    QUEUE.status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::{mpsc, Arc};
+
+    /// Queue over its own locks, so other tests' syncs don't count
+    fn queue() -> Arc<SyncQueue> {
+        Arc::new(SyncQueue::new(SyncLocks::default(), 1))
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_concurrent_syncs_wait_their_turn() {
+        let queue = queue();
+        let released = Arc::new(AtomicBool::new(false));
+        let (holding, first_holds) = mpsc::channel();
+
+        let (first_queue, first_released) = (queue.clone(), released.clone());
+        let first = tokio::task::spawn_blocking(move || {
+            let guard = first_queue.acquire("queue-first", "manual", |_, _| panic!("first sync queued")).unwrap();
+            holding.send(()).unwrap();
+            std::thread::sleep(Duration::from_millis(700));
+            first_released.store(true, Ordering::SeqCst);
+            drop(guard);
+        });
+        first_holds.recv().unwrap();
+
+        let second_queue = queue.clone();
+        let second = tokio::task::spawn_blocking(move || {
+            let positions = Mutex::new(Vec::new());
+            let guard = second_queue
+                .acquire("queue-second", "schedule", |position, running| {
+                    positions.lock().unwrap().push((position, running))
+                })
+                .unwrap();
+            let started_after_first = released.load(Ordering::SeqCst);
+            drop(guard);
+            (positions.into_inner().unwrap(), started_after_first)
+        });
+
+        // The runtime keeps serving other work while both syncs block
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(queue.status().waiting.iter().map(|q| q.project_id.as_str()).collect::<Vec<_>>(), vec!["queue-second"]);
+
+        first.await.unwrap();
+        let (positions, started_after_first) = second.await.unwrap();
+        assert_eq!(positions, vec![(1, 1)]);
+        assert!(started_after_first);
+        assert!(queue.status().running.is_empty());
+    }
+
+    #[test]
+    fn test_callback_may_use_the_queue() {
+        let queue = queue();
+        let running = queue.acquire("queue-running", "manual", |_, _| panic!("nothing runs yet")).unwrap();
+
+        let (cancelled_tx, cancelled) = mpsc::channel();
+        let waiter_queue = queue.clone();
+        let waiter = std::thread::spawn(move || {
+            // Would deadlock if the queue stayed locked during the callback
+            waiter_queue
+                .acquire("queue-waiting", "tray", |position, _| {
+                    assert_eq!(waiter_queue.status().waiting[0].position, position);
+                    cancelled_tx.send(waiter_queue.cancel("queue-waiting")).unwrap();
+                })
+                .err()
+        });
+
+        assert_eq!(cancelled.recv_timeout(Duration::from_secs(5)), Ok(true));
+        assert_eq!(waiter.join().unwrap().as_deref(), Some("Synchronisation retiree de la file d'attente"));
+        assert!(queue.status().waiting.is_empty());
+        drop(running);
+        assert!(queue.acquire("queue-waiting", "tray", |_, _| panic!("nothing runs anymore")).is_ok());
+    }
+}
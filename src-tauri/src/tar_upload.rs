@@ -0,0 +1,447 @@
+//! Tar Upload Module
+//!
+//! Fast path for SFTP targets with shell access: the files to upload are
+//! packed into tar chunks on the fly and streamed to `tar -x` on the server,
+//! one SSH channel per chunk instead of one round trip per file. Initial
+//! deploys of thousands of small files go from hours to minutes. Files a
+//! chunk couldn't carry (names too long for ustar, chunk refused by the
+//! server) and servers without tar fall back to per-file SFTP uploads. With
+//! `atomic_upload`, a chunk is extracted into a staging folder of the remote
+//! root and each file is then renamed into place. Like per-file uploads,
+//! files get the local mode with `preserve_permissions` and keep the local
+//! modification time unless `preserve_mtime` is off. The remote output is
+//! cut to its last bytes on the server so it can't stall the stream.
+
+use crate::atomic_upload;
+use crate::parallel_sync::ParallelProgressTracker;
+use crate::remote_exec::{self, shell_quote};
+use crate::remote_fs::RemoteFs;
+use crate::{create_sftp_dirs, file_attributes, is_cancelled, FileDiff, SFTPConfig, UploadOptions};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+/// Files per tar chunk
+const CHUNK_MAX_FILES: usize = 1000;
+
+/// Bytes per tar chunk, so a failure only resends this much
+const CHUNK_MAX_BYTES: u64 = 64 * 1024 * 1024;
+
+const BLOCK: usize = 512;
+
+/// Printed with the exit status when the remote extraction fails
+const TAR_FAILED_MARKER: &str = "LAFORGE_TAR_FAILED";
+
+/// Bytes of remote output kept, well under the channel window
+const MAX_REMOTE_OUTPUT: usize = 4096;
+
+/// Octal field of a tar header, NUL terminated
+fn write_octal(field: &mut [u8], value: u64) {
+    let width = field.len() - 1;
+    let digits = format!("{:0width$o}", value, width = width);
+    field[..width].copy_from_slice(digits.as_bytes());
+    field[width] = 0;
+}
+
+/// ustar header of a regular file; None when the path can't be stored (over 255 bytes)
+fn ustar_header(path: &str, size: u64, mode: u32, mtime: u64) -> Option<[u8; BLOCK]> {
+    let bytes = path.as_bytes();
+    let (prefix, name) = if bytes.len() <= 100 {
+        (&[][..], bytes)
+    } else {
+        // Split on a "/" so the name fits in 100 bytes and the prefix in 155
+        let split = (0..bytes.len())
+            .filter(|&i| bytes[i] == b'/')
+            .find(|&i| i <= 155 && bytes.len() - i - 1 <= 100)?;
+        (&bytes[..split], &bytes[split + 1..])
+    };
+    if size >= 8u64.pow(11) {
+        return None;
+    }
+
+    let mut header = [0u8; BLOCK];
+    header[..name.len()].copy_from_slice(name);
+    write_octal(&mut header[100..108], mode as u64 & 0o7777);
+    write_octal(&mut header[108..116], 0);
+    write_octal(&mut header[116..124], 0);
+    write_octal(&mut header[124..136], size);
+    write_octal(&mut header[136..148], mtime);
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+    header[345..345 + prefix.len()].copy_from_slice(prefix);
+
+    // The checksum is computed with its own field filled with spaces
+    header[148..156].copy_from_slice(b"        ");
+    let checksum: u64 = header.iter().map(|b| *b as u64).sum();
+    write_octal(&mut header[148..155], checksum);
+    header[155] = b' ';
+    Some(header)
+}
+
+/// Mode and mtime carried by the header of `path`: the local ones when the
+/// sync preserves them, else 0644 and the upload time, like a per-file upload
+fn header_mode_and_mtime(path: &Path, upload: UploadOptions) -> (u32, u64) {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let metadata = match std::fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return (0o644, now),
+    };
+    let mode = if upload.preserve_permissions {
+        file_attributes::local_mode(path).unwrap_or(0o644)
+    } else {
+        0o644
+    };
+    let mtime = if upload.preserve_mtime {
+        metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(now)
+    } else {
+        now
+    };
+    (mode, mtime)
+}
+
+/// Remote command extracting a chunk in `remote_base`. With a staging folder
+/// (created in `remote_base`, so renames stay on one filesystem), files are
+/// moved into place only once the whole chunk is extracted, and the staging
+/// folder is always removed. `-m` stamps the files with the extraction time
+/// instead of the mtime of their header.
+fn extract_command(remote_base: &str, staging: Option<&str>, keep_mtime: bool) -> String {
+    let quoted = shell_quote(remote_base);
+    let flags = if keep_mtime { "-xf" } else { "-xmf" };
+    match staging {
+        None => format!("mkdir -p {} && tar {} - -C {}", quoted, flags, quoted),
+        Some(staging) => {
+            let staging = shell_quote(staging);
+            format!(
+                "mkdir -p {base} && cd {base} && mkdir {tmp} && tar {flags} - -C {tmp} && (cd {tmp} && find . -type f -exec sh -c \
+                 'for f do mkdir -p \"../$(dirname \"$f\")\" && mv -f \"$f\" \"../$f\" || exit 1; done' sh {{}} +); \
+                 status=$?; rm -rf {tmp}; exit $status",
+                base = quoted,
+                tmp = staging,
+                flags = flags
+            )
+        }
+    }
+}
+
+/// Write the header, the content and the padding of one file to the archive
+fn write_entry(out: &mut impl Write, path: &str, file: &mut File, size: u64, (mode, mtime): (u32, u64)) -> Result<(), String> {
+    let header = ustar_header(path, size, mode, mtime).ok_or_else(|| format!("Chemin trop long pour tar: {}", path))?;
+    out.write_all(&header).map_err(|e| format!("Failed to send {}: {}", path, e))?;
+    // Exactly `size` bytes, even if the file grows meanwhile
+    let copied = std::io::copy(&mut file.take(size), out).map_err(|e| format!("Failed to send {}: {}", path, e))?;
+    if copied != size {
+        return Err(format!("{} a change pendant l'envoi", path));
+    }
+    let padding = (BLOCK - (size as usize % BLOCK)) % BLOCK;
+    out.write_all(&vec![0u8; padding]).map_err(|e| format!("Failed to send {}: {}", path, e))
+}
+
+/// Run `command` with its stdout and stderr cut to their last bytes, printed
+/// on stderr when it ends, and the marker with its exit status on failure.
+/// What tar reports while the archive still streams stays on the server:
+/// sent back, it would fill the channel window and stall both sides.
+fn bounded_output_command(command: &str) -> String {
+    format!(
+        "{{ ( {} ) || echo {} $?; }} 2>&1 | tail -c {} >&2",
+        command, TAR_FAILED_MARKER, MAX_REMOTE_OUTPUT
+    )
+}
+
+/// Stream one chunk to `tar -x` in the remote root
+fn send_chunk(
+    sess: &ssh2::Session,
+    local_path: &str,
+    remote_base: &str,
+    chunk: &[&FileDiff],
+    tracker: &ParallelProgressTracker,
+    upload: UploadOptions,
+) -> Result<(), String> {
+    let mut channel = sess.channel_session().map_err(|e| format!("Failed to open channel: {}", e))?;
+    let staging = upload
+        .atomic
+        .then(|| format!("{}-{}", atomic_upload::TEMP_SUFFIX, uuid::Uuid::new_v4().simple()));
+    channel
+        .exec(&bounded_output_command(&extract_command(remote_base, staging.as_deref(), upload.preserve_mtime)))
+        .map_err(|e| format!("Failed to start remote tar: {}", e))?;
+
+    for diff in chunk {
+        let local_file = Path::new(local_path).join(&diff.path);
+        let mut file = File::open(&local_file).map_err(|e| format!("Failed to open {}: {}", local_file.display(), e))?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        tracker.emit_file_start(&diff.path, size);
+        write_entry(&mut channel, &diff.path, &mut file, size, header_mode_and_mtime(&local_file, upload))?;
+    }
+    // End of archive: two empty blocks
+    channel.write_all(&[0u8; BLOCK * 2]).map_err(|e| format!("Failed to end archive: {}", e))?;
+    channel.send_eof().map_err(|e| format!("Failed to end archive: {}", e))?;
+
+    let mut stderr = String::new();
+    let _ = channel.stderr().read_to_string(&mut stderr);
+    channel.wait_close().map_err(|e| format!("Failed to close channel: {}", e))?;
+    match channel.exit_status().map_err(|e| format!("Failed to read exit status: {}", e))? {
+        0 if !stderr.contains(TAR_FAILED_MARKER) => Ok(()),
+        code => Err(format!("tar distant a echoue (code {}): {}", code, stderr.trim())),
+    }
+}
+
+/// Split the upload set into chunks bounded in files and bytes
+fn chunks<'a>(files: &[&'a FileDiff]) -> Vec<Vec<&'a FileDiff>> {
+    let mut chunks: Vec<Vec<&FileDiff>> = Vec::new();
+    let mut current: Vec<&FileDiff> = Vec::new();
+    let mut current_bytes = 0u64;
+    for diff in files {
+        let size = diff.local_size.unwrap_or(0);
+        if !current.is_empty() && (current.len() >= CHUNK_MAX_FILES || current_bytes + size > CHUNK_MAX_BYTES) {
+            chunks.push(std::mem::take(&mut current));
+            current_bytes = 0;
+        }
+        current.push(diff);
+        current_bytes += size;
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Upload the added and modified files of `diffs` as tar chunks over SSH
+pub fn tar_sftp_sync(
+    local_path: &str,
+    config: &SFTPConfig,
+    diffs: &[FileDiff],
+    project_id: &str,
+    app_handle: &tauri::AppHandle,
+    upload: UploadOptions,
+) -> Result<(), String> {
+    let files_to_upload: Vec<&FileDiff> = diffs
+        .iter()
+        .filter(|d| d.status == "added" || d.status == "modified")
+        .collect();
+    if files_to_upload.is_empty() {
+        return Ok(());
+    }
+
+    let sess = remote_exec::connect_ssh(config)?;
+    let tracker = ParallelProgressTracker::new(project_id.to_string(), files_to_upload.len(), app_handle.clone(), 20, 70);
+    let remote_base = if config.remote_path.is_empty() { "." } else { config.remote_path.trim_end_matches('/') };
+
+    let has_tar = remote_exec::run_command(&sess, "command -v tar", Duration::from_secs(15))
+        .map(|output| output.exit_code == 0)
+        .unwrap_or(false);
+    let (mut fallback, tarable): (Vec<&FileDiff>, Vec<&FileDiff>) = if has_tar {
+        files_to_upload
+            .iter()
+            .copied()
+            .partition(|d| ustar_header(&d.path, d.local_size.unwrap_or(0), 0, 0).is_none())
+    } else {
+        println!("[TarUpload] No tar on {}, uploading file by file", config.host);
+        (files_to_upload.clone(), Vec::new())
+    };
+
+    for chunk in chunks(&tarable) {
+        if is_cancelled(project_id) {
+            return Err("Synchronisation annulée".to_string());
+        }
+        match send_chunk(&sess, local_path, remote_base, &chunk, &tracker, upload) {
+            Ok(()) => {
+                for diff in &chunk {
+                    tracker.emit_file_complete(&diff.path, diff.local_size.unwrap_or(0));
+                }
+            }
+            Err(e) => {
+                println!("[TarUpload] Chunk of {} file(s) failed, retrying file by file: {}", chunk.len(), e);
+                fallback.extend(chunk);
+            }
+        }
+    }
+
+    if fallback.is_empty() {
+        return Ok(());
+    }
+    let mut sftp = sess.sftp().map_err(|e| format!("SFTP error: {}", e))?;
+    for diff in fallback {
+        if is_cancelled(project_id) {
+            return Err("Synchronisation annulée".to_string());
+        }
+        if tracker.should_stop() {
+            break;
+        }
+        let size = diff.local_size.unwrap_or(0);
+        let remote_file = format!("{}/{}", remote_base, diff.path);
+        tracker.emit_file_start(&diff.path, size);
+        let uploaded: Result<(), String> = (|| {
+            if let Some(parent) = Path::new(&remote_file).parent() {
+                let _ = create_sftp_dirs(&sftp, parent);
+            }
+            let local_file = Path::new(local_path).join(&diff.path);
+            let mut local = File::open(&local_file).map_err(|e| format!("Failed to open {}: {}", local_file.display(), e))?;
+            atomic_upload::put(&mut sftp, &remote_file, upload.atomic, &mut local)?;
+            sftp.apply_attributes(&local_file.to_string_lossy(), &remote_file, upload);
+            Ok(())
+        })();
+        match uploaded {
+            Ok(_) => tracker.emit_file_complete(&diff.path, size),
+            Err(e) => tracker.emit_file_error(&diff.path, &e, size),
+        }
+    }
+
+    let errors = tracker.get_errors();
+    if !errors.is_empty() {
+        return Err(format!("{} fichier(s) en erreur: {}", errors.len(), errors.join(", ")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ustar_header_checksum_and_long_paths() {
+        let header = ustar_header("css/site.css", 10, 0o644, 0).unwrap();
+        let stored = u64::from_str_radix(std::str::from_utf8(&header[148..154]).unwrap(), 8).unwrap();
+        let mut blanked = header;
+        blanked[148..156].copy_from_slice(b"        ");
+        assert_eq!(stored, blanked.iter().map(|b| *b as u64).sum::<u64>());
+        assert_eq!(&header[124..135], b"00000000012");
+
+        let long = format!("{}/{}", "d".repeat(120), "f".repeat(90));
+        let header = ustar_header(&long, 1, 0o644, 0).unwrap();
+        assert_eq!(&header[345..465], "d".repeat(120).as_bytes());
+        assert!(ustar_header(&"x".repeat(300), 1, 0o644, 0).is_none());
+    }
+
+    fn diff(path: &str, size: u64) -> FileDiff {
+        FileDiff { path: path.to_string(), status: "added".to_string(), local_size: Some(size), remote_size: None }
+    }
+
+    #[test]
+    fn test_long_names_split_on_a_slash() {
+        let exact = "n".repeat(100);
+        let header = ustar_header(&exact, 0, 0o644, 0).unwrap();
+        assert_eq!(&header[..100], exact.as_bytes());
+        assert_eq!(header[345], 0);
+
+        // The prefix takes up to 155 bytes before a "/", the name the rest
+        let prefix = format!("{}/{}", "a".repeat(60), "b".repeat(94));
+        let path = format!("{}/{}", prefix, "c".repeat(100));
+        let header = ustar_header(&path, 0, 0o644, 0).unwrap();
+        assert_eq!(&header[345..345 + 155], prefix.as_bytes());
+        assert_eq!(&header[..100], "c".repeat(100).as_bytes());
+
+        // A last component over 100 bytes, or no "/" leaving 155 bytes or less before it
+        assert!(ustar_header(&format!("dir/{}", "c".repeat(101)), 0, 0o644, 0).is_none());
+        assert!(ustar_header(&format!("{}/{}", "a".repeat(156), "c".repeat(10)), 0, 0o644, 0).is_none());
+    }
+
+    #[test]
+    fn test_chunks_bounded_in_files_and_bytes() {
+        let small: Vec<FileDiff> = (0..2500).map(|i| diff(&format!("f{}", i), 10)).collect();
+        let refs: Vec<&FileDiff> = small.iter().collect();
+        let sizes: Vec<usize> = chunks(&refs).iter().map(|c| c.len()).collect();
+        assert_eq!(sizes, vec![1000, 1000, 500]);
+
+        let mb = 1024 * 1024;
+        let large = [diff("a", 32 * mb), diff("b", 32 * mb), diff("c", 1), diff("d", 100 * mb), diff("e", 1)];
+        let refs: Vec<&FileDiff> = large.iter().collect();
+        let paths: Vec<Vec<&str>> = chunks(&refs).iter().map(|c| c.iter().map(|d| d.path.as_str()).collect()).collect();
+        // Exactly 64 MB fits, one byte more starts a new chunk; a bigger file goes alone
+        assert_eq!(paths, vec![vec!["a", "b"], vec!["c"], vec!["d"], vec!["e"]]);
+    }
+
+    #[cfg(unix)]
+    fn run_sh(command: &str, stdin: &[u8]) -> (i32, String) {
+        use std::process::{Command, Stdio};
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(stdin).unwrap();
+        let output = child.wait_with_output().unwrap();
+        (output.status.code().unwrap_or(-1), String::from_utf8_lossy(&output.stderr).to_string())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_archive_extracts_with_system_tar() {
+        use std::os::unix::fs::PermissionsExt;
+        use crate::test_support::TempDir;
+
+        let local = TempDir::new("tar-local");
+        let long = format!("{}/{}/deploy.sh", "vendor".repeat(10), "bin".repeat(20));
+        local.write_files(&[("index.html", "<h1>v2</h1>"), (long.as_str(), "#!/bin/sh\n")]);
+        let script = local.path().join(&long);
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let old = std::time::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        File::options().write(true).open(&script).unwrap().set_modified(old).unwrap();
+
+        let archive = |upload: UploadOptions| {
+            let mut out = Vec::new();
+            for path in ["index.html", long.as_str()] {
+                let local_file = local.path().join(path);
+                let mut file = File::open(&local_file).unwrap();
+                let size = file.metadata().unwrap().len();
+                write_entry(&mut out, path, &mut file, size, header_mode_and_mtime(&local_file, upload)).unwrap();
+            }
+            out.extend_from_slice(&[0u8; BLOCK * 2]);
+            out
+        };
+
+        // Atomic, attributes kept
+        let remote = TempDir::new("tar-remote");
+        let upload = UploadOptions { atomic: true, preserve_permissions: true, preserve_mtime: true };
+        let command = bounded_output_command(&extract_command(&remote.path_str(), Some(".laforge-tmp-test"), true));
+        assert_eq!(run_sh(&command, &archive(upload)), (0, String::new()));
+        let extracted = remote.path().join(&long);
+        assert_eq!(std::fs::read_to_string(remote.path().join("index.html")).unwrap(), "<h1>v2</h1>");
+        assert_eq!(std::fs::metadata(&extracted).unwrap().permissions().mode() & 0o111, 0o111);
+        assert_eq!(std::fs::metadata(&extracted).unwrap().modified().unwrap(), old);
+        assert!(!remote.path().join(".laforge-tmp-test").exists());
+
+        // Attributes left to the server
+        let remote = TempDir::new("tar-remote");
+        let command = bounded_output_command(&extract_command(&remote.path_str(), None, false));
+        assert_eq!(run_sh(&command, &archive(UploadOptions::default())).0, 0);
+        let extracted = std::fs::metadata(remote.path().join(&long)).unwrap();
+        assert_eq!(extracted.permissions().mode() & 0o111, 0);
+        assert!(extracted.modified().unwrap() > old);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_remote_output_is_bounded_and_failures_marked() {
+        let noisy = "head -c 200000 /dev/zero | tr '\\0' x >&2; exit 3";
+        let (code, stderr) = run_sh(&bounded_output_command(noisy), b"");
+        assert_eq!(code, 0);
+        assert!(stderr.len() <= MAX_REMOTE_OUTPUT);
+        assert!(stderr.trim_end().ends_with("LAFORGE_TAR_FAILED 3"));
+
+        // A broken archive fails the extraction
+        let remote = crate::test_support::TempDir::new("tar-remote");
+        let (_, stderr) = run_sh(&bounded_output_command(&extract_command(&remote.path_str(), None, true)), &[b'x'; 1024]);
+        assert!(stderr.contains(TAR_FAILED_MARKER));
+    }
+
+    #[test]
+    fn test_atomic_extract_goes_through_a_staging_folder() {
+        assert_eq!(extract_command("/var/www", None, false), "mkdir -p '/var/www' && tar -xmf - -C '/var/www'");
+        // The header mtimes are kept without -m
+        assert_eq!(extract_command("/var/www", None, true), "mkdir -p '/var/www' && tar -xf - -C '/var/www'");
+        let atomic = extract_command("/var/www", Some(".laforge-tmp-1"), true);
+        assert!(atomic.starts_with("mkdir -p '/var/www' && cd '/var/www' && mkdir '.laforge-tmp-1' && tar -xf - -C '.laforge-tmp-1'"));
+        assert!(atomic.ends_with("status=$?; rm -rf '.laforge-tmp-1'; exit $status"));
+    }
+}
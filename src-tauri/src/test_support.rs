@@ -0,0 +1,427 @@
+//! Test Support Module
+//!
+//! Fixtures for end-to-end tests of the diff, sync, resume and scrape flows:
+//! throwaway directories, a local HTTP server serving a small site, and a
+//! local folder standing in for the remote side of a sync.
+//!
+//! Not done: the in-process SFTP and FTP servers. The upload, atomic upload
+//! and resume logic runs against `FolderRemote`, a folder behind the same
+//! `RemoteFs` trait the SFTP session and the FTP stream implement, but the
+//! ssh2 and suppaftp paths themselves have no automated test: connection and
+//! authentication, the host key check, SFTP seek and FTP APPE on resume, and
+//! the server-side rename of an atomic upload.
+
+use crate::remote_fs::RemoteFs;
+use crate::{RemoteFile, SFTPConfig, UploadOptions};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+/// Directory under the system temp folder, removed on drop
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub fn new(label: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "forge-test-{}-{}-{}",
+            label,
+            std::process::id(),
+            NEXT_DIR.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).expect("create temp dir");
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn path_str(&self) -> String {
+        self.path.to_string_lossy().to_string()
+    }
+
+    /// Write files given as (relative path, content)
+    pub fn write_files(&self, files: &[(&str, &str)]) {
+        for (relative, content) in files {
+            let path = self.path.join(relative);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).expect("create fixture dir");
+            }
+            fs::write(path, content).expect("write fixture file");
+        }
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Connection to a fixture server, logging in as "deploy" without a password
+pub fn sftp_config(protocol: &str, host: &str, port: u16, remote_path: &str) -> SFTPConfig {
+    serde_json::from_value(serde_json::json!({
+        "host": host,
+        "port": port,
+        "username": "deploy",
+        "remotePath": remote_path,
+        "passive": null,
+        "protocol": protocol,
+        "acceptInvalidCerts": null,
+        "useSshAgent": null,
+        "privateKeyPath": null,
+        "keyPassphrase": null,
+        "keyboardInteractive": null,
+    }))
+    .expect("fixture config")
+}
+
+/// Listing of a local folder in the shape returned by the remote scans
+pub fn remote_listing(dir: &Path) -> HashMap<String, RemoteFile> {
+    crate::scan_local_files(&dir.to_string_lossy())
+        .expect("scan fixture remote")
+        .into_iter()
+        .map(|(path, size)| (path, RemoteFile { size, mtime: None }))
+        .collect()
+}
+
+/// Local folder served through `RemoteFs`, standing in for the server
+pub struct FolderRemote {
+    root: PathBuf,
+    /// The next write stops with an error once this many bytes are stored,
+    /// like a connection dropping mid-transfer
+    pub fail_after: Option<u64>,
+}
+
+impl FolderRemote {
+    pub fn new(root: &Path) -> Self {
+        Self { root: root.to_path_buf(), fail_after: None }
+    }
+
+    fn local(&self, path: &str) -> PathBuf {
+        self.root.join(path.trim_start_matches('/'))
+    }
+}
+
+impl RemoteFs for FolderRemote {
+    fn create_dir(&mut self, path: &str) -> Result<(), String> {
+        fs::create_dir(self.local(path)).map_err(|e| format!("Failed to create {}: {}", path, e))
+    }
+
+    fn write_file(&mut self, path: &str, append: bool, reader: &mut dyn Read) -> Result<u64, String> {
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(self.local(path))
+            .map_err(|e| format!("Failed to create {}: {}", path, e))?;
+        match self.fail_after.take() {
+            Some(limit) => {
+                std::io::copy(&mut reader.take(limit), &mut file).map_err(|e| e.to_string())?;
+                Err(format!("Failed to write {}: connection reset", path))
+            }
+            None => std::io::copy(reader, &mut file).map_err(|e| format!("Failed to write {}: {}", path, e)),
+        }
+    }
+
+    /// Refuses to replace an existing file, as SFTP v3 servers do
+    fn rename_file(&mut self, from: &str, to: &str) -> Result<(), String> {
+        if self.local(to).exists() {
+            return Err(format!("Failed to rename {} to {}: file exists", from, to));
+        }
+        fs::rename(self.local(from), self.local(to)).map_err(|e| format!("Failed to rename {} to {}: {}", from, to, e))
+    }
+
+    fn remove_file(&mut self, path: &str) -> Result<(), String> {
+        fs::remove_file(self.local(path)).map_err(|e| format!("Failed to remove {}: {}", path, e))
+    }
+
+    fn file_size(&mut self, path: &str) -> Option<u64> {
+        fs::metadata(self.local(path)).ok().map(|m| m.len())
+    }
+
+    fn apply_attributes(&mut self, local_file: &str, remote_file: &str, upload: UploadOptions) {
+        if !upload.preserve_mtime {
+            return;
+        }
+        if let Ok(modified) = fs::metadata(local_file).and_then(|m| m.modified()) {
+            let _ = fs::File::options()
+                .write(true)
+                .open(self.local(remote_file))
+                .and_then(|f| f.set_modified(modified));
+        }
+    }
+}
+
+/// Minimal HTTP/1.1 server answering GET requests from a fixed route table
+pub struct HttpFixture {
+    port: u16,
+    stop: Arc<AtomicBool>,
+}
+
+impl HttpFixture {
+    /// Serve routes given as (path, content type, body); other paths answer 404
+    pub fn serve(routes: &[(&str, &str, &str)]) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind http fixture");
+        let port = listener.local_addr().expect("fixture address").port();
+        let routes: HashMap<String, (String, String)> = routes
+            .iter()
+            .map(|(path, content_type, body)| (path.to_string(), (content_type.to_string(), body.to_string())))
+            .collect();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = stop.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stop_flag.load(Ordering::Relaxed) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    handle_request(stream, &routes);
+                }
+            }
+        });
+
+        Self { port, stop }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("http://127.0.0.1:{}{}", self.port, path)
+    }
+}
+
+impl Drop for HttpFixture {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // Wake the accept loop so the thread sees the flag
+        let _ = TcpStream::connect(("127.0.0.1", self.port));
+    }
+}
+
+fn handle_request(mut stream: TcpStream, routes: &HashMap<String, (String, String)>) {
+    let mut reader = match stream.try_clone() {
+        Ok(s) => BufReader::new(s),
+        Err(_) => return,
+    };
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    // Drain the headers
+    let mut header = String::new();
+    while reader.read_line(&mut header).map(|n| n > 2).unwrap_or(false) {
+        header.clear();
+    }
+
+    let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let path = path.split('?').next().unwrap_or(path);
+    let response = match routes.get(path) {
+        Some((content_type, body)) => format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            content_type,
+            body.len(),
+            body
+        ),
+        None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+    };
+    let _ = stream.write_all(response.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transfer_resume::{TransferSessionStore, TransferStatus};
+
+    #[test]
+    fn test_diff_against_remote_folder() {
+        let local = TempDir::new("local");
+        let remote = TempDir::new("remote");
+        local.write_files(&[("index.html", "<h1>v2</h1>"), ("css/site.css", "body{}"), ("new.js", "1")]);
+        remote.write_files(&[("index.html", "<h1>v1</h1>!"), ("css/site.css", "body{}"), ("old.js", "0")]);
+
+        let local_files = crate::scan_local_files(&local.path_str()).unwrap();
+        let diffs = crate::diff_file_maps(&local_files, &remote_listing(remote.path()), &Default::default());
+        let statuses: Vec<(&str, &str)> = diffs.iter().map(|d| (d.path.as_str(), d.status.as_str())).collect();
+
+        assert_eq!(
+            statuses,
+            vec![
+                ("css/site.css", "unchanged"),
+                ("index.html", "modified"),
+                ("new.js", "added"),
+                ("old.js", "deleted"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ignored_remote_files_are_not_orphans() {
+        let local = TempDir::new("local");
+        let remote = TempDir::new("remote");
+        local.write_files(&[
+            (crate::ignore_rules::IGNORE_FILE_NAME, ".env\nnode_modules/\ndist/\n"),
+            ("index.html", "<h1>v1</h1>"),
+            (".env", "DB_PASSWORD=local"),
+            ("node_modules/dep/index.js", "1"),
+        ]);
+        remote.write_files(&[("index.html", "<h1>v1</h1>"), ("node_modules/dep/index.js", "1"), ("dist/app.js", "2"), ("old.js", "0")]);
+        let mut remote_files = remote_listing(remote.path());
+        // Dotfiles are listed when the scan asks for them
+        remote_files.insert(".env".to_string(), RemoteFile { size: 18, mtime: None });
+
+        let local_files = crate::scan_local_files(&local.path_str()).unwrap();
+        let ignore = crate::ignore_rules::IgnoreRules::load(local.path());
+        let diffs = crate::diff_file_maps(&local_files, &remote_files, &ignore);
+        let deleted: Vec<&str> = diffs.iter().filter(|d| d.status == "deleted").map(|d| d.path.as_str()).collect();
+
+        assert_eq!(deleted, vec!["old.js"]);
+    }
+
+    #[test]
+    fn test_interrupted_session_survives_restart() {
+        let data = TempDir::new("data");
+        let local = TempDir::new("local");
+        local.write_files(&[("a.html", "aaaa"), ("b.html", "bbbb")]);
+
+        let mut store = TransferSessionStore::default();
+        let session_id = store.create_session("project");
+        let session = store.get_session_mut("project").unwrap();
+        for name in ["a.html", "b.html"] {
+            let path = local.path().join(name);
+            session.add_file(name, &path.to_string_lossy(), name, 4);
+        }
+        session.update_progress("a.html", 2);
+        crate::transfer_resume::save_sessions(data.path(), &store).unwrap();
+
+        // b.html changes while the app is closed
+        local.write_files(&[("b.html", "changed")]);
+        let interrupted = crate::transfer_resume::detect_interrupted_syncs(data.path()).unwrap();
+
+        assert_eq!(interrupted.len(), 1);
+        assert_eq!(interrupted[0].session_id, session_id);
+        assert_eq!(interrupted[0].files_remaining, 1);
+        assert_eq!(interrupted[0].bytes_remaining, 2);
+        assert_eq!(interrupted[0].invalid_files, vec!["b.html".to_string()]);
+        let store = crate::transfer_resume::load_sessions(data.path()).unwrap();
+        let file = &store.get_session("project").unwrap().files["b.html"];
+        assert_eq!(file.status, TransferStatus::Invalid);
+    }
+
+    fn upload_diffs(local: &TempDir, remote: &TempDir) -> Vec<crate::FileDiff> {
+        let local_files = crate::scan_local_files(&local.path_str()).unwrap();
+        crate::diff_file_maps(&local_files, &remote_listing(remote.path()), &Default::default())
+    }
+
+    fn files_under(dir: &Path) -> Vec<String> {
+        let mut files: Vec<String> = crate::scan_local_files(&dir.to_string_lossy()).unwrap().into_keys().collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_upload_leaves_remote_in_sync() {
+        let local = TempDir::new("local");
+        let remote = TempDir::new("remote");
+        local.write_files(&[("index.html", "<h1>v2</h1>"), ("css/site.css", "body{}"), ("js/lib/app.js", "run()")]);
+        remote.write_files(&[("index.html", "<h1>v1</h1>!"), ("css/site.css", "body{}")]);
+        let old = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_600_000_000);
+        fs::File::options().write(true).open(local.path().join("js/lib/app.js")).unwrap().set_modified(old).unwrap();
+
+        let diffs = upload_diffs(&local, &remote);
+        let upload = UploadOptions { atomic: true, preserve_permissions: false, preserve_mtime: true };
+        let mut events = Vec::new();
+        let mut server = FolderRemote::new(remote.path());
+        crate::upload_with_progress(&mut server, &local.path_str(), "", &diffs, "e2e-upload", upload, &mut |event| {
+            events.push(event.event)
+        })
+        .unwrap();
+
+        assert!(upload_diffs(&local, &remote).iter().all(|d| d.status == "unchanged"));
+        assert_eq!(files_under(remote.path()), vec!["css/site.css", "index.html", "js/lib/app.js"]);
+        assert_eq!(fs::metadata(remote.path().join("js/lib/app.js")).unwrap().modified().unwrap(), old);
+        let completed = events.iter().filter(|e| **e == crate::events::SyncEventKind::FileComplete).count();
+        assert_eq!(completed, 2);
+    }
+
+    #[test]
+    fn test_failed_atomic_upload_keeps_live_file() {
+        let local = TempDir::new("local");
+        let remote = TempDir::new("remote");
+        local.write_files(&[("index.html", "<h1>nouvelle version</h1>")]);
+        remote.write_files(&[("index.html", "<h1>en ligne</h1>")]);
+
+        let diffs = upload_diffs(&local, &remote);
+        let upload = UploadOptions { atomic: true, ..Default::default() };
+        let mut server = FolderRemote::new(remote.path());
+        server.fail_after = Some(5);
+        let result = crate::upload_with_progress(&mut server, &local.path_str(), "", &diffs, "e2e-atomic", upload, &mut |_| {});
+
+        assert!(result.unwrap_err().starts_with("1 fichier(s) en erreur"));
+        assert_eq!(fs::read_to_string(remote.path().join("index.html")).unwrap(), "<h1>en ligne</h1>");
+        assert_eq!(files_under(remote.path()), vec!["index.html"]);
+    }
+
+    #[test]
+    fn test_resume_interrupted_upload() {
+        let local = TempDir::new("local");
+        let remote = TempDir::new("remote");
+        let content: String = (0..200_000).map(|i| char::from(b'a' + (i % 26) as u8)).collect();
+        local.write_files(&[("big.txt", &content)]);
+        let local_file = local.path().join("big.txt").to_string_lossy().to_string();
+
+        let mut server = FolderRemote::new(remote.path());
+        server.fail_after = Some(70_000);
+        let mut file = fs::File::open(&local_file).unwrap();
+        assert!(server.write_file("/big.txt", false, &mut file).is_err());
+        assert_eq!(server.file_size("/big.txt"), Some(70_000));
+
+        let total = crate::transfer_resume::resume_upload(&mut server, &local_file, "/big.txt", 70_000).unwrap();
+        assert_eq!(total, 200_000);
+        assert_eq!(fs::read_to_string(remote.path().join("big.txt")).unwrap(), content);
+
+        // A stale offset starts over instead of appending at the wrong place
+        server.fail_after = Some(10_000);
+        assert!(server.write_file("/big.txt", false, &mut fs::File::open(&local_file).unwrap()).is_err());
+        let total = crate::transfer_resume::resume_upload(&mut server, &local_file, "/big.txt", 70_000).unwrap();
+        assert_eq!(total, 200_000);
+        assert_eq!(fs::read_to_string(remote.path().join("big.txt")).unwrap(), content);
+    }
+
+    #[test]
+    fn test_scrape_fixture_site() {
+        let site = HttpFixture::serve(&[
+            ("/", "text/html", r#"<html><head><title>Accueil</title><link rel="stylesheet" href="/style.css"></head><body><h1>Bienvenue sur le site</h1><a href="/contact">Contact</a></body></html>"#),
+            ("/contact", "text/html", "<html><head><title>Contact</title></head><body><p>Ecrivez-nous</p></body></html>"),
+            ("/style.css", "text/css", "body { color: #336699; font-family: 'Inter', sans-serif; }"),
+        ]);
+        let output = TempDir::new("scrape");
+
+        let result = crate::scraper::scrape_website(crate::scraper::ScrapeConfig {
+            url: site.url("/"),
+            output_path: output.path_str(),
+            max_pages: Some(10),
+            download_images: false,
+            download_css: true,
+            extract_text: true,
+            proxy: None,
+        })
+        .unwrap();
+
+        let mut titles: Vec<&str> = result.pages.iter().map(|p| p.title.as_str()).collect();
+        titles.sort();
+        assert_eq!(titles, vec!["Accueil", "Contact"]);
+        assert_eq!(result.stylesheets.len(), 1);
+        assert!(result.texts.iter().any(|t| t.content == "Bienvenue sur le site"));
+    }
+}
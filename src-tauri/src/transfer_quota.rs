@@ -0,0 +1,211 @@
+//! Transfer Quota Module
+//!
+//! Per-project monthly transfer quota and per-sync byte cap, for hosts that
+//! bill egress. Uploaded bytes are accumulated per month after each sync,
+//! and a sync that would exceed a limit is reported or refused.
+
+use crate::state_file;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Limits of a project, in bytes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferQuota {
+    pub monthly_bytes: Option<u64>,
+    pub per_sync_bytes: Option<u64>,
+    /// Refuse the sync instead of warning when a limit would be exceeded
+    #[serde(default)]
+    pub block: bool,
+    /// Warn once the month reaches this share of the quota
+    #[serde(default = "default_warn_percent")]
+    pub warn_at_percent: u8,
+}
+
+fn default_warn_percent() -> u8 { 80 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferUsage {
+    /// Uploaded bytes keyed by `YYYY-MM`
+    pub monthly: BTreeMap<String, u64>,
+    pub total_bytes: u64,
+    pub last_sync_bytes: u64,
+    pub last_sync_at: Option<String>,
+}
+
+/// Outcome of checking a planned sync against the quota
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaCheck {
+    pub allowed: bool,
+    pub planned_bytes: u64,
+    pub month_used: u64,
+    pub monthly_limit: Option<u64>,
+    pub per_sync_limit: Option<u64>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct QuotaStore {
+    quotas: HashMap<String, TransferQuota>,
+    usage: HashMap<String, TransferUsage>,
+}
+
+fn store_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("transfer_quotas.json")
+}
+
+fn load_store(app_data_dir: &Path) -> QuotaStore {
+    state_file::read_json(&store_path(app_data_dir)).ok().flatten().unwrap_or_default()
+}
+
+fn save_store(app_data_dir: &Path, store: &QuotaStore) -> Result<(), String> {
+    state_file::write_json(&store_path(app_data_dir), store)
+}
+
+fn current_month() -> String {
+    chrono::Local::now().format("%Y-%m").to_string()
+}
+
+pub fn get_quota(app_data_dir: &Path, project_id: &str) -> Option<TransferQuota> {
+    load_store(app_data_dir).quotas.remove(project_id)
+}
+
+/// Set or remove (None) the quota of a project
+pub fn set_quota(app_data_dir: &Path, project_id: &str, quota: Option<TransferQuota>) -> Result<(), String> {
+    let mut store = load_store(app_data_dir);
+    match quota {
+        Some(quota) => store.quotas.insert(project_id.to_string(), quota),
+        None => store.quotas.remove(project_id),
+    };
+    save_store(app_data_dir, &store)
+}
+
+pub fn get_usage(app_data_dir: &Path, project_id: &str) -> TransferUsage {
+    load_store(app_data_dir).usage.remove(project_id).unwrap_or_default()
+}
+
+/// Check whether uploading `planned_bytes` fits the project's limits
+pub fn check(app_data_dir: &Path, project_id: &str, planned_bytes: u64) -> QuotaCheck {
+    let mut store = load_store(app_data_dir);
+    let month_used = store
+        .usage
+        .get(project_id)
+        .and_then(|usage| usage.monthly.get(&current_month()).copied())
+        .unwrap_or(0);
+    let quota = store.quotas.remove(project_id);
+
+    let mut check = QuotaCheck {
+        allowed: true,
+        planned_bytes,
+        month_used,
+        monthly_limit: quota.as_ref().and_then(|q| q.monthly_bytes),
+        per_sync_limit: quota.as_ref().and_then(|q| q.per_sync_bytes),
+        warnings: Vec::new(),
+    };
+    let quota = match quota {
+        Some(quota) => quota,
+        None => return check,
+    };
+
+    let mut exceeded = false;
+    if let Some(limit) = quota.per_sync_bytes {
+        if planned_bytes > limit {
+            exceeded = true;
+            check.warnings.push(format!(
+                "Cette synchronisation envoie {} pour une limite de {} par synchronisation",
+                format_bytes(planned_bytes),
+                format_bytes(limit)
+            ));
+        }
+    }
+    if let Some(limit) = quota.monthly_bytes {
+        let after = month_used + planned_bytes;
+        if after > limit {
+            exceeded = true;
+            check.warnings.push(format!(
+                "Quota mensuel depasse: {} utilises + {} prevus pour {}",
+                format_bytes(month_used),
+                format_bytes(planned_bytes),
+                format_bytes(limit)
+            ));
+        } else if after * 100 >= limit * quota.warn_at_percent.min(100) as u64 {
+            check.warnings.push(format!(
+                "{}% du quota mensuel atteint apres cette synchronisation ({} / {})",
+                after * 100 / limit.max(1),
+                format_bytes(after),
+                format_bytes(limit)
+            ));
+        }
+    }
+
+    check.allowed = !(exceeded && quota.block);
+    check
+}
+
+/// Add the bytes uploaded by a sync to the project's usage
+pub fn record_transfer(app_data_dir: &Path, project_id: &str, bytes: u64) -> Result<(), String> {
+    let mut store = load_store(app_data_dir);
+    let usage = store.usage.entry(project_id.to_string()).or_default();
+    *usage.monthly.entry(current_month()).or_insert(0) += bytes;
+    usage.total_bytes += bytes;
+    usage.last_sync_bytes = bytes;
+    usage.last_sync_at = Some(chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string());
+    save_store(app_data_dir, &store)
+}
+
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["o", "Ko", "Mo", "Go"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_usage_counts_against_the_monthly_quota() {
+        let dir = TempDir::new("transfer-quota");
+        assert!(check(dir.path(), "shop", u64::MAX).allowed);
+
+        let quota = TransferQuota {
+            monthly_bytes: Some(1000),
+            per_sync_bytes: Some(600),
+            block: false,
+            warn_at_percent: 80,
+        };
+        set_quota(dir.path(), "shop", Some(quota.clone())).unwrap();
+        record_transfer(dir.path(), "shop", 300).unwrap();
+        record_transfer(dir.path(), "shop", 250).unwrap();
+        let usage = get_usage(dir.path(), "shop");
+        assert_eq!((usage.total_bytes, usage.last_sync_bytes), (550, 250));
+
+        assert!(check(dir.path(), "shop", 100).warnings.is_empty());
+        let near = check(dir.path(), "shop", 300);
+        assert!(near.allowed && near.warnings[0].starts_with("85% du quota mensuel"));
+
+        // Over both limits: reported, refused only when the quota blocks
+        let over = check(dir.path(), "shop", 700);
+        assert_eq!((over.allowed, over.month_used, over.warnings.len()), (true, 550, 2));
+        set_quota(dir.path(), "shop", Some(TransferQuota { block: true, ..quota })).unwrap();
+        assert!(!check(dir.path(), "shop", 700).allowed);
+        assert!(check(dir.path(), "blog", 700).allowed);
+
+        set_quota(dir.path(), "shop", None).unwrap();
+        assert!(get_quota(dir.path(), "shop").is_none());
+        assert_eq!(format_bytes(1536), "1.5 Ko");
+    }
+}
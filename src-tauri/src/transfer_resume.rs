@@ -3,6 +3,8 @@
 //! Implements resumable file transfers for interrupted uploads.
 //! Tracks transfer state and uses FTP REST/APPE commands or SFTP seek.
 
+use crate::remote_fs::RemoteFs;
+use crate::state_file;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::{self, File};
@@ -33,6 +35,8 @@ pub enum TransferStatus {
     Paused,
     Completed,
     Failed,
+    /// Local file deleted or changed since the transfer started
+    Invalid,
 }
 
 /// Transfer session for a project sync
@@ -121,10 +125,58 @@ impl TransferSession {
     }
 }
 
+/// Default age after which completed sessions are removed
+pub const DEFAULT_CLEANUP_HOURS: i64 = 24;
+
+fn default_cleanup_hours() -> i64 { DEFAULT_CLEANUP_HOURS }
+
 /// Session storage for persistence
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransferSessionStore {
     pub sessions: HashMap<String, TransferSession>,
+    /// Completed sessions older than this are removed; incomplete ones are flagged stale
+    #[serde(default = "default_cleanup_hours")]
+    pub cleanup_after_hours: i64,
+}
+
+impl Default for TransferSessionStore {
+    fn default() -> Self {
+        Self {
+            sessions: HashMap::new(),
+            cleanup_after_hours: DEFAULT_CLEANUP_HOURS,
+        }
+    }
+}
+
+/// Incomplete sync found at startup that can still be resumed
+#[derive(Debug, Clone, Serialize)]
+pub struct InterruptedSync {
+    pub session_id: String,
+    pub project_id: String,
+    pub started_at: String,
+    /// Files still to transfer whose local copy is unchanged
+    pub files_remaining: usize,
+    pub bytes_remaining: u64,
+    /// Files deleted or modified locally since the interruption
+    pub invalid_files: Vec<String>,
+}
+
+/// Overview of a stored session, for the session browser
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferSessionSummary {
+    pub id: String,
+    pub project_id: String,
+    pub started_at: String,
+    pub last_activity: String,
+    pub age_hours: i64,
+    pub completed: bool,
+    pub files_total: usize,
+    pub files_completed: usize,
+    pub total_bytes: u64,
+    pub transferred_bytes: u64,
+    pub completion_percent: f32,
+    /// Incomplete and inactive for longer than the cleanup window
+    pub stale: bool,
 }
 
 impl TransferSessionStore {
@@ -159,6 +211,108 @@ impl TransferSessionStore {
         }
     }
 
+    /// Summaries of every stored session, most recent first
+    pub fn summaries(&self) -> Vec<TransferSessionSummary> {
+        let now = chrono::Utc::now();
+        let mut summaries: Vec<TransferSessionSummary> = self
+            .sessions
+            .values()
+            .map(|session| {
+                let last_activity = session
+                    .files
+                    .values()
+                    .map(|f| f.updated_at.as_str())
+                    .chain(std::iter::once(session.started_at.as_str()))
+                    .max()
+                    .unwrap_or_default()
+                    .to_string();
+                let hours_since = |date: &str| {
+                    chrono::DateTime::parse_from_rfc3339(date)
+                        .map(|d| (now - d.with_timezone(&chrono::Utc)).num_hours())
+                        .unwrap_or(0)
+                };
+                let total_bytes: u64 = session.files.values().map(|f| f.total_size).sum();
+                let transferred_bytes: u64 = session.files.values().map(|f| f.transferred_bytes).sum();
+                let completion_percent = if session.completed {
+                    100.0
+                } else if total_bytes > 0 {
+                    (transferred_bytes as f32 / total_bytes as f32) * 100.0
+                } else {
+                    0.0
+                };
+
+                TransferSessionSummary {
+                    id: session.id.clone(),
+                    project_id: session.project_id.clone(),
+                    started_at: session.started_at.clone(),
+                    age_hours: hours_since(&session.started_at),
+                    stale: !session.completed && hours_since(&last_activity) > self.cleanup_after_hours,
+                    last_activity,
+                    completed: session.completed,
+                    files_total: session.files.len(),
+                    files_completed: session
+                        .files
+                        .values()
+                        .filter(|f| f.status == TransferStatus::Completed)
+                        .count(),
+                    total_bytes,
+                    transferred_bytes,
+                    completion_percent,
+                }
+            })
+            .collect();
+        summaries.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        summaries
+    }
+
+    /// Check the local files of every incomplete session, mark the ones deleted or
+    /// resized since the interruption as invalid and list the sessions still resumable
+    pub fn validate_incomplete(&mut self) -> Vec<InterruptedSync> {
+        let mut interrupted = Vec::new();
+
+        for session in self.sessions.values_mut().filter(|s| !s.completed) {
+            let mut files_remaining = 0;
+            let mut bytes_remaining = 0;
+            let mut invalid_files = Vec::new();
+
+            for file in session.files.values_mut() {
+                if file.status == TransferStatus::Completed || file.status == TransferStatus::Invalid {
+                    continue;
+                }
+                let unchanged = fs::metadata(&file.local_path)
+                    .map(|m| m.is_file() && m.len() == file.total_size)
+                    .unwrap_or(false);
+                if unchanged {
+                    files_remaining += 1;
+                    bytes_remaining += file.total_size - file.transferred_bytes.min(file.total_size);
+                } else {
+                    file.status = TransferStatus::Invalid;
+                    file.updated_at = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+                    invalid_files.push(file.path.clone());
+                }
+            }
+
+            if files_remaining > 0 {
+                invalid_files.sort();
+                interrupted.push(InterruptedSync {
+                    session_id: session.id.clone(),
+                    project_id: session.project_id.clone(),
+                    started_at: session.started_at.clone(),
+                    files_remaining,
+                    bytes_remaining,
+                    invalid_files,
+                });
+            }
+        }
+
+        interrupted.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        interrupted
+    }
+
+    pub fn delete_session(&mut self, session_id: &str) -> bool {
+        self.sessions.remove(session_id).is_some()
+    }
+
     pub fn cleanup_old_sessions(&mut self, max_age_hours: i64) {
         let cutoff = chrono::Utc::now() - chrono::Duration::hours(max_age_hours);
         self.sessions.retain(|_, s| {
@@ -180,171 +334,216 @@ pub fn get_sessions_path(app_data_dir: &Path) -> PathBuf {
 pub fn load_sessions(app_data_dir: &Path) -> Result<TransferSessionStore, String> {
     let path = get_sessions_path(app_data_dir);
 
-    if !path.exists() {
-        return Ok(TransferSessionStore::default());
-    }
-
-    let content = fs::read_to_string(&path)
-        .map_err(|e| format!("Failed to read sessions file: {}", e))?;
-
-    serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse sessions: {}", e))
+    Ok(state_file::read_json(&path)?.unwrap_or_default())
 }
 
 /// Save transfer sessions to disk
 pub fn save_sessions(app_data_dir: &Path, store: &TransferSessionStore) -> Result<(), String> {
-    let path = get_sessions_path(app_data_dir);
-
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create sessions directory: {}", e))?;
-    }
-
-    let content = serde_json::to_string_pretty(store)
-        .map_err(|e| format!("Failed to serialize sessions: {}", e))?;
+    state_file::write_json(&get_sessions_path(app_data_dir), store)
+}
 
-    fs::write(&path, content)
-        .map_err(|e| format!("Failed to write sessions file: {}", e))
+/// Validate the stored sessions and return the syncs that can be resumed
+pub fn detect_interrupted_syncs(app_data_dir: &Path) -> Result<Vec<InterruptedSync>, String> {
+    let mut store = load_sessions(app_data_dir)?;
+    let interrupted = store.validate_incomplete();
+    save_sessions(app_data_dir, &store)?;
+    Ok(interrupted)
 }
 
-/// Resume SFTP upload from offset
-pub fn resume_sftp_upload(
-    sess: &ssh2::Session,
+/// Upload `local_path` from `offset` on, after the part already on the
+/// server (SFTP seek, FTP APPE); starts over when the remote file isn't
+/// `offset` bytes long
+pub fn resume_upload(
+    remote: &mut dyn RemoteFs,
     local_path: &str,
     remote_path: &str,
     offset: u64,
 ) -> Result<u64, String> {
-    let sftp = sess.sftp().map_err(|e| format!("SFTP error: {}", e))?;
-
-    // Open local file and seek to offset
     let mut local_file = File::open(local_path)
         .map_err(|e| format!("Failed to open local file: {}", e))?;
-    let file_size = local_file
-        .metadata()
-        .map_err(|e| format!("Failed to get file metadata: {}", e))?
-        .len();
 
+    let offset = if offset > 0 && remote.file_size(remote_path) == Some(offset) { offset } else { 0 };
     local_file
         .seek(SeekFrom::Start(offset))
         .map_err(|e| format!("Failed to seek in local file: {}", e))?;
 
-    // Open remote file for appending (or create if it doesn't exist)
-    let mut remote_file = if offset > 0 {
-        // Try to open existing file and seek
-        match sftp.open(Path::new(remote_path)) {
-            Ok(mut f) => {
-                // Verify remote file size matches expected offset
-                if let Ok(stat) = f.stat() {
-                    if let Some(size) = stat.size {
-                        if size != offset {
-                            // Remote file size doesn't match, start over
-                            drop(f);
-                            sftp.create(Path::new(remote_path))
-                                .map_err(|e| format!("Failed to recreate remote file: {}", e))?
-                        } else {
-                            // Seek to end for appending
-                            let _ = f.seek(std::io::SeekFrom::End(0));
-                            f
-                        }
-                    } else {
-                        f
-                    }
-                } else {
-                    f
-                }
-            }
-            Err(_) => {
-                // File doesn't exist, create it
-                sftp.create(Path::new(remote_path))
-                    .map_err(|e| format!("Failed to create remote file: {}", e))?
-            }
-        }
-    } else {
-        // Fresh upload, create file
-        sftp.create(Path::new(remote_path))
-            .map_err(|e| format!("Failed to create remote file: {}", e))?
-    };
+    let written = remote.write_file(remote_path, offset > 0, &mut local_file)?;
+    Ok(offset + written)
+}
 
-    // Transfer remaining data
-    let chunk_size = 65536; // 64KB chunks
-    let mut buffer = vec![0u8; chunk_size];
-    let mut transferred = offset;
+/// Get remote file size for resume calculation
+pub fn get_remote_sftp_size(sess: &ssh2::Session, remote_path: &str) -> Result<u64, String> {
+    let sftp = sess.sftp().map_err(|e| format!("SFTP error: {}", e))?;
 
-    loop {
-        let bytes_read = local_file
-            .read(&mut buffer)
-            .map_err(|e| format!("Failed to read local file: {}", e))?;
+    match sftp.stat(Path::new(remote_path)) {
+        Ok(stat) => Ok(stat.size.unwrap_or(0)),
+        Err(_) => Ok(0), // File doesn't exist
+    }
+}
 
-        if bytes_read == 0 {
-            break;
-        }
+/// Get remote file size for FTP resume calculation
+pub fn get_remote_ftp_size(ftp: &mut suppaftp::FtpStream, remote_path: &str) -> Result<u64, String> {
+    match ftp.size(remote_path) {
+        Ok(size) => Ok(size as u64),
+        Err(_) => Ok(0), // File doesn't exist or command not supported
+    }
+}
 
-        remote_file
-            .write_all(&buffer[..bytes_read])
-            .map_err(|e| format!("Failed to write to remote file: {}", e))?;
+/// Outcome of verifying a partially uploaded file chunk by chunk
+#[derive(Debug, Clone, Serialize)]
+pub struct ChunkRepairResult {
+    pub path: String,
+    pub total_chunks: usize,
+    /// Chunks already on the server with the right content
+    pub verified_chunks: usize,
+    /// Chunks re-sent because they were corrupted or missing
+    pub repaired_chunks: Vec<usize>,
+    pub bytes_resent: u64,
+}
 
-        transferred += bytes_read as u64;
+/// Read up to `buffer.len()` bytes, stopping only at end of stream
+fn read_chunk(reader: &mut impl Read, buffer: &mut [u8]) -> Result<usize, String> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        let read = reader
+            .read(&mut buffer[filled..])
+            .map_err(|e| format!("Failed to read remote file: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        filled += read;
     }
+    Ok(filled)
+}
 
-    Ok(transferred)
+/// Indices of the local chunks that differ from (or are missing in) the remote content
+fn find_bad_chunks(
+    signature: &crate::delta_sync::FileSignature,
+    remote: &mut impl Read,
+) -> Result<Vec<usize>, String> {
+    let mut buffer = vec![0u8; signature.chunk_size];
+    let mut bad = Vec::new();
+    for chunk in &signature.chunk_hashes {
+        let read = read_chunk(remote, &mut buffer)?;
+        if read != chunk.size || crate::delta_sync::compute_hash(&buffer[..read]) != chunk.hash {
+            bad.push(chunk.index);
+        }
+    }
+    Ok(bad)
 }
 
-/// Get remote file size for resume calculation
-pub fn get_remote_sftp_size(sess: &ssh2::Session, remote_path: &str) -> Result<u64, String> {
+/// Verify a partial SFTP upload against the local chunk hashes and re-send only the bad or missing chunks
+pub fn repair_sftp_upload(
+    sess: &ssh2::Session,
+    local_path: &str,
+    remote_path: &str,
+) -> Result<ChunkRepairResult, String> {
+    let signature = crate::delta_sync::generate_file_signature(Path::new(local_path), remote_path)?;
     let sftp = sess.sftp().map_err(|e| format!("SFTP error: {}", e))?;
 
-    match sftp.stat(Path::new(remote_path)) {
-        Ok(stat) => Ok(stat.size.unwrap_or(0)),
-        Err(_) => Ok(0), // File doesn't exist
+    // A missing remote file means every chunk has to be sent
+    let bad_chunks = match sftp.open(Path::new(remote_path)) {
+        Ok(mut remote) => find_bad_chunks(&signature, &mut remote)?,
+        Err(_) => signature.chunk_hashes.iter().map(|c| c.index).collect(),
+    };
+
+    let mut local_file = File::open(local_path)
+        .map_err(|e| format!("Failed to open local file: {}", e))?;
+    let mut remote_file = sftp
+        .open_mode(
+            Path::new(remote_path),
+            ssh2::OpenFlags::WRITE | ssh2::OpenFlags::CREATE,
+            0o644,
+            ssh2::OpenType::File,
+        )
+        .map_err(|e| format!("Failed to open remote file for writing: {}", e))?;
+
+    let mut buffer = vec![0u8; signature.chunk_size];
+    let mut bytes_resent = 0u64;
+    for &index in &bad_chunks {
+        let chunk = &signature.chunk_hashes[index];
+        local_file
+            .seek(SeekFrom::Start(chunk.offset))
+            .map_err(|e| format!("Failed to seek in local file: {}", e))?;
+        local_file
+            .read_exact(&mut buffer[..chunk.size])
+            .map_err(|e| format!("Failed to read local file: {}", e))?;
+        remote_file
+            .seek(SeekFrom::Start(chunk.offset))
+            .map_err(|e| format!("Failed to seek in remote file: {}", e))?;
+        remote_file
+            .write_all(&buffer[..chunk.size])
+            .map_err(|e| format!("Failed to write to remote file: {}", e))?;
+        bytes_resent += chunk.size as u64;
     }
+    drop(remote_file);
+
+    // Drop any trailing data left by an earlier, larger version
+    let stat = ssh2::FileStat {
+        size: Some(signature.total_size),
+        uid: None,
+        gid: None,
+        perm: None,
+        atime: None,
+        mtime: None,
+    };
+    sftp.setstat(Path::new(remote_path), stat)
+        .map_err(|e| format!("Failed to truncate remote file: {}", e))?;
+
+    Ok(ChunkRepairResult {
+        path: remote_path.to_string(),
+        total_chunks: signature.chunk_hashes.len(),
+        verified_chunks: signature.chunk_hashes.len() - bad_chunks.len(),
+        repaired_chunks: bad_chunks,
+        bytes_resent,
+    })
 }
 
-/// Resume FTP upload using REST command
-pub fn resume_ftp_upload(
+/// FTP cannot write in the middle of a file: re-send from the first bad chunk with REST + STOR
+pub fn repair_ftp_upload(
     ftp: &mut suppaftp::FtpStream,
     local_path: &str,
     remote_path: &str,
-    offset: u64,
-) -> Result<u64, String> {
-    // Open local file and seek to offset
+) -> Result<ChunkRepairResult, String> {
+    let signature = crate::delta_sync::generate_file_signature(Path::new(local_path), remote_path)?;
+
+    let bad_chunks = match ftp.retr_as_buffer(remote_path) {
+        Ok(mut remote) => find_bad_chunks(&signature, &mut remote)?,
+        Err(_) => signature.chunk_hashes.iter().map(|c| c.index).collect(),
+    };
+    let first_bad = match bad_chunks.first() {
+        Some(&index) => index,
+        None => {
+            return Ok(ChunkRepairResult {
+                path: remote_path.to_string(),
+                total_chunks: signature.chunk_hashes.len(),
+                verified_chunks: signature.chunk_hashes.len(),
+                repaired_chunks: Vec::new(),
+                bytes_resent: 0,
+            })
+        }
+    };
+
+    let offset = signature.chunk_hashes[first_bad].offset;
     let mut local_file = File::open(local_path)
         .map_err(|e| format!("Failed to open local file: {}", e))?;
-
     local_file
         .seek(SeekFrom::Start(offset))
         .map_err(|e| format!("Failed to seek in local file: {}", e))?;
 
-    // Read remaining content
-    let mut buffer = Vec::new();
-    local_file
-        .read_to_end(&mut buffer)
-        .map_err(|e| format!("Failed to read local file: {}", e))?;
-
     if offset > 0 {
-        // Use APPE (append) command for resuming
-        let cursor = std::io::Cursor::new(buffer);
-        ftp.append_file(remote_path, &mut cursor.clone())
-            .map_err(|e| format!("Failed to append to remote file: {}", e))?;
-    } else {
-        // Fresh upload
-        let cursor = std::io::Cursor::new(buffer);
-        ftp.put_file(remote_path, &mut cursor.clone())
-            .map_err(|e| format!("Failed to upload remote file: {}", e))?;
-    }
-
-    // Get final file size
-    let file_size = local_file
-        .metadata()
-        .map_err(|e| format!("Failed to get file metadata: {}", e))?
-        .len();
-
-    Ok(file_size)
-}
-
-/// Get remote file size for FTP resume calculation
-pub fn get_remote_ftp_size(ftp: &mut suppaftp::FtpStream, remote_path: &str) -> Result<u64, String> {
-    match ftp.size(remote_path) {
-        Ok(size) => Ok(size as u64),
-        Err(_) => Ok(0), // File doesn't exist or command not supported
+        ftp.resume_transfer(offset as usize)
+            .map_err(|e| format!("Failed to set restart offset: {}", e))?;
     }
+    ftp.put_file(remote_path, &mut local_file)
+        .map_err(|e| format!("Failed to upload remote file: {}", e))?;
+
+    let repaired: Vec<usize> = (first_bad..signature.chunk_hashes.len()).collect();
+    Ok(ChunkRepairResult {
+        path: remote_path.to_string(),
+        total_chunks: signature.chunk_hashes.len(),
+        verified_chunks: first_bad,
+        repaired_chunks: repaired,
+        bytes_resent: signature.total_size - offset,
+    })
 }
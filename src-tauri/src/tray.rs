@@ -1,3 +1,4 @@
+use crate::notifications;
 use tauri::{
     AppHandle, CustomMenuItem, Manager, SystemTray, SystemTrayEvent, SystemTrayMenu,
     SystemTrayMenuItem, SystemTraySubmenu,
@@ -33,6 +34,10 @@ fn create_tray_menu(recent_projects: Vec<RecentProject>) -> SystemTrayMenu {
             } else {
                 project.name.clone()
             };
+            let label = match notifications::badge(project.color.as_deref(), project.emoji.as_deref()) {
+                Some(badge) => format!("{} {}", badge, label),
+                None => label,
+            };
 
             // Create submenu for each project
             let mut submenu = SystemTrayMenu::new();
@@ -142,7 +147,9 @@ pub fn handle_tray_event(app: &AppHandle, event: SystemTrayEvent) {
                 id if id.starts_with("sync:") => {
                     // Extract project ID and emit sync event
                     let project_id = id.strip_prefix("sync:").unwrap_or("");
-                    let _ = app.emit_all("tray:sync-project", project_id);
+                    if crate::deploy_guard::allow_triggered_sync(app, project_id, "tray") {
+                        let _ = app.emit_all("tray:sync-project", project_id);
+                    }
 
                     // Also show the window
                     if let Some(window) = app.get_window("main") {
@@ -170,11 +177,24 @@ pub struct RecentProject {
     pub has_active_timer: bool,
     #[serde(rename = "isTimerPaused")]
     pub is_timer_paused: bool,
+    /// Identity color (hex), shown as a colored dot when there is no emoji
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub emoji: Option<String>,
 }
 
 /// Update the tray menu with recent projects
 #[tauri::command]
 pub fn tray_update_recent_projects(app: AppHandle, projects: Vec<RecentProject>) -> Result<(), String> {
+    // Remember the identities for the notifications
+    for project in &projects {
+        notifications::set_identity(&project.id, notifications::ProjectIdentity {
+            name: project.name.clone(),
+            color: project.color.clone(),
+            emoji: project.emoji.clone(),
+        });
+    }
     let menu = create_tray_menu(projects);
     app.tray_handle()
         .set_menu(menu)
@@ -0,0 +1,361 @@
+//! Two-Way Sync Module
+//!
+//! Bidirectional sync between the project folder and an SFTP/FTP target.
+//! Both sides are compared with the state recorded after the last sync (the
+//! delta cache signatures): a side changed if its file differs from that
+//! state. Changes on one side only are copied to the other; files changed
+//! on both sides are conflicts, left untouched until `resolve_conflicts`
+//! applies a strategy per file. The server only gives sizes, so a remote
+//! edit that keeps the exact size goes unnoticed.
+
+use crate::delta_sync::{self, SignatureCache};
+use crate::pull_sync::RemoteEndpoint;
+use crate::{is_cancelled, scan_local_files, RemoteFile, RemoteScanContext, SFTPConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TwoWayAction {
+    /// Changed locally only
+    Upload,
+    /// Changed on the server only
+    Download,
+    /// Deleted locally, unchanged on the server
+    DeleteRemote,
+    /// Deleted on the server, unchanged locally
+    DeleteLocal,
+    /// Deleted on both sides, only the last-synced state remains
+    Forget,
+    Conflict,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TwoWayEntry {
+    pub path: String,
+    pub action: TwoWayAction,
+    #[serde(rename = "localSize")]
+    pub local_size: Option<u64>,
+    #[serde(rename = "remoteSize")]
+    pub remote_size: Option<u64>,
+    /// Conflicts only: "both-modified", "modified-deleted", "deleted-modified", "both-added"
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct TwoWayPlan {
+    pub entries: Vec<TwoWayEntry>,
+    pub unchanged: usize,
+}
+
+impl TwoWayPlan {
+    pub fn conflicts(&self) -> impl Iterator<Item = &TwoWayEntry> {
+        self.entries.iter().filter(|e| e.action == TwoWayAction::Conflict)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictStrategy {
+    KeepLocal,
+    KeepRemote,
+    /// Keep the local file and save the server version next to it on both sides
+    KeepBoth,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConflictResolution {
+    pub path: String,
+    pub strategy: ConflictStrategy,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct TwoWayResult {
+    pub uploaded: Vec<String>,
+    pub downloaded: Vec<String>,
+    pub deleted_remote: Vec<String>,
+    pub deleted_local: Vec<String>,
+    /// Copies of the server version created by keep-both
+    pub conflict_copies: Vec<String>,
+    /// Conflicts still waiting for a resolution
+    pub conflicts: Vec<TwoWayEntry>,
+    pub failed: Vec<String>,
+}
+
+/// Hash of a local file, skipped when its size and mtime match the last-synced signature
+fn local_changed(local_path: &str, relative: &str, cache: &SignatureCache) -> bool {
+    let signature = match cache.get_signature(relative) {
+        Some(signature) => signature,
+        None => return true,
+    };
+    let file = Path::new(local_path).join(relative);
+    if delta_sync::signature_is_current(signature, &file) {
+        return false;
+    }
+    match fs::read(&file) {
+        Ok(contents) => delta_sync::compute_hash(&contents) != signature.full_hash,
+        Err(_) => true,
+    }
+}
+
+/// Compare both sides with the last-synced state
+pub fn classify(
+    local_path: &str,
+    local_files: &HashMap<String, u64>,
+    remote_files: &HashMap<String, RemoteFile>,
+    cache: &SignatureCache,
+) -> TwoWayPlan {
+    let paths: BTreeSet<&String> = local_files
+        .keys()
+        .chain(remote_files.keys())
+        .chain(cache.signatures.keys())
+        .collect();
+
+    let mut plan = TwoWayPlan::default();
+    for path in paths {
+        let local_size = local_files.get(path).copied();
+        let remote_size = remote_files.get(path).map(|r| r.size);
+        let base = cache.get_signature(path);
+
+        let local_modified = local_size.is_some() && local_changed(local_path, path, cache);
+        let remote_modified = match (remote_size, base) {
+            (Some(size), Some(base)) => size != base.total_size,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        let local_deleted = local_size.is_none() && base.is_some();
+        let remote_deleted = remote_size.is_none() && base.is_some();
+
+        let (action, reason) = match (local_modified, local_deleted, remote_modified, remote_deleted) {
+            // Never synced and present on both sides with the same size: treated as identical
+            (true, _, true, _) if base.is_none() && local_size == remote_size => (None, None),
+            (true, _, true, _) if base.is_none() => (Some(TwoWayAction::Conflict), Some("both-added")),
+            (true, _, true, _) => (Some(TwoWayAction::Conflict), Some("both-modified")),
+            (true, _, _, true) => (Some(TwoWayAction::Conflict), Some("modified-deleted")),
+            (_, true, true, _) => (Some(TwoWayAction::Conflict), Some("deleted-modified")),
+            (_, true, _, true) => (Some(TwoWayAction::Forget), None),
+            (true, _, false, false) => (Some(TwoWayAction::Upload), None),
+            (_, true, false, false) => (Some(TwoWayAction::DeleteRemote), None),
+            (false, false, true, _) => (Some(TwoWayAction::Download), None),
+            (false, false, _, true) => (Some(TwoWayAction::DeleteLocal), None),
+            _ => (None, None),
+        };
+        match action {
+            Some(action) => plan.entries.push(TwoWayEntry {
+                path: path.clone(),
+                action,
+                local_size,
+                remote_size,
+                reason: reason.map(String::from),
+            }),
+            None => plan.unchanged += 1,
+        }
+    }
+    plan
+}
+
+/// Scan both sides of a project and classify every file
+pub fn plan(
+    app_data_dir: &Path,
+    local_path: &str,
+    config: &SFTPConfig,
+    project_id: &str,
+    app_handle: &tauri::AppHandle,
+) -> Result<TwoWayPlan, String> {
+    let local_files = scan_local_files(local_path)?;
+    let mut scan = RemoteScanContext::new(project_id, app_handle);
+    let remote_files = match config.protocol.as_deref().unwrap_or("ftp") {
+        "sftp" => crate::scan_sftp_remote_files(config, &config.remote_path, &mut scan)?,
+        "ftp" | "ftps" => crate::scan_ftp_remote_files(config, &config.remote_path, &mut scan)?,
+        other => return Err(format!("La synchronisation bidirectionnelle n'est pas disponible en {}", other)),
+    };
+    let cache = delta_sync::load_cache(app_data_dir, project_id)?;
+    Ok(classify(local_path, &local_files, &remote_files, &cache))
+}
+
+/// Name of the server version kept by keep-both: "page.html" -> "page.serveur.html"
+fn conflict_copy_path(path: &str) -> String {
+    let (dir, name) = match path.rsplit_once('/') {
+        Some((dir, name)) => (format!("{}/", dir), name),
+        None => (String::new(), path),
+    };
+    match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => format!("{}{}.serveur.{}", dir, stem, extension),
+        _ => format!("{}{}.serveur", dir, name),
+    }
+}
+
+/// Copy one file in the direction of `action` and record it in `result`
+fn apply_action(
+    remote: &mut RemoteEndpoint,
+    local_path: &str,
+    remote_base: &str,
+    path: &str,
+    action: &TwoWayAction,
+    result: &mut TwoWayResult,
+) -> Result<(), String> {
+    let local_file = Path::new(local_path).join(path);
+    let remote_file = format!("{}/{}", remote_base, path);
+    match action {
+        TwoWayAction::Upload => {
+            remote.upload(&local_file, remote_base, path)?;
+            result.uploaded.push(path.to_string());
+        }
+        TwoWayAction::Download => {
+            remote.download_replacing(&remote_file, &local_file)?;
+            result.downloaded.push(path.to_string());
+        }
+        TwoWayAction::DeleteRemote => {
+            remote.remove(&remote_file)?;
+            result.deleted_remote.push(path.to_string());
+        }
+        TwoWayAction::DeleteLocal => {
+            fs::remove_file(&local_file).map_err(|e| format!("Failed to delete {}: {}", local_file.display(), e))?;
+            result.deleted_local.push(path.to_string());
+        }
+        TwoWayAction::Forget | TwoWayAction::Conflict => {}
+    }
+    Ok(())
+}
+
+/// Paths whose last-synced state has to be refreshed after `result`
+fn synced_paths(result: &TwoWayResult, forgotten: &[String]) -> Vec<String> {
+    result
+        .uploaded
+        .iter()
+        .chain(&result.downloaded)
+        .chain(&result.deleted_remote)
+        .chain(&result.deleted_local)
+        .chain(&result.conflict_copies)
+        .chain(forgotten)
+        .cloned()
+        .collect()
+}
+
+/// Apply the non-conflicting actions of a fresh plan
+pub fn sync(
+    app_data_dir: &Path,
+    local_path: &str,
+    config: &SFTPConfig,
+    project_id: &str,
+    app_handle: &tauri::AppHandle,
+) -> Result<TwoWayResult, String> {
+    let plan = plan(app_data_dir, local_path, config, project_id, app_handle)?;
+    let mut result = TwoWayResult {
+        conflicts: plan.conflicts().cloned().collect(),
+        ..Default::default()
+    };
+    let forgotten: Vec<String> = plan
+        .entries
+        .iter()
+        .filter(|e| e.action == TwoWayAction::Forget)
+        .map(|e| e.path.clone())
+        .collect();
+    let to_apply: Vec<&TwoWayEntry> = plan
+        .entries
+        .iter()
+        .filter(|e| !matches!(e.action, TwoWayAction::Conflict | TwoWayAction::Forget))
+        .collect();
+
+    if !to_apply.is_empty() {
+        let mut remote = RemoteEndpoint::connect(config)?;
+        let remote_base = config.remote_path.trim_end_matches('/');
+        for entry in to_apply {
+            if is_cancelled(project_id) {
+                break;
+            }
+            if let Err(e) = apply_action(&mut remote, local_path, remote_base, &entry.path, &entry.action, &mut result) {
+                result.failed.push(format!("{}: {}", entry.path, e));
+            }
+        }
+        remote.close();
+    }
+
+    delta_sync::update_cache_after_sync(app_data_dir, project_id, local_path, &synced_paths(&result, &forgotten))?;
+    println!(
+        "[TwoWay] {}: {} up, {} down, {} conflict(s), {} failed",
+        project_id,
+        result.uploaded.len(),
+        result.downloaded.len(),
+        result.conflicts.len(),
+        result.failed.len()
+    );
+    Ok(result)
+}
+
+/// Apply a strategy to conflicts of a fresh plan; other paths are refused
+pub fn resolve_conflicts(
+    app_data_dir: &Path,
+    local_path: &str,
+    config: &SFTPConfig,
+    project_id: &str,
+    resolutions: &[ConflictResolution],
+    app_handle: &tauri::AppHandle,
+) -> Result<TwoWayResult, String> {
+    let plan = plan(app_data_dir, local_path, config, project_id, app_handle)?;
+    let conflicts: HashMap<&str, &TwoWayEntry> = plan.conflicts().map(|e| (e.path.as_str(), e)).collect();
+
+    let mut result = TwoWayResult::default();
+    let mut remote = RemoteEndpoint::connect(config)?;
+    let remote_base = config.remote_path.trim_end_matches('/');
+    for resolution in resolutions {
+        let conflict = match conflicts.get(resolution.path.as_str()) {
+            Some(conflict) => conflict,
+            None => {
+                result.failed.push(format!("{}: n'est plus en conflit", resolution.path));
+                continue;
+            }
+        };
+        let has_local = conflict.local_size.is_some();
+        let has_remote = conflict.remote_size.is_some();
+        let applied = match resolution.strategy {
+            ConflictStrategy::KeepLocal if has_local => TwoWayAction::Upload,
+            ConflictStrategy::KeepLocal => TwoWayAction::DeleteRemote,
+            ConflictStrategy::KeepRemote if has_remote => TwoWayAction::Download,
+            ConflictStrategy::KeepRemote => TwoWayAction::DeleteLocal,
+            // Only one version exists: restore it on the other side
+            ConflictStrategy::KeepBoth if !has_remote => TwoWayAction::Upload,
+            ConflictStrategy::KeepBoth if !has_local => TwoWayAction::Download,
+            ConflictStrategy::KeepBoth => {
+                let copy = conflict_copy_path(&conflict.path);
+                let copied = remote
+                    .download_replacing(&format!("{}/{}", remote_base, conflict.path), &Path::new(local_path).join(&copy))
+                    .and_then(|_| remote.upload(&Path::new(local_path).join(&copy), remote_base, &copy));
+                match copied {
+                    Ok(()) => result.conflict_copies.push(copy),
+                    Err(e) => {
+                        result.failed.push(format!("{}: {}", conflict.path, e));
+                        continue;
+                    }
+                }
+                TwoWayAction::Upload
+            }
+        };
+        if let Err(e) = apply_action(&mut remote, local_path, remote_base, &conflict.path, &applied, &mut result) {
+            result.failed.push(format!("{}: {}", conflict.path, e));
+        }
+    }
+    remote.close();
+
+    delta_sync::update_cache_after_sync(app_data_dir, project_id, local_path, &synced_paths(&result, &[]))?;
+    result.conflicts = plan
+        .conflicts()
+        .filter(|c| !resolutions.iter().any(|r| r.path == c.path))
+        .cloned()
+        .collect();
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conflict_copy_keeps_extension() {
+        assert_eq!(conflict_copy_path("css/style.css"), "css/style.serveur.css");
+        assert_eq!(conflict_copy_path("README"), "README.serveur");
+        assert_eq!(conflict_copy_path(".htaccess"), ".htaccess.serveur");
+    }
+}
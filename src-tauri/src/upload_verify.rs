@@ -0,0 +1,201 @@
+//! Upload Verify Module
+//!
+//! Sampling check of a finished upload for syncs too large to read back
+//! entirely: every uploaded file above a size threshold, plus a random share
+//! of the others, is downloaded again and its SHA-256 compared with the
+//! local file. The size of the random sample gives a confidence figure for
+//! the files that weren't checked.
+
+use crate::pull_sync::RemoteEndpoint;
+use crate::version_history::compute_file_hash;
+use crate::webdav::WebDavClient;
+use crate::{FileDiff, SFTPConfig};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::path::Path;
+
+/// Files above this size (bytes) are always checked when `verify_size_threshold` isn't set
+pub const DEFAULT_SIZE_THRESHOLD: u64 = 20 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SampleVerification {
+    /// Files uploaded by the sync
+    pub uploaded: usize,
+    /// Files checked because they are above the size threshold
+    pub large_checked: usize,
+    /// Files checked from the random sample
+    pub sampled: usize,
+    pub bytes_checked: u64,
+    /// Files whose remote copy differs from the local one
+    pub mismatched: Vec<String>,
+    /// Files that couldn't be read back (not counted as checked)
+    pub unreadable: Vec<String>,
+    /// Share of the unchecked files estimated intact, at 95% (1.0 when all were checked)
+    pub confidence: f64,
+}
+
+/// Split the uploaded files into the large ones (all checked) and a random `percent` of the others
+fn select_sample<'a>(uploaded: &[&'a FileDiff], percent: f64, size_threshold: u64) -> (Vec<&'a FileDiff>, Vec<&'a FileDiff>, usize) {
+    let (large, mut small): (Vec<&FileDiff>, Vec<&FileDiff>) = uploaded
+        .iter()
+        .copied()
+        .partition(|d| d.local_size.unwrap_or(0) > size_threshold);
+    let population = small.len();
+    let percent = percent.clamp(0.0, 100.0);
+    let wanted = match (population as f64 * percent / 100.0).ceil() as usize {
+        0 if percent > 0.0 && population > 0 => 1,
+        count => count.min(population),
+    };
+    // A randomly keyed hasher gives a fresh order on every sync
+    let state = RandomState::new();
+    small.sort_by_key(|d| state.hash_one(&d.path));
+    small.truncate(wanted);
+    (large, small, population)
+}
+
+/// Lower bound of the intact share among `population` files after checking `sampled` of them
+fn confidence(population: usize, sampled: usize, mismatches: usize) -> f64 {
+    if sampled >= population {
+        return 1.0;
+    }
+    if sampled == 0 {
+        return 0.0;
+    }
+    let k = sampled as f64;
+    let upper = if mismatches == 0 {
+        // Rule of three: no failure in k checks bounds the failure rate under 3/k
+        3.0 / k
+    } else {
+        let rate = mismatches as f64 / k;
+        rate + 1.96 * (rate * (1.0 - rate) / k).sqrt()
+    };
+    (1.0 - upper).clamp(0.0, 1.0)
+}
+
+enum Reader {
+    Dav(WebDavClient),
+    Endpoint(RemoteEndpoint),
+}
+
+impl Reader {
+    fn remote_hash(&mut self, remote_file: &str) -> Result<(String, u64), String> {
+        let mut hasher = Sha256::new();
+        let bytes = match self {
+            Reader::Dav(client) => client.download(remote_file, &mut hasher)?,
+            Reader::Endpoint(remote) => remote.download_to(remote_file, &mut hasher)?,
+        };
+        Ok((format!("{:x}", hasher.finalize()), bytes))
+    }
+}
+
+/// Read back a sample of the uploaded files of `diffs` and compare their hashes
+pub fn verify_uploads(
+    local_path: &str,
+    config: &SFTPConfig,
+    diffs: &[FileDiff],
+    percent: f64,
+    size_threshold: u64,
+    on_file: impl Fn(&str, usize, usize),
+) -> Result<SampleVerification, String> {
+    let uploaded: Vec<&FileDiff> = diffs
+        .iter()
+        .filter(|d| d.status == "added" || d.status == "modified")
+        .collect();
+    let (large, sample, population) = select_sample(&uploaded, percent, size_threshold);
+
+    let mut result = SampleVerification {
+        uploaded: uploaded.len(),
+        large_checked: 0,
+        sampled: 0,
+        bytes_checked: 0,
+        mismatched: Vec::new(),
+        unreadable: Vec::new(),
+        confidence: 1.0,
+    };
+    let to_check: Vec<(&FileDiff, bool)> = large
+        .iter()
+        .map(|d| (*d, true))
+        .chain(sample.iter().map(|d| (*d, false)))
+        .collect();
+    if to_check.is_empty() {
+        result.confidence = confidence(population, 0, 0);
+        return Ok(result);
+    }
+
+    let mut reader = match config.protocol.as_deref() {
+        Some("webdav") => Reader::Dav(WebDavClient::connect(config)?),
+        _ => Reader::Endpoint(RemoteEndpoint::connect(config)?),
+    };
+    let remote_base = config.remote_path.trim_end_matches('/');
+    let mut sample_mismatches = 0;
+
+    for (index, (diff, is_large)) in to_check.iter().enumerate() {
+        on_file(&diff.path, index + 1, to_check.len());
+        let local_hash = compute_file_hash(&Path::new(local_path).join(&diff.path));
+        let remote_hash = reader.remote_hash(&format!("{}/{}", remote_base, diff.path));
+        match (local_hash, remote_hash) {
+            (Ok(local), Ok((remote, bytes))) => {
+                if *is_large {
+                    result.large_checked += 1;
+                } else {
+                    result.sampled += 1;
+                }
+                result.bytes_checked += bytes;
+                if local != remote {
+                    println!("[Verify] {} differs from the local file", diff.path);
+                    result.mismatched.push(diff.path.clone());
+                    if !is_large {
+                        sample_mismatches += 1;
+                    }
+                }
+            }
+            (Err(e), _) | (_, Err(e)) => result.unreadable.push(format!("{}: {}", diff.path, e)),
+        }
+    }
+    if let Reader::Endpoint(remote) = reader {
+        remote.close();
+    }
+
+    result.confidence = confidence(population, result.sampled, sample_mismatches);
+    println!(
+        "[Verify] Checked {} large + {} sampled file(s) of {}, {} mismatch(es), confidence {:.1}%",
+        result.large_checked,
+        result.sampled,
+        result.uploaded,
+        result.mismatched.len(),
+        result.confidence * 100.0
+    );
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(path: &str, size: u64) -> FileDiff {
+        FileDiff {
+            path: path.to_string(),
+            status: "added".to_string(),
+            local_size: Some(size),
+            remote_size: None,
+        }
+    }
+
+    #[test]
+    fn test_sample_keeps_large_files_and_percentage() {
+        let diffs: Vec<FileDiff> = (0..200).map(|i| diff(&format!("f{}.html", i), 100)).chain([diff("video.mp4", 500)]).collect();
+        let refs: Vec<&FileDiff> = diffs.iter().collect();
+        let (large, sample, population) = select_sample(&refs, 5.0, 400);
+        assert_eq!(large.len(), 1);
+        assert_eq!(large[0].path, "video.mp4");
+        assert_eq!(population, 200);
+        assert_eq!(sample.len(), 10);
+        assert_eq!(select_sample(&refs, 0.1, 400).1.len(), 1);
+
+        assert_eq!(confidence(200, 200, 0), 1.0);
+        assert!((confidence(200, 100, 0) - 0.97).abs() < 1e-9);
+        assert_eq!(confidence(200, 0, 0), 0.0);
+    }
+}
@@ -3,14 +3,20 @@
 //! Tracks file versions before each sync, enabling rollback functionality.
 //! Stores file metadata (hash, size, timestamp) and optionally file backups.
 
+use crate::ignore_rules::IgnoreRules;
+use crate::state_file;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 use uuid::Uuid;
 
+/// Inspection folders older than this are removed
+pub const INSPECTION_MAX_AGE: Duration = Duration::from_secs(24 * 3600);
+
 /// Version entry for a single file
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileVersion {
@@ -133,8 +139,10 @@ pub fn create_snapshot(
     let mut total_size = 0u64;
 
     // Walk directory and collect file info
+    let ignore = IgnoreRules::load(base_path);
     for entry in walkdir::WalkDir::new(local_path)
         .into_iter()
+        .filter_entry(|e| ignore.keeps_entry(base_path, e))
         .filter_map(|e| e.ok())
     {
         let path = entry.path();
@@ -309,6 +317,70 @@ pub fn restore_snapshot(
     Ok(restored)
 }
 
+/// Throwaway copy of snapshot files, outside of the working tree
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectionFolder {
+    pub path: String,
+    pub snapshot_id: String,
+    /// Relative paths extracted
+    pub files: Vec<String>,
+}
+
+/// Parent of the inspection folders, in the system temporary directory
+pub fn inspection_root() -> PathBuf {
+    std::env::temp_dir().join("laforge-inspect")
+}
+
+/// Extract `files` of a snapshot into a new inspection folder, nothing in the
+/// project is touched. Old inspection folders are cleaned on the way.
+pub fn restore_for_inspection(snapshot: &SyncSnapshot, files: Vec<String>) -> Result<InspectionFolder, String> {
+    clean_inspection_folders(INSPECTION_MAX_AGE);
+    let name: String = format!(
+        "{}-{}-{}-{}",
+        snapshot.project_id,
+        snapshot.id.chars().take(8).collect::<String>(),
+        chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        &Uuid::new_v4().simple().to_string()[..4]
+    )
+    .chars()
+    .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+    .collect();
+    let folder = inspection_root().join(name);
+    let folder_str = folder.to_string_lossy().to_string();
+
+    let restored = restore_snapshot(snapshot, &folder_str, Some(files))?;
+    if restored.is_empty() {
+        let _ = fs::remove_dir_all(&folder);
+        return Err("Aucun des fichiers demandes n'a de sauvegarde dans ce snapshot".to_string());
+    }
+    Ok(InspectionFolder { path: folder_str, snapshot_id: snapshot.id.clone(), files: restored })
+}
+
+/// Remove inspection folders older than `max_age`, returns how many were removed
+pub fn clean_inspection_folders(max_age: Duration) -> usize {
+    let entries = match fs::read_dir(inspection_root()) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let age = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .unwrap_or_default();
+        if age >= max_age && fs::remove_dir_all(entry.path()).is_ok() {
+            removed += 1;
+        }
+    }
+    if removed > 0 {
+        println!("[VersionHistory] Removed {} inspection folder(s)", removed);
+    }
+    removed
+}
+
 /// Get the version history storage path for a project
 pub fn get_history_path(app_data_dir: &Path, project_id: &str) -> PathBuf {
     app_data_dir
@@ -320,32 +392,13 @@ pub fn get_history_path(app_data_dir: &Path, project_id: &str) -> PathBuf {
 pub fn load_history(app_data_dir: &Path, project_id: &str) -> Result<ProjectVersionHistory, String> {
     let path = get_history_path(app_data_dir, project_id);
 
-    if !path.exists() {
-        return Ok(ProjectVersionHistory::new(project_id.to_string()));
-    }
-
-    let content = fs::read_to_string(&path)
-        .map_err(|e| format!("Failed to read history file: {}", e))?;
-
-    serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse history: {}", e))
+    Ok(state_file::read_json(&path)?
+        .unwrap_or_else(|| ProjectVersionHistory::new(project_id.to_string())))
 }
 
 /// Save version history for a project
 pub fn save_history(app_data_dir: &Path, history: &ProjectVersionHistory) -> Result<(), String> {
-    let path = get_history_path(app_data_dir, &history.project_id);
-
-    // Ensure directory exists
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create history directory: {}", e))?;
-    }
-
-    let content = serde_json::to_string_pretty(history)
-        .map_err(|e| format!("Failed to serialize history: {}", e))?;
-
-    fs::write(&path, content)
-        .map_err(|e| format!("Failed to write history file: {}", e))
+    state_file::write_json(&get_history_path(app_data_dir, &history.project_id), history)
 }
 
 /// Get backup directory path for a project
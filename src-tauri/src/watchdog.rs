@@ -0,0 +1,423 @@
+//! Watchdog Module
+//!
+//! Internal health check run every minute. A sync or full scrape without
+//! progress for `stuck_minutes` is asked to cancel; if it is still silent
+//! one period later it is marked failed and its sync lock released, so the
+//! project can sync again. A scheduler thread that stopped ticking (or died
+//! holding its state) is restarted, and cancel flags left set after their
+//! job ended are cleared so they can't stop the next one. Every incident is
+//! logged, emitted on "watchdog-incident" (shown by the log monitor) and, for
+//! the ones needing attention, raised as a notification.
+
+use crate::{activity_feed, notifications, scheduler, sync_lock};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::Manager;
+
+pub const DEFAULT_STUCK_MINUTES: u32 = 15;
+const MIN_STUCK_MINUTES: u32 = 2;
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// The scheduler ticks every minute
+const SCHEDULER_STALL_SECS: u64 = 5 * 60;
+const MAX_INCIDENTS: usize = 50;
+/// Sync lock holders that don't check cancellation, so have no heartbeat
+const UNWATCHED_TRIGGERS: [&str; 1] = ["restore-trash"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Incident {
+    pub id: String,
+    /// "stuck_sync", "stuck_scrape", "scheduler_stalled" or "leaked_cancel_flag"
+    pub kind: String,
+    pub project_id: Option<String>,
+    pub message: String,
+    /// Recovery applied
+    pub action: String,
+    pub detected_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchdogReport {
+    pub stuck_minutes: u32,
+    pub scheduler: scheduler::SchedulerHealth,
+    pub active_syncs: usize,
+    pub active_scrapes: usize,
+    /// Most recent first
+    pub incidents: Vec<Incident>,
+}
+
+type Time = chrono::DateTime<chrono::Utc>;
+
+struct WatchdogState {
+    running: bool,
+    stuck_minutes: u32,
+    /// Last progress by "kind:project"
+    beats: HashMap<String, Time>,
+    /// Scrapes running, by project (syncs are known from their lock)
+    scrapes: HashMap<String, Time>,
+    /// Stuck jobs asked to cancel, and when
+    cancel_requested: HashMap<String, Time>,
+    /// Cancel flags seen without a job at the previous check
+    orphan_flags: HashSet<String>,
+    incidents: VecDeque<Incident>,
+}
+
+static STATE: Lazy<Mutex<WatchdogState>> = Lazy::new(|| {
+    Mutex::new(WatchdogState {
+        running: false,
+        stuck_minutes: DEFAULT_STUCK_MINUTES,
+        beats: HashMap::new(),
+        scrapes: HashMap::new(),
+        cancel_requested: HashMap::new(),
+        orphan_flags: HashSet::new(),
+        incidents: VecDeque::new(),
+    })
+});
+
+fn key(kind: &str, project_id: &str) -> String {
+    format!("{}:{}", kind, project_id)
+}
+
+/// Record progress of a job
+pub fn beat(kind: &str, project_id: &str) {
+    if let Ok(mut state) = STATE.lock() {
+        state.beats.insert(key(kind, project_id), chrono::Utc::now());
+    }
+}
+
+/// Scrape registered with the watchdog until dropped
+pub struct TrackedScrape {
+    project_id: String,
+}
+
+impl Drop for TrackedScrape {
+    fn drop(&mut self) {
+        if let Ok(mut state) = STATE.lock() {
+            state.scrapes.remove(&self.project_id);
+            state.cancel_requested.remove(&key("scrape", &self.project_id));
+        }
+    }
+}
+
+pub fn track_scrape(project_id: &str) -> TrackedScrape {
+    if let Ok(mut state) = STATE.lock() {
+        state.scrapes.insert(project_id.to_string(), chrono::Utc::now());
+    }
+    TrackedScrape { project_id: project_id.to_string() }
+}
+
+/// Minutes without progress after which a job counts as stuck
+pub fn set_stuck_minutes(minutes: u32) -> u32 {
+    let minutes = minutes.max(MIN_STUCK_MINUTES);
+    if let Ok(mut state) = STATE.lock() {
+        state.stuck_minutes = minutes;
+    }
+    minutes
+}
+
+pub fn report() -> WatchdogReport {
+    let (stuck_minutes, active_scrapes, incidents) = STATE
+        .lock()
+        .map(|state| (state.stuck_minutes, state.scrapes.len(), state.incidents.iter().cloned().collect()))
+        .unwrap_or((DEFAULT_STUCK_MINUTES, 0, Vec::new()));
+    WatchdogReport {
+        stuck_minutes,
+        scheduler: scheduler::health(),
+        active_syncs: sync_lock::active_syncs().len(),
+        active_scrapes,
+        incidents,
+    }
+}
+
+fn parse_utc(value: &str) -> Option<Time> {
+    chrono::DateTime::parse_from_rfc3339(value).ok().map(|d| d.with_timezone(&chrono::Utc))
+}
+
+/// Minutes since the later of the job start and its last progress
+fn idle_minutes(started: Time, last_beat: Option<Time>, now: Time) -> i64 {
+    let last = last_beat.map(|beat| beat.max(started)).unwrap_or(started);
+    (now - last).num_minutes()
+}
+
+/// What to do with a job silent for `idle` minutes
+#[derive(Debug, PartialEq)]
+enum Verdict {
+    Healthy,
+    /// Ask it to cancel
+    Cancel,
+    /// Still silent a period after the cancel request: give up on it
+    Abandon,
+    /// Cancel requested, waiting
+    Waiting,
+}
+
+fn verdict(idle: i64, stuck_minutes: u32, cancel_requested: Option<Time>, now: Time) -> Verdict {
+    if idle < stuck_minutes as i64 {
+        return Verdict::Healthy;
+    }
+    match cancel_requested {
+        None => Verdict::Cancel,
+        Some(at) if (now - at).num_minutes() >= stuck_minutes as i64 => Verdict::Abandon,
+        Some(_) => Verdict::Waiting,
+    }
+}
+
+fn report_incident(app_handle: &tauri::AppHandle, kind: &str, project_id: Option<&str>, message: String, action: &str, notify: bool) {
+    let incident = Incident {
+        id: uuid::Uuid::new_v4().to_string(),
+        kind: kind.to_string(),
+        project_id: project_id.map(str::to_string),
+        message,
+        action: action.to_string(),
+        detected_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+    };
+    println!(
+        "[Watchdog] {} ({}): {} -> {}",
+        incident.kind,
+        project_id.unwrap_or("app"),
+        incident.message,
+        incident.action
+    );
+    if let Ok(mut state) = STATE.lock() {
+        state.incidents.push_front(incident.clone());
+        state.incidents.truncate(MAX_INCIDENTS);
+    }
+    let _ = app_handle.emit_all(crate::events::WATCHDOG_INCIDENT, &incident);
+    if notify {
+        notifications::show(app_handle, project_id, "Surveillance de La Forge", &format!("{} {}", incident.message, incident.action));
+    }
+}
+
+fn check_syncs(app_handle: &tauri::AppHandle, now: Time) {
+    let (stuck_minutes, beats, requested) = match STATE.lock() {
+        Ok(mut state) => {
+            // Forget cancel requests of syncs that ended
+            let active: HashSet<String> = sync_lock::active_syncs().iter().map(|s| key("sync", &s.project_id)).collect();
+            state.cancel_requested.retain(|k, _| !k.starts_with("sync:") || active.contains(k));
+            (state.stuck_minutes, state.beats.clone(), state.cancel_requested.clone())
+        }
+        Err(_) => return,
+    };
+
+    for sync in sync_lock::active_syncs() {
+        if UNWATCHED_TRIGGERS.contains(&sync.trigger.as_str()) {
+            continue;
+        }
+        let started = match parse_utc(&sync.started_at) {
+            Some(started) => started,
+            None => continue,
+        };
+        let job = key("sync", &sync.project_id);
+        let idle = idle_minutes(started, beats.get(&job).copied(), now);
+        match verdict(idle, stuck_minutes, requested.get(&job).copied(), now) {
+            Verdict::Healthy | Verdict::Waiting => {}
+            Verdict::Cancel => {
+                crate::set_cancelled(&sync.project_id, true);
+                if let Ok(mut state) = STATE.lock() {
+                    state.cancel_requested.insert(job, now);
+                }
+                report_incident(
+                    app_handle,
+                    "stuck_sync",
+                    Some(&sync.project_id),
+                    format!("Synchronisation ({}) sans progression depuis {} min.", sync.trigger, idle),
+                    "Annulation demandee.",
+                    true,
+                );
+            }
+            Verdict::Abandon => {
+                sync_lock::force_release(&sync.project_id);
+                if let Ok(mut state) = STATE.lock() {
+                    state.cancel_requested.remove(&job);
+                }
+                let message = "Synchronisation bloquee, abandonnee par la surveillance".to_string();
+                let _ = app_handle.emit_all(
+                    crate::events::SYNC_PROGRESS,
+                    crate::SyncProgressEvent {
+                        project_id: sync.project_id.clone(),
+                        schema_version: crate::events::SCHEMA_VERSION,
+                        event: crate::events::SyncEventKind::Error,
+                        file: None,
+                        progress: 0,
+                        file_progress: None,
+                        bytes_sent: None,
+                        bytes_total: None,
+                        message: Some(message.clone()),
+                        timestamp: now.timestamp_millis() as u64,
+                    },
+                );
+                activity_feed::record(
+                    app_handle,
+                    activity_feed::new_event(
+                        &sync.project_id,
+                        "sync",
+                        "Synchronisation abandonnee",
+                        Some(message),
+                        Some("error"),
+                        serde_json::json!({ "trigger": sync.trigger, "startedAt": sync.started_at }),
+                    ),
+                );
+                report_incident(
+                    app_handle,
+                    "stuck_sync",
+                    Some(&sync.project_id),
+                    format!("Synchronisation ({}) toujours bloquee apres l'annulation.", sync.trigger),
+                    "Marquee en echec, verrou libere.",
+                    true,
+                );
+            }
+        }
+    }
+}
+
+fn check_scrapes(app_handle: &tauri::AppHandle, now: Time) {
+    let (stuck_minutes, beats, requested, scrapes) = match STATE.lock() {
+        Ok(state) => (
+            state.stuck_minutes,
+            state.beats.clone(),
+            state.cancel_requested.clone(),
+            state.scrapes.clone(),
+        ),
+        Err(_) => return,
+    };
+
+    for (project_id, started) in scrapes {
+        let job = key("scrape", &project_id);
+        let idle = idle_minutes(started, beats.get(&job).copied(), now);
+        match verdict(idle, stuck_minutes, requested.get(&job).copied(), now) {
+            Verdict::Healthy | Verdict::Waiting => {}
+            Verdict::Cancel => {
+                crate::set_scrape_cancelled(&project_id, true);
+                if let Ok(mut state) = STATE.lock() {
+                    state.cancel_requested.insert(job, now);
+                }
+                report_incident(
+                    app_handle,
+                    "stuck_scrape",
+                    Some(&project_id),
+                    format!("Capture sans progression depuis {} min.", idle),
+                    "Annulation demandee.",
+                    true,
+                );
+            }
+            Verdict::Abandon => {
+                if let Ok(mut state) = STATE.lock() {
+                    state.scrapes.remove(&project_id);
+                    state.cancel_requested.remove(&job);
+                }
+                report_incident(
+                    app_handle,
+                    "stuck_scrape",
+                    Some(&project_id),
+                    "Capture toujours bloquee apres l'annulation.".to_string(),
+                    "Marquee en echec, une nouvelle capture peut etre lancee.",
+                    true,
+                );
+            }
+        }
+    }
+}
+
+fn check_scheduler(app_handle: &tauri::AppHandle) {
+    let health = scheduler::health();
+    let stalled = health.running && health.seconds_since_tick.map(|s| s > SCHEDULER_STALL_SECS).unwrap_or(false);
+    if !health.poisoned && !stalled {
+        return;
+    }
+    let message = if health.poisoned {
+        "Le planificateur s'est arrete sur une erreur.".to_string()
+    } else {
+        format!(
+            "Le planificateur ne tourne plus depuis {} min.",
+            health.seconds_since_tick.unwrap_or(0) / 60
+        )
+    };
+    scheduler::restart_scheduler(app_handle.clone());
+    report_incident(app_handle, "scheduler_stalled", None, message, "Planificateur redemarre.", true);
+}
+
+/// Cancel flags with no job to stop, seen at two checks in a row
+fn check_cancel_flags(app_handle: &tauri::AppHandle) {
+    let scrapes: HashSet<String> = match STATE.lock() {
+        Ok(state) => state.scrapes.keys().cloned().collect(),
+        Err(_) => return,
+    };
+    let mut orphans: HashSet<String> = HashSet::new();
+    for project_id in crate::cancelled_syncs() {
+        if !sync_lock::is_locked(&project_id) {
+            orphans.insert(key("sync", &project_id));
+        }
+    }
+    for project_id in crate::cancelled_scrapes() {
+        if !scrapes.contains(&project_id) {
+            orphans.insert(key("scrape", &project_id));
+        }
+    }
+
+    let leaked: Vec<String> = match STATE.lock() {
+        Ok(mut state) => {
+            let leaked = orphans.intersection(&state.orphan_flags).cloned().collect();
+            state.orphan_flags = orphans;
+            leaked
+        }
+        Err(_) => return,
+    };
+    for flag in leaked {
+        let (kind, project_id) = flag.split_once(':').unwrap_or(("sync", flag.as_str()));
+        if kind == "sync" {
+            crate::set_cancelled(project_id, false);
+        } else {
+            crate::set_scrape_cancelled(project_id, false);
+        }
+        report_incident(
+            app_handle,
+            "leaked_cancel_flag",
+            Some(project_id),
+            format!("Annulation ({}) restee active sans tache en cours.", kind),
+            "Indicateur efface.",
+            false,
+        );
+    }
+}
+
+/// Start the background checks
+pub fn start(app_handle: tauri::AppHandle) {
+    match STATE.lock() {
+        Ok(mut state) if !state.running => state.running = true,
+        _ => return,
+    }
+    thread::spawn(move || {
+        println!("[Watchdog] Started");
+        loop {
+            thread::sleep(CHECK_INTERVAL);
+            let now = chrono::Utc::now();
+            check_syncs(&app_handle, now);
+            check_scrapes(&app_handle, now);
+            check_scheduler(&app_handle);
+            check_cancel_flags(&app_handle);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stuck_job_is_cancelled_then_abandoned() {
+        let at = |minute: u32| parse_utc(&format!("2026-03-02T10:{:02}:00Z", minute)).unwrap();
+        let started = at(0);
+        // Progress at :05 keeps a job started at :00 healthy until :20
+        assert_eq!(idle_minutes(started, Some(at(5)), at(19)), 14);
+        assert_eq!(verdict(14, 15, None, at(19)), Verdict::Healthy);
+        assert_eq!(idle_minutes(started, None, at(16)), 16);
+        assert_eq!(verdict(16, 15, None, at(16)), Verdict::Cancel);
+        assert_eq!(verdict(20, 15, Some(at(16)), at(20)), Verdict::Waiting);
+        assert_eq!(verdict(31, 15, Some(at(16)), at(31)), Verdict::Abandon);
+        // A beat from an earlier job doesn't count as progress of this one
+        assert_eq!(idle_minutes(at(10), Some(at(2)), at(12)), 2);
+    }
+}
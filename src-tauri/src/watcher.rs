@@ -1,8 +1,10 @@
+use crate::events::{self, WatcherEventKind};
+use crate::ignore_rules::IgnoreRules;
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
 use serde::Serialize;
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
@@ -10,13 +12,28 @@ use tauri::{AppHandle, Manager};
 
 #[derive(Debug, Clone, Serialize)]
 pub struct FileWatcherEvent {
-    pub event_type: String,
+    /// events::SCHEMA_VERSION
+    pub schema_version: u32,
+    pub event_type: WatcherEventKind,
     pub path: String,
     pub file_name: String,
     pub extension: Option<String>,
     pub project_id: String,
 }
 
+/// File dropped into a capture asset folder, renamed and flagged for the next refresh
+#[derive(Debug, Clone, Serialize)]
+pub struct ScrapeAssetFiledEvent {
+    pub project_id: String,
+    pub capture_path: String,
+    pub original_name: String,
+    /// Relative to the capture folder
+    pub local_path: String,
+}
+
+/// Asset folders of a capture and the asset type of their files
+const SCRAPE_ASSET_FOLDERS: [(&str, &str); 4] = [("images", "image"), ("fonts", "font"), ("css", "css"), ("js", "js")];
+
 struct WatcherState {
     watcher: Option<RecommendedWatcher>,
     stop_sender: Option<Sender<()>>,
@@ -58,6 +75,9 @@ impl FileWatcherManager {
         let (stop_tx, stop_rx): (Sender<()>, Receiver<()>) = channel();
         let project_id_clone = project_id.clone();
         let inbox_path_clone = inbox_path.clone();
+        // The inbox lives in the project, whose .laforgeignore also applies to it
+        let project_root = inbox.parent().map(Path::to_path_buf).unwrap_or_else(|| inbox.to_path_buf());
+        let ignore = IgnoreRules::load(&project_root);
 
         // Create the watcher
         let (tx, rx) = channel();
@@ -107,6 +127,14 @@ impl FileWatcherManager {
                             continue;
                         }
 
+                        let ignored = path
+                            .strip_prefix(&project_root)
+                            .map(|relative| ignore.is_ignored(&relative.to_string_lossy().replace('\\', "/"), false))
+                            .unwrap_or(false);
+                        if ignored {
+                            continue;
+                        }
+
                         // Only handle Create events
                         if !matches!(
                             event.kind,
@@ -120,15 +148,31 @@ impl FileWatcherManager {
                             .map(|e| e.to_string_lossy().to_lowercase());
 
                         let watcher_event = FileWatcherEvent {
-                            event_type: "file_added".to_string(),
+                            schema_version: events::SCHEMA_VERSION,
+                            event_type: WatcherEventKind::FileAdded,
                             path: path.to_string_lossy().to_string(),
                             file_name,
                             extension,
                             project_id: project_id_clone.clone(),
                         };
 
+                        // Only new files are arrivals, modifications are reported to the UI only
+                        if matches!(event.kind, notify::EventKind::Create(_)) {
+                            crate::activity_feed::record(
+                                &app_handle_clone,
+                                crate::activity_feed::new_event(
+                                    &watcher_event.project_id,
+                                    "inbox",
+                                    "Nouveau fichier",
+                                    Some(watcher_event.file_name.clone()),
+                                    None,
+                                    serde_json::json!({ "path": watcher_event.path }),
+                                ),
+                            );
+                        }
+
                         // Emit event to frontend
-                        let _ = app_handle_clone.emit_all("file-watcher-event", watcher_event);
+                        let _ = app_handle_clone.emit_all(events::FILE_WATCHER_EVENT, watcher_event);
                     }
                 }
             }
@@ -145,6 +189,83 @@ impl FileWatcherManager {
         Ok(())
     }
 
+    /// Watch the asset folders of a capture: files added later get a
+    /// normalized name and are recorded as manual assets of the capture
+    pub fn watch_scrape_assets(
+        &self,
+        project_id: String,
+        capture_path: String,
+        app_handle: AppHandle,
+    ) -> Result<(), String> {
+        let key = scrape_watch_key(&capture_path);
+        let mut watchers = self.watchers.lock().map_err(|e| e.to_string())?;
+        if let Some(state) = watchers.get_mut(&key) {
+            if let Some(sender) = state.stop_sender.take() {
+                let _ = sender.send(());
+            }
+            state.watcher = None;
+        }
+
+        let capture_dir = PathBuf::from(&capture_path);
+        let (tx, rx) = channel();
+        let mut watcher = RecommendedWatcher::new(
+            move |res: Result<Event, notify::Error>| {
+                if let Ok(event) = res {
+                    let _ = tx.send(event);
+                }
+            },
+            Config::default(),
+        )
+        .map_err(|e| format!("Failed to create watcher: {}", e))?;
+
+        let mut watched = 0;
+        for (folder, _) in SCRAPE_ASSET_FOLDERS {
+            let dir = capture_dir.join(folder);
+            if dir.is_dir() {
+                watcher
+                    .watch(&dir, RecursiveMode::NonRecursive)
+                    .map_err(|e| format!("Failed to watch path: {}", e))?;
+                watched += 1;
+            }
+        }
+        if watched == 0 {
+            return Err(format!("Aucun dossier d'assets dans {}", capture_path));
+        }
+
+        let (stop_tx, stop_rx): (Sender<()>, Receiver<()>) = channel();
+        thread::spawn(move || loop {
+            if stop_rx.try_recv().is_ok() {
+                break;
+            }
+            let event = match rx.recv_timeout(std::time::Duration::from_millis(100)) {
+                Ok(event) => event,
+                Err(_) => continue,
+            };
+            if !matches!(event.kind, notify::EventKind::Create(_)) {
+                continue;
+            }
+            for path in event.paths {
+                if let Some(filed) = file_scrape_asset(&capture_dir, &path, &project_id) {
+                    let _ = app_handle.emit_all(events::SCRAPE_ASSET_FILED, filed);
+                }
+            }
+        });
+
+        println!("[Watcher] Filing new assets of {}", capture_path);
+        watchers.insert(
+            key,
+            WatcherState {
+                watcher: Some(watcher),
+                stop_sender: Some(stop_tx),
+            },
+        );
+        Ok(())
+    }
+
+    pub fn unwatch_scrape_assets(&self, capture_path: &str) -> Result<(), String> {
+        self.stop_watching(&scrape_watch_key(capture_path))
+    }
+
     pub fn stop_watching(&self, project_id: &str) -> Result<(), String> {
         let mut watchers = self.watchers.lock().map_err(|e| e.to_string())?;
 
@@ -173,6 +294,65 @@ impl FileWatcherManager {
     }
 }
 
+/// Capture watchers share the map with the project inbox watchers
+fn scrape_watch_key(capture_path: &str) -> String {
+    format!("scrape:{}", capture_path)
+}
+
+/// Rename a file added to a capture asset folder and record it in the asset manifest
+fn file_scrape_asset(capture_dir: &Path, path: &Path, project_id: &str) -> Option<ScrapeAssetFiledEvent> {
+    if !path.is_file() {
+        return None;
+    }
+    let file_name = path.file_name()?.to_string_lossy().to_string();
+    if file_name.starts_with('.')
+        || file_name.ends_with(".tmp")
+        || file_name.ends_with(".crdownload")
+        || file_name.ends_with(".part")
+    {
+        return None;
+    }
+    let folder = path.parent()?.file_name()?.to_string_lossy().to_string();
+    let asset_type = SCRAPE_ASSET_FOLDERS.iter().find(|(name, _)| *name == folder)?.1;
+
+    let normalized = crate::scrape_refresh::normalized_file_name(&file_name);
+    let mut target = path.with_file_name(&normalized);
+    if normalized != file_name {
+        // Never overwrite a captured asset
+        let mut index = 2;
+        while target.exists() {
+            let (stem, extension) = normalized.rsplit_once('.').unwrap_or((&normalized, ""));
+            let candidate = if extension.is_empty() {
+                format!("{}-{}", stem, index)
+            } else {
+                format!("{}-{}.{}", stem, index, extension)
+            };
+            target = path.with_file_name(candidate);
+            index += 1;
+        }
+        if let Err(e) = fs::rename(path, &target) {
+            println!("[Watcher] Failed to rename {}: {}", path.display(), e);
+            return None;
+        }
+    }
+
+    let local_path = format!("{}/{}", folder, target.file_name()?.to_string_lossy());
+    match crate::scrape_refresh::flag_manual_asset(capture_dir, &local_path, asset_type, &file_name) {
+        Ok(true) => Some(ScrapeAssetFiledEvent {
+            project_id: project_id.to_string(),
+            capture_path: capture_dir.to_string_lossy().to_string(),
+            original_name: file_name,
+            local_path,
+        }),
+        // Already recorded (e.g. the event of our own rename)
+        Ok(false) => None,
+        Err(e) => {
+            println!("[Watcher] Failed to record {}: {}", local_path, e);
+            None
+        }
+    }
+}
+
 impl Default for FileWatcherManager {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,333 @@
+//! WebDAV Module
+//!
+//! Deployment to hosts that only expose WebDAV: PROPFIND listings, MKCOL
+//! folder creation and PUT uploads over HTTP(S) with basic auth. The
+//! server URL comes from `host`: a full `https://host/dav` URL is used as
+//! is, a bare host name gets `http` on port 80 and `https` otherwise.
+
+use crate::{proxy, SFTPConfig};
+use reqwest::blocking::{Body, Client};
+use reqwest::{Method, StatusCode};
+use std::collections::HashSet;
+use std::io::Read;
+use std::time::Duration;
+use url::Url;
+
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:"><d:prop><d:resourcetype/><d:getcontentlength/><d:getlastmodified/></d:prop></d:propfind>"#;
+
+/// Entry of a folder listing
+#[derive(Debug, Clone)]
+pub struct DavEntry {
+    /// Absolute remote path, decoded
+    pub path: String,
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+    /// `getlastmodified`, in unix seconds
+    pub modified: Option<i64>,
+}
+
+pub struct WebDavClient {
+    client: Client,
+    base: Url,
+    username: String,
+    password: String,
+}
+
+/// Reports the bytes read by the HTTP client while it sends a body
+struct ProgressReader<R, F> {
+    inner: R,
+    sent: u64,
+    on_progress: F,
+}
+
+impl<R: Read, F: FnMut(u64)> Read for ProgressReader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if read > 0 {
+            self.sent += read as u64;
+            (self.on_progress)(self.sent);
+        }
+        Ok(read)
+    }
+}
+
+fn server_url(config: &SFTPConfig) -> Result<Url, String> {
+    let host = config.host.trim().trim_end_matches('/');
+    let raw = if host.starts_with("http://") || host.starts_with("https://") {
+        host.to_string()
+    } else if config.port == 80 {
+        format!("http://{}", host)
+    } else {
+        format!("https://{}:{}", host, config.port)
+    };
+    Url::parse(&raw).map_err(|e| format!("URL WebDAV invalide {}: {}", raw, e))
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = value.get(i + 1..i + 3).and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// Text of the first `<prefix:name>` element of `xml`, whatever the namespace prefix
+fn element<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = xml;
+    while let Some(open) = rest.find('<') {
+        rest = &rest[open + 1..];
+        let tag_end = rest.find('>')?;
+        let tag = &rest[..tag_end];
+        let tag_name = tag.split_whitespace().next().unwrap_or("").trim_end_matches('/');
+        let local = tag_name.rsplit(':').next().unwrap_or(tag_name);
+        if local != name || tag.starts_with('/') {
+            continue;
+        }
+        if tag.ends_with('/') {
+            return Some("");
+        }
+        let body = &rest[tag_end + 1..];
+        let close = body.find(&format!("</{}>", tag_name))?;
+        return Some(&body[..close]);
+    }
+    None
+}
+
+/// `<response>` blocks of a multistatus document
+fn responses(xml: &str) -> Vec<&str> {
+    let mut blocks = Vec::new();
+    let mut rest = xml;
+    while let Some(block) = element(rest, "response") {
+        blocks.push(block);
+        let offset = block.as_ptr() as usize - rest.as_ptr() as usize + block.len();
+        rest = &rest[offset..];
+    }
+    blocks
+}
+
+impl WebDavClient {
+    pub fn connect(config: &SFTPConfig) -> Result<Self, String> {
+        let builder = Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .timeout(Duration::from_secs(300))
+            .danger_accept_invalid_certs(config.accept_invalid_certs.unwrap_or(false));
+        let client = proxy::apply(builder, config.proxy.as_ref())?
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+        Ok(Self {
+            client,
+            base: server_url(config)?,
+            username: config.username.clone(),
+            password: config.password.clone(),
+        })
+    }
+
+    /// URL of a remote path, relative to the server URL
+    fn url(&self, path: &str, is_dir: bool) -> Result<Url, String> {
+        let mut url = self.base.clone();
+        {
+            let mut segments = url.path_segments_mut().map_err(|_| "URL WebDAV invalide".to_string())?;
+            segments.pop_if_empty();
+            segments.extend(path.split('/').filter(|s| !s.is_empty()));
+            if is_dir {
+                segments.push("");
+            }
+        }
+        Ok(url)
+    }
+
+    fn request(&self, method: Method, url: Url) -> reqwest::blocking::RequestBuilder {
+        let request = self.client.request(method, url);
+        if self.username.is_empty() {
+            request
+        } else {
+            request.basic_auth(&self.username, Some(&self.password))
+        }
+    }
+
+    /// Remote path of an href returned by the server
+    fn href_path(&self, href: &str) -> String {
+        let path = match self.base.join(href.trim()) {
+            Ok(url) => url.path().to_string(),
+            Err(_) => href.trim().to_string(),
+        };
+        let decoded = percent_decode(&path);
+        let base = percent_decode(self.base.path().trim_end_matches('/'));
+        decoded.strip_prefix(&base).unwrap_or(&decoded).trim_end_matches('/').to_string()
+    }
+
+    /// Files and folders directly under `path`
+    pub fn list(&self, path: &str) -> Result<Vec<DavEntry>, String> {
+        let propfind = Method::from_bytes(b"PROPFIND").map_err(|e| e.to_string())?;
+        let response = self
+            .request(propfind, self.url(path, true)?)
+            .header("Depth", "1")
+            .header("Content-Type", "application/xml")
+            .body(PROPFIND_BODY)
+            .send()
+            .map_err(|e| format!("Failed to list {}: {}", path, e))?;
+        let status = response.status();
+        if status == StatusCode::UNAUTHORIZED {
+            return Err("Authentication failed: identifiants WebDAV refuses".to_string());
+        }
+        if status.as_u16() != 207 {
+            return Err(format!("Failed to list {}: HTTP {}", path, status));
+        }
+        let xml = response.text().map_err(|e| format!("Failed to read listing of {}: {}", path, e))?;
+
+        let own = path.trim_end_matches('/');
+        let mut entries = Vec::new();
+        for block in responses(&xml) {
+            let entry_path = match element(block, "href") {
+                Some(href) => self.href_path(href),
+                None => continue,
+            };
+            // The folder itself is part of its Depth: 1 listing
+            if entry_path == own || entry_path.is_empty() {
+                continue;
+            }
+            let is_dir = element(block, "resourcetype")
+                .map(|types| element(types, "collection").is_some())
+                .unwrap_or(false);
+            let size = element(block, "getcontentlength")
+                .and_then(|length| length.trim().parse().ok())
+                .unwrap_or(0);
+            let modified = element(block, "getlastmodified")
+                .and_then(|date| chrono::DateTime::parse_from_rfc2822(date.trim()).ok())
+                .map(|date| date.timestamp());
+            let name = entry_path.rsplit('/').next().unwrap_or("").to_string();
+            entries.push(DavEntry {
+                path: entry_path,
+                name,
+                is_dir,
+                size,
+                modified,
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Create `dir` and its missing parents; `created` remembers the folders already done
+    pub fn mkdir_all(&self, dir: &str, created: &mut HashSet<String>) -> Result<(), String> {
+        let mkcol = Method::from_bytes(b"MKCOL").map_err(|e| e.to_string())?;
+        let mut current = String::new();
+        for segment in dir.split('/').filter(|s| !s.is_empty()) {
+            current = format!("{}/{}", current, segment);
+            if created.contains(&current) {
+                continue;
+            }
+            let status = self
+                .request(mkcol.clone(), self.url(&current, true)?)
+                .send()
+                .map_err(|e| format!("Failed to create {}: {}", current, e))?
+                .status();
+            // 405: the folder already exists
+            if !status.is_success() && status != StatusCode::METHOD_NOT_ALLOWED {
+                return Err(format!("Failed to create {}: HTTP {}", current, status));
+            }
+            created.insert(current.clone());
+        }
+        Ok(())
+    }
+
+    /// Upload `contents` to `path`, calling `on_progress` with the bytes sent so far
+    pub fn upload<F>(&self, path: &str, contents: Vec<u8>, on_progress: F) -> Result<(), String>
+    where
+        F: FnMut(u64) + Send + 'static,
+    {
+        let size = contents.len() as u64;
+        let reader = ProgressReader {
+            inner: std::io::Cursor::new(contents),
+            sent: 0,
+            on_progress,
+        };
+        let status = self
+            .request(Method::PUT, self.url(path, false)?)
+            .body(Body::sized(reader, size))
+            .send()
+            .map_err(|e| format!("Failed to upload {}: {}", path, e))?
+            .status();
+        if !status.is_success() {
+            return Err(format!("Failed to upload {}: HTTP {}", path, status));
+        }
+        Ok(())
+    }
+
+    /// Stream the file at `path` into `writer`
+    pub fn download(&self, path: &str, writer: &mut impl std::io::Write) -> Result<u64, String> {
+        let mut response = self
+            .request(Method::GET, self.url(path, false)?)
+            .send()
+            .map_err(|e| format!("Failed to download {}: {}", path, e))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!("Failed to download {}: HTTP {}", path, status));
+        }
+        response.copy_to(writer).map_err(|e| format!("Failed to download {}: {}", path, e))
+    }
+
+    /// Delete a file or a folder with its content
+    pub fn delete(&self, path: &str, is_dir: bool) -> Result<(), String> {
+        let status = self
+            .request(Method::DELETE, self.url(path, is_dir)?)
+            .send()
+            .map_err(|e| format!("Failed to delete {}: {}", path, e))?
+            .status();
+        // 404: already gone
+        if !status.is_success() && status != StatusCode::NOT_FOUND {
+            return Err(format!("Failed to delete {}: HTTP {}", path, status));
+        }
+        Ok(())
+    }
+}
+
+pub fn test_connection(config: &SFTPConfig) -> Result<bool, String> {
+    let client = WebDavClient::connect(config)?;
+    let remote_path = if config.remote_path.is_empty() { "/" } else { &config.remote_path };
+    client.list(remote_path)?;
+    Ok(true)
+}
+
+pub fn list_files(config: &SFTPConfig, path: &str) -> Result<Vec<String>, String> {
+    let client = WebDavClient::connect(config)?;
+    Ok(client
+        .list(path)?
+        .into_iter()
+        .map(|entry| if entry.is_dir { format!("{}/", entry.name) } else { entry.name })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_multistatus_with_any_prefix() {
+        let xml = r#"<?xml version="1.0"?>
+<D:multistatus xmlns:D="DAV:">
+  <D:response><D:href>/dav/site/</D:href><D:propstat><D:prop><D:resourcetype><D:collection/></D:resourcetype></D:prop></D:propstat></D:response>
+  <D:response><D:href>/dav/site/mon%20fichier.html</D:href><D:propstat><D:prop><D:resourcetype/><D:getcontentlength>42</D:getcontentlength></D:prop></D:propstat></D:response>
+</D:multistatus>"#;
+        let blocks = responses(xml);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(element(blocks[1], "href"), Some("/dav/site/mon%20fichier.html"));
+        assert_eq!(element(blocks[1], "getcontentlength"), Some("42"));
+        assert!(element(element(blocks[0], "resourcetype").unwrap(), "collection").is_some());
+        assert!(element(element(blocks[1], "resourcetype").unwrap(), "collection").is_none());
+        assert_eq!(percent_decode("mon%20fichier%C3%A9.html"), "mon fichieré.html");
+    }
+}
@@ -0,0 +1,383 @@
+//! Webhook Receiver Module
+//!
+//! Inbound deploy requests from CI. A small HTTP listener on 127.0.0.1
+//! accepts `POST /deploy` with a JSON body `{"projectId": "...", "timestamp":
+//! <unix seconds>}` signed in the `X-Laforge-Signature: sha256=<hex>` header
+//! (HMAC-SHA256 of the raw body with the shared secret). Valid requests are
+//! handed to the frontend on "webhook-deploy", which runs the sync like a
+//! scheduled one, with the usual progress events. A signature is accepted
+//! once: the same signed body sent again while still fresh is a replay and
+//! is refused. A CI runner elsewhere reaches the listener through a tunnel
+//! or relay forwarding to localhost.
+
+use crate::state_file;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::Manager;
+
+pub const DEFAULT_PORT: u16 = 47615;
+
+/// Requests signed longer ago than this are refused (replays)
+const MAX_AGE_SECONDS: i64 = 300;
+
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookSettings {
+    pub enabled: bool,
+    pub port: u16,
+    /// Shared secret of the HMAC signature
+    pub secret: String,
+    /// Projects that may be deployed this way, all when empty
+    #[serde(default)]
+    pub allowed_projects: Vec<String>,
+}
+
+impl Default for WebhookSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+            secret: String::new(),
+            allowed_projects: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookStatus {
+    pub settings: WebhookSettings,
+    pub listening: bool,
+    pub url: Option<String>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeployRequest {
+    project_id: String,
+    timestamp: i64,
+    /// Free text from the CI (commit, pipeline...), shown in the activity feed
+    reference: Option<String>,
+}
+
+/// Event emitted on "webhook-deploy"
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookDeployEvent {
+    pub request_id: String,
+    pub project_id: String,
+    pub reference: Option<String>,
+    pub received_at: String,
+}
+
+#[derive(Default)]
+struct ReceiverState {
+    settings: WebhookSettings,
+    /// Bumped to stop the running listener
+    generation: u64,
+    listening: bool,
+    last_error: Option<String>,
+}
+
+static RECEIVER: Lazy<Mutex<ReceiverState>> = Lazy::new(|| Mutex::new(ReceiverState::default()));
+
+/// Signatures of the accepted requests, with their timestamp, until they expire
+static SEEN: Lazy<Mutex<HashMap<String, i64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn settings_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("webhook_receiver.json")
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut block = [0u8; 64];
+    if key.len() > 64 {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let inner_pad: Vec<u8> = block.iter().map(|b| b ^ 0x36).collect();
+    let outer_pad: Vec<u8> = block.iter().map(|b| b ^ 0x5c).collect();
+    let inner = Sha256::new().chain_update(&inner_pad).chain_update(message).finalize();
+    Sha256::new().chain_update(&outer_pad).chain_update(inner).finalize().into()
+}
+
+/// Constant-time check of a `sha256=<hex>` signature
+fn signature_valid(secret: &str, body: &[u8], header: &str) -> bool {
+    let expected = format!("sha256={}", to_hex(&hmac_sha256(secret.as_bytes(), body)));
+    let given = header.trim();
+    expected.len() == given.len() && expected.bytes().zip(given.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Record a signature of a request signed at `timestamp`; false when it was already accepted
+fn remember(seen: &mut HashMap<String, i64>, signature: &str, timestamp: i64, now: i64) -> bool {
+    seen.retain(|_, signed_at| (now - *signed_at).abs() <= MAX_AGE_SECONDS);
+    let key = signature.trim().to_ascii_lowercase();
+    if seen.contains_key(&key) {
+        return false;
+    }
+    seen.insert(key, timestamp);
+    true
+}
+
+pub fn status() -> WebhookStatus {
+    let state = match RECEIVER.lock() {
+        Ok(state) => state,
+        Err(_) => {
+            return WebhookStatus {
+                settings: WebhookSettings::default(),
+                listening: false,
+                url: None,
+                last_error: None,
+            }
+        }
+    };
+    WebhookStatus {
+        settings: state.settings.clone(),
+        listening: state.listening,
+        url: if state.listening { Some(format!("http://127.0.0.1:{}/deploy", state.settings.port)) } else { None },
+        last_error: state.last_error.clone(),
+    }
+}
+
+/// Load the saved settings and start listening if enabled
+pub fn restore(app_handle: tauri::AppHandle, app_data_dir: &Path) {
+    let settings: WebhookSettings = state_file::read_json(&settings_path(app_data_dir))
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    apply(app_handle, settings);
+}
+
+/// Save new settings and restart the listener accordingly
+pub fn configure(app_handle: tauri::AppHandle, app_data_dir: &Path, settings: WebhookSettings) -> Result<WebhookStatus, String> {
+    if settings.enabled && settings.secret.trim().len() < 16 {
+        return Err("Le secret partage doit faire au moins 16 caracteres".to_string());
+    }
+    state_file::write_json(&settings_path(app_data_dir), &settings)?;
+    apply(app_handle, settings);
+    // Leave the listener a moment to bind before reporting
+    thread::sleep(Duration::from_millis(200));
+    Ok(status())
+}
+
+fn apply(app_handle: tauri::AppHandle, settings: WebhookSettings) {
+    let generation = match RECEIVER.lock() {
+        Ok(mut state) => {
+            state.generation += 1;
+            state.settings = settings.clone();
+            state.last_error = None;
+            state.generation
+        }
+        Err(_) => return,
+    };
+    if settings.enabled {
+        thread::spawn(move || listen(app_handle, settings.port, generation));
+    }
+}
+
+fn is_current(generation: u64) -> bool {
+    RECEIVER.lock().map(|state| state.generation == generation).unwrap_or(false)
+}
+
+fn set_listening(generation: u64, listening: bool, error: Option<String>) {
+    if let Ok(mut state) = RECEIVER.lock() {
+        if state.generation == generation {
+            state.listening = listening;
+            state.last_error = error;
+        }
+    }
+}
+
+fn listen(app_handle: tauri::AppHandle, port: u16, generation: u64) {
+    // A previous listener on the same port needs a moment to notice it was replaced
+    let listener = (0..10).find_map(|_| match TcpListener::bind(("127.0.0.1", port)) {
+        Ok(listener) => Some(listener),
+        Err(_) => {
+            thread::sleep(Duration::from_millis(300));
+            None
+        }
+    });
+    let listener = match listener {
+        Some(listener) => listener,
+        None => {
+            println!("[Webhook] Failed to listen on port {}", port);
+            set_listening(generation, false, Some(format!("Port {} indisponible", port)));
+            return;
+        }
+    };
+    if listener.set_nonblocking(true).is_err() {
+        set_listening(generation, false, Some("Failed to configure listener".to_string()));
+        return;
+    }
+    println!("[Webhook] Listening on 127.0.0.1:{}", port);
+    set_listening(generation, true, None);
+
+    while is_current(generation) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let _ = stream.set_nonblocking(false);
+                let _ = stream.set_read_timeout(Some(Duration::from_secs(10)));
+                handle_connection(&app_handle, stream);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => thread::sleep(Duration::from_millis(200)),
+            Err(e) => {
+                println!("[Webhook] Accept failed: {}", e);
+                thread::sleep(Duration::from_millis(200));
+            }
+        }
+    }
+    println!("[Webhook] Listener on port {} stopped", port);
+}
+
+fn respond(stream: &mut TcpStream, status: &str, body: serde_json::Value) {
+    let body = body.to_string();
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+}
+
+fn handle_connection(app_handle: &tauri::AppHandle, mut stream: TcpStream) {
+    let mut reader = match stream.try_clone() {
+        Ok(clone) => BufReader::new(clone),
+        Err(_) => return,
+    };
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    let mut content_length = 0usize;
+    let mut signature = None;
+    let mut header = String::new();
+    while reader.read_line(&mut header).map(|n| n > 2).unwrap_or(false) {
+        if let Some((name, value)) = header.split_once(':') {
+            match name.trim().to_ascii_lowercase().as_str() {
+                "content-length" => content_length = value.trim().parse().unwrap_or(0),
+                "x-laforge-signature" => signature = Some(value.trim().to_string()),
+                _ => {}
+            }
+        }
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some("POST"), Some("/deploy")) => {}
+        (Some(_), Some("/deploy")) => return respond(&mut stream, "405 Method Not Allowed", serde_json::json!({ "error": "POST attendu" })),
+        _ => return respond(&mut stream, "404 Not Found", serde_json::json!({ "error": "Route inconnue" })),
+    }
+    if content_length == 0 || content_length > MAX_BODY_BYTES {
+        return respond(&mut stream, "400 Bad Request", serde_json::json!({ "error": "Corps de requete invalide" }));
+    }
+    let mut body = vec![0u8; content_length];
+    if reader.read_exact(&mut body).is_err() {
+        return respond(&mut stream, "400 Bad Request", serde_json::json!({ "error": "Corps de requete incomplet" }));
+    }
+
+    let (status, payload) = process(app_handle, &body, signature.as_deref());
+    respond(&mut stream, status, payload);
+}
+
+/// Validate a deploy request and hand it to the frontend
+fn process(app_handle: &tauri::AppHandle, body: &[u8], signature: Option<&str>) -> (&'static str, serde_json::Value) {
+    let settings = match RECEIVER.lock() {
+        Ok(state) => state.settings.clone(),
+        Err(_) => return ("500 Internal Server Error", serde_json::json!({ "error": "Etat indisponible" })),
+    };
+    let signed = signature.map(|s| signature_valid(&settings.secret, body, s)).unwrap_or(false);
+    if settings.secret.is_empty() || !signed {
+        println!("[Webhook] Rejected request with an invalid signature");
+        return ("401 Unauthorized", serde_json::json!({ "error": "Signature invalide" }));
+    }
+    let request: DeployRequest = match serde_json::from_slice(body) {
+        Ok(request) => request,
+        Err(e) => return ("400 Bad Request", serde_json::json!({ "error": format!("JSON invalide: {}", e) })),
+    };
+    let now = chrono::Utc::now().timestamp();
+    if (now - request.timestamp).abs() > MAX_AGE_SECONDS {
+        return ("401 Unauthorized", serde_json::json!({ "error": "Requete expiree" }));
+    }
+    if !settings.allowed_projects.is_empty() && !settings.allowed_projects.contains(&request.project_id) {
+        return ("403 Forbidden", serde_json::json!({ "error": "Projet non autorise" }));
+    }
+    if crate::readonly_mode::is_enabled() {
+        return ("423 Locked", serde_json::json!({ "error": "Mode lecture seule actif" }));
+    }
+    if !crate::automation_pause::allow_automation(app_handle, &request.project_id, "webhook") {
+        return ("423 Locked", serde_json::json!({ "error": "Automatisations en pause pour ce projet" }));
+    }
+    if !crate::deploy_guard::allow_triggered_sync(app_handle, &request.project_id, "webhook") {
+        return ("423 Locked", serde_json::json!({ "error": "Deploiements geles pour ce projet" }));
+    }
+    // Only accepted requests count, a refused one may be sent again once unlocked
+    let first_time = match SEEN.lock() {
+        Ok(mut seen) => remember(&mut seen, signature.unwrap_or_default(), request.timestamp, now),
+        Err(_) => return ("500 Internal Server Error", serde_json::json!({ "error": "Etat indisponible" })),
+    };
+    if !first_time {
+        println!("[Webhook] Rejected a replayed request for {}", request.project_id);
+        return ("409 Conflict", serde_json::json!({ "error": "Requete deja traitee" }));
+    }
+
+    let event = WebhookDeployEvent {
+        request_id: uuid::Uuid::new_v4().to_string(),
+        project_id: request.project_id,
+        reference: request.reference,
+        received_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+    };
+    println!("[Webhook] Deploy of {} requested", event.project_id);
+    let _ = app_handle.emit_all(crate::events::WEBHOOK_DEPLOY, &event);
+    crate::activity_feed::record(
+        app_handle,
+        crate::activity_feed::new_event(
+            &event.project_id,
+            "schedule",
+            "Deploiement demande par webhook",
+            event.reference.clone(),
+            None,
+            serde_json::json!({ "requestId": event.request_id, "trigger": "webhook" }),
+        ),
+    );
+    ("202 Accepted", serde_json::json!({ "accepted": true, "requestId": event.request_id }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_sha256_rfc4231() {
+        // RFC 4231, test case 2
+        let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(to_hex(&mac), "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+        let header = format!("sha256={}", to_hex(&mac));
+        assert!(signature_valid("Jefe", b"what do ya want for nothing?", &header));
+        assert!(!signature_valid("Jefe", b"what do ya want for nothing!", &header));
+    }
+
+    #[test]
+    fn test_replayed_signature_refused_until_expired() {
+        let mut seen = HashMap::new();
+        assert!(remember(&mut seen, "sha256=abcd", 1000, 1000));
+        assert!(!remember(&mut seen, " SHA256=ABCD ", 1000, 1100));
+        assert!(remember(&mut seen, "sha256=ef01", 1100, 1100));
+        // Expired entries are dropped; the request itself is refused as too old by then
+        assert!(remember(&mut seen, "sha256=abcd", 1000, 1000 + MAX_AGE_SECONDS + 1));
+        assert_eq!(seen.len(), 2);
+    }
+}
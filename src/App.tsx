@@ -9,18 +9,17 @@ import { Notifications } from './components/Notifications';
 import { AboutModal } from './components/AboutModal';
 import { ProjectFormData } from './components/ProjectForm';
 import { useProjectStore, useSettingsStore, useUIStore, useScheduleStore, useTimeStore } from './stores';
-import { useMenuEvents, useFileWatcher, useSystemTray } from './hooks';
+import { useMenuEvents, useFileWatcher, useSystemTray, useAutomationEvents } from './hooks';
 import { syncService } from './services/syncService';
+import { sftpService } from './services/sftpService';
 import { projectService } from './services/projectService';
 import { scrapingService } from './services/scrapingService';
 import { geminiService } from './services/geminiService';
 import { configStore } from './services/configStore';
 import { migrationService } from './services/migrationService';
-import { Project } from './types';
-// TODO: Réactiver quand les mises à jour seront configurées
-// import { checkUpdate, installUpdate } from '@tauri-apps/api/updater';
-// import { relaunch } from '@tauri-apps/api/process';
-// import { ask } from '@tauri-apps/api/dialog';
+import { Project, WebhookDeployEvent, OfflineQueueEvent } from './types';
+import { updateService, UpdateCheckResult } from './services/updateService';
+import { ask } from '@tauri-apps/api/dialog';
 import './styles/globals.css';
 
 function LoadingScreen() {
@@ -241,15 +240,92 @@ export function App() {
     });
   }, [onTimerStop, stopSession]);
 
+  // Run the deploys requested by CI through the webhook receiver, and the
+  // queued syncs whose server is back (after asking unless auto_run)
+  useAutomationEvents({
+    onWebhookDeploy: async (event: WebhookDeployEvent) => {
+      const project = projects.find((p) => p.id === event.project_id);
+      if (!project || !project.sftp?.configured) {
+        addNotification('error', `Webhook: projet ${event.project_id} introuvable ou sans configuration FTP`);
+        return;
+      }
+      const label = event.reference ? `${project.name} (${event.reference})` : project.name;
+      addNotification('info', `Deploiement demande par webhook: ${label}`);
+      const result = await syncService.syncWithEvents(project, { trigger: 'webhook' });
+      if (result.success) {
+        await fetchProjects();
+        addNotification('success', `Deploiement webhook termine: ${result.filesUploaded} fichiers envoyes`);
+      } else {
+        addNotification('error', `Echec du deploiement webhook: ${result.errors?.join(', ')}`);
+      }
+    },
+    onOfflineSyncReady: async ({ item }: OfflineQueueEvent) => {
+      try {
+        const project = projects.find((p) => p.id === item.project_id);
+        if (!project || !project.sftp?.configured) {
+          await sftpService.removeQueuedSync(item.id);
+          addNotification('error', `Synchronisation en attente retiree: projet ${item.project_name || item.project_id} introuvable`);
+          return;
+        }
+        if (!item.auto_run) {
+          const run = await ask(
+            `Le serveur ${item.host} est de nouveau joignable.\n\nLancer la synchronisation de ${project.name} ?`,
+            { title: 'Synchronisation en attente', type: 'info' }
+          );
+          if (!run) {
+            await sftpService.removeQueuedSync(item.id);
+            return;
+          }
+        }
+        const result = await syncService.syncWithEvents(project, { trigger: 'offline-queue' });
+        await sftpService.completeQueuedSync(item.id, result.success);
+        if (result.success) {
+          await fetchProjects();
+          addNotification('success', `Synchronisation en attente terminee: ${result.filesUploaded} fichiers envoyes`);
+        } else {
+          addNotification('error', `Echec de la synchronisation en attente, nouvel essai au retour du serveur: ${result.errors?.join(', ')}`);
+        }
+      } catch (error) {
+        addNotification('error', error instanceof Error ? error.message : 'Erreur de synchronisation');
+      }
+    },
+  });
+
   // Handle native macOS menu events
   useMenuEvents({
     onAbout: useCallback(() => {
       openModal('about');
     }, [openModal]),
-    onCheckUpdates: useCallback(async () => {
-      // TODO: Configurer les mises à jour automatiques avec signature Tauri
-      // Voir docs/TODO.md pour les étapes de configuration
-      addNotification('info', 'Mises à jour automatiques non configurées. Vérifiez GitHub pour les nouvelles versions.');
+    onCheckUpdates: useCallback(async (result: UpdateCheckResult) => {
+      if ('Err' in result) {
+        addNotification('error', result.Err);
+        return;
+      }
+      const info = result.Ok;
+      if (!info.available) {
+        addNotification('info', `La Forge ${info.current_version} est à jour`);
+        return;
+      }
+      const install = await ask(
+        `${info.notes || ''}\n\nInstaller la version ${info.latest_version} ?`,
+        { title: 'Mise à jour disponible', type: 'info' }
+      );
+      if (!install) return;
+      try {
+        const version = await updateService.install();
+        // Restart is deferred so a running sync is not interrupted
+        const restartNow = await ask(
+          `La version ${version} est installée. Redémarrer maintenant ?`,
+          { title: 'Mise à jour installée', type: 'info' }
+        );
+        if (restartNow) {
+          await updateService.restart();
+        } else {
+          addNotification('info', `La version ${version} sera utilisée au prochain démarrage`);
+        }
+      } catch (error) {
+        addNotification('error', `Mise à jour impossible: ${error}`);
+      }
     }, [addNotification]),
     onPreferences: useCallback(() => {
       setCurrentView('settings');
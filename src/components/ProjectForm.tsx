@@ -18,7 +18,7 @@ export interface ProjectFormData {
     port?: number;
     remotePath?: string;
     passive?: boolean;
-    protocol?: 'sftp' | 'ftp' | 'ftps';
+    protocol?: 'sftp' | 'ftp' | 'ftps' | 'webdav';
     acceptInvalidCerts?: boolean;
   };
   savePassword: boolean;
@@ -10,6 +10,7 @@ const PROTOCOL_CONFIG: Record<
   sftp: { label: 'SFTP', defaultPort: 22, description: 'SSH (sécurisé)' },
   ftp: { label: 'FTP', defaultPort: 21, description: 'Standard' },
   ftps: { label: 'FTPS', defaultPort: 21, description: 'SSL/TLS' },
+  webdav: { label: 'WebDAV', defaultPort: 443, description: 'HTTP(S)' },
 };
 
 export interface FTPFormData {
@@ -145,7 +146,7 @@ export function FTPConnectionCard({
         </div>
       )}
 
-      {(sftp.protocol === 'ftps' || sftp.protocol === 'sftp') && (
+      {(sftp.protocol === 'ftps' || sftp.protocol === 'sftp' || sftp.protocol === 'webdav') && (
         <div style={{ marginTop: 12 }}>
           <Switch
             label="Certificats non vérifiés"
@@ -38,6 +38,10 @@ export type { TrayIconState, UseSystemTrayResult } from './useSystemTray';
 export { useMenuEvents } from './useMenuEvents';
 export type { MenuEventHandlers } from './useMenuEvents';
 
+// Syncs requested by the backend (webhook, offline queue)
+export { useAutomationEvents } from './useAutomationEvents';
+export type { AutomationEventHandlers } from './useAutomationEvents';
+
 // File watcher
 export { useFileWatcher } from './useFileWatcher';
 export type { UseFileWatcherOptions, UseFileWatcherResult } from './useFileWatcher';
@@ -0,0 +1,55 @@
+/**
+ * Hook for handling syncs requested by the backend (webhook receiver,
+ * offline queue), which the frontend runs since it holds the credentials
+ */
+
+import { useEffect, useRef } from 'react';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+import { WebhookDeployEvent, OfflineQueueEvent } from '../types';
+
+export interface AutomationEventHandlers {
+  onWebhookDeploy?: (event: WebhookDeployEvent) => void;
+  /** A queued sync whose server is reachable again */
+  onOfflineSyncReady?: (event: OfflineQueueEvent) => void;
+}
+
+/**
+ * Hook to listen to backend sync requests
+ * Listeners are registered once; the latest handlers are always called,
+ * so a request is never handled twice while handlers change.
+ * @param handlers - Object containing callback functions for each request
+ */
+export function useAutomationEvents(handlers: AutomationEventHandlers): void {
+  const handlersRef = useRef(handlers);
+  handlersRef.current = handlers;
+
+  useEffect(() => {
+    const unlisteners: UnlistenFn[] = [];
+    let cancelled = false;
+
+    const setupListeners = async () => {
+      const registered = await Promise.all([
+        listen<WebhookDeployEvent>('webhook-deploy', (event) => {
+          handlersRef.current.onWebhookDeploy?.(event.payload);
+        }),
+        listen<OfflineQueueEvent>('sync-queue-status', (event) => {
+          if (event.payload.event_type === 'ready') {
+            handlersRef.current.onOfflineSyncReady?.(event.payload);
+          }
+        }),
+      ]);
+      if (cancelled) {
+        registered.forEach((unlisten) => unlisten());
+      } else {
+        unlisteners.push(...registered);
+      }
+    };
+
+    setupListeners();
+
+    return () => {
+      cancelled = true;
+      unlisteners.forEach((unlisten) => unlisten());
+    };
+  }, []);
+}
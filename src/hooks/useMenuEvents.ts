@@ -5,10 +5,11 @@
 
 import { useEffect } from 'react';
 import { listen, UnlistenFn } from '@tauri-apps/api/event';
+import { UpdateCheckResult } from '../services/updateService';
 
 export interface MenuEventHandlers {
   onAbout?: () => void;
-  onCheckUpdates?: () => void;
+  onCheckUpdates?: (result: UpdateCheckResult) => void;
   onPreferences?: () => void;
   onNewProject?: () => void;
   onRefresh?: () => void;
@@ -16,6 +17,13 @@ export interface MenuEventHandlers {
   onOpenBrowser?: () => void;
   onSync?: () => void;
   onScrape?: () => void;
+  onQuickAction?: (action: MenuQuickAction) => void;
+}
+
+/** Project quick action configured in the menu (Projet > Actions rapides) */
+export interface MenuQuickAction {
+  project_id: string;
+  action: 'sync' | 'open_in_browser' | 'open_in_finder' | 'scrape';
 }
 
 /**
@@ -33,7 +41,8 @@ export function useMenuEvents(handlers: MenuEventHandlers): void {
       }
 
       if (handlers.onCheckUpdates) {
-        const unlisten = await listen('menu-check-updates', handlers.onCheckUpdates);
+        const onCheckUpdates = handlers.onCheckUpdates;
+        const unlisten = await listen<UpdateCheckResult>('menu-check-updates', (event) => onCheckUpdates(event.payload));
         unlisteners.push(unlisten);
       }
 
@@ -71,6 +80,12 @@ export function useMenuEvents(handlers: MenuEventHandlers): void {
         const unlisten = await listen('menu-scrape', handlers.onScrape);
         unlisteners.push(unlisten);
       }
+
+      if (handlers.onQuickAction) {
+        const onQuickAction = handlers.onQuickAction;
+        const unlisten = await listen<MenuQuickAction>('menu-quick-action', (event) => onQuickAction(event.payload));
+        unlisteners.push(unlisten);
+      }
     };
 
     setupListeners();
@@ -88,5 +103,6 @@ export function useMenuEvents(handlers: MenuEventHandlers): void {
     handlers.onOpenBrowser,
     handlers.onSync,
     handlers.onScrape,
+    handlers.onQuickAction,
   ]);
 }
@@ -161,6 +161,8 @@ export function useSystemTray(): UseSystemTrayResult {
               hasFtp: p.sftp?.configured === true,
               hasActiveTimer: timerState?.isActive ?? false,
               isTimerPaused: timerState?.isPaused ?? false,
+              color: p.identityColor,
+              emoji: p.identityEmoji,
             };
           });
 
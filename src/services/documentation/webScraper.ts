@@ -46,6 +46,8 @@ export interface ExtractedText {
   pageUrl: string;
   elementType: string;
   content: string;
+  language: string | null;
+  wordCount: number;
 }
 
 /**
@@ -3,7 +3,8 @@ import { listen, UnlistenFn } from '@tauri-apps/api/event';
 import { geminiService } from './geminiService';
 
 export interface FileWatcherEvent {
-  event_type: string;
+  schema_version: number;
+  event_type: 'file_added';
   path: string;
   file_name: string;
   extension: string | null;
@@ -4,6 +4,7 @@
  */
 
 import { invoke } from '@tauri-apps/api/tauri';
+import { ProxyConfig } from '../types';
 
 export interface FullScrapeConfig {
   url: string;
@@ -15,11 +16,14 @@ export interface FullScrapeConfig {
   downloadFonts?: boolean;
   rewriteUrls?: boolean;
   generateReport?: boolean;
+  generateGallery?: boolean;
+  proxy?: ProxyConfig;
 }
 
 export interface FullScrapeProgress {
+  schema_version: number;
   project_id: string;
-  event_type: string; // "connecting", "page_start", "page_complete", "asset_download", "analyzing", "rewriting", "complete", "error"
+  event_type: string; // "connecting", "page_start", "page_complete", "rewriting", "analyzing", "complete", "error"
   current_step: string;
   progress_percent: number;
   pages_downloaded: number;
@@ -37,6 +41,15 @@ export interface ColorInfo {
   occurrences: number;
 }
 
+export interface ContrastPair {
+  foreground: string;
+  background: string;
+  ratio: number;
+  aa: boolean;
+  aa_large: boolean;
+  aaa: boolean;
+}
+
 export interface FontInfo {
   family: string;
   weights: string[];
@@ -46,6 +59,7 @@ export interface FontInfo {
 
 export interface DesignSystem {
   colors: ColorInfo[];
+  contrast: ContrastPair[];
   fonts: FontInfo[];
   typography: {
     base_font_size: string | null;
@@ -56,6 +70,26 @@ export interface DesignSystem {
   breakpoints: string[];
 }
 
+export interface PageInventory {
+  url: string;
+  title: string;
+  meta_description: string | null;
+  h1: string | null;
+  status: number;
+  language: string | null;
+  word_count: number;
+  text_blocks: number;
+  image_count: number;
+}
+
+export interface ContentInventory {
+  pages: PageInventory[];
+  total_pages: number;
+  total_words: number;
+  total_images: number;
+  languages: { language: string; pages: number; words: number }[];
+}
+
 export interface FullScrapeResult {
   success: boolean;
   pages_downloaded: number;
@@ -64,11 +98,21 @@ export interface FullScrapeResult {
   output_path: string;
   index_path: string;
   design_system: DesignSystem;
+  content_inventory: ContentInventory;
+  gallery_path: string | null;
+  image_metadata_path: string | null;
   report_path: string | null;
   errors: string[];
   warnings: string[];
 }
 
+export interface AttributionSummary {
+  path: string;       // ATTRIBUTION.md dans le dossier de capture
+  assets: number;
+  flagged: number;    // Fichiers avec X-Robots-Tag restrictif ou regle robots.txt
+  origins: string[];  // Hotes d'origine des fichiers
+}
+
 /**
  * Scrape an entire website with full local working copy
  */
@@ -94,6 +138,24 @@ export async function cancelFullSiteScrape(projectId: string): Promise<void> {
   return invoke('cancel_full_site_scrape', { projectId });
 }
 
+/**
+ * Write ATTRIBUTION.md (sources, capture dates, license and robots hints) in a capture folder
+ */
+export async function generateScrapeAttribution(outputPath: string): Promise<AttributionSummary> {
+  return invoke('generate_scrape_attribution', { outputPath });
+}
+
+/**
+ * Export the content inventory as CSV or XLSX (format taken from the extension when omitted)
+ */
+export async function exportContentInventory(
+  inventory: ContentInventory,
+  outputPath: string,
+  format?: 'csv' | 'xlsx'
+): Promise<number> {
+  return invoke('export_content_inventory', { inventory, outputPath, format });
+}
+
 /**
  * Format bytes to human readable
  */
@@ -2,7 +2,7 @@ import { invoke } from '@tauri-apps/api/tauri';
 import { readDir, readTextFile, writeTextFile, createDir, removeDir, exists } from '@tauri-apps/api/fs';
 import { join, basename } from '@tauri-apps/api/path';
 import { open } from '@tauri-apps/api/shell';
-import { Project, DEFAULT_FOLDER_STRUCTURE, migrateProjectStatus, ImportAnalysis, ProjectHealth } from '../types';
+import { Project, DEFAULT_FOLDER_STRUCTURE, migrateProjectStatus, ImportAnalysis, ProjectHealth, ProjectVariable, FolderAnalysis, ImportedTarget } from '../types';
 import { ProjectFormData } from '../components/ProjectForm';
 import { configStore } from './configStore';
 
@@ -615,4 +615,70 @@ body { font-family: system-ui, sans-serif; line-height: 1.6; }`
 
     return project;
   },
+
+  async listProjectEnv(projectId: string): Promise<ProjectVariable[]> {
+    return await invoke('list_project_env', { projectId });
+  },
+
+  /**
+   * Add or replace a variable; a secret value is only kept in the keychain
+   */
+  async setProjectEnv(projectId: string, name: string, value: string, secret = false): Promise<ProjectVariable[]> {
+    return await invoke('set_project_env', { projectId, name, value, secret });
+  },
+
+  async removeProjectEnv(projectId: string, name: string): Promise<ProjectVariable[]> {
+    return await invoke('remove_project_env', { projectId, name });
+  },
+
+  /**
+   * Replace ${NAME} in a config value or report template with the project's variables
+   */
+  async substituteProjectVars(projectId: string, text: string): Promise<string> {
+    return await invoke('substitute_project_vars', { projectId, text });
+  },
+
+  /**
+   * Detect the CMS or framework, git repo and editor deploy configs of a dropped folder
+   * and suggest a project configuration
+   */
+  async analyzeFolder(path: string): Promise<FolderAnalysis> {
+    return await invoke('analyze_folder', { path });
+  },
+
+  async importDeployConfigs(path: string): Promise<ImportedTarget[]> {
+    return await invoke('import_deploy_configs', { path });
+  },
+
+  /**
+   * Apply an imported target to a project: connection, paths and exclusions.
+   * The password is left out, save it with sftpService.saveCredentials.
+   */
+  applyImportedTarget(project: Project, target: ImportedTarget): Project {
+    const excludePatterns = [...(project.syncRules?.excludePatterns || [])];
+    for (const pattern of target.ignore) {
+      if (!excludePatterns.includes(pattern)) excludePatterns.push(pattern);
+    }
+    return {
+      ...project,
+      sftp: {
+        ...project.sftp,
+        configured: !!target.host,
+        host: target.host ?? project.sftp.host,
+        port: target.port ?? project.sftp.port,
+        username: target.username ?? project.sftp.username,
+        remotePath: target.remotePath ?? project.sftp.remotePath,
+        protocol: target.protocol ?? project.sftp.protocol,
+        passive: target.passive ?? project.sftp.passive,
+        useSshAgent: target.useSshAgent || project.sftp.useSshAgent,
+        privateKeyPath: target.privateKeyPath ?? project.sftp.privateKeyPath,
+      },
+      localPath: target.localPath,
+      urls: target.siteUrl && !project.urls.testUrl ? { ...project.urls, testUrl: target.siteUrl } : project.urls,
+      syncRules: excludePatterns.length > 0
+        ? { enabled: true, ...project.syncRules, excludePatterns }
+        : project.syncRules,
+      updated: new Date().toISOString(),
+    };
+  },
 };
@@ -1,5 +1,28 @@
 import { invoke } from '@tauri-apps/api/tauri';
-import { SFTPConfig, FileDiff, Project, SyncOptions } from '../types';
+import {
+  SFTPConfig,
+  FileDiff,
+  FileTypeStats,
+  Project,
+  SyncOptions,
+  RemoteRules,
+  RuleIssue,
+  RuleRecipe,
+  RecipeOptions,
+  RulesVersion,
+  RulesDeployResult,
+  ConnectionProfile,
+  ConnectionProfileInput,
+  MaintenanceOptions,
+  MaintenanceState,
+  MaintenanceCleanup,
+  SyncQueueStatus,
+  OfflineQueuedSync,
+  SyncRun,
+  SyncTarget,
+  SyncHistoryEntry,
+  DiffReport,
+} from '../types';
 import { configStore } from './configStore';
 
 // Timeout configuration
@@ -113,14 +136,201 @@ export const sftpService = {
   /**
    * Get diff between local and remote with timeout protection
    */
-  async getDiff(localPath: string, config: SFTPConfig): Promise<FileDiff[]> {
+  async getDiff(
+    localPath: string,
+    config: SFTPConfig,
+    mtimeToleranceSecs?: number,
+    projectId?: string,
+    useCachedRemote?: boolean
+  ): Promise<FileDiff[]> {
     return await withTimeout(
-      invoke('sftp_get_diff', { localPath, config }),
+      invoke('sftp_get_diff', { localPath, config, projectId, mtimeToleranceSecs, useCachedRemote }),
       TIMEOUTS.diff,
       'Analyse des différences'
     );
   },
 
+  /**
+   * Answer the prompts of a `ssh-auth-prompt` event, null cancels the connection
+   */
+  async answerSshAuth(requestId: string, answers: string[] | null): Promise<void> {
+    return await invoke('ssh_auth_answer', { requestId, answers });
+  },
+
+  /**
+   * Composition by extension and category of the files a diff would upload
+   */
+  async getDiffFileTypes(diffs: FileDiff[]): Promise<FileTypeStats> {
+    return await invoke('get_diff_file_types', { diffs });
+  },
+
+  /**
+   * Write the result of getDiff as a report to share with the client, in Documentation/deploiement
+   */
+  async exportDiffReport(
+    diffs: FileDiff[],
+    projectPath: string,
+    format: 'markdown' | 'csv' | 'json' = 'markdown',
+    projectName?: string,
+    target?: SyncTarget
+  ): Promise<DiffReport> {
+    return await invoke('export_diff_report', { diffs, projectPath, format, projectName, target });
+  },
+
+  /**
+   * Read a .htaccess or nginx redirect file (relative to the remote root)
+   */
+  async readRemoteRules(projectId: string, config: SFTPConfig, file: string): Promise<RemoteRules> {
+    return await withTimeout(
+      invoke('read_remote_rules', { projectId, config, file }),
+      TIMEOUTS.list,
+      'Lecture des regles serveur'
+    );
+  },
+
+  async validateRemoteRules(file: string, content: string): Promise<RuleIssue[]> {
+    return await invoke('validate_remote_rules', { file, content });
+  },
+
+  async listRuleRecipes(): Promise<RuleRecipe[]> {
+    return await invoke('list_rule_recipes');
+  },
+
+  /**
+   * Add or remove a recipe in the edited content, nothing is uploaded
+   */
+  async applyRuleRecipe(
+    file: string,
+    content: string,
+    recipe: RuleRecipe['id'],
+    enabled: boolean,
+    options?: RecipeOptions
+  ): Promise<string> {
+    return await invoke('apply_rule_recipe', { file, content, recipe, options, enabled });
+  },
+
+  /**
+   * Upload a rules file; refused when it has syntax errors
+   */
+  async deployRemoteRules(projectId: string, config: SFTPConfig, file: string, content: string): Promise<RulesDeployResult> {
+    return await withTimeout(
+      invoke('deploy_remote_rules', { projectId, config, file, content }),
+      TIMEOUTS.list,
+      'Deploiement des regles serveur'
+    );
+  },
+
+  async listRemoteRulesVersions(projectId: string, file?: string): Promise<RulesVersion[]> {
+    return await invoke('list_remote_rules_versions', { projectId, file });
+  },
+
+  async readRemoteRulesVersion(projectId: string, versionId: string): Promise<string> {
+    return await invoke('read_remote_rules_version', { projectId, versionId });
+  },
+
+  async listConnectionProfiles(): Promise<ConnectionProfile[]> {
+    return await invoke('list_connection_profiles');
+  },
+
+  /**
+   * Create (no id) or update a shared connection profile
+   */
+  async saveConnectionProfile(profile: ConnectionProfileInput): Promise<ConnectionProfile> {
+    return await invoke('save_connection_profile', { profile });
+  },
+
+  async deleteConnectionProfile(id: string, removePassword = true): Promise<void> {
+    return await invoke('delete_connection_profile', { id, removePassword });
+  },
+
+  /**
+   * Change the password of every project using the profile
+   */
+  async setConnectionProfilePassword(id: string, password: string): Promise<void> {
+    return await invoke('set_connection_profile_password', { id, password });
+  },
+
+  async resolveConnectionProfile(id: string, remotePath: string): Promise<SFTPConfig> {
+    return await invoke('resolve_connection_profile', { id, remotePath });
+  },
+
+  async getMaintenanceMode(projectId: string): Promise<MaintenanceState | null> {
+    return await invoke('get_maintenance_mode', { projectId });
+  },
+
+  /**
+   * Put the remote site behind its maintenance page until disableMaintenanceMode
+   */
+  async enableMaintenanceMode(projectId: string, config: SFTPConfig, options?: MaintenanceOptions): Promise<MaintenanceState> {
+    return await withTimeout(
+      invoke('enable_maintenance_mode', { projectId, config, options }),
+      TIMEOUTS.list,
+      'Activation du mode maintenance'
+    );
+  },
+
+  /**
+   * Lift maintenance; localPath deploys the project's own rules file in its place
+   */
+  async disableMaintenanceMode(projectId: string, config: SFTPConfig, localPath?: string): Promise<MaintenanceCleanup> {
+    return await withTimeout(
+      invoke('disable_maintenance_mode', { projectId, config, localPath }),
+      TIMEOUTS.list,
+      'Desactivation du mode maintenance'
+    );
+  },
+
+  /**
+   * Running syncs and the ones waiting for their turn
+   */
+  async getSyncQueue(): Promise<SyncQueueStatus> {
+    return await invoke('get_sync_queue');
+  },
+
+  /**
+   * Syncs waiting for their server to be reachable again
+   */
+  async getOfflineSyncQueue(): Promise<OfflineQueuedSync[]> {
+    return await invoke('get_offline_sync_queue');
+  },
+
+  async removeQueuedSync(id: string): Promise<void> {
+    return await invoke('remove_queued_sync', { id });
+  },
+
+  /**
+   * Outcome of a ready queued sync: removed on success, waiting again on failure
+   */
+  async completeQueuedSync(id: string, success: boolean): Promise<void> {
+    return await invoke('complete_queued_sync', { id, success });
+  },
+
+  /**
+   * Last sync runs of a project with their target, figures and file errors, newest first
+   */
+  async getSyncHistory(projectId: string, limit?: number): Promise<SyncRun[]> {
+    return await invoke('get_sync_history', { projectId, limit });
+  },
+
+  async clearSyncHistory(projectId: string): Promise<void> {
+    return await invoke('clear_sync_history', { projectId });
+  },
+
+  /**
+   * A recorded run as shown by the project dashboard
+   */
+  toHistoryEntry(run: SyncRun): SyncHistoryEntry {
+    return {
+      id: run.id,
+      timestamp: run.startedAt,
+      filesCount: run.filesUploaded,
+      bytesTransferred: run.bytes,
+      duration: Math.round(run.durationMs / 1000),
+      success: run.status === 'success',
+      error: run.message ?? undefined,
+    };
+  },
+
   /**
    * Basic sync without events
    */
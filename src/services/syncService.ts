@@ -27,6 +27,20 @@ export const syncService = {
       return null;
     }
 
+    // ${NAME} in the remote path comes from the project's variables
+    let remotePath = project.sftp.remotePath || '/public_html';
+    if (remotePath.includes('${')) {
+      remotePath = await projectService.substituteProjectVars(project.id, remotePath);
+    }
+
+    // Shared profile: host, access and password come from the backend
+    if (project.sftp.profileId) {
+      return await sftpService.resolveConnectionProfile(
+        project.sftp.profileId,
+        remotePath
+      );
+    }
+
     const password = await sftpService.getCredentials(project.id);
     if (!password) {
       return null;
@@ -37,7 +51,7 @@ export const syncService = {
       port: project.sftp.port || 21,
       username: project.sftp.username || '',
       password,
-      remotePath: project.sftp.remotePath || '/public_html',
+      remotePath,
       passive: project.sftp.passive,
       protocol: project.sftp.protocol || 'ftp',
       acceptInvalidCerts: project.sftp.acceptInvalidCerts,
@@ -150,6 +164,8 @@ export const syncService = {
     const localPath = this.getLocalSyncPath(project);
     const syncOptions: SyncOptions = {
       ...DEFAULT_SYNC_OPTIONS,
+      ...(project.preSyncCommand ? { pre_sync_command: project.preSyncCommand, pre_sync_cwd: project.path } : {}),
+      ...(project.postSyncCommand ? { post_sync_command: project.postSyncCommand } : {}),
       ...options,
     };
 
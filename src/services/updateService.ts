@@ -0,0 +1,64 @@
+/**
+ * Update Service
+ * Update checks on the stable or beta channel, download and deferred restart
+ */
+
+import { invoke } from '@tauri-apps/api/tauri';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+
+export type UpdateChannel = 'stable' | 'beta';
+
+export interface UpdateInfo {
+  available: boolean;
+  channel: UpdateChannel;
+  current_version: string;
+  latest_version: string | null;
+  notes: string | null;
+  date: string | null;
+}
+
+export interface UpdateStatus {
+  channel: UpdateChannel;
+  downloading: boolean;
+  pending_restart: string | null;
+  last_check: string | null;
+}
+
+export interface UpdateProgress {
+  downloaded: number;
+  total: number | null;
+  percent: number | null;
+}
+
+/** Result of the check run by the "Vérifier les mises à jour" menu item */
+export type UpdateCheckResult = { Ok: UpdateInfo } | { Err: string };
+
+export const updateService = {
+  async getStatus(): Promise<UpdateStatus> {
+    return await invoke<UpdateStatus>('get_update_status');
+  },
+
+  async setChannel(channel: UpdateChannel): Promise<void> {
+    await invoke('set_update_channel', { channel });
+  },
+
+  async check(): Promise<UpdateInfo> {
+    return await invoke<UpdateInfo>('check_for_update');
+  },
+
+  /**
+   * Download and install the update, returns the installed version.
+   * The new version runs after restart().
+   */
+  async install(): Promise<string> {
+    return await invoke<string>('install_update');
+  },
+
+  async restart(): Promise<void> {
+    await invoke('restart_to_update');
+  },
+
+  async onProgress(callback: (progress: UpdateProgress) => void): Promise<UnlistenFn> {
+    return await listen<UpdateProgress>('update-download-progress', (event) => callback(event.payload));
+  },
+};
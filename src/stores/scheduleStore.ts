@@ -6,7 +6,7 @@
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/tauri';
 import { listen, UnlistenFn } from '@tauri-apps/api/event';
-import { SyncSchedule, ScheduleType, ScheduleResult, ScheduleEvent } from '../types';
+import { SyncSchedule, ScheduleType, ScheduleResult, ScheduleEvent, SchedulerQueue } from '../types';
 
 interface ScheduleState {
   schedules: Record<string, SyncSchedule>; // keyed by project id
@@ -26,6 +26,9 @@ interface ScheduleStore extends ScheduleState {
   setEnabled: (projectId: string, enabled: boolean) => Promise<void>;
   updateResult: (projectId: string, result: ScheduleResult) => Promise<void>;
   subscribeToScheduledSyncs: (callback: (event: ScheduleEvent) => void) => Promise<UnlistenFn>;
+  subscribeToScheduledScrapes: (callback: (event: ScheduleEvent) => void) => Promise<UnlistenFn>;
+  setConcurrency: (maxJobs: number) => Promise<void>;
+  getQueue: () => Promise<SchedulerQueue>;
   clearError: () => void;
 }
 
@@ -216,6 +219,20 @@ export const useScheduleStore = create<ScheduleStore>((set, get) => ({
     });
   },
 
+  subscribeToScheduledScrapes: async (callback: (event: ScheduleEvent) => void) => {
+    return await listen<ScheduleEvent>('scheduled-scrape', (event) => {
+      callback(event.payload);
+    });
+  },
+
+  setConcurrency: async (maxJobs: number) => {
+    await invoke('set_scheduler_concurrency', { maxJobs });
+  },
+
+  getQueue: async () => {
+    return await invoke<SchedulerQueue>('get_scheduler_queue');
+  },
+
   clearError: () => set({ error: null }),
 }));
 
@@ -574,6 +574,15 @@ export const useSyncStore = create<SyncStore>((set, get) => ({
       let newError = current.error;
 
       switch (event.event) {
+        case 'queued':
+          newLog = {
+            id: generateLogId(),
+            timestamp: event.timestamp,
+            level: 'info',
+            message: event.message || "En attente d'une autre synchronisation...",
+          };
+          break;
+
         case 'connecting':
           newStage = 'connecting';
           newLog = {
@@ -5,6 +5,7 @@
 
 import { create } from 'zustand';
 import { persist } from 'zustand/middleware';
+import { invoke } from '@tauri-apps/api/tauri';
 import { ProjectBilling, BillingUnit } from '../types';
 
 export interface TimeSession {
@@ -173,6 +174,15 @@ export const useTimeStore = create<TimeState>()(
           sessions: [...state.sessions, session],
         }));
 
+        // Timer sessions show up in the project's activity feed
+        invoke('record_activity', {
+          projectId: session.projectId,
+          kind: 'timer',
+          title: 'Session de travail',
+          detail: session.notes ?? null,
+          data: { durationSeconds: duration, startTime: session.startTime, endTime: session.endTime },
+        }).catch((error) => console.error('Failed to record timer activity:', error));
+
         return session;
       },
 
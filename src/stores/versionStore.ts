@@ -5,7 +5,7 @@
 
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/tauri';
-import { SyncSnapshot, SnapshotSummary, SnapshotDiff } from '../types';
+import { SyncSnapshot, SnapshotSummary, SnapshotDiff, InspectionFolder } from '../types';
 
 interface VersionState {
   snapshots: Record<string, SnapshotSummary[]>; // keyed by project id
@@ -20,6 +20,7 @@ interface VersionStore extends VersionState {
   createSnapshot: (projectId: string, localPath: string, message?: string) => Promise<SyncSnapshot>;
   getSnapshotDetails: (projectId: string, snapshotId: string) => Promise<SyncSnapshot | null>;
   restoreVersion: (projectId: string, snapshotId: string, targetPath: string, files?: string[]) => Promise<string[]>;
+  inspectFiles: (projectId: string, snapshotId: string, files: string[], editor?: string) => Promise<InspectionFolder>;
   compareSnapshots: (projectId: string, oldSnapshotId: string, newSnapshotId: string) => Promise<SnapshotDiff>;
   clearError: () => void;
 }
@@ -106,6 +107,24 @@ export const useVersionStore = create<VersionStore>((set, get) => ({
     }
   },
 
+  inspectFiles: async (projectId: string, snapshotId: string, files: string[], editor?: string) => {
+    set({ loading: true, error: null });
+    try {
+      const inspection = await invoke<InspectionFolder>('inspect_snapshot_files', {
+        projectId,
+        snapshotId,
+        files,
+        editor,
+      });
+      set({ loading: false });
+      return inspection;
+    } catch (error) {
+      const errorMessage = error instanceof Error ? error.message : 'Failed to extract files';
+      set({ loading: false, error: errorMessage });
+      throw new Error(errorMessage);
+    }
+  },
+
   compareSnapshots: async (projectId: string, oldSnapshotId: string, newSnapshotId: string) => {
     set({ loading: true, error: null });
     try {
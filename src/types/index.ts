@@ -20,7 +20,11 @@ export interface Project {
   themeTags?: string[];                   // Tags de thème/orientations design
   themeTagsGeneratedAt?: string;          // Date de génération des tags
   syncRules?: SyncRules;                  // Regles de synchronisation selective
+  preSyncCommand?: string;                // Commande locale avant chaque synchro (ex: "npm run build"), lancee depuis path
+  postSyncCommand?: string;               // Commande SSH apres une synchro reussie (ex: "php artisan cache:clear")
   billing?: ProjectBilling;               // Paramètres de facturation du projet
+  identityColor?: string;                 // Couleur d'identification (tray, notifications)
+  identityEmoji?: string;                 // Emoji d'identification (prioritaire sur la couleur)
 }
 
 // Sync Rules for selective synchronization
@@ -67,7 +71,7 @@ export interface ReferenceWebsite {
   addedAt: string;
 }
 
-export type FTPProtocol = 'sftp' | 'ftp' | 'ftps';
+export type FTPProtocol = 'sftp' | 'ftp' | 'ftps' | 'webdav';
 
 export interface SFTPInfo {
   configured: boolean;
@@ -79,8 +83,12 @@ export interface SFTPInfo {
   passive?: boolean;
   protocol?: FTPProtocol;
   acceptInvalidCerts?: boolean;
+  useSshAgent?: boolean;    // SFTP: ssh-agent, then key file, then password
+  privateKeyPath?: string;
+  keyboardInteractive?: boolean;  // SFTP: code a usage unique (2FA) demande a la connexion
   passwordAvailable?: boolean;
   encryptedPassword?: string;  // AES-256 encrypted password stored inline
+  profileId?: string;          // Profil de connexion partage (hote, acces, mot de passe), remotePath reste au projet
 }
 
 export interface ScrapingStats {
@@ -120,15 +128,136 @@ export interface SFTPConfig {
   passive?: boolean;
   protocol?: FTPProtocol;
   acceptInvalidCerts?: boolean;
+  useSshAgent?: boolean;
+  privateKeyPath?: string;
+  keyPassphrase?: string;
+  keyboardInteractive?: boolean;
+  proxy?: ProxyConfig;     // Proxy SOCKS5 ou HTTP CONNECT vers le serveur
+}
+
+// Payload of the `ssh-auth-prompt` event, answered with sftpService.answerSshAuth
+export interface SshAuthPrompt {
+  requestId: string;
+  host: string;
+  username: string;
+  instructions: string;
+  prompts: { text: string; echo: boolean }[];
+}
+
+// Profil de connexion partage entre projets, mot de passe dans le trousseau
+export type ProfileAuthMethod = 'password' | 'ssh-agent' | 'key' | 'keyboard-interactive';
+
+export interface ConnectionProfile {
+  id: string;
+  name: string;
+  host: string;
+  port: number;
+  protocol: FTPProtocol;
+  username: string;
+  authMethod: ProfileAuthMethod;
+  privateKeyPath?: string;
+  passive?: boolean;
+  acceptInvalidCerts?: boolean;
+  proxy?: ProxyConfig;
+  keyringKey: string;
+  createdAt: string;
+  updatedAt: string;
+}
+
+export type ConnectionProfileInput = Omit<ConnectionProfile, 'id' | 'keyringKey' | 'createdAt' | 'updatedAt'> & {
+  id?: string;          // Absent: nouveau profil
+  keyringKey?: string;  // "profile-<id>" par defaut
+  password?: string;    // Ecrit dans le trousseau si fourni
+};
+
+export interface ProxyConfig {
+  protocol: 'socks5' | 'http';
+  host: string;
+  port: number;
+  username?: string;
+  password?: string;
 }
 
 export interface FileDiff {
   path: string;
-  status: 'added' | 'modified' | 'deleted' | 'unchanged';
+  status: 'added' | 'modified' | 'deleted' | 'unchanged' | 'oversized';
   localSize?: number;
   remoteSize?: number;
 }
 
+// Rapport d'analyse exporte dans Documentation/deploiement du projet
+export interface DiffReport {
+  path: string;
+  format: 'markdown' | 'csv' | 'json';
+  added: number;
+  modified: number;
+  deleted: number;       // Uniquement sur le serveur (supprimes en mode miroir)
+  oversized: number;
+  unchanged: number;
+  uploadBytes: number;
+  deleteBytes: number;
+}
+
+export interface TypeShare {
+  key: string;
+  files: number;
+  bytes: number;
+  share: number;
+}
+
+export interface FileTypeStats {
+  totalFiles: number;
+  totalBytes: number;
+  byExtension: TypeShare[];
+  byCategory: TypeShare[];
+}
+
+// Fichiers .htaccess / redirections nginx geres sur le serveur
+export type RulesFlavor = 'apache' | 'nginx';
+
+export interface RuleIssue {
+  line: number;
+  severity: 'error' | 'warning';
+  message: string;
+}
+
+export interface RemoteRules {
+  file: string;
+  flavor: RulesFlavor;
+  exists: boolean;
+  content: string;
+  issues: RuleIssue[];
+  recipes: string[];
+}
+
+export interface RuleRecipe {
+  id: 'maintenance' | 'force-https' | 'www' | 'no-www';
+  name: string;
+  description: string;
+  conflicts: string[];
+}
+
+export interface RecipeOptions {
+  domain?: string;
+  allowedIps?: string[];
+  maintenancePage?: string;   // "/maintenance.html" par defaut
+}
+
+export interface RulesVersion {
+  id: string;
+  file: string;
+  source: 'remote' | 'deploy';
+  size: number;
+  savedAt: string;
+}
+
+export interface RulesDeployResult {
+  file: string;
+  version: RulesVersion;
+  previous: RulesVersion | null;
+  warnings: RuleIssue[];
+}
+
 export interface FilterPreferences {
   filterBarOpen: boolean;
   statusFilters: ProjectStatus[];
@@ -198,7 +327,8 @@ export interface AutoOrganizeSettings {
 }
 
 export interface FileWatcherEvent {
-  event_type: string;
+  schema_version: number;
+  event_type: 'file_added';
   path: string;
   file_name: string;
   extension: string | null;
@@ -304,18 +434,33 @@ export function migrateProjectStatus(status: string): ProjectStatus {
 // Sync Progress Event Types
 // ============================================
 
+// Version of the typed event payloads, see get_event_schema
+export const EVENT_SCHEMA_VERSION = 1;
+
 export type SyncEventType =
+  | 'queued'
+  | 'building'
   | 'connecting'
   | 'analyzing'
+  | 'snapshot'
+  | 'delete_preview'
   | 'file_start'
   | 'file_progress'
   | 'file_complete'
   | 'file_error'
+  | 'deleting'
+  | 'verifying'
+  | 'verification'
+  | 'manifest'
+  | 'quota_warning'
+  | 'maintenance'
+  | 'post_command'
   | 'complete'
   | 'error'
   | 'cancelled';
 
 export interface SyncProgressEvent {
+  schema_version: number;
   project_id: string;
   event: SyncEventType;
   file: string | null;
@@ -327,6 +472,26 @@ export interface SyncProgressEvent {
   timestamp: number;
 }
 
+// Payload of the `build-output` event, one line of the pre-sync command
+export interface BuildOutputEvent {
+  schema_version: number;
+  project_id: string;
+  stream: 'stdout' | 'stderr';
+  line: string;
+  timestamp: number;
+}
+
+export interface EventDescription {
+  name: string;
+  description: string;
+  kinds: string[];  // Values of the typed kind field, empty for untyped payloads
+}
+
+export interface EventSchema {
+  schemaVersion: number;
+  events: EventDescription[];
+}
+
 export type SyncLogLevel = 'info' | 'success' | 'warning' | 'error';
 
 export interface SyncLogEntry {
@@ -384,11 +549,20 @@ export interface SnapshotDiff {
   unchanged: string[];
 }
 
+// Copie jetable de fichiers d'un snapshot, supprimee apres 24 h
+export interface InspectionFolder {
+  path: string;
+  snapshotId: string;
+  files: string[];
+}
+
 // ============================================
 // Scheduler Types
 // ============================================
 
 export type ScheduleType = 'hourly' | 'daily' | 'weekly' | 'monthly' | 'custom';
+export type ScheduledJob = 'sync' | 'scrape';
+export type JobPriority = 'high' | 'normal' | 'low';
 
 export interface SyncSchedule {
   project_id: string;
@@ -398,6 +572,27 @@ export interface SyncSchedule {
   next_run?: string;
   last_run?: string;
   last_result?: ScheduleResult;
+  job?: ScheduledJob;          // defaut: 'sync'
+  priority?: JobPriority;      // defaut: haute en production, basse pour une capture
+  include_db_dump?: boolean;   // dump de la base en meme temps que la synchronisation
+  db_dump?: ScheduledDbDump;   // requis avec include_db_dump
+}
+
+export interface DbDumpConfig {
+  engine: 'mysql' | 'postgres';
+  host?: string;               // defaut: localhost
+  port?: number;
+  database: string;
+  username: string;
+  passwordKey: string;         // cle du mot de passe dans le trousseau
+  keep?: number;               // dumps conserves, defaut: 7
+}
+
+// Connexion SSH prise dans un profil: le planificateur n'a pas acces aux identifiants des projets
+export interface ScheduledDbDump {
+  profileId: string;
+  projectPath: string;
+  db: DbDumpConfig;
 }
 
 export interface ScheduleResult {
@@ -411,6 +606,22 @@ export interface ScheduleEvent {
   project_id: string;
   schedule_type: string;
   timestamp: number;
+  include_db_dump: boolean;
+  priority: JobPriority;
+}
+
+export interface QueuedJob {
+  project_id: string;
+  job: ScheduledJob;
+  priority: JobPriority;
+  include_db_dump: boolean;
+  due_at: string;
+}
+
+export interface SchedulerQueue {
+  max_concurrent: number;
+  running: QueuedJob[];
+  pending: QueuedJob[];
 }
 
 // ============================================
@@ -420,16 +631,130 @@ export interface ScheduleEvent {
 export interface SyncOptions {
   parallel_enabled: boolean;
   parallel_connections: number;
+  adaptive_connections?: boolean;  // SFTP/FTP: demarre a 2 connexions et ajuste jusqu'a parallel_connections
   create_snapshot: boolean;
+  use_cached_remote?: boolean;     // Simulation: reutilise la derniere liste des fichiers distants (< 24 h)
   snapshot_message?: string;
+  max_file_size?: number;          // Octets, fichiers plus gros ignores
+  large_file_overrides?: string[]; // Chemins envoyes malgre la limite
+  confirmation?: string;           // Phrase exigee pour une cible de production
+  engine?: 'sftp' | 'rsync' | 'tar'; // Moteur SFTP (rsync: differentiel, tar: premier deploiement)
+  trigger?: string;                // Origine de la synchro (manual, webhook, ...), gardee dans l'historique
+  delete_orphans?: boolean;        // Mode miroir: supprime les fichiers distants absents en local
+  sync_preset?: string;            // Profil (wordpress, prestashop, static, none) dont les chemins proteges et la politique bornent le mode miroir; detecte si absent
+  max_deletions?: number;          // Limite de suppressions du mode miroir (defaut 50)
+  trash_retention_days?: number;   // Jours de conservation des fichiers supprimes par le mode miroir (defaut 30)
+  verify_sample_percent?: number;  // % de fichiers envoyes relus et compares apres la synchro
+  verify_size_threshold?: number;  // Taille (octets) au-dela de laquelle un fichier est toujours relu (defaut 20 Mo)
+  atomic_upload?: boolean;         // Ecrit <nom>.laforge-tmp puis renomme une fois complet (rsync: renommage en fin d'envoi, tar: dossier temporaire)
+  preserve_permissions?: boolean;  // SFTP: applique les droits locaux (bit executable compris) apres l'envoi
+  preserve_mtime?: boolean;        // SFTP/FTP: date de modification distante alignee sur la locale (defaut true)
+  mtime_tolerance_secs?: number;   // Decalage d'horloge tolere (s) entre fichiers de meme taille (defaut 120)
+  include?: string[];              // Globs a synchroniser uniquement (ex: 'dist/**')
+  exclude?: string[];              // Globs exclus de cette synchro (ex: '*.psd')
+  maintenance?: MaintenanceOptions; // Page de maintenance pendant l'envoi, retiree ensuite meme en cas d'echec
+  pre_sync_command?: string;        // Commande de build locale, un echec annule la synchro
+  pre_sync_cwd?: string;            // Dossier de la commande (defaut: dossier synchronise)
+  pre_sync_timeout_secs?: number;   // Defaut: 600
+  post_sync_command?: string;       // SFTP: commande distante apres la synchro, un echec n'est qu'un avertissement
+  post_sync_timeout_secs?: number;  // Defaut: 120
+}
+
+export interface MaintenanceOptions {
+  rulesFile?: string;   // ".htaccess" par defaut (".conf" pour nginx)
+  pagePath?: string;    // "maintenance.html" par defaut, relatif a la racine distante
+  pageHtml?: string;    // Sinon la page du site, ou une page par defaut
+  allowedIps?: string[];
+}
+
+export interface MaintenanceState {
+  rulesFile: string;
+  pagePath: string;
+  pageCreated: boolean;
+  rulesCreated: boolean;
+  enabledAt: string;
+}
+
+export interface MaintenanceCleanup {
+  wasEnabled: boolean;
+  projectRulesDeployed: boolean;
+  warnings: string[];
+}
+
+// Deploiement demande par le recepteur de webhooks (evenement webhook-deploy)
+export interface WebhookDeployEvent {
+  request_id: string;
+  project_id: string;
+  reference?: string | null;  // Commit ou tag transmis par la CI
+  received_at: string;
+}
+
+// Synchro mise en attente du retour de son serveur (file hors ligne)
+export interface OfflineQueuedSync {
+  id: string;
+  project_id: string;
+  project_name?: string | null;
+  host: string;
+  port: number;
+  local_path: string;
+  auto_run: boolean;            // Lancee sans confirmation au retour du serveur
+  status: 'waiting' | 'ready';  // ready: serveur joignable, synchro a lancer
+  queued_at: string;
+  last_check?: string | null;
+  checks: number;
+}
+
+// Evenement sync-queue-status de la file hors ligne
+export interface OfflineQueueEvent {
+  event_type: 'queued' | 'checked' | 'ready' | 'requeued' | 'removed';
+  item: OfflineQueuedSync;
+  message: string;
+}
+
+// Synchro detenant le verrou d'un projet
+export interface ActiveSync {
+  project_id: string;
+  trigger: string;
+  started_at: string;
+}
+
+// Synchro en attente de son tour
+export interface QueuedSync {
+  id: number;
+  project_id: string;
+  trigger: string;
+  queued_at: string;
+  position: number;  // 1 pour la prochaine a demarrer
+}
+
+export interface SyncQueueStatus {
+  running: ActiveSync[];
+  waiting: QueuedSync[];
+  max_running: number;
+}
+
+// Variable de projet, exportee vers les commandes et remplacee en ${NOM}
+export interface ProjectVariable {
+  name: string;
+  value?: string;  // Jamais renvoyee pour un secret
+  secret: boolean;
 }
 
 export interface SyncConfig {
   parallel_enabled: boolean;
   parallel_connections: number;
+  adaptive_connections?: boolean;
   auto_snapshot: boolean;
 }
 
+// Optimum appris par le mode adaptatif, par cible (get_learned_concurrency)
+export interface LearnedConcurrency {
+  optimum: number;
+  ceiling?: number | null;  // Nombre de connexions au-dela duquel le serveur refuse
+  throughput: number;       // Octets/s mesures a l'optimum
+  updatedAt: string;
+}
+
 // ============================================
 // Dashboard Stats Types
 // ============================================
@@ -454,6 +779,33 @@ export interface SyncHistoryEntry {
   error?: string;
 }
 
+// Serveur vise par une synchro, sans mot de passe
+export interface SyncTarget {
+  protocol: string;
+  host: string;
+  port: number;
+  username: string;
+  remotePath: string;
+}
+
+// Synchro enregistree dans l'historique persistant (sync_history/<projet>.json)
+export interface SyncRun {
+  id: string;
+  projectId: string;
+  startedAt: string;
+  durationMs: number;
+  trigger: string;                 // manual, watch, schedule, tray...
+  target: SyncTarget;
+  status: 'success' | 'error' | 'cancelled';
+  message: string | null;          // Cause de l'echec
+  filesUploaded: number;
+  filesDeleted: number;
+  bytes: number;
+  snapshotId: string | null;
+  fileErrors: { path: string | null; message: string }[];
+  fileErrorsTruncated: boolean;
+}
+
 export interface AgencyStats {
   totalProjects: number;
   activeProjects: number;
@@ -534,3 +886,68 @@ export interface ImportAnalysis {
   hasExistingConfig: boolean;
   suggestedLocalPath: string; // www, public, dist, etc.
 }
+
+export interface DetectedStack {
+  id: string;        // wordpress, laravel, hugo, vite, static...
+  name: string;
+  kind: 'cms' | 'framework' | 'static';
+  folder: string;    // Dossier relatif ou le stack a ete trouve ('' pour la racine)
+  markers: string[];
+}
+
+export interface GitInfo {
+  branch: string | null;     // null si HEAD detachee
+  remoteUrl: string | null;  // origin, sans identifiants
+}
+
+export interface ExistingDeployConfig {
+  file: string;      // Relatif au dossier analyse
+  source: 'vscode-sftp' | 'atom-remote-ftp' | 'sublime-sftp' | 'remote-sync' | 'phpstorm';
+  name: string | null;
+  host: string | null;
+  port: number | null;
+  protocol: 'sftp' | 'ftp' | 'ftps' | null;
+  username: string | null;
+  remotePath: string | null;
+  ignore: string[];
+}
+
+// Cible de synchro importee de .vscode/sftp.json ou .idea/deployment.xml
+export interface ImportedTarget {
+  source: 'vscode-sftp' | 'phpstorm';
+  file: string;
+  name: string | null;
+  host: string | null;
+  port: number | null;
+  protocol: 'sftp' | 'ftp' | 'ftps' | null;
+  username: string | null;
+  password: string | null;         // sftp.json uniquement, a ranger dans le trousseau
+  privateKeyPath: string | null;
+  useSshAgent: boolean;
+  passive: boolean | null;
+  remotePath: string | null;
+  localPath: string;               // Relatif au dossier importe ('.' pour le dossier lui-meme)
+  ignore: string[];                // Patterns gitignore-style relatifs a localPath
+  siteUrl: string | null;
+  isDefault: boolean;
+  warnings: string[];              // Reglages non importes
+}
+
+export interface SuggestedProject {
+  name: string;
+  localPath: string;               // Dossier synchronise ('.' pour le projet lui-meme)
+  syncPreset: string | null;
+  preSyncCommand: string | null;
+  deploy: ExistingDeployConfig | null;
+}
+
+export interface FolderAnalysis {
+  path: string;
+  stacks: DetectedStack[];
+  git: GitInfo | null;
+  deployConfigs: ExistingDeployConfig[];
+  packageManager: string | null;
+  fileTypes: FileTypeStats;        // Hors dependances et dossiers d'outils
+  truncated: boolean;              // Trop de fichiers: repartition partielle
+  suggested: SuggestedProject;
+}